@@ -43,6 +43,20 @@ pub fn parse_cookie_string(raw: &str) -> HashMap<String, String> {
     map
 }
 
+/// 把单独的凭证字段拼成 Cookie 字符串，空值字段会被忽略
+fn build_cookie_string(bduss: &str, stoken: Option<&str>, ptoken: Option<&str>) -> String {
+    [("BDUSS", Some(bduss)), ("STOKEN", stoken), ("PTOKEN", ptoken)]
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| format!("{}={}", name, v))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// Cookie 登录客户端
 pub struct CookieLoginAuth {
     client: Client,
@@ -132,6 +146,20 @@ impl CookieLoginAuth {
         Ok(user)
     }
 
+    /// 使用单独提交的凭证登录
+    ///
+    /// BDUSS 必填，STOKEN / PTOKEN 可选。凭证会拼成 Cookie 字符串后复用
+    /// [`Self::login_with_cookies`] 的解析和验证流程。
+    pub async fn login_with_tokens(
+        &self,
+        bduss: &str,
+        stoken: Option<&str>,
+        ptoken: Option<&str>,
+    ) -> Result<UserAuth> {
+        let raw_cookies = build_cookie_string(bduss, stoken, ptoken);
+        self.login_with_cookies(&raw_cookies).await
+    }
+
     /// 通过百度网盘 API 获取并验证用户信息
     async fn get_user_info(&self, bduss: &str) -> Result<UserAuth> {
         let url = format!(
@@ -243,4 +271,15 @@ mod tests {
         let map = parse_cookie_string(raw);
         assert!(map.get("BDUSS").is_none());
     }
+
+    #[test]
+    fn test_build_cookie_string() {
+        let raw = build_cookie_string(" abc123 ", Some("sss"), Some(""));
+        assert_eq!(raw, "BDUSS=abc123; STOKEN=sss");
+
+        let map = parse_cookie_string(&raw);
+        assert_eq!(map.get("BDUSS").map(|s| s.as_str()), Some("abc123"));
+        assert_eq!(map.get("STOKEN").map(|s| s.as_str()), Some("sss"));
+        assert!(map.get("PTOKEN").is_none());
+    }
 }
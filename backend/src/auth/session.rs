@@ -2,14 +2,21 @@
 
 use crate::auth::UserAuth;
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{info, warn};
 
 /// 会话管理器
+///
+/// 每个账号的会话按 UID 保存在 `sessions/{uid}.json`，
+/// `session.json` 始终是当前激活账号会话的副本（兼容单账号版本）
 pub struct SessionManager {
-    /// 会话文件路径
+    /// 会话文件路径（当前激活账号）
     session_file: String,
+    /// 多账号会话目录（`{uid}.json`）
+    sessions_dir: PathBuf,
+    /// 当前激活账号 UID（None 表示使用 session.json）
+    active_uid: Option<u64>,
     /// 当前会话（内存缓存）
     current_session: Option<UserAuth>,
 }
@@ -19,41 +26,95 @@ impl SessionManager {
     ///
     /// # Arguments
     /// * `session_file` - 会话文件路径，默认为 "./config/session.json"
+    ///
+    /// 多账号会话目录为会话文件同级的 `sessions/`
     pub fn new(session_file: Option<String>) -> Self {
         let session_file = session_file.unwrap_or_else(|| "./config/session.json".to_string());
+        let sessions_dir = Path::new(&session_file)
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("sessions");
 
         Self {
             session_file,
+            sessions_dir,
+            active_uid: None,
             current_session: None,
         }
     }
 
-    /// 保存会话到文件
+    /// 账号会话文件路径：`sessions/{uid}.json`
+    fn account_session_file(&self, uid: u64) -> PathBuf {
+        self.sessions_dir.join(format!("{}.json", uid))
+    }
+
+    /// 设置当前激活账号（启动时从配置恢复）
     ///
-    /// 将用户认证信息序列化为JSON并保存到文件
-    pub async fn save_session(&mut self, user_auth: &UserAuth) -> Result<()> {
-        info!("💾 保存会话到文件: {}", self.session_file);
+    /// 仅清空内存缓存，下次 get_session 时按新 UID 加载
+    pub fn set_active_uid(&mut self, uid: Option<u64>) {
+        if self.active_uid != uid {
+            self.active_uid = uid;
+            self.current_session = None;
+        }
+    }
 
-        // 确保目录存在
-        if let Some(parent) = Path::new(&self.session_file).parent() {
-            info!("📁 创建目录: {:?}", parent);
+    /// 获取当前激活账号 UID
+    pub fn active_uid(&self) -> Option<u64> {
+        self.active_uid
+    }
+
+    /// 写入会话 JSON 到指定文件（自动创建目录）
+    async fn write_session_file(path: &Path, user_auth: &UserAuth) -> Result<()> {
+        if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .await
                 .context("Failed to create config directory")?;
         }
 
-        // 序列化为JSON
         let json =
             serde_json::to_string_pretty(user_auth).context("Failed to serialize session")?;
-        info!("📝 序列化JSON成功，大小: {} bytes", json.len());
-
-        // 写入文件
-        fs::write(&self.session_file, &json)
+        fs::write(path, &json)
             .await
             .context("Failed to write session file")?;
+        Ok(())
+    }
+
+    /// 读取会话文件
+    async fn read_session_file(path: &Path) -> Result<UserAuth> {
+        let content = fs::read_to_string(path)
+            .await
+            .context("Failed to read session file")?;
+        serde_json::from_str(&content).context("Failed to deserialize session")
+    }
+
+    /// 旧版本会话没有设备 UID：生成后写回会话文件，之后重启都复用该值
+    async fn persist_missing_devuid(path: &Path, user_auth: &mut UserAuth) {
+        if !user_auth.ensure_devuid() {
+            return;
+        }
+        match Self::write_session_file(path, user_auth).await {
+            Ok(()) => info!("已为会话生成并保存设备 UID: UID={}", user_auth.uid),
+            Err(e) => warn!("保存设备 UID 失败: {}", e),
+        }
+    }
+
+    /// 保存会话到文件
+    ///
+    /// 同时写入 `sessions/{uid}.json` 和 session.json，并将该账号设为激活账号
+    pub async fn save_session(&mut self, user_auth: &UserAuth) -> Result<()> {
+        info!("💾 保存会话到文件: {} (UID={})", self.session_file, user_auth.uid);
+
+        // 🔥 设备 UID 随会话持久化，缺失时才生成
+        let mut user_auth = user_auth.clone();
+        user_auth.ensure_devuid();
+        let user_auth = &user_auth;
+
+        Self::write_session_file(&self.account_session_file(user_auth.uid), user_auth).await?;
+        Self::write_session_file(Path::new(&self.session_file), user_auth).await?;
         info!("✅ 文件写入成功: {}", self.session_file);
 
         // 更新内存缓存
+        self.active_uid = Some(user_auth.uid);
         self.current_session = Some(user_auth.clone());
 
         info!("✅ 会话保存完成");
@@ -62,8 +123,21 @@ impl SessionManager {
 
     /// 从文件加载会话
     ///
-    /// 读取会话文件并反序列化为UserAuth
+    /// 优先读取激活账号的 `sessions/{uid}.json`，不存在时回退到 session.json
     pub async fn load_session(&mut self) -> Result<Option<UserAuth>> {
+        if let Some(uid) = self.active_uid {
+            let account_file = self.account_session_file(uid);
+            if account_file.exists() {
+                info!("🔍 从文件加载会话: {:?}", account_file);
+                let mut user_auth = Self::read_session_file(&account_file).await?;
+                Self::persist_missing_devuid(&account_file, &mut user_auth).await;
+                info!("会话加载成功: UID={}", user_auth.uid);
+                self.current_session = Some(user_auth.clone());
+                return Ok(Some(user_auth));
+            }
+            warn!("激活账号 {} 的会话文件不存在，回退到 {}", uid, self.session_file);
+        }
+
         info!("🔍 从文件加载会话: {}", self.session_file);
 
         // 检查文件是否存在
@@ -72,19 +146,23 @@ impl SessionManager {
             return Ok(None);
         }
 
-        // 读取文件内容
-        let content = fs::read_to_string(&self.session_file)
-            .await
-            .context("Failed to read session file")?;
-
-        // 反序列化
-        let user_auth: UserAuth =
-            serde_json::from_str(&content).context("Failed to deserialize session")?;
+        // 读取并反序列化
+        let mut user_auth = Self::read_session_file(Path::new(&self.session_file)).await?;
+        Self::persist_missing_devuid(Path::new(&self.session_file), &mut user_auth).await;
 
         // BDUSS 本地不做过期判断，由 verify_bduss 调百度 API 决定
         info!("会话加载成功: UID={}", user_auth.uid);
 
+        // 🔥 旧版本只有 session.json，迁移一份到多账号目录
+        let account_file = self.account_session_file(user_auth.uid);
+        if !account_file.exists() {
+            if let Err(e) = Self::write_session_file(&account_file, &user_auth).await {
+                warn!("迁移会话到多账号目录失败: {}", e);
+            }
+        }
+
         // 更新内存缓存
+        self.active_uid = Some(user_auth.uid);
         self.current_session = Some(user_auth.clone());
 
         Ok(Some(user_auth))
@@ -92,24 +170,86 @@ impl SessionManager {
 
     /// 清除会话
     ///
-    /// 删除会话文件和内存缓存
+    /// 删除当前账号的会话文件（session.json 与 `sessions/{uid}.json`）和内存缓存，
+    /// 其他已保存账号不受影响
     pub async fn clear_session(&mut self) -> Result<()> {
         info!("清除会话");
 
+        let uid = self
+            .current_session
+            .as_ref()
+            .map(|s| s.uid)
+            .or(self.active_uid);
+
         // 删除文件
         if Path::new(&self.session_file).exists() {
             fs::remove_file(&self.session_file)
                 .await
                 .context("Failed to remove session file")?;
         }
+        if let Some(uid) = uid {
+            let account_file = self.account_session_file(uid);
+            if account_file.exists() {
+                fs::remove_file(&account_file)
+                    .await
+                    .context("Failed to remove account session file")?;
+            }
+        }
 
         // 清除内存缓存
+        self.active_uid = None;
         self.current_session = None;
 
         info!("会话清除成功");
         Ok(())
     }
 
+    /// 列出所有已保存的账号会话
+    ///
+    /// 按 UID 升序返回，无法解析的文件会被跳过
+    pub async fn list_sessions(&self) -> Result<Vec<UserAuth>> {
+        let mut sessions = Vec::new();
+        if !self.sessions_dir.exists() {
+            return Ok(sessions);
+        }
+
+        let mut entries = fs::read_dir(&self.sessions_dir)
+            .await
+            .context("Failed to read sessions directory")?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match Self::read_session_file(&path).await {
+                Ok(user_auth) => sessions.push(user_auth),
+                Err(e) => warn!("跳过无效会话文件 {:?}: {}", path, e),
+            }
+        }
+
+        sessions.sort_by_key(|s| s.uid);
+        Ok(sessions)
+    }
+
+    /// 切换到已保存的账号
+    ///
+    /// 读取 `sessions/{uid}.json`，设为激活账号并同步到 session.json
+    pub async fn switch_session(&mut self, uid: u64) -> Result<UserAuth> {
+        let account_file = self.account_session_file(uid);
+        if !account_file.exists() {
+            anyhow::bail!("账号 {} 未保存会话，请先登录该账号", uid);
+        }
+
+        let user_auth = Self::read_session_file(&account_file).await?;
+        Self::write_session_file(Path::new(&self.session_file), &user_auth).await?;
+
+        self.active_uid = Some(uid);
+        self.current_session = Some(user_auth.clone());
+
+        info!("已切换账号会话: UID={}, 用户名={}", uid, user_auth.username);
+        Ok(user_auth)
+    }
+
     /// 获取当前会话
     ///
     /// 返回内存中的会话，如果没有则尝试从文件加载
@@ -170,4 +310,72 @@ mod tests {
         // 清理测试文件
         let _ = manager.clear_session().await;
     }
+
+    #[tokio::test]
+    async fn test_multi_account_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_file = dir.path().join("session.json");
+        let mut manager =
+            SessionManager::new(Some(session_file.to_string_lossy().to_string()));
+
+        let personal = UserAuth::new(1001, "personal".to_string(), "bduss_a".to_string());
+        let work = UserAuth::new(1002, "work".to_string(), "bduss_b".to_string());
+        manager.save_session(&personal).await.unwrap();
+        manager.save_session(&work).await.unwrap();
+
+        // 两个账号各自保存在 sessions/{uid}.json
+        let accounts = manager.list_sessions().await.unwrap();
+        assert_eq!(accounts.iter().map(|a| a.uid).collect::<Vec<_>>(), vec![1001, 1002]);
+        assert_eq!(manager.active_uid(), Some(1002));
+
+        // 切换回个人账号
+        let switched = manager.switch_session(1001).await.unwrap();
+        assert_eq!(switched.username, "personal");
+        assert_eq!(manager.get_uid().await, Some(1001));
+
+        // 重建管理器后按激活 UID 加载
+        let mut reloaded =
+            SessionManager::new(Some(session_file.to_string_lossy().to_string()));
+        reloaded.set_active_uid(Some(1002));
+        assert_eq!(reloaded.get_uid().await, Some(1002));
+
+        // 未保存的账号无法切换
+        assert!(manager.switch_session(9999).await.is_err());
+
+        // 登出只删除当前账号
+        manager.clear_session().await.unwrap();
+        let accounts = manager.list_sessions().await.unwrap();
+        assert_eq!(accounts.iter().map(|a| a.uid).collect::<Vec<_>>(), vec![1002]);
+    }
+
+    #[tokio::test]
+    async fn test_devuid_persisted_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_file = dir.path().join("session.json");
+        let mut manager =
+            SessionManager::new(Some(session_file.to_string_lossy().to_string()));
+
+        // 旧版本会话文件没有 devuid，加载时生成并写回
+        let mut legacy = UserAuth::new(1001, "user".to_string(), "bduss_a".to_string());
+        legacy.devuid = None;
+        fs::write(&session_file, serde_json::to_string(&legacy).unwrap())
+            .await
+            .unwrap();
+        let loaded = manager.load_session().await.unwrap().unwrap();
+        let devuid = loaded.devuid.clone().unwrap();
+        assert_eq!(devuid, crate::sign::generate_devuid("bduss_a"));
+        let on_disk: UserAuth =
+            serde_json::from_str(&fs::read_to_string(&session_file).await.unwrap()).unwrap();
+        assert_eq!(on_disk.devuid.as_deref(), Some(devuid.as_str()));
+
+        // 已持久化的 devuid 不会被重新生成
+        let mut user = loaded.clone();
+        user.devuid = Some("PERSISTED|0".to_string());
+        manager.save_session(&user).await.unwrap();
+        let mut reloaded =
+            SessionManager::new(Some(session_file.to_string_lossy().to_string()));
+        reloaded.set_active_uid(Some(1001));
+        let reloaded_user = reloaded.load_session().await.unwrap().unwrap();
+        assert_eq!(reloaded_user.devuid(), "PERSISTED|0");
+    }
 }
@@ -42,6 +42,9 @@ pub struct UserAuth {
     /// 上次预热时间戳（用于判断预热数据是否过期）
     #[serde(default)]
     pub last_warmup_at: Option<i64>,
+    /// 设备 UID（首次生成后随会话持久化，重启后复用，保持设备指纹稳定）
+    #[serde(default)]
+    pub devuid: Option<String>,
 }
 
 impl UserAuth {
@@ -66,6 +69,7 @@ impl UserAuth {
             bdstoken: None,
             login_time: chrono::Utc::now().timestamp(),
             last_warmup_at: None,
+            devuid: None,
         }
     }
 
@@ -99,7 +103,24 @@ impl UserAuth {
             bdstoken: None,
             login_time: chrono::Utc::now().timestamp(),
             last_warmup_at: None,
+            devuid: None,
+        }
+    }
+
+    /// 获取设备 UID（未持久化时按 BDUSS 生成）
+    pub fn devuid(&self) -> String {
+        self.devuid
+            .clone()
+            .unwrap_or_else(|| crate::sign::generate_devuid(&self.bduss))
+    }
+
+    /// 缺少设备 UID 时生成并写入，返回是否有变更（需要重新保存会话）
+    pub fn ensure_devuid(&mut self) -> bool {
+        if self.devuid.as_deref().is_some_and(|devuid| !devuid.is_empty()) {
+            return false;
         }
+        self.devuid = Some(crate::sign::generate_devuid(&self.bduss));
+        true
     }
 
     /// 检查会话是否过期（默认30天）
@@ -159,6 +180,21 @@ pub struct CookieLoginApiRequest {
     pub cookies: String,
 }
 
+/// 凭证导入 API 请求体
+///
+/// 无法扫码的场景（如无头服务器）直接提交 BDUSS 等凭证登录。
+#[derive(Debug, Deserialize)]
+pub struct ImportCredentialsRequest {
+    /// BDUSS（必填）
+    pub bduss: String,
+    /// STOKEN（可选，部分分享/转存接口需要）
+    #[serde(default)]
+    pub stoken: Option<String>,
+    /// PTOKEN（可选，缺失则登录后跳过预热）
+    #[serde(default)]
+    pub ptoken: Option<String>,
+}
+
 /// 登录响应
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
@@ -0,0 +1,72 @@
+//! 剩余时间估算（下载、上传共用）
+
+use std::collections::VecDeque;
+
+/// 剩余时间平滑使用的速度采样数
+const ETA_SMOOTHING_SAMPLES: usize = 10;
+
+/// 剩余时间估算器
+///
+/// 对最近几次速度采样取移动平均，避免瞬时速度抖动导致剩余时间频繁跳变
+#[derive(Debug, Clone, Default)]
+pub struct EtaEstimator {
+    /// 最近的速度采样（bytes/s）
+    speeds: VecDeque<u64>,
+}
+
+impl EtaEstimator {
+    /// 记录一次速度采样并估算剩余时间（秒）
+    ///
+    /// 速度为 0 或总大小未知时返回 None，并清空采样（恢复传输后重新平滑）
+    pub fn update(&mut self, speed: u64, transferred: u64, total: u64) -> Option<u64> {
+        if speed == 0 || total == 0 {
+            self.speeds.clear();
+            return None;
+        }
+
+        if self.speeds.len() >= ETA_SMOOTHING_SAMPLES {
+            self.speeds.pop_front();
+        }
+        self.speeds.push_back(speed);
+
+        let average = self.speeds.iter().sum::<u64>() / self.speeds.len() as u64;
+        if average == 0 {
+            return None;
+        }
+        Some(total.saturating_sub(transferred).div_ceil(average))
+    }
+
+    /// 清空速度采样（任务暂停、完成或失败时调用）
+    pub fn reset(&mut self) {
+        self.speeds.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta_estimator() {
+        let mut eta = EtaEstimator::default();
+
+        // 速度为 0 或总大小未知时无法估算
+        assert_eq!(eta.update(0, 0, 1000), None);
+        assert_eq!(eta.update(100, 0, 0), None);
+
+        assert_eq!(eta.update(100, 0, 1000), Some(10));
+        // 移动平均：(100 + 300) / 2 = 200，剩余 800 字节
+        assert_eq!(eta.update(300, 200, 1000), Some(4));
+        // 剩余不足 1 秒的数据向上取整
+        assert_eq!(eta.update(200, 999, 1000), Some(1));
+
+        // 超出窗口的旧采样被丢弃
+        for _ in 0..ETA_SMOOTHING_SAMPLES {
+            eta.update(50, 0, 1000);
+        }
+        assert_eq!(eta.update(50, 0, 1000), Some(20));
+
+        eta.reset();
+        assert_eq!(eta.update(500, 0, 1000), Some(2));
+    }
+}
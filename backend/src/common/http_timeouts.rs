@@ -0,0 +1,59 @@
+//! HTTP 超时配置
+//!
+//! 进程级设置（network.connect_timeout_secs / request_timeout_secs / chunk_timeout_secs），
+//! 网盘、下载、上传模块构建 reqwest 客户端时统一读取：
+//! - 连接超时与接口请求超时对之后创建的客户端生效
+//! - 分片超时在每次分片请求时读取，修改后立即生效
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use reqwest::ClientBuilder;
+
+/// 默认接口请求超时（秒）
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// 连接超时（秒），0 表示不单独限制
+static CONNECT_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+/// 接口请求超时（秒）
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_REQUEST_TIMEOUT_SECS);
+/// 分片请求超时（秒），0 表示按分片大小和实时速度自动计算
+static CHUNK_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// 设置 HTTP 超时（request_timeout_secs 为 0 时使用默认值）
+pub fn set_http_timeouts(connect_timeout_secs: u64, request_timeout_secs: u64, chunk_timeout_secs: u64) {
+    let request_timeout_secs = if request_timeout_secs == 0 {
+        DEFAULT_REQUEST_TIMEOUT_SECS
+    } else {
+        request_timeout_secs
+    };
+    CONNECT_TIMEOUT_SECS.store(connect_timeout_secs, Ordering::Relaxed);
+    REQUEST_TIMEOUT_SECS.store(request_timeout_secs, Ordering::Relaxed);
+    CHUNK_TIMEOUT_SECS.store(chunk_timeout_secs, Ordering::Relaxed);
+}
+
+/// 接口请求超时（元数据、预热、创建目录等非分片请求）
+pub fn request_timeout() -> Duration {
+    Duration::from_secs(REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed))
+}
+
+/// 接口请求超时，未修改默认值时使用调用方自己的默认超时（如创建目录等短请求保持 30 秒）
+pub fn request_timeout_or(default: Duration) -> Duration {
+    match REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        DEFAULT_REQUEST_TIMEOUT_SECS => default,
+        secs => Duration::from_secs(secs),
+    }
+}
+
+/// 配置的分片请求超时（秒），未配置时返回 None，由调用方自动计算
+pub fn chunk_timeout_secs() -> Option<u64> {
+    Some(CHUNK_TIMEOUT_SECS.load(Ordering::Relaxed)).filter(|&secs| secs > 0)
+}
+
+/// 为客户端设置连接超时（未配置时保持 reqwest 默认行为）
+pub fn apply_connect_timeout(builder: ClientBuilder) -> ClientBuilder {
+    match CONNECT_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => builder,
+        secs => builder.connect_timeout(Duration::from_secs(secs)),
+    }
+}
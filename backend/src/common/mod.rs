@@ -2,6 +2,8 @@
 //!
 //! 提供跨模块使用的通用组件
 
+mod eta;
+pub mod http_timeouts;
 mod memory_monitor;
 pub mod proxy;
 pub mod proxy_fallback;
@@ -10,6 +12,8 @@ mod refresh_coordinator;
 mod speed_anomaly_detector;
 mod thread_stagnation_detector;
 
+pub use eta::EtaEstimator;
+pub use http_timeouts::{apply_connect_timeout, chunk_timeout_secs, request_timeout, request_timeout_or, set_http_timeouts};
 pub use memory_monitor::{MemoryAnomaly, MemoryMonitor, MemoryMonitorConfig, MemorySample};
 pub use proxy::{ProxyConfig, ProxyType};
 pub use proxy_fallback::{
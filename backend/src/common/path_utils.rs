@@ -67,6 +67,46 @@ where
     )
 }
 
+/// 文件夹名最大字符数（兼顾各平台文件名长度限制）
+const MAX_FOLDER_NAME_CHARS: usize = 100;
+
+/// 将任意名称清理为可安全用作本地文件夹名的字符串
+///
+/// - 非法字符（`<>:"/\|?*` 及控制字符）替换为 `_`
+/// - 去除首尾空白和末尾的 `.`（Windows 不允许）
+/// - Windows 保留名（CON、NUL、COM1 等）前加 `_`
+/// - 超过 100 个字符时截断
+///
+/// 清理后为空时返回 None
+pub fn sanitize_folder_name(name: &str) -> Option<String> {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FOLDER_NAME_CHARS)
+        .collect();
+
+    let trimmed = replaced.trim().trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() || trimmed.chars().all(|c| c == '_') {
+        return None;
+    }
+
+    let stem = trimmed.split('.').next().unwrap_or(trimmed).to_ascii_uppercase();
+    let is_reserved = matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit());
+
+    Some(if is_reserved {
+        format!("_{}", trimmed)
+    } else {
+        trimmed.to_string()
+    })
+}
+
 /// 解析文件名，提取基础名称和扩展名
 /// 如果文件名已经是 "name (N)" 格式，则提取原始名称
 /// 
@@ -235,7 +275,6 @@ mod tests {
             .to_string()
             .contains("已尝试 9999 次"));
     }
-}
 
     #[test]
     fn test_incremental_rename_from_numbered() {
@@ -266,3 +305,19 @@ mod tests {
         assert_eq!(parse_filename("file (test).txt"), ("file (test)".to_string(), Some("txt".to_string())));
         assert_eq!(parse_filename("file (a1).txt"), ("file (a1)".to_string(), Some("txt".to_string())));
     }
+
+    #[test]
+    fn test_sanitize_folder_name() {
+        assert_eq!(sanitize_folder_name("电影合集").as_deref(), Some("电影合集"));
+        assert_eq!(sanitize_folder_name("a/b:c*d?").as_deref(), Some("a_b_c_d_"));
+        assert_eq!(sanitize_folder_name("  name. . ").as_deref(), Some("name"));
+        assert_eq!(sanitize_folder_name("line\nbreak").as_deref(), Some("line_break"));
+        assert_eq!(sanitize_folder_name("con").as_deref(), Some("_con"));
+        assert_eq!(sanitize_folder_name("COM1.txt").as_deref(), Some("_COM1.txt"));
+        assert_eq!(sanitize_folder_name("COMPUTER").as_deref(), Some("COMPUTER"));
+        assert!(sanitize_folder_name("").is_none());
+        assert!(sanitize_folder_name(" . . ").is_none());
+        assert!(sanitize_folder_name("?*").is_none());
+        assert_eq!(sanitize_folder_name(&"长".repeat(300)).unwrap().chars().count(), 100);
+    }
+}
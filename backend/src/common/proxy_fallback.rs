@@ -521,6 +521,7 @@ mod tests {
             username: None,
             password: None,
             allow_fallback,
+            url: None,
         }
     }
 
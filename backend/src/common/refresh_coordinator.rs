@@ -129,6 +129,11 @@ impl RefreshCoordinator {
         self.is_refreshing.load(Ordering::SeqCst)
     }
 
+    /// 获取最小刷新间隔（秒）
+    pub fn min_refresh_interval_secs(&self) -> u64 {
+        self.config.min_refresh_interval_secs
+    }
+
     /// 获取距离上次刷新的时间（秒）
     pub fn seconds_since_last_refresh(&self) -> u64 {
         let now_ms = Self::current_time_ms();
@@ -252,4 +257,22 @@ mod tests {
         let guard3 = coordinator.force_acquire();
         assert!(guard3.is_some());
     }
+
+    #[test]
+    fn test_interval_remaining() {
+        let coordinator = RefreshCoordinator::new(RefreshCoordinatorConfig {
+            min_refresh_interval_secs: 60,
+        });
+        assert_eq!(coordinator.min_refresh_interval_secs(), 60);
+
+        {
+            let _guard = coordinator.try_acquire();
+        }
+
+        // 刚刚刷新过，剩余等待时间接近完整间隔
+        let remaining = coordinator
+            .min_refresh_interval_secs()
+            .saturating_sub(coordinator.seconds_since_last_refresh());
+        assert!(remaining >= 59);
+    }
 }
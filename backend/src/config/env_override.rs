@@ -0,0 +1,268 @@
+// 环境变量覆盖层
+//
+// 环境变量只覆盖运行时配置，不写入配置文件：覆盖时记录各配置项在配置文件中的取值，
+// 保存配置时若该项仍是环境变量的取值，则写回配置文件中的原值
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use super::AppConfig;
+
+/// 当前生效的覆盖层（进程级，环境变量在进程内不变）
+static ENV_OVERRIDE_LAYER: RwLock<Option<EnvOverrideLayer>> = RwLock::new(None);
+
+/// 单个被环境变量覆盖的配置项
+#[derive(Debug, Clone)]
+struct EnvOverride<T> {
+    /// 环境变量名
+    name: &'static str,
+    /// 配置文件中的取值
+    file: T,
+    /// 环境变量的取值
+    env: T,
+}
+
+impl<T: Clone + PartialEq> EnvOverride<T> {
+    fn capture(name: &'static str, current: &T, env: Option<T>) -> Option<Self> {
+        env.map(|env| Self {
+            name,
+            file: current.clone(),
+            env,
+        })
+    }
+
+    fn apply(&self, value: &mut T, applied: &mut Vec<&'static str>) {
+        *value = self.env.clone();
+        applied.push(self.name);
+    }
+
+    /// 当前值仍是环境变量的取值时换回配置文件中的原值（用户修改过的值照常保存）
+    fn restore(&self, value: &mut T) {
+        if *value == self.env {
+            *value = self.file.clone();
+        }
+    }
+
+    fn remember_saved(&mut self, value: &T) {
+        self.file = value.clone();
+    }
+}
+
+/// 环境变量覆盖层
+///
+/// 支持的变量：
+/// - `BPR_SERVER_HOST` / `BPR_SERVER_PORT`
+/// - `BPR_DOWNLOAD_DIR`（必须为绝对路径）
+/// - `BPR_MAX_GLOBAL_THREADS` / `BPR_MAX_CONCURRENT_TASKS`（下载，必须大于 0）
+/// - `BPR_UPLOAD_MAX_GLOBAL_THREADS` / `BPR_UPLOAD_MAX_CONCURRENT_TASKS`（上传，必须大于 0）
+#[derive(Debug, Clone, Default)]
+pub struct EnvOverrideLayer {
+    server_host: Option<EnvOverride<String>>,
+    server_port: Option<EnvOverride<u16>>,
+    download_dir: Option<EnvOverride<PathBuf>>,
+    max_global_threads: Option<EnvOverride<usize>>,
+    max_concurrent_tasks: Option<EnvOverride<usize>>,
+    upload_max_global_threads: Option<EnvOverride<usize>>,
+    upload_max_concurrent_tasks: Option<EnvOverride<usize>>,
+}
+
+impl EnvOverrideLayer {
+    /// 读取环境变量，记录被覆盖配置项的当前取值（解析或验证失败的变量仅记录警告并忽略）
+    pub fn capture(config: &AppConfig) -> Self {
+        Self {
+            server_host: EnvOverride::capture(
+                "BPR_SERVER_HOST",
+                &config.server.host,
+                env_override::<String>("BPR_SERVER_HOST", |v| !v.trim().is_empty()),
+            ),
+            server_port: EnvOverride::capture(
+                "BPR_SERVER_PORT",
+                &config.server.port,
+                env_override::<u16>("BPR_SERVER_PORT", |v| *v > 0),
+            ),
+            download_dir: EnvOverride::capture(
+                "BPR_DOWNLOAD_DIR",
+                &config.download.download_dir,
+                env_override::<PathBuf>("BPR_DOWNLOAD_DIR", |v| v.is_absolute()),
+            ),
+            max_global_threads: EnvOverride::capture(
+                "BPR_MAX_GLOBAL_THREADS",
+                &config.download.max_global_threads,
+                env_override::<usize>("BPR_MAX_GLOBAL_THREADS", |v| *v > 0),
+            ),
+            max_concurrent_tasks: EnvOverride::capture(
+                "BPR_MAX_CONCURRENT_TASKS",
+                &config.download.max_concurrent_tasks,
+                env_override::<usize>("BPR_MAX_CONCURRENT_TASKS", |v| *v > 0),
+            ),
+            upload_max_global_threads: EnvOverride::capture(
+                "BPR_UPLOAD_MAX_GLOBAL_THREADS",
+                &config.upload.max_global_threads,
+                env_override::<usize>("BPR_UPLOAD_MAX_GLOBAL_THREADS", |v| *v > 0),
+            ),
+            upload_max_concurrent_tasks: EnvOverride::capture(
+                "BPR_UPLOAD_MAX_CONCURRENT_TASKS",
+                &config.upload.max_concurrent_tasks,
+                env_override::<usize>("BPR_UPLOAD_MAX_CONCURRENT_TASKS", |v| *v > 0),
+            ),
+        }
+    }
+
+    /// 将环境变量的取值应用到配置，返回实际生效的变量名
+    pub fn apply(&self, config: &mut AppConfig) -> Vec<&'static str> {
+        let mut applied = Vec::new();
+        if let Some(o) = &self.server_host {
+            o.apply(&mut config.server.host, &mut applied);
+        }
+        if let Some(o) = &self.server_port {
+            o.apply(&mut config.server.port, &mut applied);
+        }
+        if let Some(o) = &self.download_dir {
+            o.apply(&mut config.download.download_dir, &mut applied);
+        }
+        if let Some(o) = &self.max_global_threads {
+            o.apply(&mut config.download.max_global_threads, &mut applied);
+        }
+        if let Some(o) = &self.max_concurrent_tasks {
+            o.apply(&mut config.download.max_concurrent_tasks, &mut applied);
+        }
+        if let Some(o) = &self.upload_max_global_threads {
+            o.apply(&mut config.upload.max_global_threads, &mut applied);
+        }
+        if let Some(o) = &self.upload_max_concurrent_tasks {
+            o.apply(&mut config.upload.max_concurrent_tasks, &mut applied);
+        }
+        applied
+    }
+
+    /// 去掉配置中的环境变量取值（保存前调用）
+    pub fn restore(&self, config: &mut AppConfig) {
+        if let Some(o) = &self.server_host {
+            o.restore(&mut config.server.host);
+        }
+        if let Some(o) = &self.server_port {
+            o.restore(&mut config.server.port);
+        }
+        if let Some(o) = &self.download_dir {
+            o.restore(&mut config.download.download_dir);
+        }
+        if let Some(o) = &self.max_global_threads {
+            o.restore(&mut config.download.max_global_threads);
+        }
+        if let Some(o) = &self.max_concurrent_tasks {
+            o.restore(&mut config.download.max_concurrent_tasks);
+        }
+        if let Some(o) = &self.upload_max_global_threads {
+            o.restore(&mut config.upload.max_global_threads);
+        }
+        if let Some(o) = &self.upload_max_concurrent_tasks {
+            o.restore(&mut config.upload.max_concurrent_tasks);
+        }
+    }
+
+    /// 记录已写入配置文件的取值（保存后调用）
+    fn remember_saved(&mut self, saved: &AppConfig) {
+        if let Some(o) = &mut self.server_host {
+            o.remember_saved(&saved.server.host);
+        }
+        if let Some(o) = &mut self.server_port {
+            o.remember_saved(&saved.server.port);
+        }
+        if let Some(o) = &mut self.download_dir {
+            o.remember_saved(&saved.download.download_dir);
+        }
+        if let Some(o) = &mut self.max_global_threads {
+            o.remember_saved(&saved.download.max_global_threads);
+        }
+        if let Some(o) = &mut self.max_concurrent_tasks {
+            o.remember_saved(&saved.download.max_concurrent_tasks);
+        }
+        if let Some(o) = &mut self.upload_max_global_threads {
+            o.remember_saved(&saved.upload.max_global_threads);
+        }
+        if let Some(o) = &mut self.upload_max_concurrent_tasks {
+            o.remember_saved(&saved.upload.max_concurrent_tasks);
+        }
+    }
+
+    /// 设为当前生效的覆盖层
+    pub fn install(self) {
+        *ENV_OVERRIDE_LAYER.write().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+
+    /// 生成用于保存的配置副本（去掉当前生效的环境变量取值）
+    pub fn strip_installed(config: &AppConfig) -> AppConfig {
+        let mut stripped = config.clone();
+        if let Some(layer) = ENV_OVERRIDE_LAYER.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            layer.restore(&mut stripped);
+        }
+        stripped
+    }
+
+    /// 配置文件写入后更新当前覆盖层记录的配置文件取值
+    pub fn remember_installed(saved: &AppConfig) {
+        if let Some(layer) = ENV_OVERRIDE_LAYER.write().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            layer.remember_saved(saved);
+        }
+    }
+}
+
+/// 读取并解析单个环境变量覆盖值
+///
+/// 未设置时返回 None；解析失败或未通过验证时记录警告并返回 None
+fn env_override<T>(name: &str, is_valid: impl Fn(&T) -> bool) -> Option<T>
+where
+    T: std::str::FromStr,
+{
+    let raw = std::env::var(name).ok()?;
+    match raw.trim().parse::<T>() {
+        Ok(value) if is_valid(&value) => Some(value),
+        _ => {
+            tracing::warn!("环境变量 {}={:?} 无效，已忽略", name, raw);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_keeps_file_values() {
+        let mut config = AppConfig::default();
+        let file_port = config.server.port;
+        let file_threads = config.download.max_global_threads;
+
+        let layer = EnvOverrideLayer {
+            server_port: EnvOverride::capture("BPR_SERVER_PORT", &config.server.port, Some(19999)),
+            max_global_threads: EnvOverride::capture(
+                "BPR_MAX_GLOBAL_THREADS",
+                &config.download.max_global_threads,
+                Some(4),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            layer.apply(&mut config),
+            vec!["BPR_SERVER_PORT", "BPR_MAX_GLOBAL_THREADS"]
+        );
+        assert_eq!(config.server.port, 19999);
+
+        // 用户在界面修改过的值照常保存，仍是环境变量取值的项写回配置文件原值
+        config.download.max_global_threads = 8;
+        let mut saved = config.clone();
+        layer.restore(&mut saved);
+        assert_eq!(saved.server.port, file_port);
+        assert_eq!(saved.download.max_global_threads, 8);
+        assert_ne!(file_threads, 8);
+
+        // 保存后记录新的配置文件取值
+        let mut layer = layer;
+        layer.remember_saved(&saved);
+        config.download.max_global_threads = 4;
+        let mut saved_again = config.clone();
+        layer.restore(&mut saved_again);
+        assert_eq!(saved_again.download.max_global_threads, 8);
+    }
+}
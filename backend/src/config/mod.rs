@@ -1,15 +1,18 @@
 // 配置管理模块
 
 pub mod env_detector;
+pub mod env_override;
 pub mod mount_detector;
 pub mod path_validator;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use tokio::fs;
 
 pub use env_detector::{EnvDetector, EnvInfo, OsType};
+pub use env_override::EnvOverrideLayer;
 pub use mount_detector::{MountDetector, MountPoint};
 pub use path_validator::{PathValidationResult, PathValidator};
 
@@ -58,6 +61,108 @@ pub struct AppConfig {
     /// 冲突策略配置
     #[serde(default)]
     pub conflict_strategy: ConflictStrategyConfig,
+    /// 账号配置（多账号切换）
+    #[serde(default)]
+    pub account: AccountConfig,
+    /// 登录态检测配置
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// 离线下载配置
+    #[serde(default)]
+    pub cloud_dl: CloudDlConfig,
+    /// 安全配置
+    #[serde(default)]
+    pub security: SecurityConfig,
+    /// 下载时间段调度配置
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+}
+
+/// 下载时间段调度配置
+///
+/// 启用后只在允许的时间段内（系统本地时区）启动下载任务，例如只在夜间使用闲时带宽
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// 是否启用（默认关闭，全天下载）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 允许下载的时间段，格式 "HH:MM-HH:MM"（如 "00:00-08:00"），结束早于开始表示跨午夜
+    #[serde(default)]
+    pub windows: Vec<String>,
+    /// 时间段外是否暂停正在下载的任务（默认只暂停启动新任务）
+    #[serde(default)]
+    pub pause_running: bool,
+}
+
+impl ScheduleConfig {
+    /// 验证时间段格式（启用时至少需要一个时间段）
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, window) in self.windows.iter().enumerate() {
+            crate::downloader::TimeWindow::parse(window)
+                .map_err(|e| format!("schedule.windows[{}] {}", i, e))?;
+        }
+        if self.enabled && self.windows.is_empty() {
+            return Err("schedule.enabled 开启时至少需要配置一个时间段".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 账号配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// 当前激活账号的 UID（重启后自动加载该账号的会话）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_uid: Option<u64>,
+}
+
+/// 登录态检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// 后台检测 BDUSS 是否有效的间隔（秒），0 表示禁用
+    #[serde(default = "default_auth_check_interval_secs")]
+    pub auth_check_interval_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            auth_check_interval_secs: default_auth_check_interval_secs(),
+        }
+    }
+}
+
+fn default_auth_check_interval_secs() -> u64 {
+    1800
+}
+
+/// 离线下载配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloudDlConfig {
+    /// 新建离线任务默认启用完成后自动下载到本地（请求中显式指定时以请求为准）
+    #[serde(default)]
+    pub auto_download: bool,
+    /// 自动下载的本地目录（为空时使用下载配置的 download_dir）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_dir: Option<PathBuf>,
+}
+
+/// 安全配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// 只读模式：禁止删除、上传、转存等破坏性操作（下载和浏览不受影响）
+    #[serde(default)]
+    pub read_only: bool,
+    /// 开放签名诊断接口（返回 Locate 签名参数，默认关闭；只读模式下始终关闭）
+    #[serde(default)]
+    pub sign_diagnostics: bool,
+}
+
+impl SecurityConfig {
+    /// 签名诊断接口是否可用
+    pub fn sign_diagnostics_enabled(&self) -> bool {
+        self.sign_diagnostics && !self.read_only
+    }
 }
 
 /// 扫描配置
@@ -118,16 +223,70 @@ impl Default for ConflictStrategyConfig {
 pub struct NetworkConfig {
     #[serde(default)]
     pub proxy: ProxyConfig,
+
+    /// 移动端 User-Agent 覆盖（Locate 下载等接口使用，未设置时使用内置默认值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mobile_user_agent: Option<String>,
+
+    /// Web 端 User-Agent 覆盖（PCS/浏览器接口使用，未设置时使用内置默认值）
+    ///
+    /// 内置默认值与扫码登录时的 UA 一致，覆盖后建议重新登录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_user_agent: Option<String>,
+
+    /// 连接超时（秒），0 表示不单独限制（默认）
+    #[serde(default)]
+    pub connect_timeout_secs: u64,
+
+    /// 接口请求超时（秒），用于元数据、预热、创建目录等非分片请求，默认60秒
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// 分片上传/下载请求超时（秒），0 表示按分片大小和实时速度自动计算（默认）
+    ///
+    /// 大分片在慢速网络下可能需要比接口请求更长的读取时间
+    #[serde(default)]
+    pub chunk_timeout_secs: u64,
+}
+
+fn default_request_timeout_secs() -> u64 {
+    crate::common::http_timeouts::DEFAULT_REQUEST_TIMEOUT_SECS
 }
 
 impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
             proxy: ProxyConfig::default(),
+            mobile_user_agent: None,
+            web_user_agent: None,
+            connect_timeout_secs: 0,
+            request_timeout_secs: default_request_timeout_secs(),
+            chunk_timeout_secs: 0,
         }
     }
 }
 
+impl NetworkConfig {
+    /// 验证 User-Agent 覆盖（设置时不能为空，也不能包含控制字符）
+    pub fn validate_user_agents(&self) -> Result<(), String> {
+        let fields = [
+            ("network.mobile_user_agent", &self.mobile_user_agent),
+            ("network.web_user_agent", &self.web_user_agent),
+        ];
+        for (name, ua) in fields {
+            if let Some(ua) = ua {
+                if ua.trim().is_empty() {
+                    return Err(format!("{} 不能为空", name));
+                }
+                if ua.chars().any(char::is_control) {
+                    return Err(format!("{} 不能包含控制字符", name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// 自动备份配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoBackupConfig {
@@ -405,6 +564,20 @@ pub struct LogConfig {
     /// 单个日志文件最大大小（字节，默认 50MB）
     #[serde(default = "default_log_max_file_size")]
     pub max_file_size: u64,
+    /// 日志输出格式（默认 text，json 便于 Loki 等日志系统采集）
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 人类可读的文本格式
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象，事件字段展开到顶层
+    Json,
 }
 
 fn default_log_enabled() -> bool {
@@ -435,6 +608,7 @@ impl Default for LogConfig {
             retention_days: default_log_retention_days(),
             level: default_log_level(),
             max_file_size: default_log_max_file_size(),
+            format: LogFormat::default(),
         }
     }
 }
@@ -448,6 +622,37 @@ pub struct ServerConfig {
     pub port: u16,
     /// CORS允许的源
     pub cors_origins: Vec<String>,
+    /// 全局进度事件上限（每秒，所有任务合计，0 表示不限制）
+    #[serde(default = "default_max_events_per_sec")]
+    pub max_events_per_sec: u64,
+}
+
+fn default_max_events_per_sec() -> u64 {
+    100
+}
+
+/// 最近使用的下载目录数量上限
+pub const MAX_RECENT_DIRECTORIES: usize = 10;
+
+/// 反序列化最近下载目录：兼容旧配置的单个路径和新配置的路径列表
+fn deserialize_recent_directories<'de, D>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+
+    let mut dirs = match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(dir)) => vec![dir],
+        Some(OneOrMany::Many(dirs)) => dirs,
+        None => Vec::new(),
+    };
+    dirs.truncate(MAX_RECENT_DIRECTORIES);
+    Ok(dirs)
 }
 
 /// 下载配置
@@ -458,9 +663,15 @@ pub struct DownloadConfig {
     /// 用户设置的默认目录（用于"设置为默认"功能）
     #[serde(default)]
     pub default_directory: Option<PathBuf>,
-    /// 最近使用的下载目录
-    #[serde(default)]
-    pub recent_directory: Option<PathBuf>,
+    /// 最近使用的下载目录（最近使用的在前，最多 `MAX_RECENT_DIRECTORIES` 个）
+    ///
+    /// 兼容旧配置的单值字段 `recent_directory`，加载时自动迁移为列表
+    #[serde(
+        default,
+        alias = "recent_directory",
+        deserialize_with = "deserialize_recent_directories"
+    )]
+    pub recent_directories: Vec<PathBuf>,
     /// 每次下载时是否询问保存位置
     #[serde(default = "default_ask_each_time")]
     pub ask_each_time: bool,
@@ -475,6 +686,269 @@ pub struct DownloadConfig {
     /// CDN刷新配置
     #[serde(default)]
     pub cdn_refresh: CdnRefreshConfig,
+    /// 全局限速 (KB/s)，所有下载任务共享，0 表示不限速
+    #[serde(default)]
+    pub global_speed_limit_kbps: u64,
+    /// 下载完成后是否校验 MD5（网盘未提供 MD5 的文件自动跳过）
+    #[serde(default = "default_verify_md5_after_download")]
+    pub verify_md5_after_download: bool,
+    /// 下载前磁盘预留空间（MB）：剩余空间需大于待下载大小加该值，否则任务直接失败
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64,
+    /// 分片重试退避配置
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// 限流检测与全局冷却配置
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// 自适应并发配置（按实测吞吐量动态调整全局分片线程数）
+    #[serde(default)]
+    pub adaptive_concurrency: AdaptiveConcurrencyConfig,
+    /// 下载完成回调地址（任务完成时 POST JSON，未配置时不发送）
+    #[serde(default)]
+    pub completion_webhook_url: Option<String>,
+    /// 下载完成回调共享密钥（通过 X-Webhook-Secret 请求头发送）
+    #[serde(default)]
+    pub completion_webhook_secret: Option<String>,
+    /// 单任务最大并发分片数默认覆盖值，0 表示按文件大小自动计算
+    ///
+    /// 创建任务时可单独指定 max_chunks_override；实际值受会员等级上限约束
+    /// （见 max_chunks_ceiling_for_vip），超过推荐线程数可能导致账号被限速
+    #[serde(default)]
+    pub max_chunks_per_task: usize,
+    /// 本地 MD5 去重：网盘 MD5 与已下载过的本地文件一致时直接复制，不再重复下载
+    #[serde(default)]
+    pub dedup_local: bool,
+    /// 本地去重时使用硬链接代替复制（两个路径共享同一份内容，修改其中一个会影响另一个）
+    #[serde(default)]
+    pub dedup_hardlink: bool,
+    /// 单个 CDN 主机的最大并发分片数，0 表示不限制
+    ///
+    /// 存在多个下载链接时分片会分散到不同主机，避免集中请求同一主机触发限速
+    #[serde(default)]
+    pub max_chunks_per_host: usize,
+    /// 任务无任何下载进度的最长时间（秒），超过后按 "stalled" 失败（可恢复时转为任务级重试），0 表示不限制
+    ///
+    /// 独立于 CDN 刷新的停滞检测，作为链接刷新失效时的兜底
+    #[serde(default = "default_max_stall_secs")]
+    pub max_stall_secs: u64,
+    /// 按文件类型自动选择下载目录（键为分类或扩展名，值为绝对路径）
+    ///
+    /// 分类：video / audio / image / document / archive；扩展名不区分大小写，可带前导点，
+    /// 且优先于分类。创建任务未指定目录时按文件名匹配，未匹配时使用 download_dir
+    #[serde(default)]
+    pub type_dirs: BTreeMap<String, PathBuf>,
+}
+
+/// 文件类型分类及其扩展名（用于 type_dirs 按分类匹配下载目录）
+const FILE_TYPE_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "video",
+        &["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "ts", "rmvb", "rm", "3gp", "mpg", "mpeg"],
+    ),
+    ("audio", &["mp3", "flac", "wav", "aac", "ogg", "m4a", "wma", "ape", "opus"]),
+    ("image", &["jpg", "jpeg", "png", "gif", "bmp", "webp", "heic", "tif", "tiff", "svg"]),
+    (
+        "document",
+        &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "md", "epub", "mobi", "csv", "rtf", "odt"],
+    ),
+    ("archive", &["zip", "rar", "7z", "tar", "gz", "bz2", "xz", "tgz", "iso"]),
+];
+
+/// 文件扩展名所属的分类（未知扩展名返回 None）
+fn file_type_category(ext: &str) -> Option<&'static str> {
+    FILE_TYPE_CATEGORIES
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext))
+        .map(|(category, _)| *category)
+}
+
+/// 按文件名从 type_dirs 中选择下载目录（扩展名优先于分类，未匹配时返回 None）
+pub fn match_type_dir<'a>(type_dirs: &'a BTreeMap<String, PathBuf>, filename: &str) -> Option<&'a PathBuf> {
+    if type_dirs.is_empty() {
+        return None;
+    }
+    let ext = std::path::Path::new(filename)
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    let find = |key: &str| {
+        type_dirs
+            .iter()
+            .find(|(k, _)| k.trim().trim_start_matches('.').eq_ignore_ascii_case(key))
+            .map(|(_, dir)| dir)
+    };
+    find(&ext).or_else(|| file_type_category(&ext).and_then(find))
+}
+
+/// 分片下载重试退避配置
+///
+/// 分片零数据失败时切换链接重试，第 n 次重试前等待
+/// `min(base_delay_ms * 2^n, max_delay_ms)` 毫秒
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// 退避初始延迟（毫秒），默认100ms
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+
+    /// 退避最大延迟（毫秒），默认5000ms
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+
+    /// 单次调度内的最大重试次数，0 表示沿用 max_retries
+    #[serde(default)]
+    pub max_attempts: u32,
+
+    /// 是否随机化延迟（在 [delay/2, delay] 区间随机），避免多个分片同时重试
+    #[serde(default)]
+    pub jitter: bool,
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    100
+}
+fn default_retry_max_delay_ms() -> u64 {
+    5000
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: 100,
+            max_delay_ms: 5000,
+            max_attempts: 0,
+            jitter: false,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// 解析实际重试次数：max_attempts 为 0 时使用 fallback
+    pub fn with_fallback_attempts(&self, fallback: u32) -> Self {
+        Self {
+            max_attempts: if self.max_attempts == 0 {
+                fallback
+            } else {
+                self.max_attempts
+            },
+            ..self.clone()
+        }
+    }
+
+    /// 计算第 retry_count 次重试前的退避延迟（毫秒）
+    ///
+    /// 默认配置下的延迟序列：200ms → 400ms → 800ms → ... → 5000ms
+    pub fn delay_ms(&self, retry_count: u32) -> u64 {
+        let delay = 1u64
+            .checked_shl(retry_count)
+            .and_then(|factor| self.base_delay_ms.checked_mul(factor))
+            .unwrap_or(u64::MAX)
+            .min(self.max_delay_ms);
+
+        if self.jitter && delay > 1 {
+            use rand::Rng;
+            rand::thread_rng().gen_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+}
+
+/// 限流检测与全局冷却配置
+///
+/// 统计窗口内命中百度限流（频控 errno 或 HTTP 429）的次数达到阈值时进入全局冷却：
+/// 冷却期间暂停启动新任务，全局分片线程数降到 `cooldown_max_threads`，到期后自动恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// 是否启用限流冷却
+    #[serde(default = "default_rate_limit_enabled")]
+    pub enabled: bool,
+
+    /// 统计窗口（秒），默认60秒
+    #[serde(default = "default_rate_limit_window_secs")]
+    pub window_secs: u64,
+
+    /// 触发冷却的限流次数（窗口内），默认5次
+    #[serde(default = "default_rate_limit_threshold")]
+    pub threshold: u32,
+
+    /// 冷却时长（秒），默认300秒
+    #[serde(default = "default_rate_limit_cooldown_secs")]
+    pub cooldown_secs: u64,
+
+    /// 冷却期间的全局最大线程数，默认2
+    #[serde(default = "default_rate_limit_cooldown_max_threads")]
+    pub cooldown_max_threads: usize,
+}
+
+fn default_rate_limit_enabled() -> bool {
+    true
+}
+fn default_rate_limit_window_secs() -> u64 {
+    60
+}
+fn default_rate_limit_threshold() -> u32 {
+    5
+}
+fn default_rate_limit_cooldown_secs() -> u64 {
+    300
+}
+fn default_rate_limit_cooldown_max_threads() -> usize {
+    2
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 60,
+            threshold: 5,
+            cooldown_secs: 300,
+            cooldown_max_threads: 2,
+        }
+    }
+}
+
+/// 自适应并发配置
+///
+/// 开启后调度器按 AIMD 方式调整全局分片线程数：吞吐量持续提升时每轮加 1，
+/// 吞吐量不再提升时回退到上一档，分片失败激增时减半。实际线程数始终在
+/// `[min_threads, max_threads]` 范围内且不超过 `max_global_threads`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveConcurrencyConfig {
+    /// 是否启用自适应并发，默认关闭
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// 线程数下限，默认2
+    #[serde(default = "default_adaptive_min_threads")]
+    pub min_threads: usize,
+
+    /// 线程数上限，0 表示使用 max_global_threads
+    #[serde(default)]
+    pub max_threads: usize,
+
+    /// 评估间隔（秒），默认5秒
+    #[serde(default = "default_adaptive_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_adaptive_min_threads() -> usize {
+    2
+}
+fn default_adaptive_interval_secs() -> u64 {
+    5
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_threads: 2,
+            max_threads: 0,
+            interval_secs: 5,
+        }
+    }
 }
 
 /// CDN链接刷新配置
@@ -610,6 +1084,19 @@ fn default_ask_each_time() -> bool {
     true
 }
 
+/// 默认下载完成后校验 MD5
+fn default_verify_md5_after_download() -> bool {
+    true
+}
+
+fn default_min_free_space_mb() -> u64 {
+    100
+}
+
+fn default_max_stall_secs() -> u64 {
+    300
+}
+
 /// 上传配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UploadConfig {
@@ -644,7 +1131,9 @@ impl Default for UploadConfig {
 /// 转存配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferConfig {
-    /// 转存后默认行为：transfer_only / transfer_and_download
+    /// 转存后默认行为：transfer_only / transfer_and_download / transfer_download_cleanup
+    ///
+    /// transfer_download_cleanup：转存到网盘临时目录 → 下载到本地 → 下载成功且 MD5 校验后删除网盘副本
     #[serde(default = "default_transfer_behavior")]
     pub default_behavior: String,
 
@@ -655,11 +1144,27 @@ pub struct TransferConfig {
     /// 最近使用的网盘目录路径（与 fs_id 对应）
     #[serde(default)]
     pub recent_save_path: Option<String>,
+
+    /// 最大同时执行的转存任务数（超出的任务排队，避免批量转存触发百度风控）
+    #[serde(default = "default_max_concurrent_transfers")]
+    pub max_concurrent_transfers: usize,
 }
 
+/// 转存后行为：仅转存
+pub const TRANSFER_BEHAVIOR_ONLY: &str = "transfer_only";
+/// 转存后行为：转存后自动下载
+pub const TRANSFER_BEHAVIOR_AND_DOWNLOAD: &str = "transfer_and_download";
+/// 转存后行为：转存到临时目录 → 下载 → 删除网盘副本
+pub const TRANSFER_BEHAVIOR_DOWNLOAD_CLEANUP: &str = "transfer_download_cleanup";
+
 /// 默认转存行为：仅转存
 fn default_transfer_behavior() -> String {
-    "transfer_only".to_string()
+    TRANSFER_BEHAVIOR_ONLY.to_string()
+}
+
+/// 默认最大同时转存数
+fn default_max_concurrent_transfers() -> usize {
+    3
 }
 
 impl Default for TransferConfig {
@@ -668,6 +1173,7 @@ impl Default for TransferConfig {
             default_behavior: default_transfer_behavior(),
             recent_save_fs_id: None,
             recent_save_path: None,
+            max_concurrent_transfers: default_max_concurrent_transfers(),
         }
     }
 }
@@ -784,6 +1290,59 @@ impl FilesystemConfig {
 
         Ok(())
     }
+
+    /// 校验写入目标路径位于 allowed_paths 白名单内（白名单为空时不限制）
+    ///
+    /// 目标路径可以尚不存在：规范化其最近的已存在祖先目录（解析符号链接）后再拼接剩余部分，
+    /// 剩余部分不允许包含 `..`。`constraint` 为违规时错误信息中展示的配置项名称
+    pub fn ensure_path_allowed(&self, path: &std::path::Path, constraint: &str) -> Result<()> {
+        if self.allowed_paths.is_empty() {
+            return Ok(());
+        }
+
+        let resolved = resolve_write_target(path).with_context(|| {
+            format!("{} ({:?}) 无法校验 filesystem.allowed_paths 白名单", constraint, path)
+        })?;
+        let in_allowlist = self.allowed_paths.iter().any(|allowed| {
+            std::path::PathBuf::from(allowed)
+                .canonicalize()
+                .map(|c| resolved.starts_with(&c))
+                .unwrap_or(false)
+        });
+        if !in_allowlist {
+            anyhow::bail!(
+                "{} ({:?}) 不在 filesystem.allowed_paths 白名单内: {:?}",
+                constraint,
+                path,
+                self.allowed_paths
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 解析写入目标的真实路径（目标可以尚不存在）
+fn resolve_write_target(path: &std::path::Path) -> Result<PathBuf> {
+    if !path.is_absolute() {
+        anyhow::bail!("路径必须是绝对路径");
+    }
+
+    let mut existing = path;
+    let mut remainder = Vec::new();
+    while !existing.exists() {
+        // 不存在的部分以 `..` 结尾时 file_name 返回 None，无法安全拼接
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            anyhow::bail!("路径不存在的部分不允许包含 ..");
+        };
+        remainder.push(name.to_os_string());
+        existing = parent;
+    }
+
+    let mut resolved = existing.canonicalize().context("规范化路径失败")?;
+    for name in remainder.into_iter().rev() {
+        resolved.push(name);
+    }
+    Ok(resolved)
 }
 
 /// 持久化配置
@@ -826,6 +1385,18 @@ pub struct PersistenceConfig {
     /// 历史任务保留天数（超过此天数的历史任务将被清理，默认 30 天）
     #[serde(default = "default_history_retention_days")]
     pub history_retention_days: u64,
+
+    /// 恢复下载任务时是否校验最近完成的分片（重新读取文件区域对比 WAL 中的 CRC32，默认关闭）
+    ///
+    /// 开启后分片完成时也会读取一次分片数据计算 CRC32，会增加磁盘 I/O
+    #[serde(default)]
+    pub verify_chunks_on_recovery: bool,
+
+    /// 是否压缩 WAL 文件（gzip，`{task_id}.wal.gz`，默认关闭）
+    ///
+    /// 新记录先追加到未压缩的 `.wal`，累积到一定大小后合并压缩，适合分片数很多的任务
+    #[serde(default)]
+    pub compress_wal: bool,
 }
 
 // PersistenceConfig 默认值函数
@@ -872,6 +1443,8 @@ impl Default for PersistenceConfig {
             history_archive_hour: default_history_archive_hour(),
             history_archive_minute: default_history_archive_minute(),
             history_retention_days: default_history_retention_days(),
+            verify_chunks_on_recovery: false,
+            compress_wal: false,
         }
     }
 }
@@ -921,6 +1494,24 @@ pub struct VipRecommendedConfig {
 }
 
 impl DownloadConfig {
+    /// 记录最近使用的下载目录：移到列表最前（去重），超过上限时丢弃最久未使用的
+    ///
+    /// 返回列表是否发生变化（目录已在最前时无需保存配置）
+    pub fn push_recent_directory(&mut self, dir: PathBuf) -> bool {
+        if self.recent_directories.first() == Some(&dir) {
+            return false;
+        }
+        self.recent_directories.retain(|existing| existing != &dir);
+        self.recent_directories.insert(0, dir);
+        self.recent_directories.truncate(MAX_RECENT_DIRECTORIES);
+        true
+    }
+
+    /// 获取分片重试配置（已解析实际重试次数）
+    pub fn chunk_retry_config(&self) -> RetryConfig {
+        self.retry.with_fallback_attempts(self.max_retries)
+    }
+
     /// 验证下载路径是否为绝对路径
     ///
     /// # 返回值
@@ -940,6 +1531,23 @@ impl DownloadConfig {
         Ok(())
     }
 
+    /// 验证按文件类型配置的下载目录（与主下载目录一样必须是绝对路径）
+    pub fn validate_type_dirs(&self) -> Result<()> {
+        for (key, dir) in &self.type_dirs {
+            if key.trim().trim_start_matches('.').is_empty() {
+                anyhow::bail!("download.type_dirs 的文件类型不能为空");
+            }
+            if !dir.is_absolute() {
+                anyhow::bail!(
+                    "download.type_dirs.{} 必须是绝对路径，当前值: {:?}",
+                    key,
+                    dir
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// 增强路径验证（检查存在性、可写性、挂载点等）
     ///
     /// # 返回值
@@ -1049,6 +1657,37 @@ impl DownloadConfig {
         self.max_concurrent_tasks = recommended.max_tasks;
     }
 
+    /// 验证下载完成回调地址（仅支持 http/https）
+    pub fn validate_completion_webhook(&self) -> Result<(), String> {
+        let Some(url) = self
+            .completion_webhook_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+        else {
+            return Ok(());
+        };
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+            Ok(parsed) => Err(format!(
+                "download.completion_webhook_url 仅支持 http/https 协议，当前为: {}",
+                parsed.scheme()
+            )),
+            Err(e) => Err(format!("download.completion_webhook_url 格式无效: {}", e)),
+        }
+    }
+
+    /// 单任务并发分片数上限（与 validate_for_vip 的安全线一致）
+    ///
+    /// 普通用户为推荐线程数，会员 10，SVIP 20；覆盖值超过上限时按上限处理
+    pub fn max_chunks_ceiling_for_vip(vip_type: VipType) -> usize {
+        match vip_type {
+            VipType::Normal => Self::recommended_for_vip(vip_type).threads,
+            VipType::Vip => 10,
+            VipType::Svip => 20,
+        }
+    }
+
     /// 验证配置是否安全
     pub fn validate_for_vip(&self, vip_type: VipType) -> Result<(), String> {
         let recommended = Self::recommended_for_vip(vip_type);
@@ -1081,6 +1720,16 @@ impl DownloadConfig {
             ));
         }
 
+        // 警告：单任务分片数覆盖值超过会员等级上限
+        let ceiling = Self::max_chunks_ceiling_for_vip(vip_type);
+        if self.max_chunks_per_task > ceiling {
+            return Err(format!(
+                "⚠️ 注意：单任务并发分片数超过上限！当前: {}, 上限: {}\n\
+                 实际下载时将按 {} 处理，过高的并发可能导致限速",
+                self.max_chunks_per_task, ceiling, ceiling
+            ));
+        }
+
         Ok(())
     }
 }
@@ -1125,17 +1774,32 @@ impl Default for AppConfig {
                 host,
                 port: 18888,
                 cors_origins: vec!["*".to_string()],
+                max_events_per_sec: default_max_events_per_sec(),
             },
             download: DownloadConfig {
                 download_dir,
                 default_directory: None,
-                recent_directory: None,
+                recent_directories: Vec::new(),
                 ask_each_time: true,
                 max_global_threads: svip_config.threads,
                 chunk_size_mb: svip_config.chunk_size,
                 max_concurrent_tasks: svip_config.max_tasks,
                 max_retries: 3,
                 cdn_refresh: CdnRefreshConfig::default(),
+                global_speed_limit_kbps: 0,
+                verify_md5_after_download: true,
+                min_free_space_mb: 100,
+                retry: RetryConfig::default(),
+                rate_limit: RateLimitConfig::default(),
+                adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+                completion_webhook_url: None,
+                completion_webhook_secret: None,
+                max_chunks_per_task: 0,
+                dedup_local: false,
+                dedup_hardlink: false,
+                max_chunks_per_host: 0,
+                max_stall_secs: 300,
+                type_dirs: BTreeMap::new(),
             },
             upload: UploadConfig::default(),
             transfer: TransferConfig::default(),
@@ -1148,6 +1812,11 @@ impl Default for AppConfig {
             network: NetworkConfig::default(),
             scan: ScanConfig::default(),
             conflict_strategy: ConflictStrategyConfig::default(),
+            account: AccountConfig::default(),
+            auth: AuthConfig::default(),
+            cloud_dl: CloudDlConfig::default(),
+            security: SecurityConfig::default(),
+            schedule: ScheduleConfig::default(),
         }
     }
 }
@@ -1170,7 +1839,7 @@ impl AppConfig {
         // 检测是否为旧配置格式（没有 conflict_strategy 字段）
         let is_legacy_config = !content.contains("conflict_strategy");
 
-        let config: AppConfig = toml::from_str(&content).context("Failed to parse config file")?;
+        let mut config: AppConfig = toml::from_str(&content).context("Failed to parse config file")?;
 
         // 如果是旧配置，记录迁移事件
         if is_legacy_config {
@@ -1189,6 +1858,10 @@ impl AppConfig {
             .download
             .validate_download_dir()
             .context("配置文件中的下载路径验证失败")?;
+        config
+            .download
+            .validate_type_dirs()
+            .context("配置文件中的分类下载目录验证失败")?;
 
         // 验证分享直下临时目录路径是否安全
         config
@@ -1202,6 +1875,21 @@ impl AppConfig {
             .validate()
             .context("配置文件中的文件系统配置验证失败")?;
 
+        // 展开并验证代理配置（启动时即发现无效的代理地址）
+        config
+            .network
+            .proxy
+            .resolve_url()
+            .and_then(|()| config.network.proxy.validate())
+            .context("配置文件中的代理配置验证失败")?;
+
+        // 验证下载时间段
+        config
+            .schedule
+            .validate()
+            .map_err(anyhow::Error::msg)
+            .context("配置文件中的下载时间段配置验证失败")?;
+
         Ok(config)
     }
 
@@ -1211,7 +1899,7 @@ impl AppConfig {
     /// 1. 验证下载路径格式（绝对路径）
     /// 2. 增强验证（存在性、可写性、可用空间）
     /// 3. 如果路径不存在，报错（要求用户先创建目录）
-    /// 4. 序列化并保存配置文件
+    /// 4. 序列化并保存配置文件（环境变量覆盖的配置项写回配置文件中的原值）
     ///
     /// # 返回值
     /// - Ok(PathValidationResult): 保存成功，返回路径验证结果
@@ -1232,6 +1920,11 @@ impl AppConfig {
             .validate()
             .context("保存配置失败：文件系统配置验证失败")?;
 
+        // 1d. 下载目录必须位于文件系统白名单内（白名单非空时）
+        self.filesystem
+            .ensure_path_allowed(&self.download.download_dir, "download.download_dir")
+            .context("保存配置失败：下载目录不在白名单内")?;
+
         // 2. 增强验证（存在性、可写性、可用空间）
         let validation_result = self
             .download
@@ -1256,8 +1949,31 @@ impl AppConfig {
             );
         }
 
-        // 4. 序列化并保存配置文件
-        let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        // 3b. 分类下载目录与主下载目录一样验证（绝对路径、白名单、存在且可写）
+        self.download
+            .validate_type_dirs()
+            .context("保存配置失败：分类下载目录必须是绝对路径")?;
+        let is_docker = EnvDetector::get_env_info().is_docker;
+        for (key, dir) in &self.download.type_dirs {
+            self.filesystem
+                .ensure_path_allowed(dir, &format!("download.type_dirs.{}", key))
+                .context("保存配置失败：分类下载目录不在白名单内")?;
+            let result = PathValidator::validate_with_docker_check(dir, is_docker);
+            if !result.exists {
+                anyhow::bail!(
+                    "保存配置失败：分类下载目录 {} 不存在。路径: {:?}。请先手动创建该目录",
+                    key,
+                    dir
+                );
+            }
+            if !result.valid {
+                anyhow::bail!("保存配置失败：分类下载目录 {} {}", key, result.message);
+            }
+        }
+
+        // 4. 序列化并保存配置文件（去掉环境变量覆盖，环境变量只影响运行时配置）
+        let saved = EnvOverrideLayer::strip_installed(self);
+        let content = toml::to_string_pretty(&saved).context("Failed to serialize config")?;
 
         // 确保父目录存在
         if let Some(parent) = std::path::Path::new(path).parent() {
@@ -1270,14 +1986,15 @@ impl AppConfig {
             .await
             .context("Failed to write config file")?;
 
+        EnvOverrideLayer::remember_installed(&saved);
         tracing::info!("✓ 配置已保存: {}", path);
 
         Ok(validation_result)
     }
 
-    /// 加载或创建默认配置
+    /// 加载或创建默认配置，并应用环境变量覆盖
     pub async fn load_or_default(path: &str) -> Self {
-        match Self::load_from_file(path).await {
+        let mut config = match Self::load_from_file(path).await {
             Ok(config) => {
                 tracing::info!("配置文件加载成功: {}", path);
                 config
@@ -1309,7 +2026,28 @@ impl AppConfig {
 
                 default_config
             }
+        };
+
+        // 🔥 环境变量覆盖（优先级：环境变量 > 配置文件 > 默认值）
+        config.apply_env_overrides();
+        config
+    }
+
+    /// 应用环境变量覆盖（便于 Docker 部署时无需挂载 TOML 即可调整常用配置）
+    ///
+    /// 优先级：环境变量 > 配置文件 > 默认值，支持的变量见 [`EnvOverrideLayer`]。
+    /// 覆盖层同时设为当前生效的覆盖层，保存配置时写回配置文件中的原值。
+    ///
+    /// 解析或验证失败的变量仅记录警告并保留原值，返回实际生效的变量名
+    pub fn apply_env_overrides(&mut self) -> Vec<&'static str> {
+        let layer = EnvOverrideLayer::capture(self);
+        let applied = layer.apply(self);
+        layer.install();
+
+        if !applied.is_empty() {
+            tracing::info!("已应用环境变量配置覆盖: {:?}", applied);
         }
+        applied
     }
 }
 
@@ -1318,6 +2056,163 @@ mod tests {
     use super::*;
     use tempfile::NamedTempFile;
 
+    /// 环境变量是进程级共享状态，相关测试需串行执行
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_apply_env_overrides() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let download_dir = std::env::temp_dir().join("bpr-env-override");
+        std::env::set_var("BPR_SERVER_PORT", "19999");
+        std::env::set_var("BPR_DOWNLOAD_DIR", &download_dir);
+        std::env::set_var("BPR_MAX_GLOBAL_THREADS", " 4 ");
+
+        let mut config = AppConfig::default();
+        let applied = config.apply_env_overrides();
+
+        std::env::remove_var("BPR_SERVER_PORT");
+        std::env::remove_var("BPR_DOWNLOAD_DIR");
+        std::env::remove_var("BPR_MAX_GLOBAL_THREADS");
+
+        assert_eq!(
+            applied,
+            vec!["BPR_SERVER_PORT", "BPR_DOWNLOAD_DIR", "BPR_MAX_GLOBAL_THREADS"]
+        );
+        assert_eq!(config.server.port, 19999);
+        assert_eq!(config.download.download_dir, download_dir);
+        assert_eq!(config.download.max_global_threads, 4);
+        // 未设置的字段保持原值
+        assert_eq!(config.download.max_concurrent_tasks, AppConfig::default().download.max_concurrent_tasks);
+    }
+
+    #[test]
+    fn test_validate_user_agents() {
+        let mut network = NetworkConfig::default();
+        assert!(network.validate_user_agents().is_ok());
+
+        network.mobile_user_agent = Some("netdisk;11.12.3;android".to_string());
+        assert!(network.validate_user_agents().is_ok());
+
+        network.web_user_agent = Some("   ".to_string());
+        assert!(network.validate_user_agents().is_err());
+
+        network.web_user_agent = Some("Mozilla/5.0\r\nX-Injected: 1".to_string());
+        assert!(network.validate_user_agents().is_err());
+    }
+
+    #[test]
+    fn test_network_timeouts_default() {
+        // 旧配置文件没有超时字段时保持原有超时行为
+        let network: NetworkConfig = toml::from_str("").unwrap();
+        assert_eq!(network.connect_timeout_secs, 0);
+        assert_eq!(network.request_timeout_secs, 60);
+        assert_eq!(network.chunk_timeout_secs, 0);
+    }
+
+    #[test]
+    fn test_validate_completion_webhook() {
+        let mut download = AppConfig::default().download;
+        assert!(download.validate_completion_webhook().is_ok());
+
+        download.completion_webhook_url = Some("http://127.0.0.1:8096/hook".to_string());
+        assert!(download.validate_completion_webhook().is_ok());
+
+        download.completion_webhook_url = Some("ftp://127.0.0.1/hook".to_string());
+        assert!(download.validate_completion_webhook().is_err());
+
+        download.completion_webhook_url = Some("not a url".to_string());
+        assert!(download.validate_completion_webhook().is_err());
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        let mut schedule = ScheduleConfig::default();
+        assert!(schedule.validate().is_ok());
+
+        // 启用时必须配置时间段
+        schedule.enabled = true;
+        assert!(schedule.validate().is_err());
+
+        schedule.windows = vec!["00:00-08:00".to_string()];
+        assert!(schedule.validate().is_ok());
+
+        schedule.windows.push("8点-9点".to_string());
+        assert!(schedule.validate().unwrap_err().contains("schedule.windows[1]"));
+    }
+
+    #[test]
+    fn test_apply_env_overrides_ignores_invalid() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("BPR_SERVER_PORT", "not-a-port");
+        std::env::set_var("BPR_DOWNLOAD_DIR", "relative/dir");
+        std::env::set_var("BPR_MAX_CONCURRENT_TASKS", "0");
+
+        let mut config = AppConfig::default();
+        let applied = config.apply_env_overrides();
+
+        std::env::remove_var("BPR_SERVER_PORT");
+        std::env::remove_var("BPR_DOWNLOAD_DIR");
+        std::env::remove_var("BPR_MAX_CONCURRENT_TASKS");
+
+        assert!(applied.is_empty());
+        let default = AppConfig::default();
+        assert_eq!(config.server.port, default.server.port);
+        assert_eq!(config.download.download_dir, default.download.download_dir);
+        assert_eq!(config.download.max_concurrent_tasks, default.download.max_concurrent_tasks);
+    }
+
+    #[test]
+    fn test_ensure_path_allowed_nested() {
+        let root = tempfile::tempdir().unwrap();
+        let allowed = root.path().join("allowed");
+        let sibling = root.path().join("allowed-other");
+        std::fs::create_dir_all(&allowed).unwrap();
+        std::fs::create_dir_all(&sibling).unwrap();
+
+        let fs_config = FilesystemConfig {
+            allowed_paths: vec![allowed.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+
+        // 白名单内的已存在目录、尚不存在的嵌套目录均允许
+        assert!(fs_config.ensure_path_allowed(&allowed, "下载目录").is_ok());
+        assert!(fs_config.ensure_path_allowed(&allowed.join("a/b/c"), "下载目录").is_ok());
+
+        // 前缀相同但不是子目录的路径应被拒绝，错误信息包含违规的配置项
+        let err = fs_config.ensure_path_allowed(&sibling, "下载目录").unwrap_err();
+        assert!(err.to_string().contains("filesystem.allowed_paths"));
+        assert!(err.to_string().contains("下载目录"));
+
+        // 通过不存在的目录配合 .. 逃逸应被拒绝
+        assert!(fs_config
+            .ensure_path_allowed(&allowed.join("missing/../../allowed-other"), "下载目录")
+            .is_err());
+
+        // 白名单为空时不限制
+        assert!(FilesystemConfig::default().ensure_path_allowed(&sibling, "下载目录").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ensure_path_allowed_symlink_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let allowed = root.path().join("allowed");
+        let outside = root.path().join("outside");
+        std::fs::create_dir_all(&allowed).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        let link = allowed.join("link");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+        let fs_config = FilesystemConfig {
+            allowed_paths: vec![allowed.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+
+        // 白名单内的符号链接指向白名单外，规范化后应被拒绝
+        assert!(fs_config.ensure_path_allowed(&link, "下载目录").is_err());
+        assert!(fs_config.ensure_path_allowed(&link.join("sub"), "下载目录").is_err());
+    }
+
     #[tokio::test]
     async fn test_default_config() {
         let config = AppConfig::default();
@@ -1362,18 +2257,57 @@ mod tests {
         assert_eq!(svip.max_tasks, 5);
     }
 
+    #[test]
+    fn test_recent_directories() {
+        let mut config = AppConfig::default().download;
+        assert!(config.push_recent_directory(PathBuf::from("/a")));
+        assert!(config.push_recent_directory(PathBuf::from("/b")));
+        // 已在最前时不变化
+        assert!(!config.push_recent_directory(PathBuf::from("/b")));
+        // 再次使用时移到最前
+        assert!(config.push_recent_directory(PathBuf::from("/a")));
+        assert_eq!(config.recent_directories, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+
+        for i in 0..20 {
+            config.push_recent_directory(PathBuf::from(format!("/dir{}", i)));
+        }
+        assert_eq!(config.recent_directories.len(), MAX_RECENT_DIRECTORIES);
+        assert_eq!(config.recent_directories[0], PathBuf::from("/dir19"));
+
+        // 旧配置的单值字段自动迁移为列表
+        let toml_str = toml::to_string(&AppConfig::default().download).unwrap();
+        let legacy = toml_str.replace("recent_directories = []", "recent_directory = \"/old\"");
+        assert_ne!(legacy, toml_str);
+        let migrated: DownloadConfig = toml::from_str(&legacy).unwrap();
+        assert_eq!(migrated.recent_directories, vec![PathBuf::from("/old")]);
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = DownloadConfig {
             download_dir: std::env::current_dir().unwrap().join("downloads"),
             default_directory: None,
-            recent_directory: None,
+            recent_directories: Vec::new(),
             ask_each_time: true,
             max_global_threads: 5,
             chunk_size_mb: 10,
             max_concurrent_tasks: 2,
             max_retries: 3,
             cdn_refresh: CdnRefreshConfig::default(),
+            global_speed_limit_kbps: 0,
+            verify_md5_after_download: true,
+            min_free_space_mb: 100,
+            retry: RetryConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+            completion_webhook_url: None,
+            completion_webhook_secret: None,
+            max_chunks_per_task: 0,
+            dedup_local: false,
+            dedup_hardlink: false,
+            max_chunks_per_host: 0,
+            max_stall_secs: 300,
+            type_dirs: BTreeMap::new(),
         };
 
         // 普通用户：5个线程应该触发警告
@@ -1388,6 +2322,13 @@ mod tests {
         config.max_global_threads = 30;
         let result = config.validate_for_vip(VipType::Svip);
         assert!(result.is_err());
+
+        // 单任务分片数覆盖值超过会员等级上限应该触发警告
+        config.max_global_threads = 5;
+        config.max_chunks_per_task = 20;
+        assert!(config.validate_for_vip(VipType::Svip).is_ok());
+        assert!(config.validate_for_vip(VipType::Vip).is_err());
+        assert_eq!(DownloadConfig::max_chunks_ceiling_for_vip(VipType::Normal), 1);
     }
 
     #[test]
@@ -1397,13 +2338,27 @@ mod tests {
         let absolute_config = DownloadConfig {
             download_dir: absolute_path,
             default_directory: None,
-            recent_directory: None,
+            recent_directories: Vec::new(),
             ask_each_time: true,
             max_global_threads: 5,
             chunk_size_mb: 10,
             max_concurrent_tasks: 2,
             max_retries: 3,
             cdn_refresh: CdnRefreshConfig::default(),
+            global_speed_limit_kbps: 0,
+            verify_md5_after_download: true,
+            min_free_space_mb: 100,
+            retry: RetryConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+            completion_webhook_url: None,
+            completion_webhook_secret: None,
+            max_chunks_per_task: 0,
+            dedup_local: false,
+            dedup_hardlink: false,
+            max_chunks_per_host: 0,
+            max_stall_secs: 300,
+            type_dirs: BTreeMap::new(),
         };
         assert!(absolute_config.validate_download_dir().is_ok());
 
@@ -1411,13 +2366,27 @@ mod tests {
         let relative_config = DownloadConfig {
             download_dir: PathBuf::from("downloads"),
             default_directory: None,
-            recent_directory: None,
+            recent_directories: Vec::new(),
             ask_each_time: true,
             max_global_threads: 5,
             chunk_size_mb: 10,
             max_concurrent_tasks: 2,
             max_retries: 3,
             cdn_refresh: CdnRefreshConfig::default(),
+            global_speed_limit_kbps: 0,
+            verify_md5_after_download: true,
+            min_free_space_mb: 100,
+            retry: RetryConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+            completion_webhook_url: None,
+            completion_webhook_secret: None,
+            max_chunks_per_task: 0,
+            dedup_local: false,
+            dedup_hardlink: false,
+            max_chunks_per_host: 0,
+            max_stall_secs: 300,
+            type_dirs: BTreeMap::new(),
         };
         assert!(relative_config.validate_download_dir().is_err());
 
@@ -1427,13 +2396,27 @@ mod tests {
             let windows_config = DownloadConfig {
                 download_dir: PathBuf::from("D:\\Downloads"),
                 default_directory: None,
-                recent_directory: None,
+                recent_directories: Vec::new(),
                 ask_each_time: true,
                 max_global_threads: 5,
                 chunk_size_mb: 10,
                 max_concurrent_tasks: 2,
                 max_retries: 3,
                 cdn_refresh: CdnRefreshConfig::default(),
+                global_speed_limit_kbps: 0,
+                verify_md5_after_download: true,
+                min_free_space_mb: 100,
+                retry: RetryConfig::default(),
+                rate_limit: RateLimitConfig::default(),
+                adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+                completion_webhook_url: None,
+                completion_webhook_secret: None,
+                max_chunks_per_task: 0,
+                dedup_local: false,
+                dedup_hardlink: false,
+                max_chunks_per_host: 0,
+                max_stall_secs: 300,
+                type_dirs: BTreeMap::new(),
             };
             assert!(windows_config.validate_download_dir().is_ok());
         }
@@ -1443,13 +2426,27 @@ mod tests {
             let unix_config = DownloadConfig {
                 download_dir: PathBuf::from("/app/downloads"),
                 default_directory: None,
-                recent_directory: None,
+                recent_directories: Vec::new(),
                 ask_each_time: true,
                 max_global_threads: 5,
                 chunk_size_mb: 10,
                 max_concurrent_tasks: 2,
                 max_retries: 3,
                 cdn_refresh: CdnRefreshConfig::default(),
+                global_speed_limit_kbps: 0,
+                verify_md5_after_download: true,
+                min_free_space_mb: 100,
+                retry: RetryConfig::default(),
+                rate_limit: RateLimitConfig::default(),
+                adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+                completion_webhook_url: None,
+                completion_webhook_secret: None,
+                max_chunks_per_task: 0,
+                dedup_local: false,
+                dedup_hardlink: false,
+                max_chunks_per_host: 0,
+                max_stall_secs: 300,
+                type_dirs: BTreeMap::new(),
             };
             assert!(unix_config.validate_download_dir().is_ok());
         }
@@ -1472,6 +2469,58 @@ mod tests {
         assert_eq!(config.startup_delay_secs, 10);
     }
 
+    #[test]
+    fn test_retry_config_delay_sequence() {
+        // 默认配置：与原固定参数一致（100ms 起，5000ms 封顶）
+        let config = RetryConfig::default();
+        let delays: Vec<u64> = (1..=7).map(|n| config.delay_ms(n)).collect();
+        assert_eq!(delays, vec![200, 400, 800, 1600, 3200, 5000, 5000]);
+
+        let config = RetryConfig {
+            base_delay_ms: 50,
+            max_delay_ms: 300,
+            max_attempts: 5,
+            jitter: false,
+        };
+        let delays: Vec<u64> = (0..5).map(|n| config.delay_ms(n)).collect();
+        assert_eq!(delays, vec![50, 100, 200, 300, 300]);
+
+        // 重试次数过大时不溢出
+        assert_eq!(config.delay_ms(200), 300);
+    }
+
+    #[test]
+    fn test_retry_config_jitter_range() {
+        let config = RetryConfig {
+            jitter: true,
+            ..Default::default()
+        };
+        for n in 1..=7 {
+            let expected = RetryConfig::default().delay_ms(n);
+            for _ in 0..20 {
+                let delay = config.delay_ms(n);
+                assert!(delay >= expected / 2 && delay <= expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_config_max_attempts_fallback() {
+        let mut download_config = AppConfig::default().download;
+        download_config.max_retries = 5;
+        assert_eq!(download_config.chunk_retry_config().max_attempts, 5);
+
+        download_config.retry.max_attempts = 2;
+        assert_eq!(download_config.chunk_retry_config().max_attempts, 2);
+
+        // 旧配置文件没有 [download.retry] 时使用默认值
+        let retry: RetryConfig = toml::from_str("").unwrap();
+        assert_eq!(retry.base_delay_ms, 100);
+        assert_eq!(retry.max_delay_ms, 5000);
+        assert_eq!(retry.max_attempts, 0);
+        assert!(!retry.jitter);
+    }
+
     #[test]
     fn test_cdn_refresh_config_to_speed_anomaly() {
         let config = CdnRefreshConfig {
@@ -1560,7 +2609,7 @@ mod tests {
         let download_config = DownloadConfig {
             download_dir: std::env::current_dir().unwrap().join("downloads"),
             default_directory: None,
-            recent_directory: None,
+            recent_directories: Vec::new(),
             ask_each_time: true,
             max_global_threads: 10,
             chunk_size_mb: 5,
@@ -1571,6 +2620,20 @@ mod tests {
                 refresh_interval_minutes: 15,
                 ..Default::default()
             },
+            global_speed_limit_kbps: 0,
+            verify_md5_after_download: true,
+            min_free_space_mb: 100,
+            retry: RetryConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+            completion_webhook_url: None,
+            completion_webhook_secret: None,
+            max_chunks_per_task: 0,
+            dedup_local: false,
+            dedup_hardlink: false,
+            max_chunks_per_host: 0,
+            max_stall_secs: 300,
+            type_dirs: BTreeMap::new(),
         };
 
         // 验证 cdn_refresh 配置被正确包含
@@ -1853,4 +2916,34 @@ ask_each_time = true
         };
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_match_type_dir() {
+        let type_dirs = BTreeMap::from([
+            ("video".to_string(), PathBuf::from("/data/videos")),
+            ("document".to_string(), PathBuf::from("/data/docs")),
+            (".MKV".to_string(), PathBuf::from("/data/mkv")),
+        ]);
+
+        assert_eq!(match_type_dir(&type_dirs, "movie.mp4"), Some(&PathBuf::from("/data/videos")));
+        assert_eq!(match_type_dir(&type_dirs, "Report.PDF"), Some(&PathBuf::from("/data/docs")));
+        // 扩展名优先于分类
+        assert_eq!(match_type_dir(&type_dirs, "movie.mkv"), Some(&PathBuf::from("/data/mkv")));
+        // 未匹配或没有扩展名时回退到默认下载目录
+        assert_eq!(match_type_dir(&type_dirs, "song.mp3"), None);
+        assert_eq!(match_type_dir(&type_dirs, "README"), None);
+    }
+
+    #[test]
+    fn test_validate_type_dirs() {
+        let mut config = AppConfig::default().download;
+        assert!(config.validate_type_dirs().is_ok());
+
+        config.type_dirs.insert("video".to_string(), PathBuf::from("relative/videos"));
+        assert!(config.validate_type_dirs().is_err());
+
+        config.type_dirs.clear();
+        config.type_dirs.insert(".".to_string(), std::env::temp_dir());
+        assert!(config.validate_type_dirs().is_err());
+    }
 }
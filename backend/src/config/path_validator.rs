@@ -191,6 +191,55 @@ impl PathValidator {
         Ok(())
     }
 
+    /// 查询路径所在卷的可用空间（字节）
+    ///
+    /// 路径可以尚不存在，使用其最近的已存在祖先目录定位挂载点；无法确定时返回 None
+    pub fn available_space(path: &Path) -> Option<u64> {
+        let existing = path.ancestors().find(|p| p.exists())?;
+        // 使用 dunce 避免 Windows 下 `\\?\` 前缀导致无法匹配盘符挂载点
+        let canonical = dunce::canonicalize(existing).ok()?;
+
+        // 选择挂载点最长匹配的磁盘（与 MountDetector::find_mount_point_for_path 一致）
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        disks
+            .list()
+            .iter()
+            .filter(|disk| canonical.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| disk.available_space())
+    }
+
+    /// 检查路径所在卷是否有足够空间写入 `required` 字节，并额外保留 `reserve` 字节
+    ///
+    /// 无法获取可用空间时跳过检查（返回 Ok）
+    pub fn ensure_free_space(path: &Path, required: u64, reserve: u64) -> Result<()> {
+        match Self::available_space(path) {
+            Some(available) => Self::check_free_space(available, required, reserve),
+            None => {
+                tracing::debug!("无法获取 {:?} 所在卷的可用空间，跳过磁盘空间检查", path);
+                Ok(())
+            }
+        }
+    }
+
+    /// 比较可用空间与所需空间，不足时返回说明差额的错误
+    fn check_free_space(available: u64, required: u64, reserve: u64) -> Result<()> {
+        use crate::common::MemoryMonitor;
+
+        let needed = required.saturating_add(reserve);
+        if available < needed {
+            anyhow::bail!(
+                "磁盘空间不足: 需要 {}（待下载 {} + 预留 {}），可用 {}，还差 {}",
+                MemoryMonitor::format_bytes(needed),
+                MemoryMonitor::format_bytes(required),
+                MemoryMonitor::format_bytes(reserve),
+                MemoryMonitor::format_bytes(available),
+                MemoryMonitor::format_bytes(needed - available)
+            );
+        }
+        Ok(())
+    }
+
     /// 自动创建目录（如果不存在）
     ///
     /// # 参数
@@ -294,4 +343,29 @@ mod tests {
         let result = PathValidator::validate_or_error(invalid_path);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_check_free_space() {
+        const MB: u64 = 1024 * 1024;
+
+        assert!(PathValidator::check_free_space(500 * MB, 300 * MB, 100 * MB).is_ok());
+        assert!(PathValidator::check_free_space(400 * MB, 300 * MB, 100 * MB).is_ok());
+
+        // 空间不足时错误信息说明差额
+        let err = PathValidator::check_free_space(350 * MB, 300 * MB, 100 * MB).unwrap_err();
+        assert!(err.to_string().contains("磁盘空间不足"));
+        assert!(err.to_string().contains("还差 50.00 MB"));
+    }
+
+    #[test]
+    fn test_available_space_for_missing_subdir() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("not/created/yet");
+
+        // 尚不存在的路径按最近的已存在祖先目录所在卷计算
+        assert_eq!(
+            PathValidator::available_space(&missing).is_some(),
+            PathValidator::available_space(temp_dir.path()).is_some()
+        );
+    }
 }
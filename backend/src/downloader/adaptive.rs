@@ -0,0 +1,295 @@
+//! 自适应并发控制（AIMD）
+//!
+//! 固定线程数很难兼顾不同网络环境：线程太少跑不满带宽，太多则徒增限流风险。
+//! 开启后调度器每隔 `interval_secs` 汇总所有任务的 SpeedCalculator 速度并评估一次：
+//! - 加性增：线程已跑满且吞吐量比上一档提升超过 5% 时，线程数加 1 继续试探
+//! - 回退：加线程后吞吐量不再提升，回到上一档并保持若干轮后再试探
+//! - 乘性减：评估周期内分片失败次数激增时线程数减半
+//!
+//! 实际线程数始终在 `[min_threads, max_threads]` 范围内且不超过 `max_global_threads`
+
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use tracing::info;
+
+use crate::config::AdaptiveConcurrencyConfig;
+
+/// 吞吐量提升超过该比例才视为有效提升
+const IMPROVEMENT_RATIO: f64 = 0.05;
+/// 单个评估周期内分片失败达到该次数视为错误激增
+const ERROR_SPIKE_THRESHOLD: u32 = 3;
+/// 吞吐量到顶回退后保持的评估轮数（之后重新试探）
+const HOLD_ROUNDS: u32 = 6;
+
+/// 上一轮的调整动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// 刚加过线程，本轮检验吞吐量是否提升
+    Probing,
+    /// 保持当前线程数，剩余轮数归零后重新试探
+    Holding(u32),
+}
+
+/// 控制器状态
+#[derive(Debug)]
+struct AdaptiveState {
+    /// 当前线程数上限（0 表示尚未初始化）
+    limit: usize,
+    /// 上一档线程数下的吞吐量（字节/秒）
+    baseline: u64,
+    phase: Phase,
+    /// 本评估周期内的分片失败次数
+    errors: u32,
+    /// 上次评估时间
+    last_evaluated: Option<Instant>,
+}
+
+impl AdaptiveState {
+    fn new() -> Self {
+        Self {
+            limit: 0,
+            baseline: 0,
+            phase: Phase::Holding(0),
+            errors: 0,
+            last_evaluated: None,
+        }
+    }
+}
+
+/// 全局自适应并发控制器（调度器持有）
+#[derive(Debug)]
+pub struct AdaptiveConcurrency {
+    /// 配置（动态可调整）
+    config: RwLock<AdaptiveConcurrencyConfig>,
+    state: Mutex<AdaptiveState>,
+}
+
+impl Default for AdaptiveConcurrency {
+    fn default() -> Self {
+        Self::new(AdaptiveConcurrencyConfig::default())
+    }
+}
+
+impl AdaptiveConcurrency {
+    /// 创建控制器
+    pub fn new(config: AdaptiveConcurrencyConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(AdaptiveState::new()),
+        }
+    }
+
+    /// 动态更新配置（配置变化时重置状态，下一轮从下限重新试探）
+    pub fn update_config(&self, config: AdaptiveConcurrencyConfig) {
+        let mut current = self.config.write();
+        if *current != config {
+            *self.state.lock() = AdaptiveState::new();
+            *current = config;
+        }
+    }
+
+    /// 是否启用
+    pub fn is_enabled(&self) -> bool {
+        self.config.read().enabled
+    }
+
+    /// 线程数范围（下限不超过上限）
+    fn bounds(config: &AdaptiveConcurrencyConfig, max_threads: usize) -> (usize, usize) {
+        let upper = match config.max_threads {
+            0 => max_threads,
+            n => n.min(max_threads),
+        }
+        .max(1);
+        (config.min_threads.clamp(1, upper), upper)
+    }
+
+    /// 实际生效的全局最大线程数（未启用时原样返回）
+    pub fn effective_max_threads(&self, max_threads: usize) -> usize {
+        let config = self.config.read().clone();
+        if !config.enabled {
+            return max_threads;
+        }
+
+        let (lower, upper) = Self::bounds(&config, max_threads);
+        match self.state.lock().limit {
+            0 => lower,
+            limit => limit.clamp(lower, upper),
+        }
+    }
+
+    /// 记录一次分片失败
+    pub fn record_error(&self) {
+        if self.is_enabled() {
+            self.state.lock().errors += 1;
+        }
+    }
+
+    /// 是否到了下一次评估时间
+    pub fn is_due(&self) -> bool {
+        let config = self.config.read();
+        if !config.enabled {
+            return false;
+        }
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        self.state
+            .lock()
+            .last_evaluated
+            .is_none_or(|last| last.elapsed() >= interval)
+    }
+
+    /// 按本周期的吞吐量评估一次，返回新的线程数上限
+    ///
+    /// # 参数
+    /// * `throughput` - 所有任务的总速度（字节/秒）
+    /// * `active_chunks` - 当前活跃分片数（未跑满时吞吐量不代表当前上限的能力，不做调整）
+    /// * `max_threads` - 配置的全局最大线程数
+    pub fn evaluate(&self, throughput: u64, active_chunks: usize, max_threads: usize) -> usize {
+        let config = self.config.read().clone();
+        if !config.enabled {
+            return max_threads;
+        }
+
+        let (lower, upper) = Self::bounds(&config, max_threads);
+        let mut state = self.state.lock();
+        state.last_evaluated = Some(Instant::now());
+        let errors = std::mem::take(&mut state.errors);
+
+        if state.limit == 0 {
+            state.limit = lower;
+            state.baseline = throughput;
+            state.phase = Phase::Holding(0);
+            return state.limit;
+        }
+        let old_limit = state.limit.clamp(lower, upper);
+        state.limit = old_limit;
+
+        // 乘性减：错误激增时立即减半
+        if errors >= ERROR_SPIKE_THRESHOLD {
+            state.limit = (old_limit / 2).max(lower);
+            state.baseline = throughput;
+            state.phase = Phase::Holding(HOLD_ROUNDS);
+            if state.limit != old_limit {
+                info!(
+                    "📉 自适应并发: 本周期 {} 个分片失败，线程数 {} -> {}",
+                    errors, old_limit, state.limit
+                );
+            }
+            return state.limit;
+        }
+
+        // 线程未跑满，吞吐量反映不了当前上限，保持不变
+        if active_chunks < old_limit {
+            return old_limit;
+        }
+
+        let improved = throughput as f64 > state.baseline as f64 * (1.0 + IMPROVEMENT_RATIO);
+        match state.phase {
+            Phase::Probing if improved => {
+                state.baseline = throughput;
+                state.limit = (old_limit + 1).min(upper);
+            }
+            Phase::Probing => {
+                // 加线程没有带来提升，回到上一档
+                state.limit = old_limit.saturating_sub(1).max(lower);
+                state.phase = Phase::Holding(HOLD_ROUNDS);
+                info!(
+                    "⏸️ 自适应并发: 吞吐量不再提升（{} 线程），回退到 {}",
+                    old_limit, state.limit
+                );
+            }
+            Phase::Holding(rounds) if rounds > 0 => {
+                state.baseline = throughput;
+                state.phase = Phase::Holding(rounds - 1);
+            }
+            Phase::Holding(_) => {
+                // 保持结束，加性增试探
+                state.baseline = throughput;
+                state.limit = (old_limit + 1).min(upper);
+                state.phase = Phase::Probing;
+            }
+        }
+        state.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> AdaptiveConcurrencyConfig {
+        AdaptiveConcurrencyConfig {
+            enabled: true,
+            min_threads: 2,
+            max_threads: 32,
+            interval_secs: 5,
+        }
+    }
+
+    /// 模拟带宽曲线：每个线程 1MB/s，8 个线程后带宽跑满，再多线程反而因争抢略有下降
+    fn simulated_throughput(threads: usize) -> u64 {
+        const PER_THREAD: u64 = 1024 * 1024;
+        const KNEE: usize = 8;
+        if threads <= KNEE {
+            threads as u64 * PER_THREAD
+        } else {
+            KNEE as u64 * PER_THREAD - (threads - KNEE) as u64 * PER_THREAD / 20
+        }
+    }
+
+    #[test]
+    fn test_converges_to_bandwidth_knee() {
+        let controller = AdaptiveConcurrency::new(test_config());
+        assert_eq!(controller.effective_max_threads(20), 2);
+
+        let mut limit = controller.effective_max_threads(20);
+        let mut history = Vec::new();
+        for _ in 0..120 {
+            limit = controller.evaluate(simulated_throughput(limit), limit, 20);
+            history.push(limit);
+        }
+
+        // 收敛后只在拐点附近试探，不会越过拐点太多
+        assert!(history[60..].iter().all(|&l| (8..=9).contains(&l)), "{:?}", history);
+        assert!(history.iter().all(|&l| (2..=20).contains(&l)));
+        assert_eq!(controller.effective_max_threads(20), limit);
+    }
+
+    #[test]
+    fn test_backs_off_on_error_spike() {
+        let controller = AdaptiveConcurrency::new(test_config());
+        let mut limit = controller.effective_max_threads(20);
+        for _ in 0..10 {
+            limit = controller.evaluate(simulated_throughput(limit), limit, 20);
+        }
+        assert!(limit > 4);
+
+        for _ in 0..ERROR_SPIKE_THRESHOLD {
+            controller.record_error();
+        }
+        let backed_off = controller.evaluate(simulated_throughput(limit), limit, 20);
+        assert_eq!(backed_off, (limit / 2).max(2));
+    }
+
+    #[test]
+    fn test_respects_bounds_and_disabled() {
+        let controller = AdaptiveConcurrency::new(AdaptiveConcurrencyConfig {
+            max_threads: 4,
+            ..test_config()
+        });
+        let mut limit = controller.effective_max_threads(20);
+        for _ in 0..30 {
+            // 带宽无限时线程数也不超过上限
+            limit = controller.evaluate(limit as u64 * 1024 * 1024, limit, 20);
+        }
+        assert_eq!(limit, 4);
+
+        // 线程未跑满时不调整
+        assert_eq!(controller.evaluate(0, 1, 20), 4);
+
+        controller.update_config(AdaptiveConcurrencyConfig::default());
+        assert!(!controller.is_due());
+        assert_eq!(controller.effective_max_threads(20), 20);
+        assert_eq!(controller.evaluate(0, 0, 20), 20);
+    }
+}
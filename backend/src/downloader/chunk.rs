@@ -9,9 +9,14 @@ use tokio::{
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
+use super::error::HttpStatusError;
+
 /// 默认分片大小: 5MB
 pub const DEFAULT_CHUNK_SIZE: u64 = 5 * 1024 * 1024;
 
+/// 数据流空闲判定阈值：读取超时窗口内累计到达不足该字节数视为连接停滞
+const BODY_IDLE_MIN_BYTES: u64 = 16 * 1024;
+
 /// 分片信息
 #[derive(Debug, Clone)]
 pub struct Chunk {
@@ -55,8 +60,10 @@ impl Chunk {
     ///
     /// # 参数
     /// * `referer` - Referer 头（如果存在），用于 Range 请求避免 403 Forbidden
-    /// * `progress_callback` - 进度回调函数，参数为新下载的字节数
-    /// * `read_timeout_secs` - 流式读取超时（秒），防止CDN连接挂起
+    /// * `timeout_secs` - 等待响应头的超时（秒）
+    /// * `progress_callback` - 进度回调函数，参数为新下载的字节数（限速时在回调中等待）
+    /// * `read_timeout_secs` - 数据流空闲超时（秒）：累计等待数据的时间超过该值且期间到达不足
+    ///   `BODY_IDLE_MIN_BYTES` 时失败，防止 CDN 连接挂起或以极低速度持续滴流
     pub async fn download<F>(
         &mut self,
         client: &Client,
@@ -121,10 +128,11 @@ impl Chunk {
             request = request.header("Referer", referer_val);
         }
 
-        let resp = request
-            .timeout(std::time::Duration::from_secs(timeout_secs))
-            .send()
+        // 🔥 超时只覆盖到收到响应头为止：数据传输阶段由逐次读取的空闲超时把关，
+        // 限速等待发生在两次读取之间，不会被计入超时导致分片被误判失败
+        let resp = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), request.send())
             .await
+            .map_err(|_| anyhow::anyhow!("等待响应超时: {}秒内未收到响应头", timeout_secs))?
             .context("发送HTTP请求失败")?;
 
         // 🔥 严格校验 Range 响应
@@ -139,7 +147,7 @@ impl Chunk {
                     self.range.end - 1
                 );
             }
-            anyhow::bail!("HTTP错误: {}", status);
+            return Err(HttpStatusError(status.as_u16()).into());
         }
 
         // 🔥 校验 Content-Range 头（防止 CDN 返回错误的字节范围）
@@ -184,8 +192,13 @@ impl Chunk {
         // 使用动态值（由 engine 根据链接速度计算），慢链接获得更长超时
 
         let read_timeout_dur = std::time::Duration::from_secs(read_timeout_secs);
+        // 🔥 空闲计时：只累计等待 stream.next() 的时间（限速等待不计入），
+        // 到达足够数据后重置，零星字节无法让停滞的连接一直存活
+        let mut idle_waited = std::time::Duration::ZERO;
+        let mut idle_bytes = 0u64;
 
         loop {
+            let wait_started = std::time::Instant::now();
             // 🔥 tokio::select! 同时等待三个信号：
             //   1. cancellation_token.cancelled()  — pause/cancel 立即中断
             //   2. tokio::time::sleep(read_timeout) — 读取超时
@@ -206,7 +219,7 @@ impl Chunk {
                     anyhow::bail!("分片 #{} 下载被取消", self.index);
                 }
 
-                result = tokio::time::timeout(read_timeout_dur, stream.next()) => {
+                result = tokio::time::timeout(read_timeout_dur.saturating_sub(idle_waited), stream.next()) => {
                     match result {
                         Ok(Some(Ok(data))) => data,
                         Ok(Some(Err(e))) => {
@@ -229,12 +242,13 @@ impl Chunk {
                             }
                             self.bytes_downloaded += total_bytes_downloaded;
                             warn!(
-                                "[分片线程{}] 分片 #{} 读取超时({}秒无数据)，本次已下载 {} bytes，累计 {} bytes",
-                                chunk_thread_id, self.index, read_timeout_secs, total_bytes_downloaded, self.bytes_downloaded
+                                "[分片线程{}] 分片 #{} 读取超时({}秒内到达数据不足 {} bytes)，本次已下载 {} bytes，累计 {} bytes",
+                                chunk_thread_id, self.index, read_timeout_secs, BODY_IDLE_MIN_BYTES, total_bytes_downloaded, self.bytes_downloaded
                             );
                             anyhow::bail!(
-                                "读取数据流超时: {}秒内无数据到达",
-                                read_timeout_secs
+                                "读取数据流超时: {}秒内到达数据不足 {} bytes",
+                                read_timeout_secs,
+                                BODY_IDLE_MIN_BYTES
                             );
                         }
                     }
@@ -242,6 +256,13 @@ impl Chunk {
             };
             let chunk_len = chunk_data.len() as u64;
 
+            idle_waited += wait_started.elapsed();
+            idle_bytes += chunk_len;
+            if idle_bytes >= BODY_IDLE_MIN_BYTES {
+                idle_waited = std::time::Duration::ZERO;
+                idle_bytes = 0;
+            }
+
             // 🔥 溢出保护：防止写入超过分片边界
             if total_bytes_downloaded + chunk_len > remaining {
                 let safe_len = (remaining - total_bytes_downloaded) as usize;
@@ -540,4 +561,100 @@ mod tests {
         assert_eq!(manager.completed_count(), 0);
         assert!(!manager.is_completed());
     }
+
+    #[tokio::test]
+    async fn test_trickling_body_times_out() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // 本地 CDN：返回 100 字节的 206 响应，但每 200ms 只发送 1 字节
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 206 Partial Content\r\nContent-Length: 100\r\nContent-Range: bytes 0-99/100\r\n\r\n")
+                .await;
+            for _ in 0..100 {
+                if socket.write_all(b"x").await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_path = temp_dir.path().join("chunk.bin");
+        std::fs::write(&output_path, [0u8; 100]).unwrap();
+
+        let mut chunk = Chunk::new(0, 0..100);
+        let started = std::time::Instant::now();
+        let result = chunk
+            .download(
+                &Client::new(),
+                "BDUSS=test",
+                None,
+                &format!("http://{}/file", addr),
+                &output_path,
+                30,
+                0,
+                1,
+                &CancellationToken::new(),
+                |_| {},
+            )
+            .await;
+
+        // 每次读取都在读取超时内到达，但累计数据不足，应按空闲超时失败而不是等满 20 秒
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("读取数据流超时"), "{}", err);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(!chunk.completed);
+        assert!(chunk.bytes_downloaded > 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_throttle_wait_not_counted_as_timeout() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // 本地 CDN：返回 8 字节的 206 响应
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 206 Partial Content\r\nContent-Length: 8\r\nContent-Range: bytes 0-7/8\r\n\r\n01234567")
+                .await;
+            // 保持连接，直到客户端读完
+            let _ = socket.read(&mut buf).await;
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_path = temp_dir.path().join("chunk.bin");
+        std::fs::write(&output_path, [0u8; 8]).unwrap();
+
+        // 进度回调中的限速等待超过请求超时，分片仍应成功
+        let mut chunk = Chunk::new(0, 0..8);
+        let downloaded = chunk
+            .download(
+                &Client::new(),
+                "BDUSS=test",
+                None,
+                &format!("http://{}/file", addr),
+                &output_path,
+                1,
+                0,
+                30,
+                &CancellationToken::new(),
+                |_| std::thread::sleep(std::time::Duration::from_millis(1500)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(downloaded, 8);
+        assert!(chunk.completed);
+        assert_eq!(std::fs::read(&output_path).unwrap(), b"01234567");
+    }
 }
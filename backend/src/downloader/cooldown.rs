@@ -0,0 +1,214 @@
+//! 限流检测与全局冷却
+//!
+//! 线程数过高时百度会返回频控 errno（31034 等）或 HTTP 429，继续请求可能导致账号被限速数小时。
+//! 统计窗口内命中限流的次数达到阈值后进入全局冷却：
+//! - 调度器把全局分片线程数降到 `cooldown_max_threads`
+//! - DownloadManager 暂停从等待队列启动新任务
+//!
+//! 冷却到期后自动恢复，无需额外操作
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::config::RateLimitConfig;
+use crate::downloader::DownloadErrorKind;
+
+/// 冷却状态
+#[derive(Debug, Default)]
+struct CooldownState {
+    /// 统计窗口内的限流命中时间
+    hits: VecDeque<Instant>,
+    /// 冷却截止时间
+    cooldown_until: Option<Instant>,
+}
+
+/// 全局限流冷却器（调度器、下载引擎、DownloadManager 共享）
+#[derive(Debug)]
+pub struct RateLimitCooldown {
+    /// 冷却配置（动态可调整）
+    config: RwLock<RateLimitConfig>,
+    /// 冷却状态
+    state: Mutex<CooldownState>,
+    /// 冷却触发通知发送器（发送冷却秒数，由 DownloadManager 推送 WebSocket 消息）
+    cooldown_tx: RwLock<Option<mpsc::UnboundedSender<u64>>>,
+}
+
+impl Default for RateLimitCooldown {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+impl RateLimitCooldown {
+    /// 创建冷却器
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            state: Mutex::new(CooldownState::default()),
+            cooldown_tx: RwLock::new(None),
+        }
+    }
+
+    /// 设置冷却触发通知发送器
+    pub fn set_cooldown_sender(&self, tx: mpsc::UnboundedSender<u64>) {
+        *self.cooldown_tx.write() = Some(tx);
+    }
+
+    /// 动态更新冷却配置（关闭时立即结束当前冷却）
+    pub fn update_config(&self, config: RateLimitConfig) {
+        if !config.enabled {
+            let mut state = self.state.lock();
+            state.hits.clear();
+            if state.cooldown_until.take().is_some() {
+                info!("🔧 限流冷却已关闭，立即恢复正常调度");
+            }
+        }
+        *self.config.write() = config;
+    }
+
+    /// 记录一次下载错误，只有限流错误计入统计窗口
+    pub fn record_error(&self, error: &anyhow::Error) {
+        if DownloadErrorKind::classify(error) != DownloadErrorKind::RateLimited {
+            return;
+        }
+
+        if let Some(cooldown_secs) = self.record_hit_at(Instant::now()) {
+            warn!(
+                "⚠️ 短时间内多次触发百度限流，进入全局冷却 {} 秒（暂停启动新任务并降低并发）",
+                cooldown_secs
+            );
+            if let Some(tx) = self.cooldown_tx.read().as_ref() {
+                let _ = tx.send(cooldown_secs);
+            }
+        }
+    }
+
+    /// 记录一次限流命中，返回 Some(冷却秒数) 表示本次命中触发了冷却
+    ///
+    /// 冷却期间的命中不再计数，避免冷却结束后立即再次触发
+    fn record_hit_at(&self, now: Instant) -> Option<u64> {
+        let config = self.config.read().clone();
+        if !config.enabled || config.threshold == 0 {
+            return None;
+        }
+
+        let mut state = self.state.lock();
+        if state.cooldown_until.is_some_and(|until| now < until) {
+            return None;
+        }
+
+        let window = Duration::from_secs(config.window_secs);
+        while state
+            .hits
+            .front()
+            .is_some_and(|&hit| now.duration_since(hit) > window)
+        {
+            state.hits.pop_front();
+        }
+        state.hits.push_back(now);
+
+        if state.hits.len() < config.threshold as usize {
+            return None;
+        }
+
+        state.hits.clear();
+        state.cooldown_until = Some(now + Duration::from_secs(config.cooldown_secs));
+        Some(config.cooldown_secs)
+    }
+
+    /// 冷却剩余时间
+    fn remaining_at(&self, now: Instant) -> Option<Duration> {
+        self.state
+            .lock()
+            .cooldown_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// 冷却剩余时间（不在冷却期时为 None）
+    pub fn remaining(&self) -> Option<Duration> {
+        self.remaining_at(Instant::now())
+    }
+
+    /// 是否处于冷却期
+    pub fn is_cooling_down(&self) -> bool {
+        self.remaining_at(Instant::now()).is_some()
+    }
+
+    /// 冷却期间实际生效的全局最大线程数
+    pub fn effective_max_threads(&self, max_threads: usize) -> usize {
+        if self.is_cooling_down() {
+            max_threads.min(self.config.read().cooldown_max_threads.max(1))
+        } else {
+            max_threads
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::HttpStatusError;
+
+    fn test_config() -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            window_secs: 10,
+            threshold: 3,
+            cooldown_secs: 60,
+            cooldown_max_threads: 2,
+        }
+    }
+
+    #[test]
+    fn test_cooldown_triggers_on_burst() {
+        let cooldown = RateLimitCooldown::new(test_config());
+        let start = Instant::now();
+
+        // 窗口外的命中不计数
+        assert_eq!(cooldown.record_hit_at(start), None);
+        assert_eq!(cooldown.record_hit_at(start + Duration::from_secs(11)), None);
+        assert_eq!(cooldown.record_hit_at(start + Duration::from_secs(12)), None);
+        assert_eq!(cooldown.record_hit_at(start + Duration::from_secs(13)), Some(60));
+
+        // 冷却期间不重复触发，到期后自动恢复
+        let triggered = start + Duration::from_secs(13);
+        assert_eq!(cooldown.record_hit_at(triggered + Duration::from_secs(1)), None);
+        assert!(cooldown.remaining_at(triggered + Duration::from_secs(59)).is_some());
+        assert!(cooldown.remaining_at(triggered + Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn test_record_error_and_effective_threads() {
+        let cooldown = RateLimitCooldown::new(test_config());
+        assert_eq!(cooldown.effective_max_threads(10), 10);
+
+        // 非限流错误不计数
+        for _ in 0..5 {
+            cooldown.record_error(&anyhow::Error::new(HttpStatusError(503)));
+        }
+        assert!(!cooldown.is_cooling_down());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        cooldown.set_cooldown_sender(tx);
+        for _ in 0..3 {
+            cooldown.record_error(&anyhow::Error::new(HttpStatusError(429)));
+        }
+        assert!(cooldown.is_cooling_down());
+        assert_eq!(rx.try_recv().ok(), Some(60));
+        assert_eq!(cooldown.effective_max_threads(10), 2);
+        assert_eq!(cooldown.effective_max_threads(1), 1);
+
+        // 关闭后立即恢复
+        cooldown.update_config(RateLimitConfig {
+            enabled: false,
+            ..test_config()
+        });
+        assert!(!cooldown.is_cooling_down());
+        assert_eq!(cooldown.effective_max_threads(10), 10);
+    }
+}
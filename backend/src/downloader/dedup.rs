@@ -0,0 +1,418 @@
+//! 本地 MD5 去重索引
+//!
+//! 开启 `download.dedup_local` 后，下载完成的文件按网盘 MD5 记录到索引中。之后创建的任务
+//! 如果 MD5 命中、且本地文件仍然存在且大小、修改时间一致，直接复制到目标路径并标记完成，
+//! 不再重复下载：
+//! - 额外开启 `download.dedup_hardlink` 时改用硬链接（跨分区时仍复制）
+//! - 索引保存在 WAL 目录下的 `dedup_index.json`，重启后继续生效；修改只标记为脏，
+//!   延迟 `FLUSH_DEBOUNCE` 后在 `spawn_blocking` 中合并写盘，关闭前调用 `flush` 落盘
+//! - 最多保留 `MAX_DEDUP_ENTRIES` 条，超出时淘汰最早记录的条目
+//! - 查询时发现文件已删除、大小或修改时间变化的条目会被移除
+//! - 加密文件下载后会被解密，本地内容与网盘 MD5 不对应，不会记录
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+/// 索引最大条目数
+pub const MAX_DEDUP_ENTRIES: usize = 10_000;
+/// 索引文件名（位于 WAL 目录下）
+const DEDUP_INDEX_FILE: &str = "dedup_index.json";
+/// 索引修改后延迟写盘的时间，期间的多次修改合并为一次写入
+const FLUSH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// 索引条目：某个 MD5 对应的本地文件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DedupEntry {
+    /// 本地文件路径
+    pub path: PathBuf,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 记录时文件的修改时间（Unix 时间戳，毫秒），旧版本索引没有此字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<i64>,
+    /// 记录时间（Unix 时间戳，秒）
+    pub recorded_at: i64,
+}
+
+/// 本地 MD5 去重索引（动态可开关，未开启时不记录也不命中）
+#[derive(Debug, Default)]
+pub struct LocalDedupIndex {
+    enabled: AtomicBool,
+    /// 命中时是否使用硬链接（默认复制）
+    hardlink: AtomicBool,
+    /// 索引内容（与延迟写盘任务共享）
+    store: Arc<DedupStore>,
+}
+
+/// 索引内容及写盘状态
+#[derive(Debug, Default)]
+struct DedupStore {
+    /// MD5（小写）→ 本地文件
+    entries: Mutex<HashMap<String, DedupEntry>>,
+    /// 索引文件路径，未设置时只保存在内存中
+    index_path: RwLock<Option<PathBuf>>,
+    /// 有尚未写盘的修改
+    dirty: AtomicBool,
+    /// 已安排延迟写盘任务
+    flush_scheduled: AtomicBool,
+}
+
+impl DedupStore {
+    /// 有未写盘的修改时写入索引文件
+    ///
+    /// 只在锁内复制一份快照，序列化和写文件都在锁外进行
+    fn flush(&self) {
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        let Some(path) = self.index_path.read().clone() else {
+            return;
+        };
+        let snapshot = self.entries.lock().clone();
+        match serde_json::to_string(&snapshot) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(&path, json) {
+                    warn!("写入本地去重索引失败: {:?}, 错误: {}", path, e);
+                }
+            }
+            Err(e) => warn!("序列化本地去重索引失败: {}", e),
+        }
+    }
+}
+
+impl LocalDedupIndex {
+    /// 创建去重索引（默认未启用）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// 开启/关闭本地去重（关闭时保留已有索引，重新开启后继续使用）
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn allows_hardlink(&self) -> bool {
+        self.hardlink.load(Ordering::SeqCst)
+    }
+
+    /// 开启/关闭硬链接复用
+    pub fn set_hardlink(&self, hardlink: bool) {
+        self.hardlink.store(hardlink, Ordering::SeqCst);
+    }
+
+    /// 当前索引条目数
+    pub fn len(&self) -> usize {
+        self.store.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.entries.lock().is_empty()
+    }
+
+    /// 从目录加载索引文件，之后的修改会写回该文件
+    pub fn load(&self, dir: &Path) {
+        let path = dir.join(DEDUP_INDEX_FILE);
+        let loaded: HashMap<String, DedupEntry> = match std::fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+                warn!("解析本地去重索引失败，重新建立: {:?}, 错误: {}", path, e);
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!("读取本地去重索引失败: {:?}, 错误: {}", path, e);
+                HashMap::new()
+            }
+        };
+
+        info!("已加载本地去重索引: {} 条", loaded.len());
+        *self.store.entries.lock() = loaded;
+        *self.store.index_path.write() = Some(path);
+    }
+
+    /// 记录已下载完成的文件（未启用或 MD5 无法识别时忽略）
+    pub fn record(&self, md5: &str, path: &Path, size: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(md5) = normalize_md5(md5) else {
+            return;
+        };
+        let Some(mtime) = file_mtime(path) else {
+            return;
+        };
+
+        let mut entries = self.store.entries.lock();
+        entries.insert(
+            md5.clone(),
+            DedupEntry {
+                path: path.to_path_buf(),
+                size,
+                mtime: Some(mtime),
+                recorded_at: chrono::Utc::now().timestamp(),
+            },
+        );
+        Self::evict_oldest(&mut entries);
+        drop(entries);
+        debug!("本地去重索引已记录: md5={}, path={:?}", md5, path);
+        self.mark_dirty();
+    }
+
+    /// 查找 MD5 和大小都一致的本地文件
+    ///
+    /// 文件已不存在、大小或修改时间变化（或条目没有记录修改时间）时移除该条目并返回 None
+    pub fn lookup(&self, md5: &str, size: u64) -> Option<PathBuf> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let md5 = normalize_md5(md5)?;
+
+        let mut entries = self.store.entries.lock();
+        let entry = entries.get(&md5)?;
+        let still_valid = entry.mtime.is_some()
+            && std::fs::metadata(&entry.path)
+                .map(|meta| meta.is_file() && meta.len() == entry.size)
+                .unwrap_or(false)
+            && file_mtime(&entry.path) == entry.mtime;
+
+        if still_valid && entry.size == size {
+            return Some(entry.path.clone());
+        }
+        if !still_valid {
+            debug!("本地去重索引条目已失效，移除: {:?}", entry.path);
+            entries.remove(&md5);
+            drop(entries);
+            self.mark_dirty();
+        }
+        None
+    }
+
+    /// 移除指向指定路径的条目（本地文件被删除时调用）
+    pub fn forget_path(&self, path: &Path) {
+        let mut entries = self.store.entries.lock();
+        let before = entries.len();
+        entries.retain(|_, entry| entry.path != path);
+        let changed = entries.len() != before;
+        drop(entries);
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    /// 超出上限时淘汰最早记录的条目
+    fn evict_oldest(entries: &mut HashMap<String, DedupEntry>) {
+        if entries.len() <= MAX_DEDUP_ENTRIES {
+            return;
+        }
+        let mut by_age: Vec<(i64, String)> = entries
+            .iter()
+            .map(|(md5, entry)| (entry.recorded_at, md5.clone()))
+            .collect();
+        by_age.sort();
+        let overflow = entries.len() - MAX_DEDUP_ENTRIES;
+        for (_, md5) in by_age.into_iter().take(overflow) {
+            entries.remove(&md5);
+        }
+    }
+
+    /// 立即写入未落盘的修改（关闭前调用，会阻塞当前线程）
+    pub fn flush(&self) {
+        self.store.flush();
+    }
+
+    /// 标记索引已修改，并安排一次延迟写盘
+    ///
+    /// 没有 tokio 运行时（如同步测试）时只标记，需要显式调用 `flush`
+    fn mark_dirty(&self) {
+        self.store.dirty.store(true, Ordering::SeqCst);
+        if self.store.flush_scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            self.store.flush_scheduled.store(false, Ordering::SeqCst);
+            return;
+        };
+
+        let store = self.store.clone();
+        handle.spawn(async move {
+            tokio::time::sleep(FLUSH_DEBOUNCE).await;
+            // 先清除标记，写盘期间的新修改会安排下一次写盘
+            store.flush_scheduled.store(false, Ordering::SeqCst);
+            let _ = tokio::task::spawn_blocking(move || store.flush()).await;
+        });
+    }
+}
+
+/// 规范化 MD5：转为小写，不是 32 位十六进制字符串时返回 None
+fn normalize_md5(md5: &str) -> Option<String> {
+    let md5 = md5.trim().to_ascii_lowercase();
+    (md5.len() == 32 && md5.chars().all(|c| c.is_ascii_hexdigit())).then_some(md5)
+}
+
+/// 文件修改时间（Unix 时间戳，毫秒）
+fn file_mtime(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_millis() as i64)
+}
+
+/// 将已有文件放到目标路径：允许硬链接时优先硬链接，失败（如跨分区）或不允许时复制
+///
+/// 复制大文件耗时较长，异步上下文中应放到 `spawn_blocking` 中执行
+pub fn link_or_copy(source: &Path, target: &Path, hardlink: bool) -> std::io::Result<()> {
+    if hardlink && std::fs::hard_link(source, target).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(source, target).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const MD5: &str = "0123456789ABCDEF0123456789abcdef";
+
+    #[test]
+    fn test_record_and_lookup() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.bin");
+        std::fs::write(&file, b"hello").unwrap();
+
+        let index = LocalDedupIndex::new();
+        // 未启用时不记录
+        index.record(MD5, &file, 5);
+        assert!(index.is_empty());
+
+        index.set_enabled(true);
+        index.record(MD5, &file, 5);
+        index.record("not-a-md5", &file, 5);
+        assert_eq!(index.len(), 1);
+
+        assert_eq!(index.lookup(&MD5.to_lowercase(), 5), Some(file.clone()));
+        // 大小不一致时不命中，但条目仍然有效
+        assert_eq!(index.lookup(MD5, 6), None);
+        assert_eq!(index.len(), 1);
+
+        // 文件被修改后条目失效并被移除
+        std::fs::write(&file, b"changed!").unwrap();
+        assert_eq!(index.lookup(MD5, 5), None);
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_rejects_changed_mtime() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.bin");
+        std::fs::write(&file, b"hello").unwrap();
+
+        let index = LocalDedupIndex::new();
+        index.set_enabled(true);
+        index.record(MD5, &file, 5);
+
+        // 大小不变但内容被改写（修改时间变化）时不再命中
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(index.lookup(MD5, 5), None);
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn test_persist_and_forget() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.bin");
+        std::fs::write(&file, b"hello").unwrap();
+
+        let index = LocalDedupIndex::new();
+        index.set_enabled(true);
+        index.load(dir.path());
+        index.record(MD5, &file, 5);
+        index.flush();
+
+        let reloaded = LocalDedupIndex::new();
+        reloaded.set_enabled(true);
+        reloaded.load(dir.path());
+        assert_eq!(reloaded.lookup(MD5, 5), Some(file.clone()));
+
+        reloaded.forget_path(&file);
+        reloaded.flush();
+        let again = LocalDedupIndex::new();
+        again.load(dir.path());
+        assert!(again.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_debounced_flush() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.bin");
+        std::fs::write(&file, b"hello").unwrap();
+
+        let index = LocalDedupIndex::new();
+        index.set_enabled(true);
+        index.load(dir.path());
+        index.record(MD5, &file, 5);
+        // 修改后不立即写盘
+        assert!(!dir.path().join(DEDUP_INDEX_FILE).exists());
+
+        tokio::time::sleep(FLUSH_DEBOUNCE + Duration::from_millis(500)).await;
+        let reloaded = LocalDedupIndex::new();
+        reloaded.load(dir.path());
+        assert_eq!(reloaded.len(), 1);
+    }
+
+    #[test]
+    fn test_evict_oldest() {
+        let mut entries: HashMap<String, DedupEntry> = (0..MAX_DEDUP_ENTRIES + 2)
+            .map(|i| {
+                (
+                    format!("{:032x}", i),
+                    DedupEntry {
+                        path: PathBuf::from(format!("/tmp/{}", i)),
+                        size: 1,
+                        mtime: None,
+                        recorded_at: i as i64,
+                    },
+                )
+            })
+            .collect();
+        LocalDedupIndex::evict_oldest(&mut entries);
+        assert_eq!(entries.len(), MAX_DEDUP_ENTRIES);
+        assert!(!entries.contains_key(&format!("{:032x}", 0)));
+        assert!(!entries.contains_key(&format!("{:032x}", 1)));
+        assert!(entries.contains_key(&format!("{:032x}", 2)));
+    }
+
+    #[test]
+    fn test_link_or_copy() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("a.bin");
+        let target = dir.path().join("b.bin");
+        std::fs::write(&source, b"hello").unwrap();
+
+        link_or_copy(&source, &target, false).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"hello");
+
+        // 复制得到独立文件，修改目标不影响源文件
+        std::fs::write(&target, b"changed").unwrap();
+        assert_eq!(std::fs::read(&source).unwrap(), b"hello");
+
+        let linked = dir.path().join("c.bin");
+        link_or_copy(&source, &linked, true).unwrap();
+        assert_eq!(std::fs::read(&linked).unwrap(), b"hello");
+    }
+}
@@ -1,10 +1,14 @@
 use crate::auth::UserAuth;
 use crate::autobackup::events::{BackupTransferNotification, TransferTaskType};
 use crate::common::{ProxyConfig, RefreshCoordinator, RefreshCoordinatorConfig};
-use crate::config::{DownloadConfig, VipType};
-use crate::downloader::{ChunkManager, DownloadTask, SpeedCalculator};
-use crate::netdisk::NetdiskClient;
-use crate::server::events::{DownloadEvent, ProgressThrottler, TaskEvent};
+use crate::config::{DownloadConfig, RetryConfig, VipType};
+use crate::downloader::{
+    speed_test, ChunkManager, DownloadErrorKind, DownloadLinkSource, DownloadTask, LinkRefreshTarget,
+    RateLimitCooldown, SpeedCalculator, SpeedLimiter, SpeedTestResult, TaskRefreshHandles,
+};
+use crate::downloader::link_source::fetch_refresh_links;
+use crate::netdisk::{NetdiskClient, NetdiskError};
+use crate::server::events::{DownloadEvent, ProgressThrottler, TaskEvent, TransferMetrics};
 use crate::server::websocket::WebSocketManager;
 use anyhow::{Context, Result};
 use dashmap::DashMap;
@@ -12,21 +16,15 @@ use futures::future::join_all;
 use reqwest::Client;
 use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
 use std::time::Duration;
-use tokio::fs::File;
+use tokio::fs::OpenOptions;
 use tokio::sync::{mpsc, Mutex, Semaphore};
 use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
-/// 重试指数退避初始延迟（毫秒）
-const INITIAL_BACKOFF_MS: u64 = 100;
-
-/// 重试指数退避最大延迟（毫秒）
-const MAX_BACKOFF_MS: u64 = 5000;
-
 /// 最少保留链接数
 const MIN_AVAILABLE_LINKS: usize = 2;
 
@@ -39,19 +37,11 @@ const SPEED_WINDOW_SIZE: usize = 7;
 /// 避免前期数据不足导致误判
 const MIN_WINDOW_SAMPLES: usize = 5;
 
-/// 🔥 计算指数退避延迟
+/// 下载请求使用的 User-Agent（Android 客户端，与 NetdiskClient 一致）
 ///
-/// # 延迟序列
-/// - retry_count=0: 100ms
-/// - retry_count=1: 200ms
-/// - retry_count=2: 400ms
-/// - retry_count=3: 800ms
-/// - ...
-/// - 最大: 5000ms
-fn calculate_backoff_delay(retry_count: u32) -> u64 {
-    let delay = INITIAL_BACKOFF_MS * 2u64.pow(retry_count);
-    delay.min(MAX_BACKOFF_MS)
-}
+/// CDN 链接校验 User-Agent，外部下载器使用直链时也必须携带
+pub const DOWNLOAD_USER_AGENT: &str =
+    "netdisk;P2SP;3.0.0.8;netdisk;11.12.3;ANG-AN00;android-android;10.0;JSbridge4.4.0;jointBridge;1.1.0;";
 
 /// URL 健康状态管理器
 ///
@@ -106,6 +96,15 @@ pub struct UrlHealthManager {
     global_avg_speed: Arc<AtomicU64>,
     /// 已完成的分片总数（用于计算平均速度）
     total_chunks: Arc<AtomicU64>,
+
+    /// 🔥 首选链接索引（任务指定 dlink_prefer 时设置，None 表示使用全部链接）
+    /// 失败触发的链接刷新会轮换到下一个索引
+    dlink_prefer: Option<Arc<AtomicUsize>>,
+
+    /// 🔥 各 CDN 主机当前活跃分片数（host -> 正在请求的分片数）
+    host_active_chunks: Arc<DashMap<String, usize>>,
+    /// 🔥 单主机最大并发分片数（0 表示不限制），注册到调度器后与调度器共享以支持动态调整
+    max_chunks_per_host: Arc<AtomicUsize>,
 }
 
 impl UrlHealthManager {
@@ -162,46 +161,82 @@ impl UrlHealthManager {
             url_avg_speeds,
             url_sample_counts,
             url_recent_speeds,
+            dlink_prefer: None,
+            host_active_chunks: Arc::new(DashMap::new()),
+            max_chunks_per_host: Arc::new(AtomicUsize::new(0)),
         }
     }
 
-    /// 获取可用的链接数量（权重>0的链接，包括原始和动态添加的）
-    pub fn available_count(&self) -> usize {
-        let original_count = self
-            .weights
-            .iter()
-            .filter(|entry| *entry.value() > 0)
-            .count();
+    /// 🔥 设置首选链接索引（只使用该索引对应的 CDN 节点）
+    pub fn with_dlink_prefer(mut self, dlink_prefer: Option<usize>) -> Self {
+        self.dlink_prefer = dlink_prefer.map(|index| Arc::new(AtomicUsize::new(index)));
+        self
+    }
 
-        // 计算动态添加链接中可用的数量
-        let additional_count = self
-            .additional_urls
-            .iter()
-            .filter(|entry| {
-                self.weights
-                    .get(entry.key())
-                    .map(|w| *w > 0)
-                    .unwrap_or(false)
-            })
-            .count();
+    /// 当前首选链接索引
+    pub fn dlink_prefer(&self) -> Option<usize> {
+        self.dlink_prefer
+            .as_ref()
+            .map(|index| index.load(Ordering::SeqCst))
+    }
 
-        original_count + additional_count
+    /// 更新首选链接索引（未指定首选节点时忽略）
+    pub fn set_dlink_prefer(&self, index: usize) {
+        if let Some(ref current) = self.dlink_prefer {
+            current.store(index, Ordering::SeqCst);
+        }
     }
 
-    /// 根据索引获取可用链接（跳过权重=0的链接）
-    pub fn get_url(&self, index: usize) -> Option<&String> {
-        let available: Vec<&String> = self
-            .all_urls
+    /// 🔥 只保留指定链接（首选节点刷新后调用），其余链接禁用且不再探测恢复
+    pub fn pin_url(&self, url: String, speed: f64) {
+        if self.weights.contains_key(&url) {
+            self.restore_link(&url, speed);
+        } else {
+            self.add_refreshed_urls(vec![url.clone()], vec![speed]);
+        }
+
+        for mut entry in self.weights.iter_mut() {
+            if *entry.key() != url {
+                *entry.value_mut() = 0;
+            }
+        }
+        self.next_probe_time.retain(|key, _| *key == url);
+        info!("📌 已切换到首选下载链接: {}", url);
+    }
+
+    /// 🔥 停用不在 `keep` 中的链接（手动刷新后调用），被停用的链接不再探测恢复
+    ///
+    /// # 返回
+    /// 被停用的链接数量
+    pub fn retire_urls_except(&self, keep: &[String]) -> usize {
+        let mut retired = 0;
+        for mut entry in self.weights.iter_mut() {
+            if *entry.value() > 0 && !keep.contains(entry.key()) {
+                *entry.value_mut() = 0;
+                retired += 1;
+            }
+        }
+        self.next_probe_time.retain(|key, _| keep.contains(key));
+        retired
+    }
+
+    /// 获取可用的链接数量（权重>0的链接，包括原始和动态添加的）
+    pub fn available_count(&self) -> usize {
+        // weights 同时包含原始和动态添加的链接
+        self.weights
             .iter()
-            .filter(|url| self.weights.get(*url).map(|w| *w > 0).unwrap_or(false))
-            .collect();
+            .filter(|entry| *entry.value() > 0)
+            .count()
+    }
 
+    /// 根据索引获取可用链接（跳过权重=0的链接，包括刷新添加的链接）
+    pub fn get_url(&self, index: usize) -> Option<String> {
+        let available = self.all_available_urls();
         if available.is_empty() {
             return None;
         }
 
-        let url_index = index % available.len();
-        available.get(url_index).copied()
+        available.get(index % available.len()).cloned()
     }
 
     /// 🔥 混合加权选择：权重 = 速度 × (score/100)
@@ -308,6 +343,11 @@ impl UrlHealthManager {
     /// - Some(url): 返回 score 最高的被禁用链接
     /// - None: 链接充足（>=5）或无被禁用链接
     pub fn get_warm_url(&self) -> Option<&String> {
+        // 指定了首选节点时不向其他链接分配流量
+        if self.dlink_prefer.is_some() {
+            return None;
+        }
+
         // 条件1：可用链接数是否不足5个
         if self.available_count() >= 5 {
             return None; // 链接充足，不需要 warm 链路
@@ -801,6 +841,181 @@ impl UrlHealthManager {
 
         urls
     }
+
+    /// 指定 CDN 主机是否仍有可用链接
+    pub fn has_available_host(&self, host: &str) -> bool {
+        self.all_available_urls()
+            .iter()
+            .any(|url| Self::host_of(url) == host)
+    }
+
+    /// 🔥 共享调度器的单主机最大并发分片数（任务注册到调度器时调用）
+    pub fn set_host_chunk_limit(&mut self, limit: Arc<AtomicUsize>) {
+        self.max_chunks_per_host = limit;
+    }
+
+    /// 链接所属的 CDN 主机（无法解析时使用整个链接）
+    pub fn host_of(url: &str) -> String {
+        reqwest::Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(|host| host.to_string()))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// 指定链接所在主机的活跃分片数
+    pub fn host_active_count(&self, url: &str) -> usize {
+        self.host_active_chunks
+            .get(&Self::host_of(url))
+            .map(|count| *count)
+            .unwrap_or(0)
+    }
+
+    /// 🔥 按单主机上限计算任务可同时下载的分片数
+    ///
+    /// # 返回
+    /// - Some(n): 可用主机数 × 单主机上限
+    /// - None: 未限制单主机并发
+    pub fn host_chunk_capacity(&self) -> Option<usize> {
+        let limit = self.max_chunks_per_host.load(Ordering::SeqCst);
+        if limit == 0 {
+            return None;
+        }
+
+        let hosts: std::collections::HashSet<String> = self
+            .all_available_urls()
+            .iter()
+            .map(|url| Self::host_of(url))
+            .collect();
+        Some(hosts.len() * limit)
+    }
+
+    /// 🔥 将分片分散到多个 CDN 主机
+    ///
+    /// 选中链接所在主机已达上限时，改用未达上限且活跃分片最少的主机上的链接
+    /// （优先未尝试过的链接）；所有主机都已满时保留原链接
+    pub fn spread_across_hosts(&self, url: String, tried_urls: &std::collections::HashSet<String>) -> String {
+        let limit = self.max_chunks_per_host.load(Ordering::SeqCst);
+        if limit == 0 || self.host_active_count(&url) < limit {
+            return url;
+        }
+
+        let candidate = self
+            .all_available_urls()
+            .into_iter()
+            .map(|candidate| {
+                let active = self.host_active_count(&candidate);
+                (candidate, active)
+            })
+            .filter(|(_, active)| *active < limit)
+            .min_by_key(|(candidate, active)| (tried_urls.contains(candidate), *active));
+
+        match candidate {
+            Some((candidate, active)) => {
+                debug!(
+                    "主机 {} 已达并发上限 {}，改用主机 {} (活跃分片: {})",
+                    Self::host_of(&url),
+                    limit,
+                    Self::host_of(&candidate),
+                    active
+                );
+                candidate
+            }
+            None => url,
+        }
+    }
+
+    /// 分片开始请求时增加所在主机的活跃分片数
+    pub fn acquire_host(&self, url: &str) {
+        *self.host_active_chunks.entry(Self::host_of(url)).or_insert(0) += 1;
+    }
+
+    /// 分片请求结束时减少所在主机的活跃分片数
+    pub fn release_host(&self, url: &str) {
+        if let Some(mut count) = self.host_active_chunks.get_mut(&Self::host_of(url)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+/// 🔥 远端文件指纹（探测响应中的文件总大小和 ETag）
+///
+/// 恢复下载时与上次记录的指纹比较，不一致说明网盘文件已变化（重新编码、分享内容更换等），
+/// 此时已完成的分片不可再复用
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteFingerprint {
+    /// 文件总大小（Content-Range 的总长度，200 响应时取 Content-Length）
+    pub size: Option<u64>,
+    /// ETag（CDN 未返回时为 None）
+    pub etag: Option<String>,
+}
+
+impl RemoteFingerprint {
+    /// 从探测响应头解析
+    pub fn from_headers(status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name: reqwest::header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        // Content-Range: bytes 0-65535/1234567（总长度为 * 时视为未知）
+        let size = match status {
+            reqwest::StatusCode::PARTIAL_CONTENT => header(reqwest::header::CONTENT_RANGE)
+                .and_then(|range| range.rsplit('/').next().and_then(|total| total.parse().ok())),
+            _ => header(reqwest::header::CONTENT_LENGTH).and_then(|len| len.parse().ok()),
+        };
+
+        Self {
+            size,
+            etag: header(reqwest::header::ETAG),
+        }
+    }
+}
+
+/// 分片下载上下文（同一任务的所有分片共享，由调度器或独立下载模式构造）
+pub struct ChunkDownloadContext<'a> {
+    /// HTTP 客户端
+    pub client: Client,
+    /// Cookie 字符串
+    pub cookie: &'a str,
+    /// Referer 头（如果存在），用于 Range 请求避免 403 Forbidden
+    pub referer: Option<&'a str>,
+    /// URL 健康管理器，用于动态管理可用链接
+    pub url_health: Arc<Mutex<UrlHealthManager>>,
+    /// 输出文件路径
+    pub output_path: &'a Path,
+    /// 分片管理器
+    pub chunk_manager: Arc<Mutex<ChunkManager>>,
+    /// 速度计算器
+    pub speed_calc: Arc<Mutex<SpeedCalculator>>,
+    /// 下载任务
+    pub task: Arc<Mutex<DownloadTask>>,
+    /// 分片大小（用于动态计算超时）
+    pub chunk_size: u64,
+    /// 文件总大小（用于探测恢复链接）
+    pub total_size: u64,
+    /// 取消令牌（用于中断下载）
+    pub cancellation_token: CancellationToken,
+    /// WebSocket 管理器（可选，用于发布进度事件）
+    pub ws_manager: Option<Arc<WebSocketManager>>,
+    /// 进度节流器（可选，200ms 间隔）
+    pub progress_throttler: Option<Arc<ProgressThrottler>>,
+    /// 任务 ID（用于进度事件）
+    pub task_id: String,
+    /// 文件夹进度通知发送器（可选，仅文件夹子任务需要）
+    pub folder_progress_tx: Option<mpsc::UnboundedSender<String>>,
+    /// 备份任务统一通知发送器（可选，仅备份任务需要）
+    pub backup_notification_tx: Option<mpsc::UnboundedSender<BackupTransferNotification>>,
+    /// 任务级共享槽位刷新节流器（可选）
+    pub slot_touch_throttler: Option<Arc<crate::task_slot_pool::SlotTouchThrottler>>,
+    /// 分片重试配置
+    pub retry_config: RetryConfig,
+    /// 代理故障回退管理器（可选）
+    pub fallback_mgr: Option<Arc<crate::common::ProxyFallbackManager>>,
+    /// 全局限速器（可选）
+    pub global_speed_limiter: Option<Arc<SpeedLimiter>>,
 }
 
 /// 下载引擎
@@ -821,6 +1036,8 @@ pub struct DownloadEngine {
     shared_download_client: Arc<StdRwLock<Client>>,
     /// 代理故障回退管理器
     pub(crate) fallback_mgr: Option<Arc<crate::common::ProxyFallbackManager>>,
+    /// 🔥 全局限流冷却器（获取下载链接命中限流时计数）
+    rate_limit_cooldown: Arc<RateLimitCooldown>,
 }
 
 impl DownloadEngine {
@@ -837,11 +1054,16 @@ impl DownloadEngine {
     ) -> Self {
         // 基础HTTP客户端，使用较长的超时时间以支持大分片下载
         // 实际超时会在每个请求中根据分片大小动态调整
-        let client = Client::builder()
+        let mut client_builder = Client::builder()
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-            .timeout(std::time::Duration::from_secs(600)) // 10分钟基础超时（会被请求级别的超时覆盖）
-            .build()
-            .expect("Failed to build HTTP client");
+            .timeout(std::time::Duration::from_secs(600)); // 10分钟基础超时（会被请求级别的超时覆盖）
+        client_builder = crate::common::apply_connect_timeout(client_builder);
+        if let Some(proxy) = proxy_config {
+            client_builder = proxy
+                .apply_to_builder(client_builder)
+                .expect("Failed to apply proxy config");
+        }
+        let client = client_builder.build().expect("Failed to build HTTP client");
 
         // 从 user_auth 中提取 VIP 等级
         let vip_type = VipType::from_u32(user_auth.vip_type.unwrap_or(0));
@@ -865,16 +1087,20 @@ impl DownloadEngine {
             proxy_config: proxy_config_shared,
             shared_download_client,
             fallback_mgr,
+            rate_limit_cooldown: Arc::new(RateLimitCooldown::default()),
         }
     }
 
+    /// 使用共享的限流冷却器（与调度器共用同一份统计）
+    pub fn with_rate_limit_cooldown(mut self, cooldown: Arc<RateLimitCooldown>) -> Self {
+        self.rate_limit_cooldown = cooldown;
+        self
+    }
+
     /// 构建下载专用 HTTP 客户端（静态方法，可用于初始创建和热更新重建）
     fn build_download_client(proxy_config: Option<&ProxyConfig>) -> Result<Client> {
-        // 使用 Android 客户端的 User-Agent（与 NetdiskClient 一致）
-        let pan_ua = "netdisk;P2SP;3.0.0.8;netdisk;11.12.3;ANG-AN00;android-android;10.0;JSbridge4.4.0;jointBridge;1.1.0;";
-
         let mut builder = Client::builder()
-            .user_agent(pan_ua)
+            .user_agent(DOWNLOAD_USER_AGENT)
             .timeout(std::time::Duration::from_secs(120)) // 2分钟超时
             .pool_max_idle_per_host(200) // 增大连接池：100 -> 200
             .pool_idle_timeout(std::time::Duration::from_secs(90)) // IdleConnTimeout: 90s
@@ -887,6 +1113,7 @@ impl DownloadEngine {
             .http2_initial_connection_window_size(Some(1024 * 1024 * 4)) // 4MB初始连接窗口（默认65KB）
             .http2_keep_alive_interval(Some(std::time::Duration::from_secs(10))) // HTTP/2 keep-alive
             .http2_keep_alive_timeout(std::time::Duration::from_secs(20)); // HTTP/2 keep-alive超时
+        builder = crate::common::apply_connect_timeout(builder);
 
         if let Some(proxy) = proxy_config {
             builder = proxy.apply_to_builder(builder)
@@ -915,6 +1142,11 @@ impl DownloadEngine {
         self.shared_download_client.read().unwrap().clone()
     }
 
+    /// 当前账号的单任务并发分片数上限（按会员等级）
+    pub fn max_chunks_ceiling(&self) -> usize {
+        DownloadConfig::max_chunks_ceiling_for_vip(self.vip_type)
+    }
+
     /// 获取当前网盘客户端快照（Locate 请求等场景使用）
     /// 克隆出来避免跨 await 持锁
     fn get_netdisk_client(&self) -> NetdiskClient {
@@ -990,20 +1222,24 @@ impl DownloadEngine {
         Arc<Mutex<ChunkManager>>,     // 分片管理器
         Arc<Mutex<SpeedCalculator>>,  // 速度计算器
     )> {
-        let (fs_id, remote_path, local_path, total_size) = {
+        // 🔥 下载过程中写入临时文件（.bdtmp），完成后由调度器重命名为最终文件名
+        let (fs_id, remote_path, local_path, mut total_size, direct_dlink, dlink_prefer, refresh_target) = {
             let t = task.lock().await;
             (
                 t.fs_id,
                 t.remote_path.clone(),
-                t.local_path.clone(),
+                t.write_path(),
                 t.total_size,
+                t.direct_dlink.clone(),
+                t.dlink_prefer,
+                LinkRefreshTarget::from_task(&t),
             )
         };
 
-        info!("准备任务调度: fs_id={}, 本地路径={:?}", fs_id, local_path);
+        info!("准备任务调度: fs_id={}, 写入路径={:?}", fs_id, local_path);
 
         // 1. 计算自适应分片大小
-        let chunk_size = DownloadConfig::calculate_adaptive_chunk_size(total_size, self.vip_type);
+        let mut chunk_size = DownloadConfig::calculate_adaptive_chunk_size(total_size, self.vip_type);
         info!(
             "自适应分片大小: {} bytes ({}), 文件大小: {} bytes, VIP等级: {:?}",
             chunk_size,
@@ -1012,27 +1248,54 @@ impl DownloadEngine {
             self.vip_type
         );
 
-        // 2. 获取所有可用下载链接
-        let all_urls = match self
-            .get_netdisk_client()
-            .get_locate_download_url(&remote_path)
-            .await
-        {
-            Ok(urls) => {
-                if urls.is_empty() {
-                    error!("获取到下载链接列表为空: path={}", remote_path);
-                    anyhow::bail!("未找到可用的下载链接");
+        // 2. 获取所有可用下载链接（分享免转存任务直接使用 share/list 返回的直链）
+        let all_urls = if let Some(dlink) = direct_dlink {
+            info!("使用分享直链下载: path={}", remote_path);
+            // 🔥 保存的直链可能已过期（暂停/重启后恢复），有分享来源时重新列出分享目录获取新直链
+            if matches!(refresh_target, LinkRefreshTarget::Share { .. }) {
+                match fetch_refresh_links(&self.get_netdisk_client(), &refresh_target).await {
+                    Ok(urls) if !urls.is_empty() => urls,
+                    Ok(_) => vec![dlink],
+                    Err(e) => {
+                        warn!("重新获取分享直链失败，使用保存的直链: path={}, 错误: {}", remote_path, e);
+                        vec![dlink]
+                    }
                 }
-                urls
+            } else {
+                vec![dlink]
             }
-            Err(e) => {
-                error!("获取下载链接列表失败: path={}, 错误: {}", remote_path, e);
-                return Err(e).context("获取下载链接列表失败");
+        } else {
+            // 🔥 取消时直接丢弃 locate 请求，不必等待 HTTP 超时
+            let netdisk_client = self.get_netdisk_client();
+            let locate = Self::until_cancelled(
+                &cancellation_token,
+                netdisk_client.get_locate_download_url(&remote_path),
+            )
+            .await
+            .context("任务在获取下载链接时被取消")?;
+            match locate {
+                Ok(urls) => {
+                    if urls.is_empty() {
+                        error!("获取到下载链接列表为空: path={}", remote_path);
+                        anyhow::bail!("未找到可用的下载链接");
+                    }
+                    urls
+                }
+                Err(e) => {
+                    error!("获取下载链接列表失败: path={}, 错误: {}", remote_path, e);
+                    return Err(self.link_fetch_error(e));
+                }
             }
         };
 
         info!("获取到 {} 个下载链接", all_urls.len());
 
+        // 🔥 任务指定了首选节点：按 dlink_prefer 选出链接（跳过 nb.cache）
+        let preferred = dlink_prefer.map(|prefer| {
+            let index = NetdiskClient::select_dlink_index(&all_urls, prefer);
+            (index, all_urls[index].clone())
+        });
+
         // 3. 获取共享下载客户端引用（代理热更新时会替换内部 Client，后续重试自动生效）
         let download_client = self.shared_download_client.clone();
         let download_client_snapshot = self.get_download_client();
@@ -1043,6 +1306,7 @@ impl DownloadEngine {
         let mut valid_urls = Vec::new();
         let mut url_speeds = Vec::new();
         let mut referer: Option<String> = None;
+        let mut fingerprint: Option<RemoteFingerprint> = None;
 
         // 预先获取 bduss，避免在 async 闭包中借用 self
         let bduss = self.get_netdisk_client().bduss().to_string();
@@ -1072,21 +1336,26 @@ impl DownloadEngine {
                 })
                 .collect();
 
-            // 并行执行本批次的探测
-            let batch_results = join_all(probe_futures).await;
+            // 并行执行本批次的探测（取消时中止所有探测请求）
+            let batch_results = Self::until_cancelled(&cancellation_token, join_all(probe_futures))
+                .await
+                .context("任务在探测下载链接时被取消")?;
 
             // 处理探测结果
             for (idx, url, result) in batch_results {
                 match result {
-                    Ok((ref_url, speed)) => {
+                    Ok((ref_url, speed, remote)) => {
                         info!("✓ 链接 #{} 探测成功，速度: {:.2} KB/s", idx, speed);
                         valid_urls.push(url);
                         url_speeds.push(speed);
 
-                        // 保存第一个成功链接的 Referer
+                        // 保存第一个成功链接的 Referer 和远端文件指纹
                         if referer.is_none() {
                             referer = ref_url;
                         }
+                        if fingerprint.is_none() {
+                            fingerprint = Some(remote);
+                        }
                     }
                     Err(e) => {
                         warn!("✗ 链接 #{} 探测失败: {}", idx, e);
@@ -1106,6 +1375,37 @@ impl DownloadEngine {
             all_urls.len()
         );
 
+        // 🔥 记录远端文件指纹（恢复时由持久化管理器与上次记录比较），
+        // 远端大小与任务记录不一致时按远端大小重新规划分片
+        if let Some(fingerprint) = fingerprint {
+            let mut t = task.lock().await;
+            if let Some(remote_size) = fingerprint.size.filter(|&size| size > 0 && size != total_size) {
+                warn!(
+                    "⚠️ 远端文件大小已变化: 记录 {} bytes, 远端 {} bytes，按远端大小重新下载",
+                    total_size, remote_size
+                );
+                total_size = remote_size;
+                chunk_size = DownloadConfig::calculate_adaptive_chunk_size(total_size, self.vip_type);
+                t.total_size = remote_size;
+                t.downloaded_size = 0;
+            }
+            t.remote_size = fingerprint.size;
+            t.remote_etag = fingerprint.etag;
+        }
+
+        // 🔥 首选链接探测成功时只使用该链接，否则回退到全部可用链接
+        let mut pinned_index = None;
+        if let Some((index, url)) = preferred {
+            if let Some(pos) = valid_urls.iter().position(|valid| *valid == url) {
+                info!("使用首选下载链接 #{} ({:.2} KB/s)", index, url_speeds[pos]);
+                url_speeds = vec![url_speeds[pos]];
+                valid_urls = vec![url];
+                pinned_index = Some(index);
+            } else {
+                warn!("首选下载链接 #{} 探测失败，回退到全部可用链接", index);
+            }
+        }
+
         // 🔥 淘汰慢速链接（使用中位数替代平均值）
         if url_speeds.len() > 1 {
             // 计算中位数速度
@@ -1161,8 +1461,15 @@ impl DownloadEngine {
             url_speeds = filtered_speeds;
         }
 
+        // 🔥 记录初始 CDN 主机（下载过程中按实际成功的分片链接更新）
+        if let Some(url) = valid_urls.first() {
+            task.lock().await.set_cdn_host(UrlHealthManager::host_of(url));
+        }
+
         // 5. 创建 URL 健康管理器（传递speeds）
-        let url_health = Arc::new(Mutex::new(UrlHealthManager::new(valid_urls, url_speeds)));
+        let url_health = Arc::new(Mutex::new(
+            UrlHealthManager::new(valid_urls, url_speeds).with_dlink_prefer(pinned_index),
+        ));
 
         // 6. 创建本地文件（内部会加锁检查取消状态）
         self.prepare_file(&local_path, total_size, &cancellation_token)
@@ -1198,6 +1505,20 @@ impl DownloadEngine {
         ))
     }
 
+    /// 等待异步操作完成，取消令牌触发时立即返回 None
+    ///
+    /// 被丢弃的 future 中未完成的 HTTP 请求会随之中止
+    async fn until_cancelled<T>(
+        cancellation_token: &CancellationToken,
+        future: impl std::future::Future<Output = T>,
+    ) -> Option<T> {
+        tokio::select! {
+            biased; // 优先检查取消，已取消的任务不再发起请求
+            _ = cancellation_token.cancelled() => None,
+            output = future => Some(output),
+        }
+    }
+
     /// 下载文件（自动计算最优分片大小）
     ///
     /// # 参数
@@ -1209,12 +1530,13 @@ impl DownloadEngine {
         global_semaphore: Arc<Semaphore>,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
-        let (fs_id, remote_path, local_path, total_size) = {
+        let (fs_id, remote_path, local_path, write_path, total_size) = {
             let t = task.lock().await;
             (
                 t.fs_id,
                 t.remote_path.clone(),
                 t.local_path.clone(),
+                t.write_path(),
                 t.total_size,
             )
         };
@@ -1252,7 +1574,7 @@ impl DownloadEngine {
             }
             Err(e) => {
                 error!("获取下载链接列表失败: path={}, 错误: {}", remote_path, e);
-                return Err(e).context("获取下载链接列表失败");
+                return Err(self.link_fetch_error(e));
             }
         };
 
@@ -1269,18 +1591,25 @@ impl DownloadEngine {
             .try_download_with_url(
                 task.clone(),
                 global_semaphore.clone(),
-                &remote_path,
                 &all_urls,
                 total_size,
                 chunk_size,
-                &local_path,
+                &write_path,
                 cancellation_token.clone(),
             )
             .await
         {
             Ok(_) => {
-                // 下载成功，标记任务完成
+                // 下载成功，临时文件重命名为最终文件名
+                if write_path != local_path {
+                    tokio::fs::rename(&write_path, &local_path)
+                        .await
+                        .context("临时文件重命名失败")?;
+                }
+
+                // 标记任务完成
                 let mut t = task.lock().await;
+                t.temp_path = None;
                 t.mark_completed();
                 info!("✓ 任务下载完成: {}", t.id);
                 Ok(())
@@ -1307,7 +1636,6 @@ impl DownloadEngine {
         &self,
         task: Arc<Mutex<DownloadTask>>,
         global_semaphore: Arc<Semaphore>,
-        remote_path: &str, // 用于 CDN 链接刷新
         download_urls: &[String],
         total_size: u64,
         chunk_size: u64,
@@ -1427,9 +1755,10 @@ impl DownloadEngine {
         // 3.2 启动定时刷新循环（10分钟间隔）
         // 使用 Arc 包装 self 以便在 spawn 的任务中使用
         let engine_arc = Arc::new(self.clone());
+        let refresh_target = LinkRefreshTarget::from_task(&*task.lock().await);
         let _periodic_refresh_handle = Self::start_periodic_refresh(
             engine_arc,
-            remote_path.to_string(),
+            refresh_target,
             total_size,
             url_health.clone(),
             download_client.clone(),
@@ -1708,7 +2037,7 @@ impl DownloadEngine {
         bduss: &str,
         url: &str,
         expected_size: u64,
-    ) -> Result<(Option<String>, f64)> {
+    ) -> Result<(Option<String>, f64, RemoteFingerprint)> {
         const PROBE_SIZE: u64 = 64 * 1024; // 64KB
 
         let probe_end = if expected_size > 0 {
@@ -1744,6 +2073,8 @@ impl DownloadEngine {
             );
         }
 
+        let fingerprint = RemoteFingerprint::from_headers(status, response.headers());
+
         // 获取最终的 URL（如果有重定向，这将是重定向后的 URL）
         let final_url = response.url().to_string();
 
@@ -1772,7 +2103,7 @@ impl DownloadEngine {
             speed_kbps
         );
 
-        Ok((referer, speed_kbps))
+        Ok((referer, speed_kbps, fingerprint))
     }
 
     /// 用于恢复链接的简化探测函数（静态方法）
@@ -1840,45 +2171,212 @@ impl DownloadEngine {
     // CDN 链接刷新机制 - 阶段二
     // ========================================
 
+    /// 🔥 手动强制刷新任务的下载链接
+    ///
+    /// 与检测循环共享任务级 RefreshCoordinator，遵守最小刷新间隔，
+    /// 刷新正在进行或距上次刷新过近时返回错误。
+    /// 新链接写入 UrlHealthManager 后，分片在下一次选链（新分片或重试）时即切换到新链接
+    ///
+    /// # 参数
+    /// * `task_id` - 任务ID
+    /// * `chunk_scheduler` - 分片调度器（用于查找正在下载的任务）
+    ///
+    /// # 返回
+    /// 新增/更新的链接数量
+    pub async fn force_refresh_url(
+        &self,
+        task_id: &str,
+        chunk_scheduler: &crate::downloader::ChunkScheduler,
+    ) -> Result<usize> {
+        let handles = chunk_scheduler
+            .get_task_refresh_handles(task_id)
+            .await
+            .context("任务未在下载中，无法刷新链接")?;
+
+        self.force_refresh_with(task_id, &handles, &self.get_netdisk_client())
+            .await
+    }
+
+    /// 使用指定链接来源手动刷新任务的下载链接（force_refresh_url 的实现）
+    pub async fn force_refresh_with(
+        &self,
+        task_id: &str,
+        handles: &TaskRefreshHandles,
+        links: &dyn DownloadLinkSource,
+    ) -> Result<usize> {
+        let coordinator = &handles.refresh_coordinator;
+        let Some(_guard) = coordinator.try_acquire() else {
+            if coordinator.is_refreshing() {
+                anyhow::bail!("链接正在刷新中，请稍后再试");
+            }
+            let wait_secs = coordinator
+                .min_refresh_interval_secs()
+                .saturating_sub(coordinator.seconds_since_last_refresh())
+                .max(1);
+            anyhow::bail!("链接刷新过于频繁，请 {} 秒后重试", wait_secs);
+        };
+
+        info!("🔄 手动刷新下载链接: 任务 {}", task_id);
+
+        let refreshed_urls = self
+            .refresh_links_from(
+                links,
+                &handles.target,
+                handles.total_size,
+                &handles.url_health,
+                &handles.client,
+                false,
+            )
+            .await?;
+        let count = refreshed_urls.len();
+
+        // 🔥 手动刷新说明旧链接已不可信：停用未出现在刷新结果中的链接，
+        // 分片线程下一次选择链接（新分片或重试）即切换到新链接
+        if count > 0 {
+            let retired = handles.url_health.lock().await.retire_urls_except(&refreshed_urls);
+            if retired > 0 {
+                info!("🔄 任务 {} 已停用 {} 个旧链接", task_id, retired);
+            }
+        }
+
+        info!("🔄 手动刷新完成: 任务 {} 新增/更新 {} 个链接", task_id, count);
+        Ok(count)
+    }
+
+    /// 🔥 下载测速：获取文件下载链接后下载 `test_bytes` 字节测量当前速度
+    ///
+    /// 从默认选中的链接开始（跳过 nb.cache），失败时依次尝试后续链接，
+    /// 数据不写入本地文件
+    pub async fn speed_test(&self, remote_path: &str, test_bytes: u64) -> Result<SpeedTestResult> {
+        let urls = self
+            .get_netdisk_client()
+            .get_locate_download_url(remote_path)
+            .await
+            .inspect_err(|e| self.rate_limit_cooldown.record_error(e))
+            .context("测速时获取下载链接失败")?;
+        if urls.is_empty() {
+            anyhow::bail!("未找到可用的下载链接");
+        }
+
+        let client = self.get_download_client();
+        let cookie = format!("BDUSS={}", self.get_netdisk_client().bduss());
+        let first = NetdiskClient::select_dlink_index(&urls, 0);
+        let mut last_error = None;
+
+        for offset in 0..urls.len() {
+            let index = (first + offset) % urls.len();
+            match speed_test::measure_link(&client, &cookie, &urls[index], test_bytes).await {
+                Ok((bytes, elapsed, cdn_host)) => {
+                    let speed_mbps = speed_test::throughput_mbps(bytes, elapsed);
+                    info!(
+                        "📶 测速完成: 链接 #{} ({}), {} bytes, {:.2} MB/s",
+                        index,
+                        cdn_host.as_deref().unwrap_or("-"),
+                        bytes,
+                        speed_mbps
+                    );
+                    return Ok(SpeedTestResult {
+                        remote_path: remote_path.to_string(),
+                        bytes,
+                        elapsed_ms: elapsed.as_millis() as u64,
+                        speed_mbps,
+                        cdn_host,
+                        url_index: index,
+                        url_count: urls.len(),
+                    });
+                }
+                Err(e) => {
+                    warn!("测速链接 #{} 失败: {}", index, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("无可用下载链接"))
+            .context("所有下载链接测速失败"))
+    }
+
     /// 刷新下载链接
     ///
     /// ⚠️ 修复问题1：使用 join_all 并行探测所有链接，避免串行阻塞
     ///
     /// # 流程
-    /// 1. 重新调用 get_locate_download_url 获取新链接
+    /// 1. 重新获取新链接（网盘文件重新 locate，免转存分享文件重新列出分享目录）
     /// 2. **并行**探测每个新链接（使用 futures::future::join_all）
     /// 3. 筛选高速链接（中位数 × 0.6 阈值）
     /// 4. 添加到 UrlHealthManager
     ///
     /// # 参数
-    /// * `remote_path` - 远程文件路径
+    /// * `target` - 刷新目标（网盘路径或分享文件）
     /// * `total_size` - 文件总大小
     /// * `url_health` - URL 健康管理器
     /// * `download_client` - HTTP 客户端
+    /// * `rotate_preferred` - 指定了首选节点时是否轮换到下一个链接索引（失败触发的刷新）
     ///
     /// # 返回
     /// 成功添加的新链接数量
     pub async fn refresh_download_links(
         &self,
-        remote_path: &str,
+        target: &LinkRefreshTarget,
         total_size: u64,
         url_health: &Arc<Mutex<UrlHealthManager>>,
         download_client: &Client,
+        rotate_preferred: bool,
     ) -> Result<usize> {
-        info!("🔄 开始刷新下载链接: {}", remote_path);
+        self.refresh_links_from(
+            &self.get_netdisk_client(),
+            target,
+            total_size,
+            url_health,
+            download_client,
+            rotate_preferred,
+        )
+        .await
+        .map(|urls| urls.len())
+    }
+
+    /// 从指定链接来源刷新下载链接（refresh_download_links 的实现），返回加入健康管理器的链接
+    async fn refresh_links_from(
+        &self,
+        links: &dyn DownloadLinkSource,
+        target: &LinkRefreshTarget,
+        total_size: u64,
+        url_health: &Arc<Mutex<UrlHealthManager>>,
+        download_client: &Client,
+        rotate_preferred: bool,
+    ) -> Result<Vec<String>> {
+        info!("🔄 开始刷新下载链接: {}", target.remote_path());
 
         // 1. 获取新链接
-        let all_urls = self
-            .get_netdisk_client()
-            .get_locate_download_url(remote_path)
+        let all_urls = fetch_refresh_links(links, target)
             .await
+            .inspect_err(|e| self.rate_limit_cooldown.record_error(e))
             .context("刷新时获取下载链接失败")?;
 
         if all_urls.is_empty() {
             warn!("刷新链接: 获取到空列表，跳过");
-            return Ok(0);
+            return Ok(Vec::new());
         }
 
+        // 🔥 指定了首选节点：只刷新首选链接，失败触发的刷新轮换到下一个索引
+        let preferred_index = {
+            let health = url_health.lock().await;
+            health.dlink_prefer().map(|current| {
+                let next = (current + usize::from(rotate_preferred)) % all_urls.len();
+                let index = NetdiskClient::select_dlink_index(&all_urls, next);
+                health.set_dlink_prefer(index);
+                index
+            })
+        };
+        let all_urls = match preferred_index {
+            Some(index) => {
+                info!("刷新链接: 使用首选链接索引 {} / {}", index, all_urls.len());
+                vec![all_urls[index].clone()]
+            }
+            None => all_urls,
+        };
+
         info!("刷新链接: 获取到 {} 个链接，开始并行探测", all_urls.len());
 
         // 2. ⚠️ 并行探测所有链接（修复问题1）
@@ -1921,7 +2419,7 @@ impl DownloadEngine {
 
         if valid_urls.is_empty() {
             warn!("所有刷新链接探测失败，保留现有链接");
-            return Ok(0);
+            return Ok(Vec::new());
         }
 
         info!(
@@ -1933,19 +2431,24 @@ impl DownloadEngine {
         // 4. 筛选高速链接（复用现有逻辑）
         let (filtered_urls, filtered_speeds) = Self::filter_fast_urls(valid_urls, url_speeds);
 
-        // 5. 添加到健康管理器
+        // 5. 添加到健康管理器（首选节点只保留刷新后的链接）
         let added_count = filtered_urls.len();
+        let refreshed_urls = filtered_urls.clone();
         {
             let health = url_health.lock().await;
-            health.add_refreshed_urls(filtered_urls, filtered_speeds);
+            match (preferred_index, filtered_urls.first(), filtered_speeds.first()) {
+                (Some(_), Some(url), Some(&speed)) => health.pin_url(url.clone(), speed),
+                _ => health.add_refreshed_urls(filtered_urls, filtered_speeds),
+            }
             info!(
                 "🔗 链接刷新完成，新增/更新 {} 个链接，当前可用: {}",
                 added_count,
                 health.available_count()
             );
         }
+        TransferMetrics::global().record_cdn_refresh();
 
-        Ok(added_count)
+        Ok(refreshed_urls)
     }
 
     /// 筛选高速链接（中位数 × 0.6 阈值）
@@ -2016,7 +2519,7 @@ impl DownloadEngine {
     ///
     /// # 参数
     /// * `engine` - 下载引擎（Arc 包装）
-    /// * `remote_path` - 远程文件路径
+    /// * `target` - 刷新目标（网盘路径或分享文件）
     /// * `total_size` - 文件总大小
     /// * `url_health` - URL 健康管理器
     /// * `download_client` - HTTP 客户端
@@ -2028,7 +2531,7 @@ impl DownloadEngine {
     /// tokio task handle
     pub fn start_periodic_refresh(
         engine: Arc<DownloadEngine>,
-        remote_path: String,
+        target: LinkRefreshTarget,
         total_size: u64,
         url_health: Arc<Mutex<UrlHealthManager>>,
         download_client: Client,
@@ -2062,10 +2565,11 @@ impl DownloadEngine {
 
                     match engine
                         .refresh_download_links(
-                            &remote_path,
+                            &target,
                             total_size,
                             &url_health,
                             &download_client,
+                            false,
                         )
                         .await
                     {
@@ -2093,7 +2597,7 @@ impl DownloadEngine {
     ///
     /// # 参数
     /// * `engine` - 下载引擎（Arc 包装）
-    /// * `remote_path` - 远程文件路径
+    /// * `target` - 刷新目标（网盘路径或分享文件）
     /// * `total_size` - 文件总大小
     /// * `url_health` - URL 健康管理器
     /// * `chunk_scheduler` - 分片调度器（用于获取全局速度）
@@ -2106,7 +2610,7 @@ impl DownloadEngine {
     /// tokio task handle
     pub fn start_speed_anomaly_detection(
         engine: Arc<DownloadEngine>,
-        remote_path: String,
+        target: LinkRefreshTarget,
         total_size: u64,
         url_health: Arc<Mutex<UrlHealthManager>>,
         chunk_scheduler: Arc<crate::downloader::ChunkScheduler>,
@@ -2144,10 +2648,11 @@ impl DownloadEngine {
 
                         match engine
                             .refresh_download_links(
-                                &remote_path,
+                                &target,
                                 total_size,
                                 &url_health,
                                 &download_client,
+                                true,
                             )
                             .await
                         {
@@ -2180,7 +2685,7 @@ impl DownloadEngine {
     ///
     /// # 参数
     /// * `engine` - 下载引擎（Arc 包装）
-    /// * `remote_path` - 远程文件路径
+    /// * `target` - 刷新目标（网盘路径或分享文件）
     /// * `total_size` - 文件总大小
     /// * `url_health` - URL 健康管理器
     /// * `download_client` - HTTP 客户端
@@ -2193,7 +2698,7 @@ impl DownloadEngine {
     /// tokio task handle
     pub fn start_stagnation_detection(
         engine: Arc<DownloadEngine>,
-        remote_path: String,
+        target: LinkRefreshTarget,
         total_size: u64,
         url_health: Arc<Mutex<UrlHealthManager>>,
         download_client: Client,
@@ -2239,10 +2744,11 @@ impl DownloadEngine {
 
                         match engine
                             .refresh_download_links(
-                                &remote_path,
+                                &target,
                                 total_size,
                                 &url_health,
                                 &download_client,
+                                true,
                             )
                             .await
                         {
@@ -2263,6 +2769,18 @@ impl DownloadEngine {
         })
     }
 
+    /// 包装获取下载链接失败的错误
+    ///
+    /// 网盘 API 返回可识别的错误（登录失效、文件不存在等）时，将原因附加到错误信息中，
+    /// 便于用户区分需要重新登录还是文件本身的问题
+    fn link_fetch_error(&self, error: anyhow::Error) -> anyhow::Error {
+        self.rate_limit_cooldown.record_error(&error);
+        match NetdiskError::find(&error) {
+            Some(NetdiskError::Unknown(_)) | None => error.context("获取下载链接列表失败"),
+            Some(api_error) => error.context(format!("获取下载链接列表失败: {}", api_error)),
+        }
+    }
+
     /// 格式化文件大小为人类可读格式
     fn format_size(bytes: u64) -> String {
         const KB: u64 = 1024;
@@ -2318,7 +2836,14 @@ impl DownloadEngine {
         }
 
         // 创建文件并预分配空间（不需要锁，因为文件路径唯一）
-        let file = File::create(path).await.context("创建文件失败")?;
+        // 🔥 不截断已有文件：恢复的任务继续写入同一个临时文件，已完成分片的数据得以保留
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .await
+            .context("创建文件失败")?;
         file.set_len(size).await.context("预分配文件空间失败")?;
 
         info!("文件准备完成: {:?}, 大小: {} bytes", path, size);
@@ -2380,8 +2905,9 @@ impl DownloadEngine {
             (0..manager.chunk_count()).collect()
         };
 
-        // 根据分片大小计算超时时间
-        let timeout_secs = Self::calculate_timeout_secs(chunk_size);
+        // 根据分片大小计算超时时间（配置了分片超时时使用配置值）
+        let timeout_secs = crate::common::chunk_timeout_secs()
+            .unwrap_or_else(|| Self::calculate_timeout_secs(chunk_size));
 
         let available_urls_count = {
             let health = url_health.lock().await;
@@ -2463,30 +2989,30 @@ impl DownloadEngine {
                     chunk_index
                 );
 
-                let result = Self::download_chunk_with_retry(
-                    chunk_index,
+                let ctx = ChunkDownloadContext {
                     client,
-                    &cookie,
-                    referer.as_deref(), // 传递 Referer
+                    cookie: &cookie,
+                    referer: referer.as_deref(), // 传递 Referer
                     url_health,
-                    &output_path,
-                    chunk_manager.clone(),
-                    speed_calc.clone(),
-                    task.clone(),
-                    timeout_secs,
+                    output_path: &output_path,
+                    chunk_manager: chunk_manager.clone(),
+                    speed_calc: speed_calc.clone(),
+                    task: task.clone(),
+                    chunk_size: timeout_secs,
                     total_size,
                     cancellation_token,
-                    "usize".parse()?,
-                    None, // ws_manager（独立模式不需要）
-                    None, // progress_throttler（独立模式不需要）
-                    String::new(), // task_id（独立模式不需要）
-                    None, // folder_progress_tx（独立模式不需要）
-                    None, // backup_notification_tx（独立模式不需要）
-                    None, // slot_touch_throttler（独立模式不需要）
-                    3,    // max_retries（独立模式使用默认值）
-                    None, // fallback_mgr（独立模式不需要）
-                )
-                    .await;
+                    ws_manager: None, // 独立模式不需要
+                    progress_throttler: None, // 独立模式不需要
+                    task_id: String::new(), // 独立模式不需要
+                    folder_progress_tx: None, // 独立模式不需要
+                    backup_notification_tx: None, // 独立模式不需要
+                    slot_touch_throttler: None, // 独立模式不需要
+                    retry_config: RetryConfig::default().with_fallback_attempts(3), // 独立模式使用默认值
+                    fallback_mgr: None, // 独立模式不需要
+                    global_speed_limiter: None, // 独立模式不需要
+                };
+                let result =
+                    Self::download_chunk_with_retry(ctx, chunk_index, "usize".parse()?).await;
 
                 drop(permit); // 🔥 释放 permit，其他等待的分片可以使用
 
@@ -2567,47 +3093,37 @@ impl DownloadEngine {
     /// - 成功下载后记录链接成功，给链接"恢复"的机会
     ///
     /// # 参数
+    /// * `ctx` - 分片下载上下文（同一任务的所有分片共享）
     /// * `chunk_index` - 分片索引
-    /// * `client` - HTTP 客户端
-    /// * `cookie` - Cookie 字符串
-    /// * `referer` - Referer 头（如果存在），用于 Range 请求避免 403 Forbidden
-    /// * `url_health` - URL 健康管理器，用于动态管理可用链接
-    /// * `output_path` - 输出文件路径
-    /// * `chunk_manager` - 分片管理器
-    /// * `speed_calc` - 速度计算器
-    /// * `task` - 下载任务
-    /// * `chunk_size` - 分片大小（用于动态计算超时）
-    /// * `total_size` - 文件总大小（用于探测恢复链接）
-    /// * `cancellation_token` - 取消令牌（用于中断下载）
     /// * `chunk_thread_id` - 分片线程ID（用于日志）
-    /// * `ws_manager` - WebSocket 管理器（可选，用于发布进度事件）
-    /// * `progress_throttler` - 进度节流器（可选，200ms 间隔）
-    /// * `task_id` - 任务 ID（用于进度事件）
-    /// * `folder_progress_tx` - 文件夹进度通知发送器（可选，仅文件夹子任务需要）
-    /// * `backup_notification_tx` - 备份任务统一通知发送器（可选，仅备份任务需要）
     pub async fn download_chunk_with_retry(
+        ctx: ChunkDownloadContext<'_>,
         chunk_index: usize,
-        client: Client,
-        cookie: &str,
-        referer: Option<&str>,
-        url_health: Arc<Mutex<UrlHealthManager>>,
-        output_path: &Path,
-        chunk_manager: Arc<Mutex<ChunkManager>>,
-        speed_calc: Arc<Mutex<SpeedCalculator>>,
-        task: Arc<Mutex<DownloadTask>>,
-        chunk_size: u64,
-        total_size: u64,
-        cancellation_token: CancellationToken,
         chunk_thread_id: usize,
-        ws_manager: Option<Arc<WebSocketManager>>,
-        progress_throttler: Option<Arc<ProgressThrottler>>,
-        task_id: String,
-        folder_progress_tx: Option<mpsc::UnboundedSender<String>>,
-        backup_notification_tx: Option<mpsc::UnboundedSender<BackupTransferNotification>>,
-        slot_touch_throttler: Option<Arc<crate::task_slot_pool::SlotTouchThrottler>>,
-        max_retries: u32,
-        fallback_mgr: Option<Arc<crate::common::ProxyFallbackManager>>,
     ) -> Result<()> {
+        let ChunkDownloadContext {
+            client,
+            cookie,
+            referer,
+            url_health,
+            output_path,
+            chunk_manager,
+            speed_calc,
+            task,
+            chunk_size,
+            total_size,
+            cancellation_token,
+            ws_manager,
+            progress_throttler,
+            task_id,
+            folder_progress_tx,
+            backup_notification_tx,
+            slot_touch_throttler,
+            retry_config,
+            fallback_mgr,
+            global_speed_limiter,
+        } = ctx;
+
         // 记录尝试过的链接（避免在同一次重试循环中重复尝试同一个链接）
         let mut tried_urls = std::collections::HashSet::new();
         let mut retries = 0;
@@ -2651,7 +3167,7 @@ impl DownloadEngine {
                             .get_url_hybrid(chunk_index)
                             .or_else(|| {
                                 let url_index = chunk_index % count;
-                                health.get_url(url_index).map(|s| s.clone())
+                                health.get_url(url_index)
                             })
                             .ok_or_else(|| anyhow::anyhow!("无法获取 URL"))?
                     }
@@ -2665,7 +3181,7 @@ impl DownloadEngine {
                             .get_url_hybrid(chunk_index)
                             .or_else(|| {
                                 let url_index = chunk_index % count;
-                                health.get_url(url_index).map(|s| s.clone())
+                                health.get_url(url_index)
                             })
                             .ok_or_else(|| anyhow::anyhow!("无法获取 URL"))?
                     } else {
@@ -2675,7 +3191,7 @@ impl DownloadEngine {
                             let index = (chunk_index + i) % count;
                             if let Some(url) = health.get_url(index) {
                                 if !tried_urls.contains(url.as_str()) {
-                                    found_url = Some(url.clone());
+                                    found_url = Some(url);
                                     break;
                                 }
                             }
@@ -2684,8 +3200,13 @@ impl DownloadEngine {
                     }
                 };
 
-                // 🔥 动态计算超时时间（基于 EWMA 速度和分片大小）
-                let timeout = health.calculate_timeout(&url, chunk_size);
+                // 🔥 单主机并发限制：所在主机已满时分散到其他主机，并占用主机名额
+                let url = health.spread_across_hosts(url, &tried_urls);
+                health.acquire_host(&url);
+
+                // 🔥 动态计算超时时间（基于 EWMA 速度和分片大小），配置了分片超时时使用配置值
+                let timeout = crate::common::chunk_timeout_secs()
+                    .unwrap_or_else(|| health.calculate_timeout(&url, chunk_size));
 
                 (count, url, timeout)
             };
@@ -2716,24 +3237,45 @@ impl DownloadEngine {
             let backup_notification_tx_clone = backup_notification_tx.clone();
             // 🔥 使用任务级共享槽位刷新节流器（由调用方传入，所有分片共享同一实例）
             let slot_touch_throttler_clone = slot_touch_throttler.clone();
+            let global_speed_limiter_clone = global_speed_limiter.clone();
+            // 🔥 本次请求中限速等待的累计时长（毫秒），记录链接速度时扣除
+            let throttled_ms = Arc::new(AtomicU64::new(0));
+            let throttled_ms_clone = throttled_ms.clone();
             let progress_callback = move |bytes: u64| {
                 // 使用 tokio::task::block_in_place 在同步闭包中执行异步操作
                 tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async {
                         // 更新任务已下载大小，并获取 group_id 和 is_backup
-                        let (downloaded_size, speed, group_id, is_backup) = {
+                        let (downloaded_size, speed, eta_secs, group_id, is_backup, cdn_host, task_delay) = {
                             let mut t = task_clone.lock().await;
                             // 🔥 修复：限制 downloaded_size 不超过 total_size，防止断点续传时重复累加
                             let new_size = t.downloaded_size.saturating_add(bytes);
                             t.downloaded_size = std::cmp::min(new_size, t.total_size);
                             let downloaded = t.downloaded_size;
 
+                            TransferMetrics::global().add_downloaded_bytes(bytes);
+
                             // 更新速度计算器
                             let mut calc = speed_calc_clone.lock().await;
                             calc.add_sample(bytes);
                             t.speed = calc.speed();
-
-                            (downloaded, t.speed, t.group_id.clone(), t.is_backup)
+                            let eta_secs = t.update_eta();
+
+                            // 🔥 单任务限速：速度计算器由任务所有分片共享，按任务聚合速度计算等待时长
+                            let delay = t
+                                .speed_limit_bytes_per_sec
+                                .map(|limit| calc.throttle_delay(limit))
+                                .unwrap_or_default();
+
+                            (
+                                downloaded,
+                                t.speed,
+                                eta_secs,
+                                t.group_id.clone(),
+                                t.is_backup,
+                                t.cdn_host.clone(),
+                                delay,
+                            )
                         };
 
                         // 🔧 克隆一个临时变量用于 send
@@ -2766,6 +3308,8 @@ impl DownloadEngine {
                                             total_size: total_size_clone,
                                             speed,
                                             progress,
+                                            eta_secs,
+                                            cdn_host,
                                             group_id: group_id.clone(),
                                             is_backup,
                                         }),
@@ -2794,6 +3338,19 @@ impl DownloadEngine {
                                 }
                             }
                         }
+
+                        // 🔥 全局限速：所有任务共享令牌桶，取单任务与全局限速中较长的等待时长
+                        let global_delay = global_speed_limiter_clone
+                            .as_ref()
+                            .map(|limiter| limiter.acquire_delay(bytes))
+                            .unwrap_or_default();
+                        let throttle_delay = task_delay.max(global_delay);
+
+                        // 🔥 超出限速时暂缓读取（锁已释放，不影响其他分片更新进度）
+                        if !throttle_delay.is_zero() {
+                            tokio::time::sleep(throttle_delay).await;
+                            throttled_ms_clone.fetch_add(throttle_delay.as_millis() as u64, Ordering::Relaxed);
+                        }
                     })
                 });
             };
@@ -2803,12 +3360,13 @@ impl DownloadEngine {
 
             // 尝试下载
             // 读取超时：取连接超时的一半，钳位到 [30, 90] 秒
-            // timeout_secs（30-180s）是整个分片的连接+传输超时，
-            // 但 read_timeout 针对的是"单次 stream.next() 零字节到达"的场景，
-            // 不需要那么长，否则挂起的 CDN 连接要等 180s 才能被发现
+            // timeout_secs（30-180s）只限制收到响应头之前的等待，
+            // 数据传输阶段由 read_timeout 空闲超时把关（无数据或只有零星字节到达的场景），
+            // 不需要那么长，否则挂起的 CDN 连接要等 180s 才能被发现；
+            // 限速等待在两次读取之间进行，不计入任何超时
             let read_timeout = (timeout_secs / 2).clamp(30, 90);
 
-            match chunk
+            let download_result = chunk
                 .download(
                     &client,
                     cookie,
@@ -2821,13 +3379,19 @@ impl DownloadEngine {
                     &cancellation_token,
                     progress_callback,
                 )
-                .await
-            {
+                .await;
+
+            // 🔥 请求结束，归还主机名额
+            url_health.lock().await.release_host(&current_url);
+
+            match download_result {
                 Ok(bytes_downloaded) => {
                     // ✅ 下载成功
 
-                    // 计算下载耗时
-                    let duration_ms = download_start.elapsed().as_millis() as u64;
+                    // 计算下载耗时（扣除限速等待，链接评分和超时计算按链接实际传输速度进行，
+                    // 避免限速使链接被误判为慢速而降权、超时被无谓放大）
+                    let duration_ms = (download_start.elapsed().as_millis() as u64)
+                        .saturating_sub(throttled_ms.load(Ordering::Relaxed));
 
                     // 记录分片速度（动态权重调整,使用score机制）
                     {
@@ -2842,6 +3406,25 @@ impl DownloadEngine {
                         );
                     }
 
+                    // 🔥 记录实际提供下载的 CDN 主机：仅在已记录的节点不再可用（链接刷新或故障切换）时更新，
+                    // 多个链接轮流下载分片时不会每个分片都改写
+                    {
+                        let host = UrlHealthManager::host_of(&current_url);
+                        let previous = task.lock().await.cdn_host.clone();
+                        let switched = match previous.as_deref() {
+                            None => true,
+                            Some(prev) if prev != host => {
+                                !url_health.lock().await.has_available_host(prev)
+                            }
+                            Some(_) => false,
+                        };
+                        if switched && task.lock().await.set_cdn_host(host.clone()) {
+                            if let Some(previous) = previous {
+                                debug!("🌐 任务 {} CDN 节点切换: {} -> {}", task_id, previous, host);
+                            }
+                        }
+                    }
+
                     // 更新分片状态
                     {
                         let mut manager = chunk_manager.lock().await;
@@ -2903,10 +3486,12 @@ impl DownloadEngine {
                         }
                     }
 
+                    // 🔥 不可恢复错误（本地磁盘写满、权限不足等）换链接也无法恢复，直接返回
+                    let is_fatal = !DownloadErrorKind::classify(&e).is_retryable();
                     last_error = Some(e);
 
                     // 🔥 分片内断点续传：区分"有数据"和"零数据"两种失败
-                    if bytes_this_attempt > 0 {
+                    if bytes_this_attempt > 0 && !is_fatal {
                         // ✅ 有部分数据：链接本身可用，只是被限速/断流
                         // 不递增 retries，不切换链接，从断点继续
                         // tried_urls 中已有当前 URL，需要移除以允许复用
@@ -2928,7 +3513,10 @@ impl DownloadEngine {
                         retries += 1;
 
                         // 检查是否达到重试次数上限，或所有链接都已尝试过
-                        if retries >= max_retries || tried_urls.len() >= available_count {
+                        if is_fatal
+                            || retries >= retry_config.max_attempts
+                            || tried_urls.len() >= available_count
+                        {
                             error!(
                                 "[分片线程{}] ✗ 分片 #{} 下载失败，已尝试 {} 个链接，重试 {} 次",
                                 chunk_thread_id,
@@ -2947,12 +3535,12 @@ impl DownloadEngine {
                             tried_urls.len(),
                             available_count,
                             retries,
-                            max_retries,
+                            retry_config.max_attempts,
                             last_error
                         );
 
-                        // 🔥 使用指数退避延迟重试（100ms → 200ms → 400ms → ...）
-                        let backoff_ms = calculate_backoff_delay(retries);
+                        // 🔥 使用指数退避延迟重试（可配置初始/最大延迟及随机抖动）
+                        let backoff_ms = retry_config.delay_ms(retries);
                         debug!(
                             "[分片线程{}] ⏳ 分片 #{} 等待 {}ms 后重试",
                             chunk_thread_id, chunk_index, backoff_ms
@@ -2990,6 +3578,7 @@ mod tests {
             bdstoken: Some("mock_bdstoken".to_string()),
             login_time: 0,
             last_warmup_at: None,
+            devuid: None,
         }
     }
 
@@ -2999,4 +3588,247 @@ mod tests {
         let engine = DownloadEngine::new(user_auth);
         assert_eq!(engine.vip_type as u32, 2); // SVIP
     }
+
+    #[test]
+    fn test_url_health_pin_preferred_url() {
+        let urls = vec!["https://a/file".to_string(), "https://b/file".to_string()];
+        let health = UrlHealthManager::new(urls, vec![100.0, 200.0]);
+        assert_eq!(health.dlink_prefer(), None);
+        health.set_dlink_prefer(1); // 未指定首选节点时忽略
+        assert_eq!(health.dlink_prefer(), None);
+
+        let health = health.with_dlink_prefer(Some(0));
+        health.set_dlink_prefer(1);
+        assert_eq!(health.dlink_prefer(), Some(1));
+
+        // 刷新后只保留首选链接，且不再向其他链接分配 warm 流量
+        health.pin_url("https://c/file".to_string(), 300.0);
+        assert_eq!(health.all_available_urls(), vec!["https://c/file".to_string()]);
+        assert!(health.get_warm_url().is_none());
+
+        health.pin_url("https://b/file".to_string(), 150.0);
+        assert_eq!(health.all_available_urls(), vec!["https://b/file".to_string()]);
+
+        // 刷新后原节点不再可用，CDN 主机记录才会切换
+        assert!(health.has_available_host("b"));
+        assert!(!health.has_available_host("a"));
+    }
+
+    #[test]
+    fn test_url_health_spread_chunks_across_hosts() {
+        let urls = vec![
+            "https://fast.cdn/file?sign=1".to_string(),
+            "https://fast.cdn/file?sign=2".to_string(),
+            "https://slow.cdn/file?sign=3".to_string(),
+        ];
+        let mut health = UrlHealthManager::new(urls, vec![1000.0, 1000.0, 10.0]);
+
+        // 未限制时不改变选择
+        assert_eq!(health.host_chunk_capacity(), None);
+        let tried = std::collections::HashSet::new();
+        let first = health.get_url_hybrid(0).unwrap();
+        assert_eq!(health.spread_across_hosts(first.clone(), &tried), first);
+
+        health.set_host_chunk_limit(Arc::new(AtomicUsize::new(2)));
+        assert_eq!(health.host_chunk_capacity(), Some(4));
+
+        // 模拟 4 个并发分片：加权选择偏向快主机，满额后分散到慢主机
+        let mut selected = Vec::new();
+        for chunk_index in 0..4 {
+            let url = health.get_url_hybrid(chunk_index).unwrap();
+            let url = health.spread_across_hosts(url, &tried);
+            health.acquire_host(&url);
+            selected.push(url);
+        }
+        assert_eq!(health.host_active_count("https://fast.cdn/x"), 2);
+        assert_eq!(health.host_active_count("https://slow.cdn/x"), 2);
+        assert_eq!(
+            selected.iter().filter(|url| url.starts_with("https://slow.cdn")).count(),
+            2
+        );
+
+        // 快主机释放名额后重新优先使用
+        health.release_host(&selected[0]);
+        let url = health.get_url_hybrid(0).unwrap();
+        assert!(health.spread_across_hosts(url, &tried).starts_with("https://fast.cdn"));
+    }
+
+    #[test]
+    fn test_remote_fingerprint_from_headers() {
+        use reqwest::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, CONTENT_RANGE, ETAG};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-65535/1234567"));
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("65536"));
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        let fingerprint = RemoteFingerprint::from_headers(reqwest::StatusCode::PARTIAL_CONTENT, &headers);
+        assert_eq!(fingerprint.size, Some(1234567));
+        assert_eq!(fingerprint.etag.as_deref(), Some("\"abc\""));
+
+        // 不支持 Range 时 Content-Length 即文件总大小
+        let fingerprint = RemoteFingerprint::from_headers(reqwest::StatusCode::OK, &headers);
+        assert_eq!(fingerprint.size, Some(65536));
+
+        // 总长度未知
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-65535/*"));
+        let fingerprint = RemoteFingerprint::from_headers(reqwest::StatusCode::PARTIAL_CONTENT, &headers);
+        assert_eq!(fingerprint, RemoteFingerprint::default());
+    }
+
+    #[tokio::test]
+    async fn test_until_cancelled_aborts_slow_locate() {
+        let token = CancellationToken::new();
+        let dropped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        // 模拟卡住的 locate 请求：future 被丢弃时记录下来
+        struct DropFlag(Arc<std::sync::atomic::AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+        let flag = DropFlag(dropped.clone());
+        let slow_locate = async move {
+            let _flag = flag;
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok::<Vec<String>, anyhow::Error>(vec!["https://a/file".to_string()])
+        };
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = DownloadEngine::until_cancelled(&token, slow_locate).await;
+        assert!(result.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(dropped.load(Ordering::SeqCst));
+
+        // 已取消的令牌不再等待新的请求；未取消时正常返回结果
+        assert!(DownloadEngine::until_cancelled(&token, async { 1 }).await.is_none());
+        let fresh = CancellationToken::new();
+        assert_eq!(DownloadEngine::until_cancelled(&fresh, async { 1 }).await, Some(1));
+    }
+
+    /// 模拟链接来源：locate 和分享目录都返回同一个本地 CDN 地址，并记录调用
+    struct MockLinkSource {
+        url: String,
+        calls: StdMutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DownloadLinkSource for MockLinkSource {
+        async fn locate(&self, remote_path: &str) -> Result<Vec<String>> {
+            self.calls.lock().unwrap().push(format!("locate:{}", remote_path));
+            Ok(vec![self.url.clone()])
+        }
+
+        async fn list_share_dir(
+            &self,
+            _source: &crate::transfer::ShareDirectSource,
+            dir: Option<&str>,
+            _page: u32,
+            _num: u32,
+        ) -> Result<Vec<crate::transfer::SharedFileInfo>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("share:{}", dir.unwrap_or("<root>")));
+            Ok(vec![crate::transfer::SharedFileInfo {
+                fs_id: 7,
+                is_dir: false,
+                path: "/share/a.txt".to_string(),
+                size: 4,
+                name: "a.txt".to_string(),
+                dlink: Some(self.url.clone()),
+                md5: None,
+            }])
+        }
+    }
+
+    /// 启动本地 CDN：对任意请求返回 206 和 4 字节数据
+    async fn spawn_mock_cdn() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 206 Partial Content\r\nContent-Length: 4\r\nContent-Range: bytes 0-3/4\r\nConnection: close\r\n\r\ndata",
+                        )
+                        .await;
+                });
+            }
+        });
+        format!("http://{}/file", addr)
+    }
+
+    fn refresh_handles(target: LinkRefreshTarget) -> TaskRefreshHandles {
+        TaskRefreshHandles {
+            target,
+            total_size: 4,
+            url_health: Arc::new(Mutex::new(UrlHealthManager::new(
+                vec!["http://127.0.0.1:1/expired".to_string()],
+                vec![100.0],
+            ))),
+            client: Client::new(),
+            refresh_coordinator: Arc::new(RefreshCoordinator::new(RefreshCoordinatorConfig::default())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_force_refresh_with_mock_link_source() {
+        let engine = DownloadEngine::new(create_mock_user_auth());
+        let url = spawn_mock_cdn().await;
+        let links = MockLinkSource {
+            url: url.clone(),
+            calls: StdMutex::new(Vec::new()),
+        };
+
+        // 网盘文件：按路径 locate，新链接探测通过后加入健康管理器
+        let handles = refresh_handles(LinkRefreshTarget::Netdisk {
+            remote_path: "/my/a.txt".to_string(),
+        });
+        let count = engine.force_refresh_with("task-1", &handles, &links).await.unwrap();
+        assert_eq!(count, 1);
+        {
+            // 旧链接被停用，分片线程下一次选择链接时即使用刷新后的链接
+            let health = handles.url_health.lock().await;
+            assert_eq!(health.all_available_urls(), vec![url.clone()]);
+            assert_eq!(health.available_count(), 1);
+            assert_eq!(health.get_url(0), Some(url.clone()));
+            assert_eq!(health.get_url_hybrid(0), Some(url.clone()));
+        }
+        assert_eq!(*links.calls.lock().unwrap(), vec!["locate:/my/a.txt".to_string()]);
+
+        // 最小刷新间隔内再次手动刷新被拒绝
+        let err = engine.force_refresh_with("task-1", &handles, &links).await.unwrap_err();
+        assert!(err.to_string().contains("过于频繁"), "{}", err);
+
+        // 免转存分享文件：重新列出分享目录，不按分享者的路径 locate
+        links.calls.lock().unwrap().clear();
+        let handles = refresh_handles(LinkRefreshTarget::Share {
+            source: crate::transfer::ShareDirectSource {
+                short_key: "1abc".to_string(),
+                shareid: "1".to_string(),
+                uk: "2".to_string(),
+                bdstoken: "token".to_string(),
+                sekey: None,
+            },
+            remote_path: "/share/a.txt".to_string(),
+            fs_id: 7,
+        });
+        let count = engine.force_refresh_with("task-2", &handles, &links).await.unwrap();
+        assert_eq!(count, 1);
+        assert!(handles.url_health.lock().await.all_available_urls().contains(&url));
+        assert_eq!(*links.calls.lock().unwrap(), vec!["share:/share".to_string()]);
+    }
 }
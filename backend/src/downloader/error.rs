@@ -0,0 +1,142 @@
+//! 下载错误分类
+//!
+//! 分片级重试与任务级重试共用同一套分类规则：
+//! - 分片级：遇到不可恢复错误时不再切换链接重试
+//! - 任务级：分片重试耗尽后，仅对可恢复错误重新获取链接并重新调度整个任务
+//!
+//! 网盘 API 错误（`NetdiskError`）同样参与分类：登录失效等需要用户处理的错误不重试
+
+use std::fmt;
+use std::io;
+
+use crate::netdisk::NetdiskError;
+
+/// 分片请求返回的非 206 HTTP 状态
+///
+/// 显示格式与原有错误信息保持一致（"HTTP错误: 403"），便于错误分类时向下转型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpStatusError(pub u16);
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match reqwest::StatusCode::from_u16(self.0) {
+            Ok(status) => write!(f, "HTTP错误: {}", status),
+            Err(_) => write!(f, "HTTP错误: {}", self.0),
+        }
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// 下载错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadErrorKind {
+    /// 临时性错误（网络超时、连接中断、5xx 等），重试即可恢复
+    Transient,
+    /// 触发百度限流（频控 errno 或 HTTP 429），稍后重试可恢复，频繁命中时进入全局冷却
+    RateLimited,
+    /// 下载链接失效（401/403/404/410），重新获取链接后可恢复
+    LinkExpired,
+    /// 不可恢复错误（本地磁盘写满、权限不足、请求范围无效等），重试无意义
+    Fatal,
+}
+
+impl DownloadErrorKind {
+    /// 沿错误链分类，未识别的错误按临时性错误处理
+    pub fn classify(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(status) = cause.downcast_ref::<HttpStatusError>() {
+                return Self::from_http_status(status.0);
+            }
+            if let Some(api_error) = cause.downcast_ref::<NetdiskError>() {
+                return Self::from_netdisk_error(*api_error);
+            }
+            if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                if Self::is_local_io_error(io_error.kind()) {
+                    return Self::Fatal;
+                }
+            }
+        }
+        Self::Transient
+    }
+
+    /// 按 HTTP 状态码分类
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            401 | 403 | 404 | 410 => Self::LinkExpired,
+            416 => Self::Fatal,
+            429 => Self::RateLimited,
+            _ => Self::Transient,
+        }
+    }
+
+    /// 按网盘 API 错误分类（获取下载链接时返回）
+    ///
+    /// 登录失效、需要验证、文件不存在、空间不足都需要用户处理，重试无意义
+    pub fn from_netdisk_error(error: NetdiskError) -> Self {
+        match error {
+            NetdiskError::RateLimited => Self::RateLimited,
+            NetdiskError::Unknown(_) => Self::Transient,
+            NetdiskError::AuthExpired
+            | NetdiskError::NeedVerify
+            | NetdiskError::NotFound
+            | NetdiskError::QuotaExceeded
+            | NetdiskError::UploadIdExpired => Self::Fatal,
+        }
+    }
+
+    /// 是否值得重试
+    pub fn is_retryable(self) -> bool {
+        self != Self::Fatal
+    }
+
+    /// 本地文件系统错误（磁盘满、权限不足、文件被删除等），换链接或重新调度都无法恢复
+    fn is_local_io_error(kind: io::ErrorKind) -> bool {
+        matches!(
+            kind,
+            io::ErrorKind::NotFound
+                | io::ErrorKind::PermissionDenied
+                | io::ErrorKind::StorageFull
+                | io::ErrorKind::ReadOnlyFilesystem
+                | io::ErrorKind::QuotaExceeded
+                | io::ErrorKind::FileTooLarge
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classify() {
+        let expired = anyhow::Error::new(HttpStatusError(403));
+        assert_eq!(expired.to_string(), "HTTP错误: 403 Forbidden");
+        assert_eq!(DownloadErrorKind::classify(&expired), DownloadErrorKind::LinkExpired);
+
+        let server_error = anyhow::Error::new(HttpStatusError(503));
+        assert_eq!(DownloadErrorKind::classify(&server_error), DownloadErrorKind::Transient);
+
+        let range_error = anyhow::Error::new(HttpStatusError(416));
+        assert!(!DownloadErrorKind::classify(&range_error).is_retryable());
+
+        // 包装在 context 中的本地 IO 错误同样识别为不可恢复
+        let disk_full: anyhow::Result<()> = Err(io::Error::from(io::ErrorKind::StorageFull).into());
+        let disk_full = disk_full.context("写入文件失败").unwrap_err();
+        assert_eq!(DownloadErrorKind::classify(&disk_full), DownloadErrorKind::Fatal);
+
+        // 网盘 API 错误：登录失效不重试，限流可重试
+        let auth = NetdiskError::api_error(-6, "百度 API 错误 -6: 身份验证失败");
+        assert_eq!(DownloadErrorKind::classify(&auth), DownloadErrorKind::Fatal);
+        let limited = NetdiskError::api_error(31034, "百度 API 错误 31034: 命中频控");
+        assert_eq!(DownloadErrorKind::classify(&limited), DownloadErrorKind::RateLimited);
+        assert!(DownloadErrorKind::classify(&limited).is_retryable());
+        let too_many = anyhow::Error::new(HttpStatusError(429));
+        assert_eq!(DownloadErrorKind::classify(&too_many), DownloadErrorKind::RateLimited);
+
+        let reset = anyhow::Error::new(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(DownloadErrorKind::classify(&reset), DownloadErrorKind::Transient);
+        assert!(DownloadErrorKind::classify(&anyhow::anyhow!("读取超时")).is_retryable());
+    }
+}
@@ -23,6 +23,17 @@ pub enum FolderStatus {
     Cancelled,
 }
 
+/// 平铺下载时不同子目录下同名文件的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlattenCollisionPolicy {
+    /// 追加序号："a.txt" -> "a (1).txt"
+    #[default]
+    Rename,
+    /// 以所在子目录路径作为前缀："sub/dir/a.txt" -> "sub_dir_a.txt"
+    PrefixPath,
+}
+
 /// 待下载的文件信息（扫描结果）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PendingFile {
@@ -31,6 +42,9 @@ pub struct PendingFile {
     pub remote_path: String,
     pub relative_path: String,
     pub size: u64,
+    /// 网盘文件 MD5（跳过策略下用于校验已有文件）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
 }
 
 /// 文件夹下载任务组
@@ -105,6 +119,14 @@ pub struct FolderDownload {
     #[serde(default, skip)]
     pub conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
 
+    /// 平铺下载：所有文件直接放在 local_root 下，不重建远程目录结构（None 表示保留目录结构）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flatten: Option<FlattenCollisionPolicy>,
+
+    /// 平铺下载已分配的文件名（小写，用于同名检测）
+    #[serde(default, skip)]
+    pub flattened_names: HashSet<String>,
+
     /// 🔥 已成功完成的子任务累计字节数（运行时字段，单调递增）
     /// 每当一个子任务成功完成时 += file_size，失败的任务不计入
     /// downloaded_size = max(downloaded_size, completed_downloaded_size + active_sum)
@@ -120,6 +142,11 @@ pub struct FolderDownload {
     /// 避免同一任务多次失败时重复计数；重试成功时从此集合移除并减少 failed_count
     #[serde(default, skip)]
     pub failed_task_ids: HashSet<String>,
+
+    /// 🔥 完成后 MD5 校验通过的子任务数（运行时字段，重启后从 0 开始）
+    /// 用于判断文件夹内容是否全部经过完整性校验
+    #[serde(default, skip)]
+    pub md5_verified_count: u64,
 }
 
 impl FolderDownload {
@@ -161,9 +188,12 @@ impl FolderDownload {
             encrypted_folder_mappings: HashMap::new(),
             counted_task_ids: HashSet::new(),
             conflict_strategy: None,
+            flatten: None,
+            flattened_names: HashSet::new(),
             completed_downloaded_size: 0,
             failed_count: 0,
             failed_task_ids: HashSet::new(),
+            md5_verified_count: 0,
         }
     }
 
@@ -205,6 +235,34 @@ impl FolderDownload {
         self.status = FolderStatus::Cancelled;
     }
 
+    /// 计算文件在本地的相对路径
+    ///
+    /// 未启用平铺时原样返回；启用时只保留文件名，与已分配的文件名冲突（不区分大小写）时
+    /// 按 flatten 策略重命名，仍冲突则追加序号
+    pub fn local_relative_path(&mut self, relative_path: &str) -> String {
+        let Some(policy) = self.flatten else {
+            return relative_path.to_string();
+        };
+
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        let mut candidate = file_name.to_string();
+        if self.flattened_names.contains(&candidate.to_lowercase())
+            && policy == FlattenCollisionPolicy::PrefixPath
+        {
+            candidate = relative_path.replace('/', "_");
+        }
+
+        let mut index = 1;
+        let base = candidate.clone();
+        while self.flattened_names.contains(&candidate.to_lowercase()) {
+            candidate = numbered_file_name(&base, index);
+            index += 1;
+        }
+
+        self.flattened_names.insert(candidate.to_lowercase());
+        candidate
+    }
+
     /// 🔥 计算并更新 downloaded_size：已完成累计 + 当前活跃子任务已下载
     ///
     /// 使用 max() 保证单调性，即使完成通知和进度通知乱序也不会丢字节
@@ -215,6 +273,14 @@ impl FolderDownload {
     }
 }
 
+/// 在扩展名前追加序号："a.txt" -> "a (1).txt"，无扩展名或隐藏文件直接追加
+fn numbered_file_name(name: &str, index: usize) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{} ({}){}", &name[..dot], index, &name[dot..]),
+        _ => format!("{} ({})", name, index),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,4 +358,36 @@ mod tests {
         // computed = 1000 + 0 = 1000，但 max(1500, 1000) = 1500，不回退
         assert_eq!(folder.compute_downloaded_size(0), 1500);
     }
+
+    #[test]
+    fn test_local_relative_path_preserves_structure_by_default() {
+        let mut folder = FolderDownload::new("/test".to_string(), PathBuf::from("./test"));
+        assert_eq!(folder.local_relative_path("a/b/c.txt"), "a/b/c.txt");
+        assert!(folder.flattened_names.is_empty());
+    }
+
+    #[test]
+    fn test_local_relative_path_flatten_rename() {
+        let mut folder = FolderDownload::new("/test".to_string(), PathBuf::from("./test"));
+        folder.flatten = Some(FlattenCollisionPolicy::Rename);
+
+        assert_eq!(folder.local_relative_path("a/cover.jpg"), "cover.jpg");
+        assert_eq!(folder.local_relative_path("b/Cover.JPG"), "Cover (1).JPG");
+        assert_eq!(folder.local_relative_path("c/cover.jpg"), "cover (2).jpg");
+        assert_eq!(folder.local_relative_path("a/README"), "README");
+        assert_eq!(folder.local_relative_path("b/README"), "README (1)");
+        assert_eq!(folder.local_relative_path(".env"), ".env");
+        assert_eq!(folder.local_relative_path("x/.env"), ".env (1)");
+    }
+
+    #[test]
+    fn test_local_relative_path_flatten_prefix_path() {
+        let mut folder = FolderDownload::new("/test".to_string(), PathBuf::from("./test"));
+        folder.flatten = Some(FlattenCollisionPolicy::PrefixPath);
+
+        assert_eq!(folder.local_relative_path("s1/e01.mkv"), "e01.mkv");
+        assert_eq!(folder.local_relative_path("s2/e01.mkv"), "s2_e01.mkv");
+        // 前缀后仍冲突时追加序号
+        assert_eq!(folder.local_relative_path("s2_e01.mkv"), "s2_e01 (1).mkv");
+    }
 }
@@ -1,7 +1,9 @@
 //! 文件夹下载管理器
 
 use crate::autobackup::record::BackupRecordManager;
-use crate::downloader::{DownloadManager, DownloadTask, TaskStatus};
+use crate::downloader::{
+    DownloadManager, DownloadTask, FlattenCollisionPolicy, SubtaskCompletion, TaskStatus,
+};
 use crate::netdisk::NetdiskClient;
 use crate::server::events::{FolderEvent, TaskEvent};
 use crate::server::websocket::WebSocketManager;
@@ -15,8 +17,7 @@ use tracing::{error, info, warn};
 
 use super::folder::{FolderDownload, FolderStatus, PendingFile};
 use crate::persistence::{
-    delete_folder as delete_folder_persistence, load_all_folders,
-    remove_folder_from_history, remove_tasks_by_group_from_history, save_folder, FolderPersisted,
+    delete_folder as delete_folder_persistence, load_all_folders, save_folder, FolderPersisted,
     PersistenceManager,
 };
 
@@ -62,6 +63,19 @@ impl FolderDownloadManager {
         }
     }
 
+    /// 🔥 清空内存中的文件夹（切换账号时调用，文件夹已持久化在旧账号的 WAL 目录中）
+    ///
+    /// 取消进行中的扫描，返回清空的文件夹数
+    pub async fn clear_folders(&self) -> usize {
+        for (_, token) in self.cancellation_tokens.write().await.drain() {
+            token.cancel();
+        }
+        let mut folders = self.folders.write().await;
+        let count = folders.len();
+        folders.clear();
+        count
+    }
+
     /// 设置持久化管理器
     pub async fn set_persistence_manager(&self, pm: Arc<tokio::sync::Mutex<PersistenceManager>>) {
         let mut pm_guard = self.persistence_manager.write().await;
@@ -584,6 +598,17 @@ impl FolderDownloadManager {
                     }
                 }
 
+                // 🔥 本地去重命中时直接按已完成计数，不再创建下载任务
+                if download_manager
+                    .reuse_local_duplicate(&local_path, pending_file.size, pending_file.md5.as_deref())
+                    .await
+                {
+                    download_manager
+                        .notify_subtask_skipped(folder_id.clone(), format!("dedup-{}", uuid::Uuid::new_v4()), pending_file.size)
+                        .await;
+                    continue;
+                }
+
                 let mut task = DownloadTask::new_with_group(
                     pending_file.fs_id,
                     pending_file.remote_path.clone(),
@@ -594,6 +619,9 @@ impl FolderDownloadManager {
                     pending_file.relative_path,
                 );
 
+                // 🔥 携带网盘文件 MD5，下载完成后校验完整性
+                task.expected_md5 = pending_file.md5.clone();
+
                 // 恢复模式下，保持任务创建时间不晚于原文件夹创建时间，
                 // 避免前端按 created_at 排序时，新补的暂停任务排在旧任务前。
                 task.created_at = folder_created_at;
@@ -629,8 +657,8 @@ impl FolderDownloadManager {
 
     /// 设置下载管理器
     pub async fn set_download_manager(&self, manager: Arc<DownloadManager>) {
-        // 创建任务完成通知 channel（发送 group_id 和 task_id）
-        let (tx, rx) = mpsc::unbounded_channel::<(String, String, u64, bool)>();
+        // 创建任务完成通知 channel
+        let (tx, rx) = mpsc::unbounded_channel::<SubtaskCompletion>();
 
         // 设置 sender 到 download_manager
         manager.set_task_completed_sender(tx).await;
@@ -751,7 +779,7 @@ impl FolderDownloadManager {
     ///
     /// 当收到子任务完成通知时，立即从 pending_files 补充新任务
     /// 根据文件夹可用槽位数量（借调位+固定位）动态补充，充分利用槽位资源
-    fn start_task_completed_listener(&self, mut rx: mpsc::UnboundedReceiver<(String, String, u64, bool)>) {
+    fn start_task_completed_listener(&self, mut rx: mpsc::UnboundedReceiver<SubtaskCompletion>) {
         let folders = self.folders.clone();
         let download_manager = self.download_manager.clone();
         let wal_dir = self.wal_dir.clone();
@@ -759,7 +787,14 @@ impl FolderDownloadManager {
         let cancellation_tokens = self.cancellation_tokens.clone();
 
         tokio::spawn(async move {
-            while let Some((group_id, task_id, file_size, is_success)) = rx.recv().await {
+            while let Some(SubtaskCompletion {
+                group_id,
+                task_id,
+                size: file_size,
+                success: is_success,
+                md5_verified,
+            }) = rx.recv().await
+            {
                 // 获取下载管理器
                 let dm = {
                     let guard = download_manager.read().await;
@@ -803,6 +838,9 @@ impl FolderDownloadManager {
                                 folder.counted_task_ids.insert(task_id.clone());
                                 folder.completed_count += 1;
                                 folder.completed_downloaded_size += file_size;
+                                if md5_verified {
+                                    folder.md5_verified_count += 1;
+                                }
                                 // 如果之前失败过（retry→success），从 failed 中移除
                                 if folder.failed_task_ids.remove(&task_id) {
                                     folder.failed_count = folder.failed_count.saturating_sub(1);
@@ -1099,6 +1137,16 @@ impl FolderDownloadManager {
                         }
                     }
 
+                    // 🔥 本地去重命中时直接按已完成计数，不再创建下载任务
+                    if dm
+                        .reuse_local_duplicate(&local_path, file_to_create.size, file_to_create.md5.as_deref())
+                        .await
+                    {
+                        dm.notify_subtask_skipped(group_id.clone(), format!("dedup-{}", uuid::Uuid::new_v4()), file_to_create.size)
+                            .await;
+                        continue;
+                    }
+
                     let mut task = DownloadTask::new_with_group(
                         file_to_create.fs_id,
                         file_to_create.remote_path.clone(),
@@ -1109,6 +1157,9 @@ impl FolderDownloadManager {
                         file_to_create.relative_path,
                     );
 
+                    // 🔥 携带网盘文件 MD5，下载完成后校验完整性
+                    task.expected_md5 = file_to_create.md5.clone();
+
                     // 🔥 尝试为子任务分配借调位
                     let borrowed_slot_assigned = {
                         let folders_guard = folders.read().await;
@@ -1217,18 +1268,20 @@ impl FolderDownloadManager {
 
     /// 创建文件夹下载任务
     pub async fn create_folder_download(&self, remote_path: String) -> Result<String> {
-        self.create_folder_download_with_name(remote_path, None, None).await
+        self.create_folder_download_with_name(remote_path, None, None, None).await
     }
 
     /// 创建文件夹下载任务（支持指定原始文件夹名）
     ///
     /// 如果传入 original_name，则使用该名称作为本地文件夹名（用于加密文件夹还原）
     /// 如果没有传入，会自动尝试从映射表还原加密的文件夹名
+    /// 传入 flatten 时所有文件直接放在本地文件夹下，不重建子目录
     pub async fn create_folder_download_with_name(
         &self,
         remote_path: String,
         original_name: Option<String>,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
+        flatten: Option<FlattenCollisionPolicy>,
     ) -> Result<String> {
         // 获取远程路径中的文件夹名
         let encrypted_folder_name = remote_path
@@ -1263,7 +1316,7 @@ impl FolderDownloadManager {
         let local_root = download_dir.join(&folder_name);
         drop(download_dir);
 
-        self.create_folder_download_internal(remote_path, local_root, conflict_strategy)
+        self.create_folder_download_internal(remote_path, local_root, conflict_strategy, flatten)
             .await
     }
 
@@ -1275,13 +1328,21 @@ impl FolderDownloadManager {
     /// * `remote_path` - 远程路径
     /// * `target_dir` - 目标下载目录
     /// * `original_name` - 原始文件夹名（如果是加密文件夹，传入还原后的名称）
+    /// * `flatten` - 平铺下载策略（None 表示保留目录结构）
     pub async fn create_folder_download_with_dir(
         &self,
         remote_path: String,
         target_dir: &std::path::Path,
         original_name: Option<String>,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
+        flatten: Option<FlattenCollisionPolicy>,
     ) -> Result<String> {
+        // 🔥 自定义下载目录必须位于 allowed_paths 白名单内（与单文件下载一致）
+        let download_manager = self.download_manager.read().await.clone();
+        if let Some(dm) = download_manager {
+            dm.ensure_download_dir_allowed(target_dir).await?;
+        }
+
         // 获取远程路径中的文件夹名
         let encrypted_folder_name = remote_path
             .trim_end_matches('/')
@@ -1313,7 +1374,7 @@ impl FolderDownloadManager {
 
         let local_root = target_dir.join(&folder_name);
 
-        self.create_folder_download_internal(remote_path, local_root, conflict_strategy)
+        self.create_folder_download_internal(remote_path, local_root, conflict_strategy, flatten)
             .await
     }
 
@@ -1327,12 +1388,14 @@ impl FolderDownloadManager {
         remote_path: String,
         local_root: PathBuf,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
+        flatten: Option<FlattenCollisionPolicy>,
     ) -> Result<String> {
         let mut folder = FolderDownload::new(remote_path.clone(), local_root);
         let folder_id = folder.id.clone();
 
         // 🔥 设置冲突策略
         folder.conflict_strategy = conflict_strategy;
+        folder.flatten = flatten;
 
         // 🔥 尝试为文件夹分配固定任务位（使用优先级分配，可抢占备份任务）
         let (mut fixed_slot_id, mut preempted_task_id) = {
@@ -1370,29 +1433,13 @@ impl FolderDownloadManager {
         // 这确保了多个文件夹任务之间的公平性：每个文件夹至少能获得一个固定位
         if fixed_slot_id.is_none() {
             info!("文件夹 {} 无空闲槽位，尝试回收其他文件夹的借调位", folder_id);
-            if let Some(reclaimed_slot_id) = self.reclaim_borrowed_slot().await {
-                // 回收成功，重新分配固定位
-                let dm = self.download_manager.read().await;
-                if let Some(ref dm) = *dm {
-                    let slot_pool = dm.task_slot_pool();
-                    if let Some((slot_id, preempted)) = slot_pool.allocate_fixed_slot_with_priority(
-                        &folder_id, true, crate::task_slot_pool::TaskPriority::Normal
-                    ).await {
-                        fixed_slot_id = Some(slot_id);
-                        info!(
-                            "文件夹 {} 通过回收借调位获得固定任务位: slot_id={} (回收的槽位={})",
-                            folder_id, slot_id, reclaimed_slot_id
-                        );
-                        // 处理可能被抢占的备份任务
-                        if let Some(preempted_id) = preempted {
-                            info!("文件夹 {} 抢占了备份任务 {} 的槽位", folder_id, preempted_id);
-                            if let Err(e) = dm.pause_task(&preempted_id, true).await {
-                                warn!("暂停被抢占的备份任务 {} 失败: {}", preempted_id, e);
-                            }
-                            dm.add_preempted_backup_to_queue(&preempted_id).await;
-                        }
-                    }
-                }
+            // 回收成功时借调位已直接转为该文件夹的固定位
+            if let Some(slot_id) = self
+                .reclaim_borrowed_slot(&folder_id, true, crate::task_slot_pool::TaskPriority::Normal)
+                .await
+            {
+                fixed_slot_id = Some(slot_id);
+                info!("文件夹 {} 通过回收借调位获得固定任务位: slot_id={}", folder_id, slot_id);
             }
         }
 
@@ -1559,6 +1606,18 @@ impl FolderDownloadManager {
         )
             .await?;
 
+        // 🔥 磁盘空间预检查：按文件夹总剩余大小检查目标卷，失败时由调用方标记文件夹失败
+        let remaining = {
+            let folders = self.folders.read().await;
+            folders
+                .get(folder_id)
+                .map(|f| f.total_size.saturating_sub(f.downloaded_size))
+                .unwrap_or(0)
+        };
+        if let Some(dm) = self.download_manager.read().await.clone() {
+            dm.ensure_free_space(&local_root, remaining)?;
+        }
+
         // 扫描完成，更新状态并对 pending_files 排序
         let should_publish_status_changed = {
             let mut folders = self.folders.write().await;
@@ -1748,6 +1807,7 @@ impl FolderDownloadManager {
                         remote_path: item.path.clone(),
                         relative_path,
                         size: item.size,
+                        md5: item.md5.clone(),
                     };
 
                     batch_files.push(pending_file);
@@ -1762,6 +1822,10 @@ impl FolderDownloadManager {
                 {
                     let mut folders = self.folders.write().await;
                     if let Some(folder) = folders.get_mut(folder_id) {
+                        // 平铺下载时在加入队列前确定本地文件名（同名文件按策略重命名）
+                        for file in batch_files.iter_mut() {
+                            file.relative_path = folder.local_relative_path(&file.relative_path);
+                        }
                         folder.pending_files.extend(batch_files);
                         folder.total_files += batch_count as u64;
                         folder.total_size += batch_size;
@@ -1822,103 +1886,51 @@ impl FolderDownloadManager {
 
     /// 清除内存中已完成的文件夹
     ///
-    /// 返回清除的数量
-    pub async fn clear_completed_folders(&self) -> usize {
+    /// 返回清除的文件夹 ID
+    pub async fn clear_completed_folders(&self) -> Vec<String> {
         let mut folders = self.folders.write().await;
-        let before_count = folders.len();
-
-        folders.retain(|_, folder| folder.status != FolderStatus::Completed);
+        let removed: Vec<String> = folders
+            .iter()
+            .filter(|(_, folder)| folder.status == FolderStatus::Completed)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &removed {
+            folders.remove(id);
+        }
 
-        let removed = before_count - folders.len();
-        if removed > 0 {
-            info!("从内存中清除了 {} 个已完成的文件夹", removed);
+        if !removed.is_empty() {
+            info!("从内存中清除了 {} 个已完成的文件夹", removed.len());
         }
         removed
     }
 
-    /// 从历史记录加载已完成的文件夹（优先从数据库加载）
+    /// 从历史数据库加载已完成的文件夹
     ///
     /// 返回已完成文件夹的列表（用于前端显示历史记录）
     pub async fn load_folder_history(&self) -> Vec<FolderDownload> {
-        // 优先从数据库加载
         let pm_opt = self.persistence_manager.read().await.clone();
-        if let Some(pm) = pm_opt {
-            let pm_guard = pm.lock().await;
-            if let Some(db) = pm_guard.history_db() {
-                match db.load_all_folder_history() {
-                    Ok(folders) => {
-                        return folders.into_iter().map(|f| f.to_folder()).collect();
-                    }
-                    Err(e) => {
-                        error!("从数据库加载文件夹历史失败: {}", e);
-                    }
-                }
-            }
-        }
-
-        // 回退到文件加载（兼容旧数据）
-        let wal_dir = {
-            let dir = self.wal_dir.read().await;
-            dir.clone()
+        let Some(pm) = pm_opt else {
+            return Vec::new();
         };
-
-        let wal_dir = match wal_dir {
-            Some(dir) => dir,
-            None => return Vec::new(),
+        let pm_guard = pm.lock().await;
+        let Some(db) = pm_guard.history_db() else {
+            return Vec::new();
         };
-
-        match crate::persistence::folder::load_folder_history(&wal_dir) {
+        match db.load_all_folder_history() {
             Ok(folders) => folders.into_iter().map(|f| f.to_folder()).collect(),
             Err(e) => {
-                error!("加载文件夹历史失败: {}", e);
+                error!("从数据库加载文件夹历史失败: {}", e);
                 Vec::new()
             }
         }
     }
 
-    /// 从历史记录加载已完成的文件夹到内存（优先从数据库加载）
+    /// 从历史数据库加载已完成的文件夹到内存
     ///
     /// 在恢复时调用，将历史归档的已完成文件夹加载到内存中
     /// 这样前端获取所有下载时可以看到历史完成的文件夹
     pub async fn load_history_folders_to_memory(&self) -> usize {
-        // 优先从数据库加载
-        let pm_opt = self.persistence_manager.read().await.clone();
-        let history_folders: Vec<FolderPersisted> = if let Some(pm) = pm_opt {
-            let pm_guard = pm.lock().await;
-            if let Some(db) = pm_guard.history_db() {
-                match db.load_all_folder_history() {
-                    Ok(folders) => folders,
-                    Err(e) => {
-                        error!("从数据库加载文件夹历史失败: {}", e);
-                        Vec::new()
-                    }
-                }
-            } else {
-                Vec::new()
-            }
-        } else {
-            // 回退到文件加载（兼容旧数据）
-            let wal_dir = {
-                let dir = self.wal_dir.read().await;
-                dir.clone()
-            };
-
-            match wal_dir {
-                Some(dir) => {
-                    match crate::persistence::folder::load_folder_history(&dir) {
-                        Ok(folders) => folders,
-                        Err(e) => {
-                            error!("加载文件夹历史失败: {}", e);
-                            Vec::new()
-                        }
-                    }
-                }
-                None => {
-                    warn!("WAL 目录未设置，跳过加载历史文件夹");
-                    Vec::new()
-                }
-            }
-        };
+        let history_folders = self.load_folder_history().await;
 
         if history_folders.is_empty() {
             return 0;
@@ -1927,10 +1939,9 @@ impl FolderDownloadManager {
         let mut loaded = 0;
         {
             let mut folders = self.folders.write().await;
-            for persisted in history_folders {
+            for folder in history_folders {
                 // 只添加不存在于内存中的文件夹（避免重复）
-                if !folders.contains_key(&persisted.id) {
-                    let folder = persisted.to_folder();
+                if !folders.contains_key(&folder.id) {
                     folders.insert(folder.id.clone(), folder);
                     loaded += 1;
                 }
@@ -1944,36 +1955,18 @@ impl FolderDownloadManager {
         loaded
     }
 
-    /// 从历史记录中删除文件夹（优先从数据库删除）
+    /// 从历史数据库中删除文件夹
     pub async fn delete_folder_from_history(&self, folder_id: &str) -> Result<bool> {
-        // 优先从数据库删除
         let pm_opt = self.persistence_manager.read().await.clone();
-        if let Some(pm) = pm_opt {
-            let pm_guard = pm.lock().await;
-            if let Some(db) = pm_guard.history_db() {
-                match db.remove_folder_from_history(folder_id) {
-                    Ok(removed) => return Ok(removed),
-                    Err(e) => {
-                        error!("从数据库删除文件夹历史失败: {}", e);
-                    }
-                }
-            }
-        }
-
-        // 回退到文件删除（兼容旧数据）
-        let wal_dir = {
-            let dir = self.wal_dir.read().await;
-            dir.clone()
+        let Some(pm) = pm_opt else {
+            return Ok(false);
         };
-
-        let wal_dir = match wal_dir {
-            Some(dir) => dir,
-            None => return Ok(false),
-        };
-
-        match remove_folder_from_history(&wal_dir, folder_id) {
-            Ok(removed) => Ok(removed),
-            Err(e) => Err(anyhow!("从历史删除文件夹失败: {}", e)),
+        let pm_guard = pm.lock().await;
+        match pm_guard.history_db() {
+            Some(db) => db
+                .remove_folder_from_history(folder_id)
+                .map_err(|e| anyhow!("从历史删除文件夹失败: {}", e)),
+            None => Ok(false),
         }
     }
 
@@ -2129,25 +2122,13 @@ impl FolderDownloadManager {
         // 这确保了多个文件夹任务之间的公平性：每个文件夹至少能获得一个固定位
         if fixed_slot_id.is_none() {
             info!("恢复文件夹 {} 无空闲槽位，尝试回收其他文件夹的借调位", folder_id);
-            if let Some(reclaimed_slot_id) = self.reclaim_borrowed_slot().await {
-                // 回收成功，重新分配固定位（使用优先级分配）
-                if let Some((slot_id, preempted)) = slot_pool.allocate_fixed_slot_with_priority(
-                    folder_id, true, crate::task_slot_pool::TaskPriority::Normal
-                ).await {
-                    fixed_slot_id = Some(slot_id);
-                    info!(
-                        "恢复文件夹 {} 通过回收借调位获得固定任务位: slot_id={} (回收的槽位={})",
-                        folder_id, slot_id, reclaimed_slot_id
-                    );
-                    // 处理可能被抢占的备份任务
-                    if let Some(preempted_id) = preempted {
-                        info!("恢复文件夹 {} 抢占了备份任务 {} 的槽位", folder_id, preempted_id);
-                        if let Err(e) = download_manager.pause_task(&preempted_id, true).await {
-                            warn!("暂停被抢占的备份任务 {} 失败: {}", preempted_id, e);
-                        }
-                        download_manager.add_preempted_backup_to_queue(&preempted_id).await;
-                    }
-                }
+            // 回收成功时借调位已直接转为该文件夹的固定位
+            if let Some(slot_id) = self
+                .reclaim_borrowed_slot(folder_id, true, crate::task_slot_pool::TaskPriority::Normal)
+                .await
+            {
+                fixed_slot_id = Some(slot_id);
+                info!("恢复文件夹 {} 通过回收借调位获得固定任务位: slot_id={}", folder_id, slot_id);
             }
         }
 
@@ -2349,10 +2330,11 @@ impl FolderDownloadManager {
         Ok(())
     }
 
-    /// 取消文件夹下载
-    pub async fn cancel_folder(&self, folder_id: &str, delete_files: bool) -> Result<()> {
-        info!("取消文件夹下载: {}, 删除文件: {}", folder_id, delete_files);
-
+    /// 停止文件夹扫描并标记为已取消，返回文件夹本地根目录（文件夹不存在时返回 None）
+    ///
+    /// 同时清空 pending_files，阻止 task_completed_listener 补充新任务，
+    /// 必须在删除子任务之前调用，避免竞态条件
+    pub async fn mark_folder_cancelled(&self, folder_id: &str) -> Option<PathBuf> {
         // 触发取消令牌，停止扫描
         {
             let mut tokens = self.cancellation_tokens.write().await;
@@ -2361,23 +2343,20 @@ impl FolderDownloadManager {
             }
         }
 
+        let mut folders = self.folders.write().await;
+        let folder = folders.get_mut(folder_id)?;
+        folder.mark_cancelled();
+        folder.pending_files.clear(); // 清空待处理队列
+        info!("文件夹 {} 已标记为取消，已清空 pending_files", folder.name);
+        Some(folder.local_root.clone())
+    }
+
+    /// 取消文件夹下载
+    pub async fn cancel_folder(&self, folder_id: &str, delete_files: bool) -> Result<()> {
+        info!("取消文件夹下载: {}, 删除文件: {}", folder_id, delete_files);
+
         // 🔥 关键：先更新文件夹状态并清空 pending_files，阻止 task_completed_listener 补充新任务
-        // 这必须在删除任务之前执行，避免竞态条件
-        let local_root = {
-            let mut folders = self.folders.write().await;
-            if let Some(folder) = folders.get_mut(folder_id) {
-                folder.mark_cancelled();
-                folder.pending_files.clear(); // 清空待处理队列
-                info!(
-                    "文件夹 {} 已标记为取消，已清空 pending_files ({} 个待处理文件)",
-                    folder.name,
-                    folder.pending_files.len()
-                );
-                Some(folder.local_root.clone())
-            } else {
-                None
-            }
-        };
+        let local_root = self.mark_folder_cancelled(folder_id).await;
 
         // 获取下载管理器
         let download_manager = {
@@ -2473,7 +2452,7 @@ impl FolderDownloadManager {
         })
             .await;
 
-        // 删除子任务的历史记录（优先从数据库删除）
+        // 删除子任务的历史记录
         let pm_opt = self.persistence_manager.read().await.clone();
         if let Some(pm) = pm_opt {
             let pm_guard = pm.lock().await;
@@ -2488,23 +2467,6 @@ impl FolderDownloadManager {
                     _ => {}
                 }
             }
-        } else {
-            // 回退到文件删除（兼容旧数据）
-            let wal_dir = {
-                let dir = self.wal_dir.read().await;
-                dir.clone()
-            };
-            if let Some(wal_dir) = wal_dir {
-                match remove_tasks_by_group_from_history(&wal_dir, folder_id) {
-                    Ok(count) if count > 0 => {
-                        info!("已删除文件夹 {} 的 {} 个子任务历史记录", folder_id, count);
-                    }
-                    Err(e) => {
-                        error!("删除子任务历史记录失败: {}", e);
-                    }
-                    _ => {}
-                }
-            }
         }
 
         Ok(())
@@ -2613,11 +2575,28 @@ impl FolderDownloadManager {
                     .and_then(|f| f.conflict_strategy)
                     .unwrap_or(crate::uploader::conflict::DownloadConflictStrategy::Overwrite);
 
+                drop(folders_guard);
+
                 use crate::uploader::conflict_resolver::ConflictResolver;
-                match ConflictResolver::resolve_download_conflict(&local_path, strategy) {
+                match ConflictResolver::resolve_download_conflict_verified(
+                    &local_path,
+                    strategy,
+                    pending_file.size,
+                    pending_file.md5.as_deref(),
+                )
+                .await
+                {
                     Ok(crate::uploader::conflict::ConflictResolution::Proceed) => local_path,
                     Ok(crate::uploader::conflict::ConflictResolution::Skip) => {
-                        info!("跳过下载（文件已存在）: {:?}", local_path);
+                        // 🔥 已有文件一致：按已完成计数，重新运行部分完成的文件夹时才能走到完成状态
+                        info!("跳过下载（文件已存在且内容一致）: {:?}", local_path);
+                        download_manager
+                            .notify_subtask_skipped(
+                                folder_id.to_string(),
+                                format!("skipped-{}", uuid::Uuid::new_v4()),
+                                pending_file.size,
+                            )
+                            .await;
                         continue; // 跳过此文件，继续下一个
                     }
                     Ok(crate::uploader::conflict::ConflictResolution::UseNewPath(new_path)) => {
@@ -2638,6 +2617,17 @@ impl FolderDownloadManager {
                     .context(format!("创建目录失败: {:?}", parent))?;
             }
 
+            // 🔥 本地去重命中时直接按已完成计数，不再创建下载任务
+            if download_manager
+                .reuse_local_duplicate(&final_local_path, pending_file.size, pending_file.md5.as_deref())
+                .await
+            {
+                download_manager
+                    .notify_subtask_skipped(folder_id.to_string(), format!("dedup-{}", uuid::Uuid::new_v4()), pending_file.size)
+                    .await;
+                continue;
+            }
+
             let mut task = DownloadTask::new_with_group(
                 pending_file.fs_id,
                 pending_file.remote_path.clone(),
@@ -2648,6 +2638,9 @@ impl FolderDownloadManager {
                 pending_file.relative_path,
             );
 
+            // 🔥 携带网盘文件 MD5，下载完成后校验完整性
+            task.expected_md5 = pending_file.md5.clone();
+
             // 🔥 尝试为子任务分配借调位
             // 修复：同时检查 borrowed_subtask_map 和已恢复任务的 slot_id，避免重复分配
             let borrowed_slot_assigned = {
@@ -2864,9 +2857,14 @@ impl FolderDownloadManager {
     /// 1. 查找有借调位的文件夹
     /// 2. 选择一个使用借调位的子任务
     /// 3. 暂停该子任务并等待分片完成
-    /// 4. 释放借调位
-    /// 5. 返回释放的槽位ID
-    pub async fn reclaim_borrowed_slot(&self) -> Option<usize> {
+    /// 4. 借调位直接转为 `new_task_id` 的固定位（不经过释放再分配，避免被并发启动的任务抢走）
+    /// 5. 返回已分配给 `new_task_id` 的槽位ID
+    pub async fn reclaim_borrowed_slot(
+        &self,
+        new_task_id: &str,
+        is_folder: bool,
+        priority: crate::task_slot_pool::TaskPriority,
+    ) -> Option<usize> {
         // 获取下载管理器
         let dm = {
             let guard = self.download_manager.read().await;
@@ -2914,7 +2912,12 @@ impl FolderDownloadManager {
                     // 确实没有正在运行的子任务，直接释放一个借调位
                     let borrowed_slots = slot_pool.get_borrowed_slots(&folder_id).await;
                     if let Some(&slot_id) = borrowed_slots.first() {
-                        slot_pool.release_borrowed_slot(&folder_id, slot_id).await;
+                        if !slot_pool
+                            .reassign_borrowed_slot(&folder_id, slot_id, new_task_id, is_folder, priority)
+                            .await
+                        {
+                            return None;
+                        }
 
                         // 更新文件夹的借调位记录
                         {
@@ -2924,11 +2927,10 @@ impl FolderDownloadManager {
                             }
                         }
 
-                        info!("直接释放空闲借调位: slot_id={} from folder {}", slot_id, folder_id);
-
-                        // 🔥 修复：释放槽位后不触发 try_start_waiting_tasks
-                        // 因为这个槽位是要给新任务用的，不是给等待队列的
-                        // dm.try_start_waiting_tasks().await; // 已移除
+                        info!(
+                            "直接回收空闲借调位: slot_id={} from folder {} -> {}",
+                            slot_id, folder_id, new_task_id
+                        );
 
                         return Some(slot_id);
                     }
@@ -2973,13 +2975,17 @@ impl FolderDownloadManager {
             slot_id
         };
 
-        // 释放到任务位池
-        slot_pool.release_borrowed_slot(&folder_id, slot_id).await;
+        // 🔥 借调位直接转给新任务，槽位不会出现空闲窗口
+        let reassigned = slot_pool
+            .reassign_borrowed_slot(&folder_id, slot_id, new_task_id, is_folder, priority)
+            .await;
 
-        info!(
-            "回收完成：释放借调位 {} 从文件夹 {}",
-            slot_id, folder_id
-        );
+        if reassigned {
+            info!(
+                "回收完成：借调位 {} 从文件夹 {} 转给任务 {}",
+                slot_id, folder_id, new_task_id
+            );
+        }
 
         // 🔥 关键修复：将被暂停的子任务重新加入等待队列
         // 子任务不应该一直暂停，而是重新排队等待后续有空闲槽位时继续下载
@@ -2989,11 +2995,8 @@ impl FolderDownloadManager {
             info!("子任务 {} 已重新加入等待队列", task_id);
         }
 
-        // 🔥 修复：释放槽位后不触发 try_start_waiting_tasks
-        // 因为这个槽位是要给新任务用的，不是给等待队列的
-        // dm.try_start_waiting_tasks().await; // 已移除
-
-        Some(slot_id)
+        // 槽位已直接转给新任务，无需触发 try_start_waiting_tasks
+        reassigned.then_some(slot_id)
     }
 
     /// 等待任务暂停完成（所有运行中分片完成）
@@ -0,0 +1,255 @@
+//! 下载链接来源
+//!
+//! CDN 链接刷新（定时、速度异常、线程停滞、手动）按任务类型获取新链接：
+//! - 自己网盘中的文件：按网盘路径重新 locate
+//! - 免转存分享文件：网盘路径属于分享者，只能重新列出分享目录，按 fs_id 取出新的直链
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use crate::downloader::DownloadTask;
+use crate::netdisk::NetdiskClient;
+use crate::transfer::{ShareDirectSource, SharedFileInfo};
+
+/// 列分享目录时每页条数
+const SHARE_LIST_PAGE_SIZE: u32 = 100;
+/// 列分享目录时最多翻页数
+const SHARE_LIST_MAX_PAGES: u32 = 100;
+
+/// 刷新下载链接的目标
+#[derive(Debug, Clone)]
+pub enum LinkRefreshTarget {
+    /// 自己网盘中的文件
+    Netdisk { remote_path: String },
+    /// 免转存分享文件
+    Share {
+        source: ShareDirectSource,
+        remote_path: String,
+        fs_id: u64,
+    },
+}
+
+impl LinkRefreshTarget {
+    /// 按任务类型确定刷新目标（有分享来源的直链任务走分享目录列表）
+    pub fn from_task(task: &DownloadTask) -> Self {
+        match task.share_source {
+            Some(ref source) if task.direct_dlink.is_some() => Self::Share {
+                source: source.clone(),
+                remote_path: task.remote_path.clone(),
+                fs_id: task.fs_id,
+            },
+            _ => Self::Netdisk {
+                remote_path: task.remote_path.clone(),
+            },
+        }
+    }
+
+    /// 文件路径（分享文件为分享内的路径）
+    pub fn remote_path(&self) -> &str {
+        match self {
+            Self::Netdisk { remote_path } | Self::Share { remote_path, .. } => remote_path,
+        }
+    }
+}
+
+/// 获取下载链接的接口（由 NetdiskClient 实现，测试中可替换）
+#[async_trait::async_trait]
+pub trait DownloadLinkSource: Send + Sync {
+    /// 按网盘路径获取全部下载链接
+    async fn locate(&self, remote_path: &str) -> Result<Vec<String>>;
+
+    /// 列出分享目录（携带直链），dir 为 None 时列分享根目录
+    async fn list_share_dir(
+        &self,
+        source: &ShareDirectSource,
+        dir: Option<&str>,
+        page: u32,
+        num: u32,
+    ) -> Result<Vec<SharedFileInfo>>;
+}
+
+#[async_trait::async_trait]
+impl DownloadLinkSource for NetdiskClient {
+    async fn locate(&self, remote_path: &str) -> Result<Vec<String>> {
+        self.get_locate_download_url(remote_path).await
+    }
+
+    async fn list_share_dir(
+        &self,
+        source: &ShareDirectSource,
+        dir: Option<&str>,
+        page: u32,
+        num: u32,
+    ) -> Result<Vec<SharedFileInfo>> {
+        self.list_share_files_with_dlink(source, dir, page, num).await
+    }
+}
+
+/// 获取刷新目标的全部新链接
+pub async fn fetch_refresh_links(
+    links: &dyn DownloadLinkSource,
+    target: &LinkRefreshTarget,
+) -> Result<Vec<String>> {
+    match target {
+        LinkRefreshTarget::Netdisk { remote_path } => links.locate(remote_path).await,
+        LinkRefreshTarget::Share {
+            source,
+            remote_path,
+            fs_id,
+        } => {
+            let dlink = find_share_dlink(links, source, remote_path, *fs_id).await?;
+            Ok(vec![dlink])
+        }
+    }
+}
+
+/// 重新列出分享目录，按 fs_id 找到文件的新直链
+///
+/// 先列文件所在目录，找不到时再列分享根目录（根目录文件的路径不一定能直接按目录列出）
+async fn find_share_dlink(
+    links: &dyn DownloadLinkSource,
+    source: &ShareDirectSource,
+    remote_path: &str,
+    fs_id: u64,
+) -> Result<String> {
+    let parent = remote_path
+        .rsplit_once('/')
+        .map(|(parent, _)| parent)
+        .filter(|parent| !parent.is_empty());
+
+    let mut dirs = vec![parent];
+    if parent.is_some() {
+        dirs.push(None);
+    }
+
+    for dir in dirs {
+        for page in 1..=SHARE_LIST_MAX_PAGES {
+            let files = links
+                .list_share_dir(source, dir, page, SHARE_LIST_PAGE_SIZE)
+                .await
+                .context("重新列出分享目录失败")?;
+            let count = files.len();
+
+            if let Some(file) = files.into_iter().find(|f| f.fs_id == fs_id) {
+                debug!("分享直链已刷新: path={}, dir={:?}", remote_path, dir);
+                return file.dlink.context("分享目录未返回直链");
+            }
+            if count < SHARE_LIST_PAGE_SIZE as usize {
+                break;
+            }
+        }
+    }
+
+    anyhow::bail!("分享中未找到文件（可能已被取消分享）: {}", remote_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 记录调用的模拟链接来源
+    #[derive(Default)]
+    struct MockLinkSource {
+        locate_urls: Vec<String>,
+        share_files: Vec<SharedFileInfo>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DownloadLinkSource for MockLinkSource {
+        async fn locate(&self, remote_path: &str) -> Result<Vec<String>> {
+            self.calls.lock().unwrap().push(format!("locate:{}", remote_path));
+            Ok(self.locate_urls.clone())
+        }
+
+        async fn list_share_dir(
+            &self,
+            _source: &ShareDirectSource,
+            dir: Option<&str>,
+            _page: u32,
+            _num: u32,
+        ) -> Result<Vec<SharedFileInfo>> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("share:{}", dir.unwrap_or("<root>")));
+            Ok(self
+                .share_files
+                .iter()
+                .filter(|f| dir.is_none() || dir.is_some_and(|d| f.path.starts_with(d)))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn share_source() -> ShareDirectSource {
+        ShareDirectSource {
+            short_key: "1abc".to_string(),
+            shareid: "1".to_string(),
+            uk: "2".to_string(),
+            bdstoken: "token".to_string(),
+            sekey: None,
+        }
+    }
+
+    fn share_file(path: &str, fs_id: u64, dlink: &str) -> SharedFileInfo {
+        SharedFileInfo {
+            fs_id,
+            is_dir: false,
+            path: path.to_string(),
+            size: 4,
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            dlink: Some(dlink.to_string()),
+            md5: None,
+        }
+    }
+
+    #[test]
+    fn test_target_from_task() {
+        let mut task = DownloadTask::new(7, "/share/a.txt".to_string(), "/tmp/a.txt".into(), 4);
+        assert!(matches!(
+            LinkRefreshTarget::from_task(&task),
+            LinkRefreshTarget::Netdisk { .. }
+        ));
+
+        task.direct_dlink = Some("https://d.pcs.baidu.com/old".to_string());
+        task.share_source = Some(share_source());
+        match LinkRefreshTarget::from_task(&task) {
+            LinkRefreshTarget::Share { fs_id, remote_path, .. } => {
+                assert_eq!(fs_id, 7);
+                assert_eq!(remote_path, "/share/a.txt");
+            }
+            other => panic!("unexpected target: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_target_relists_share_instead_of_locate() {
+        let links = MockLinkSource {
+            locate_urls: vec!["https://locate/should-not-be-used".to_string()],
+            share_files: vec![
+                share_file("/share/dir/b.txt", 8, "https://new/b"),
+                share_file("/share/dir/a.txt", 7, "https://new/a"),
+            ],
+            ..Default::default()
+        };
+        let target = LinkRefreshTarget::Share {
+            source: share_source(),
+            remote_path: "/share/dir/a.txt".to_string(),
+            fs_id: 7,
+        };
+
+        let urls = fetch_refresh_links(&links, &target).await.unwrap();
+        assert_eq!(urls, vec!["https://new/a".to_string()]);
+        assert_eq!(*links.calls.lock().unwrap(), vec!["share:/share/dir".to_string()]);
+
+        // 文件已不在分享中：目录和根目录都找不到时报错
+        let missing = LinkRefreshTarget::Share {
+            source: share_source(),
+            remote_path: "/share/dir/gone.txt".to_string(),
+            fs_id: 99,
+        };
+        assert!(fetch_refresh_links(&links, &missing).await.is_err());
+    }
+}
@@ -1,22 +1,25 @@
 use crate::auth::UserAuth;
 use crate::autobackup::events::BackupTransferNotification;
 use crate::common::{
-    ProxyConfig, RefreshCoordinator, RefreshCoordinatorConfig, SpeedAnomalyConfig, StagnationConfig,
+    EtaEstimator, ProxyConfig, RefreshCoordinator, RefreshCoordinatorConfig, SpeedAnomalyConfig, StagnationConfig,
 };
+use crate::config::{AdaptiveConcurrencyConfig, FilesystemConfig, RateLimitConfig, RetryConfig, ScheduleConfig};
 use crate::downloader::{
-    calculate_task_max_chunks, ChunkScheduler, DownloadEngine, DownloadTask, TaskScheduleInfo,
-    TaskStatus, FolderDownloadManager,
+    resolve_task_max_chunks, ChunkScheduler, DownloadEngine, DownloadPriority, DownloadSchedule, DownloadTask,
+    TaskScheduleInfo, TaskStatus, FolderDownloadManager, FolderStatus, RateLimitCooldown, ScheduleStatus,
+    LinkRefreshTarget, SlotDebugInfo, SpeedLimiter, SpeedTestResult, SubtaskCompletion,
 };
-use crate::task_slot_pool::{TaskSlotPool, TaskPriority};
+use crate::task_slot_pool::{SlotAllocation, TaskSlotPool, TaskPriority};
 use crate::persistence::{
-    DownloadRecoveryInfo, PersistenceManager, TaskMetadata,
+    DownloadRecoveryInfo, PersistenceManager, TaskHistoryQuery, TaskMetadata,
 };
 use crate::server::events::{DownloadEvent, ProgressThrottler, TaskEvent};
-use crate::server::websocket::WebSocketManager;
+use crate::server::websocket::{WebSocketManager, WsServerMessage};
+use crate::transfer::ShareDirectSource;
 use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
@@ -35,6 +38,8 @@ pub struct DownloadManager {
     engine: Arc<DownloadEngine>,
     /// 默认下载目录（使用 RwLock 支持动态更新）
     download_dir: Arc<RwLock<PathBuf>>,
+    /// 🔥 按文件类型选择的下载目录（未指定目录时优先于默认下载目录）
+    type_dirs: Arc<RwLock<BTreeMap<String, PathBuf>>>,
     /// 全局分片调度器
     chunk_scheduler: ChunkScheduler,
     /// 最大同时下载任务数
@@ -55,16 +60,52 @@ pub struct DownloadManager {
     snapshot_manager: Arc<RwLock<Option<Arc<crate::encryption::snapshot::SnapshotManager>>>>,
     /// 🔥 加密配置存储（用于根据 key_version 选择正确的解密密钥）
     encryption_config_store: Arc<RwLock<Option<Arc<crate::encryption::EncryptionConfigStore>>>>,
-    /// 🔥 链接级重试次数（从配置读取，传递给 TaskScheduleInfo）
-    max_retries: u32,
+    /// 🔥 分片重试配置（从配置读取，传递给 TaskScheduleInfo）
+    retry_config: RetryConfig,
     /// 🔥 活跃任务计数（O(1) 查询，漂移校准每 60 秒）
     active_count: Arc<AtomicUsize>,
+    /// 🔥 全局限速器（所有任务的分片共享同一个令牌桶，传递给 TaskScheduleInfo）
+    global_speed_limiter: Arc<SpeedLimiter>,
+    /// 🔥 文件系统配置（用于校验自定义下载目录位于 allowed_paths 白名单内）
+    filesystem_config: Arc<RwLock<FilesystemConfig>>,
+    /// 🔥 下载前磁盘预留空间（MB，启动任务前检查目标卷剩余空间）
+    min_free_space_mb: Arc<AtomicU64>,
+    /// 🔥 单任务最大并发分片数默认覆盖值（0 表示按文件大小计算，任务可单独覆盖）
+    max_chunks_per_task: Arc<AtomicUsize>,
+    /// 🔥 一键暂停的单文件任务（按原顺序记录，等待队列不会自动启动，resume_all 时按此顺序恢复）
+    user_paused_tasks: Arc<RwLock<Vec<String>>>,
+    /// 🔥 正在修改下载目录（移动临时文件）的任务，移动完成前不允许恢复或启动
+    relocating_tasks: Arc<RwLock<HashSet<String>>>,
+    /// 🔥 一键暂停的文件夹（resume_all 时统一恢复）
+    user_paused_folders: Arc<RwLock<Vec<String>>>,
+    /// 🔥 全局限流冷却器（冷却期间暂停启动新任务，与调度器、下载引擎共享）
+    rate_limit_cooldown: Arc<RateLimitCooldown>,
+    /// 🔥 服务正在关闭（不再启动任何新任务）
+    shutting_down: Arc<AtomicBool>,
+    /// 🔥 下载时间段调度（时间段外暂停启动新任务）
+    download_schedule: Arc<DownloadSchedule>,
+    /// 🔥 上一次检查时是否处于下载时间段
+    schedule_was_open: Arc<AtomicBool>,
+    /// 🔥 最近一次离开下载时间段的时间（Unix 时间戳，秒），之后新建的文件夹会被暂停
+    schedule_closed_at: Arc<AtomicI64>,
+    /// 🔥 因离开下载时间段而暂停的单文件任务（进入时间段后恢复）
+    schedule_paused_tasks: Arc<RwLock<Vec<String>>>,
+    /// 🔥 因离开下载时间段而暂停的文件夹（进入时间段后恢复）
+    schedule_paused_folders: Arc<RwLock<Vec<String>>>,
 }
 
 impl DownloadManager {
     /// 创建新的下载管理器
     pub fn new(user_auth: UserAuth, download_dir: PathBuf) -> Result<Self> {
-        Self::with_config(user_auth, download_dir, 10, 5, 3, None, None)
+        Self::with_config(
+            user_auth,
+            download_dir,
+            10,
+            5,
+            RetryConfig::default().with_fallback_attempts(3),
+            None,
+            None,
+        )
     }
 
     /// 使用指定配置创建下载管理器（不再需要 chunk_size 参数，引擎会自动计算）
@@ -73,7 +114,7 @@ impl DownloadManager {
         download_dir: PathBuf,
         max_global_threads: usize,
         max_concurrent_tasks: usize,
-        max_retries: u32,
+        retry_config: RetryConfig,
         proxy_config: Option<&ProxyConfig>,
         fallback_mgr: Option<std::sync::Arc<crate::common::ProxyFallbackManager>>,
     ) -> Result<Self> {
@@ -83,15 +124,29 @@ impl DownloadManager {
             info!("✓ 下载目录已创建: {:?}", download_dir);
         }
 
+        // 🔥 创建全局限速器（所有任务共享，默认不限速，由 update_global_speed_limit 按配置设置）
+        let global_speed_limiter = Arc::new(SpeedLimiter::new(0));
+
+        // 🔥 创建全局限流冷却器（默认配置，由 update_rate_limit_config 按配置设置）
+        let rate_limit_cooldown = Arc::new(RateLimitCooldown::default());
+
         // 创建全局分片调度器（不再使用 Semaphore）
-        let chunk_scheduler = ChunkScheduler::new(max_global_threads, max_concurrent_tasks);
+        let chunk_scheduler = ChunkScheduler::new(
+            max_global_threads,
+            max_concurrent_tasks,
+            global_speed_limiter.clone(),
+            rate_limit_cooldown.clone(),
+        );
 
         info!(
             "创建下载管理器: 下载目录={:?}, 全局线程数={}, 最大同时下载数={} (分片大小自适应)",
             download_dir, max_global_threads, max_concurrent_tasks
         );
 
-        let engine = Arc::new(DownloadEngine::new_with_proxy(user_auth, proxy_config, fallback_mgr));
+        let engine = Arc::new(
+            DownloadEngine::new_with_proxy(user_auth, proxy_config, fallback_mgr)
+                .with_rate_limit_cooldown(rate_limit_cooldown.clone()),
+        );
 
         let manager = Self {
             tasks: Arc::new(RwLock::new(HashMap::new())),
@@ -99,6 +154,7 @@ impl DownloadManager {
             waiting_queue: Arc::new(RwLock::new(VecDeque::new())),
             engine,
             download_dir: Arc::new(RwLock::new(download_dir)),
+            type_dirs: Arc::new(RwLock::new(BTreeMap::new())),
             chunk_scheduler,
             max_concurrent_tasks,
             persistence_manager: None,
@@ -119,13 +175,33 @@ impl DownloadManager {
             folder_manager: Arc::new(RwLock::new(None)),
             snapshot_manager: Arc::new(RwLock::new(None)),
             encryption_config_store: Arc::new(RwLock::new(None)),
-            max_retries,
+            retry_config,
             active_count: Arc::new(AtomicUsize::new(0)),
+            global_speed_limiter,
+            filesystem_config: Arc::new(RwLock::new(FilesystemConfig::default())),
+            min_free_space_mb: Arc::new(AtomicU64::new(0)),
+            max_chunks_per_task: Arc::new(AtomicUsize::new(0)),
+            user_paused_tasks: Arc::new(RwLock::new(Vec::new())),
+            relocating_tasks: Arc::new(RwLock::new(HashSet::new())),
+            user_paused_folders: Arc::new(RwLock::new(Vec::new())),
+            rate_limit_cooldown,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            download_schedule: Arc::new(DownloadSchedule::new()),
+            schedule_was_open: Arc::new(AtomicBool::new(true)),
+            schedule_closed_at: Arc::new(AtomicI64::new(0)),
+            schedule_paused_tasks: Arc::new(RwLock::new(Vec::new())),
+            schedule_paused_folders: Arc::new(RwLock::new(Vec::new())),
         };
 
         // 🔥 设置槽位超时释放处理器
         manager.setup_stale_release_handler();
 
+        // 🔥 设置任务级重试处理器
+        manager.setup_task_retry_handler();
+
+        // 🔥 设置限流冷却通知处理器
+        manager.setup_rate_limit_handler();
+
         // 🔥 启动活跃计数漂移校准（每 60 秒）
         // 注意：start_waiting_queue_monitor 和 setup_waiting_queue_trigger 已移至
         // set_persistence_manager() 中调用，确保它们捕获到有效的 persistence_manager
@@ -168,6 +244,11 @@ impl DownloadManager {
         self.setup_waiting_queue_trigger();
     }
 
+    /// 🔥 下载测速（不创建任务，数据不落盘）
+    pub async fn speed_test(&self, remote_path: &str, test_bytes: u64) -> Result<SpeedTestResult> {
+        self.engine.speed_test(remote_path, test_bytes).await
+    }
+
     /// 热更新代理配置（由 update_config handler 调用）
     /// 直接通过 self.engine 调用 DownloadEngine 的方法，无需中间引用
     pub fn update_proxy_config(&self, new_proxy: Option<&ProxyConfig>) {
@@ -245,25 +326,69 @@ impl DownloadManager {
     }
 
     /// 创建下载任务
+    ///
+    /// `expected_md5` 为网盘文件列表返回的 MD5，用于下载完成后校验完整性（None 表示不校验）
     pub async fn create_task(
         &self,
         fs_id: u64,
         remote_path: String,
         filename: String,
         total_size: u64,
+        expected_md5: Option<String>,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
     ) -> Result<String> {
-        let download_dir = self.download_dir.read().await;
-        let local_path = download_dir.join(&filename);
-        drop(download_dir);
+        let local_path = self.default_dir_for(&filename).await.join(&filename);
+
+        self.create_task_internal(fs_id, remote_path, local_path, total_size, expected_md5, None, conflict_strategy)
+            .await
+    }
+
+    /// 🔥 未指定下载目录时按文件类型选择目录，未匹配时使用默认下载目录
+    async fn default_dir_for(&self, filename: &str) -> PathBuf {
+        if let Some(dir) = crate::config::match_type_dir(&*self.type_dirs.read().await, filename) {
+            debug!("按文件类型选择下载目录: {} -> {:?}", filename, dir);
+            return dir.clone();
+        }
+        self.download_dir.read().await.clone()
+    }
+
+    /// 🔥 尝试用本地去重索引中的已有文件满足下载（未开启去重或未命中时返回 false）
+    ///
+    /// 文件夹子任务创建前也会调用，命中时由调用方按已完成计数
+    pub async fn reuse_local_duplicate(&self, target: &std::path::Path, total_size: u64, expected_md5: Option<&str>) -> bool {
+        let Some(md5) = expected_md5 else {
+            return false;
+        };
+        let dedup_index = self.chunk_scheduler.dedup_index();
+        let Some(source) = dedup_index.lookup(md5, total_size) else {
+            return false;
+        };
+        if source == target {
+            return false;
+        }
 
-        self.create_task_internal(fs_id, remote_path, local_path, total_size, conflict_strategy)
+        let hardlink = dedup_index.allows_hardlink();
+        let (src, dst) = (source.clone(), target.to_path_buf());
+        let result = tokio::task::spawn_blocking(move || crate::downloader::dedup::link_or_copy(&src, &dst, hardlink))
             .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+
+        match result {
+            Ok(()) => {
+                info!("本地已有相同文件，跳过下载: {:?} -> {:?}", source, target);
+                true
+            }
+            Err(e) => {
+                warn!("复用本地已有文件失败，改为正常下载: {:?} -> {:?}, 错误: {}", source, target, e);
+                false
+            }
+        }
     }
 
     /// 创建下载任务（指定下载目录）
     ///
-    /// 用于批量下载时支持自定义下载目录
+    /// 用于批量下载时支持自定义下载目录，batch_id 为批量下载请求的批次 ID
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_task_with_dir(
         &self,
         fs_id: u64,
@@ -271,53 +396,53 @@ impl DownloadManager {
         filename: String,
         total_size: u64,
         target_dir: &std::path::Path,
+        expected_md5: Option<String>,
+        batch_id: Option<String>,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
     ) -> Result<String> {
+        self.ensure_download_dir_allowed(target_dir).await?;
+
         let local_path = target_dir.join(&filename);
-        self.create_task_internal(fs_id, remote_path, local_path, total_size, conflict_strategy)
+        self.create_task_internal(fs_id, remote_path, local_path, total_size, expected_md5, batch_id, conflict_strategy)
             .await
     }
 
     /// 内部方法：创建下载任务
+    #[allow(clippy::too_many_arguments)]
     async fn create_task_internal(
         &self,
         fs_id: u64,
         remote_path: String,
         local_path: PathBuf,
         total_size: u64,
+        expected_md5: Option<String>,
+        batch_id: Option<String>,
         conflict_strategy: Option<crate::uploader::conflict::DownloadConflictStrategy>,
     ) -> Result<String> {
         // 获取默认策略（如果未指定）
         let strategy = conflict_strategy.unwrap_or(crate::uploader::conflict::DownloadConflictStrategy::Overwrite);
 
-        // 解决冲突
+        // 解决冲突（跳过策略会校验已有文件的大小/MD5）
         use crate::uploader::conflict_resolver::ConflictResolver;
-        let resolution = ConflictResolver::resolve_download_conflict(&local_path, strategy)?;
+        let resolution = ConflictResolver::resolve_download_conflict_verified(
+            &local_path,
+            strategy,
+            total_size,
+            expected_md5.as_deref(),
+        )
+        .await?;
 
         // 根据解决方案处理
-        let final_local_path = match resolution {
-            crate::uploader::conflict::ConflictResolution::Proceed => local_path,
+        // 🔥 跳过时仍创建任务并直接标记为已完成，便于在任务列表中看到结果
+        let (final_local_path, mut already_complete) = match resolution {
+            crate::uploader::conflict::ConflictResolution::Proceed => (local_path, false),
             crate::uploader::conflict::ConflictResolution::Skip => {
-                // 发送跳过事件
-                let filename = local_path
-                    .file_name()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                info!("跳过下载（文件已存在）: {:?}", local_path);
-
-                self.publish_event(DownloadEvent::Skipped {
-                    task_id: format!("skipped-{}", uuid::Uuid::new_v4()),
-                    filename,
-                    reason: "文件已存在".to_string(),
-                })
-                    .await;
-
-                return Ok("skipped".to_string());
+                info!("跳过下载（文件已存在且内容一致）: {:?}", local_path);
+                (local_path, true)
             }
             crate::uploader::conflict::ConflictResolution::UseNewPath(new_path) => {
                 info!("自动重命名下载路径: {:?} -> {}", local_path, new_path);
-                PathBuf::from(new_path)
+                (PathBuf::from(new_path), false)
             }
         };
 
@@ -328,6 +453,11 @@ impl DownloadManager {
             }
         }
 
+        // 🔥 本地去重：已下载过相同 MD5 的文件时直接复制（或按配置硬链接），不再下载
+        if !already_complete {
+            already_complete = self.reuse_local_duplicate(&final_local_path, total_size, expected_md5.as_deref()).await;
+        }
+
         let filename = final_local_path
             .file_name()
             .map(|s| s.to_string_lossy().to_string())
@@ -338,12 +468,21 @@ impl DownloadManager {
 
         let mut task = DownloadTask::new(fs_id, remote_path.clone(), final_local_path.clone(), total_size);
 
+        // 🔥 记录网盘 MD5，下载完成后校验完整性（空字符串视为未提供）
+        task.expected_md5 = expected_md5.filter(|md5| !md5.is_empty());
+        task.batch_id = batch_id.clone();
+
         // 🔥 设置原始文件名和加密标记
         if let Some(ref orig_name) = original_filename {
             task.original_filename = Some(orig_name.clone());
             task.is_encrypted = true;
         }
 
+        if already_complete {
+            task.downloaded_size = total_size;
+            task.mark_completed();
+        }
+
         let task_id = task.id.clone();
         let group_id = task.group_id.clone();
 
@@ -352,8 +491,10 @@ impl DownloadManager {
         let task_arc = Arc::new(Mutex::new(task));
         self.tasks.write().await.insert(task_id.clone(), task_arc);
 
-        // 🔥 活跃计数 +1（新建任务为 Pending）
-        self.inc_active();
+        // 🔥 活跃计数 +1（新建任务为 Pending，已完成的跳过任务不计入）
+        if !already_complete {
+            self.inc_active();
+        }
 
         // 🔥 发送任务创建事件
         self.publish_event(DownloadEvent::Created {
@@ -362,15 +503,41 @@ impl DownloadManager {
             remote_path,
             local_path: final_local_path.to_string_lossy().to_string(),
             total_size,
-            group_id,
+            group_id: group_id.clone(),
             is_backup: false,
             original_filename,
+            batch_id,
         })
             .await;
 
+        if already_complete {
+            self.publish_event(DownloadEvent::Completed {
+                task_id: task_id.clone(),
+                completed_at: chrono::Utc::now().timestamp_millis(),
+                group_id,
+                is_backup: false,
+            })
+                .await;
+        }
+
         Ok(task_id)
     }
 
+    /// 任务是否已完成（跳过策略下已有文件一致时，新建任务会直接标记为已完成）
+    pub async fn is_task_completed(&self, task_id: &str) -> bool {
+        match self.tasks.read().await.get(task_id) {
+            Some(task) => task.lock().await.status == TaskStatus::Completed,
+            None => false,
+        }
+    }
+
+    /// 🔥 通知文件夹管理器子文件已跳过（已有文件一致，按已完成计数）
+    pub async fn notify_subtask_skipped(&self, group_id: String, task_id: String, total_size: u64) {
+        self.chunk_scheduler
+            .notify_subtask_completed(group_id, task_id, total_size)
+            .await;
+    }
+
     /// 🔥 查询映射表获取原始文件名
     async fn query_original_filename(&self, encrypted_filename: &str) -> Option<String> {
         // 检查是否为加密文件名格式
@@ -404,6 +571,13 @@ impl DownloadManager {
     /// 2. 如果没有任务位，加入等待队列
     /// 3. 获得任务位后，启动任务
     pub async fn start_task(&self, task_id: &str) -> Result<()> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            anyhow::bail!("服务正在关闭，无法启动任务");
+        }
+        if self.relocating_tasks.read().await.contains(task_id) {
+            anyhow::bail!("任务正在修改下载目录，请稍后再试");
+        }
+
         let task = self
             .tasks
             .read()
@@ -425,7 +599,7 @@ impl DownloadManager {
             t.group_id.is_some()
         };
 
-        info!("请求启动下载任务: {} (文件夹子任务: {})", task_id, is_folder_task);
+        info!(task_id = %task_id, event = "start", is_folder_task, "请求启动下载任务");
 
         // 🔥 关键修复：文件夹子任务必须检查是否有槽位，没有槽位不能启动
         if is_folder_task {
@@ -451,6 +625,22 @@ impl DownloadManager {
 
         // 🔥 尝试分配固定任务位（文件夹子任务由 FolderManager 管理槽位，这里跳过）
         if !is_folder_task {
+            // 🔥 限流冷却期间不启动新任务，加入等待队列，冷却结束后由等待队列监控自动启动
+            if self.rate_limit_cooldown.is_cooling_down() {
+                let is_backup = task.lock().await.is_backup;
+                info!("限流冷却中，任务 {} 加入等待队列", task_id);
+                self.add_to_waiting_queue_by_priority(task_id, is_backup).await;
+                return Ok(());
+            }
+
+            // 🔥 不在下载时间段内，加入等待队列，进入时间段后由等待队列监控自动启动
+            if !self.download_schedule.is_open() {
+                let is_backup = task.lock().await.is_backup;
+                info!("不在下载时间段内，任务 {} 加入等待队列", task_id);
+                self.add_to_waiting_queue_by_priority(task_id, is_backup).await;
+                return Ok(());
+            }
+
             // 获取任务是否为备份任务和 group_id（用于槽位刷新）
             let (is_backup, start_task_group_id) = {
                 let t = task.lock().await;
@@ -485,12 +675,8 @@ impl DownloadManager {
                     return Ok(());
                 }
             } else {
-                // 普通任务：使用带优先级的分配方法，可以抢占备份任务
-                let result = self.task_slot_pool.allocate_fixed_slot_with_priority(
-                    task_id, false, TaskPriority::Normal
-                ).await;
-
-                match result {
+                // 普通任务：空闲位 → 抢占备份任务 → 回收文件夹借调位
+                match self.acquire_fixed_slot(task_id, TaskPriority::Normal).await {
                     Some((slot_id, preempted_task_id)) => {
                         // 分配成功，记录槽位信息
                         {
@@ -516,78 +702,14 @@ impl DownloadManager {
                         }
                     }
                     None => {
-                        // 🔥 无可用任务位，先尝试回收文件夹的借调槽位
-                        let folder_manager = {
-                            let fm = self.folder_manager.read().await;
-                            fm.clone()
-                        };
-
-                        if let Some(fm) = folder_manager {
-                            // 检查是否有借调槽位可回收
-                            if self.task_slot_pool.find_folder_with_borrowed_slots().await.is_some() {
-                                info!("普通任务 {} 无可用槽位，尝试回收文件夹借调槽位", task_id);
-
-                                // 尝试回收一个借调槽位
-                                if let Some(reclaimed_slot_id) = fm.reclaim_borrowed_slot().await {
-                                    // 回收成功，分配槽位给新任务
-                                    if let Some((slot_id, preempted_task_id)) = self.task_slot_pool.allocate_fixed_slot_with_priority(
-                                        task_id, false, TaskPriority::Normal
-                                    ).await {
-                                        {
-                                            let mut t = task.lock().await;
-                                            t.slot_id = Some(slot_id);
-                                            t.is_borrowed_slot = false;
-                                        }
-                                        // 🔥 槽位分配后立即刷新，防止长准备阶段被误判超时
-                                        self.task_slot_pool.touch_slot(&start_task_touch_id).await;
-                                        // 🔥 处理被抢占的备份任务
-                                        if let Some(preempted_id) = preempted_task_id {
-                                            info!("普通任务 {} 通过回收借调槽位获得任务位并抢占了备份任务 {}: slot_id={} (回收的槽位={})，已刷新槽位时间戳", task_id, preempted_id, slot_id, reclaimed_slot_id);
-                                            self.pause_preempted_task(&preempted_id).await;
-                                            // 🔥 将被暂停的备份任务加入等待队列末尾（包含状态转换和通知）
-                                            self.add_preempted_backup_to_queue(&preempted_id).await;
-                                        } else {
-                                            info!("普通任务 {} 通过回收借调槽位获得任务位: slot_id={} (回收的槽位={})，已刷新槽位时间戳", task_id, slot_id, reclaimed_slot_id);
-                                        }
-                                    } else {
-                                        warn!("回收借调槽位成功但重新分配失败，普通任务 {} 加入等待队列", task_id);
-                                        self.add_to_waiting_queue_by_priority(task_id, false).await;
-                                        return Ok(());
-                                    }
-                                } else {
-                                    // 回收失败，加入等待队列
-                                    info!("回收借调槽位失败，普通任务 {} 加入等待队列", task_id);
-                                    self.add_to_waiting_queue_by_priority(task_id, false).await;
-                                    info!(
-                                        "普通任务 {} 无可用任务位，加入等待队列 (已用槽位: {}/{})",
-                                        task_id,
-                                        self.task_slot_pool.used_slots().await,
-                                        self.max_concurrent_tasks
-                                    );
-                                    return Ok(());
-                                }
-                            } else {
-                                // 没有借调槽位可回收，直接加入等待队列
-                                self.add_to_waiting_queue_by_priority(task_id, false).await;
-                                info!(
-                                    "普通任务 {} 无可用任务位且无借调槽位可回收，加入等待队列 (已用槽位: {}/{})",
-                                    task_id,
-                                    self.task_slot_pool.used_slots().await,
-                                    self.max_concurrent_tasks
-                                );
-                                return Ok(());
-                            }
-                        } else {
-                            // 无文件夹管理器，直接加入等待队列
-                            self.add_to_waiting_queue_by_priority(task_id, false).await;
-                            info!(
-                                "普通任务 {} 无可用任务位，加入等待队列 (已用槽位: {}/{})",
-                                task_id,
-                                self.task_slot_pool.used_slots().await,
-                                self.max_concurrent_tasks
-                            );
-                            return Ok(());
-                        }
+                        self.add_to_waiting_queue_by_priority(task_id, false).await;
+                        info!(
+                            "普通任务 {} 无可用任务位，加入等待队列 (已用槽位: {}/{})",
+                            task_id,
+                            self.task_slot_pool.used_slots().await,
+                            self.max_concurrent_tasks
+                        );
+                        return Ok(());
                     }
                 }
             }
@@ -723,7 +845,10 @@ impl DownloadManager {
         let tasks_clone = self.tasks.clone(); // 🔥 用于 handle_task_failure 的优先级队列插入
         let snapshot_manager_arc = self.snapshot_manager.clone(); // 🔥 用于查询加密文件映射
         let encryption_config_store_arc = self.encryption_config_store.clone(); // 🔥 用于根据 key_version 选择解密密钥
-        let max_retries = self.max_retries;
+        let retry_config = self.retry_config.clone();
+        let global_speed_limiter = self.global_speed_limiter.clone(); // 🔥 全局限速器（所有任务共享）
+        let free_space_reserve = self.min_free_space_mb.load(Ordering::Relaxed) * 1024 * 1024;
+        let max_chunks_per_task = self.max_chunks_per_task.load(Ordering::Relaxed);
 
         tokio::spawn(async move {
             // 获取 WebSocket 管理器和文件夹进度发送器
@@ -732,6 +857,37 @@ impl DownloadManager {
             let backup_notification_tx = backup_notification_tx_arc.read().await.clone();
             let snapshot_manager = snapshot_manager_arc.read().await.clone(); // 🔥 获取快照管理器
             let encryption_config_store = encryption_config_store_arc.read().await.clone(); // 🔥 获取加密配置存储
+
+            // 🔥 磁盘空间预检查：剩余待下载字节 + 预留空间，不足时直接失败，避免写满磁盘后才报错
+            // 文件夹子任务已在扫描完成时按文件夹总剩余大小检查；子任务失败会被放回队列，逐个检查只会反复重试
+            let (target_dir, remaining) = if is_folder_task {
+                (None, 0)
+            } else {
+                let t = task_clone.lock().await;
+                (
+                    t.local_path.parent().map(|p| p.to_path_buf()),
+                    t.total_size.saturating_sub(t.downloaded_size),
+                )
+            };
+            if let Some(dir) = target_dir {
+                if let Err(e) = crate::config::PathValidator::ensure_free_space(&dir, remaining, free_space_reserve) {
+                    let error_msg = e.to_string();
+                    error!("任务 {} 磁盘空间检查失败: {}", task_id_clone, error_msg);
+                    Self::handle_task_failure(
+                        task_id_clone,
+                        task_clone,
+                        error_msg,
+                        waiting_queue,
+                        cancellation_tokens,
+                        ws_manager,
+                        persistence_manager,
+                        tasks_clone,
+                    )
+                        .await;
+                    return;
+                }
+            }
+
             // 准备任务
             let prepare_result = engine
                 .prepare_for_scheduling(task_clone.clone(), cancellation_token.clone())
@@ -758,6 +914,7 @@ impl DownloadManager {
                     let (
                         total_size,
                         remote_path,
+                        refresh_target,
                         fs_id,
                         local_path,
                         group_id,
@@ -771,6 +928,7 @@ impl DownloadManager {
                         (
                             t.total_size,
                             t.remote_path.clone(),
+                            LinkRefreshTarget::from_task(&t),
                             t.fs_id,
                             t.local_path.clone(),
                             t.group_id.clone(),
@@ -889,6 +1047,12 @@ impl DownloadManager {
                             );
                         }
 
+                        // 🔥 补充写入注册参数之外的任务属性（MD5 等），恢复后仍然生效
+                        Self::persist_task_extras(pm, &task_clone, &task_id_clone).await;
+
+                        // 🔥 远端文件已变化时放弃断点，避免把新旧内容拼接到同一个文件
+                        Self::validate_resume_remote(pm, &task_clone, &task_id_clone, chunk_size, total_chunks).await;
+
                         // 🔥 修复：从持久化管理器获取已完成的分片，并标记到 ChunkManager（实现真正的断点续传）
                         if let Some(completed_chunks) = pm.lock().await.get_completed_chunks(&task_id_clone) {
                             let mut cm = chunk_manager.lock().await;
@@ -922,7 +1086,12 @@ impl DownloadManager {
                     }
 
                     // 创建任务调度信息
-                    let max_concurrent_chunks = calculate_task_max_chunks(total_size);
+                    let max_concurrent_chunks = resolve_task_max_chunks(
+                        total_size,
+                        task_clone.lock().await.max_chunks_override,
+                        max_chunks_per_task,
+                        engine.max_chunks_ceiling(),
+                    );
                     info!(
                         "任务 {} 文件大小 {} 字节, 最大并发分片数: {}",
                         task_id_clone, total_size, max_concurrent_chunks
@@ -946,6 +1115,11 @@ impl DownloadManager {
                         task_slot_pool_clone.clone(), touch_id,
                     ));
 
+                    // 🔥 创建刷新协调器（每个任务独立一个，检测循环与手动刷新共享，防止并发刷新）
+                    let refresh_coordinator = Arc::new(RefreshCoordinator::new(
+                        RefreshCoordinatorConfig::default(),
+                    ));
+
                     let task_info = TaskScheduleInfo {
                         task_id: task_id_clone.clone(),
                         task: task_clone.clone(),
@@ -978,12 +1152,14 @@ impl DownloadManager {
                         encryption_config_store: encryption_config_store.clone(),
                         // 🔥 Manager 任务列表引用（用于任务完成时立即清理）
                         manager_tasks: Some(tasks_clone.clone()),
-                        // 🔥 链接级重试次数（从配置读取）
-                        max_retries,
+                        // 🔥 分片重试配置（从配置读取）
+                        retry_config: retry_config.clone(),
                         // 🔥 代理故障回退管理器
                         fallback_mgr: engine.fallback_mgr.clone(),
                         // 🔥 任务级共享槽位刷新节流器
                         slot_touch_throttler,
+                        global_speed_limiter: global_speed_limiter.clone(),
+                        refresh_coordinator: refresh_coordinator.clone(),
                     };
 
                     // 注册到调度器
@@ -992,16 +1168,11 @@ impl DownloadManager {
                             // 注册成功，启动速度异常检测循环和线程停滞检测循环
                             info!("任务 {} 注册成功，启动CDN链接检测", task_id_clone);
 
-                            // 创建刷新协调器（每个任务独立一个，防止并发刷新）
-                            let refresh_coordinator = Arc::new(RefreshCoordinator::new(
-                                RefreshCoordinatorConfig::default(),
-                            ));
-
                             // 启动速度异常检测循环
                             let _speed_anomaly_handle =
                                 DownloadEngine::start_speed_anomaly_detection(
                                     engine.clone(),
-                                    remote_path.clone(),
+                                    refresh_target.clone(),
                                     total_size,
                                     url_health_for_detection.clone(),
                                     Arc::new(chunk_scheduler_for_detection.clone()),
@@ -1014,7 +1185,7 @@ impl DownloadManager {
                             // 启动线程停滞检测循环
                             let _stagnation_handle = DownloadEngine::start_stagnation_detection(
                                 engine.clone(),
-                                remote_path,
+                                refresh_target,
                                 total_size,
                                 url_health_for_detection,
                                 client_for_detection,
@@ -1084,6 +1255,14 @@ impl DownloadManager {
     /// - 备份任务使用 allocate_backup_slot（不抢占）
     /// - 普通任务使用 allocate_fixed_slot_with_priority（可抢占备份任务）
     pub(crate) async fn try_start_waiting_tasks(&self) {
+        // 🔥 限流冷却、不在下载时间段或服务关闭期间暂停启动新任务
+        if self.rate_limit_cooldown.is_cooling_down()
+            || !self.download_schedule.is_open()
+            || self.shutting_down.load(Ordering::SeqCst)
+        {
+            return;
+        }
+
         loop {
             // 检查是否有可用任务槽
             let available_slots = self.task_slot_pool.available_slots().await;
@@ -1099,6 +1278,12 @@ impl DownloadManager {
 
             match task_id {
                 Some(id) => {
+                    // 🔥 用户一键暂停的任务不自动启动
+                    if self.user_paused_tasks.read().await.contains(&id) {
+                        info!("任务 {} 已被用户暂停，跳过自动启动", id);
+                        continue;
+                    }
+
                     // 🔥 获取任务信息：是否为备份任务、是否需要槽位、是否为文件夹子任务、group_id
                     let (is_backup, needs_slot, is_folder_subtask, try_start_group_id) = {
                         if let Some(task) = self.tasks.read().await.get(&id).cloned() {
@@ -1217,7 +1402,13 @@ impl DownloadManager {
         let backup_notification_tx_arc = self.backup_notification_tx.clone();
         let snapshot_manager_arc = self.snapshot_manager.clone(); // 🔥 用于查询加密文件映射
         let encryption_config_store_arc = self.encryption_config_store.clone(); // 🔥 用于根据 key_version 选择解密密钥
-        let max_retries = self.max_retries;
+        let retry_config = self.retry_config.clone();
+        let global_speed_limiter = self.global_speed_limiter.clone(); // 🔥 全局限速器（所有任务共享）
+        let user_paused_tasks = self.user_paused_tasks.clone(); // 🔥 用户一键暂停的任务不自动启动
+        let rate_limit_cooldown = self.rate_limit_cooldown.clone(); // 🔥 限流冷却期间不启动新任务
+        let shutting_down = self.shutting_down.clone(); // 🔥 服务关闭期间不启动新任务
+        let download_schedule = self.download_schedule.clone(); // 🔥 不在下载时间段内不启动新任务
+        let max_chunks_per_task = self.max_chunks_per_task.clone(); // 🔥 单任务并发分片数默认覆盖值
 
         tokio::spawn(async move {
             // 🔥 优化：缩短检查间隔从3秒到1秒，减少等待时间
@@ -1233,7 +1424,11 @@ impl DownloadManager {
                     !queue.is_empty()
                 };
 
-                if !has_waiting {
+                if !has_waiting
+                    || rate_limit_cooldown.is_cooling_down()
+                    || !download_schedule.is_open()
+                    || shutting_down.load(Ordering::SeqCst)
+                {
                     continue;
                 }
 
@@ -1258,6 +1453,11 @@ impl DownloadManager {
 
                     match task_id {
                         Some(id) => {
+                            if user_paused_tasks.read().await.contains(&id) {
+                                info!("后台监控：任务 {} 已被用户暂停，跳过自动启动", id);
+                                continue;
+                            }
+
                             info!("🔄 后台监控：从等待队列启动任务 {} (可用槽位: {})", id, available_slots);
 
                             // 获取任务
@@ -1323,6 +1523,7 @@ impl DownloadManager {
                                 // 启动任务（简化版，直接在这里处理）
                                 let engine_clone = engine.clone();
                                 let task_clone = task.clone();
+                                let max_chunks_per_task_clone = max_chunks_per_task.clone();
                                 let chunk_scheduler_clone = chunk_scheduler.clone();
                                 let id_clone = id.clone();
                                 let cancellation_tokens_clone = cancellation_tokens.clone();
@@ -1335,6 +1536,8 @@ impl DownloadManager {
                                 let tasks_clone = tasks.clone(); // 🔥 用于 handle_task_failure 的优先级队列插入
                                 let snapshot_manager_arc_clone = snapshot_manager_arc.clone(); // 🔥 用于查询加密文件映射
                                 let encryption_config_store_arc_clone = encryption_config_store_arc.clone(); // 🔥 用于根据 key_version 选择解密密钥
+                                let global_speed_limiter_clone = global_speed_limiter.clone(); // 🔥 全局限速器
+                                let retry_config_clone = retry_config.clone(); // 🔥 分片重试配置
 
                                 tokio::spawn(async move {
                                     // 获取 WebSocket 管理器和文件夹进度发送器
@@ -1373,6 +1576,7 @@ impl DownloadManager {
                                             let (
                                                 total_size,
                                                 remote_path,
+                                                refresh_target,
                                                 fs_id,
                                                 local_path,
                                                 group_id,
@@ -1386,6 +1590,7 @@ impl DownloadManager {
                                                 (
                                                     t.total_size,
                                                     t.remote_path.clone(),
+                                                    LinkRefreshTarget::from_task(&t),
                                                     t.fs_id,
                                                     t.local_path.clone(),
                                                     t.group_id.clone(),
@@ -1502,6 +1707,12 @@ impl DownloadManager {
                                                     );
                                                 }
 
+                                                // 🔥 补充写入注册参数之外的任务属性（MD5 等），恢复后仍然生效
+                                                Self::persist_task_extras(pm, &task_clone, &id_clone).await;
+
+                                                // 🔥 远端文件已变化时放弃断点，避免把新旧内容拼接到同一个文件
+                                                Self::validate_resume_remote(pm, &task_clone, &id_clone, chunk_size, total_chunks).await;
+
                                                 // 🔥 修复：从持久化管理器获取已完成的分片，并标记到 ChunkManager（实现真正的断点续传）
                                                 if let Some(completed_chunks) = pm.lock().await.get_completed_chunks(&id_clone) {
                                                     let mut cm = chunk_manager.lock().await;
@@ -1534,8 +1745,12 @@ impl DownloadManager {
                                                 }
                                             }
 
-                                            let max_concurrent_chunks =
-                                                calculate_task_max_chunks(total_size);
+                                            let max_concurrent_chunks = resolve_task_max_chunks(
+                                                total_size,
+                                                task_clone.lock().await.max_chunks_override,
+                                                max_chunks_per_task_clone.load(Ordering::Relaxed),
+                                                engine_clone.max_chunks_ceiling(),
+                                            );
                                             info!(
                                                 "后台任务 {} 文件大小 {} 字节, 最大并发分片数: {}",
                                                 id_clone, total_size, max_concurrent_chunks
@@ -1561,6 +1776,11 @@ impl DownloadManager {
                                                 task_slot_pool_clone.clone(), touch_id,
                                             ));
 
+                                            // 🔥 创建刷新协调器（每个任务独立一个，检测循环与手动刷新共享，防止并发刷新）
+                                            let refresh_coordinator = Arc::new(RefreshCoordinator::new(
+                                                RefreshCoordinatorConfig::default(),
+                                            ));
+
                                             let task_info = TaskScheduleInfo {
                                                 task_id: id_clone.clone(),
                                                 task: task_clone.clone(),
@@ -1596,12 +1816,14 @@ impl DownloadManager {
                                                 encryption_config_store: encryption_config_store.clone(),
                                                 // 🔥 Manager 任务列表引用（用于任务完成时立即清理）
                                                 manager_tasks: Some(tasks_clone.clone()),
-                                                // 🔥 链接级重试次数（从配置读取）
-                                                max_retries,
+                                                // 🔥 分片重试配置（从配置读取）
+                                                retry_config: retry_config_clone.clone(),
                                                 // 🔥 代理故障回退管理器
                                                 fallback_mgr: engine_clone.fallback_mgr.clone(),
                                                 // 🔥 任务级共享槽位刷新节流器
                                                 slot_touch_throttler,
+                                                global_speed_limiter: global_speed_limiter_clone.clone(),
+                                                refresh_coordinator: refresh_coordinator.clone(),
                                             };
 
                                             // 注册任务到调度器
@@ -1616,16 +1838,10 @@ impl DownloadManager {
                                                         id_clone
                                                     );
 
-                                                    // 创建刷新协调器
-                                                    let refresh_coordinator =
-                                                        Arc::new(RefreshCoordinator::new(
-                                                            RefreshCoordinatorConfig::default(),
-                                                        ));
-
                                                     // 启动速度异常检测循环
                                                     let _speed_anomaly_handle = DownloadEngine::start_speed_anomaly_detection(
                                                         engine_clone.clone(),
-                                                        remote_path.clone(),
+                                                        refresh_target.clone(),
                                                         total_size,
                                                         url_health_for_detection.clone(),
                                                         Arc::new(chunk_scheduler_for_detection.clone()),
@@ -1639,7 +1855,7 @@ impl DownloadManager {
                                                     let _stagnation_handle =
                                                         DownloadEngine::start_stagnation_detection(
                                                             engine_clone.clone(),
-                                                            remote_path,
+                                                            refresh_target,
                                                             total_size,
                                                             url_health_for_detection,
                                                             client_for_detection,
@@ -1761,6 +1977,93 @@ impl DownloadManager {
         info!("下载管理器已设置槽位超时释放处理器");
     }
 
+    /// 🔥 设置任务级重试处理器
+    ///
+    /// 调度器将可恢复错误导致失败的任务转为 Pending 后发送 (task_id, attempt)，
+    /// 这里按退避延迟后将任务放回等待队列，由等待队列监控重新执行 prepare_for_scheduling 获取新链接
+    fn setup_task_retry_handler(&self) {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(String, u32)>();
+
+        // 设置通知通道到调度器
+        let chunk_scheduler = self.chunk_scheduler.clone();
+        tokio::spawn(async move {
+            chunk_scheduler.set_task_retry_sender(tx).await;
+        });
+
+        // 启动监听循环
+        let tasks = self.tasks.clone();
+        let waiting_queue = self.waiting_queue.clone();
+        let retry_config = self.retry_config.clone();
+        tokio::spawn(async move {
+            while let Some((task_id, attempt)) = rx.recv().await {
+                let tasks = tasks.clone();
+                let waiting_queue = waiting_queue.clone();
+                let delay_ms = retry_config.delay_ms(attempt);
+                tokio::spawn(async move {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+                    // 退避期间任务可能已被暂停或删除，只重新排队仍处于 Pending 的任务
+                    let task = tasks.read().await.get(&task_id).cloned();
+                    let still_pending = match task {
+                        Some(task) => task.lock().await.status == TaskStatus::Pending,
+                        None => false,
+                    };
+                    if !still_pending {
+                        info!("任务 {} 在重试等待期间状态已变化，取消任务级重试", task_id);
+                        return;
+                    }
+                    if waiting_queue.read().await.contains(&task_id) {
+                        return;
+                    }
+
+                    info!("任务 {} 等待 {}ms 后重新排队（任务级重试第 {} 次）", task_id, delay_ms, attempt);
+                    Self::add_to_queue_by_priority(&waiting_queue, &tasks, &task_id, false, false).await;
+                });
+            }
+        });
+
+        info!("下载管理器已设置任务级重试处理器");
+    }
+
+    /// 🔥 设置限流冷却通知处理器
+    ///
+    /// 冷却器触发全局冷却时发送冷却秒数，这里向前端广播 RateLimited 消息；
+    /// 冷却到期后等待队列监控自动恢复启动任务
+    fn setup_rate_limit_handler(&self) {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<u64>();
+        self.rate_limit_cooldown.set_cooldown_sender(tx);
+
+        let ws_manager_arc = self.ws_manager.clone();
+        let rate_limit_cooldown = self.rate_limit_cooldown.clone();
+        let chunk_scheduler = self.chunk_scheduler.clone();
+        tokio::spawn(async move {
+            while let Some(cooldown_secs) = rx.recv().await {
+                if let Some(ws_manager) = ws_manager_arc.read().await.as_ref() {
+                    ws_manager.broadcast(WsServerMessage::rate_limited(cooldown_secs));
+                }
+
+                let rate_limit_cooldown = rate_limit_cooldown.clone();
+                let chunk_scheduler = chunk_scheduler.clone();
+                tokio::spawn(async move {
+                    let mut wait = tokio::time::Duration::from_secs(cooldown_secs);
+                    // 等到冷却真正结束（剩余时间为空）后再恢复调度
+                    loop {
+                        tokio::time::sleep(wait).await;
+                        match rate_limit_cooldown.remaining() {
+                            Some(remaining) => wait = remaining,
+                            None => break,
+                        }
+                    }
+                    info!("限流冷却结束，恢复正常调度");
+                    // 冷却期间没有启动的等待任务，冷却结束后立即检查
+                    chunk_scheduler.trigger_waiting_queue().await;
+                });
+            }
+        });
+
+        info!("下载管理器已设置限流冷却通知处理器");
+    }
+
     /// 🔥 设置任务完成触发器（0延迟启动等待任务）
     ///
     /// 当调度器检测到任务完成时，会通过 channel 发送信号，
@@ -1787,7 +2090,13 @@ impl DownloadManager {
         let backup_notification_tx_arc = self.backup_notification_tx.clone();
         let snapshot_manager_arc = self.snapshot_manager.clone(); // 🔥 用于查询加密文件映射
         let encryption_config_store_arc = self.encryption_config_store.clone(); // 🔥 用于根据 key_version 选择解密密钥
-        let max_retries = self.max_retries;
+        let retry_config = self.retry_config.clone();
+        let global_speed_limiter = self.global_speed_limiter.clone(); // 🔥 全局限速器（所有任务共享）
+        let user_paused_tasks = self.user_paused_tasks.clone(); // 🔥 用户一键暂停的任务不自动启动
+        let rate_limit_cooldown = self.rate_limit_cooldown.clone(); // 🔥 限流冷却期间不启动新任务
+        let shutting_down = self.shutting_down.clone(); // 🔥 服务关闭期间不启动新任务
+        let download_schedule = self.download_schedule.clone(); // 🔥 不在下载时间段内不启动新任务
+        let max_chunks_per_task = self.max_chunks_per_task.clone(); // 🔥 单任务并发分片数默认覆盖值
 
         tokio::spawn(async move {
             while let Some(()) = rx.recv().await {
@@ -1798,7 +2107,11 @@ impl DownloadManager {
                     !queue.is_empty()
                 };
 
-                if !has_waiting {
+                if !has_waiting
+                    || rate_limit_cooldown.is_cooling_down()
+                    || !download_schedule.is_open()
+                    || shutting_down.load(Ordering::SeqCst)
+                {
                     continue;
                 }
 
@@ -1825,6 +2138,11 @@ impl DownloadManager {
 
                     match task_id {
                         Some(id) => {
+                            if user_paused_tasks.read().await.contains(&id) {
+                                info!("0延迟启动：任务 {} 已被用户暂停，跳过自动启动", id);
+                                continue;
+                            }
+
                             info!("⚡ 0延迟启动：从等待队列启动任务 {} (可用槽位: {})", id, available_slots);
 
                             // 获取任务
@@ -1891,6 +2209,7 @@ impl DownloadManager {
                                 // 启动任务
                                 let engine_clone = engine.clone();
                                 let task_clone = task.clone();
+                                let max_chunks_per_task_clone = max_chunks_per_task.clone();
                                 let chunk_scheduler_clone = chunk_scheduler.clone();
                                 let id_clone = id.clone();
                                 let cancellation_tokens_clone = cancellation_tokens.clone();
@@ -1901,8 +2220,10 @@ impl DownloadManager {
                                 let task_slot_pool_clone = task_slot_pool.clone();
                                 let snapshot_manager_arc_clone = snapshot_manager_arc.clone(); // 🔥 用于查询加密文件映射
                                 let encryption_config_store_arc_clone = encryption_config_store_arc.clone(); // 🔥 用于根据 key_version 选择解密密钥
+                                let global_speed_limiter_clone = global_speed_limiter.clone(); // 🔥 全局限速器
                                 let tasks_clone = tasks.clone(); // 🔥 用于任务完成时立即清理
                                 let waiting_queue_clone = waiting_queue.clone(); // 🔥 用于备份任务失败重试
+                                let retry_config_clone = retry_config.clone(); // 🔥 分片重试配置
 
                                 tokio::spawn(async move {
                                     // 获取 WebSocket 管理器和文件夹进度发送器
@@ -1941,6 +2262,7 @@ impl DownloadManager {
                                             let (
                                                 total_size,
                                                 remote_path,
+                                                refresh_target,
                                                 fs_id,
                                                 local_path,
                                                 group_id,
@@ -1954,6 +2276,7 @@ impl DownloadManager {
                                                 (
                                                     t.total_size,
                                                     t.remote_path.clone(),
+                                                    LinkRefreshTarget::from_task(&t),
                                                     t.fs_id,
                                                     t.local_path.clone(),
                                                     t.group_id.clone(),
@@ -2070,6 +2393,12 @@ impl DownloadManager {
                                                     );
                                                 }
 
+                                                // 🔥 补充写入注册参数之外的任务属性（MD5 等），恢复后仍然生效
+                                                Self::persist_task_extras(pm, &task_clone, &id_clone).await;
+
+                                                // 🔥 远端文件已变化时放弃断点，避免把新旧内容拼接到同一个文件
+                                                Self::validate_resume_remote(pm, &task_clone, &id_clone, chunk_size, total_chunks).await;
+
                                                 // 🔥 修复：从持久化管理器获取已完成的分片，并标记到 ChunkManager（实现真正的断点续传）
                                                 if let Some(completed_chunks) = pm.lock().await.get_completed_chunks(&id_clone) {
                                                     let mut cm = chunk_manager.lock().await;
@@ -2102,8 +2431,12 @@ impl DownloadManager {
                                                 }
                                             }
 
-                                            let max_concurrent_chunks =
-                                                calculate_task_max_chunks(total_size);
+                                            let max_concurrent_chunks = resolve_task_max_chunks(
+                                                total_size,
+                                                task_clone.lock().await.max_chunks_override,
+                                                max_chunks_per_task_clone.load(Ordering::Relaxed),
+                                                engine_clone.max_chunks_ceiling(),
+                                            );
                                             info!(
                                                 "0延迟任务 {} 文件大小 {} 字节, 最大并发分片数: {}",
                                                 id_clone, total_size, max_concurrent_chunks
@@ -2128,6 +2461,11 @@ impl DownloadManager {
                                                 task_slot_pool_clone.clone(), touch_id,
                                             ));
 
+                                            // 🔥 创建刷新协调器（每个任务独立一个，检测循环与手动刷新共享，防止并发刷新）
+                                            let refresh_coordinator = Arc::new(RefreshCoordinator::new(
+                                                RefreshCoordinatorConfig::default(),
+                                            ));
+
                                             let task_info = TaskScheduleInfo {
                                                 task_id: id_clone.clone(),
                                                 task: task_clone.clone(),
@@ -2163,12 +2501,14 @@ impl DownloadManager {
                                                 encryption_config_store: encryption_config_store.clone(),
                                                 // 🔥 Manager 任务列表引用（用于任务完成时立即清理）
                                                 manager_tasks: Some(tasks_clone.clone()),
-                                                // 🔥 链接级重试次数（从配置读取）
-                                                max_retries,
+                                                // 🔥 分片重试配置（从配置读取）
+                                                retry_config: retry_config_clone.clone(),
                                                 // 🔥 代理故障回退管理器
                                                 fallback_mgr: engine_clone.fallback_mgr.clone(),
                                                 // 🔥 任务级共享槽位刷新节流器
                                                 slot_touch_throttler,
+                                                global_speed_limiter: global_speed_limiter_clone.clone(),
+                                                refresh_coordinator: refresh_coordinator.clone(),
                                             };
 
                                             match chunk_scheduler_clone
@@ -2181,14 +2521,9 @@ impl DownloadManager {
                                                         id_clone
                                                     );
 
-                                                    let refresh_coordinator =
-                                                        Arc::new(RefreshCoordinator::new(
-                                                            RefreshCoordinatorConfig::default(),
-                                                        ));
-
                                                     let _speed_anomaly_handle = DownloadEngine::start_speed_anomaly_detection(
                                                         engine_clone.clone(),
-                                                        remote_path.clone(),
+                                                        refresh_target.clone(),
                                                         total_size,
                                                         url_health_for_detection.clone(),
                                                         Arc::new(chunk_scheduler_for_detection.clone()),
@@ -2201,7 +2536,7 @@ impl DownloadManager {
                                                     let _stagnation_handle =
                                                         DownloadEngine::start_stagnation_detection(
                                                             engine_clone.clone(),
-                                                            remote_path,
+                                                            refresh_target,
                                                             total_size,
                                                             url_health_for_detection,
                                                             client_for_detection,
@@ -2562,8 +2897,16 @@ impl DownloadManager {
         info!("被抢占的备份任务 {} 已加入等待队列末尾", task_id);
     }
 
+    /// 🔥 等待队列排序键（值越小越先启动）
+    ///
+    /// 依次比较：是否为备份任务（备份任务始终排在最后）→ 用户设置的优先级 → 是否为文件夹子任务
+    fn queue_rank(is_backup: bool, priority: DownloadPriority, is_folder_subtask: bool) -> (bool, DownloadPriority, bool) {
+        (is_backup, priority, is_folder_subtask)
+    }
+
     /// 🔥 静态方法：按优先级将任务加入等待队列
     ///
+    /// 插入到第一个排序键大于该任务的位置之前，同一排序键保持先进先出
     /// 用于 handle_task_failure 等静态上下文中
     async fn add_to_queue_by_priority(
         waiting_queue: &Arc<RwLock<VecDeque<String>>>,
@@ -2572,61 +2915,36 @@ impl DownloadManager {
         is_backup: bool,
         is_folder_subtask: bool,
     ) {
-        let mut queue = waiting_queue.write().await;
+        // 使用 try_lock 避免调用方持有任务锁时死锁（取不到时按普通优先级处理）
+        let priority = match tasks.read().await.get(task_id) {
+            Some(task_arc) => task_arc.try_lock().map(|t| t.priority).unwrap_or_default(),
+            None => DownloadPriority::default(),
+        };
+        let rank = Self::queue_rank(is_backup, priority, is_folder_subtask);
 
-        if is_backup {
-            queue.push_back(task_id.to_string());
-            info!("备份任务 {} 加入等待队列末尾 (队列长度: {})", task_id, queue.len());
-        } else if is_folder_subtask {
-            // 文件夹子任务：插入到备份任务之前
-            let insert_pos = {
-                let tasks_guard = tasks.read().await;
-                let mut backup_pos = None;
-                for (i, id) in queue.iter().enumerate() {
-                    if let Some(task_arc) = tasks_guard.get(id) {
-                        if let Ok(t) = task_arc.try_lock() {
-                            if t.is_backup {
-                                backup_pos = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
-                backup_pos
-            };
+        let mut queue = waiting_queue.write().await;
+        let insert_pos = {
+            let tasks_guard = tasks.read().await;
+            queue.iter().position(|id| {
+                tasks_guard
+                    .get(id)
+                    .and_then(|task_arc| task_arc.try_lock().ok())
+                    .is_some_and(|t| Self::queue_rank(t.is_backup, t.priority, t.group_id.is_some()) > rank)
+            })
+        };
 
-            if let Some(pos) = insert_pos {
-                queue.insert(pos, task_id.to_string());
-                info!("文件夹子任务 {} 插入到等待队列位置 {} (队列长度: {})", task_id, pos, queue.len());
-            } else {
-                queue.push_back(task_id.to_string());
-                info!("文件夹子任务 {} 加入等待队列末尾 (队列长度: {})", task_id, queue.len());
-            }
+        if let Some(pos) = insert_pos {
+            queue.insert(pos, task_id.to_string());
+            info!(
+                "任务 {} 插入到等待队列位置 {} (is_backup={}, 优先级={:?}, 队列长度: {})",
+                task_id, pos, is_backup, priority, queue.len()
+            );
         } else {
-            // 普通任务：插入到文件夹子任务和备份任务之前
-            let insert_pos = {
-                let tasks_guard = tasks.read().await;
-                let mut pos = None;
-                for (i, id) in queue.iter().enumerate() {
-                    if let Some(task_arc) = tasks_guard.get(id) {
-                        if let Ok(t) = task_arc.try_lock() {
-                            if t.is_backup || t.group_id.is_some() {
-                                pos = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
-                pos
-            };
-
-            if let Some(pos) = insert_pos {
-                queue.insert(pos, task_id.to_string());
-                info!("普通任务 {} 插入到等待队列位置 {} (队列长度: {})", task_id, pos, queue.len());
-            } else {
-                queue.push_back(task_id.to_string());
-                info!("普通任务 {} 加入等待队列末尾 (队列长度: {})", task_id, queue.len());
-            }
+            queue.push_back(task_id.to_string());
+            info!(
+                "任务 {} 加入等待队列末尾 (is_backup={}, 优先级={:?}, 队列长度: {})",
+                task_id, is_backup, priority, queue.len()
+            );
         }
     }
 
@@ -2672,82 +2990,20 @@ impl DownloadManager {
         }
     }
 
-    /// 🔥 按优先级将任务加入等待队列（完整版，支持三级优先级）
+    /// 🔥 按优先级将任务加入等待队列
     ///
-    /// 等待队列按优先级排序：
-    /// - 普通下载任务（is_backup=false, is_folder_subtask=false）：最高优先级
-    /// - 文件夹子任务（is_backup=false, is_folder_subtask=true）：中等优先级
-    /// - 自动备份任务（is_backup=true）：最低优先级，插入到队列末尾
+    /// 等待队列排序规则（见 [`Self::queue_rank`]）：
+    /// - 自动备份任务（is_backup=true）：始终排在非备份任务之后
+    /// - 非备份任务按用户设置的优先级（高 > 普通 > 低）排序
+    /// - 同一优先级下普通下载任务先于文件夹子任务
+    /// - 排序键相同的任务保持先进先出
     ///
     /// # 参数
     /// - `task_id`: 任务ID
     /// - `is_backup`: 是否为备份任务
     /// - `is_folder_subtask`: 是否为文件夹子任务
     async fn add_to_waiting_queue_with_task_type(&self, task_id: &str, is_backup: bool, is_folder_subtask: bool) {
-        let mut queue = self.waiting_queue.write().await;
-
-        if is_backup {
-            // 备份任务：直接加入队列末尾
-            queue.push_back(task_id.to_string());
-            info!("备份任务 {} 加入等待队列末尾 (队列长度: {})", task_id, queue.len());
-        } else if is_folder_subtask {
-            // 文件夹子任务：插入到备份任务之前，但在普通任务之后
-            // 找到第一个备份任务或文件夹子任务的位置
-            let insert_pos = {
-                let tasks = self.tasks.read().await;
-                let mut backup_pos = None;
-                for (i, id) in queue.iter().enumerate() {
-                    if let Some(task_arc) = tasks.get(id) {
-                        if let Ok(t) = task_arc.try_lock() {
-                            if t.is_backup {
-                                backup_pos = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
-                backup_pos
-            };
-
-            if let Some(pos) = insert_pos {
-                // 插入到第一个备份任务之前
-                queue.insert(pos, task_id.to_string());
-                info!("文件夹子任务 {} 插入到等待队列位置 {} (在备份任务之前, 队列长度: {})", task_id, pos, queue.len());
-            } else {
-                // 没有备份任务，加入队列末尾
-                queue.push_back(task_id.to_string());
-                info!("文件夹子任务 {} 加入等待队列末尾 (无备份任务, 队列长度: {})", task_id, queue.len());
-            }
-        } else {
-            // 普通任务：插入到所有文件夹子任务和备份任务之前
-            // 找到第一个文件夹子任务或备份任务的位置
-            let insert_pos = {
-                let tasks = self.tasks.read().await;
-                let mut pos = None;
-                for (i, id) in queue.iter().enumerate() {
-                    if let Some(task_arc) = tasks.get(id) {
-                        if let Ok(t) = task_arc.try_lock() {
-                            // 找到第一个文件夹子任务或备份任务
-                            if t.is_backup || t.group_id.is_some() {
-                                pos = Some(i);
-                                break;
-                            }
-                        }
-                    }
-                }
-                pos
-            };
-
-            if let Some(pos) = insert_pos {
-                // 插入到第一个文件夹子任务或备份任务之前
-                queue.insert(pos, task_id.to_string());
-                info!("普通任务 {} 插入到等待队列位置 {} (在文件夹子任务/备份任务之前, 队列长度: {})", task_id, pos, queue.len());
-            } else {
-                // 没有文件夹子任务和备份任务，加入队列末尾
-                queue.push_back(task_id.to_string());
-                info!("普通任务 {} 加入等待队列末尾 (无低优先级任务, 队列长度: {})", task_id, queue.len());
-            }
-        }
+        Self::add_to_queue_by_priority(&self.waiting_queue, &self.tasks, task_id, is_backup, is_folder_subtask).await;
     }
 
     /// 🔥 从等待队列移除并暂停指定的任务列表
@@ -2852,6 +3108,30 @@ impl DownloadManager {
         false
     }
 
+    /// 🔥 为非备份任务获取固定任务位：空闲位 → 抢占备份任务 → 回收文件夹借调位
+    ///
+    /// 回收借调位时槽位直接转给当前任务（TaskSlotPool::reassign_borrowed_slot），
+    /// 不经过释放再分配，避免并发启动/恢复的任务抢走槽位导致超出 max_concurrent_tasks
+    ///
+    /// # 返回
+    /// (slot_id, 被抢占的备份任务ID)，无可用槽位时返回 None
+    async fn acquire_fixed_slot(&self, task_id: &str, priority: TaskPriority) -> Option<(usize, Option<String>)> {
+        match self.task_slot_pool.allocate_or_find_reclaimable(task_id, false, priority).await {
+            SlotAllocation::Allocated { slot_id, preempted } => Some((slot_id, preempted)),
+            SlotAllocation::Reclaimable => {
+                let fm = self.folder_manager.read().await.clone()?;
+                info!("任务 {} 无可用槽位，尝试回收文件夹借调槽位", task_id);
+                let slot_id = fm.reclaim_borrowed_slot(task_id, false, priority).await;
+                match slot_id {
+                    Some(slot_id) => info!("任务 {} 通过回收借调槽位获得任务位: slot_id={}", task_id, slot_id),
+                    None => info!("回收借调槽位失败，任务 {} 将加入等待队列", task_id),
+                }
+                slot_id.map(|slot_id| (slot_id, None))
+            }
+            SlotAllocation::Unavailable => None,
+        }
+    }
+
     /// 🔥 暂停被抢占的任务（简化版，不触发等待队列启动，避免递归）
     ///
     /// 用于 try_start_waiting_tasks 中抢占备份任务时使用
@@ -2969,6 +3249,13 @@ impl DownloadManager {
         let old_status;
         let is_backup;
 
+        if self.relocating_tasks.read().await.contains(task_id) {
+            anyhow::bail!("任务正在修改下载目录，请稍后再试");
+        }
+
+        // 🔥 手动恢复后不再视为一键暂停的任务
+        self.user_paused_tasks.write().await.retain(|id| id != task_id);
+
         // 检查任务状态并将 Paused/Failed 改回 Pending
 
         {
@@ -2987,8 +3274,9 @@ impl DownloadManager {
                 }
             }
 
-            // 将状态改回 Pending，准备重新启动
+            // 将状态改回 Pending，准备重新启动（手动恢复后重新计算任务级自动重试次数）
             t.status = TaskStatus::Pending;
+            t.task_retry_count = 0;
             group_id = t.group_id.clone();
             is_backup = t.is_backup;
         }
@@ -3070,11 +3358,7 @@ impl DownloadManager {
                 };
                 let task_type_str = if is_folder_subtask { "文件夹子任务" } else { "普通任务" };
 
-                let result = self.task_slot_pool.allocate_fixed_slot_with_priority(
-                    task_id, false, priority
-                ).await;
-
-                match result {
+                match self.acquire_fixed_slot(task_id, priority).await {
                     Some((slot_id, preempted_task_id)) => {
                         {
                             let mut t = task.lock().await;
@@ -3096,69 +3380,13 @@ impl DownloadManager {
                         }
                     }
                     None => {
-                        // 🔥 无可用任务位，先尝试回收文件夹的借调槽位
-                        let folder_manager = {
-                            let fm = self.folder_manager.read().await;
-                            fm.clone()
-                        };
-
-                        if let Some(fm) = folder_manager {
-                            // 检查是否有借调槽位可回收
-                            if self.task_slot_pool.find_folder_with_borrowed_slots().await.is_some() {
-                                info!("恢复{} {} 无可用槽位，尝试回收文件夹借调槽位", task_type_str, task_id);
-
-                                // 尝试回收一个借调槽位
-                                if let Some(reclaimed_slot_id) = fm.reclaim_borrowed_slot().await {
-                                    // 回收成功，分配槽位给恢复的任务（使用正确的优先级）
-                                    if let Some((slot_id, preempted_task_id)) = self.task_slot_pool.allocate_fixed_slot_with_priority(
-                                        task_id, false, priority
-                                    ).await {
-                                        {
-                                            let mut t = task.lock().await;
-                                            t.slot_id = Some(slot_id);
-                                            t.is_borrowed_slot = false;
-                                        }
-                                        // 🔥 槽位分配后立即刷新，防止长准备阶段被误判超时
-                                        self.task_slot_pool.touch_slot(&resume_touch_id).await;
-                                        // 🔥 处理被抢占的备份任务
-                                        if let Some(preempted_id) = preempted_task_id {
-                                            info!("恢复{} {} 通过回收借调槽位获得任务位并抢占了备份任务 {}: slot_id={} (回收的槽位={})，已刷新槽位时间戳", task_type_str, task_id, preempted_id, slot_id, reclaimed_slot_id);
-                                            self.pause_preempted_task(&preempted_id).await;
-                                            // 🔥 将被暂停的备份任务加入等待队列末尾（包含状态转换和通知）
-                                            self.add_preempted_backup_to_queue(&preempted_id).await;
-                                        } else {
-                                            info!("恢复{} {} 通过回收借调槽位获得任务位: slot_id={} (回收的槽位={})，已刷新槽位时间戳", task_type_str, task_id, slot_id, reclaimed_slot_id);
-                                        }
-                                    } else {
-                                        warn!("回收借调槽位成功但重新分配失败，恢复{} {} 加入等待队列", task_type_str, task_id);
-                                        self.add_to_waiting_queue_with_task_type(task_id, false, is_folder_subtask).await;
-                                        return Ok(());
-                                    }
-                                } else {
-                                    // 回收失败，加入等待队列
-                                    info!("回收借调槽位失败，恢复{} {} 加入等待队列", task_type_str, task_id);
-                                    self.add_to_waiting_queue_with_task_type(task_id, false, is_folder_subtask).await;
-                                    return Ok(());
-                                }
-                            } else {
-                                // 没有借调槽位可回收，加入等待队列
-                                self.add_to_waiting_queue_with_task_type(task_id, false, is_folder_subtask).await;
-                                info!(
-                                    "恢复{} {} 无可用槽位且无借调槽位可回收，加入等待队列",
-                                    task_type_str, task_id
-                                );
-                                return Ok(());
-                            }
-                        } else {
-                            // 无文件夹管理器，加入等待队列
-                            self.add_to_waiting_queue_with_task_type(task_id, false, is_folder_subtask).await;
-                            info!("恢复{} {} 无可用槽位，加入等待队列", task_type_str, task_id);
-                            return Ok(());
-                        }
-                    }
-                }
-            }
-        }
+                        self.add_to_waiting_queue_with_task_type(task_id, false, is_folder_subtask).await;
+                        info!("恢复{} {} 无可用槽位，加入等待队列", task_type_str, task_id);
+                        return Ok(());
+                    }
+                }
+            }
+        }
 
         // 有槽位，立即启动
         self.start_task_internal(task_id).await
@@ -3413,11 +3641,20 @@ impl DownloadManager {
         };
 
         if let Some(path) = local_path {
+            // 🔥 未完成任务的数据写在 .bdtmp 临时文件中
+            let path = if status_completed == Some(true) {
+                path
+            } else {
+                DownloadTask::partial_file_path(&path)
+            };
             if should_delete && path.exists() {
                 tokio::fs::remove_file(&path)
                     .await
                     .context("删除本地文件失败")?;
                 info!("已删除本地文件: {:?}", path);
+                if status_completed == Some(true) {
+                    self.chunk_scheduler.dedup_index().forget_path(&path);
+                }
             }
         }
 
@@ -3562,6 +3799,12 @@ impl DownloadManager {
         };
 
         if let Some(path) = local_path {
+            // 🔥 未完成任务的数据写在 .bdtmp 临时文件中
+            let path = if status_completed == Some(true) {
+                path
+            } else {
+                DownloadTask::partial_file_path(&path)
+            };
             if should_delete && path.exists() {
                 let _ = tokio::fs::remove_file(&path).await;
             }
@@ -3595,6 +3838,291 @@ impl DownloadManager {
         }
     }
 
+    /// 🔥 设置单任务限速（实时生效）
+    ///
+    /// 限速保存在任务本身并写入元数据，暂停/恢复和重启后依然保留
+    ///
+    /// # 参数
+    /// - `task_id`: 任务ID
+    /// - `limit_kbps`: 限速值（KB/s），0 或 None 表示不限速
+    pub async fn set_task_speed_limit(&self, task_id: &str, limit_kbps: Option<u64>) -> Result<()> {
+        let task = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?;
+
+        let speed_limit = {
+            let mut t = task.lock().await;
+            t.set_speed_limit_kbps(limit_kbps);
+            t.speed_limit_bytes_per_sec
+        };
+
+        match speed_limit {
+            Some(limit) => info!(
+                "任务 {} 限速已设置为 {}",
+                task_id,
+                crate::downloader::progress::format_bytes_per_second(limit)
+            ),
+            None => info!("任务 {} 已取消限速", task_id),
+        }
+
+        // 已注册到持久化管理器的任务立即写入元数据（未注册的任务在注册时由 persist_task_extras 写入）
+        if let Some(ref pm) = self.persistence_manager {
+            let pm = pm.lock().await;
+            if pm.task_exists(task_id) {
+                if let Err(e) = pm.update_download_speed_limit(task_id, speed_limit) {
+                    warn!("持久化任务限速失败: task_id={}, 错误: {}", task_id, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 🔥 设置任务首选下载链接索引（None 表示使用全部可用链接）
+    ///
+    /// 在下一次准备调度（启动或恢复任务）时生效
+    pub async fn set_task_dlink_prefer(&self, task_id: &str, dlink_prefer: Option<usize>) -> Result<()> {
+        let task = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?;
+
+        task.lock().await.dlink_prefer = dlink_prefer;
+
+        match dlink_prefer {
+            Some(index) => info!("任务 {} 首选下载链接索引已设置为 {}", task_id, index),
+            None => info!("任务 {} 已取消首选下载链接", task_id),
+        }
+        Ok(())
+    }
+
+    /// 🔥 设置任务排队优先级
+    ///
+    /// 任务在等待队列中时立即按新优先级重新排队；已在下载的任务只记录优先级，
+    /// 暂停/恢复后重新排队时生效
+    pub async fn set_task_priority(&self, task_id: &str, priority: DownloadPriority) -> Result<()> {
+        let task = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?;
+
+        let (is_backup, is_folder_subtask) = {
+            let mut t = task.lock().await;
+            t.priority = priority;
+            (t.is_backup, t.group_id.is_some())
+        };
+
+        let was_queued = {
+            let mut queue = self.waiting_queue.write().await;
+            let len = queue.len();
+            queue.retain(|id| id != task_id);
+            queue.len() != len
+        };
+        if was_queued {
+            self.add_to_waiting_queue_with_task_type(task_id, is_backup, is_folder_subtask)
+                .await;
+        }
+
+        info!("任务 {} 优先级已设置为 {:?}", task_id, priority);
+        Ok(())
+    }
+
+    /// 🔥 修改已暂停/已失败任务的下载目录
+    ///
+    /// - 仅允许已暂停/已失败的任务；等待中的任务可能正在准备下载，下载中的任务需先暂停
+    /// - 文件夹子任务的目录由文件夹结构决定，不支持单独修改
+    /// - 已写入的 `.bdtmp` 临时文件会一并移动（跨分区时复制，不持有任务锁），断点续传不受影响；
+    ///   移动期间任务不能被恢复或启动
+    ///
+    /// 返回新的本地保存路径
+    pub async fn update_task_dir(&self, task_id: &str, new_dir: &std::path::Path) -> Result<PathBuf> {
+        self.ensure_download_dir_allowed(new_dir).await?;
+        if tokio::fs::metadata(new_dir).await.is_ok_and(|m| !m.is_dir()) {
+            anyhow::bail!("目标路径不是目录: {:?}", new_dir);
+        }
+
+        let task = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?;
+
+        if !self.relocating_tasks.write().await.insert(task_id.to_string()) {
+            anyhow::bail!("任务正在修改下载目录，请稍后再试");
+        }
+        let result = self.relocate_task_files(task_id, &task, new_dir).await;
+        self.relocating_tasks.write().await.remove(task_id);
+        result
+    }
+
+    /// 移动任务的本地文件并更新路径（由 `update_task_dir` 调用，调用方已将任务标记为移动中）
+    async fn relocate_task_files(
+        &self,
+        task_id: &str,
+        task: &Arc<Mutex<DownloadTask>>,
+        new_dir: &std::path::Path,
+    ) -> Result<PathBuf> {
+        let (old_local_path, old_write_path, has_temp_path) = {
+            let t = task.lock().await;
+            match t.status {
+                TaskStatus::Paused | TaskStatus::Failed => {}
+                TaskStatus::Pending | TaskStatus::Downloading | TaskStatus::Decrypting => {
+                    anyhow::bail!("任务正在下载，请先暂停后再修改下载目录")
+                }
+                TaskStatus::Completed => {
+                    anyhow::bail!("任务已完成，无法修改下载目录")
+                }
+            }
+            if t.group_id.is_some() {
+                anyhow::bail!("文件夹子任务不支持单独修改下载目录");
+            }
+            (t.local_path.clone(), t.write_path(), t.temp_path.is_some())
+        };
+
+        let file_name = old_local_path.file_name().context("任务本地路径无效")?;
+        let new_local_path = new_dir.join(file_name);
+        if new_local_path == old_local_path {
+            return Ok(new_local_path);
+        }
+        if tokio::fs::try_exists(&new_local_path).await.unwrap_or(false) {
+            anyhow::bail!("目标目录已存在同名文件: {:?}", new_local_path);
+        }
+
+        tokio::fs::create_dir_all(new_dir)
+            .await
+            .context("创建下载目录失败")?;
+
+        // 已写入部分数据的临时文件一起移动（不持有任务锁，大文件跨分区复制不阻塞其他操作）
+        let new_temp_path = has_temp_path.then(|| DownloadTask::build_temp_path(&new_local_path));
+        if tokio::fs::try_exists(&old_write_path).await.unwrap_or(false) {
+            let new_write_path = new_temp_path.clone().unwrap_or_else(|| new_local_path.clone());
+            if tokio::fs::rename(&old_write_path, &new_write_path).await.is_err() {
+                // 跨分区无法重命名时回退为复制后删除
+                if let Err(e) = tokio::fs::copy(&old_write_path, &new_write_path).await {
+                    let _ = tokio::fs::remove_file(&new_write_path).await;
+                    return Err(e).context("移动已下载的临时文件失败");
+                }
+                if let Err(e) = tokio::fs::remove_file(&old_write_path).await {
+                    warn!("删除原临时文件失败: {:?}, 错误: {}", old_write_path, e);
+                }
+            }
+        }
+
+        info!(
+            "任务 {} 下载目录已修改: {:?} -> {:?}",
+            task_id, old_local_path, new_local_path
+        );
+        {
+            let mut t = task.lock().await;
+            t.local_path = new_local_path.clone();
+            t.temp_path = new_temp_path;
+        }
+
+        if let Some(ref pm) = self.persistence_manager {
+            if let Err(e) = pm.lock().await.update_local_path(task_id, new_local_path.clone()) {
+                warn!("更新持久化本地路径失败: {}", e);
+            }
+        }
+
+        Ok(new_local_path)
+    }
+
+    /// 🔥 获取等待队列中的任务快照（按出队顺序）
+    pub async fn get_waiting_queue(&self) -> Vec<DownloadTask> {
+        let queue: Vec<String> = self.waiting_queue.read().await.iter().cloned().collect();
+        let tasks = self.tasks.read().await;
+        let mut result = Vec::with_capacity(queue.len());
+        for id in queue {
+            if let Some(task) = tasks.get(&id) {
+                result.push(task.lock().await.clone());
+            }
+        }
+        result
+    }
+
+    /// 🔥 调整任务在等待队列中的位置
+    ///
+    /// - 任务必须在等待队列中，位置超出范围时钳制到队尾
+    /// - 文件夹子任务连同同组排队中的兄弟任务整体移动（被移动的任务排在组内最前，其余保持原顺序），
+    ///   避免同一文件夹的子任务被拆散
+    ///
+    /// 返回调整后的等待队列顺序
+    pub async fn move_in_queue(&self, task_id: &str, position: usize) -> Result<Vec<String>> {
+        let group_id = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?
+            .lock()
+            .await
+            .group_id
+            .clone();
+
+        // 收集同组排队中的兄弟任务（在获取队列写锁前完成，避免持锁等待任务锁）
+        let siblings: HashSet<String> = match &group_id {
+            Some(gid) => {
+                let queue_snapshot: Vec<String> = self.waiting_queue.read().await.iter().cloned().collect();
+                let tasks = self.tasks.read().await;
+                let mut ids = HashSet::new();
+                for id in queue_snapshot {
+                    if let Some(task) = tasks.get(&id) {
+                        if task.lock().await.group_id.as_ref() == Some(gid) {
+                            ids.insert(id);
+                        }
+                    }
+                }
+                ids
+            }
+            None => HashSet::new(),
+        };
+
+        let mut queue = self.waiting_queue.write().await;
+        if !queue.iter().any(|id| id == task_id) {
+            anyhow::bail!("任务 {} 不在等待队列中", task_id);
+        }
+
+        // 取出要移动的任务块：目标任务在前，同组兄弟任务保持原顺序
+        let mut block = vec![task_id.to_string()];
+        block.extend(
+            queue
+                .iter()
+                .filter(|id| id.as_str() != task_id && siblings.contains(*id))
+                .cloned(),
+        );
+        queue.retain(|id| !block.contains(id));
+
+        let position = position.min(queue.len());
+        for (offset, id) in block.into_iter().enumerate() {
+            queue.insert(position + offset, id);
+        }
+
+        info!("任务 {} 已移动到等待队列位置 {} (队列长度: {})", task_id, position, queue.len());
+        Ok(queue.iter().cloned().collect())
+    }
+
+    /// 🔥 手动刷新任务的下载链接
+    ///
+    /// 仅对正在下载的任务有效，受最小刷新间隔限制
+    pub async fn refresh_task_url(&self, task_id: &str) -> Result<usize> {
+        self.engine
+            .force_refresh_url(task_id, &self.chunk_scheduler)
+            .await
+    }
+
     /// 🔥 更新任务的槽位信息
     ///
     /// 用于恢复时为子任务分配借调位后更新任务状态
@@ -3700,6 +4228,27 @@ impl DownloadManager {
         }
     }
 
+    /// 🔥 设置任务的分享直链
+    ///
+    /// 免转存下载分享文件时使用，需在 start_task 之前调用；
+    /// 分享来源用于直链过期后重新列出分享目录
+    pub async fn set_task_direct_dlink(
+        &self,
+        task_id: &str,
+        dlink: String,
+        source: ShareDirectSource,
+    ) -> Result<()> {
+        let tasks = self.tasks.read().await;
+        if let Some(task) = tasks.get(task_id) {
+            let mut t = task.lock().await;
+            t.direct_dlink = Some(dlink);
+            t.share_source = Some(source);
+            Ok(())
+        } else {
+            anyhow::bail!("任务不存在: {}", task_id)
+        }
+    }
+
     /// 设置任务为分享直下任务
     ///
     /// 分享直下任务完成后不会被 clear_completed 清除，由转存管理器负责清理
@@ -3799,6 +4348,30 @@ impl DownloadManager {
         result
     }
 
+    /// 分页查询下载历史（直接查询历史数据库，不合并内存任务）
+    ///
+    /// # Returns
+    /// * `(Vec<DownloadTask>, usize)` - (当前页任务, 符合条件的总数)
+    pub async fn query_history(&self, mut query: TaskHistoryQuery) -> (Vec<DownloadTask>, usize) {
+        let Some(ref pm) = self.persistence_manager else {
+            return (Vec::new(), 0);
+        };
+
+        query.task_type = Some("download".to_string());
+        query.exclude_backup = true;
+
+        match pm.lock().await.query_history_tasks(&query) {
+            Some((history_tasks, total)) => (
+                history_tasks
+                    .iter()
+                    .filter_map(Self::convert_history_to_task)
+                    .collect(),
+                total,
+            ),
+            None => (Vec::new(), 0),
+        }
+    }
+
     /// 获取所有备份任务
     pub async fn get_backup_tasks(&self) -> Vec<DownloadTask> {
         let tasks = self.tasks.read().await;
@@ -3860,8 +4433,10 @@ impl DownloadManager {
         // 获取默认策略（如果未指定，使用 Overwrite 默认值）
         let strategy = conflict_strategy.unwrap_or(DownloadConflictStrategy::Overwrite);
 
-        // 解决下载冲突
-        let resolution = ConflictResolver::resolve_download_conflict(&local_path, strategy)?;
+        // 解决下载冲突（跳过策略会校验已有文件的大小）
+        let resolution =
+            ConflictResolver::resolve_download_conflict_verified(&local_path, strategy, total_size, None)
+                .await?;
 
         // 根据解决方案处理
         let final_local_path = match resolution {
@@ -3873,7 +4448,7 @@ impl DownloadManager {
                     .unwrap_or("unknown")
                     .to_string();
 
-                info!("跳过备份下载（文件已存在）: {:?}", local_path);
+                info!("跳过备份下载（文件已存在且内容一致）: {:?}", local_path);
 
                 self.publish_event(DownloadEvent::Skipped {
                     task_id: format!("backup-skipped-{}", uuid::Uuid::new_v4()),
@@ -3926,6 +4501,7 @@ impl DownloadManager {
             group_id: None,
             is_backup: true,
             original_filename: None, // 备份下载任务不需要原始文件名
+            batch_id: None,
         })
             .await;
 
@@ -3949,6 +4525,7 @@ impl DownloadManager {
             downloaded_size: file_size, // 已完成的任务
             status: TaskStatus::Completed,
             speed: 0,
+            eta_secs: None,
             created_at: metadata.created_at.timestamp(),
             started_at: Some(metadata.created_at.timestamp()),
             completed_at: metadata.completed_at.map(|t| t.timestamp()),
@@ -3965,6 +4542,7 @@ impl DownloadManager {
             is_backup: metadata.is_backup,
             backup_config_id: metadata.backup_config_id.clone(),
             start_retry_count: 0,
+            task_retry_count: 0,
             // 解密字段（历史任务默认无解密）
             is_encrypted: false,
             decrypt_progress: 0.0,
@@ -3972,9 +4550,110 @@ impl DownloadManager {
             original_filename: None,
             // 分享直下字段（历史任务默认为 false）
             is_share_direct_download: false,
+            // 限速字段（历史任务不需要限速）
+            speed_limit_bytes_per_sec: None,
+            // 完整性校验字段（历史任务已完成，不需要校验）
+            expected_md5: None,
+            md5_verified: false,
+            // 批量下载字段（保留批次 ID，便于按批次查看历史）
+            batch_id: metadata.batch_id.clone(),
+            // 临时文件字段（历史任务已完成重命名）
+            temp_path: None,
+            // 分享直链字段（历史任务不需要下载链接）
+            direct_dlink: None,
+            share_source: None,
+            // 排队优先级字段（历史任务不再排队）
+            priority: DownloadPriority::Normal,
+            // 首选节点字段（历史任务不需要下载链接）
+            dlink_prefer: None,
+            // 并发分片数覆盖字段（历史任务不再下载）
+            max_chunks_override: None,
+            // 远端文件校验字段（历史任务不再下载）
+            remote_size: None,
+            remote_etag: None,
+            // CDN 节点字段（保留历史记录中的主机，便于排查慢速下载）
+            cdn_host: metadata.cdn_host.clone(),
+            cdn_host_dirty: false,
+            // 剩余时间估算字段（历史任务已完成）
+            eta_estimator: EtaEstimator::default(),
         })
     }
 
+    /// 🔥 将注册参数之外的任务属性写入元数据
+    async fn persist_task_extras(
+        pm: &Arc<Mutex<PersistenceManager>>,
+        task: &Arc<Mutex<DownloadTask>>,
+        task_id: &str,
+    ) {
+        let (expected_md5, batch_id, direct_dlink, share_source, speed_limit) = {
+            let t = task.lock().await;
+            (
+                t.expected_md5.clone(),
+                t.batch_id.clone(),
+                t.direct_dlink.clone(),
+                t.share_source.clone(),
+                t.speed_limit_bytes_per_sec,
+            )
+        };
+
+        if let Some(md5) = expected_md5 {
+            if let Err(e) = pm.lock().await.update_download_expected_md5(task_id, md5) {
+                warn!("持久化任务 MD5 失败: task_id={}, 错误: {}", task_id, e);
+            }
+        }
+        if let Some(batch_id) = batch_id {
+            if let Err(e) = pm.lock().await.update_download_batch_id(task_id, batch_id) {
+                warn!("持久化批次ID失败: task_id={}, 错误: {}", task_id, e);
+            }
+        }
+        if let Some(dlink) = direct_dlink {
+            if let Err(e) = pm.lock().await.update_download_direct_dlink(task_id, dlink) {
+                warn!("持久化分享直链失败: task_id={}, 错误: {}", task_id, e);
+            }
+        }
+        if let Some(source) = share_source {
+            if let Err(e) = pm.lock().await.update_download_share_source(task_id, source) {
+                warn!("持久化分享来源失败: task_id={}, 错误: {}", task_id, e);
+            }
+        }
+        if speed_limit.is_some() {
+            if let Err(e) = pm.lock().await.update_download_speed_limit(task_id, speed_limit) {
+                warn!("持久化任务限速失败: task_id={}, 错误: {}", task_id, e);
+            }
+        }
+    }
+
+    /// 🔥 恢复已完成分片前校验远端文件指纹（探测时记录在任务上），变化时重置断点进度
+    async fn validate_resume_remote(
+        pm: &Arc<Mutex<PersistenceManager>>,
+        task: &Arc<Mutex<DownloadTask>>,
+        task_id: &str,
+        chunk_size: u64,
+        total_chunks: usize,
+    ) {
+        let (total_size, remote_size, remote_etag) = {
+            let t = task.lock().await;
+            (t.total_size, t.remote_size, t.remote_etag.clone())
+        };
+
+        let result = pm.lock().await.validate_download_remote(
+            task_id,
+            total_size,
+            chunk_size,
+            total_chunks,
+            remote_size,
+            remote_etag,
+        );
+        match result {
+            Ok(Some(reason)) => {
+                warn!("任务 {} {}，放弃已下载的分片重新下载", task_id, reason);
+                task.lock().await.downloaded_size = 0;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("任务 {} 记录远端文件指纹失败: {}", task_id, e),
+        }
+    }
+
     /// 获取进行中的任务数量
     pub async fn active_count(&self) -> usize {
         // 使用调度器的计数（更准确）
@@ -3982,26 +4661,36 @@ impl DownloadManager {
     }
 
     /// 清除已完成的任务
+    ///
+    /// 已归档的任务可能同时在内存和历史数据库中，按 ID 去重计数
     pub async fn clear_completed(&self) -> usize {
-        let mut tasks = self.tasks.write().await;
-        let mut to_remove = Vec::new();
-
         // 1. 收集内存中的已完成任务（跳过分享直下任务，由转存管理器清理）
-        for (id, task) in tasks.iter() {
+        //    先复制任务引用再逐个加锁，避免持有任务表锁时等待单个任务锁
+        let snapshot: Vec<(String, Arc<Mutex<DownloadTask>>)> = self
+            .tasks
+            .read()
+            .await
+            .iter()
+            .map(|(id, task)| (id.clone(), task.clone()))
+            .collect();
+        let mut to_remove = Vec::new();
+        for (id, task) in snapshot {
             let t = task.lock().await;
             if t.status == TaskStatus::Completed && !t.is_share_direct_download {
-                to_remove.push(id.clone());
+                to_remove.push(id);
             }
         }
 
         // 2. 从内存中移除
         let memory_count = to_remove.len();
-        for id in &to_remove {
-            tasks.remove(id);
+        {
+            let mut tasks = self.tasks.write().await;
+            for id in &to_remove {
+                tasks.remove(id);
+            }
         }
-
-        // 释放写锁，避免长时间持锁
-        drop(tasks);
+        let mut cleared_tasks: HashSet<String> = to_remove.into_iter().collect();
+        let mut cleared_folders: HashSet<String> = HashSet::new();
 
         // 3. 从历史数据库中清除已完成任务
         let mut history_count = 0;
@@ -4014,9 +4703,10 @@ impl DownloadManager {
 
             // 从历史数据库中删除已完成的下载任务
             if let Some(db) = history_db {
-                match db.remove_tasks_by_type_and_status("download", "completed") {
-                    Ok(count) => {
-                        history_count = count;
+                match db.remove_task_ids_by_type_and_status("download", "completed") {
+                    Ok(ids) => {
+                        history_count = ids.len();
+                        cleared_tasks.extend(ids);
                     }
                     Err(e) => {
                         warn!("从历史数据库删除已完成下载任务失败: {}", e);
@@ -4024,9 +4714,10 @@ impl DownloadManager {
                 }
                 // 🔥 同时清除 folder_history表中已完成的文件夹任务
                 match db.remove_completed_folders() {
-                    Ok(count) => {
-                        history_count += count;
-                        info!("从历史数据库删除了 {} 个已完成的文件夹任务", count);
+                    Ok(ids) => {
+                        history_count += ids.len();
+                        info!("从历史数据库删除了 {} 个已完成的文件夹任务", ids.len());
+                        cleared_folders.extend(ids);
                     }
                     Err(e) => {
                         warn!("从历史数据库删除已完成文件夹任务失败: {}", e);
@@ -4036,16 +4727,18 @@ impl DownloadManager {
         }
 
         // 4. 清除 FolderDownloadManager 内存中已完成的文件夹
-        let folder_memory_count = {
-            let fm = self.folder_manager.read().await;
-            if let Some(ref folder_manager) = *fm {
-                folder_manager.clear_completed_folders().await
-            } else {
-                0
+        let folder_manager = self.folder_manager.read().await.clone();
+        let folder_memory_count = match folder_manager {
+            Some(folder_manager) => {
+                let ids = folder_manager.clear_completed_folders().await;
+                let count = ids.len();
+                cleared_folders.extend(ids);
+                count
             }
+            None => 0,
         };
 
-        let total_count = memory_count + history_count + folder_memory_count;
+        let total_count = cleared_tasks.len() + cleared_folders.len();
         info!(
             "清除了 {} 个已完成的任务（文件内存: {}, 文件夹内存: {}, 历史: {}）",
             total_count, memory_count, folder_memory_count, history_count
@@ -4061,7 +4754,7 @@ impl DownloadManager {
         for (id, task) in tasks.iter() {
             let t = task.lock().await;
             if t.status == TaskStatus::Failed {
-                to_remove.push((id.clone(), t.local_path.clone()));
+                to_remove.push((id.clone(), t.write_path()));
             }
         }
 
@@ -4242,18 +4935,163 @@ impl DownloadManager {
         results
     }
 
-    /// 批量删除下载任务
-    pub async fn batch_delete(&self, task_ids: &[String], delete_files: bool) -> Vec<(String, bool, Option<String>)> {
-        let mut results = Vec::with_capacity(task_ids.len());
-        for id in task_ids {
-            match self.delete_task(id, delete_files).await {
-                Ok(_) => results.push((id.clone(), true, None)),
-                Err(e) => results.push((id.clone(), false, Some(e.to_string()))),
+    /// 🔥 一键暂停全部下载（不含自动备份任务）
+    ///
+    /// - 单文件任务：活跃任务和等待队列中的任务全部暂停，按原顺序（活跃任务在前）记录，
+    ///   等待队列监控不会自动启动它们，直到调用 [`Self::resume_all`]
+    /// - 文件夹：交由 FolderDownloadManager::pause_folder 统一暂停子任务
+    ///
+    /// 返回 (任务ID或文件夹ID, 是否成功, 错误信息)
+    pub async fn pause_all(&self) -> Vec<(String, bool, Option<String>)> {
+        // 1. 按原顺序收集单文件任务：活跃任务按创建时间在前，等待队列顺序在后
+        let queue_snapshot: Vec<String> = self.waiting_queue.read().await.iter().cloned().collect();
+        let (mut active, queued) = {
+            let tasks = self.tasks.read().await;
+            let mut active = Vec::new();
+            let mut queued = Vec::new();
+            for (id, task) in tasks.iter() {
+                let t = task.lock().await;
+                if t.is_backup || t.group_id.is_some() {
+                    continue;
+                }
+                if !matches!(t.status, TaskStatus::Downloading | TaskStatus::Pending) {
+                    continue;
+                }
+                if let Some(pos) = queue_snapshot.iter().position(|q| q == id) {
+                    queued.push((pos, id.clone()));
+                } else {
+                    active.push((t.created_at, id.clone()));
+                }
             }
-        }
-        results
-    }
-
+            (active, queued)
+        };
+        active.sort();
+        let mut queued = queued;
+        queued.sort();
+        let ordered: Vec<String> = active
+            .into_iter()
+            .map(|(_, id)| id)
+            .chain(queued.into_iter().map(|(_, id)| id))
+            .collect();
+
+        // 2. 探测中的任务（Pending 且不在队列中）先触发取消令牌，防止探测完成后继续注册到调度器
+        for id in &ordered {
+            let is_pending = match self.tasks.read().await.get(id).cloned() {
+                Some(task) => task.lock().await.status == TaskStatus::Pending,
+                None => false,
+            };
+            if is_pending {
+                if let Some(token) = self.cancellation_tokens.read().await.get(id) {
+                    token.cancel();
+                }
+            }
+        }
+
+        // 先记录再暂停，避免暂停过程中释放的槽位把排在后面的任务拉起
+        {
+            let mut paused = self.user_paused_tasks.write().await;
+            for id in &ordered {
+                if !paused.contains(id) {
+                    paused.push(id.clone());
+                }
+            }
+        }
+
+        let mut results = self.batch_pause(&ordered).await;
+
+        // 暂停失败的任务不记录（例如已经完成）
+        {
+            let failed: HashSet<&String> = results
+                .iter()
+                .filter(|(_, success, _)| !success)
+                .map(|(id, _, _)| id)
+                .collect();
+            if !failed.is_empty() {
+                self.user_paused_tasks.write().await.retain(|id| !failed.contains(id));
+            }
+        }
+
+        // 3. 暂停进行中的文件夹（扫描中或下载中）
+        let fm_opt = self.folder_manager.read().await.clone();
+        if let Some(fm) = fm_opt {
+            let mut folders: Vec<_> = fm
+                .get_all_folders()
+                .await
+                .into_iter()
+                .filter(|f| matches!(f.status, FolderStatus::Scanning | FolderStatus::Downloading))
+                .collect();
+            folders.sort_by_key(|f| f.created_at);
+            for folder in folders {
+                match fm.pause_folder(&folder.id).await {
+                    Ok(_) => {
+                        let mut paused = self.user_paused_folders.write().await;
+                        if !paused.contains(&folder.id) {
+                            paused.push(folder.id.clone());
+                        }
+                        results.push((folder.id, true, None));
+                    }
+                    Err(e) => results.push((folder.id, false, Some(e.to_string()))),
+                }
+            }
+        }
+
+        info!(
+            "一键暂停完成: 单文件任务 {} 个，文件夹 {} 个",
+            self.user_paused_tasks.read().await.len(),
+            self.user_paused_folders.read().await.len()
+        );
+
+        results
+    }
+
+    /// 🔥 一键恢复全部下载
+    ///
+    /// 按 [`Self::pause_all`] 记录的原顺序重新加入等待队列，再恢复一键暂停的文件夹
+    ///
+    /// 返回 (任务ID或文件夹ID, 是否成功, 错误信息)
+    pub async fn resume_all(&self) -> Vec<(String, bool, Option<String>)> {
+        let task_ids = std::mem::take(&mut *self.user_paused_tasks.write().await);
+        let folder_ids = std::mem::take(&mut *self.user_paused_folders.write().await);
+
+        // 只恢复仍处于暂停状态的任务（期间可能已被手动恢复或删除）
+        let mut to_resume = Vec::with_capacity(task_ids.len());
+        for id in task_ids {
+            if let Some(task) = self.tasks.read().await.get(&id).cloned() {
+                if task.lock().await.status == TaskStatus::Paused {
+                    to_resume.push(id);
+                }
+            }
+        }
+
+        let mut results = self.batch_resume(&to_resume).await;
+
+        let fm_opt = self.folder_manager.read().await.clone();
+        if let Some(fm) = fm_opt {
+            for folder_id in folder_ids {
+                match fm.resume_folder(&folder_id).await {
+                    Ok(_) => results.push((folder_id, true, None)),
+                    Err(e) => results.push((folder_id, false, Some(e.to_string()))),
+                }
+            }
+        }
+
+        info!("一键恢复完成: 共 {} 项", results.len());
+
+        results
+    }
+
+    /// 批量删除下载任务
+    pub async fn batch_delete(&self, task_ids: &[String], delete_files: bool) -> Vec<(String, bool, Option<String>)> {
+        let mut results = Vec::with_capacity(task_ids.len());
+        for id in task_ids {
+            match self.delete_task(id, delete_files).await {
+                Ok(_) => results.push((id.clone(), true, None)),
+                Err(e) => results.push((id.clone(), false, Some(e.to_string()))),
+            }
+        }
+        results
+    }
+
     /// 获取可暂停的任务ID列表
     pub async fn get_pausable_task_ids(&self) -> Vec<String> {
         let tasks = self.tasks.read().await;
@@ -4320,6 +5158,277 @@ impl DownloadManager {
         self.chunk_scheduler.update_max_threads(new_max);
     }
 
+    /// 🔥 动态更新下载完成后是否校验 MD5
+    pub fn update_verify_md5(&self, enabled: bool) {
+        self.chunk_scheduler.update_verify_md5(enabled);
+    }
+
+    /// 🔥 动态更新任务级最大重试次数（取 DownloadConfig.max_retries）
+    pub fn update_max_task_retries(&self, max_retries: u32) {
+        self.chunk_scheduler.update_max_task_retries(max_retries);
+    }
+
+    /// 🔥 动态更新下载完成回调地址和共享密钥
+    pub fn update_completion_webhook(&self, url: Option<String>, secret: Option<String>) {
+        self.chunk_scheduler.update_completion_webhook(url, secret);
+    }
+
+    /// 🔥 动态更新限流冷却配置
+    pub fn update_rate_limit_config(&self, config: RateLimitConfig) {
+        self.rate_limit_cooldown.update_config(config);
+    }
+
+    /// 🔥 动态更新自适应并发配置
+    pub fn update_adaptive_concurrency_config(&self, config: AdaptiveConcurrencyConfig) {
+        self.chunk_scheduler.update_adaptive_concurrency_config(config);
+    }
+
+    /// 🔥 动态更新下载时间段调度
+    pub fn update_download_schedule(&self, config: &ScheduleConfig) {
+        self.download_schedule.update_config(config);
+    }
+
+    /// 🔥 当前下载时间段调度状态
+    pub fn schedule_status(&self) -> ScheduleStatus {
+        self.download_schedule.status()
+    }
+
+    /// 🔥 按下载时间段暂停/恢复任务（由 AppState 定时调用，配置更新后也会立即调用）
+    ///
+    /// - 离开时间段：开启 pause_running 时暂停正在下载的单文件任务和文件夹；
+    ///   否则只暂停离开时间段之后新建的文件夹（单文件任务在 start_task 中进入等待队列）
+    /// - 进入时间段：恢复因调度暂停的任务，并启动等待队列中的任务
+    pub async fn apply_download_schedule(&self) {
+        let open = self.download_schedule.is_open();
+        let was_open = self.schedule_was_open.swap(open, Ordering::SeqCst);
+
+        if open {
+            if !was_open {
+                info!("⏰ 进入下载时间段，恢复因调度暂停的任务");
+                self.resume_schedule_paused().await;
+                self.try_start_waiting_tasks().await;
+            }
+            return;
+        }
+
+        let pause_running = self.download_schedule.pause_running();
+        if was_open {
+            info!("⏰ 已离开下载时间段，暂停启动新任务 (暂停进行中的任务: {})", pause_running);
+            self.schedule_closed_at
+                .store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+
+            if pause_running {
+                self.pause_running_for_schedule().await;
+            }
+        }
+
+        // 暂停进行中的文件夹（未开启 pause_running 时只处理离开时间段之后新建的文件夹）
+        let closed_at = self.schedule_closed_at.load(Ordering::SeqCst);
+        let fm_opt = self.folder_manager.read().await.clone();
+        if let Some(fm) = fm_opt {
+            let folders: Vec<_> = fm
+                .get_all_folders()
+                .await
+                .into_iter()
+                .filter(|f| matches!(f.status, FolderStatus::Scanning | FolderStatus::Downloading))
+                .filter(|f| pause_running || f.created_at >= closed_at)
+                .collect();
+            for folder in folders {
+                match fm.pause_folder(&folder.id).await {
+                    Ok(_) => {
+                        info!("不在下载时间段内，暂停文件夹: {}", folder.id);
+                        let mut paused = self.schedule_paused_folders.write().await;
+                        if !paused.contains(&folder.id) {
+                            paused.push(folder.id);
+                        }
+                    }
+                    Err(e) => warn!("按下载时间段暂停文件夹 {} 失败: {}", folder.id, e),
+                }
+            }
+        }
+    }
+
+    /// 离开下载时间段时暂停正在下载的单文件任务（不含自动备份任务和用户一键暂停的任务）
+    async fn pause_running_for_schedule(&self) {
+        let running: Vec<String> = {
+            let tasks = self.tasks.read().await;
+            let mut running = Vec::new();
+            for (id, task) in tasks.iter() {
+                let t = task.lock().await;
+                if !t.is_backup && t.group_id.is_none() && t.status == TaskStatus::Downloading {
+                    running.push(id.clone());
+                }
+            }
+            running
+        };
+
+        let results = self.batch_pause(&running).await;
+        let mut paused = self.schedule_paused_tasks.write().await;
+        for (id, success, _) in results {
+            if success && !paused.contains(&id) {
+                paused.push(id);
+            }
+        }
+        info!("不在下载时间段内，已暂停 {} 个正在下载的任务", paused.len());
+    }
+
+    /// 进入下载时间段时恢复因调度暂停的任务和文件夹
+    async fn resume_schedule_paused(&self) {
+        let task_ids = std::mem::take(&mut *self.schedule_paused_tasks.write().await);
+        let folder_ids = std::mem::take(&mut *self.schedule_paused_folders.write().await);
+
+        // 只恢复仍处于暂停状态的任务（期间可能已被手动恢复或删除）
+        let mut to_resume = Vec::with_capacity(task_ids.len());
+        for id in task_ids {
+            if let Some(task) = self.tasks.read().await.get(&id).cloned() {
+                if task.lock().await.status == TaskStatus::Paused {
+                    to_resume.push(id);
+                }
+            }
+        }
+        self.batch_resume(&to_resume).await;
+
+        let fm_opt = self.folder_manager.read().await.clone();
+        if let Some(fm) = fm_opt {
+            for folder_id in folder_ids {
+                if let Err(e) = fm.resume_folder(&folder_id).await {
+                    info!("进入下载时间段：文件夹 {} 恢复跳过: {}", folder_id, e);
+                }
+            }
+        }
+    }
+
+    /// 🔥 关闭前排空下载：停止启动新任务，中断进行中的任务并等待分片线程退出
+    ///
+    /// 任务状态保持不变，分片取消路径会持久化已写入的部分进度，重启后从 WAL 恢复续传
+    /// 排空后写入尚未落盘的本地去重索引
+    /// 返回 (被中断的任务数, 超时后仍未退出的分片线程数)
+    pub async fn prepare_shutdown(&self, timeout: std::time::Duration) -> (usize, usize) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let interrupted = {
+            let tokens = self.cancellation_tokens.read().await;
+            tokens
+                .values()
+                .filter(|token| !token.is_cancelled())
+                .inspect(|token| token.cancel())
+                .count()
+        };
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.chunk_scheduler.active_threads() > 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        // 写入尚未落盘的本地去重索引
+        let dedup_index = self.chunk_scheduler.dedup_index();
+        let _ = tokio::task::spawn_blocking(move || dedup_index.flush()).await;
+
+        (interrupted, self.chunk_scheduler.active_threads())
+    }
+
+    /// 🔥 停用管理器（切换账号时调用）：排空进行中的任务后停止调度器
+    ///
+    /// 后台循环持有管理器内部状态的克隆，旧管理器的引用释放后调度器不一定停止，需要显式停用
+    pub async fn shutdown(&self, timeout: std::time::Duration) -> (usize, usize) {
+        let drained = self.prepare_shutdown(timeout).await;
+        self.chunk_scheduler.stop();
+        drained
+    }
+
+    /// 🔥 动态更新下载前磁盘预留空间（MB）
+    pub fn update_min_free_space_mb(&self, mb: u64) {
+        self.min_free_space_mb.store(mb, Ordering::Relaxed);
+    }
+
+    /// 🔥 动态更新单任务最大并发分片数默认覆盖值（0 表示按文件大小计算，下次启动任务时生效）
+    pub fn update_max_chunks_per_task(&self, max_chunks: usize) {
+        self.max_chunks_per_task.store(max_chunks, Ordering::Relaxed);
+    }
+
+    /// 🔥 动态更新是否启用本地 MD5 去重，以及命中时是否使用硬链接
+    pub fn update_dedup_local(&self, enabled: bool, hardlink: bool) {
+        self.chunk_scheduler.update_dedup_local(enabled, hardlink);
+    }
+
+    /// 🔥 动态更新单 CDN 主机最大并发分片数（0 表示不限制）
+    pub fn update_max_chunks_per_host(&self, max_chunks: usize) {
+        self.chunk_scheduler.update_max_chunks_per_host(max_chunks);
+    }
+
+    /// 🔥 动态更新任务无进度失败时长（秒，0 表示不限制）
+    pub fn update_max_stall_secs(&self, secs: u64) {
+        self.chunk_scheduler.update_max_stall_secs(secs);
+    }
+
+    /// 🔥 从 WAL 目录加载本地去重索引
+    pub fn load_dedup_index(&self, wal_dir: &std::path::Path) {
+        self.chunk_scheduler.dedup_index().load(wal_dir);
+    }
+
+    /// 🔥 设置任务最大并发分片数覆盖值（None 表示使用全局默认值）
+    ///
+    /// 在下一次启动或恢复任务时生效，超过会员等级上限时按上限处理
+    pub async fn set_task_max_chunks(&self, task_id: &str, max_chunks: Option<usize>) -> Result<()> {
+        let task = self
+            .tasks
+            .read()
+            .await
+            .get(task_id)
+            .cloned()
+            .context("任务不存在")?;
+
+        task.lock().await.max_chunks_override = max_chunks;
+
+        match max_chunks {
+            Some(n) => info!(
+                "任务 {} 最大并发分片数覆盖值已设置为 {}（上限 {}）",
+                task_id,
+                n,
+                self.engine.max_chunks_ceiling()
+            ),
+            None => info!("任务 {} 已取消最大并发分片数覆盖值", task_id),
+        }
+        Ok(())
+    }
+
+    /// 🔥 检查目标目录所在卷是否能容纳 `remaining` 字节（额外保留 min_free_space_mb）
+    pub fn ensure_free_space(&self, dir: &std::path::Path, remaining: u64) -> Result<()> {
+        let reserve = self.min_free_space_mb.load(Ordering::Relaxed) * 1024 * 1024;
+        crate::config::PathValidator::ensure_free_space(dir, remaining, reserve)
+    }
+
+    /// 🔥 动态更新按文件类型选择的下载目录（只影响新创建的任务）
+    pub async fn update_type_dirs(&self, type_dirs: BTreeMap<String, PathBuf>) {
+        let mut current = self.type_dirs.write().await;
+        if *current != type_dirs {
+            info!("🔧 动态调整分类下载目录: {} 条规则", type_dirs.len());
+            *current = type_dirs;
+        }
+    }
+
+    /// 🔥 校验自定义下载目录位于 allowed_paths 白名单内（白名单为空时不限制）
+    ///
+    /// 单文件、文件夹和批量下载创建任务前都需调用
+    pub async fn ensure_download_dir_allowed(&self, dir: &std::path::Path) -> Result<()> {
+        self.filesystem_config
+            .read()
+            .await
+            .ensure_path_allowed(dir, "下载目录")
+    }
+
+    /// 🔥 动态更新文件系统配置（allowed_paths 白名单变更后对新建任务立即生效）
+    pub async fn update_filesystem_config(&self, config: FilesystemConfig) {
+        *self.filesystem_config.write().await = config;
+    }
+
+    /// 🔥 动态更新全局限速（KB/s，0 表示不限速）
+    ///
+    /// 无需重启，正在下载的任务立即按新限速执行
+    pub fn update_global_speed_limit(&self, limit_kbps: u64) {
+        self.chunk_scheduler.update_global_speed_limit(limit_kbps);
+    }
+
     /// 动态更新最大并发任务数
     ///
     /// 该方法可以在运行时调整最大并发任务数：
@@ -4371,8 +5480,37 @@ impl DownloadManager {
         (active_threads, max_threads)
     }
 
+    /// 🔥 获取调度调试信息（各活跃任务的槽位、分片数、速度，以及任务位池占用）
+    pub async fn get_slot_debug_info(&self) -> SlotDebugInfo {
+        let (active_threads, max_threads) = self.get_thread_pool_stats();
+        SlotDebugInfo {
+            active_threads,
+            max_threads,
+            tasks: self.chunk_scheduler.get_active_task_slots().await,
+            slot_pool: self.task_slot_pool.snapshot().await,
+        }
+    }
+
+    /// 获取等待队列长度
+    pub async fn waiting_count(&self) -> usize {
+        self.waiting_queue.read().await.len()
+    }
+
+    /// 获取所有下载中任务的速度总和 (bytes/s)
+    pub async fn total_speed(&self) -> u64 {
+        let tasks: Vec<_> = self.tasks.read().await.values().cloned().collect();
+        let mut speed = 0;
+        for task in tasks {
+            let t = task.lock().await;
+            if t.status == TaskStatus::Downloading {
+                speed += t.speed;
+            }
+        }
+        speed
+    }
+
     /// 设置任务完成通知发送器（用于文件夹下载补充任务）
-    pub async fn set_task_completed_sender(&self, tx: tokio::sync::mpsc::UnboundedSender<(String, String, u64, bool)>) {
+    pub async fn set_task_completed_sender(&self, tx: tokio::sync::mpsc::UnboundedSender<SubtaskCompletion>) {
         self.chunk_scheduler.set_task_completed_sender(tx).await;
     }
 
@@ -4511,6 +5649,54 @@ impl DownloadManager {
         }
     }
 
+    /// 🔥 批量删除指定 group 的所有任务，返回删除的任务数
+    ///
+    /// 1. 停止文件夹扫描并标记取消，阻止补充新任务
+    /// 2. 通过 [`Self::cancel_tasks_by_group`] 停止所有子任务（包括探测中的）
+    /// 3. 逐个删除子任务，清理 WAL/元数据（`delete_files` 为 true 时同时删除本地文件）
+    /// 4. 删除文件夹记录（持久化文件、历史记录、子任务历史）
+    pub async fn delete_group(&self, group_id: &str, delete_files: bool) -> Result<usize> {
+        info!("批量删除 group {} 的任务, 删除文件: {}", group_id, delete_files);
+
+        let fm_opt = self.folder_manager.read().await.clone();
+        let local_root = match fm_opt {
+            Some(ref fm) => fm.mark_folder_cancelled(group_id).await,
+            None => None,
+        };
+
+        self.cancel_tasks_by_group(group_id).await;
+
+        let tasks = self.get_tasks_by_group(group_id).await;
+        let mut deleted = 0;
+        for task in &tasks {
+            match self.delete_task(&task.id, delete_files).await {
+                Ok(_) => deleted += 1,
+                Err(e) => warn!("批量删除：任务 {} 删除失败: {}", task.id, e),
+            }
+        }
+
+        if let Some(fm) = fm_opt {
+            fm.release_folder_slots(group_id).await;
+
+            // 已完成并移出内存的子任务只剩历史记录，删除文件时整体移除文件夹目录
+            if delete_files {
+                if let Some(root_path) = local_root.filter(|path| path.exists()) {
+                    // 等待分片检测到取消并释放文件句柄（与 cancel_folder 一致）
+                    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+                    match tokio::fs::remove_dir_all(&root_path).await {
+                        Ok(_) => info!("已删除文件夹目录: {:?}", root_path),
+                        Err(e) => error!("删除文件夹目录失败: {:?}, 错误: {}", root_path, e),
+                    }
+                }
+            }
+
+            fm.delete_folder(group_id).await?;
+        }
+
+        info!("批量删除 group {} 完成，共删除 {} 个任务", group_id, deleted);
+        Ok(deleted)
+    }
+
     /// 添加任务（由 FolderDownloadManager 调用）
     pub async fn add_task(&self, task: DownloadTask) -> Result<String> {
         let task_id = task.id.clone();
@@ -4597,6 +5783,18 @@ impl DownloadManager {
         // 恢复任务 ID（保持原有 ID）
         task.id = task_id.clone();
 
+        // 🔥 继续写入同一个 .bdtmp 临时文件；
+        // 旧版本任务直接写入最终路径（只有最终文件存在），继续写入最终文件
+        if let Some(ref temp_path) = task.temp_path {
+            if !temp_path.exists() && recovery_info.local_path.exists() {
+                info!(
+                    "任务 {} 未找到临时文件，继续写入原文件: {:?}",
+                    task_id, recovery_info.local_path
+                );
+                task.temp_path = None;
+            }
+        }
+
         // 设置为暂停状态（等待用户手动恢复）
         task.status = TaskStatus::Paused;
 
@@ -4621,6 +5819,22 @@ impl DownloadManager {
         task.downloaded_size = downloaded_size + partial_bytes;
         task.created_at = recovery_info.created_at;
 
+        // 恢复上次实际使用的 CDN 主机
+        task.cdn_host = recovery_info.cdn_host.clone();
+
+        // 恢复网盘文件 MD5（下载完成后继续校验）
+        task.expected_md5 = recovery_info.expected_md5.clone();
+
+        // 恢复批量下载批次 ID
+        task.batch_id = recovery_info.batch_id.clone();
+
+        // 恢复分享直链（免转存直接下载任务不能按网盘路径获取下载链接）
+        task.direct_dlink = recovery_info.direct_dlink.clone();
+        task.share_source = recovery_info.share_source.clone();
+
+        // 恢复单任务限速
+        task.speed_limit_bytes_per_sec = recovery_info.speed_limit_bytes_per_sec;
+
         // 恢复文件夹下载组信息
         task.group_id = recovery_info.group_id.clone();
         task.group_root = recovery_info.group_root.clone();
@@ -4742,6 +5956,7 @@ mod tests {
             bdstoken: Some("mock_bdstoken".to_string()),
             login_time: 0,
             last_warmup_at: None,
+            devuid: None,
         }
     }
 
@@ -4768,6 +5983,7 @@ mod tests {
                 "file.txt".to_string(),
                 1024,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -4793,6 +6009,7 @@ mod tests {
                 "file.txt".to_string(),
                 1024,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -4803,6 +6020,154 @@ mod tests {
         assert_eq!(manager.get_all_tasks().await.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_update_task_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        let task_id = manager
+            .create_task(
+                12345,
+                "/test/file.txt".to_string(),
+                "file.txt".to_string(),
+                1024,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // 已暂停任务的临时文件随目录一起移动
+        let old_temp = temp_dir.path().join("file.txt.bdtmp");
+        std::fs::write(&old_temp, b"partial").unwrap();
+        {
+            let task = manager.tasks.read().await.get(&task_id).cloned().unwrap();
+            task.lock().await.status = TaskStatus::Paused;
+        }
+
+        let new_dir = temp_dir.path().join("moved");
+        let new_path = manager.update_task_dir(&task_id, &new_dir).await.unwrap();
+        assert_eq!(new_path, new_dir.join("file.txt"));
+        assert!(!old_temp.exists());
+        assert_eq!(std::fs::read(new_dir.join("file.txt.bdtmp")).unwrap(), b"partial");
+
+        let task = manager.get_task(&task_id).await.unwrap();
+        assert_eq!(task.local_path, new_path);
+        assert_eq!(task.temp_path, Some(new_dir.join("file.txt.bdtmp")));
+
+        // 下载中的任务需要先暂停
+        {
+            let task = manager.tasks.read().await.get(&task_id).cloned().unwrap();
+            task.lock().await.status = TaskStatus::Downloading;
+        }
+        let err = manager
+            .update_task_dir(&task_id, temp_dir.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("请先暂停"));
+
+        // 等待中的任务可能正在准备下载，同样不允许修改
+        {
+            let task = manager.tasks.read().await.get(&task_id).cloned().unwrap();
+            task.lock().await.status = TaskStatus::Pending;
+        }
+        assert!(manager.update_task_dir(&task_id, temp_dir.path()).await.is_err());
+
+        // 已失败的任务可以修改后重试
+        {
+            let task = manager.tasks.read().await.get(&task_id).cloned().unwrap();
+            task.lock().await.status = TaskStatus::Failed;
+        }
+        let back_path = manager.update_task_dir(&task_id, temp_dir.path()).await.unwrap();
+        assert_eq!(back_path, temp_dir.path().join("file.txt"));
+        assert!(temp_dir.path().join("file.txt.bdtmp").exists());
+        assert!(manager.relocating_tasks.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_folder_download_rejects_dir_outside_allowed_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager =
+            Arc::new(DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap());
+
+        let allowed = temp_dir.path().join("allowed");
+        std::fs::create_dir_all(&allowed).unwrap();
+        manager
+            .update_filesystem_config(FilesystemConfig {
+                allowed_paths: vec![allowed.to_string_lossy().to_string()],
+                ..Default::default()
+            })
+            .await;
+
+        let folder_manager =
+            crate::downloader::FolderDownloadManager::new(temp_dir.path().to_path_buf());
+        folder_manager.set_download_manager(manager.clone()).await;
+
+        // 白名单外的目录在创建文件夹任务前即被拒绝，且不会创建本地目录
+        let outside = temp_dir.path().join("outside");
+        let err = folder_manager
+            .create_folder_download_with_dir("/remote/dir".to_string(), &outside, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("filesystem.allowed_paths"));
+        assert!(!outside.exists());
+        assert!(folder_manager.get_all_folders().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_task_reuses_local_duplicate() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+        manager.update_dedup_local(true, false);
+
+        let md5 = "5d41402abc4b2a76b9719d911017c592";
+        let existing = temp_dir.path().join("existing.txt");
+        std::fs::write(&existing, b"hello").unwrap();
+        manager.chunk_scheduler.dedup_index().record(md5, &existing, 5);
+
+        let task_id = manager
+            .create_task(1, "/a/copy.txt".to_string(), "copy.txt".to_string(), 5, Some(md5.to_string()), None)
+            .await
+            .unwrap();
+        let task = manager.get_task(&task_id).await.unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(std::fs::read(temp_dir.path().join("copy.txt")).unwrap(), b"hello");
+
+        // 关闭去重后正常创建等待中的任务
+        manager.update_dedup_local(false, false);
+        let task_id = manager
+            .create_task(2, "/a/other.txt".to_string(), "other.txt".to_string(), 5, Some(md5.to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(manager.get_task(&task_id).await.unwrap().status, TaskStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_delete_group() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        // 同一 group 的 2 个任务 + 1 个单文件任务
+        for fs_id in 1..=3 {
+            let task_id = manager
+                .create_task(fs_id, format!("/test{}", fs_id), format!("file{}.txt", fs_id), 1024, None, None)
+                .await
+                .unwrap();
+            if fs_id < 3 {
+                let tasks = manager.tasks.read().await;
+                tasks.get(&task_id).unwrap().lock().await.group_id = Some("group-1".to_string());
+            }
+        }
+
+        assert_eq!(manager.delete_group("group-1", false).await.unwrap(), 2);
+        assert!(manager.get_tasks_by_group("group-1").await.is_empty());
+        assert_eq!(manager.get_all_tasks().await.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_clear_completed() {
         let temp_dir = TempDir::new().unwrap();
@@ -4811,15 +6176,15 @@ mod tests {
 
         // 创建3个任务
         let task_id1 = manager
-            .create_task(1, "/test1".to_string(), "file1.txt".to_string(), 1024, None)
+            .create_task(1, "/test1".to_string(), "file1.txt".to_string(), 1024, None, None)
             .await
             .unwrap();
         let task_id2 = manager
-            .create_task(2, "/test2".to_string(), "file2.txt".to_string(), 1024, None)
+            .create_task(2, "/test2".to_string(), "file2.txt".to_string(), 1024, None, None)
             .await
             .unwrap();
         let _task_id3 = manager
-            .create_task(3, "/test3".to_string(), "file3.txt".to_string(), 1024, None)
+            .create_task(3, "/test3".to_string(), "file3.txt".to_string(), 1024, None, None)
             .await
             .unwrap();
 
@@ -4836,6 +6201,143 @@ mod tests {
         assert_eq!(manager.get_all_tasks().await.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_pause_all_keeps_queue_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        let task_id1 = manager
+            .create_task(1, "/test1".to_string(), "file1.txt".to_string(), 1024, None, None)
+            .await
+            .unwrap();
+        let task_id2 = manager
+            .create_task(2, "/test2".to_string(), "file2.txt".to_string(), 1024, None, None)
+            .await
+            .unwrap();
+
+        // 模拟等待队列：任务2排在任务1之前
+        {
+            let mut queue = manager.waiting_queue.write().await;
+            queue.push_back(task_id2.clone());
+            queue.push_back(task_id1.clone());
+        }
+
+        let results = manager.pause_all().await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, ok, _)| *ok));
+
+        // 等待队列已清空，一键暂停记录保持原队列顺序
+        assert!(manager.waiting_queue.read().await.is_empty());
+        assert_eq!(
+            *manager.user_paused_tasks.read().await,
+            vec![task_id2.clone(), task_id1.clone()]
+        );
+        for id in [&task_id1, &task_id2] {
+            assert_eq!(manager.get_task(id).await.unwrap().status, TaskStatus::Paused);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_waiting_queue_priority_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..4 {
+            let id = manager
+                .create_task(i, format!("/test{}", i), format!("file{}.txt", i), 1024, None, None)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        manager.set_task_priority(&ids[1], DownloadPriority::Low).await.unwrap();
+        manager.set_task_priority(&ids[3], DownloadPriority::High).await.unwrap();
+
+        for id in &ids {
+            manager.add_to_waiting_queue_with_task_type(id, false, false).await;
+        }
+        let queue: Vec<String> = manager.waiting_queue.read().await.iter().cloned().collect();
+        // 高优先级在前，同优先级先进先出，低优先级在后
+        assert_eq!(queue, vec![ids[3].clone(), ids[0].clone(), ids[2].clone(), ids[1].clone()]);
+
+        // 等待中的任务修改优先级后立即重新排队（排到同优先级末尾）
+        manager.set_task_priority(&ids[1], DownloadPriority::High).await.unwrap();
+        let queue: Vec<String> = manager.waiting_queue.read().await.iter().cloned().collect();
+        assert_eq!(queue, vec![ids[3].clone(), ids[1].clone(), ids[0].clone(), ids[2].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_move_in_queue() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let id = manager
+                .create_task(i, format!("/test{}", i), format!("file{}.txt", i), 1024, None, None)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        let not_queued = manager
+            .create_task(9, "/test9".to_string(), "file9.txt".to_string(), 1024, None, None)
+            .await
+            .unwrap();
+        manager.waiting_queue.write().await.extend(ids.iter().cloned());
+
+        // 移到队首
+        let order = manager.move_in_queue(&ids[2], 0).await.unwrap();
+        assert_eq!(order, vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]);
+
+        // 位置超出范围时钳制到队尾
+        let order = manager.move_in_queue(&ids[2], 100).await.unwrap();
+        assert_eq!(order, vec![ids[0].clone(), ids[1].clone(), ids[2].clone()]);
+
+        // 不在等待队列中的任务拒绝移动
+        assert!(manager.move_in_queue(&not_queued, 0).await.is_err());
+        assert!(manager.move_in_queue("missing", 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_waiting_queue() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_auth = create_mock_user_auth();
+        let manager = DownloadManager::new(user_auth, temp_dir.path().to_path_buf()).unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let id = manager
+                .create_task(i, format!("/test{}", i), format!("file{}.txt", i), 1024, None, None)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        assert!(manager.get_waiting_queue().await.is_empty());
+
+        // 队列快照与出队顺序一致，已删除的任务被跳过
+        manager
+            .waiting_queue
+            .write()
+            .await
+            .extend([ids[2].clone(), "missing".to_string(), ids[0].clone()]);
+        let queued: Vec<(String, String)> = manager
+            .get_waiting_queue()
+            .await
+            .into_iter()
+            .map(|t| (t.id, t.remote_path))
+            .collect();
+        assert_eq!(
+            queued,
+            vec![
+                (ids[2].clone(), "/test2".to_string()),
+                (ids[0].clone(), "/test0".to_string()),
+            ]
+        );
+    }
+
     /// 回归测试：DownloadManager::restore_task 冷恢复主链路
     ///
     /// 验证从 DownloadRecoveryInfo 恢复任务时：
@@ -4865,6 +6367,8 @@ mod tests {
             history_archive_hour: 2,
             history_archive_minute: 0,
             history_retention_days: 30,
+            verify_chunks_on_recovery: false,
+            compress_wal: false,
         };
 
         // 第一阶段：模拟上一次运行——写入 WAL 然后“崩溃”
@@ -4934,6 +6438,13 @@ mod tests {
             is_encrypted: false,
             encryption_key_version: None,
             partial_progress,
+            chunk_crcs: Vec::new(),
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         };
 
         // 执行冷恢复
@@ -1,20 +1,41 @@
+pub mod adaptive;
 pub mod chunk;
+pub mod cooldown;
+pub mod dedup;
 pub mod engine;
+pub mod error;
 pub mod folder;
 pub mod folder_manager;
+pub mod link_source;
 pub mod manager;
 pub mod progress;
+pub mod rate_limiter;
+pub mod schedule;
 pub mod scheduler;
+pub mod speed_test;
 pub mod task;
+pub mod webhook;
 
+pub use adaptive::AdaptiveConcurrency;
 pub use chunk::{Chunk, ChunkManager};
-pub use engine::{DownloadEngine, UrlHealthManager};
-pub use folder::{FolderDownload, FolderStatus, PendingFile};
+pub use cooldown::RateLimitCooldown;
+pub use dedup::LocalDedupIndex;
+pub use engine::{ChunkDownloadContext, DownloadEngine, UrlHealthManager};
+pub use error::{DownloadErrorKind, HttpStatusError};
+pub use folder::{FlattenCollisionPolicy, FolderDownload, FolderStatus, PendingFile};
 pub use folder_manager::FolderDownloadManager;
+pub use link_source::{DownloadLinkSource, LinkRefreshTarget};
 pub use manager::DownloadManager;
 pub use progress::SpeedCalculator;
-pub use scheduler::{calculate_task_max_chunks, ChunkScheduler, TaskScheduleInfo};
-pub use task::{DownloadTask, TaskStatus};
+pub use rate_limiter::SpeedLimiter;
+pub use schedule::{DownloadSchedule, ScheduleStatus, TimeWindow};
+pub use scheduler::{
+    calculate_task_max_chunks, resolve_task_max_chunks, ActiveTaskSlotInfo, ChunkScheduler, SlotDebugInfo,
+    SubtaskCompletion, TaskRefreshHandles, TaskScheduleInfo,
+};
+pub use speed_test::SpeedTestResult;
+pub use task::{DownloadPriority, DownloadTask, TaskStatus};
+pub use webhook::{CompletionPayload, CompletionWebhook};
 
 // Re-export conflict strategy from uploader module for convenience
 pub use crate::uploader::conflict::DownloadConflictStrategy;
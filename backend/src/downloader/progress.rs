@@ -65,6 +65,26 @@ impl SpeedCalculator {
         0
     }
 
+    /// 计算为满足限速需要等待的时长
+    ///
+    /// 按滑动窗口内实际传输的字节数估算：以 `limit_bytes_per_sec` 传输这些数据
+    /// 应耗费的时间，减去实际已耗费的时间，即为需要补足的等待时长（最长不超过窗口大小）
+    pub fn throttle_delay(&self, limit_bytes_per_sec: u64) -> Duration {
+        if limit_bytes_per_sec == 0 {
+            return Duration::ZERO;
+        }
+
+        let Some((first_time, _)) = self.samples.front() else {
+            return Duration::ZERO;
+        };
+
+        let window_bytes: u64 = self.samples.iter().map(|(_, bytes)| bytes).sum();
+        let expected = Duration::from_secs_f64(window_bytes as f64 / limit_bytes_per_sec as f64);
+        let elapsed = Instant::now().duration_since(*first_time);
+
+        expected.saturating_sub(elapsed).min(self.window_size)
+    }
+
     /// 获取累计下载字节数
     pub fn total_bytes(&self) -> u64 {
         self.total_bytes
@@ -173,6 +193,27 @@ mod tests {
         assert_eq!(calc.speed(), 0);
     }
 
+    #[test]
+    fn test_throttle_delay() {
+        let mut calc = SpeedCalculator::new(5);
+
+        // 无数据或不限速时无需等待
+        assert_eq!(calc.throttle_delay(1024), Duration::ZERO);
+        calc.add_sample(1024 * 1024); // 1MB
+        assert_eq!(calc.throttle_delay(0), Duration::ZERO);
+
+        // 1MB 限速 1MB/s，应等待接近 1 秒
+        let delay = calc.throttle_delay(1024 * 1024);
+        assert!(delay > Duration::from_millis(900));
+        assert!(delay <= Duration::from_secs(1));
+
+        // 限速远低于实际速度时，等待时长不超过窗口大小
+        assert_eq!(calc.throttle_delay(1), Duration::from_secs(5));
+
+        // 限速远高于实际速度时无需等待
+        assert_eq!(calc.throttle_delay(u64::MAX), Duration::ZERO);
+    }
+
     #[test]
     fn test_format_speed() {
         assert_eq!(format_bytes_per_second(500), "500 B/s");
@@ -0,0 +1,159 @@
+//! 下载限速器
+//!
+//! 基于令牌桶的全局带宽限制，所有下载任务的分片共享同一个实例
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 令牌桶状态
+#[derive(Debug)]
+struct BucketState {
+    /// 当前令牌数（字节），允许为负数表示已预支的额度
+    tokens: f64,
+    /// 上次补充令牌的时间
+    last_refill: Instant,
+}
+
+/// 令牌桶限速器
+///
+/// 线程安全，可在多个分片间共享。分片每读取一批数据后调用 `acquire_delay()`：
+/// - 令牌充足时直接扣减，无需等待
+/// - 令牌不足时照常扣减（令牌数变为负数），按欠额计算需要等待的时长
+///
+/// 扣减总是立即完成，后到的分片需要偿还更多欠额、等待更久，
+/// 因此即使限速低于分片数量所能达到的最低速度，也不会死锁，
+/// 各分片按请求先后轮流获得带宽
+#[derive(Debug)]
+pub struct SpeedLimiter {
+    /// 限速值（字节/秒），0 表示不限速
+    limit_bytes_per_sec: AtomicU64,
+    /// 令牌桶状态
+    state: Mutex<BucketState>,
+}
+
+impl SpeedLimiter {
+    /// 创建新的限速器
+    ///
+    /// # 参数
+    /// * `limit_bytes_per_sec` - 限速值（字节/秒），0 表示不限速
+    pub fn new(limit_bytes_per_sec: u64) -> Self {
+        Self {
+            limit_bytes_per_sec: AtomicU64::new(limit_bytes_per_sec),
+            state: Mutex::new(BucketState {
+                tokens: limit_bytes_per_sec as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// 使用 KB/s 创建限速器（0 表示不限速）
+    pub fn with_kbps(limit_kbps: u64) -> Self {
+        Self::new(limit_kbps.saturating_mul(1024))
+    }
+
+    /// 获取当前限速值（字节/秒），0 表示不限速
+    pub fn limit(&self) -> u64 {
+        self.limit_bytes_per_sec.load(Ordering::SeqCst)
+    }
+
+    /// 是否启用了限速
+    pub fn is_enabled(&self) -> bool {
+        self.limit() > 0
+    }
+
+    /// 动态更新限速值（字节/秒，0 表示不限速）
+    ///
+    /// 更新时清空已预支的欠额，新限速立即生效
+    pub fn set_limit(&self, limit_bytes_per_sec: u64) {
+        self.limit_bytes_per_sec
+            .store(limit_bytes_per_sec, Ordering::SeqCst);
+
+        let mut state = self.state.lock();
+        state.tokens = limit_bytes_per_sec as f64;
+        state.last_refill = Instant::now();
+    }
+
+    /// 扣减令牌并返回需要等待的时长
+    ///
+    /// # 参数
+    /// * `bytes` - 本次已传输的字节数
+    pub fn acquire_delay(&self, bytes: u64) -> Duration {
+        let limit = self.limit();
+        if limit == 0 {
+            return Duration::ZERO;
+        }
+
+        let mut state = self.state.lock();
+        let now = Instant::now();
+
+        // 按流逝时间补充令牌，桶容量为 1 秒的额度
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * limit as f64).min(limit as f64);
+        state.last_refill = now;
+
+        state.tokens -= bytes as f64;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / limit as f64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = SpeedLimiter::new(0);
+        assert!(!limiter.is_enabled());
+        assert_eq!(limiter.acquire_delay(100 * 1024 * 1024), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_with_kbps() {
+        let limiter = SpeedLimiter::with_kbps(500);
+        assert_eq!(limiter.limit(), 500 * 1024);
+    }
+
+    #[test]
+    fn test_acquire_within_burst() {
+        let limiter = SpeedLimiter::new(1024 * 1024);
+        // 初始令牌为 1 秒额度，桶内额度足够时无需等待
+        assert_eq!(limiter.acquire_delay(512 * 1024), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_acquire_over_limit() {
+        let limiter = SpeedLimiter::new(1024 * 1024);
+
+        // 耗尽初始额度
+        limiter.acquire_delay(1024 * 1024);
+
+        // 再欠 1MB，约需等待 1 秒
+        let delay = limiter.acquire_delay(1024 * 1024);
+        assert!(delay > Duration::from_millis(900));
+        assert!(delay <= Duration::from_secs(1));
+
+        // 后续请求需要偿还更多欠额，等待时间递增（不会死锁）
+        let delay2 = limiter.acquire_delay(1024 * 1024);
+        assert!(delay2 > delay);
+    }
+
+    #[test]
+    fn test_set_limit_resets_debt() {
+        let limiter = SpeedLimiter::new(1024);
+        limiter.acquire_delay(10 * 1024 * 1024);
+
+        limiter.set_limit(100 * 1024 * 1024);
+        assert_eq!(limiter.limit(), 100 * 1024 * 1024);
+        assert_eq!(limiter.acquire_delay(1024 * 1024), Duration::ZERO);
+
+        limiter.set_limit(0);
+        assert!(!limiter.is_enabled());
+        assert_eq!(limiter.acquire_delay(u64::MAX), Duration::ZERO);
+    }
+}
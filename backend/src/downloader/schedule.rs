@@ -0,0 +1,217 @@
+//! 下载时间段调度
+//!
+//! 配置 `[schedule]` 后只在允许的时间段内下载（使用系统本地时区）：
+//! - 时间段外 DownloadManager 不再启动新任务：单文件任务进入等待队列，时间段外新建的文件夹暂停
+//! - 开启 `pause_running` 时同时暂停正在下载的任务
+//! - 进入时间段后自动恢复因调度暂停的任务，并启动等待队列
+
+use chrono::{Local, NaiveTime, Timelike};
+use parking_lot::RwLock;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::ScheduleConfig;
+
+/// 一天的秒数
+const SECS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// 允许下载的时间段
+///
+/// 结束早于开始表示跨午夜（如 23:00-07:00），开始等于结束表示全天
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeWindow {
+    /// 解析 "HH:MM-HH:MM" 格式的时间段
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| format!("时间段格式无效（应为 HH:MM-HH:MM）: {}", value))?;
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|_| format!("时间格式无效（应为 HH:MM）: {}", time.trim()))
+        };
+        Ok(Self {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        })
+    }
+
+    /// 指定时间是否在时间段内（包含开始，不包含结束）
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start == self.end {
+            true
+        } else if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// 从指定时间到下一次时间段开始的秒数
+    fn secs_until_start(&self, time: NaiveTime) -> u32 {
+        let start = self.start.num_seconds_from_midnight();
+        let now = time.num_seconds_from_midnight();
+        (start + SECS_PER_DAY - now) % SECS_PER_DAY
+    }
+}
+
+/// 调度状态（通过统计接口展示，便于用户了解任务为何没有启动）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ScheduleStatus {
+    /// 是否启用时间段调度
+    pub enabled: bool,
+    /// 当前是否允许下载（未启用时始终为 true）
+    pub in_window: bool,
+    /// 配置的时间段
+    pub windows: Vec<String>,
+    /// 时间段外是否暂停正在下载的任务
+    pub pause_running: bool,
+    /// 下一个时间段的开始时间（HH:MM，当前允许下载时为 null）
+    pub next_window_start: Option<String>,
+}
+
+/// 解析后的调度配置
+#[derive(Debug, Clone, Default)]
+struct ParsedSchedule {
+    enabled: bool,
+    pause_running: bool,
+    windows: Vec<(String, TimeWindow)>,
+}
+
+/// 下载时间段调度器（动态可调整）
+#[derive(Debug, Default)]
+pub struct DownloadSchedule {
+    schedule: RwLock<ParsedSchedule>,
+}
+
+impl DownloadSchedule {
+    /// 创建调度器（默认未启用，全天允许下载）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 动态更新调度配置，无效的时间段跳过
+    pub fn update_config(&self, config: &ScheduleConfig) {
+        let windows = config
+            .windows
+            .iter()
+            .filter_map(|value| match TimeWindow::parse(value) {
+                Ok(window) => Some((value.trim().to_string(), window)),
+                Err(e) => {
+                    warn!("忽略无效的下载时间段: {}", e);
+                    None
+                }
+            })
+            .collect();
+        *self.schedule.write() = ParsedSchedule {
+            enabled: config.enabled,
+            pause_running: config.pause_running,
+            windows,
+        };
+    }
+
+    /// 时间段外是否暂停正在下载的任务
+    pub fn pause_running(&self) -> bool {
+        let schedule = self.schedule.read();
+        schedule.enabled && schedule.pause_running
+    }
+
+    /// 当前是否允许下载
+    pub fn is_open(&self) -> bool {
+        self.is_open_at(Local::now().time())
+    }
+
+    /// 指定时间是否允许下载（未启用或未配置时间段时始终允许）
+    fn is_open_at(&self, time: NaiveTime) -> bool {
+        let schedule = self.schedule.read();
+        !schedule.enabled
+            || schedule.windows.is_empty()
+            || schedule.windows.iter().any(|(_, window)| window.contains(time))
+    }
+
+    /// 当前调度状态
+    pub fn status(&self) -> ScheduleStatus {
+        self.status_at(Local::now().time())
+    }
+
+    fn status_at(&self, time: NaiveTime) -> ScheduleStatus {
+        let in_window = self.is_open_at(time);
+        let schedule = self.schedule.read();
+        let next_window_start = if in_window {
+            None
+        } else {
+            schedule
+                .windows
+                .iter()
+                .min_by_key(|(_, window)| window.secs_until_start(time))
+                .map(|(_, window)| window.start.format("%H:%M").to_string())
+        };
+
+        ScheduleStatus {
+            enabled: schedule.enabled,
+            in_window,
+            windows: schedule.windows.iter().map(|(value, _)| value.clone()).collect(),
+            pause_running: schedule.pause_running,
+            next_window_start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_time_window() {
+        let night = TimeWindow::parse("00:00-08:00").unwrap();
+        assert!(night.contains(time(0, 0)));
+        assert!(night.contains(time(7, 59)));
+        assert!(!night.contains(time(8, 0)));
+
+        // 跨午夜
+        let overnight = TimeWindow::parse("23:00 - 07:00").unwrap();
+        assert!(overnight.contains(time(23, 30)));
+        assert!(overnight.contains(time(6, 0)));
+        assert!(!overnight.contains(time(12, 0)));
+
+        assert!(TimeWindow::parse("08:00-08:00").unwrap().contains(time(15, 0)));
+        assert!(TimeWindow::parse("25:00-08:00").is_err());
+        assert!(TimeWindow::parse("08:00").is_err());
+    }
+
+    #[test]
+    fn test_schedule_status() {
+        let schedule = DownloadSchedule::new();
+        assert!(schedule.is_open_at(time(12, 0)));
+
+        schedule.update_config(&ScheduleConfig {
+            enabled: true,
+            windows: vec!["00:00-08:00".to_string(), "12:00-13:00".to_string()],
+            pause_running: true,
+        });
+        assert!(schedule.is_open_at(time(1, 0)));
+        assert!(schedule.is_open_at(time(12, 30)));
+        assert!(schedule.pause_running());
+
+        let status = schedule.status_at(time(9, 0));
+        assert!(!status.in_window);
+        assert_eq!(status.next_window_start.as_deref(), Some("12:00"));
+
+        let status = schedule.status_at(time(20, 0));
+        assert_eq!(status.next_window_start.as_deref(), Some("00:00"));
+        assert!(schedule.status_at(time(3, 0)).next_window_start.is_none());
+
+        // 关闭后全天允许
+        schedule.update_config(&ScheduleConfig::default());
+        assert!(schedule.is_open_at(time(9, 0)));
+        assert!(!schedule.pause_running());
+    }
+}
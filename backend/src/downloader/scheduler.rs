@@ -1,20 +1,32 @@
 use crate::encryption::service::EncryptionService;
 use crate::autobackup::events::{BackupTransferNotification, TransferTaskType};
+use crate::common::RefreshCoordinator;
+use crate::config::AdaptiveConcurrencyConfig;
 use crate::downloader::{
-    ChunkManager, DownloadEngine, DownloadTask, SpeedCalculator, UrlHealthManager,
+    AdaptiveConcurrency, ChunkDownloadContext, ChunkManager, CompletionPayload, CompletionWebhook, DownloadEngine, DownloadErrorKind, DownloadTask,
+    LinkRefreshTarget, LocalDedupIndex, RateLimitCooldown, SpeedCalculator, SpeedLimiter, TaskStatus, UrlHealthManager,
 };
 use crate::persistence::PersistenceManager;
 use crate::server::events::{DownloadEvent, ProgressThrottler, TaskEvent};
 use crate::server::websocket::WebSocketManager;
 use anyhow::Result;
 use reqwest::Client;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock};
 use tokio::sync::{mpsc, Mutex, RwLock, Semaphore};
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
+
+/// 🔥 任务日志 span（供 TaskLogLayer 按任务收集日志）
+///
+/// span 以 ERROR 级别创建，避免被 `warn`/`error` 等较严格的全局过滤器禁用，
+/// 否则 span 内的事件无法关联到任务；span 内的事件仍按全局过滤器的级别记录
+fn download_task_span(task_id: &str) -> tracing::Span {
+    tracing::error_span!("download_task", task_id = %task_id)
+}
 
 /// 🔥 根据文件大小计算单任务最大并发分片数
 ///
@@ -35,6 +47,22 @@ pub fn calculate_task_max_chunks(file_size: u64) -> usize {
     }
 }
 
+/// 🔥 计算单任务最大并发分片数（支持覆盖值）
+///
+/// 任务覆盖值优先，其次全局默认值（0 表示未设置），都未设置时按文件大小计算；
+/// 覆盖值限制在 `1..=ceiling`（会员等级上限），避免并发过高触发限速
+pub fn resolve_task_max_chunks(
+    file_size: u64,
+    task_override: Option<usize>,
+    global_default: usize,
+    ceiling: usize,
+) -> usize {
+    match task_override.filter(|&n| n > 0).or(Some(global_default).filter(|&n| n > 0)) {
+        Some(n) => n.clamp(1, ceiling.max(1)),
+        None => calculate_task_max_chunks(file_size),
+    }
+}
+
 /// 分片线程槽位池
 ///
 /// 为每个正在下载的分片分配一个唯一的槽位ID（1, 2, 3...max_slots）
@@ -75,6 +103,21 @@ impl ChunkSlotPool {
     }
 }
 
+/// 🔥 文件夹子任务结束通知（调度器 → 文件夹管理器）
+#[derive(Debug, Clone)]
+pub struct SubtaskCompletion {
+    /// 所属文件夹 ID
+    pub group_id: String,
+    /// 子任务 ID
+    pub task_id: String,
+    /// 文件大小
+    pub size: u64,
+    /// 是否下载成功
+    pub success: bool,
+    /// 是否通过 MD5 校验（下载完成后删除网盘副本的前提）
+    pub md5_verified: bool,
+}
+
 /// 任务调度信息
 #[derive(Debug, Clone)]
 pub struct TaskScheduleInfo {
@@ -159,9 +202,9 @@ pub struct TaskScheduleInfo {
     /// DownloadManager.tasks 的引用，任务完成后从中移除
     pub manager_tasks: Option<Arc<RwLock<std::collections::HashMap<String, Arc<Mutex<crate::downloader::DownloadTask>>>>>>,
 
-    // 🔥 链接级重试次数（单次调度内换链接重试的上限）
-    /// 从配置 DownloadConfig.max_retries 读取
-    pub max_retries: u32,
+    // 🔥 分片重试配置（单次调度内换链接重试的上限及退避延迟）
+    /// 从配置 DownloadConfig::chunk_retry_config() 读取
+    pub retry_config: crate::config::RetryConfig,
 
     // 🔥 代理故障回退管理器
     /// 可选，用于记录代理失败/成功并触发自动回退
@@ -170,6 +213,62 @@ pub struct TaskScheduleInfo {
     // 🔥 任务级槽位刷新节流器，所有分片共享
     /// 防止分片切换时重置节流计时器，确保槽位心跳持续有效
     pub slot_touch_throttler: Arc<crate::task_slot_pool::SlotTouchThrottler>,
+
+    // 🔥 全局限速器（所有任务共享同一个令牌桶）
+    /// 由 DownloadManager 创建，限速值从配置 DownloadConfig.global_speed_limit_kbps 读取
+    pub global_speed_limiter: Arc<SpeedLimiter>,
+
+    // 🔥 任务级链接刷新协调器（检测循环与手动刷新共享，遵守最小刷新间隔）
+    pub refresh_coordinator: Arc<RefreshCoordinator>,
+}
+
+/// 手动刷新下载链接所需的任务引用
+#[derive(Debug, Clone)]
+pub struct TaskRefreshHandles {
+    /// 刷新目标（网盘路径或分享文件）
+    pub target: LinkRefreshTarget,
+    /// 文件总大小
+    pub total_size: u64,
+    /// URL 健康管理器
+    pub url_health: Arc<Mutex<UrlHealthManager>>,
+    /// HTTP 客户端
+    pub client: Client,
+    /// 刷新协调器
+    pub refresh_coordinator: Arc<RefreshCoordinator>,
+}
+
+/// 活跃任务的槽位与分片占用（调试用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveTaskSlotInfo {
+    /// 任务 ID
+    pub task_id: String,
+    /// 所属文件夹任务 ID（单文件任务为 null）
+    pub group_id: Option<String>,
+    /// 注册时占用的槽位ID
+    pub slot_id: Option<usize>,
+    /// 是否使用借调位
+    pub is_borrowed_slot: bool,
+    /// 当前正在下载的分片数
+    pub active_chunks: usize,
+    /// 单任务最大并发分片数
+    pub max_concurrent_chunks: usize,
+    /// 当前速度（字节/秒）
+    pub speed: u64,
+    /// 实际提供下载的 CDN 主机
+    pub cdn_host: Option<String>,
+}
+
+/// 下载调度调试信息（线程池 + 活跃任务 + 任务位池）
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotDebugInfo {
+    /// 正在下载的分片线程数
+    pub active_threads: usize,
+    /// 最大全局线程数
+    pub max_threads: usize,
+    /// 调度器中的活跃任务（按任务 ID 排序）
+    pub tasks: Vec<ActiveTaskSlotInfo>,
+    /// 任务位池占用
+    pub slot_pool: crate::task_slot_pool::SlotPoolSnapshot,
 }
 
 /// 全局分片调度器
@@ -195,7 +294,7 @@ pub struct ChunkScheduler {
     /// 调度器是否正在运行
     scheduler_running: Arc<AtomicBool>,
     /// 任务完成通知发送器（用于通知 FolderDownloadManager 补充任务）
-    task_completed_tx: Arc<RwLock<Option<mpsc::UnboundedSender<(String, String, u64, bool)>>>>,
+    task_completed_tx: Arc<RwLock<Option<mpsc::UnboundedSender<SubtaskCompletion>>>>,
     /// 🔥 备份任务统一通知发送器（用于通知 AutoBackupManager 所有事件）
     /// 包括：进度更新、状态变更、任务完成、任务失败等
     backup_notification_tx: Arc<RwLock<Option<mpsc::UnboundedSender<BackupTransferNotification>>>>,
@@ -205,6 +304,65 @@ pub struct ChunkScheduler {
     last_task_count: Arc<AtomicUsize>,
     /// 🔥 解密并发控制信号量（限制同时解密的文件数，避免内存和CPU过载）
     decrypt_semaphore: Arc<Semaphore>,
+    /// 🔥 全局限速器（所有任务共享，动态可调整）
+    global_speed_limiter: Arc<SpeedLimiter>,
+    /// 🔥 下载完成后是否校验 MD5（动态可调整）
+    verify_md5: Arc<AtomicBool>,
+    /// 🔥 任务级重试通知发送器（分片重试耗尽且错误可恢复时，通知 DownloadManager 重新排队）
+    /// 发送 (task_id, 第几次重试)
+    task_retry_tx: Arc<RwLock<Option<mpsc::UnboundedSender<(String, u32)>>>>,
+    /// 🔥 任务级最大重试次数（0 表示不自动重试，动态可调整）
+    max_task_retries: Arc<AtomicU32>,
+    /// 🔥 全局限流冷却器（分片失败时计数，冷却期间降低全局线程数）
+    rate_limit_cooldown: Arc<RateLimitCooldown>,
+    /// 🔥 下载完成回调（未配置地址时不发送，动态可调整）
+    completion_webhook: Arc<CompletionWebhook>,
+    /// 🔥 本地 MD5 去重索引（任务完成时记录，未开启时不记录，动态可调整）
+    dedup_index: Arc<LocalDedupIndex>,
+    /// 🔥 单 CDN 主机最大并发分片数（0 表示不限制，动态可调整，注册任务时共享给 UrlHealthManager）
+    max_chunks_per_host: Arc<AtomicUsize>,
+    /// 🔥 自适应并发控制器（按实测吞吐量调整全局线程数，未开启时不生效）
+    adaptive_concurrency: Arc<AdaptiveConcurrency>,
+    /// 🔥 任务无进度失败时长（秒，0 表示不限制，动态可调整）
+    max_stall_secs: Arc<AtomicU64>,
+    /// 🔥 无进度检测循环是否在运行（随调度循环启停，避免重复启动）
+    stall_watchdog_running: Arc<AtomicBool>,
+}
+
+/// 🔥 任务进度停滞跟踪（记录每个任务最近一次下载量变化的时间）
+#[derive(Debug, Default)]
+struct StallTracker {
+    /// task_id -> (上次观察到的已下载字节数, 该值首次出现的时间)
+    last_progress: HashMap<String, (u64, std::time::Instant)>,
+}
+
+impl StallTracker {
+    /// 记录一次观察，返回任务持续没有进度的时长
+    fn observe(
+        &mut self,
+        task_id: &str,
+        downloaded: u64,
+        now: std::time::Instant,
+    ) -> std::time::Duration {
+        match self.last_progress.get_mut(task_id) {
+            Some((last, since)) if *last == downloaded => now.saturating_duration_since(*since),
+            Some(entry) => {
+                *entry = (downloaded, now);
+                std::time::Duration::ZERO
+            }
+            None => {
+                self.last_progress
+                    .insert(task_id.to_string(), (downloaded, now));
+                std::time::Duration::ZERO
+            }
+        }
+    }
+
+    /// 移除已不在调度器中的任务
+    fn retain(&mut self, task_ids: &[String]) {
+        self.last_progress
+            .retain(|task_id, _| task_ids.contains(task_id));
+    }
 }
 
 impl ChunkScheduler {
@@ -227,10 +385,17 @@ impl ChunkScheduler {
     }
 
     /// 创建新的调度器
-    pub fn new(max_global_threads: usize, max_concurrent_tasks: usize) -> Self {
+    pub fn new(
+        max_global_threads: usize,
+        max_concurrent_tasks: usize,
+        global_speed_limiter: Arc<SpeedLimiter>,
+        rate_limit_cooldown: Arc<RateLimitCooldown>,
+    ) -> Self {
         info!(
-            "创建全局分片调度器: 全局线程数={}, 最大并发任务数={}",
-            max_global_threads, max_concurrent_tasks
+            "创建全局分片调度器: 全局线程数={}, 最大并发任务数={}, 全局限速={}",
+            max_global_threads,
+            max_concurrent_tasks,
+            Self::format_speed_limit(global_speed_limiter.limit())
         );
 
         let scheduler = Self {
@@ -248,9 +413,20 @@ impl ChunkScheduler {
             // 解密是 CPU 密集型 + 磁盘 IO 操作
             // 使用 CPU 核心数的一半（至少 2，最多 8）作为并发数
             decrypt_semaphore: Arc::new(Semaphore::new(Self::calculate_decrypt_concurrency())),
+            global_speed_limiter,
+            verify_md5: Arc::new(AtomicBool::new(true)),
+            task_retry_tx: Arc::new(RwLock::new(None)),
+            max_task_retries: Arc::new(AtomicU32::new(0)),
+            rate_limit_cooldown,
+            completion_webhook: Arc::new(CompletionWebhook::new()),
+            dedup_index: Arc::new(LocalDedupIndex::new()),
+            max_chunks_per_host: Arc::new(AtomicUsize::new(0)),
+            adaptive_concurrency: Arc::new(AdaptiveConcurrency::default()),
+            max_stall_secs: Arc::new(AtomicU64::new(300)),
+            stall_watchdog_running: Arc::new(AtomicBool::new(false)),
         };
 
-        // 启动全局调度循环
+        // 启动全局调度循环（同时启动任务无进度兜底检测）
         scheduler.start_scheduling();
 
         scheduler
@@ -259,8 +435,8 @@ impl ChunkScheduler {
     /// 设置任务完成通知发送器
     ///
     /// FolderDownloadManager 调用此方法设置 channel sender，
-    /// 当文件夹子任务完成时会发送 group_id 到 channel
-    pub async fn set_task_completed_sender(&self, tx: mpsc::UnboundedSender<(String, String, u64, bool)>) {
+    /// 当文件夹子任务结束时会发送 `SubtaskCompletion` 到 channel
+    pub async fn set_task_completed_sender(&self, tx: mpsc::UnboundedSender<SubtaskCompletion>) {
         let mut sender = self.task_completed_tx.write().await;
         *sender = Some(tx);
         info!("任务完成通知 channel 已设置");
@@ -272,12 +448,38 @@ impl ChunkScheduler {
     pub async fn notify_subtask_failed(&self, group_id: String, task_id: String, total_size: u64) {
         let tx_guard = self.task_completed_tx.read().await;
         if let Some(tx) = tx_guard.as_ref() {
-            if let Err(e) = tx.send((group_id, task_id, total_size, false)) {
+            let completion = SubtaskCompletion {
+                group_id,
+                task_id,
+                size: total_size,
+                success: false,
+                md5_verified: false,
+            };
+            if let Err(e) = tx.send(completion) {
                 error!("发送子任务失败通知失败: {}", e);
             }
         }
     }
 
+    /// 🔥 通知文件夹管理器子任务已完成
+    ///
+    /// 供未经过调度器的完成路径（如跳过策略下已有文件一致）调用
+    pub async fn notify_subtask_completed(&self, group_id: String, task_id: String, total_size: u64) {
+        let tx_guard = self.task_completed_tx.read().await;
+        if let Some(tx) = tx_guard.as_ref() {
+            let completion = SubtaskCompletion {
+                group_id,
+                task_id,
+                size: total_size,
+                success: true,
+                md5_verified: false,
+            };
+            if let Err(e) = tx.send(completion) {
+                error!("发送子任务完成通知失败: {}", e);
+            }
+        }
+    }
+
     /// 🔥 设置备份任务统一通知发送器
     ///
     /// AutoBackupManager 调用此方法设置 channel sender，
@@ -298,6 +500,16 @@ impl ChunkScheduler {
         info!("等待队列触发器已设置（0延迟启动）");
     }
 
+    /// 🔥 设置任务级重试通知发送器
+    ///
+    /// DownloadManager 调用此方法设置 channel sender，
+    /// 任务因可恢复错误重试耗尽时发送 (task_id, attempt)，由 DownloadManager 延迟后重新排队
+    pub async fn set_task_retry_sender(&self, tx: mpsc::UnboundedSender<(String, u32)>) {
+        let mut sender = self.task_retry_tx.write().await;
+        *sender = Some(tx);
+        info!("任务级重试通知 channel 已设置");
+    }
+
     /// 动态更新最大全局线程数
     ///
     /// 该方法可以在运行时调整线程池大小，无需重启下载管理器
@@ -312,6 +524,116 @@ impl ChunkScheduler {
         info!("🔧 动态调整最大并发任务数: {} -> {}", old_max, new_max);
     }
 
+    /// 🔥 动态更新全局限速（KB/s，0 表示不限速）
+    ///
+    /// 所有任务共享同一个限速器，正在下载的分片在下一次读取时即按新限速执行
+    pub fn update_global_speed_limit(&self, limit_kbps: u64) {
+        let old_limit = self.global_speed_limiter.limit();
+        let new_limit = limit_kbps.saturating_mul(1024);
+        self.global_speed_limiter.set_limit(new_limit);
+        info!(
+            "🔧 动态调整全局限速: {} -> {}",
+            Self::format_speed_limit(old_limit),
+            Self::format_speed_limit(new_limit)
+        );
+    }
+
+    /// 🔥 动态更新是否在下载完成后校验 MD5
+    pub fn update_verify_md5(&self, enabled: bool) {
+        let old = self.verify_md5.swap(enabled, Ordering::SeqCst);
+        if old != enabled {
+            info!("🔧 动态调整下载完成后 MD5 校验: {} -> {}", old, enabled);
+        }
+    }
+
+    /// 🔥 动态更新任务级最大重试次数
+    pub fn update_max_task_retries(&self, max_retries: u32) {
+        let old = self.max_task_retries.swap(max_retries, Ordering::SeqCst);
+        if old != max_retries {
+            info!("🔧 动态调整任务级最大重试次数: {} -> {}", old, max_retries);
+        }
+    }
+
+    /// 🔥 动态更新下载完成回调地址和共享密钥
+    pub fn update_completion_webhook(&self, url: Option<String>, secret: Option<String>) {
+        let was_enabled = self.completion_webhook.is_enabled();
+        self.completion_webhook.update_config(url, secret);
+        let enabled = self.completion_webhook.is_enabled();
+        if was_enabled != enabled {
+            info!("🔧 动态调整下载完成回调: {} -> {}", was_enabled, enabled);
+        }
+    }
+
+    /// 🔥 动态更新是否启用本地 MD5 去重，以及命中时是否使用硬链接
+    pub fn update_dedup_local(&self, enabled: bool, hardlink: bool) {
+        let old = self.dedup_index.is_enabled();
+        self.dedup_index.set_enabled(enabled);
+        if old != enabled {
+            info!("🔧 动态调整本地 MD5 去重: {} -> {}", old, enabled);
+        }
+        let old_hardlink = self.dedup_index.allows_hardlink();
+        self.dedup_index.set_hardlink(hardlink);
+        if old_hardlink != hardlink {
+            info!("🔧 动态调整本地去重硬链接: {} -> {}", old_hardlink, hardlink);
+        }
+    }
+
+    /// 🔥 动态更新单 CDN 主机最大并发分片数（0 表示不限制）
+    pub fn update_max_chunks_per_host(&self, max_chunks: usize) {
+        let old = self.max_chunks_per_host.swap(max_chunks, Ordering::SeqCst);
+        if old != max_chunks {
+            info!("🔧 动态调整单主机最大并发分片数: {} -> {}", old, max_chunks);
+        }
+    }
+
+    /// 🔥 动态更新自适应并发配置
+    pub fn update_adaptive_concurrency_config(&self, config: AdaptiveConcurrencyConfig) {
+        let old = self.adaptive_concurrency.is_enabled();
+        let enabled = config.enabled;
+        self.adaptive_concurrency.update_config(config);
+        if old != enabled {
+            info!("🔧 动态调整自适应并发: {} -> {}", old, enabled);
+        }
+    }
+
+    /// 🔥 汇总所有活跃任务的实时速度（字节/秒），供自适应并发评估
+    async fn total_throughput(active_tasks: &RwLock<HashMap<String, TaskScheduleInfo>>) -> u64 {
+        let speed_calcs: Vec<Arc<Mutex<SpeedCalculator>>> = active_tasks
+            .read()
+            .await
+            .values()
+            .map(|task_info| task_info.speed_calc.clone())
+            .collect();
+
+        let mut total = 0u64;
+        for speed_calc in speed_calcs {
+            total = total.saturating_add(speed_calc.lock().await.speed());
+        }
+        total
+    }
+
+    /// 🔥 动态更新任务无进度失败时长（秒，0 表示不限制）
+    pub fn update_max_stall_secs(&self, secs: u64) {
+        let old = self.max_stall_secs.swap(secs, Ordering::SeqCst);
+        if old != secs {
+            info!("🔧 动态调整任务无进度失败时长: {}s -> {}s", old, secs);
+        }
+    }
+
+    /// 获取本地 MD5 去重索引
+    pub fn dedup_index(&self) -> Arc<LocalDedupIndex> {
+        self.dedup_index.clone()
+    }
+
+    /// 格式化限速值（用于日志）
+    fn format_speed_limit(limit_bytes_per_sec: u64) -> String {
+        if limit_bytes_per_sec == 0 {
+            "不限速".to_string()
+        } else {
+            crate::downloader::progress::format_bytes_per_second(limit_bytes_per_sec)
+        }
+    }
+
     /// 获取当前最大线程数
     pub fn max_threads(&self) -> usize {
         self.max_global_threads.load(Ordering::SeqCst)
@@ -335,11 +657,18 @@ impl ChunkScheduler {
                 let notification_tx = self.backup_notification_tx.read().await.clone();
                 if notification_tx.is_some() {
                     task_info.backup_notification_tx = notification_tx;
-                    info!("备份下载任务 {} 已注入统一通知 sender", task_id);
+                    info!(task_id = %task_id, "备份下载任务已注入统一通知 sender");
                 }
             }
         }
 
+        // 🔥 共享单主机并发上限，任务的链接选择按主机分散
+        task_info
+            .url_health
+            .lock()
+            .await
+            .set_host_chunk_limit(self.max_chunks_per_host.clone());
+
         // 添加到活跃任务列表（不再检查并发上限，由任务槽控制）
         self.active_tasks
             .write()
@@ -347,11 +676,7 @@ impl ChunkScheduler {
             .insert(task_id.clone(), task_info);
 
         let active_count = self.active_tasks.read().await.len();
-        info!(
-            "任务 {} 已注册到调度器 (当前活跃任务数: {})",
-            task_id,
-            active_count
-        );
+        info!(task_id = %task_id, "任务已注册到调度器 (当前活跃任务数: {})", active_count);
         Ok(())
     }
 
@@ -359,10 +684,28 @@ impl ChunkScheduler {
     pub async fn cancel_task(&self, task_id: &str) {
         if let Some(task_info) = self.active_tasks.write().await.remove(task_id) {
             task_info.cancellation_token.cancel();
-            info!("任务 {} 已从调度器移除并取消", task_id);
+            info!(task_id = %task_id, event = "cancelled", "任务已从调度器移除并取消");
         }
     }
 
+    /// 🔥 获取活跃任务的链接刷新引用
+    ///
+    /// 任务未在调度器中（未开始或已结束）时返回 None
+    pub async fn get_task_refresh_handles(&self, task_id: &str) -> Option<TaskRefreshHandles> {
+        let tasks = self.active_tasks.read().await;
+        let task_info = tasks.get(task_id)?;
+        let target = LinkRefreshTarget::from_task(&*task_info.task.lock().await);
+
+        let handles = TaskRefreshHandles {
+            target,
+            total_size: task_info.total_size,
+            url_health: task_info.url_health.clone(),
+            client: task_info.client.read().unwrap().clone(),
+            refresh_coordinator: task_info.refresh_coordinator.clone(),
+        };
+        Some(handles)
+    }
+
     /// 获取活跃任务数量（已注册的任务数）
     pub async fn active_task_count(&self) -> usize {
         self.active_tasks.read().await.len()
@@ -384,16 +727,24 @@ impl ChunkScheduler {
         let active_tasks = self.active_tasks.clone();
         let max_global_threads = self.max_global_threads.clone();
         let active_chunk_count = self.active_chunk_count.clone();
-        let slot_pool = self.slot_pool.clone();
         let scheduler_running = self.scheduler_running.clone();
         let task_completed_tx = self.task_completed_tx.clone();
         let backup_notification_tx = self.backup_notification_tx.clone();
         let waiting_queue_trigger = self.waiting_queue_trigger.clone();
         let last_task_count = self.last_task_count.clone();
         let decrypt_semaphore = self.decrypt_semaphore.clone();
+        let verify_md5 = self.verify_md5.clone();
+        let rate_limit_cooldown = self.rate_limit_cooldown.clone();
+        let completion_webhook = self.completion_webhook.clone();
+        let dedup_index = self.dedup_index.clone();
+        let max_chunks_per_host = self.max_chunks_per_host.clone();
+        let adaptive_concurrency = self.adaptive_concurrency.clone();
+        let scheduler = self.clone();
 
         // 标记调度器正在运行
         scheduler_running.store(true, Ordering::SeqCst);
+        // 🔥 无进度检测与调度循环同生命周期：调度器停止后退出，重新启动时一并启动
+        self.start_stall_watchdog();
 
         info!("🚀 全局分片调度循环已启动");
 
@@ -438,9 +789,23 @@ impl ChunkScheduler {
                     continue;
                 }
 
+                // 🔥 自适应并发：按评估间隔汇总吞吐量，调整全局线程数上限
+                if adaptive_concurrency.is_due() {
+                    let throughput = Self::total_throughput(&active_tasks).await;
+                    adaptive_concurrency.evaluate(
+                        throughput,
+                        active_chunk_count.load(Ordering::SeqCst),
+                        max_global_threads.load(Ordering::SeqCst),
+                    );
+                }
+
                 // 🔥 批量调度：尽可能填满所有空闲线程，同时保持公平性
                 let mut scheduled_count = 0;
-                let max_threads = max_global_threads.load(Ordering::SeqCst);
+                // 🔥 自适应并发生效时使用控制器给出的线程数，限流冷却期间进一步降低，
+                // 正在下载的分片完成后自然收敛
+                let max_threads = rate_limit_cooldown.effective_max_threads(
+                    adaptive_concurrency.effective_max_threads(max_global_threads.load(Ordering::SeqCst)),
+                );
                 let current_active = active_chunk_count.load(Ordering::SeqCst);
 
                 // 检查是否有空闲线程
@@ -483,7 +848,7 @@ impl ChunkScheduler {
 
                     // 检查任务是否被取消
                     if task_info.cancellation_token.is_cancelled() {
-                        info!("任务 {} 已被取消，从调度器移除", task_id);
+                        info!(task_id = %task_id, event = "cancelled", "任务已被取消，从调度器移除");
                         active_tasks.write().await.remove(task_id);
                         consecutive_empty_rounds += 1;
                         if consecutive_empty_rounds >= task_count {
@@ -496,8 +861,9 @@ impl ChunkScheduler {
                     let task_active = task_info.active_chunk_count.load(Ordering::SeqCst);
                     if task_active >= task_info.max_concurrent_chunks {
                         debug!(
-                            "任务 {} 已达并发上限 ({}/{}), 跳过",
-                            task_id, task_active, task_info.max_concurrent_chunks
+                            task_id = %task_id,
+                            "任务已达并发上限 ({}/{}), 跳过",
+                            task_active, task_info.max_concurrent_chunks
                         );
                         consecutive_empty_rounds += 1;
                         if consecutive_empty_rounds >= task_count {
@@ -506,6 +872,25 @@ impl ChunkScheduler {
                         continue;
                     }
 
+                    // 🔥 检查单主机并发限制（可用主机数 × 单主机上限）
+                    if max_chunks_per_host.load(Ordering::SeqCst) > 0 {
+                        let host_capacity = task_info.url_health.lock().await.host_chunk_capacity();
+                        if let Some(capacity) = host_capacity {
+                            if task_active >= capacity {
+                                debug!(
+                                    task_id = %task_id,
+                                    "任务已达主机并发上限 ({}/{}), 跳过",
+                                    task_active, capacity
+                                );
+                                consecutive_empty_rounds += 1;
+                                if consecutive_empty_rounds >= task_count {
+                                    break;
+                                }
+                                continue;
+                            }
+                        }
+                    }
+
                     // 获取下一个待下载的分片索引（跳过正在下载的分片）
                     let next_chunk_index = {
                         let mut manager = task_info.chunk_manager.lock().await;
@@ -532,22 +917,15 @@ impl ChunkScheduler {
                             let new_active = active_chunk_count.load(Ordering::SeqCst);
 
                             debug!(
-                                "调度器选择: 任务 {} 分片 #{} (活跃线程: {}/{}, 本轮已调度: {})",
-                                task_id,
-                                chunk_index,
-                                new_active,
-                                max_threads,
-                                scheduled_count + 1
+                                task_id = %task_id,
+                                "调度器选择: 分片 #{} (活跃线程: {}/{}, 本轮已调度: {})",
+                                chunk_index, new_active, max_threads, scheduled_count + 1
                             );
 
                             Self::spawn_chunk_download(
                                 chunk_index,
                                 task_info.clone(),
-                                active_tasks.clone(),
-                                slot_pool.clone(),
-                                active_chunk_count.clone(),
-                                backup_notification_tx.clone(),
-                                task_completed_tx.clone(),
+                                scheduler.clone(),
                             );
 
                             scheduled_count += 1;
@@ -560,12 +938,12 @@ impl ChunkScheduler {
                             // 检查是否所有分片都完成
                             if task_info.active_chunk_count.load(Ordering::SeqCst) == 0 {
                                 // 所有分片完成，从调度器移除
-                                info!("任务 {} 所有分片完成，从调度器移除", task_id);
+                                info!(task_id = %task_id, event = "chunks_completed", "任务所有分片完成，从调度器移除");
                                 active_tasks.write().await.remove(task_id);
 
                                 // 🔥 修复：取消 cancellation_token，停止速度异常检测和线程停滞检测循环
                                 task_info.cancellation_token.cancel();
-                                debug!("任务 {} 的 cancellation_token 已取消", task_id);
+                                debug!(task_id = %task_id, "任务的 cancellation_token 已取消");
 
                                 // 🔥 异步并发解密：将解密任务 spawn 到独立线程，不阻塞调度循环
                                 let task_id_clone = task_id.to_string();
@@ -574,28 +952,64 @@ impl ChunkScheduler {
                                 let backup_notification_tx_clone = backup_notification_tx.clone();
                                 let waiting_queue_trigger_clone = waiting_queue_trigger.clone();
                                 let decrypt_semaphore_clone = decrypt_semaphore.clone();
+                                let verify_md5_clone = verify_md5.clone();
+                                let completion_webhook_clone = completion_webhook.clone();
+                                let dedup_index_clone = dedup_index.clone();
 
                                 tokio::spawn(async move {
                                     // 🔥 获取解密信号量，限制并发解密数量
                                     let _permit = decrypt_semaphore_clone.acquire().await.unwrap();
-                                    debug!("任务 {} 获取解密信号量，开始解密流程", task_id_clone);
+                                    debug!(task_id = %task_id_clone, "任务获取解密信号量，开始解密流程");
+
+                                    // 🔥 先校验完整性（加密文件校验的是网盘上的密文），
+                                    // 校验通过后将临时文件重命名为最终文件名，再执行解密
+                                    let completion_result = if verify_md5_clone.load(Ordering::SeqCst) {
+                                        Self::verify_md5_if_available(&task_info_clone).await
+                                    } else {
+                                        Ok(false)
+                                    };
+                                    // 🔥 记录是否实际校验通过（转存下载后清理网盘副本依赖此标记）
+                                    let completion_result = match completion_result {
+                                        Ok(verified) => {
+                                            task_info_clone.task.lock().await.md5_verified = verified;
+                                            Ok(())
+                                        }
+                                        Err(e) => Err(e),
+                                    };
+                                    let completion_result = match completion_result {
+                                        Ok(()) => Self::finalize_temp_file(&task_info_clone).await,
+                                        Err(e) => Err(e),
+                                    };
+                                    let completion_result = match completion_result {
+                                        Ok(()) => Self::try_decrypt_if_encrypted(&task_info_clone)
+                                            .await
+                                            .map_err(|e| anyhow::anyhow!("解密失败: {}", e)),
+                                        Err(e) => {
+                                            // 校验或重命名失败，临时文件内容不可信，直接清理
+                                            Self::remove_temp_file(&task_info_clone).await;
+                                            Err(e)
+                                        }
+                                    };
 
-                                    // 执行解密
-                                    let decrypt_result = Self::try_decrypt_if_encrypted(&task_info_clone).await;
+                                    // 🔥 完成的文件记入本地去重索引
+                                    if completion_result.is_ok() {
+                                        Self::record_dedup_entry(&dedup_index_clone, &task_info_clone).await;
+                                    }
 
-                                    // 处理解密结果
+                                    // 处理校验/解密结果
                                     Self::handle_task_completion(
                                         &task_id_clone,
                                         &task_info_clone,
-                                        decrypt_result,
+                                        completion_result,
                                         &task_completed_tx_clone,
                                         &backup_notification_tx_clone,
                                         &waiting_queue_trigger_clone,
+                                        &completion_webhook_clone,
                                     ).await;
 
-                                    debug!("任务 {} 解密流程完成，释放解密信号量", task_id_clone);
+                                    debug!(task_id = %task_id_clone, "任务解密流程完成，释放解密信号量");
                                     // _permit 在这里自动释放
-                                });
+                                }.instrument(download_task_span(task_id)));
                             }
 
                             consecutive_empty_rounds += 1;
@@ -622,26 +1036,46 @@ impl ChunkScheduler {
         });
     }
 
+    /// 🔥 读取已写入磁盘的分片数据计算 CRC32（用于崩溃恢复校验，失败时返回 None）
+    async fn compute_chunk_crc32(task_info: &TaskScheduleInfo, chunk_index: usize) -> Option<u32> {
+        let range = {
+            let manager = task_info.chunk_manager.lock().await;
+            manager.chunks().get(chunk_index)?.range.clone()
+        };
+        let path = task_info.output_path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            crate::persistence::file_region_crc32(&path, range.start, range.end - range.start)
+        })
+        .await;
+        match result {
+            Ok(Ok(crc)) => Some(crc),
+            Ok(Err(e)) => {
+                warn!("分片 #{} 计算 CRC32 失败，跳过记录: {}", chunk_index, e);
+                None
+            }
+            Err(e) => {
+                warn!("分片 #{} 计算 CRC32 任务异常: {}", chunk_index, e);
+                None
+            }
+        }
+    }
+
     /// 启动单个分片的下载任务
     ///
     /// # 参数
     /// * `chunk_index` - 分片索引
     /// * `task_info` - 任务信息
-    /// * `active_tasks` - 活跃任务列表（用于在失败时移除任务）
-    /// * `slot_pool` - 线程槽位池
-    /// * `global_active_count` - 全局活跃分片计数器
-    /// * `backup_notification_tx` - 备份任务统一通知发送器
-    fn spawn_chunk_download(
-        chunk_index: usize,
-        task_info: TaskScheduleInfo,
-        active_tasks: Arc<RwLock<HashMap<String, TaskScheduleInfo>>>,
-        slot_pool: Arc<ChunkSlotPool>,
-        global_active_count: Arc<AtomicUsize>,
-        backup_notification_tx: Arc<RwLock<Option<mpsc::UnboundedSender<BackupTransferNotification>>>>,
-        task_completed_tx: Arc<RwLock<Option<mpsc::UnboundedSender<(String, String, u64, bool)>>>>,
-    ) {
+    /// * `scheduler` - 调度器（提供活跃任务列表、线程槽位池、全局活跃分片计数器及各类通知发送器）
+    fn spawn_chunk_download(chunk_index: usize, task_info: TaskScheduleInfo, scheduler: ChunkScheduler) {
+        // 🔥 分片日志挂到任务 span 下，便于按任务查看最近日志
+        let task_span = download_task_span(&task_info.task_id);
         tokio::spawn(async move {
             let task_id = task_info.task_id.clone();
+            let active_tasks = &scheduler.active_tasks;
+            let slot_pool = &scheduler.slot_pool;
+            let global_active_count = &scheduler.active_chunk_count;
+            let backup_notification_tx = &scheduler.backup_notification_tx;
+            let task_completed_tx = &scheduler.task_completed_tx;
 
             // 从槽位池获取一个槽位ID
             let slot_id = slot_pool.acquire();
@@ -655,30 +1089,29 @@ impl ChunkScheduler {
             let client = task_info.client.read().unwrap().clone();
 
             // 调用 DownloadEngine 的下载方法（传入事件总线和节流器）
-            let result = DownloadEngine::download_chunk_with_retry(
-                chunk_index,
+            let ctx = ChunkDownloadContext {
                 client,
-                &task_info.cookie,
-                task_info.referer.as_deref(),
-                task_info.url_health.clone(),
-                &task_info.output_path,
-                task_info.chunk_manager.clone(),
-                task_info.speed_calc.clone(),
-                task_info.task.clone(),
-                task_info.chunk_size,
-                task_info.total_size,
-                task_info.cancellation_token.clone(),
-                slot_id, // 传递槽位ID
-                task_info.ws_manager.clone(),
-                Some(task_info.progress_throttler.clone()),
-                task_id.clone(),
-                task_info.folder_progress_tx.clone(), // 🔥 文件夹进度通知发送器
-                task_info.backup_notification_tx.clone(), // 🔥 备份任务统一通知发送器
-                Some(task_info.slot_touch_throttler.clone()), // 🔥 任务级共享槽位刷新节流器
-                task_info.max_retries, // 🔥 链接级重试次数（从配置读取）
-                task_info.fallback_mgr.clone(), // 🔥 代理故障回退管理器
-            )
-                .await;
+                cookie: &task_info.cookie,
+                referer: task_info.referer.as_deref(),
+                url_health: task_info.url_health.clone(),
+                output_path: &task_info.output_path,
+                chunk_manager: task_info.chunk_manager.clone(),
+                speed_calc: task_info.speed_calc.clone(),
+                task: task_info.task.clone(),
+                chunk_size: task_info.chunk_size,
+                total_size: task_info.total_size,
+                cancellation_token: task_info.cancellation_token.clone(),
+                ws_manager: task_info.ws_manager.clone(),
+                progress_throttler: Some(task_info.progress_throttler.clone()),
+                task_id: task_id.clone(),
+                folder_progress_tx: task_info.folder_progress_tx.clone(), // 🔥 文件夹进度通知发送器
+                backup_notification_tx: task_info.backup_notification_tx.clone(), // 🔥 备份任务统一通知发送器
+                slot_touch_throttler: Some(task_info.slot_touch_throttler.clone()), // 🔥 任务级共享槽位刷新节流器
+                retry_config: task_info.retry_config.clone(), // 🔥 分片重试配置（从配置读取）
+                fallback_mgr: task_info.fallback_mgr.clone(), // 🔥 代理故障回退管理器
+                global_speed_limiter: Some(task_info.global_speed_limiter.clone()), // 🔥 全局限速器
+            };
+            let result = DownloadEngine::download_chunk_with_retry(ctx, chunk_index, slot_id).await;
 
             // 释放全局活跃分片计数
             global_active_count.fetch_sub(1, Ordering::SeqCst);
@@ -696,11 +1129,33 @@ impl ChunkScheduler {
                 Ok(()) => {
                     // 🔥 分片下载成功，调用持久化回调
                     if let Some(ref pm) = task_info.persistence_manager {
-                        pm.lock().await.on_chunk_completed(&task_id, chunk_index);
+                        let verify_enabled = pm.lock().await.config().verify_chunks_on_recovery;
+                        // 🔥 开启恢复校验时读取刚写入的分片数据计算 CRC32（在持久化锁外进行）
+                        let crc32 = if verify_enabled {
+                            Self::compute_chunk_crc32(&task_info, chunk_index).await
+                        } else {
+                            None
+                        };
+                        match crc32 {
+                            Some(crc) => pm.lock().await.on_chunk_completed_with_crc(
+                                &task_id,
+                                chunk_index,
+                                crc,
+                            ),
+                            None => pm.lock().await.on_chunk_completed(&task_id, chunk_index),
+                        }
                         debug!(
                             "[分片线程{}] 分片 #{} 已记录到持久化管理器",
                             slot_id, chunk_index
                         );
+
+                        // 🔥 CDN 节点变化时写入元数据
+                        let cdn_host = task_info.task.lock().await.take_dirty_cdn_host();
+                        if let Some(cdn_host) = cdn_host {
+                            if let Err(e) = pm.lock().await.update_download_cdn_host(&task_id, cdn_host) {
+                                warn!(task_id = %task_id, "记录任务 CDN 主机失败: {}", e);
+                            }
+                        }
                     }
 
                     // 注意：进度事件已在流式回调中通过节流器发布，此处不再重复发布
@@ -731,6 +1186,8 @@ impl ChunkScheduler {
                             "[分片线程{}] 分片 #{} 下载失败: {}",
                             slot_id, chunk_index, e
                         );
+                        scheduler.rate_limit_cooldown.record_error(&e);
+                        scheduler.adaptive_concurrency.record_error();
 
                         // 取消下载标记 + 递增分片调度级重试计数 + 持久化部分进度
                         let chunk_retries = {
@@ -751,9 +1208,9 @@ impl ChunkScheduler {
                         };
 
                         // 外层调度级重试上限 = 内层链接级重试 * 2
-                        // 内层（engine）每次调度换链接重试 max_retries 次
+                        // 内层（engine）每次调度换链接重试 max_attempts 次
                         // 外层（scheduler）控制分片总共被重新调度几次
-                        let max_schedule_retries = task_info.max_retries * 2;
+                        let max_schedule_retries = task_info.retry_config.max_attempts * 2;
 
                         if chunk_retries < max_schedule_retries {
                             // 分片还有重试机会，留在任务中等待调度器下一轮重新调度
@@ -761,6 +1218,9 @@ impl ChunkScheduler {
                                 "[分片线程{}] 分片 #{} 第 {}/{} 次调度失败，等待重新调度: {}",
                                 slot_id, chunk_index, chunk_retries, max_schedule_retries, e
                             );
+                        } else if scheduler.try_schedule_task_retry(&task_info, &e).await {
+                            // 可恢复错误：任务已从调度器移除，等待 DownloadManager 重新获取链接后调度
+                            warn!(task_id = %task_id, "任务因分片 #{} 重试耗尽，转为任务级重试: {}", chunk_index, e);
                         } else {
                             // 重试耗尽，杀掉整个任务（保持现有逻辑）
                             let (error_msg, group_id, is_backup) = {
@@ -770,6 +1230,9 @@ impl ChunkScheduler {
                                 (err, t.group_id.clone(), t.is_backup)
                             };
 
+                            // 最终失败时删除临时文件，避免遗留大体积 .bdtmp（仅暂停/可续传的取消保留）
+                            Self::remove_temp_file(&task_info).await;
+
                             if !is_backup {
                                 if let Some(ref ws_manager) = task_info.ws_manager {
                                     ws_manager.send_if_subscribed(
@@ -803,58 +1266,255 @@ impl ChunkScheduler {
                             }
 
                             active_tasks.write().await.remove(&task_id);
-                            error!("任务 {} 因分片 #{} 重试耗尽已从调度器移除", task_id, chunk_index);
+                            error!(
+                                task_id = %task_id,
+                                event = "failed",
+                                chunk_index,
+                                "任务因分片重试耗尽已从调度器移除"
+                            );
 
                             // 🔥 通知文件夹管理器：子任务失败
                             if let Some(gid) = group_id.clone() {
                                 let tx_guard = task_completed_tx.read().await;
                                 if let Some(tx) = tx_guard.as_ref() {
-                                    let _ = tx.send((gid, task_id.clone(), task_info.total_size, false));
+                                    let _ = tx.send(SubtaskCompletion {
+                                        group_id: gid,
+                                        task_id: task_id.clone(),
+                                        size: task_info.total_size,
+                                        success: false,
+                                        md5_verified: false,
+                                    });
                                 }
                             }
                         }
                     }
                 }
             }
+        }.instrument(task_span));
+    }
+
+    /// 🔥 启动任务无进度兜底检测
+    ///
+    /// 每 5 秒检查一次调度器中的任务，已下载字节数持续 `max_stall_secs` 没有变化时
+    /// 按 "stalled" 失败：可恢复时转为任务级重试，否则标记失败并发送 Failed 事件。
+    /// 与 CDN 刷新的停滞检测相互独立，链接刷新无法恢复时兜底。
+    /// 随 `scheduler_running` 启停，旧循环尚未退出时不重复启动
+    fn start_stall_watchdog(&self) {
+        if self.stall_watchdog_running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let scheduler = self.clone();
+
+        tokio::spawn(async move {
+            let mut tracker = StallTracker::default();
+            let mut timer = tokio::time::interval(tokio::time::Duration::from_secs(5));
+
+            while scheduler.scheduler_running.load(Ordering::SeqCst) {
+                timer.tick().await;
+
+                let max_stall_secs = scheduler.max_stall_secs.load(Ordering::SeqCst);
+                let tasks: Vec<(String, TaskScheduleInfo)> = {
+                    let active = scheduler.active_tasks.read().await;
+                    active
+                        .iter()
+                        .map(|(task_id, task_info)| (task_id.clone(), task_info.clone()))
+                        .collect()
+                };
+                let task_ids: Vec<String> =
+                    tasks.iter().map(|(task_id, _)| task_id.clone()).collect();
+                tracker.retain(&task_ids);
+                if max_stall_secs == 0 {
+                    continue;
+                }
+
+                let now = std::time::Instant::now();
+                for (task_id, task_info) in tasks {
+                    if task_info.cancellation_token.is_cancelled() {
+                        continue;
+                    }
+                    let downloaded = task_info.task.lock().await.downloaded_size;
+                    let stalled = tracker.observe(&task_id, downloaded, now);
+                    if stalled.as_secs() >= max_stall_secs {
+                        tracker.last_progress.remove(&task_id);
+                        scheduler
+                            .fail_stalled_task(&task_info, stalled.as_secs())
+                            .await;
+                    }
+                }
+            }
+
+            scheduler.stall_watchdog_running.store(false, Ordering::SeqCst);
+            // 退出期间调度器已重新启动时，补启动检测循环
+            if scheduler.scheduler_running.load(Ordering::SeqCst) {
+                scheduler.start_stall_watchdog();
+                return;
+            }
+            info!("任务无进度兜底检测已停止");
         });
     }
 
+    /// 🔥 处理无进度超时的任务（可恢复时转为任务级重试，否则标记失败）
+    async fn fail_stalled_task(&self, task_info: &TaskScheduleInfo, stalled_secs: u64) {
+        let task_id = &task_info.task_id;
+        let error = anyhow::anyhow!("stalled: 任务已 {} 秒没有下载进度", stalled_secs);
+        warn!(task_id = %task_id, event = "stalled", stalled_secs, "任务长时间没有下载进度");
+
+        if self.try_schedule_task_retry(task_info, &error).await {
+            return;
+        }
+
+        task_info.cancellation_token.cancel();
+        if self.active_tasks.write().await.remove(task_id).is_none() {
+            // 已被其他路径移除（暂停、删除、完成）
+            return;
+        }
+
+        Self::handle_task_completion(
+            task_id,
+            task_info,
+            Err(error),
+            &self.task_completed_tx,
+            &self.backup_notification_tx,
+            &self.waiting_queue_trigger,
+            &self.completion_webhook,
+        )
+        .await;
+    }
+
+    /// 🔥 尝试将任务转为任务级重试（分片重试耗尽时调用）
+    ///
+    /// 仅对可恢复错误的普通单文件任务生效（文件夹子任务由文件夹管理器补充，备份任务由备份管理器重试）。
+    /// 重试时取消任务（停止剩余分片和检测循环）、从调度器移除并释放任务槽位，
+    /// 任务状态置为 Pending，由 DownloadManager 延迟后放回等待队列，重新执行 prepare_for_scheduling 获取新链接；
+    /// 已完成分片和分片内进度均已持久化，重新调度后断点续传
+    ///
+    /// # Returns
+    /// - `true` - 已转为任务级重试
+    /// - `false` - 不满足重试条件，调用方按失败处理
+    async fn try_schedule_task_retry(&self, task_info: &TaskScheduleInfo, error: &anyhow::Error) -> bool {
+        let kind = DownloadErrorKind::classify(error);
+        let max_attempts = self.max_task_retries.load(Ordering::SeqCst);
+        if !kind.is_retryable() || max_attempts == 0 {
+            return false;
+        }
+
+        let tx = match self.task_retry_tx.read().await.as_ref() {
+            Some(tx) => tx.clone(),
+            None => return false,
+        };
+
+        let task_id = &task_info.task_id;
+        let error_msg = error.to_string();
+        let attempt = {
+            let mut t = task_info.task.lock().await;
+            if t.group_id.is_some() || t.is_backup || t.task_retry_count >= max_attempts {
+                return false;
+            }
+            t.task_retry_count += 1;
+            t.status = TaskStatus::Pending;
+            t.error = Some(error_msg.clone());
+            t.speed = 0;
+            t.clear_eta();
+            t.slot_id = None;
+            t.task_retry_count
+        };
+
+        task_info.cancellation_token.cancel();
+        self.active_tasks.write().await.remove(task_id);
+
+        // 释放任务槽位（重新排队时重新分配）
+        if let Some(slot_id) = task_info.slot_id {
+            if !task_info.is_borrowed_slot {
+                if let Some(ref slot_pool) = task_info.task_slot_pool {
+                    slot_pool.release_fixed_slot(task_id).await;
+                    info!(task_id = %task_id, "任务转为任务级重试，释放固定槽位 {}", slot_id);
+                }
+            }
+        }
+
+        info!(
+            task_id = %task_id,
+            "🔁 任务第 {}/{} 次任务级重试 (错误类型: {:?}): {}",
+            attempt, max_attempts, kind, error_msg
+        );
+
+        if let Some(ref ws_manager) = task_info.ws_manager {
+            ws_manager.send_if_subscribed(
+                TaskEvent::Download(DownloadEvent::Retrying {
+                    task_id: task_id.clone(),
+                    attempt,
+                    max_attempts,
+                    error: error_msg,
+                }),
+                None,
+            );
+        }
+
+        let _ = tx.send((task_id.clone(), attempt));
+
+        // 槽位已释放，通知等待队列启动其他任务
+        self.trigger_waiting_queue().await;
+
+        true
+    }
+
+    /// 通知等待队列检查并启动等待中的任务
+    pub async fn trigger_waiting_queue(&self) {
+        if let Some(trigger) = self.waiting_queue_trigger.read().await.as_ref() {
+            let _ = trigger.send(());
+        }
+    }
+
     /// 停止调度器
     pub fn stop(&self) {
         self.scheduler_running.store(false, Ordering::SeqCst);
         info!("调度器停止信号已发送");
     }
 
-    /// 🔥 处理任务完成（解密后的后续处理）
+    /// 🔥 处理任务完成（完整性校验、解密后的后续处理）
     ///
-    /// 包括：更新任务状态、发送事件、归档、释放槽位、通知等
+    /// 包括：更新任务状态、发送事件、归档、释放槽位、通知、完成回调等
+    /// `completion_result` 为校验/解密的结果，失败时任务标记为 Failed
     async fn handle_task_completion(
         task_id: &str,
         task_info: &TaskScheduleInfo,
-        decrypt_result: Result<()>,
-        task_completed_tx: &Arc<RwLock<Option<mpsc::UnboundedSender<(String, String, u64, bool)>>>>,
+        completion_result: Result<()>,
+        task_completed_tx: &Arc<RwLock<Option<mpsc::UnboundedSender<SubtaskCompletion>>>>,
         backup_notification_tx: &Arc<RwLock<Option<mpsc::UnboundedSender<BackupTransferNotification>>>>,
         waiting_queue_trigger: &Arc<RwLock<Option<mpsc::UnboundedSender<()>>>>,
+        completion_webhook: &CompletionWebhook,
     ) {
-        // 根据解密结果决定任务状态
-        let (group_id, is_backup, decrypt_error) = {
+        // 根据校验/解密结果决定任务状态
+        let (group_id, is_backup, failure_error, md5_verified) = {
             let mut t = task_info.task.lock().await;
 
-            if let Err(ref e) = decrypt_result {
-                let error_msg = format!("解密失败: {}", e);
+            if let Err(ref e) = completion_result {
+                let error_msg = e.to_string();
                 t.mark_failed(error_msg.clone());
-                error!("任务 {} 解密失败: {}", task_id, e);
-                (t.group_id.clone(), t.is_backup, Some(error_msg))
+                error!(task_id = %task_id, event = "failed", error = %error_msg, "任务完成处理失败");
+                (t.group_id.clone(), t.is_backup, Some(error_msg), false)
             } else {
                 t.mark_completed();
-                (t.group_id.clone(), t.is_backup, None)
+                info!(
+                    task_id = %task_id,
+                    event = "completed",
+                    total_size = t.total_size,
+                    group_id = ?t.group_id,
+                    "任务下载完成"
+                );
+                // 🔥 下载完成回调（后台发送，失败不影响任务状态）
+                completion_webhook.notify(CompletionPayload::from_task(
+                    &t,
+                    chrono::Utc::now().timestamp_millis(),
+                ));
+                (t.group_id.clone(), t.is_backup, None, t.md5_verified)
             }
         };
 
         // 发布任务事件
         if !is_backup {
             if let Some(ref ws_manager) = task_info.ws_manager {
-                if let Some(ref error_msg) = decrypt_error {
+                if let Some(ref error_msg) = failure_error {
                     ws_manager.send_if_subscribed(
                         TaskEvent::Download(DownloadEvent::Failed {
                             task_id: task_id.to_string(),
@@ -879,12 +1539,12 @@ impl ChunkScheduler {
         }
 
         // 处理持久化和清理
-        if decrypt_error.is_none() {
+        if failure_error.is_none() {
             if let Some(ref pm) = task_info.persistence_manager {
                 if let Err(e) = pm.lock().await.on_task_completed(task_id) {
                     error!("归档下载任务到历史数据库失败: {}", e);
                 } else {
-                    debug!("下载任务 {} 已归档到历史数据库", task_id);
+                    debug!(task_id = %task_id, "下载任务已归档到历史数据库");
                 }
             }
             // 🔥 分享直下任务不从内存中移除，由转存管理器清理后移除
@@ -892,16 +1552,16 @@ impl ChunkScheduler {
             if !is_share_direct_download {
                 if let Some(ref manager_tasks) = task_info.manager_tasks {
                     manager_tasks.write().await.remove(task_id);
-                    debug!("下载任务 {} 已从 DownloadManager.tasks 中移除", task_id);
+                    debug!(task_id = %task_id, "下载任务已从 DownloadManager.tasks 中移除");
                 }
             } else {
-                debug!("分享直下任务 {} 完成，保留在内存中等待转存管理器清理", task_id);
+                debug!(task_id = %task_id, "分享直下任务完成，保留在内存中等待转存管理器清理");
             }
         } else {
             if let Some(ref pm) = task_info.persistence_manager {
                 if let Err(e) = pm.lock().await.update_task_error(
                     task_id,
-                    decrypt_error.clone().unwrap_or_default()
+                    failure_error.clone().unwrap_or_default()
                 ) {
                     warn!("更新下载任务错误信息失败: {}", e);
                 }
@@ -913,7 +1573,7 @@ impl ChunkScheduler {
             if !task_info.is_borrowed_slot {
                 if let Some(ref slot_pool) = task_info.task_slot_pool {
                     slot_pool.release_fixed_slot(task_id).await;
-                    info!("任务 {} 完成，释放固定槽位 {}", task_id, slot_id);
+                    info!(task_id = %task_id, "任务完成，释放固定槽位 {}", slot_id);
                 }
             }
         }
@@ -922,8 +1582,14 @@ impl ChunkScheduler {
         if let Some(gid) = group_id.clone() {
             let tx_guard = task_completed_tx.read().await;
             if let Some(tx) = tx_guard.as_ref() {
-                let is_success = decrypt_error.is_none();
-                if let Err(e) = tx.send((gid.clone(), task_id.to_string(), task_info.total_size, is_success)) {
+                let completion = SubtaskCompletion {
+                    group_id: gid.clone(),
+                    task_id: task_id.to_string(),
+                    size: task_info.total_size,
+                    success: failure_error.is_none(),
+                    md5_verified,
+                };
+                if let Err(e) = tx.send(completion) {
                     error!("发送任务完成通知失败: {}", e);
                 }
             }
@@ -933,7 +1599,7 @@ impl ChunkScheduler {
         if is_backup {
             let tx_guard = backup_notification_tx.read().await;
             if let Some(tx) = tx_guard.as_ref() {
-                let notification = if let Some(ref error_msg) = decrypt_error {
+                let notification = if let Some(ref error_msg) = failure_error {
                     BackupTransferNotification::Failed {
                         task_id: task_id.to_string(),
                         task_type: TransferTaskType::Download,
@@ -958,6 +1624,183 @@ impl ChunkScheduler {
         }
     }
 
+    /// 🔥 校验下载文件的 MD5
+    ///
+    /// 将本地文件 MD5 与网盘文件列表返回的 MD5 比对：
+    /// - 任务没有 expected_md5（API 未提供）时跳过校验
+    /// - expected_md5 不是 32 位十六进制字符串时无法比对，同样跳过
+    /// - 校验不一致时发送 IntegrityFailed 事件并返回错误
+    ///
+    /// 返回是否实际执行了校验且通过（跳过校验时为 false）
+    async fn verify_md5_if_available(task_info: &TaskScheduleInfo) -> Result<bool> {
+        // 校验实际写入的文件（重命名前的临时文件）
+        let local_path = task_info.output_path.clone();
+        let (expected_md5, task_id, group_id, is_backup) = {
+            let task = task_info.task.lock().await;
+            (
+                task.expected_md5.clone(),
+                task.id.clone(),
+                task.group_id.clone(),
+                task.is_backup,
+            )
+        };
+
+        let Some(expected) = expected_md5 else {
+            debug!(task_id = %task_id, "任务未提供 MD5，跳过完整性校验");
+            return Ok(false);
+        };
+
+        let expected = expected.to_ascii_lowercase();
+        if expected.len() != 32 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+            warn!(task_id = %task_id, "任务的 MD5 格式无法识别: {}，跳过完整性校验", expected);
+            return Ok(false);
+        }
+
+        info!(task_id = %task_id, "任务开始校验 MD5: {:?}", local_path);
+
+        let path = local_path.clone();
+        let actual = tokio::task::spawn_blocking(move || Self::calculate_file_md5(&path))
+            .await
+            .map_err(|e| anyhow::anyhow!("MD5 计算任务执行失败: {}", e))??;
+
+        if actual == expected {
+            info!(task_id = %task_id, "任务 MD5 校验通过: {}", actual);
+            return Ok(true);
+        }
+
+        error!(task_id = %task_id, "任务 MD5 校验失败: 期望 {}, 实际 {}", expected, actual);
+
+        if !is_backup {
+            if let Some(ref ws_manager) = task_info.ws_manager {
+                ws_manager.send_if_subscribed(
+                    TaskEvent::Download(DownloadEvent::IntegrityFailed {
+                        task_id: task_id.clone(),
+                        expected: expected.clone(),
+                        actual: actual.clone(),
+                        group_id: group_id.clone(),
+                        is_backup,
+                    }),
+                    group_id,
+                );
+            }
+        }
+
+        anyhow::bail!(
+            "文件完整性校验失败: MD5 不一致（期望 {}, 实际 {}），文件可能已损坏",
+            expected,
+            actual
+        )
+    }
+
+    /// 🔥 将下载完成的文件记入本地去重索引
+    ///
+    /// 加密文件解密后的内容与网盘 MD5 不对应，不记录
+    async fn record_dedup_entry(dedup_index: &LocalDedupIndex, task_info: &TaskScheduleInfo) {
+        if !dedup_index.is_enabled() {
+            return;
+        }
+        let (expected_md5, local_path, total_size, is_encrypted) = {
+            let task = task_info.task.lock().await;
+            (
+                task.expected_md5.clone(),
+                task.local_path.clone(),
+                task.total_size,
+                task.is_encrypted,
+            )
+        };
+        if let (Some(md5), false) = (expected_md5, is_encrypted) {
+            dedup_index.record(&md5, &local_path, total_size);
+        }
+    }
+
+    /// 🔥 将下载临时文件（.bdtmp）原子重命名为最终文件名
+    ///
+    /// 旧版本任务直接写入最终路径（temp_path 为 None），无需处理
+    async fn finalize_temp_file(task_info: &TaskScheduleInfo) -> Result<()> {
+        let (temp_path, local_path, task_id) = {
+            let task = task_info.task.lock().await;
+            (task.temp_path.clone(), task.local_path.clone(), task.id.clone())
+        };
+
+        let Some(temp_path) = temp_path else {
+            return Ok(());
+        };
+
+        tokio::fs::rename(&temp_path, &local_path)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "临时文件重命名失败: {:?} -> {:?}: {}",
+                    temp_path,
+                    local_path,
+                    e
+                )
+            })?;
+
+        task_info.task.lock().await.temp_path = None;
+        info!(task_id = %task_id, "任务临时文件已重命名为: {:?}", local_path);
+        Ok(())
+    }
+
+    /// 删除任务的下载临时文件（任务失败时调用）
+    ///
+    /// 🔥 临时文件删除后已完成分片的数据随之丢失，同时清空断点进度（WAL 分片记录和已下载大小），
+    /// 否则重试时会把全部分片当作已完成，得到一个预分配的空文件
+    async fn remove_temp_file(task_info: &TaskScheduleInfo) {
+        Self::discard_temp_file(
+            &task_info.task,
+            &task_info.task_id,
+            task_info.persistence_manager.as_ref(),
+        )
+        .await;
+    }
+
+    /// 删除临时文件并清空断点进度（见 `remove_temp_file`）
+    async fn discard_temp_file(
+        task: &Arc<Mutex<DownloadTask>>,
+        task_id: &str,
+        persistence_manager: Option<&Arc<Mutex<PersistenceManager>>>,
+    ) {
+        let temp_path = task.lock().await.temp_path.clone();
+        let Some(temp_path) = temp_path else {
+            return;
+        };
+
+        match tokio::fs::remove_file(&temp_path).await {
+            Ok(()) => info!("已删除下载临时文件: {:?}", temp_path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("删除下载临时文件失败: {:?}, 错误: {}", temp_path, e),
+        }
+
+        if let Some(pm) = persistence_manager {
+            if let Err(e) = pm.lock().await.reset_download_progress(task_id) {
+                warn!(task_id = %task_id, "重置下载任务断点进度失败: {}", e);
+            }
+        }
+        task.lock().await.downloaded_size = 0;
+    }
+
+    /// 计算本地文件 MD5（同步，需在阻塞线程池中调用）
+    pub(crate) fn calculate_file_md5(path: &std::path::Path) -> Result<String> {
+        use std::io::Read;
+
+        let file = std::fs::File::open(path)
+            .map_err(|e| anyhow::anyhow!("无法打开文件 {:?}: {}", path, e))?;
+        let mut reader = std::io::BufReader::with_capacity(1024 * 1024, file);
+        let mut hasher = md5::Context::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.consume(&buffer[..bytes_read]);
+        }
+
+        Ok(format!("{:x}", hasher.compute()))
+    }
+
     /// 🔥 检测并解密加密文件
     ///
     /// 下载完成后自动检测文件是否为加密文件，如果是则执行解密流程
@@ -999,7 +1842,7 @@ impl ChunkScheduler {
         let is_encrypted = is_encrypted_by_name || is_encrypted_by_content;
 
         if !is_encrypted {
-            debug!("任务 {} 不是加密文件，跳过解密", task_id);
+            debug!(task_id = %task_id, "任务不是加密文件，跳过解密");
             return Ok(());
         }
 
@@ -1011,17 +1854,18 @@ impl ChunkScheduler {
                 match snapshot_mgr.find_by_encrypted_name(&filename) {
                     Ok(Some(snapshot_info)) => {
                         info!(
-                            "任务 {} 从映射表获取 key_version: {}",
-                            task_id, snapshot_info.key_version
+                            task_id = %task_id,
+                            "任务从映射表获取 key_version: {}",
+                            snapshot_info.key_version
                         );
                         Some(snapshot_info.key_version)
                     }
                     Ok(None) => {
-                        debug!("任务 {} 在映射表中未找到加密信息，使用默认密钥", task_id);
+                        debug!(task_id = %task_id, "任务在映射表中未找到加密信息，使用默认密钥");
                         None
                     }
                     Err(e) => {
-                        warn!("任务 {} 查询映射表失败: {}，使用默认密钥", task_id, e);
+                        warn!(task_id = %task_id, "任务查询映射表失败: {}，使用默认密钥", e);
                         None
                     }
                 }
@@ -1033,22 +1877,20 @@ impl ChunkScheduler {
             if let (Some(version), Some(ref config_store)) = (key_version, &task_info.encryption_config_store) {
                 match config_store.get_key_by_version(version) {
                     Ok(Some(key_info)) => {
-                        info!(
-                            "任务 {} 使用 key_version={} 的密钥进行解密",
-                            task_id, version
-                        );
+                        info!(task_id = %task_id, "任务使用 key_version={} 的密钥进行解密", version);
                         match EncryptionService::from_base64_key(&key_info.master_key, key_info.algorithm) {
                             Ok(service) => Arc::new(service),
                             Err(e) => {
                                 warn!(
-                                    "任务 {} 创建 key_version={} 的加密服务失败: {}，回退到默认密钥",
-                                    task_id, version, e
+                                    task_id = %task_id,
+                                    "任务创建 key_version={} 的加密服务失败: {}，回退到默认密钥",
+                                    version, e
                                 );
                                 // 回退到默认的 encryption_service
                                 match &task_info.encryption_service {
                                     Some(service) => service.clone(),
                                     None => {
-                                        warn!("任务 {} 是加密文件但没有配置加密服务，跳过解密", task_id);
+                                        warn!(task_id = %task_id, "任务是加密文件但没有配置加密服务，跳过解密");
                                         return Ok(());
                                     }
                                 }
@@ -1056,29 +1898,27 @@ impl ChunkScheduler {
                         }
                     }
                     Ok(None) => {
-                        warn!(
-                            "任务 {} 未找到 key_version={} 的密钥，回退到默认密钥",
-                            task_id, version
-                        );
+                        warn!(task_id = %task_id, "任务未找到 key_version={} 的密钥，回退到默认密钥", version);
                         // 回退到默认的 encryption_service
                         match &task_info.encryption_service {
                             Some(service) => service.clone(),
                             None => {
-                                warn!("任务 {} 是加密文件但没有配置加密服务，跳过解密", task_id);
+                                warn!(task_id = %task_id, "任务是加密文件但没有配置加密服务，跳过解密");
                                 return Ok(());
                             }
                         }
                     }
                     Err(e) => {
                         warn!(
-                            "任务 {} 获取 key_version={} 的密钥失败: {}，回退到默认密钥",
-                            task_id, version, e
+                            task_id = %task_id,
+                            "任务获取 key_version={} 的密钥失败: {}，回退到默认密钥",
+                            version, e
                         );
                         // 回退到默认的 encryption_service
                         match &task_info.encryption_service {
                             Some(service) => service.clone(),
                             None => {
-                                warn!("任务 {} 是加密文件但没有配置加密服务，跳过解密", task_id);
+                                warn!(task_id = %task_id, "任务是加密文件但没有配置加密服务，跳过解密");
                                 return Ok(());
                             }
                         }
@@ -1089,14 +1929,14 @@ impl ChunkScheduler {
                 match &task_info.encryption_service {
                     Some(service) => service.clone(),
                     None => {
-                        warn!("任务 {} 是加密文件但没有配置加密服务，跳过解密", task_id);
+                        warn!(task_id = %task_id, "任务是加密文件但没有配置加密服务，跳过解密");
                         return Ok(());
                     }
                 }
             }
         };
 
-        info!("🔐 任务 {} 检测到加密文件，开始解密...", task_id);
+        info!(task_id = %task_id, "🔐 任务检测到加密文件，开始解密...");
 
         // 3. 更新任务状态为解密中
         {
@@ -1243,7 +2083,7 @@ impl ChunkScheduler {
             }
         }
 
-        info!("✅ 任务 {} 解密完成，原始大小: {} bytes", task_id, original_size);
+        info!(task_id = %task_id, "✅ 任务解密完成，原始大小: {} bytes", original_size);
 
         Ok(())
     }
@@ -1314,6 +2154,33 @@ impl ChunkScheduler {
         speeds
     }
 
+    /// 🔥 获取所有活跃任务的槽位与分片占用（用于排查任务卡住、借调位异常）
+    pub async fn get_active_task_slots(&self) -> Vec<ActiveTaskSlotInfo> {
+        let tasks = self.active_tasks.read().await;
+        let mut infos = Vec::with_capacity(tasks.len());
+
+        for (task_id, task_info) in tasks.iter() {
+            let (group_id, cdn_host) = {
+                let t = task_info.task.lock().await;
+                (t.group_id.clone(), t.cdn_host.clone())
+            };
+            let speed = task_info.speed_calc.lock().await.speed();
+            infos.push(ActiveTaskSlotInfo {
+                task_id: task_id.clone(),
+                group_id,
+                slot_id: task_info.slot_id,
+                is_borrowed_slot: task_info.is_borrowed_slot,
+                active_chunks: task_info.active_chunk_count.load(Ordering::SeqCst),
+                max_concurrent_chunks: task_info.max_concurrent_chunks,
+                speed,
+                cdn_host,
+            });
+        }
+
+        infos.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+        infos
+    }
+
     /// 🔥 获取所有活跃任务的速度（仅速度值）
     ///
     /// ⚠️ 修复问题4：过滤掉未开始和已完成的任务，避免停滞误判
@@ -1361,3 +2228,69 @@ impl ChunkScheduler {
         self.get_valid_task_speed_values().await.iter().sum()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_task_max_chunks() {
+        let size = 2_000_000_000; // 2GB，自动计算为 10
+        assert_eq!(resolve_task_max_chunks(size, None, 0, 20), 10);
+        // 任务覆盖值优先于全局默认值
+        assert_eq!(resolve_task_max_chunks(size, Some(16), 4, 20), 16);
+        assert_eq!(resolve_task_max_chunks(size, None, 4, 20), 4);
+        assert_eq!(resolve_task_max_chunks(size, Some(0), 4, 20), 4);
+        // 超过会员等级上限时按上限处理
+        assert_eq!(resolve_task_max_chunks(size, Some(32), 0, 20), 20);
+        assert_eq!(resolve_task_max_chunks(size, Some(8), 0, 1), 1);
+    }
+
+    #[tokio::test]
+    async fn test_discard_temp_file_on_final_failure() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let task = DownloadTask::new(
+            1,
+            "/test/file.bin".to_string(),
+            temp_dir.path().join("file.bin"),
+            1024,
+        );
+        let temp_path = task.temp_path.clone().unwrap();
+        std::fs::write(&temp_path, vec![0u8; 1024]).unwrap();
+        let task_id = task.id.clone();
+        let task = Arc::new(Mutex::new(task));
+        task.lock().await.downloaded_size = 512;
+
+        ChunkScheduler::discard_temp_file(&task, &task_id, None).await;
+
+        // 临时文件被删除，已下载大小清零（重试时从头下载）
+        assert!(!temp_path.exists());
+        assert_eq!(task.lock().await.downloaded_size, 0);
+
+        // 临时文件已不存在时重复清理不报错
+        ChunkScheduler::discard_temp_file(&task, &task_id, None).await;
+    }
+
+    #[test]
+    fn test_stall_tracker() {
+        let mut tracker = StallTracker::default();
+        let start = std::time::Instant::now();
+        let secs = std::time::Duration::from_secs;
+
+        assert_eq!(tracker.observe("a", 0, start), std::time::Duration::ZERO);
+        assert_eq!(tracker.observe("a", 0, start + secs(10)), secs(10));
+        // 有进度后重新计时
+        assert_eq!(
+            tracker.observe("a", 100, start + secs(20)),
+            std::time::Duration::ZERO
+        );
+        assert_eq!(tracker.observe("a", 100, start + secs(25)), secs(5));
+
+        tracker.observe("b", 0, start);
+        tracker.retain(&["b".to_string()]);
+        assert_eq!(
+            tracker.observe("a", 100, start + secs(30)),
+            std::time::Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! 下载测速
+//!
+//! 复用 locate 获取下载链接 + Range 请求，下载一小段数据测量当前可达速度：
+//! - 使用与下载任务相同的客户端（代理等设置一致），不受全局/单任务限速影响
+//! - 数据只在内存中计数后丢弃，不写入本地文件，也不创建下载任务
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use reqwest::Client;
+use serde::Serialize;
+
+use super::error::HttpStatusError;
+
+/// 默认测速下载量
+pub const SPEED_TEST_DEFAULT_BYTES: u64 = 8 * 1024 * 1024;
+/// 最大测速下载量
+pub const SPEED_TEST_MAX_BYTES: u64 = 64 * 1024 * 1024;
+/// 单个链接测速超时
+const SPEED_TEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 测速结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SpeedTestResult {
+    /// 测速使用的网盘文件路径
+    pub remote_path: String,
+    /// 实际下载字节数
+    pub bytes: u64,
+    /// 下载耗时（毫秒，从收到响应头开始计算）
+    pub elapsed_ms: u64,
+    /// 平均速度（MB/s）
+    pub speed_mbps: f64,
+    /// 提供数据的 CDN 主机（重定向后的最终地址）
+    pub cdn_host: Option<String>,
+    /// 使用的链接索引（对应 locate 返回的顺序，可作为下载任务的 dlink_prefer）
+    pub url_index: usize,
+    /// locate 返回的链接总数
+    pub url_count: usize,
+}
+
+/// 计算平均速度（MB/s），耗时为 0 时返回 0
+pub fn throughput_mbps(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    bytes as f64 / 1024.0 / 1024.0 / secs
+}
+
+/// 对单个链接发起 Range 请求并读取 `max_bytes` 字节
+///
+/// 返回 (实际下载字节数, 耗时, CDN 主机)
+pub(crate) async fn measure_link(
+    client: &Client,
+    cookie: &str,
+    url: &str,
+    max_bytes: u64,
+) -> Result<(u64, Duration, Option<String>)> {
+    let response = client
+        .get(url)
+        .header("Cookie", cookie)
+        .header("Range", format!("bytes=0-{}", max_bytes.saturating_sub(1)))
+        .timeout(SPEED_TEST_TIMEOUT)
+        .send()
+        .await
+        .context("发送测速请求失败")?;
+
+    let status = response.status();
+    if status != reqwest::StatusCode::PARTIAL_CONTENT && status != reqwest::StatusCode::OK {
+        return Err(HttpStatusError(status.as_u16()).into());
+    }
+
+    let cdn_host = response.url().host_str().map(str::to_string);
+    let start = Instant::now();
+    let mut stream = response.bytes_stream();
+    let mut bytes = 0u64;
+
+    // 服务器忽略 Range 返回 200 时，读满测速量后主动断开
+    while bytes < max_bytes {
+        match stream.next().await {
+            Some(data) => bytes += data.context("读取测速数据失败")?.len() as u64,
+            None => break,
+        }
+    }
+
+    Ok((bytes.min(max_bytes), start.elapsed(), cdn_host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throughput_mbps() {
+        assert_eq!(throughput_mbps(8 * 1024 * 1024, Duration::from_secs(2)), 4.0);
+        assert_eq!(throughput_mbps(512 * 1024, Duration::from_millis(500)), 1.0);
+        assert_eq!(throughput_mbps(1024, Duration::ZERO), 0.0);
+    }
+}
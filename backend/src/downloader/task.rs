@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::common::EtaEstimator;
+
+/// 下载中临时文件的扩展名（完成后原子重命名为最终文件名）
+pub const TEMP_FILE_EXTENSION: &str = "bdtmp";
+
 /// 下载任务状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +25,21 @@ pub enum TaskStatus {
     Failed,
 }
 
+/// 下载任务优先级
+///
+/// 等待队列中优先级高的任务先启动，同一优先级保持先进先出（排序值越小越优先）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadPriority {
+    /// 高优先级
+    High,
+    /// 普通优先级（默认）
+    #[default]
+    Normal,
+    /// 低优先级
+    Low,
+}
+
 /// 下载任务
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadTask {
@@ -39,6 +59,9 @@ pub struct DownloadTask {
     pub status: TaskStatus,
     /// 下载速度 (bytes/s)
     pub speed: u64,
+    /// 预计剩余时间（秒），速度为 0 或总大小未知时为 None
+    #[serde(default)]
+    pub eta_secs: Option<u64>,
     /// 创建时间 (Unix timestamp)
     pub created_at: i64,
     /// 开始时间 (Unix timestamp)
@@ -86,6 +109,10 @@ pub struct DownloadTask {
     #[serde(skip)]
     pub start_retry_count: u32,
 
+    /// 🔥 任务级自动重试次数（分片重试耗尽后重新获取链接重新调度，手动恢复时清零）
+    #[serde(skip)]
+    pub task_retry_count: u32,
+
     // === 🔥 解密相关字段 ===
     /// 是否为加密文件（通过文件名或内容检测）
     #[serde(default)]
@@ -107,10 +134,78 @@ pub struct DownloadTask {
     /// 是否为分享直下任务（完成后不自动清除，由转存管理器清理）
     #[serde(default)]
     pub is_share_direct_download: bool,
+
+    // === 🔥 限速相关字段 ===
+    /// 单任务限速（字节/秒，None 表示不限速）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_limit_bytes_per_sec: Option<u64>,
+
+    // === 🔥 完整性校验相关字段 ===
+    /// 网盘文件列表返回的 MD5（用于下载完成后校验，None 表示不校验）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_md5: Option<String>,
+    /// 下载完成后是否实际执行了 MD5 校验且通过（运行时字段，未校验/格式无法识别均为 false）
+    #[serde(default)]
+    pub md5_verified: bool,
+
+    // === 🔥 批量下载相关字段 ===
+    /// 批量下载批次ID（同一次批量请求创建的任务共享，与文件夹 group_id 无关）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+
+    // === 🔥 临时文件相关字段 ===
+    /// 下载过程中写入的临时文件路径（`{filename}.bdtmp`），完成重命名后为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_path: Option<PathBuf>,
+
+    // === 🔥 分享免转存下载相关字段 ===
+    /// 分享文件直链（share/list 返回的 dlink），设置后跳过 locate 直接使用该链接下载
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_dlink: Option<String>,
+    /// 直链所属的分享来源（刷新直链时重新列出分享目录，含提取码凭据，不返回给前端）
+    #[serde(skip)]
+    pub share_source: Option<crate::transfer::ShareDirectSource>,
+
+    // === 🔥 排队优先级相关字段 ===
+    /// 等待队列优先级（默认普通）
+    #[serde(default)]
+    pub priority: DownloadPriority,
+
+    // === 🔥 首选 CDN 节点相关字段 ===
+    /// 首选下载链接索引（对应 locate 返回的链接顺序，None 表示使用全部可用链接）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlink_prefer: Option<usize>,
+
+    // === 🔥 并发分片数覆盖相关字段 ===
+    /// 单任务最大并发分片数覆盖值（None 表示使用全局默认或按文件大小计算）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chunks_override: Option<usize>,
+
+    // === 🔥 远端文件校验相关字段 ===
+    /// 探测时远端报告的文件总大小（恢复时与上次记录比较）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_size: Option<u64>,
+    /// 探测时远端返回的 ETag（CDN 未返回时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_etag: Option<String>,
+
+    // === 🔥 CDN 节点相关字段 ===
+    /// 实际提供下载的 CDN 主机（最近一次成功下载分片的链接主机，刷新切换节点后更新）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn_host: Option<String>,
+    /// CDN 主机变化后尚未写入元数据
+    #[serde(skip)]
+    pub cdn_host_dirty: bool,
+
+    // === 🔥 剩余时间估算相关字段 ===
+    /// 剩余时间估算器（速度移动平均，不持久化）
+    #[serde(skip)]
+    pub eta_estimator: EtaEstimator,
 }
 
 impl DownloadTask {
     pub fn new(fs_id: u64, remote_path: String, local_path: PathBuf, total_size: u64) -> Self {
+        let temp_path = Some(Self::build_temp_path(&local_path));
         Self {
             id: Uuid::new_v4().to_string(),
             fs_id,
@@ -120,6 +215,7 @@ impl DownloadTask {
             downloaded_size: 0,
             status: TaskStatus::Pending,
             speed: 0,
+            eta_secs: None,
             created_at: chrono::Utc::now().timestamp(),
             started_at: None,
             completed_at: None,
@@ -137,6 +233,7 @@ impl DownloadTask {
             is_backup: false,
             backup_config_id: None,
             start_retry_count: 0,
+            task_retry_count: 0,
             // 解密字段初始化
             is_encrypted: false,
             decrypt_progress: 0.0,
@@ -144,6 +241,63 @@ impl DownloadTask {
             original_filename: None,
             // 分享直下字段初始化
             is_share_direct_download: false,
+            // 限速字段初始化（默认不限速）
+            speed_limit_bytes_per_sec: None,
+            // 完整性校验字段初始化
+            expected_md5: None,
+            md5_verified: false,
+            // 批量下载字段初始化
+            batch_id: None,
+            // 临时文件字段初始化（下载过程中写入 .bdtmp）
+            temp_path,
+            // 分享直链字段初始化（默认走 locate 获取链接）
+            direct_dlink: None,
+            share_source: None,
+            // 排队优先级字段初始化
+            priority: DownloadPriority::Normal,
+            // 首选节点字段初始化（默认使用全部链接）
+            dlink_prefer: None,
+            // 并发分片数覆盖字段初始化（默认按文件大小计算）
+            max_chunks_override: None,
+            // 远端文件校验字段初始化（探测后填充）
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            cdn_host_dirty: false,
+            // 剩余时间估算字段初始化
+            eta_estimator: EtaEstimator::default(),
+        }
+    }
+
+    /// 根据最终路径生成临时文件路径：`{filename}.bdtmp`
+    pub fn build_temp_path(local_path: &Path) -> PathBuf {
+        let mut file_name = local_path
+            .file_name()
+            .map(|n| n.to_os_string())
+            .unwrap_or_default();
+        file_name.push(".");
+        file_name.push(TEMP_FILE_EXTENSION);
+        local_path.with_file_name(file_name)
+    }
+
+    /// 获取下载过程中实际写入的文件路径
+    ///
+    /// 有临时文件时写入临时文件，否则直接写入最终路径（兼容旧版本创建的任务）
+    pub fn write_path(&self) -> PathBuf {
+        self.temp_path
+            .clone()
+            .unwrap_or_else(|| self.local_path.clone())
+    }
+
+    /// 获取未完成任务的残留文件路径（用于删除任务时清理）
+    ///
+    /// 优先返回 `.bdtmp` 临时文件，不存在时回退到最终路径（兼容旧版本任务）
+    pub fn partial_file_path(local_path: &Path) -> PathBuf {
+        let temp_path = Self::build_temp_path(local_path);
+        if temp_path.exists() {
+            temp_path
+        } else {
+            local_path.to_path_buf()
         }
     }
 
@@ -191,13 +345,36 @@ impl DownloadTask {
         (self.downloaded_size as f64 / self.total_size as f64) * 100.0
     }
 
-    /// 估算剩余时间 (秒)
-    pub fn eta(&self) -> Option<u64> {
-        if self.speed == 0 || self.downloaded_size >= self.total_size {
+    /// 按当前速度更新预计剩余时间（速度经移动平均平滑）
+    pub fn update_eta(&mut self) -> Option<u64> {
+        self.eta_secs = self
+            .eta_estimator
+            .update(self.speed, self.downloaded_size, self.total_size);
+        self.eta_secs
+    }
+
+    /// 清除预计剩余时间（任务不再传输时调用）
+    pub fn clear_eta(&mut self) {
+        self.eta_secs = None;
+        self.eta_estimator.reset();
+    }
+
+    /// 记录实际提供下载的 CDN 主机，返回是否发生了变化
+    pub fn set_cdn_host(&mut self, host: String) -> bool {
+        if self.cdn_host.as_deref() == Some(host.as_str()) {
+            return false;
+        }
+        self.cdn_host = Some(host);
+        self.cdn_host_dirty = true;
+        true
+    }
+
+    /// 取出尚未写入元数据的 CDN 主机
+    pub fn take_dirty_cdn_host(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.cdn_host_dirty) {
             return None;
         }
-        let remaining = self.total_size - self.downloaded_size;
-        Some(remaining / self.speed)
+        self.cdn_host.clone()
     }
 
     /// 标记为下载中
@@ -236,17 +413,27 @@ impl DownloadTask {
         self.status = TaskStatus::Completed;
         self.completed_at = Some(chrono::Utc::now().timestamp());
         self.downloaded_size = self.total_size;
+        self.clear_eta();
     }
 
     /// 标记为失败
     pub fn mark_failed(&mut self, error: String) {
         self.status = TaskStatus::Failed;
         self.error = Some(error);
+        self.clear_eta();
     }
 
     /// 标记为暂停
     pub fn mark_paused(&mut self) {
         self.status = TaskStatus::Paused;
+        self.clear_eta();
+    }
+
+    /// 设置单任务限速（KB/s，0 或 None 表示不限速）
+    pub fn set_speed_limit_kbps(&mut self, limit_kbps: Option<u64>) {
+        self.speed_limit_bytes_per_sec = limit_kbps
+            .filter(|&kbps| kbps > 0)
+            .map(|kbps| kbps.saturating_mul(1024));
     }
 }
 
@@ -289,10 +476,12 @@ mod tests {
 
         task.downloaded_size = 200;
         task.speed = 100; // 100 bytes/s
-        assert_eq!(task.eta(), Some(8)); // (1000 - 200) / 100 = 8s
+        assert_eq!(task.update_eta(), Some(8)); // (1000 - 200) / 100 = 8s
+        assert_eq!(task.eta_secs, Some(8));
 
         task.speed = 0;
-        assert_eq!(task.eta(), None); // 速度为0，无法估算
+        assert_eq!(task.update_eta(), None); // 速度为0，无法估算
+        assert_eq!(task.eta_secs, None);
     }
 
     #[test]
@@ -412,6 +601,9 @@ mod tests {
         assert_eq!(task.decrypt_progress, 0.0); // 默认 0.0
         assert!(task.decrypted_path.is_none()); // 默认 None
         assert!(task.original_filename.is_none()); // 默认 None
+        assert!(task.speed_limit_bytes_per_sec.is_none()); // 默认不限速
+        assert!(task.expected_md5.is_none()); // 默认不校验
+        assert_eq!(task.priority, DownloadPriority::Normal); // 默认普通优先级
     }
 
     /// 测试新版本 JSON 数据序列化/反序列化
@@ -443,4 +635,65 @@ mod tests {
         );
         assert_eq!(restored.original_filename, Some("original.txt".to_string()));
     }
+
+    #[test]
+    fn test_set_speed_limit_kbps() {
+        let mut task = DownloadTask::new(
+            12345,
+            "/test/file.txt".to_string(),
+            PathBuf::from("./downloads/file.txt"),
+            1024,
+        );
+
+        task.set_speed_limit_kbps(Some(500));
+        assert_eq!(task.speed_limit_bytes_per_sec, Some(500 * 1024));
+
+        // 0 表示不限速
+        task.set_speed_limit_kbps(Some(0));
+        assert!(task.speed_limit_bytes_per_sec.is_none());
+
+        task.set_speed_limit_kbps(Some(100));
+        task.set_speed_limit_kbps(None);
+        assert!(task.speed_limit_bytes_per_sec.is_none());
+    }
+
+    #[test]
+    fn test_temp_path() {
+        let mut task = DownloadTask::new(
+            12345,
+            "/test/file.txt".to_string(),
+            PathBuf::from("./downloads/file.txt"),
+            1024,
+        );
+
+        // 下载过程中写入 .bdtmp 临时文件
+        assert_eq!(task.temp_path, Some(PathBuf::from("./downloads/file.txt.bdtmp")));
+        assert_eq!(task.write_path(), PathBuf::from("./downloads/file.txt.bdtmp"));
+
+        // 旧版本任务没有临时文件，直接写入最终路径
+        task.temp_path = None;
+        assert_eq!(task.write_path(), PathBuf::from("./downloads/file.txt"));
+
+        // 临时文件不存在时回退到最终路径
+        assert_eq!(
+            DownloadTask::partial_file_path(Path::new("./not-exists/file.txt")),
+            PathBuf::from("./not-exists/file.txt")
+        );
+    }
+
+    #[test]
+    fn test_cdn_host_tracking() {
+        let mut task = DownloadTask::new(1, "/test".to_string(), PathBuf::from("./test"), 1000);
+        assert_eq!(task.take_dirty_cdn_host(), None);
+
+        assert!(task.set_cdn_host("d1.pcs.baidu.com".to_string()));
+        assert!(!task.set_cdn_host("d1.pcs.baidu.com".to_string()));
+        assert_eq!(task.take_dirty_cdn_host().as_deref(), Some("d1.pcs.baidu.com"));
+        assert_eq!(task.take_dirty_cdn_host(), None);
+
+        // 刷新后切换到新节点
+        assert!(task.set_cdn_host("d2.pcs.baidu.com".to_string()));
+        assert_eq!(task.take_dirty_cdn_host().as_deref(), Some("d2.pcs.baidu.com"));
+        assert_eq!(task.cdn_host.as_deref(), Some("d2.pcs.baidu.com"));
+    }
 }
@@ -0,0 +1,179 @@
+//! 下载完成回调（Webhook）
+//!
+//! 配置 `download.completion_webhook_url` 后，任务下载完成时向该地址 POST JSON，
+//! 用于触发媒体库扫描等下游自动化。回调在后台发送，不影响任务状态：
+//! - 单次请求超时 10 秒，失败后重试一次，仍失败只记录日志
+//! - 配置了 `completion_webhook_secret` 时通过 `X-Webhook-Secret` 请求头携带，供接收方校验来源
+
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::downloader::DownloadTask;
+
+/// 单次回调请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// 首次失败后重试前的等待时间
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+/// 共享密钥请求头
+pub const WEBHOOK_SECRET_HEADER: &str = "X-Webhook-Secret";
+
+/// 下载完成回调内容
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CompletionPayload {
+    /// 事件类型，固定为 "download.completed"
+    pub event: &'static str,
+    /// 任务 ID
+    pub task_id: String,
+    /// 网盘路径
+    pub remote_path: String,
+    /// 本地保存路径
+    pub local_path: String,
+    /// 文件大小（字节）
+    pub total_size: u64,
+    /// 所属文件夹任务 ID（单文件任务为 null）
+    pub group_id: Option<String>,
+    /// 是否为自动备份任务
+    pub is_backup: bool,
+    /// 完成时间（Unix 时间戳，毫秒）
+    pub completed_at: i64,
+}
+
+impl CompletionPayload {
+    /// 从已完成的任务构建回调内容
+    pub fn from_task(task: &DownloadTask, completed_at: i64) -> Self {
+        Self {
+            event: "download.completed",
+            task_id: task.id.clone(),
+            remote_path: task.remote_path.clone(),
+            local_path: task.local_path.to_string_lossy().into_owned(),
+            total_size: task.total_size,
+            group_id: task.group_id.clone(),
+            is_backup: task.is_backup,
+            completed_at,
+        }
+    }
+}
+
+/// 回调目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WebhookTarget {
+    url: String,
+    secret: Option<String>,
+}
+
+/// 下载完成回调发送器（动态可调整，未配置地址时不发送）
+#[derive(Debug, Default)]
+pub struct CompletionWebhook {
+    target: RwLock<Option<WebhookTarget>>,
+    client: Client,
+}
+
+impl CompletionWebhook {
+    /// 创建回调发送器（默认未启用）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 动态更新回调地址和共享密钥（地址为空时关闭回调）
+    pub fn update_config(&self, url: Option<String>, secret: Option<String>) {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let target = non_empty(url).map(|url| WebhookTarget {
+            url,
+            secret: non_empty(secret),
+        });
+        *self.target.write() = target;
+    }
+
+    /// 是否已配置回调地址
+    pub fn is_enabled(&self) -> bool {
+        self.target.read().is_some()
+    }
+
+    /// 后台发送下载完成回调（未配置地址时直接返回）
+    pub fn notify(&self, payload: CompletionPayload) {
+        let Some(target) = self.target.read().clone() else {
+            return;
+        };
+        let client = self.client.clone();
+
+        tokio::spawn(async move {
+            if let Err(first) = Self::send(&client, &target, &payload).await {
+                debug!("下载完成回调失败，{} 秒后重试: {}", WEBHOOK_RETRY_DELAY.as_secs(), first);
+                tokio::time::sleep(WEBHOOK_RETRY_DELAY).await;
+                if let Err(e) = Self::send(&client, &target, &payload).await {
+                    warn!("下载完成回调发送失败: 任务={}, 地址={}, 错误={}", payload.task_id, target.url, e);
+                    return;
+                }
+            }
+            debug!("下载完成回调已发送: 任务={}", payload.task_id);
+        });
+    }
+
+    /// 发送一次回调，非 2xx 响应视为失败
+    async fn send(client: &Client, target: &WebhookTarget, payload: &CompletionPayload) -> anyhow::Result<()> {
+        let mut request = client.post(&target.url).timeout(WEBHOOK_TIMEOUT).json(payload);
+        if let Some(ref secret) = target.secret {
+            request = request.header(WEBHOOK_SECRET_HEADER, secret);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("HTTP {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_update_config() {
+        let webhook = CompletionWebhook::new();
+        assert!(!webhook.is_enabled());
+
+        webhook.update_config(Some(" http://127.0.0.1:8080/hook ".to_string()), Some("  ".to_string()));
+        assert_eq!(
+            *webhook.target.read(),
+            Some(WebhookTarget {
+                url: "http://127.0.0.1:8080/hook".to_string(),
+                secret: None,
+            })
+        );
+
+        // 地址为空时关闭回调
+        webhook.update_config(Some(String::new()), Some("secret".to_string()));
+        assert!(!webhook.is_enabled());
+    }
+
+    #[test]
+    fn test_payload_from_task() {
+        let mut task = DownloadTask::new(
+            1,
+            "/电影/a.mkv".to_string(),
+            PathBuf::from("/downloads/a.mkv"),
+            1024,
+        );
+        task.group_id = Some("group-1".to_string());
+
+        let payload = CompletionPayload::from_task(&task, 1_700_000_000_000);
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "download.completed");
+        assert_eq!(json["task_id"], task.id);
+        assert_eq!(json["local_path"], "/downloads/a.mkv");
+        assert_eq!(json["total_size"], 1024);
+        assert_eq!(json["group_id"], "group-1");
+        assert_eq!(json["is_backup"], false);
+    }
+}
@@ -62,6 +62,14 @@ impl FilesystemService {
             .filter_map(|entry| self.to_file_entry(&entry).ok())
             .collect();
 
+        // 挂载点目录附带所在卷的空间信息
+        let disks = DiskTable::load();
+        for entry in entries.iter_mut() {
+            if entry.entry_type == EntryType::Directory {
+                entry.disk_space = disks.mount_space(Path::new(&entry.path));
+            }
+        }
+
         let total = entries.len();
 
         // 排序
@@ -106,6 +114,7 @@ impl FilesystemService {
             page: req.page,
             page_size: req.page_size,
             has_more: offset + req.page_size < total,
+            disk_space: disks.space_for(&path),
         })
     }
 
@@ -249,24 +258,115 @@ impl FilesystemService {
             updated_at: modified_at,
             icon,
             path: normalized.to_string_lossy().to_string(),
+            disk_space: None,
         })
     }
 
     /// 获取根目录列表（Windows 驱动器列表 / Unix 根目录 / 白名单目录）
     pub fn get_roots(&self) -> Result<Vec<FileEntry>, FsError> {
-        if self.guard.has_allowed_paths() {
-            return self.get_allowed_roots();
-        }
+        let mut roots = if self.guard.has_allowed_paths() {
+            self.get_allowed_roots()?
+        } else {
+            #[cfg(target_os = "windows")]
+            {
+                self.get_windows_drives()?
+            }
 
-        #[cfg(target_os = "windows")]
-        {
-            self.get_windows_drives()
+            #[cfg(not(target_os = "windows"))]
+            {
+                self.get_unix_roots()?
+            }
+        };
+
+        let disks = DiskTable::load();
+        for root in roots.iter_mut() {
+            root.disk_space = disks.space_for(Path::new(&root.path));
         }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            self.get_unix_roots()
+        Ok(roots)
+    }
+
+    /// 获取驱动器/挂载点列表及其空间信息
+    ///
+    /// 启用白名单时只返回白名单根目录（空间为其所在卷）；Windows 返回存在的盘符；
+    /// Unix 返回根目录（Docker 环境除外）和 MountDetector 检测到的用户挂载点。
+    /// 无法获取空间信息的路径会被跳过
+    pub fn get_drives(&self) -> Result<DrivesResponse, FsError> {
+        let disks = DiskTable::load();
+
+        let candidates = if self.guard.has_allowed_paths() {
+            self.guard
+                .resolve_allowed_roots()?
+                .into_iter()
+                .map(|path| DriveCandidate::named(path.clone(), path.to_string_lossy()))
+                .collect()
+        } else {
+            Self::system_drive_candidates(&disks)
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        let drives = candidates
+            .into_iter()
+            .filter(|candidate| seen.insert(candidate.path.clone()))
+            .filter_map(|candidate| {
+                let space = disks.space_for(&candidate.path)?;
+                Some(DriveInfo {
+                    path: candidate.path.to_string_lossy().to_string(),
+                    name: candidate.name,
+                    fs_type: candidate.fs_type,
+                    device: candidate.device,
+                    total_bytes: space.total_bytes,
+                    free_bytes: space.free_bytes,
+                })
+            })
+            .collect();
+
+        Ok(DrivesResponse { drives })
+    }
+
+    /// Windows: 存在的盘符根目录
+    #[cfg(target_os = "windows")]
+    fn system_drive_candidates(_disks: &DiskTable) -> Vec<DriveCandidate> {
+        ('A'..='Z')
+            .map(|letter| (letter, PathBuf::from(format!("{}:\\", letter))))
+            .filter(|(_, path)| path.exists())
+            .map(|(letter, path)| DriveCandidate::named(path, format!("本地磁盘 ({}:)", letter)))
+            .collect()
+    }
+
+    /// Unix: 根目录（Docker 环境除外）和用户挂载点
+    ///
+    /// 非 Linux 系统无法读取 /proc/mounts，改用系统磁盘列表
+    #[cfg(not(target_os = "windows"))]
+    #[cfg_attr(target_os = "linux", allow(unused_variables))]
+    fn system_drive_candidates(disks: &DiskTable) -> Vec<DriveCandidate> {
+        let mut candidates = Vec::new();
+
+        if !EnvDetector::get_env_info().is_docker {
+            candidates.push(DriveCandidate::named(PathBuf::from("/"), "/"));
         }
+
+        let mut mounts: Vec<DriveCandidate> = MountDetector::get_mount_points()
+            .into_iter()
+            .map(|mount| DriveCandidate {
+                path: PathBuf::from(&mount.path),
+                name: mount.path,
+                fs_type: Some(mount.fs_type),
+                device: Some(mount.device),
+            })
+            .collect();
+
+        #[cfg(not(target_os = "linux"))]
+        mounts.extend(disks.volumes.iter().map(|volume| DriveCandidate {
+            path: volume.mount_point.clone(),
+            name: volume.mount_point.to_string_lossy().to_string(),
+            fs_type: volume.fs_type.clone(),
+            device: None,
+        }));
+
+        mounts.sort_by(|a, b| a.path.cmp(&b.path));
+        candidates.extend(mounts.into_iter().filter(|mount| mount.path.exists()));
+        candidates
     }
 
     /// 获取根目录列表及默认目录路径
@@ -341,6 +441,7 @@ impl FilesystemService {
                     updated_at: String::new(),
                     icon: Some("drive".to_string()),
                     path: drive_path,
+                    disk_space: None,
                 });
             }
         }
@@ -391,6 +492,7 @@ impl FilesystemService {
             updated_at: modified_at,
             icon: Some("drive".to_string()),
             path: "/".to_string(),
+            disk_space: None,
         }])
     }
 
@@ -468,6 +570,7 @@ impl FilesystemService {
             updated_at: modified_at,
             icon: Some("drive".to_string()),
             path: path.to_string_lossy().to_string(),
+            disk_space: None,
         })
     }
 
@@ -522,6 +625,7 @@ impl FilesystemService {
             updated_at: modified_at,
             icon,
             path: path.to_string_lossy().to_string(),
+            disk_space: None,
         })
     }
 
@@ -576,6 +680,78 @@ impl FilesystemService {
     }
 }
 
+/// 驱动器候选路径（尚未查询空间）
+struct DriveCandidate {
+    path: PathBuf,
+    name: String,
+    fs_type: Option<String>,
+    device: Option<String>,
+}
+
+impl DriveCandidate {
+    fn named(path: PathBuf, name: impl Into<String>) -> Self {
+        Self {
+            path,
+            name: name.into(),
+            fs_type: None,
+            device: None,
+        }
+    }
+}
+
+/// 系统卷信息
+struct DiskVolume {
+    mount_point: PathBuf,
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    fs_type: Option<String>,
+    space: DiskSpace,
+}
+
+/// 卷空间快照（单次请求内复用，避免重复刷新磁盘列表）
+struct DiskTable {
+    volumes: Vec<DiskVolume>,
+}
+
+impl DiskTable {
+    fn load() -> Self {
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        let volumes = disks
+            .list()
+            .iter()
+            .map(|disk| DiskVolume {
+                mount_point: disk.mount_point().to_path_buf(),
+                fs_type: Some(disk.file_system().to_string_lossy().to_string())
+                    .filter(|fs_type| !fs_type.is_empty()),
+                space: DiskSpace {
+                    total_bytes: disk.total_space(),
+                    free_bytes: disk.available_space(),
+                },
+            })
+            .collect();
+        Self { volumes }
+    }
+
+    /// 路径所在卷的空间（挂载点最长匹配，与 PathValidator::available_space 一致）
+    fn space_for(&self, path: &Path) -> Option<DiskSpace> {
+        // 去掉 Windows `\\?\` 前缀，否则无法匹配盘符挂载点
+        let path = dunce::simplified(path);
+        self.volumes
+            .iter()
+            .filter(|volume| path.starts_with(&volume.mount_point))
+            .max_by_key(|volume| volume.mount_point.as_os_str().len())
+            .map(|volume| volume.space)
+    }
+
+    /// 路径本身是挂载点时返回其空间
+    fn mount_space(&self, path: &Path) -> Option<DiskSpace> {
+        let path = dunce::simplified(path);
+        self.volumes
+            .iter()
+            .find(|volume| volume.mount_point == path)
+            .map(|volume| volume.space)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -750,4 +926,48 @@ mod tests {
             alpha.canonicalize().unwrap().to_string_lossy()
         );
     }
+
+    fn volume(mount_point: &str, total_bytes: u64, free_bytes: u64) -> DiskVolume {
+        DiskVolume {
+            mount_point: PathBuf::from(mount_point),
+            fs_type: None,
+            space: DiskSpace {
+                total_bytes,
+                free_bytes,
+            },
+        }
+    }
+
+    #[test]
+    fn test_disk_table_longest_mount_match() {
+        let disks = DiskTable {
+            volumes: vec![volume("/", 100, 10), volume("/mnt/data", 1000, 500)],
+        };
+
+        assert_eq!(disks.space_for(Path::new("/mnt/data/movies")).unwrap().free_bytes, 500);
+        assert_eq!(disks.space_for(Path::new("/mnt/database")).unwrap().free_bytes, 10);
+        assert_eq!(disks.mount_space(Path::new("/mnt/data")).unwrap().total_bytes, 1000);
+        assert!(disks.mount_space(Path::new("/mnt/data/movies")).is_none());
+        assert!(DiskTable { volumes: vec![] }.space_for(Path::new("/")).is_none());
+    }
+
+    #[test]
+    fn test_get_drives_limited_to_allowed_paths() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("downloads");
+        std::fs::create_dir_all(&root).unwrap();
+
+        let service = FilesystemService::new(FilesystemConfig {
+            allowed_paths: vec![root.to_string_lossy().to_string()],
+            ..Default::default()
+        });
+
+        // 沙箱环境可能无法获取卷信息，只校验返回的路径不超出白名单
+        let drives = service.get_drives().unwrap().drives;
+        assert!(drives.len() <= 1);
+        for drive in drives {
+            assert_eq!(drive.path, root.canonicalize().unwrap().to_string_lossy());
+            assert!(drive.free_bytes <= drive.total_bytes);
+        }
+    }
 }
@@ -124,6 +124,48 @@ pub struct FileEntry {
     pub icon: Option<String>,
     /// 完整路径
     pub path: String,
+    /// 所在卷的空间信息（仅驱动器/挂载点目录）
+    #[serde(rename = "diskSpace", skip_serializing_if = "Option::is_none")]
+    pub disk_space: Option<DiskSpace>,
+}
+
+/// 卷空间信息
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct DiskSpace {
+    /// 总容量（字节）
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    /// 可用空间（字节）
+    #[serde(rename = "freeBytes")]
+    pub free_bytes: u64,
+}
+
+/// 驱动器/挂载点信息
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveInfo {
+    /// 挂载路径（Windows 为盘符根目录，如 "C:\\"）
+    pub path: String,
+    /// 显示名称
+    pub name: String,
+    /// 文件系统类型
+    #[serde(rename = "fsType", skip_serializing_if = "Option::is_none")]
+    pub fs_type: Option<String>,
+    /// 设备名称
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// 总容量（字节）
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    /// 可用空间（字节）
+    #[serde(rename = "freeBytes")]
+    pub free_bytes: u64,
+}
+
+/// 驱动器列表响应
+#[derive(Debug, Serialize)]
+pub struct DrivesResponse {
+    /// 驱动器/挂载点列表
+    pub drives: Vec<DriveInfo>,
 }
 
 /// 排序字段
@@ -190,6 +232,9 @@ pub struct ListResponse {
     /// 是否还有更多
     #[serde(rename = "hasMore")]
     pub has_more: bool,
+    /// 当前目录所在卷的空间信息（无法获取时省略）
+    #[serde(rename = "diskSpace", skip_serializing_if = "Option::is_none")]
+    pub disk_space: Option<DiskSpace>,
 }
 
 /// 根目录列表响应
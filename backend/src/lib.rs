@@ -78,4 +78,4 @@ pub use config::PersistenceConfig;
 pub use persistence::{TaskMetadata, TaskPersistenceInfo, TaskType, WalRecord};
 
 // 🔥 导出任务槽池相关类型
-pub use task_slot_pool::{TaskSlot, TaskSlotPool, TaskSlotType, TaskPriority, SlotTouchThrottler, STALE_WARNING_THRESHOLD, STALE_RELEASE_THRESHOLD, CLEANUP_INTERVAL};
+pub use task_slot_pool::{SlotAllocation, SlotOccupancy, SlotPoolSnapshot, TaskSlot, TaskSlotPool, TaskSlotType, TaskPriority, SlotTouchThrottler, STALE_WARNING_THRESHOLD, STALE_RELEASE_THRESHOLD, CLEANUP_INTERVAL};
@@ -1,22 +1,181 @@
 //! 日志系统配置
 //!
-//! 支持控制台输出和文件持久化，按文件大小和启动时间滚动，自动清理过期日志
+//! 支持控制台输出和文件持久化，按启动时间和日期生成日志文件，超过大小时滚动为 `.log.1`、`.log.2`...
+//! （数字越大越旧）；启动时、每天以及每次滚动时按当前的 retention_days 清理过期日志
+//! 输出格式支持文本（默认）和 JSON（每行一个对象，便于日志系统采集）
+//! 带 `task_id` 字段（或位于带 `task_id` 的 span 内）的日志额外保存在内存中，供按任务查看最近日志
 
-use crate::config::LogConfig;
+use crate::config::{LogConfig, LogFormat};
 use chrono::{Local, NaiveDate};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use tracing::info;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{info, Event, Subscriber};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{
-    fmt::{self, time::ChronoLocal},
-    layer::SubscriberExt,
+    fmt::{self, time::ChronoLocal, MakeWriter},
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    reload,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Layer, Registry,
 };
 
+/// 过期日志清理间隔
+const LOG_CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 日志保留天数（配置热更新时动态调整，清理时读取当前值）
+static LOG_RETENTION_DAYS: AtomicU32 = AtomicU32::new(7);
+
+/// 日志级别过滤器的重载句柄（用于配置热更新时动态调整日志级别）
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 每个任务保留的最近日志行数
+pub const TASK_LOG_CAPACITY: usize = 200;
+
+/// 最多同时保留日志的任务数（超出后淘汰最早出现的任务）
+const MAX_TRACKED_TASKS: usize = 256;
+
+/// 按任务保存的最近日志
+static TASK_LOGS: OnceLock<Mutex<TaskLogStore>> = OnceLock::new();
+
+/// 任务日志环形缓冲区
+#[derive(Default)]
+struct TaskLogStore {
+    /// task_id -> 最近日志行（旧 -> 新）
+    logs: HashMap<String, VecDeque<String>>,
+    /// task_id 首次出现的顺序，用于淘汰
+    order: VecDeque<String>,
+}
+
+impl TaskLogStore {
+    fn push(&mut self, task_id: String, line: String) {
+        if !self.logs.contains_key(&task_id) {
+            if self.order.len() >= MAX_TRACKED_TASKS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.logs.remove(&oldest);
+                }
+            }
+            self.order.push_back(task_id.clone());
+        }
+
+        let lines = self.logs.entry(task_id).or_default();
+        if lines.len() >= TASK_LOG_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+fn task_logs() -> &'static Mutex<TaskLogStore> {
+    TASK_LOGS.get_or_init(Default::default)
+}
+
+/// 获取任务最近的 `limit` 行日志（按时间先后排列）
+pub fn tail_task_logs(task_id: &str, limit: usize) -> Vec<String> {
+    let store = task_logs().lock().unwrap_or_else(PoisonError::into_inner);
+    store
+        .logs
+        .get(task_id)
+        .map(|lines| {
+            let skip = lines.len().saturating_sub(limit);
+            lines.iter().skip(skip).cloned().collect()
+        })
+        .unwrap_or_default()
+}
+
+/// span 上记录的 task_id
+struct SpanTaskId(String);
+
+/// 提取 task_id、message 和其余字段
+#[derive(Default)]
+struct TaskLogVisitor {
+    task_id: Option<String>,
+    message: String,
+    fields: String,
+}
+
+impl Visit for TaskLogVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "task_id" => self.task_id = Some(value.to_string()),
+            "message" => self.message = value.to_string(),
+            name => {
+                let _ = write!(self.fields, " {}={}", name, value);
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "task_id" => {
+                self.task_id = Some(format!("{:?}", value).trim_matches('"').to_string())
+            }
+            "message" => self.message = format!("{:?}", value),
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+/// 按任务收集日志的 Layer
+///
+/// 事件自身带 `task_id` 字段时直接使用，否则取最近一层带 `task_id` 的 span。
+/// 与其他 Layer 共用全局日志级别过滤：只收集通过过滤的事件，携带 `task_id` 的 span
+/// 需以不低于过滤级别的级别创建（调度器使用 ERROR 级别 span）
+pub struct TaskLogLayer;
+
+impl<S> Layer<S> for TaskLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = TaskLogVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(task_id), Some(span)) = (visitor.task_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanTaskId(task_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = TaskLogVisitor::default();
+        event.record(&mut visitor);
+
+        let task_id = visitor.task_id.or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| {
+                span.extensions()
+                    .get::<SpanTaskId>()
+                    .map(|task_id| task_id.0.clone())
+            })
+        });
+        let Some(task_id) = task_id else {
+            return;
+        };
+
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:>5} {}: {}{}",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            metadata.level(),
+            metadata.target(),
+            visitor.message,
+            visitor.fields
+        );
+        task_logs()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(task_id, line);
+    }
+}
+
 /// 日志文件管理器（内部状态）
 ///
 /// 负责管理日志文件的创建、滚动和写入
@@ -29,8 +188,6 @@ struct LogFileManagerInner {
     log_dir: PathBuf,
     /// 当前文件句柄
     current_file: Option<File>,
-    /// 当前文件序号（0 表示基础文件，1、2、3... 表示滚动文件）
-    current_index: u32,
     /// 单个文件最大大小（字节）
     max_file_size: u64,
     /// 当前文件已写入的字节数
@@ -49,7 +206,6 @@ impl LogFileManagerInner {
             current_timestamp,
             log_dir,
             current_file: None,
-            current_index: 0,
             max_file_size,
             current_size: 0,
         };
@@ -60,27 +216,30 @@ impl LogFileManagerInner {
         Ok(manager)
     }
 
-    /// 生成日志文件路径
-    fn generate_file_path(&self, index: u32) -> PathBuf {
-        let filename = if index == 0 {
-            format!("baidu-pcs-rust.{}.log", self.current_timestamp)
-        } else {
-            format!("baidu-pcs-rust.{}_{}.log", self.current_timestamp, index)
-        };
-        self.log_dir.join(filename)
+    /// 当前正在写入的日志文件路径
+    fn base_file_path(&self) -> PathBuf {
+        self.log_dir.join(format!("baidu-pcs-rust.{}.log", self.current_timestamp))
+    }
+
+    /// 第 `index` 个滚动文件路径（`.log.1` 最新）
+    fn rotated_file_path(&self, index: u32) -> PathBuf {
+        let mut path = self.base_file_path().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
     }
 
     /// 创建新的日志文件
     fn create_new_file(&mut self) -> io::Result<()> {
-        let file_path = self.generate_file_path(self.current_index);
+        let file_path = self.base_file_path();
 
         let file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&file_path)?;
 
+        // 同名文件已存在时追加写入，从现有大小开始计数
+        self.current_size = file.metadata().map(|m| m.len()).unwrap_or(0);
         self.current_file = Some(file);
-        self.current_size = 0;
 
         Ok(())
     }
@@ -107,28 +266,33 @@ impl LogFileManagerInner {
         self.current_date = now.date_naive();
         self.current_timestamp = now.format("%Y-%m-%d-%H%M%S").to_string();
 
-        // 重置文件序号
-        self.current_index = 0;
-
         // 创建新文件
         self.create_new_file()?;
 
+        cleanup_old_logs(&self.log_dir, log_retention_days());
         Ok(())
     }
 
-    /// 按大小滚动到新文件
+    /// 按大小滚动：已有滚动文件序号依次加一，当前文件改名为 `.log.1`，再重新创建当前文件
     fn rotate_by_size(&mut self) -> io::Result<()> {
         // 关闭当前文件
         if let Some(mut file) = self.current_file.take() {
             file.flush()?;
         }
 
-        // 增加文件序号
-        self.current_index += 1;
+        let mut highest = 0;
+        while self.rotated_file_path(highest + 1).exists() {
+            highest += 1;
+        }
+        for index in (1..=highest).rev() {
+            fs::rename(self.rotated_file_path(index), self.rotated_file_path(index + 1))?;
+        }
+        fs::rename(self.base_file_path(), self.rotated_file_path(1))?;
 
         // 创建新文件
         self.create_new_file()?;
 
+        cleanup_old_logs(&self.log_dir, log_retention_days());
         Ok(())
     }
 
@@ -138,16 +302,16 @@ impl LogFileManagerInner {
         if self.should_rotate_by_date() {
             self.rotate_by_date()?;
         }
-        // 再检查大小滚动
-        else if self.should_rotate_by_size(buf.len()) {
+        // 再检查大小滚动（当前文件为空时不滚动，避免单条超大日志反复创建空文件）
+        else if self.current_size > 0 && self.should_rotate_by_size(buf.len()) {
             self.rotate_by_size()?;
         }
 
-        // 写入数据
+        // 整条写入，保证一条日志不会被拆分到两个文件
         if let Some(file) = &mut self.current_file {
-            let written = file.write(buf)?;
-            self.current_size += written as u64;
-            Ok(written)
+            file.write_all(buf)?;
+            self.current_size += buf.len() as u64;
+            Ok(buf.len())
         } else {
             Err(io::Error::new(io::ErrorKind::Other, "日志文件未打开"))
         }
@@ -179,14 +343,15 @@ impl LogFileManager {
     }
 }
 
+/// 多个写入方共享同一把锁，滚动判断和写入在锁内完成，并发写入时不会交错或重复滚动
 impl Write for LogFileManager {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let mut inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
         inner.write_data(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        let mut inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
         inner.flush_file()
     }
 }
@@ -199,6 +364,35 @@ impl Clone for LogFileManager {
     }
 }
 
+/// 构建日志输出层
+///
+/// JSON 格式下事件字段（task_id、event 等）展开为顶层字段，并附带当前 span，时间使用 RFC 3339
+fn build_fmt_layer<S, W>(format: LogFormat, ansi: bool, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_target(true)
+        .with_level(true)
+        .with_writer(writer);
+
+    match format {
+        LogFormat::Text => layer
+            .with_timer(ChronoLocal::new("%Y-%m-%d %H:%M:%S%.3f".to_string()))
+            .with_ansi(ansi)
+            .boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_timer(ChronoLocal::rfc_3339())
+            .with_ansi(false)
+            .boxed(),
+    }
+}
+
 /// 日志系统守卫
 /// 必须保持存活，否则日志写入线程会终止
 pub struct LogGuard {
@@ -216,13 +410,12 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
     // 创建环境过滤器
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(&config.level));
+    // 🔥 包装为可重载层，支持运行时调整日志级别
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+    let _ = LOG_FILTER_HANDLE.set(filter_handle);
 
     // 控制台输出层
-    let console_layer = fmt::layer()
-        .with_target(true)
-        .with_level(true)
-        .with_timer(ChronoLocal::new("%Y-%m-%d %H:%M:%S%.3f".to_string()))
-        .with_ansi(true);
+    let console_layer = build_fmt_layer(config.format, true, io::stdout);
 
     if config.enabled {
         // 确保日志目录存在
@@ -232,13 +425,14 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
             tracing_subscriber::registry()
                 .with(env_filter)
                 .with(console_layer)
+                .with(TaskLogLayer)
                 .init();
 
             return LogGuard { _file_guard: None };
         }
 
         // 创建自定义日志文件管理器
-        // 文件名格式: baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log
+        // 文件名格式: baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log，滚动文件追加 .1、.2...
         let file_manager = match LogFileManager::new(
             config.log_dir.clone(),
             config.max_file_size,
@@ -249,6 +443,7 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
                 tracing_subscriber::registry()
                     .with(env_filter)
                     .with(console_layer)
+                    .with(TaskLogLayer)
                     .init();
                 return LogGuard { _file_guard: None };
             }
@@ -258,27 +453,24 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
         let (non_blocking, file_guard) = tracing_appender::non_blocking(file_manager);
 
         // 文件输出层（不带 ANSI 颜色）
-        let file_layer = fmt::layer()
-            .with_target(true)
-            .with_level(true)
-            .with_timer(ChronoLocal::new("%Y-%m-%d %H:%M:%S%.3f".to_string()))
-            .with_ansi(false)
-            .with_writer(non_blocking);
+        let file_layer = build_fmt_layer(config.format, false, non_blocking);
 
         // 初始化订阅器
         tracing_subscriber::registry()
             .with(env_filter)
             .with(console_layer)
             .with(file_layer)
+            .with(TaskLogLayer)
             .init();
 
         info!(
-            "日志系统初始化完成: 目录={:?}, 保留天数={}, 级别={}, 单文件最大={:.1}MB",
-            config.log_dir, config.retention_days, config.level, config.max_file_size as f64 / 1024.0 / 1024.0
+            "日志系统初始化完成: 目录={:?}, 保留天数={}, 级别={}, 格式={:?}, 单文件最大={:.1}MB",
+            config.log_dir, config.retention_days, config.level, config.format, config.max_file_size as f64 / 1024.0 / 1024.0
         );
 
-        // 启动过期日志清理
-        cleanup_old_logs(&config.log_dir, config.retention_days);
+        // 启动过期日志清理（立即执行一次，之后每天执行）
+        update_log_retention_days(config.retention_days);
+        spawn_log_cleanup(config.log_dir.clone());
 
         LogGuard {
             _file_guard: Some(file_guard),
@@ -288,6 +480,7 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
         tracing_subscriber::registry()
             .with(env_filter)
             .with(console_layer)
+            .with(TaskLogLayer)
             .init();
 
         info!("日志系统初始化完成（仅控制台输出）");
@@ -296,11 +489,54 @@ pub fn init_logging(config: &LogConfig) -> LogGuard {
     }
 }
 
+/// 动态更新日志级别
+///
+/// 设置了 `RUST_LOG` 环境变量时以环境变量为准，不做更新
+pub fn update_log_level(level: &str) -> anyhow::Result<()> {
+    if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+        anyhow::bail!("已设置 {} 环境变量，忽略配置中的日志级别", EnvFilter::DEFAULT_ENV);
+    }
+    let handle = LOG_FILTER_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("日志系统尚未初始化"))?;
+    let filter = EnvFilter::try_new(level)
+        .map_err(|e| anyhow::anyhow!("无效的日志级别 {}: {}", level, e))?;
+    handle
+        .reload(filter)
+        .map_err(|e| anyhow::anyhow!("重载日志过滤器失败: {}", e))
+}
+
+/// 动态更新日志保留天数（下一次清理或滚动时生效）
+pub fn update_log_retention_days(days: u32) {
+    LOG_RETENTION_DAYS.store(days, Ordering::Relaxed);
+}
+
+/// 当前日志保留天数
+fn log_retention_days() -> u32 {
+    LOG_RETENTION_DAYS.load(Ordering::Relaxed)
+}
+
+/// 启动过期日志清理线程
+///
+/// 使用独立线程，不依赖 tokio 运行时；进程运行多天时也能按 retention_days 清理，
+/// 每次清理都读取当前的保留天数
+fn spawn_log_cleanup(log_dir: PathBuf) {
+    let result = std::thread::Builder::new()
+        .name("log-cleanup".to_string())
+        .spawn(move || loop {
+            cleanup_old_logs(&log_dir, log_retention_days());
+            std::thread::sleep(LOG_CLEANUP_INTERVAL);
+        });
+    if let Err(e) = result {
+        tracing::warn!("启动过期日志清理线程失败: {}", e);
+    }
+}
+
 /// 清理过期日志文件
 ///
-/// 支持两种文件格式：
-/// - 旧格式：baidu-pcs-rust.YYYY-MM-DD.log
-/// - 新格式：baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log 和 baidu-pcs-rust.YYYY-MM-DD-HHMMSS_N.log
+/// 支持的文件格式：
+/// - 旧格式：baidu-pcs-rust.YYYY-MM-DD.log 和 baidu-pcs-rust.YYYY-MM-DD-HHMMSS_N.log
+/// - 新格式：baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log 和滚动文件 baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log.N
 fn cleanup_old_logs(log_dir: &Path, retention_days: u32) {
     let now = Local::now().date_naive();
     let retention_duration = chrono::Duration::days(retention_days as i64);
@@ -329,7 +565,7 @@ fn cleanup_old_logs(log_dir: &Path, retention_days: u32) {
         };
 
         // 检查是否为日志文件
-        if !filename.starts_with("baidu-pcs-rust.") || !filename.ends_with(".log") {
+        if !filename.starts_with("baidu-pcs-rust.") || strip_log_suffix(filename).is_none() {
             continue;
         }
 
@@ -369,10 +605,11 @@ fn cleanup_old_logs(log_dir: &Path, retention_days: u32) {
 /// - baidu-pcs-rust.YYYY-MM-DD.log -> YYYY-MM-DD
 /// - baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log -> YYYY-MM-DD
 /// - baidu-pcs-rust.YYYY-MM-DD-HHMMSS_N.log -> YYYY-MM-DD
+/// - baidu-pcs-rust.YYYY-MM-DD-HHMMSS.log.N -> YYYY-MM-DD
 fn extract_date_from_filename(filename: &str) -> Option<String> {
     // 移除前缀和后缀
     let name = filename.strip_prefix("baidu-pcs-rust.")?;
-    let name = name.strip_suffix(".log")?;
+    let name = strip_log_suffix(name)?;
 
     // 提取日期部分 (YYYY-MM-DD)
     // 格式可能是：
@@ -388,6 +625,18 @@ fn extract_date_from_filename(filename: &str) -> Option<String> {
     }
 }
 
+/// 去掉 `.log` 或滚动文件的 `.log.N` 后缀，不是日志文件时返回 None
+fn strip_log_suffix(filename: &str) -> Option<&str> {
+    if let Some(name) = filename.strip_suffix(".log") {
+        return Some(name);
+    }
+    let (name, index) = filename.rsplit_once('.')?;
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    name.strip_suffix(".log")
+}
+
 /// 根据文件修改时间检查是否过期（后备方案）
 fn check_by_modified_time(entry: &fs::DirEntry, retention_days: u32) -> bool {
     let now = chrono::Utc::now();
@@ -416,5 +665,160 @@ mod tests {
         assert_eq!(config.log_dir, PathBuf::from("logs"));
         assert_eq!(config.retention_days, 7);
         assert_eq!(config.level, "info");
+        assert_eq!(config.format, LogFormat::Text);
+    }
+
+    /// 测试用写入器（收集输出内容）
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for BufferWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = LogFileManager::new(dir.path().to_path_buf(), 100).unwrap();
+
+        // 多线程并发写入，每条 60 字节，两条即超过阈值
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let mut writer = manager.clone();
+                std::thread::spawn(move || {
+                    let line = [b'a' + i as u8; 60];
+                    for _ in 0..5 {
+                        writer.write_all(&line).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let files: Vec<_> = fs::read_dir(dir.path()).unwrap().flatten().collect();
+        assert_eq!(files.len(), 20);
+        // 当前文件保持原名，滚动文件依次为 .log.1 ~ .log.19
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| f.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.iter().filter(|name| name.ends_with(".log")).count(), 1);
+        for index in 1..20 {
+            let suffix = format!(".log.{}", index);
+            assert!(names.iter().any(|name| name.ends_with(&suffix)));
+        }
+        for file in &files {
+            // 每个文件只包含一条完整日志
+            let content = fs::read(file.path()).unwrap();
+            assert_eq!(content.len(), 60);
+            assert!(content.iter().all(|&b| b == content[0]));
+        }
+    }
+
+    #[test]
+    fn test_extract_date_from_filename() {
+        for name in [
+            "baidu-pcs-rust.2024-01-02.log",
+            "baidu-pcs-rust.2024-01-02-030405.log",
+            "baidu-pcs-rust.2024-01-02-030405_3.log",
+            "baidu-pcs-rust.2024-01-02-030405.log.12",
+        ] {
+            assert_eq!(extract_date_from_filename(name).as_deref(), Some("2024-01-02"));
+        }
+        assert_eq!(extract_date_from_filename("baidu-pcs-rust.2024-01-02.log.bak"), None);
+        assert_eq!(extract_date_from_filename("other.log"), None);
+    }
+
+    #[test]
+    fn test_json_format_structured_fields() {
+        let buffer = BufferWriter::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(build_fmt_layer(LogFormat::Json, false, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(task_id = "task-1", event = "completed", "任务下载完成");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "任务下载完成");
+        assert_eq!(line["task_id"], "task-1");
+        assert_eq!(line["event"], "completed");
+    }
+
+    #[test]
+    fn test_task_log_layer() {
+        let subscriber = tracing_subscriber::registry().with(TaskLogLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!(task_id = "log-test-1", "事件字段携带 task_id");
+            let span = tracing::error_span!("download_task", task_id = %"log-test-2");
+            let _enter = span.enter();
+            info!(chunk = 3, "span 内的日志");
+            tracing::warn!("第二条");
+        });
+
+        let lines = tail_task_logs("log-test-1", 10);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("事件字段携带 task_id"));
+
+        let lines = tail_task_logs("log-test-2", 10);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("span 内的日志 chunk=3"));
+        assert!(lines[1].contains("WARN"));
+
+        // 只取最近 1 行
+        let lines = tail_task_logs("log-test-2", 1);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("第二条"));
+
+        assert!(tail_task_logs("log-test-unknown", 10).is_empty());
+    }
+
+    #[test]
+    fn test_task_log_layer_under_warn_filter() {
+        let subscriber = tracing_subscriber::registry()
+            .with(EnvFilter::new("warn"))
+            .with(TaskLogLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::error_span!("download_task", task_id = %"log-test-warn");
+            let _enter = span.enter();
+            info!("被过滤的日志");
+            tracing::warn!("分片重试");
+        });
+
+        let lines = tail_task_logs("log-test-warn", 10);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("分片重试"));
+    }
+
+    #[test]
+    fn test_task_log_store_bounds() {
+        let mut store = TaskLogStore::default();
+        for i in 0..(TASK_LOG_CAPACITY + 10) {
+            store.push("task".to_string(), format!("line {}", i));
+        }
+        let lines = &store.logs["task"];
+        assert_eq!(lines.len(), TASK_LOG_CAPACITY);
+        assert_eq!(lines.front().unwrap(), "line 10");
+
+        for i in 0..MAX_TRACKED_TASKS {
+            store.push(format!("other-{}", i), "x".to_string());
+        }
+        // 最早出现的任务被淘汰
+        assert!(!store.logs.contains_key("task"));
+        assert_eq!(store.logs.len(), MAX_TRACKED_TASKS);
     }
 }
@@ -262,6 +262,9 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // 🔥 启动配置热重载（SIGHUP / 配置文件变更）
+    baidu_netdisk_rust::server::config_reload::spawn_config_reload_tasks(app_state.clone());
+
     // 获取配置
     let config = app_state.config.read().await.clone();
     let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -286,22 +289,53 @@ async fn main() -> anyhow::Result<()> {
         .route("/auth/qrcode/generate", post(handlers::generate_qrcode))
         .route("/auth/qrcode/status", get(handlers::qrcode_status))
         .route("/auth/cookie/login", post(handlers::cookie_login))
+        .route("/auth/import", post(handlers::import_credentials))
         .route("/auth/user", get(handlers::get_current_user))
+        .route("/user/quota", get(handlers::get_user_quota))
+        .route("/auth/status", get(handlers::get_auth_status))
         .route("/auth/logout", post(handlers::logout))
+        .route("/auth/accounts", get(handlers::list_accounts))
+        .route("/auth/switch", post(handlers::switch_account))
         // 文件API
         .route("/files", get(handlers::get_file_list))
+        .route("/files", delete(handlers::delete_files))
+        .route("/files/search", get(handlers::search_files))
         .route("/files/download", get(handlers::get_download_url))
+        .route("/files/direct-url", get(handlers::get_direct_url))
+        .route("/export/aria2", post(handlers::export_aria2))
+        .route("/export/curl", post(handlers::export_curl))
+        .route("/files/thumbnail", get(handlers::get_thumbnail))
         .route("/files/folder", post(handlers::create_folder))
+        .route("/files/rename", post(handlers::rename_file))
+        .route("/files/move", post(handlers::move_files))
+        .route("/files/copy", post(handlers::copy_files))
+        // 回收站API
+        .route("/recycle", get(handlers::list_recycle_bin))
+        .route("/recycle", delete(handlers::clean_recycle_bin))
+        .route("/recycle/restore", post(handlers::restore_recycle_files))
         // 下载API
         .route("/downloads", post(handlers::create_download))
         .route("/downloads", get(handlers::get_all_downloads))
         .route("/downloads/all", get(handlers::get_all_downloads_mixed)) // 新增：统一接口
         .route("/downloads/active", get(handlers::get_active_downloads)) // 🔥 活跃任务（降级轮询）
+        .route("/downloads/history", get(handlers::get_download_history)) // 🔥 历史分页查询
+        .route("/downloads/queue", get(handlers::get_download_queue)) // 🔥 等待队列内容
+        .route("/downloads/debug/slots", get(handlers::get_download_slot_debug)) // 🔥 槽位调试信息
         .route("/downloads/batch", post(handlers::create_batch_download)) // 批量下载
         .route("/downloads/:id", get(handlers::get_download))
+        .route("/downloads/:id/logs", get(handlers::get_download_logs)) // 🔥 任务最近日志
         .route("/downloads/:id/pause", post(handlers::pause_download))
         .route("/downloads/:id/resume", post(handlers::resume_download))
+        .route("/downloads/:id/limit", post(handlers::set_download_limit)) // 🔥 单任务限速
+        .route("/downloads/:id/priority", post(handlers::set_download_priority)) // 🔥 排队优先级
+        .route("/downloads/:id/reorder", post(handlers::reorder_download)) // 🔥 调整等待队列位置
+        .route("/downloads/:id/refresh-url", post(handlers::refresh_download_url)) // 🔥 手动刷新 CDN 链接
+        .route("/downloads/:id/move-dir", post(handlers::move_download_dir)) // 🔥 修改下载目录
         .route("/downloads/:id", delete(handlers::delete_download))
+        .route(
+            "/downloads/group/:group_id",
+            delete(handlers::delete_download_group),
+        ) // 🔥 按 group 批量删除
         .route(
             "/downloads/clear/completed",
             delete(handlers::clear_completed),
@@ -310,6 +344,8 @@ async fn main() -> anyhow::Result<()> {
         // 下载批量操作
         .route("/downloads/batch/pause", post(handlers::batch_pause_downloads))
         .route("/downloads/batch/resume", post(handlers::batch_resume_downloads))
+        .route("/downloads/pause-all", post(handlers::pause_all_downloads))
+        .route("/downloads/resume-all", post(handlers::resume_all_downloads))
         .route("/downloads/batch/delete", post(handlers::batch_delete_downloads))
         // 文件夹下载API
         .route("/downloads/folder", post(handlers::create_folder_download))
@@ -318,6 +354,10 @@ async fn main() -> anyhow::Result<()> {
             get(handlers::get_all_folder_downloads),
         )
         .route("/downloads/folder/:id", get(handlers::get_folder_download))
+        .route(
+            "/downloads/folder/:id/files",
+            get(handlers::get_folder_download_files),
+        )
         .route(
             "/downloads/folder/:id/pause",
             post(handlers::pause_folder_download),
@@ -339,6 +379,8 @@ async fn main() -> anyhow::Result<()> {
         .route("/uploads/:id", delete(handlers::delete_upload))
         .route("/uploads/folder", post(handlers::create_folder_upload))
         .route("/uploads/batch", post(handlers::create_batch_upload))
+        .route("/uploads/check-rapid", post(handlers::check_rapid_upload))
+        .route("/uploads/history", get(handlers::get_upload_history))
         .route("/uploads/scan/:id", get(handlers::get_scan_status))
         .route("/uploads/scan/:id/cancel", post(handlers::cancel_scan))
         .route(
@@ -356,23 +398,36 @@ async fn main() -> anyhow::Result<()> {
         // 转存API
         .route("/transfers", post(handlers::create_transfer))
         .route("/transfers", get(handlers::get_all_transfers))
+        .route("/transfers/history", get(handlers::get_transfer_history))
+        .route(
+            "/transfers/clear/completed",
+            delete(handlers::clear_completed_transfers),
+        )
         .route("/transfers/preview", post(handlers::preview_share_files))
+        .route("/transfers/direct-download", post(handlers::direct_download_share))
+        .route("/transfers/captcha", post(handlers::submit_share_captcha))
+        .route("/transfers/inspect", post(handlers::inspect_share))
         .route("/transfers/preview/dir", post(handlers::preview_share_dir))
         .route("/transfers/cleanup", post(handlers::cleanup_orphaned_temp_dirs))
         .route("/transfers/:id", get(handlers::get_transfer))
         .route("/transfers/:id", delete(handlers::delete_transfer))
         .route("/transfers/:id/cancel", post(handlers::cancel_transfer))
+        // 🔥 任务迁移API（导出/导入未完成任务）
+        .route("/tasks/export", get(handlers::export_task_list))
+        .route("/tasks/import", post(handlers::import_task_list))
         // 本地文件系统API
         .route("/fs/list", get(handlers::list_directory))
         .route("/fs/goto", get(handlers::goto_path))
         .route("/fs/validate", get(handlers::validate_path))
         .route("/fs/roots", get(handlers::get_roots))
+        .route("/fs/drives", get(handlers::get_drives))
         // 配置API
         .route("/config", get(handlers::get_config))
         .route("/config", put(handlers::update_config))
         .route("/config/recommended", get(handlers::get_recommended_config))
         .route("/config/reset", post(handlers::reset_to_recommended))
         .route("/config/recent-dir", post(handlers::update_recent_dir))
+        .route("/config/recent-dirs", get(handlers::get_recent_dirs))
         .route(
             "/config/default-download-dir",
             post(handlers::set_default_download_dir),
@@ -417,6 +472,7 @@ async fn main() -> anyhow::Result<()> {
         // 🔥 离线下载 API
         .route("/cloud-dl/tasks", post(handlers::cloud_dl::add_task))
         .route("/cloud-dl/tasks", get(handlers::cloud_dl::list_tasks))
+        .route("/cloud-dl/tasks/batch", post(handlers::cloud_dl::batch_add_tasks))
         .route("/cloud-dl/tasks/clear", delete(handlers::cloud_dl::clear_tasks))
         .route("/cloud-dl/tasks/refresh", post(handlers::cloud_dl::refresh_tasks))
         .route("/cloud-dl/tasks/:task_id", get(handlers::cloud_dl::query_task))
@@ -425,16 +481,27 @@ async fn main() -> anyhow::Result<()> {
         // 🔥 分享 API
         .route("/shares", post(handlers::create_share))
         .route("/shares", get(handlers::get_share_list))
+        .route("/shares", delete(handlers::cancel_share))
         .route("/shares/cancel", post(handlers::cancel_share))
         .route("/shares/:id", get(handlers::get_share_detail))
         // 🔥 系统能力检测 API
         .route("/system/watch-capability", get(handlers::autobackup::get_watch_capability))
+        // 🔥 传输统计 API
+        .route("/stats", get(handlers::get_dashboard_stats))
+        // 🔥 诊断 API
+        .route("/diagnostics/speedtest", post(handlers::speed_test))
+        .route("/diagnostics/sign", get(handlers::sign_diagnostics))
         // 🔥 自动备份全局触发配置 API
         .route("/config/autobackup/trigger", get(handlers::autobackup::get_trigger_config))
         .route("/config/autobackup/trigger", put(handlers::autobackup::update_trigger_config))
         // 🔥 WebSocket 路由
         .route("/ws", get(websocket::handle_websocket))
         .with_state(app_state.clone())
+        // 🔥 只读模式拦截破坏性操作（位于认证之后）
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            baidu_netdisk_rust::server::read_only::read_only_middleware,
+        ))
         // 🔥 应用 Web 认证中间件到所有 API 路由
         .layer(middleware::from_fn_with_state(
             web_auth_state.clone(),
@@ -488,6 +555,8 @@ async fn main() -> anyhow::Result<()> {
         .nest("/api/v1", api_routes)
         .nest("/api/v1/web-auth", web_auth_routes)
         .route("/health", get(health_check))
+        .route("/ready", get(handlers::get_readiness).with_state(app_state.clone()))
+        .route("/metrics", get(handlers::get_metrics).with_state(app_state.clone()))
         .fallback_service(static_service)
         .layer(middleware);
 
@@ -499,6 +568,8 @@ async fn main() -> anyhow::Result<()> {
     info!("API 基础路径: http://{}/api/v1", addr);
     info!("WebSocket: ws://{}/api/v1/ws", addr);
     info!("健康检查: http://{}/health", addr);
+    info!("就绪检查: http://{}/ready", addr);
+    info!("Prometheus 指标: http://{}/metrics", addr);
     info!("前端页面: http://{}/", addr);
 
     // 🔥 使用 select! 监听关闭信号，支持优雅关闭
@@ -510,9 +581,7 @@ async fn main() -> anyhow::Result<()> {
                 tracing::error!("服务器错误: {}", e);
             }
         }
-        _ = tokio::signal::ctrl_c() => {
-            info!("收到 Ctrl+C，开始优雅关闭...");
-        }
+        _ = shutdown_signal() => {}
     }
 
     // 🔥 优雅关闭
@@ -524,3 +593,36 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// 等待关闭信号（Ctrl+C 或 SIGTERM）
+///
+/// 容器停止、systemd 停止服务时发送的是 SIGTERM，同样走优雅关闭流程
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("监听 Ctrl+C 信号失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("监听 SIGTERM 信号失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("收到 Ctrl+C，开始优雅关闭..."),
+        _ = terminate => info!("收到 SIGTERM，开始优雅关闭..."),
+    }
+}
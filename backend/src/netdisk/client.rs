@@ -1,12 +1,12 @@
 // 网盘客户端实现
 
 use crate::auth::constants::USER_AGENT as WEB_USER_AGENT; // 导入登录时的 UA,确保一致
-use crate::auth::constants::{API_USER_INFO, BAIDU_APP_ID, CLIENT_TYPE, USER_AGENT};
+use crate::auth::constants::{API_QUOTA, API_USER_INFO, BAIDU_APP_ID, CLIENT_TYPE, USER_AGENT};
 use crate::auth::UserAuth;
 use crate::common::ProxyConfig;
 use crate::netdisk::{
-    CreateFileResponse, FileListResponse, LocateDownloadResponse, PrecreateResponse,
-    RapidUploadResponse, UploadChunkResponse, UploadErrorKind,
+    CreateFileResponse, FileItem, FileListOrder, FileListResponse, LocateDownloadResponse, PrecreateResponse,
+    NetdiskError, QuotaInfo, RapidUploadResponse, ThumbnailSize, UploadChunkResponse, UploadErrorKind,
 };
 use crate::sign::LocateSign;
 use anyhow::{Context, Result};
@@ -18,6 +18,58 @@ use serde_json::Value;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
+/// User-Agent 覆盖配置（network.mobile_user_agent / network.web_user_agent）
+///
+/// 进程级设置，对之后创建的 NetdiskClient 生效；未设置时使用内置默认值
+static USER_AGENT_OVERRIDES: parking_lot::RwLock<UserAgentOverrides> =
+    parking_lot::RwLock::new(UserAgentOverrides {
+        mobile: None,
+        web: None,
+    });
+
+#[derive(Debug, Clone)]
+struct UserAgentOverrides {
+    mobile: Option<String>,
+    web: Option<String>,
+}
+
+/// 设置 User-Agent 覆盖（空字符串视为未设置）
+pub fn set_user_agent_overrides(mobile: Option<String>, web: Option<String>) {
+    let normalize = |ua: Option<String>| ua.filter(|s| !s.trim().is_empty());
+    let mut overrides = USER_AGENT_OVERRIDES.write();
+    overrides.mobile = normalize(mobile);
+    overrides.web = normalize(web);
+}
+
+/// 完整列目录时的单页数量（list 接口单页上限）
+const LIST_ALL_PAGE_SIZE: u32 = 1000;
+/// 完整列目录最多获取的页数，避免接口异常时无限翻页
+const LIST_ALL_MAX_PAGES: u32 = 500;
+/// 完整列目录的最大并发页数
+pub const LIST_ALL_MAX_CONCURRENCY: usize = 4;
+/// 网盘配额缓存时间（转存/上传前的空间检查复用，避免每个任务都请求一次）
+const QUOTA_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+/// 缩略图链接缓存时间（百度返回的链接带签名，有效期较短，缓存时间需小于有效期）
+const THUMBNAIL_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+/// 缩略图链接缓存（(fs_id, 尺寸) -> (获取时间, 链接)）
+type ThumbnailCache = std::collections::HashMap<(u64, ThumbnailSize), (std::time::Instant, String)>;
+/// 视频在线播放使用的转码格式
+const STREAMING_TYPE: &str = "M3U8_AUTO_720";
+
+/// 按页码顺序追加一批分页结果，遇到不满一页的页（最后一页）时返回 true
+///
+/// 并发获取时最后一页之后的页应为空，一并丢弃
+fn append_list_pages<T>(out: &mut Vec<T>, pages: Vec<Vec<T>>, page_size: usize) -> bool {
+    for page in pages {
+        let is_last = page.len() < page_size;
+        out.extend(page);
+        if is_last {
+            return true;
+        }
+    }
+    false
+}
+
 /// 百度网盘客户端
 #[derive(Debug, Clone)]
 pub struct NetdiskClient {
@@ -42,6 +94,10 @@ pub struct NetdiskClient {
     proxy_config: Option<ProxyConfig>,
     /// 代理故障回退管理器
     pub(crate) fallback_mgr: Option<std::sync::Arc<crate::common::ProxyFallbackManager>>,
+    /// 网盘配额缓存（获取时间, 配额）
+    quota_cache: std::sync::Arc<parking_lot::Mutex<Option<(std::time::Instant, QuotaInfo)>>>,
+    /// 缩略图链接缓存
+    thumbnail_cache: std::sync::Arc<parking_lot::Mutex<ThumbnailCache>>,
 }
 
 impl NetdiskClient {
@@ -124,8 +180,9 @@ impl NetdiskClient {
         // 注意: 不要禁用重定向 (Policy::none())，否则 Cookie Jar 可能无法正确携带 Cookie
         let mut builder = Client::builder()
             .cookie_provider(Arc::clone(&jar))
-            .timeout(std::time::Duration::from_secs(60))
+            .timeout(crate::common::request_timeout())
             .redirect(reqwest::redirect::Policy::limited(10)); // 允许最多 10 次重定向
+        builder = crate::common::apply_connect_timeout(builder);
 
         // 应用代理配置
         if let Some(proxy) = proxy_config {
@@ -156,17 +213,26 @@ impl NetdiskClient {
                 && user_auth.bdstoken.is_some(),
         ));
 
+        // 🔥 配置中的 UA 覆盖优先于内置默认值
+        let overrides = USER_AGENT_OVERRIDES.read().clone();
+        let mobile_user_agent = overrides
+            .mobile
+            .unwrap_or_else(Self::default_mobile_user_agent);
+        let web_user_agent = overrides.web.unwrap_or_else(Self::default_web_user_agent);
+
         Ok(Self {
             client,
             cookie_jar: jar,
             user_auth,
-            mobile_user_agent: Self::default_mobile_user_agent(),
-            web_user_agent: Self::default_web_user_agent(),
+            mobile_user_agent,
+            web_user_agent,
             web_session_ready,
             panpsc_cookie,
             bdstoken,
             proxy_config: proxy_config.cloned(),
             fallback_mgr,
+            quota_cache: Default::default(),
+            thumbnail_cache: Default::default(),
         })
     }
 
@@ -175,7 +241,8 @@ impl NetdiskClient {
         let mut builder = Client::builder()
             .cookie_store(false)
             .redirect(reqwest::redirect::Policy::none())
-            .timeout(std::time::Duration::from_secs(30));
+            .timeout(crate::common::request_timeout_or(std::time::Duration::from_secs(30)));
+        builder = crate::common::apply_connect_timeout(builder);
         if let Some(ref proxy) = self.proxy_config {
             builder = proxy.apply_to_builder(builder)?;
         }
@@ -906,7 +973,30 @@ impl NetdiskClient {
         page: u32,
         page_size: u32,
     ) -> Result<FileListResponse> {
-        info!("获取文件列表: dir={}, page={}", dir, page);
+        self.get_file_list_ordered(dir, page, page_size, FileListOrder::Name, false)
+            .await
+    }
+
+    /// 获取文件列表（指定排序方式）
+    ///
+    /// # 参数
+    /// * `order` - 排序字段（文件名/修改时间/大小）
+    /// * `desc` - 是否降序
+    pub async fn get_file_list_ordered(
+        &self,
+        dir: &str,
+        page: u32,
+        page_size: u32,
+        order: FileListOrder,
+        desc: bool,
+    ) -> Result<FileListResponse> {
+        info!(
+            "获取文件列表: dir={}, page={}, order={}, desc={}",
+            dir,
+            page,
+            order.as_api_value(),
+            desc
+        );
 
         let url = "https://pan.baidu.com/rest/2.0/xpan/file";
 
@@ -915,8 +1005,8 @@ impl NetdiskClient {
             .get(url)
             .query(&[
                 ("method", "list"),
-                ("order", "name"),
-                ("desc", "0"),
+                ("order", order.as_api_value()),
+                ("desc", if desc { "1" } else { "0" }),
                 ("showempty", "0"),
                 ("web", "1"),
                 ("page", &page.to_string()),
@@ -947,13 +1037,148 @@ impl NetdiskClient {
             .context("Failed to parse file list response")?;
 
         if file_list.errno != 0 {
-            anyhow::bail!("API error {}: {}", file_list.errno, file_list.errmsg);
+            return Err(NetdiskError::api_error(
+                file_list.errno as i64,
+                format!("获取文件列表失败: errno={}, errmsg={}", file_list.errno, file_list.errmsg),
+            ));
         }
 
         debug!("获取到 {} 个文件/文件夹", file_list.list.len());
         Ok(file_list)
     }
 
+    /// 获取目录下的全部文件（内部自动翻页）
+    ///
+    /// 适用于数万个文件的大目录：
+    /// - 每批并发获取 `concurrency` 页（1 表示顺序获取，最大 `LIST_ALL_MAX_CONCURRENCY`）
+    /// - 结果按页码顺序合并，与逐页调用 `get_file_list` 的顺序一致
+    /// - 遇到不满一页的页时停止；任意一页失败时直接返回该错误，不返回部分结果
+    pub async fn list_dir_all(
+        &self,
+        dir: &str,
+        order: FileListOrder,
+        desc: bool,
+        concurrency: usize,
+    ) -> Result<Vec<FileItem>> {
+        let concurrency = concurrency.clamp(1, LIST_ALL_MAX_CONCURRENCY) as u32;
+        info!("获取完整文件列表: dir={}, concurrency={}", dir, concurrency);
+
+        let mut items = Vec::new();
+        let mut next_page = 1u32;
+
+        while next_page <= LIST_ALL_MAX_PAGES {
+            let last_page = (next_page + concurrency - 1).min(LIST_ALL_MAX_PAGES);
+            let pages = futures::future::try_join_all(
+                (next_page..=last_page).map(|page| {
+                    self.get_file_list_ordered(dir, page, LIST_ALL_PAGE_SIZE, order, desc)
+                }),
+            )
+            .await
+            .with_context(|| format!("获取完整文件列表失败: dir={}", dir))?;
+
+            let pages = pages.into_iter().map(|response| response.list).collect();
+            if append_list_pages(&mut items, pages, LIST_ALL_PAGE_SIZE as usize) {
+                info!("完整文件列表获取完成: dir={}, 共 {} 项", dir, items.len());
+                return Ok(items);
+            }
+            next_page = last_page + 1;
+        }
+
+        warn!(
+            "目录 {} 超过 {} 页，仅返回前 {} 项",
+            dir,
+            LIST_ALL_MAX_PAGES,
+            items.len()
+        );
+        Ok(items)
+    }
+
+    /// 按关键词搜索网盘文件
+    ///
+    /// 接口单页最多返回 1000 条，`has_more` 为 1 时自动向后翻页，
+    /// 最多获取 `MAX_SEARCH_PAGES` 页，避免关键词过于宽泛时无限请求
+    ///
+    /// # 参数
+    /// * `keyword` - 搜索关键词
+    /// * `dir` - 搜索目录（如 "/"）
+    /// * `recursive` - 是否递归搜索子目录
+    /// * `page` - 起始页码（从1开始）
+    ///
+    /// # 返回
+    /// 匹配的文件列表
+    pub async fn search_files(
+        &self,
+        keyword: &str,
+        dir: &str,
+        recursive: bool,
+        page: u32,
+    ) -> Result<Vec<FileItem>> {
+        const SEARCH_PAGE_SIZE: u32 = 1000;
+        const MAX_SEARCH_PAGES: u32 = 10;
+
+        info!(
+            "搜索文件: keyword={}, dir={}, recursive={}, page={}",
+            keyword, dir, recursive, page
+        );
+
+        let url = "https://pan.baidu.com/rest/2.0/xpan/file";
+        let mut results = Vec::new();
+        let start_page = page.max(1);
+
+        for current_page in start_page..start_page + MAX_SEARCH_PAGES {
+            let response = self
+                .client
+                .get(url)
+                .query(&[
+                    ("method", "search"),
+                    ("key", keyword),
+                    ("dir", dir),
+                    ("recursion", if recursive { "1" } else { "0" }),
+                    ("web", "1"),
+                    ("page", &current_page.to_string()),
+                    ("num", &SEARCH_PAGE_SIZE.to_string()),
+                ])
+                .header("Cookie", format!("BDUSS={}", self.bduss()))
+                .header("User-Agent", &self.mobile_user_agent)
+                .send()
+                .await;
+
+            let response = match response {
+                Ok(resp) => {
+                    self.record_proxy_success();
+                    resp
+                }
+                Err(e) => {
+                    let err = anyhow::Error::from(e).context("Failed to search files");
+                    self.record_proxy_failure(&err);
+                    return Err(err);
+                }
+            };
+
+            let file_list: FileListResponse = response
+                .json()
+                .await
+                .context("Failed to parse search response")?;
+
+            if file_list.errno != 0 {
+                return Err(NetdiskError::api_error(
+                    file_list.errno as i64,
+                    format!("搜索文件失败: errno={}, errmsg={}", file_list.errno, file_list.errmsg),
+                ));
+            }
+
+            let has_more = file_list.has_more == 1 && !file_list.list.is_empty();
+            results.extend(file_list.list);
+
+            if !has_more {
+                break;
+            }
+        }
+
+        debug!("搜索到 {} 个文件/文件夹", results.len());
+        Ok(results)
+    }
+
     /// 获取文件元信息（包含 block_list）
     ///
     /// # 参数
@@ -964,21 +1189,36 @@ impl NetdiskClient {
     pub async fn filemetas(&self, paths: &[String]) -> Result<crate::netdisk::FileMetasResponse> {
         info!("获取文件元信息: paths={:?}", paths);
 
-        let url = "https://pan.baidu.com/rest/2.0/xpan/multimedia";
-
         // 将路径数组转换为 JSON 字符串
         let dlink_str = serde_json::to_string(paths)?;
 
+        self.request_filemetas(&[
+            ("dlink", &dlink_str),
+            ("thumb", "0"),
+            ("extra", "1"),
+            ("needmedia", "1"),
+        ])
+        .await
+    }
+
+    /// 按 fs_id 获取文件元信息（含缩略图链接，不含下载链接）
+    pub async fn filemetas_by_fs_ids(&self, fs_ids: &[u64]) -> Result<crate::netdisk::FileMetasResponse> {
+        debug!("按 fs_id 获取文件元信息: fs_ids={:?}", fs_ids);
+
+        let fsids_str = serde_json::to_string(fs_ids)?;
+        self.request_filemetas(&[("fsids", &fsids_str), ("thumb", "1"), ("dlink", "0")])
+            .await
+    }
+
+    /// 调用 xpan filemetas 接口
+    async fn request_filemetas(&self, params: &[(&str, &str)]) -> Result<crate::netdisk::FileMetasResponse> {
+        let url = "https://pan.baidu.com/rest/2.0/xpan/multimedia";
+
         let response = self
             .client
             .get(url)
-            .query(&[
-                ("method", "filemetas"),
-                ("dlink", &dlink_str),
-                ("thumb", "0"),
-                ("extra", "1"),
-                ("needmedia", "1"),
-            ])
+            .query(&[("method", "filemetas")])
+            .query(params)
             .header("Cookie", format!("BDUSS={}", self.bduss()))
             .header("User-Agent", &self.mobile_user_agent)
             .send()
@@ -1002,38 +1242,78 @@ impl NetdiskClient {
             .context("解析文件元信息响应失败")?;
 
         if file_metas.errno != 0 {
-            anyhow::bail!(
-                "获取文件元信息失败: errno={}, errmsg={}",
-                file_metas.errno,
-                file_metas.errmsg
-            );
+            return Err(NetdiskError::api_error(
+                file_metas.errno as i64,
+                format!(
+                    "获取文件元信息失败: errno={}, errmsg={}",
+                    file_metas.errno, file_metas.errmsg
+                ),
+            ));
         }
 
         debug!("获取到 {} 个文件元信息", file_metas.list.len());
         Ok(file_metas)
     }
 
-    /// 获取Locate下载链接（通过文件路径）
+    /// 获取图片/视频缩略图链接（缓存 THUMBNAIL_CACHE_TTL）
     ///
-    /// # 参数
-    /// * `path` - 文件路径（如 "/apps/test/file.zip"）
-    ///
-    /// # 返回
-    /// 下载URL数组
-    pub async fn get_locate_download_url(&self, path: &str) -> Result<Vec<String>> {
-        info!("获取Locate下载链接: path={}", path);
+    /// 只调用 filemetas 元信息接口，不获取下载链接，不计入下载次数和限速
+    pub async fn get_thumbnail_url(&self, fs_id: u64, size: ThumbnailSize) -> Result<String> {
+        if let Some((fetched_at, url)) = self.thumbnail_cache.lock().get(&(fs_id, size)) {
+            if fetched_at.elapsed() < THUMBNAIL_CACHE_TTL {
+                return Ok(url.clone());
+            }
+        }
 
-        // 1. 检查 UID
-        if self.uid() == 0 {
-            error!("UID 未设置，无法获取下载链接");
-            anyhow::bail!("UID 未设置，请先登录");
+        let metas = self.filemetas_by_fs_ids(&[fs_id]).await?;
+        let meta = metas
+            .list
+            .into_iter()
+            .find(|meta| meta.fs_id == fs_id)
+            .ok_or_else(|| anyhow::anyhow!("文件不存在: fs_id={}", fs_id))?;
+        let url = meta
+            .thumbs
+            .as_ref()
+            .and_then(|thumbs| thumbs.url(size))
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("文件没有缩略图: {}", meta.server_filename))?;
+
+        let mut cache = self.thumbnail_cache.lock();
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < THUMBNAIL_CACHE_TTL);
+        cache.insert((fs_id, size), (std::time::Instant::now(), url.clone()));
+        Ok(url)
+    }
+
+    /// 获取视频在线播放（M3U8）链接
+    ///
+    /// 通过 filemetas 查询文件路径后拼接 streaming 接口地址；请求该地址需携带账号 Cookie，
+    /// 与缩略图一样不获取下载链接，不计入下载次数
+    pub async fn get_media_streaming_url(&self, fs_id: u64) -> Result<String> {
+        let metas = self.filemetas_by_fs_ids(&[fs_id]).await?;
+        let meta = metas
+            .list
+            .into_iter()
+            .find(|meta| meta.fs_id == fs_id)
+            .ok_or_else(|| anyhow::anyhow!("文件不存在: fs_id={}", fs_id))?;
+        if meta.isdir == 1 {
+            anyhow::bail!("目录不支持在线播放: {}", meta.path);
         }
 
-        // 2. 生成Locate签名
-        let sign = LocateSign::new(self.uid(), self.bduss());
+        Ok(Self::streaming_url(&meta.path))
+    }
 
-        // 3. 构建完整UR
-        let url = format!(
+    /// 拼接 streaming 接口地址
+    fn streaming_url(path: &str) -> String {
+        format!(
+            "https://pan.baidu.com/rest/2.0/xpan/file?method=streaming&path={}&type={}",
+            urlencoding::encode(path),
+            STREAMING_TYPE
+        )
+    }
+
+    /// 构建 Locate 下载请求 URL（附带签名参数）
+    pub fn locate_download_url(path: &str, sign: &LocateSign) -> String {
+        format!(
             "https://pcs.baidu.com/rest/2.0/pcs/file?\
              ant=1&\
              check_blue=1&\
@@ -1052,7 +1332,30 @@ impl NetdiskClient {
              {}",
             urlencoding::encode(path),
             sign.url_params()
-        );
+        )
+    }
+
+    /// 获取Locate下载链接（通过文件路径）
+    ///
+    /// # 参数
+    /// * `path` - 文件路径（如 "/apps/test/file.zip"）
+    ///
+    /// # 返回
+    /// 下载URL数组
+    pub async fn get_locate_download_url(&self, path: &str) -> Result<Vec<String>> {
+        info!("获取Locate下载链接: path={}", path);
+
+        // 1. 检查 UID
+        if self.uid() == 0 {
+            error!("UID 未设置，无法获取下载链接");
+            anyhow::bail!("UID 未设置，请先登录");
+        }
+
+        // 2. 生成Locate签名
+        let sign = LocateSign::with_devuid(self.uid(), self.bduss(), self.user_auth.devuid());
+
+        // 3. 构建完整UR
+        let url = Self::locate_download_url(path, &sign);
 
         debug!("Locate 请求 URL: {}", url);
         debug!("UID: {}", self.uid());
@@ -1122,7 +1425,10 @@ impl NetdiskClient {
                     "百度 API 返回错误: errno={}, errmsg={}, path={}",
                     errno, errmsg, path
                 );
-                anyhow::bail!("百度 API 错误 {}: {}", errno, errmsg);
+                return Err(NetdiskError::api_error(
+                    errno,
+                    format!("百度 API 错误 {}: {}", errno, errmsg),
+                ));
             }
         }
 
@@ -1164,13 +1470,58 @@ impl NetdiskClient {
     ///
     /// # 返回
     /// Locate下载响应
+    ///
+    /// 通过 filemetas 接口（dlink=1）按 fs_id 获取下载链接，无需知道文件路径；
+    /// 每个文件只返回一个链接，文件夹和不存在的文件没有链接
     pub async fn get_locate_download_urls(&self, fs_ids: &[u64]) -> Result<LocateDownloadResponse> {
         info!("获取Locate下载链接: {} 个文件", fs_ids.len());
 
-        // 注意：这个API可能需要不同的处理方式
-        // 目前优先使用 get_locate_download_url (通过路径)
+        if fs_ids.is_empty() {
+            anyhow::bail!("fs_id 列表为空");
+        }
+
+        let fsids_str = serde_json::to_string(fs_ids)?;
+        let metas = self
+            .request_filemetas(&[("fsids", &fsids_str), ("dlink", "1")])
+            .await?;
+
+        Ok(metas.into())
+    }
+
+    /// 获取单个文件的下载链接（通过文件ID）
+    ///
+    /// 优先使用 fs_id 接口，失败或没有返回链接时回退到按路径 Locate
+    ///
+    /// # 参数
+    /// * `fs_id` - 文件服务器ID
+    /// * `path` - 文件路径（可选，未指定时使用 fs_id 接口返回的路径回退）
+    ///
+    /// # 返回
+    /// (文件路径, 下载URL数组)
+    pub async fn get_locate_download_url_by_fs_id(&self, fs_id: u64, path: Option<&str>) -> Result<(String, Vec<String>)> {
+        let mut fallback_path = path.map(|p| p.to_string());
+
+        match self.get_locate_download_urls(&[fs_id]).await {
+            Ok(response) => match response.list.into_iter().find(|info| info.fs_id == fs_id) {
+                Some(info) if info.isdir == 1 => anyhow::bail!("不支持下载文件夹: {}", info.path),
+                Some(info) if !info.dlink.is_empty() => {
+                    let urls = info.dlink.into_iter().map(|dlink| dlink.url).collect();
+                    return Ok((info.path, urls));
+                }
+                Some(info) => {
+                    warn!("fs_id 接口未返回下载链接，回退到按路径获取: fs_id={}", fs_id);
+                    fallback_path.get_or_insert(info.path);
+                }
+                None => warn!("fs_id 接口未找到文件，回退到按路径获取: fs_id={}", fs_id),
+            },
+            Err(e) => warn!("按 fs_id 获取下载链接失败，回退到按路径获取: fs_id={}, 错误: {}", fs_id, e),
+        }
 
-        anyhow::bail!("批量下载暂不支持，请使用 get_locate_download_url (通过文件路径)")
+        let Some(path) = fallback_path else {
+            anyhow::bail!("按 fs_id 获取下载链接失败，且没有可回退的文件路径: fs_id={}", fs_id);
+        };
+        let urls = self.get_locate_download_url(&path).await?;
+        Ok((path, urls))
     }
 
     /// 获取单个文件的下载链接（通过文件路径）
@@ -1193,24 +1544,37 @@ impl NetdiskClient {
             anyhow::bail!("未找到可用的下载链接");
         }
 
-        // 1. 边界检查：如果 dlink_prefer 超出范围，使用最后一个链接
-        let mut selected_index = if dlink_prefer >= urls.len() {
-            urls.len() - 1
-        } else {
-            dlink_prefer
-        };
+        let selected_index = Self::select_dlink_index(&urls, dlink_prefer);
+        let selected_url = &urls[selected_index];
 
-        // 2. 选择链接
-        let mut selected_url = &urls[selected_index];
+        info!(
+            "选择下载链接 (索引: {}, 总数: {}): {}",
+            selected_index,
+            urls.len(),
+            selected_url
+        );
+
+        Ok(selected_url.clone())
+    }
+
+    /// 根据 dlink_prefer 从链接列表中选择链接索引（下载任务指定首选节点时复用）
+    ///
+    /// 1. 如果索引超出范围，使用最后一个链接
+    /// 2. 如果选中的链接是 nb.cache 开头且有更多链接，自动使用下一个链接
+    ///
+    /// `urls` 不能为空
+    pub fn select_dlink_index(urls: &[String], dlink_prefer: usize) -> usize {
+        // 1. 边界检查：如果 dlink_prefer 超出范围，使用最后一个链接
+        let mut selected_index = dlink_prefer.min(urls.len().saturating_sub(1));
 
-        // 3. 跳过 nb.cache 链接（如果选中的是 nb.cache 且有更多链接可用）
+        // 2. 跳过 nb.cache 链接（如果选中的是 nb.cache 且有更多链接可用）
+        let selected_url = &urls[selected_index];
         if selected_url.starts_with("http://nb.cache")
             || selected_url.starts_with("https://nb.cache")
         {
             if selected_index + 1 < urls.len() {
                 // 使用下一个链接
                 selected_index += 1;
-                selected_url = &urls[selected_index];
                 info!(
                     "检测到 nb.cache 链接，自动切换到下一个链接 (索引: {})",
                     selected_index
@@ -1220,14 +1584,7 @@ impl NetdiskClient {
             }
         }
 
-        info!(
-            "选择下载链接 (索引: {}, 总数: {}): {}",
-            selected_index,
-            urls.len(),
-            selected_url
-        );
-
-        Ok(selected_url.clone())
+        selected_index
     }
 
     // =====================================================
@@ -1253,6 +1610,7 @@ impl NetdiskClient {
         is_dir: &str,
         rtype: &str,
     ) -> Result<RapidUploadResponse> {
+        crate::uploader::conflict::validate_rtype(rtype)?;
         let url = "https://pan.baidu.com/api/create";
 
         let response = self
@@ -1408,7 +1766,12 @@ impl NetdiskClient {
         block_list: &str,
         rtype: &str,
     ) -> Result<PrecreateResponse> {
+        crate::uploader::conflict::validate_rtype(rtype)?;
+        self.ensure_free_space(file_size).await?;
         info!("预创建文件: path={}, size={}, rtype={}", remote_path, file_size, rtype);
+        if rtype == "3" {
+            warn!("上传使用覆盖策略（rtype=3），网盘同名文件将被直接替换: {}", remote_path);
+        }
 
         let url = "https://pan.baidu.com/api/precreate";
 
@@ -1454,11 +1817,13 @@ impl NetdiskClient {
                 "预创建失败: errno={}, errmsg={}",
                 precreate_response.errno, precreate_response.errmsg
             );
-            anyhow::bail!(
-                "预创建失败: {} - {}",
-                precreate_response.errno,
-                precreate_response.errmsg
-            );
+            return Err(NetdiskError::api_error(
+                precreate_response.errno as i64,
+                format!(
+                    "预创建失败: {} - {}",
+                    precreate_response.errno, precreate_response.errmsg
+                ),
+            ));
         }
 
         info!(
@@ -1522,14 +1887,17 @@ impl NetdiskClient {
 
         let form = multipart::Form::new().part("file", part);
 
-        let response = self
+        let mut request = self
             .client
             .post(&url)
             .header("Cookie", format!("BDUSS={}", self.bduss()))
             .header("User-Agent", &self.mobile_user_agent)
-            .multipart(form)
-            .send()
-            .await;
+            .multipart(form);
+        // 🔥 配置了分片超时时覆盖客户端的接口请求超时
+        if let Some(secs) = crate::common::chunk_timeout_secs() {
+            request = request.timeout(std::time::Duration::from_secs(secs));
+        }
+        let response = request.send().await;
 
         let response = match response {
             Ok(resp) => {
@@ -1580,11 +1948,13 @@ impl NetdiskClient {
                 chunk_response.error_msg,
                 error_kind.is_retriable()
             );
-            anyhow::bail!(
-                "上传分片失败: {} - {}",
-                chunk_response.error_code,
-                chunk_response.error_msg
-            );
+            return Err(NetdiskError::api_error(
+                chunk_response.error_code as i64,
+                format!(
+                    "上传分片失败: {} - {}",
+                    chunk_response.error_code, chunk_response.error_msg
+                ),
+            ));
         }
 
         debug!(
@@ -1830,7 +2200,10 @@ impl NetdiskClient {
                 "创建文件夹失败: error_code={}, error_msg={}",
                 error_code, error_msg
             );
-            anyhow::bail!("创建文件夹失败: errno={}, msg={}", error_code, error_msg);
+            return Err(NetdiskError::api_error(
+                error_code as i64,
+                format!("创建文件夹失败: errno={}, msg={}", error_code, error_msg),
+            ));
         }
 
         info!("创建文件夹成功: path={}", remote_path);
@@ -1935,7 +2308,7 @@ impl NetdiskClient {
     /// * `first` - 是否为首次访问（影响 Referer）
     ///
     /// # 返回
-    /// SharePageInfo 或错误（需要密码/分享失效/页面不存在）
+    /// SharePageInfo 或错误（需要密码/分享失效/页面不存在时为 TransferError）
     pub async fn access_share_page(
         &self,
         short_key: &str,
@@ -1980,10 +2353,10 @@ impl NetdiskClient {
 
         // 检测页面状态
         if body.contains("platform-non-found") {
-            anyhow::bail!("分享已失效");
+            return Err(crate::transfer::TransferError::ShareExpired.into());
         }
         if body.contains("error-404") {
-            anyhow::bail!("分享不存在");
+            return Err(crate::transfer::TransferError::ShareNotFound.into());
         }
 
         // 检测是否需要密码
@@ -2070,14 +2443,14 @@ impl NetdiskClient {
             }
             // 如果需要密码且无法提取分享信息，提示需要密码
             if need_password && _password {
-                anyhow::bail!("需要密码");
+                return Err(crate::transfer::TransferError::NeedPassword.into());
             }
             anyhow::bail!("无法提取分享信息，请确认链接有效");
         }
 
         // 检测到需要密码时，返回错误让调用方处理
         if need_password && _password {
-            anyhow::bail!("需要密码");
+            return Err(crate::transfer::TransferError::NeedPassword.into());
         }
 
         info!("提取分享信息成功: shareid={}, uk={}", shareid, uk);
@@ -2100,7 +2473,7 @@ impl NetdiskClient {
     /// * `referer` - 来源页面
     ///
     /// # 返回
-    /// 成功返回 randsk，失败返回错误
+    /// 成功返回 randsk，失败返回错误（提取码错误、需要验证码时为 TransferError）
     pub async fn verify_share_password(
         &self,
         shareid: &str,
@@ -2109,7 +2482,24 @@ impl NetdiskClient {
         password: &str,
         referer: &str,
     ) -> Result<String> {
-        info!("验证提取码: shareid={}", shareid);
+        self.verify_share_password_with_captcha(shareid, share_uk, bdstoken, password, referer, None)
+            .await
+    }
+
+    /// 携带验证码重新验证提取码
+    ///
+    /// 首次验证返回 TransferError::CaptchaRequired 后，由用户识别验证码图片并提交 vcode；
+    /// 验证码错误时返回 TransferError::CaptchaIncorrect，并附带新的验证码
+    pub async fn verify_share_password_with_captcha(
+        &self,
+        shareid: &str,
+        share_uk: &str,
+        bdstoken: &str,
+        password: &str,
+        referer: &str,
+        captcha: Option<&crate::transfer::ShareCaptchaAnswer>,
+    ) -> Result<String> {
+        info!("验证提取码: shareid={}, 携带验证码={}", shareid, captcha.is_some());
 
         let timestamp = chrono::Utc::now().timestamp_millis();
         let url = format!(
@@ -2128,8 +2518,8 @@ impl NetdiskClient {
             )
             .form(&[
                 ("pwd", password),
-                ("vcode", ""),
-                ("vcode_str", ""),
+                ("vcode", captcha.map_or("", |c| c.vcode.as_str())),
+                ("vcode_str", captcha.map_or("", |c| c.vcode_str.as_str())),
                 ("bdstoken", bdstoken),
             ])
             .send()
@@ -2170,72 +2560,214 @@ impl NetdiskClient {
             }
 
             Ok(randsk)
-        } else if errno == -9 {
-            anyhow::bail!("提取码错误")
+        } else if errno == -9 || errno == -12 {
+            Err(crate::transfer::TransferError::InvalidPassword.into())
+        } else if errno == -19 || errno == -62 {
+            // 多次验证后百度要求输入验证码，获取验证码返回给调用方
+            let new_captcha = self
+                .get_share_captcha(bdstoken, referer)
+                .await
+                .context("需要输入验证码，但获取验证码失败")?;
+            warn!("验证提取码需要验证码: errno={}, 已提交验证码={}", errno, captcha.is_some());
+            Err(if captcha.is_some() {
+                crate::transfer::TransferError::CaptchaIncorrect(new_captcha)
+            } else {
+                crate::transfer::TransferError::CaptchaRequired(new_captcha)
+            }
+                .into())
         } else {
             anyhow::bail!("验证失败: errno={}", errno)
         }
     }
 
-    /// 列出分享中的文件（根目录，与官方接口对齐）
-    ///
-    /// 官方根目录请求使用 shorturl + root=1，不传 dir
-    /// 响应中包含 uk 和 share_id，用于后续子目录导航拼接 dir
-    ///
-    /// # 参数
-    /// * `short_key` - 分享短链 ID（如 "1abcDEFg"）
-    /// * `bdstoken` - CSRF 令牌
-    /// * `page` - 页码（从 1 开始）
-    /// * `num` - 每页数量
-    ///
-    /// # 返回
-    /// ShareFileListResult（包含文件列表 + uk + shareid）
-    pub async fn list_share_files(
-        &self,
-        short_key: &str,
-        bdstoken: &str,
-        page: u32,
-        num: u32,
-    ) -> Result<crate::transfer::ShareFileListResult> {
-        info!("获取分享文件列表(根目录): short_key={}, page={}, num={}", short_key, page, num);
-
-        // short_key 包含 '1'（如 "1abcDEFg"），需要去掉第一个字符
-        let shorturl = if short_key.starts_with('1') && short_key.len() > 1 {
-            &short_key[1..]
-        } else {
-            short_key
-        };
+    /// 获取网盘空间配额（缓存 QUOTA_CACHE_TTL）
+    pub async fn get_quota(&self) -> Result<QuotaInfo> {
+        if let Some((fetched_at, quota)) = *self.quota_cache.lock() {
+            if fetched_at.elapsed() < QUOTA_CACHE_TTL {
+                return Ok(quota);
+            }
+        }
 
         let url = format!(
-            "https://pan.baidu.com/share/list?\
-             shorturl={}&root=1&order=time&desc=1&showempty=0&\
-             web=1&page={}&num={}&view_mode=1&channel=chunlei&\
-             app_id={}&bdstoken={}&clienttype=0",
-            shorturl, page, num, BAIDU_APP_ID, bdstoken
+            "{}?checkfree=1&checkexpire=1&app_id={}&web=1",
+            API_QUOTA, BAIDU_APP_ID
         );
-
-        let referer = format!("https://pan.baidu.com/s/{}", short_key);
-
         let response = self
             .client
             .get(&url)
             .header("User-Agent", &self.web_user_agent)
-            .header("Referer", &referer)
+            .header("Referer", "https://pan.baidu.com/disk/home")
             .send()
             .await
-            .context("获取分享文件列表失败")?;
+            .context("获取网盘配额失败")?;
 
-        let response_text = response.text().await.context("读取文件列表响应失败")?;
-        info!("文件列表响应: {}", response_text);
+        let json: Value = response.json().await.context("解析网盘配额响应失败")?;
+        let quota = Self::parse_quota(&json)?;
+        debug!("网盘配额: total={}, used={}, free={}", quota.total, quota.used, quota.free);
 
-        let json: Value = serde_json::from_str(&response_text).context("解析文件列表响应失败")?;
+        *self.quota_cache.lock() = Some((std::time::Instant::now(), quota));
+        Ok(quota)
+    }
 
+    /// 解析 /api/quota 响应（缺少 free 时按 total - used 计算）
+    fn parse_quota(json: &Value) -> Result<QuotaInfo> {
         let errno = json["errno"].as_i64().unwrap_or(-1);
         if errno != 0 {
-            let errmsg = json["errmsg"]
-                .as_str()
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| match errno {
+            return Err(NetdiskError::api_error(
+                errno,
+                format!("获取网盘配额失败: errno={}", errno),
+            ));
+        }
+
+        let total = json["total"]
+            .as_u64()
+            .filter(|&total| total > 0)
+            .context("网盘配额响应缺少 total")?;
+        let used = json["used"].as_u64().unwrap_or(0);
+        let free = json["free"]
+            .as_u64()
+            .unwrap_or_else(|| total.saturating_sub(used));
+
+        Ok(QuotaInfo { total, used, free })
+    }
+
+    /// 检查网盘剩余空间是否足够（转存、上传前调用）
+    ///
+    /// 空间不足时返回 NetdiskError::QuotaExceeded；获取配额失败时只记录日志，不阻止后续操作
+    pub async fn ensure_free_space(&self, required: u64) -> Result<()> {
+        if required == 0 {
+            return Ok(());
+        }
+
+        match self.get_quota().await {
+            Ok(quota) if quota.free < required => {
+                const GB: f64 = (1024 * 1024 * 1024) as f64;
+                Err(anyhow::Error::new(NetdiskError::QuotaExceeded).context(format!(
+                    "网盘空间不足: 需要 {:.2} GB，剩余 {:.2} GB（总空间 {:.2} GB）",
+                    required as f64 / GB,
+                    quota.free as f64 / GB,
+                    quota.total as f64 / GB
+                )))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                warn!("获取网盘配额失败，跳过空间检查: {:#}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// 获取分享提取码验证使用的图片验证码
+    pub async fn get_share_captcha(
+        &self,
+        bdstoken: &str,
+        referer: &str,
+    ) -> Result<crate::transfer::ShareCaptcha> {
+        let url = format!(
+            "https://pan.baidu.com/api/getcaptcha?prod=shareverify&web=1&channel=chunlei&\
+             clienttype=0&app_id={}&bdstoken={}",
+            BAIDU_APP_ID, bdstoken
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", &self.web_user_agent)
+            .header("Referer", referer)
+            .send()
+            .await
+            .context("获取验证码请求失败")?;
+
+        let response_text = response.text().await.context("读取验证码响应失败")?;
+        debug!("获取验证码响应: {}", response_text);
+
+        let json: Value = serde_json::from_str(&response_text).context("解析验证码响应失败")?;
+        Self::parse_share_captcha(&json)
+    }
+
+    /// 解析 getcaptcha 响应（缺少图片地址时按 vcode_str 拼接）
+    fn parse_share_captcha(json: &Value) -> Result<crate::transfer::ShareCaptcha> {
+        let errno = json["errno"].as_i64().unwrap_or(-1);
+        if errno != 0 {
+            return Err(NetdiskError::api_error(
+                errno,
+                format!("获取验证码失败: errno={}", errno),
+            ));
+        }
+
+        let vcode_str = json["vcode_str"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .context("验证码响应缺少 vcode_str")?
+            .to_string();
+        let img_url = json["vcode_img"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("https://pan.baidu.com/genimage?{}", vcode_str));
+
+        Ok(crate::transfer::ShareCaptcha { img_url, vcode_str })
+    }
+
+    /// 列出分享中的文件（根目录，与官方接口对齐）
+    ///
+    /// 官方根目录请求使用 shorturl + root=1，不传 dir
+    /// 响应中包含 uk 和 share_id，用于后续子目录导航拼接 dir
+    ///
+    /// # 参数
+    /// * `short_key` - 分享短链 ID（如 "1abcDEFg"）
+    /// * `bdstoken` - CSRF 令牌
+    /// * `page` - 页码（从 1 开始）
+    /// * `num` - 每页数量
+    ///
+    /// # 返回
+    /// ShareFileListResult（包含文件列表 + uk + shareid）
+    pub async fn list_share_files(
+        &self,
+        short_key: &str,
+        bdstoken: &str,
+        page: u32,
+        num: u32,
+    ) -> Result<crate::transfer::ShareFileListResult> {
+        info!("获取分享文件列表(根目录): short_key={}, page={}, num={}", short_key, page, num);
+
+        // short_key 包含 '1'（如 "1abcDEFg"），需要去掉第一个字符
+        let shorturl = if short_key.starts_with('1') && short_key.len() > 1 {
+            &short_key[1..]
+        } else {
+            short_key
+        };
+
+        let url = format!(
+            "https://pan.baidu.com/share/list?\
+             shorturl={}&root=1&order=time&desc=1&showempty=0&\
+             web=1&page={}&num={}&view_mode=1&channel=chunlei&\
+             app_id={}&bdstoken={}&clienttype=0",
+            shorturl, page, num, BAIDU_APP_ID, bdstoken
+        );
+
+        let referer = format!("https://pan.baidu.com/s/{}", short_key);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", &self.web_user_agent)
+            .header("Referer", &referer)
+            .send()
+            .await
+            .context("获取分享文件列表失败")?;
+
+        let response_text = response.text().await.context("读取文件列表响应失败")?;
+        info!("文件列表响应: {}", response_text);
+
+        let json: Value = serde_json::from_str(&response_text).context("解析文件列表响应失败")?;
+
+        let errno = json["errno"].as_i64().unwrap_or(-1);
+        if errno != 0 {
+            let errmsg = json["errmsg"]
+                .as_str()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| match errno {
                     132 => "您的帐号可能存在安全风险，为了确保为您本人操作，请先进行安全验证"
                         .to_string(),
                     -7 => "该分享已删除或已取消".to_string(),
@@ -2247,7 +2779,10 @@ impl NetdiskClient {
                     _ => format!("未知错误，错误码: {}", errno),
                 });
 
-            anyhow::bail!("获取文件列表失败: errno={}, errmsg={}", errno, errmsg);
+            return Err(NetdiskError::api_error(
+                errno,
+                format!("获取文件列表失败: errno={}, errmsg={}", errno, errmsg),
+            ));
         }
 
         // 从响应中提取 uk 和 share_id（用于子目录导航拼接 dir）
@@ -2264,44 +2799,12 @@ impl NetdiskClient {
 
         let mut files = Vec::new();
         for item in list {
-            let fs_id = if let Some(id_str) = item["fs_id"].as_str() {
-                id_str.parse::<u64>().unwrap_or(0)
-            } else {
-                item["fs_id"].as_u64().unwrap_or(0)
-            };
-
-            let is_dir = if let Some(n) = item["isdir"].as_i64() {
-                n == 1
-            } else if let Some(s) = item["isdir"].as_str() {
-                s == "1"
-            } else {
-                false
-            };
-            let path = item["path"].as_str().unwrap_or_default().to_string();
-            let size = if let Some(n) = item["size"].as_u64() {
-                n
-            } else if let Some(s) = item["size"].as_str() {
-                s.parse::<u64>().unwrap_or(0)
-            } else {
-                0
-            };
-            let name = item["server_filename"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-
+            let file = Self::parse_shared_file_item(item);
             info!(
                 "解析文件: fs_id={}, name={}, is_dir={}",
-                fs_id, name, is_dir
+                file.fs_id, file.name, file.is_dir
             );
-
-            files.push(crate::transfer::SharedFileInfo {
-                fs_id,
-                is_dir,
-                path,
-                size,
-                name,
-            });
+            files.push(file);
         }
 
         Ok(crate::transfer::ShareFileListResult {
@@ -2358,50 +2861,148 @@ impl NetdiskClient {
                 .as_str()
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| format!("未知错误，错误码: {}", errno));
-            anyhow::bail!("获取子目录文件列表失败: errno={}, errmsg={}", errno, errmsg);
+            return Err(NetdiskError::api_error(
+                errno,
+                format!("获取子目录文件列表失败: errno={}, errmsg={}", errno, errmsg),
+            ));
         }
 
         let list = json["list"].as_array().context("子目录文件列表格式错误")?;
 
-        let mut files = Vec::new();
-        for item in list {
-            let fs_id = if let Some(id_str) = item["fs_id"].as_str() {
-                id_str.parse::<u64>().unwrap_or(0)
-            } else {
-                item["fs_id"].as_u64().unwrap_or(0)
-            };
+        let files: Vec<_> = list.iter().map(Self::parse_shared_file_item).collect();
 
-            let is_dir = if let Some(n) = item["isdir"].as_i64() {
-                n == 1
-            } else if let Some(s) = item["isdir"].as_str() {
-                s == "1"
-            } else {
-                false
-            };
-            let path = item["path"].as_str().unwrap_or_default().to_string();
-            let size = if let Some(n) = item["size"].as_u64() {
-                n
-            } else if let Some(s) = item["size"].as_str() {
-                s.parse::<u64>().unwrap_or(0)
-            } else {
-                0
-            };
-            let name = item["server_filename"]
+        info!("子目录文件列表: {} 个文件, dir={}", files.len(), dir);
+        Ok(files)
+    }
+
+    /// 列出分享目录并获取文件直链（免转存直接下载）
+    ///
+    /// 与 list_share_files / list_share_files_in_dir 使用同一个 share/list 接口，
+    /// 额外携带 sekey，响应中的文件会带上 dlink
+    ///
+    /// # 参数
+    /// * `source` - 已验证的分享来源
+    /// * `dir` - 分享内目录路径，None 表示根目录
+    /// * `page` - 页码（从 1 开始）
+    /// * `num` - 每页数量
+    pub async fn list_share_files_with_dlink(
+        &self,
+        source: &crate::transfer::ShareDirectSource,
+        dir: Option<&str>,
+        page: u32,
+        num: u32,
+    ) -> Result<Vec<crate::transfer::SharedFileInfo>> {
+        debug!(
+            "获取分享直链列表: shareid={}, dir={:?}, page={}, num={}",
+            source.shareid, dir, page, num
+        );
+
+        let location = match dir {
+            Some(dir) => format!(
+                "uk={}&shareid={}&dir={}",
+                source.uk,
+                source.shareid,
+                urlencoding::encode(dir)
+            ),
+            None => {
+                let shorturl = source
+                    .short_key
+                    .strip_prefix('1')
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or(&source.short_key);
+                format!("shorturl={}&root=1", shorturl)
+            }
+        };
+        let sekey = source
+            .sekey
+            .as_deref()
+            .map(|k| format!("&sekey={}", urlencoding::encode(k)))
+            .unwrap_or_default();
+
+        let url = format!(
+            "https://pan.baidu.com/share/list?\
+             {}&order=name&desc=0&showempty=0&\
+             web=1&page={}&num={}&channel=chunlei&\
+             app_id={}&bdstoken={}&clienttype=0{}",
+            location, page, num, BAIDU_APP_ID, source.bdstoken, sekey
+        );
+
+        let referer = format!("https://pan.baidu.com/s/{}", source.short_key);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", &self.web_user_agent)
+            .header("Referer", &referer)
+            .send()
+            .await
+            .context("获取分享直链列表失败")?;
+
+        let json: Value = response.json().await.context("解析分享直链列表响应失败")?;
+
+        let errno = json["errno"].as_i64().unwrap_or(-1);
+        if errno != 0 {
+            let errmsg = json["errmsg"]
                 .as_str()
-                .unwrap_or_default()
-                .to_string();
-
-            files.push(crate::transfer::SharedFileInfo {
-                fs_id,
-                is_dir,
-                path,
-                size,
-                name,
-            });
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("未知错误，错误码: {}", errno));
+            return Err(NetdiskError::api_error(
+                errno,
+                format!("获取分享直链列表失败: errno={}, errmsg={}", errno, errmsg),
+            ));
         }
 
-        info!("子目录文件列表: {} 个文件, dir={}", files.len(), dir);
-        Ok(files)
+        let list = json["list"].as_array().context("分享直链列表格式错误")?;
+        Ok(list.iter().map(Self::parse_shared_file_item).collect())
+    }
+
+    /// 解析 share/list 响应中的单个文件条目
+    ///
+    /// fs_id / isdir / size 可能是数字或字符串，统一兼容处理
+    fn parse_shared_file_item(item: &Value) -> crate::transfer::SharedFileInfo {
+        let fs_id = if let Some(id_str) = item["fs_id"].as_str() {
+            id_str.parse::<u64>().unwrap_or(0)
+        } else {
+            item["fs_id"].as_u64().unwrap_or(0)
+        };
+
+        let is_dir = if let Some(n) = item["isdir"].as_i64() {
+            n == 1
+        } else if let Some(s) = item["isdir"].as_str() {
+            s == "1"
+        } else {
+            false
+        };
+        let path = item["path"].as_str().unwrap_or_default().to_string();
+        let size = if let Some(n) = item["size"].as_u64() {
+            n
+        } else if let Some(s) = item["size"].as_str() {
+            s.parse::<u64>().unwrap_or(0)
+        } else {
+            0
+        };
+        let name = item["server_filename"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let dlink = item["dlink"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        let md5 = item["md5"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+
+        crate::transfer::SharedFileInfo {
+            fs_id,
+            is_dir,
+            path,
+            size,
+            name,
+            dlink,
+            md5,
+        }
     }
 
     /// 执行转存
@@ -2540,6 +3141,7 @@ impl NetdiskClient {
                 from_paths,
                 error: None,
                 transferred_fs_ids,
+                errno: None,
             })
         } else if errno == 12 {
             // 部分错误
@@ -2559,6 +3161,7 @@ impl NetdiskClient {
                             from_paths: vec![],
                             error: Some(format!("同名文件已存在: {}", filename)),
                             transferred_fs_ids: vec![],
+                            errno: Some(errno),
                         });
                     }
                 }
@@ -2577,6 +3180,7 @@ impl NetdiskClient {
                         target_file_nums, target_file_nums_limit
                     )),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 });
             }
 
@@ -2586,6 +3190,7 @@ impl NetdiskClient {
                 from_paths: vec![],
                 error: Some(format!("转存失败: {}", response_text)),
                 transferred_fs_ids: vec![],
+                errno: Some(errno),
             })
         } else if errno == 4 {
             // errno=4 + duplicated 字段 = 文件/文件夹重复
@@ -2612,6 +3217,7 @@ impl NetdiskClient {
                     from_paths: vec![],
                     error: Some(error_msg),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 })
             } else {
                 // 没有 duplicated 字段，可能是真的超时
@@ -2622,6 +3228,7 @@ impl NetdiskClient {
                     from_paths: vec![],
                     error: Some(show_msg),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 })
             }
         } else {
@@ -2631,6 +3238,7 @@ impl NetdiskClient {
                 from_paths: vec![],
                 error: Some(format!("转存失败: {}", response_text)),
                 transferred_fs_ids: vec![],
+                errno: Some(errno),
             })
         }
     }
@@ -2815,6 +3423,7 @@ impl NetdiskClient {
                         from_paths,
                         error: None,
                         transferred_fs_ids,
+                        errno: None,
                     });
                 }
                 "failed" => {
@@ -3024,15 +3633,20 @@ impl NetdiskClient {
     /// * `save_path` - 网盘保存路径（默认为根目录 "/"）
     ///
     /// # 返回
-    /// 新创建的任务 ID
+    /// 新创建的任务 ID；链接校验失败或 API 拒绝时返回 `CloudDlAddTaskError`
     pub async fn cloud_dl_add_task(&self, source_url: &str, save_path: &str) -> Result<i64> {
         info!("添加离线下载任务: source_url={}, save_path={}", source_url, save_path);
 
-        // 标准化磁力链接（将 Base32 转换为十六进制，小写转大写）
-        let normalized_url = Self::normalize_magnet_link(source_url);
+        // 校验链接协议和格式，不支持的链接直接拒绝，不调用 API
+        let source = crate::netdisk::cloud_dl::parse_source_url(source_url)?;
+        let is_magnet = source.kind == crate::netdisk::cloud_dl::CloudDlSourceKind::Magnet;
 
-        // 判断是否为磁力链接
-        let is_magnet = normalized_url.starts_with("magnet:");
+        // 标准化磁力链接（将 Base32 转换为十六进制，小写转大写）
+        let normalized_url = if is_magnet {
+            Self::normalize_magnet_link(&source.url)
+        } else {
+            source.url
+        };
 
         // 对于磁力链接，需要先查询文件列表，然后选择所有文件
         let selected_idx = if is_magnet {
@@ -3095,7 +3709,11 @@ impl NetdiskClient {
                 "添加离线任务失败: error_code={}, error_msg={}",
                 error_code, error_msg
             );
-            anyhow::bail!("添加离线任务失败: {}", error_msg);
+            return Err(crate::netdisk::cloud_dl::CloudDlAddTaskError::ApiRejected {
+                code: error_code,
+                message: error_msg,
+            }
+            .into());
         }
 
         info!("添加离线任务成功: task_id={}", api_response.task_id);
@@ -3115,6 +3733,48 @@ impl NetdiskClient {
         self.cloud_dl_list_task_with_params(0, 1000, 255).await
     }
 
+    /// 查询离线下载任务列表，并补全进行中任务的进度
+    ///
+    /// list_task 接口不返回进行中任务的已下载大小，需要再调用 query_task 查询详情；
+    /// 详情查询失败时保留列表数据
+    pub async fn cloud_dl_list_task_with_progress(
+        &self,
+    ) -> Result<Vec<crate::netdisk::CloudDlTaskInfo>> {
+        let mut tasks = self.cloud_dl_list_task().await?;
+
+        let running_task_ids: Vec<i64> = tasks
+            .iter()
+            .filter(|t| t.get_status() == crate::netdisk::CloudDlTaskStatus::Running)
+            .map(|t| t.task_id)
+            .collect();
+
+        if !running_task_ids.is_empty() {
+            match self.cloud_dl_query_task(&running_task_ids).await {
+                Ok(details) => {
+                    let detail_map: std::collections::HashMap<i64, crate::netdisk::CloudDlTaskInfo> =
+                        details.into_iter().map(|t| (t.task_id, t)).collect();
+
+                    for task in &mut tasks {
+                        if let Some(detail) = detail_map.get(&task.task_id) {
+                            task.file_size = detail.file_size;
+                            task.finished_size = detail.finished_size;
+                            task.start_time = detail.start_time;
+                            task.finish_time = detail.finish_time;
+                            task.file_list = detail.file_list.clone();
+                            task.refresh_progress();
+                        }
+                    }
+                    debug!("已更新 {} 个进行中任务的进度信息", detail_map.len());
+                }
+                Err(e) => {
+                    warn!("查询任务详情失败: {}", e);
+                }
+            }
+        }
+
+        Ok(tasks)
+    }
+
     /// 查询离线下载任务列表（带参数）
     ///
     /// # 参数
@@ -3793,10 +4453,21 @@ impl NetdiskClient {
                 api_response.errmsg.clone()
             };
 
-            // errno 12: 文件不存在（可能已被删除），视为成功（幂等性）
+            // errno 12: 部分失败，解析 info 列表确定具体失败的路径
             if api_response.errno == 12 {
-                warn!("文件不存在（errno=12），视为删除成功");
-                Ok(DeleteFilesResponse::success(paths.len()))
+                let failed_paths = api_response.failed_paths();
+                if failed_paths.is_empty() {
+                    // 未返回失败明细或仅文件不存在，视为成功（幂等性）
+                    warn!("删除返回 errno=12 但无失败路径，视为删除成功");
+                    Ok(DeleteFilesResponse::success(paths.len()))
+                } else {
+                    let deleted_count = paths.len().saturating_sub(failed_paths.len());
+                    warn!(
+                        "部分文件删除失败(errno=12): 成功={}, 失败={:?}",
+                        deleted_count, failed_paths
+                    );
+                    Ok(DeleteFilesResponse::partial_success(deleted_count, failed_paths))
+                }
             } else {
                 // 风控/删除失败诊断摘要（不打印完整 safesign 避免日志泄露）
                 if api_response.errno == 132 {
@@ -3827,6 +4498,411 @@ impl NetdiskClient {
             }
         }
     }
+
+    /// 重命名网盘文件/目录
+    ///
+    /// # 参数
+    /// * `path` - 原文件完整路径
+    /// * `new_name` - 新文件名（不含路径）
+    pub async fn rename_file(
+        &self,
+        path: &str,
+        new_name: &str,
+    ) -> Result<crate::netdisk::FileOperationResponse> {
+        let filelist = serde_json::json!([{ "path": path, "newname": new_name }]);
+        self.file_manager_operation(crate::netdisk::FileOperation::Rename, &filelist, 1)
+            .await
+    }
+
+    /// 移动网盘文件/目录到指定目录（保留原文件名）
+    ///
+    /// # 参数
+    /// * `sources` - 源文件完整路径列表
+    /// * `dest_dir` - 目标目录
+    pub async fn move_files(
+        &self,
+        sources: &[String],
+        dest_dir: &str,
+    ) -> Result<crate::netdisk::FileOperationResponse> {
+        let filelist = Self::build_transfer_filelist(sources, dest_dir);
+        self.file_manager_operation(crate::netdisk::FileOperation::Move, &filelist, sources.len())
+            .await
+    }
+
+    /// 复制网盘文件/目录到指定目录（保留原文件名）
+    ///
+    /// # 参数
+    /// * `sources` - 源文件完整路径列表
+    /// * `dest_dir` - 目标目录
+    pub async fn copy_files(
+        &self,
+        sources: &[String],
+        dest_dir: &str,
+    ) -> Result<crate::netdisk::FileOperationResponse> {
+        let filelist = Self::build_transfer_filelist(sources, dest_dir);
+        self.file_manager_operation(crate::netdisk::FileOperation::Copy, &filelist, sources.len())
+            .await
+    }
+
+    /// 构建移动/复制的 filelist: [{"path","dest","newname"}]
+    fn build_transfer_filelist(sources: &[String], dest_dir: &str) -> Value {
+        let dest = if dest_dir.len() > 1 {
+            dest_dir.trim_end_matches('/')
+        } else {
+            dest_dir
+        };
+
+        let items: Vec<Value> = sources
+            .iter()
+            .map(|source| {
+                let newname = source
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default();
+                serde_json::json!({ "path": source, "dest": dest, "newname": newname })
+            })
+            .collect();
+
+        Value::Array(items)
+    }
+
+    /// 调用 filemanager API 执行重命名/移动/复制
+    ///
+    /// 使用 async=2 提交，服务端返回非 0 的 taskid 时轮询任务直至完成
+    async fn file_manager_operation(
+        &self,
+        operation: crate::netdisk::FileOperation,
+        filelist: &Value,
+        item_count: usize,
+    ) -> Result<crate::netdisk::FileOperationResponse> {
+        use crate::netdisk::{FileOperationApiResponse, FileOperationResponse};
+
+        let op_name = operation.display_name();
+
+        if item_count == 0 {
+            return Ok(FileOperationResponse::success(0, None));
+        }
+
+        info!("{}网盘文件: filelist={}", op_name, filelist);
+
+        // 获取 bdstoken
+        let bdstoken = {
+            let token_guard = self.bdstoken.lock().await;
+            match token_guard.as_ref() {
+                Some(token) if !token.is_empty() => token.clone(),
+                _ => return Err(anyhow::anyhow!("bdstoken 尚未获取，无法{}文件", op_name)),
+            }
+        };
+
+        let filelist_json = serde_json::to_string(filelist).context("序列化文件列表失败")?;
+
+        let url = format!(
+            "https://pan.baidu.com/api/filemanager?opera={}&async=2&onnest=fail&bdstoken={}&newVerify=1&clienttype=0&app_id={}&web=1",
+            operation.as_opera(),
+            urlencoding::encode(&bdstoken),
+            BAIDU_APP_ID
+        );
+
+        // 收集 cookies 并创建独立 client（与 create_folder 一致）
+        let merged_cookie_str = self.collect_all_baidu_cookies().await?;
+        let pan_client = self.build_temp_client_with_proxy()?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", HeaderValue::from_str(&self.web_user_agent)?);
+        headers.insert("Cookie", HeaderValue::from_str(&merged_cookie_str)?);
+
+        let response = pan_client
+            .post(&url)
+            .headers(headers.clone())
+            .form(&[(operation.form_field(), filelist_json.as_str()), ("ondup", "fail")])
+            .send()
+            .await;
+
+        let response = match response {
+            Ok(resp) => {
+                self.record_proxy_success();
+                resp
+            }
+            Err(e) => {
+                let err = anyhow::Error::from(e).context(format!("{}文件请求失败", op_name));
+                self.record_proxy_failure(&err);
+                return Err(err);
+            }
+        };
+
+        let response_text = response
+            .text()
+            .await
+            .with_context(|| format!("读取{}文件响应失败", op_name))?;
+
+        info!("{}文件响应: {}", op_name, response_text);
+
+        let api_response: FileOperationApiResponse = serde_json::from_str(&response_text)
+            .with_context(|| format!("解析{}文件响应失败", op_name))?;
+
+        if api_response.is_success() {
+            if api_response.taskid == 0 {
+                info!("{}文件成功: {} 个", op_name, item_count);
+                return Ok(FileOperationResponse::success(item_count, None));
+            }
+
+            // 异步任务：轮询直到完成
+            let task_id = api_response.taskid;
+            info!("{}文件为异步任务: taskid={}，开始轮询", op_name, task_id);
+            self.wait_file_manager_task(&pan_client, &headers, task_id, &bdstoken, op_name)
+                .await?;
+            info!("{}文件异步任务完成: taskid={}", op_name, task_id);
+            return Ok(FileOperationResponse::success(item_count, Some(task_id)));
+        }
+
+        // errno 12: 部分失败，解析 info 列表确定具体失败的路径
+        if api_response.errno == 12 {
+            let failed_paths = api_response.failed_paths();
+            if !failed_paths.is_empty() {
+                let success_count = item_count.saturating_sub(failed_paths.len());
+                warn!(
+                    "部分文件{}失败(errno=12): 成功={}, 失败={:?}",
+                    op_name, success_count, failed_paths
+                );
+                return Ok(FileOperationResponse::partial_success(success_count, failed_paths));
+            }
+        }
+
+        let error_msg = if api_response.errmsg.is_empty() {
+            format!("{}失败: errno={}", op_name, api_response.errno)
+        } else {
+            api_response.errmsg.clone()
+        };
+
+        error!(
+            "{}文件失败: errno={}, errmsg={}",
+            op_name, api_response.errno, error_msg
+        );
+        Ok(FileOperationResponse::failure(error_msg, Some(api_response.errno)))
+    }
+
+    /// 轮询 filemanager 异步任务直到完成
+    ///
+    /// 每秒查询一次，最多等待约 2 分钟
+    async fn wait_file_manager_task(
+        &self,
+        pan_client: &Client,
+        headers: &HeaderMap,
+        task_id: u64,
+        bdstoken: &str,
+        op_name: &str,
+    ) -> Result<()> {
+        const MAX_ATTEMPTS: u32 = 120;
+
+        let url = format!(
+            "https://pan.baidu.com/share/taskquery?taskid={}&bdstoken={}&app_id={}&channel=chunlei&clienttype=0&web=1",
+            task_id,
+            urlencoding::encode(bdstoken),
+            BAIDU_APP_ID
+        );
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+            let response_text = pan_client
+                .get(&url)
+                .headers(headers.clone())
+                .send()
+                .await
+                .context("查询异步任务请求失败")?
+                .text()
+                .await
+                .context("读取异步任务响应失败")?;
+
+            debug!("{}任务查询响应 (尝试 {}): {}", op_name, attempt, response_text);
+
+            let json: Value =
+                serde_json::from_str(&response_text).context("解析异步任务响应失败")?;
+
+            let errno = json["errno"].as_i64().unwrap_or(-1);
+            if errno != 0 {
+                return Err(anyhow::anyhow!(
+                    "{}任务查询 API 错误: errno={}",
+                    op_name,
+                    errno
+                ));
+            }
+
+            let task_errno = json["task_errno"].as_i64().unwrap_or(0);
+            if task_errno != 0 {
+                return Err(anyhow::anyhow!(
+                    "{}任务失败: task_errno={}",
+                    op_name,
+                    task_errno
+                ));
+            }
+
+            match json["status"].as_str().unwrap_or("") {
+                "success" => return Ok(()),
+                "failed" => {
+                    return Err(anyhow::anyhow!("{}任务失败: status=failed", op_name));
+                }
+                status => {
+                    debug!(
+                        "{}任务仍在进行 (taskid={}, status='{}', 尝试 {})",
+                        op_name, task_id, status, attempt
+                    );
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "{}任务超时: taskid={} 在 {} 秒内未完成",
+            op_name,
+            task_id,
+            MAX_ATTEMPTS
+        ))
+    }
+
+    /// 获取回收站文件列表
+    ///
+    /// # 参数
+    /// * `page` - 页码（从1开始）
+    /// * `num` - 每页数量
+    pub async fn list_recycle_bin(
+        &self,
+        page: u32,
+        num: u32,
+    ) -> Result<Vec<crate::netdisk::RecycleItem>> {
+        use crate::netdisk::RecycleListResponse;
+
+        info!("获取回收站列表: page={}, num={}", page, num);
+
+        let start = page.saturating_sub(1).saturating_mul(num);
+        let url = "https://pcs.baidu.com/rest/2.0/pcs/file";
+
+        let response = self
+            .client
+            .get(url)
+            .query(&[
+                ("method", "listrecycle"),
+                ("app_id", &BAIDU_APP_ID.to_string()),
+                ("start", &start.to_string()),
+                ("limit", &num.to_string()),
+            ])
+            .header("Cookie", format!("BDUSS={}", self.bduss()))
+            .header("User-Agent", &self.mobile_user_agent)
+            .send()
+            .await;
+
+        let response = match response {
+            Ok(resp) => {
+                self.record_proxy_success();
+                resp
+            }
+            Err(e) => {
+                let err = anyhow::Error::from(e).context("获取回收站列表请求失败");
+                self.record_proxy_failure(&err);
+                return Err(err);
+            }
+        };
+
+        let response_text = response.text().await.context("读取回收站列表响应失败")?;
+        debug!("回收站列表响应: {}", response_text);
+
+        let recycle_list: RecycleListResponse =
+            serde_json::from_str(&response_text).context("解析回收站列表响应失败")?;
+
+        if !recycle_list.is_success() {
+            anyhow::bail!(
+                "获取回收站列表失败: errno={}, error_code={}, error_msg={}",
+                recycle_list.errno,
+                recycle_list.error_code,
+                recycle_list.error_msg
+            );
+        }
+
+        let items = recycle_list
+            .list
+            .into_iter()
+            .map(|mut item| {
+                if item.server_filename.is_empty() {
+                    item.server_filename = item
+                        .path
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+                }
+                item
+            })
+            .collect::<Vec<_>>();
+
+        debug!("回收站中获取到 {} 个文件/文件夹", items.len());
+        Ok(items)
+    }
+
+    /// 从回收站还原文件
+    ///
+    /// # 参数
+    /// * `fs_ids` - 回收站中文件的 fs_id 列表
+    pub async fn restore_from_recycle(
+        &self,
+        fs_ids: &[u64],
+    ) -> Result<crate::netdisk::FileOperationResponse> {
+        let fidlist = serde_json::json!(fs_ids);
+        self.file_manager_operation(crate::netdisk::FileOperation::Restore, &fidlist, fs_ids.len())
+            .await
+    }
+
+    /// 清空回收站
+    pub async fn clean_recycle_bin(&self) -> Result<()> {
+        info!("清空回收站");
+
+        let bdstoken = {
+            let token_guard = self.bdstoken.lock().await;
+            match token_guard.as_ref() {
+                Some(token) if !token.is_empty() => token.clone(),
+                _ => return Err(anyhow::anyhow!("bdstoken 尚未获取，无法清空回收站")),
+            }
+        };
+
+        let url = format!(
+            "https://pan.baidu.com/api/recycle/clear?async=1&bdstoken={}&clienttype=0&app_id={}&web=1",
+            urlencoding::encode(&bdstoken),
+            BAIDU_APP_ID
+        );
+
+        // 收集 cookies 并创建独立 client（与 create_folder 一致）
+        let merged_cookie_str = self.collect_all_baidu_cookies().await?;
+        let pan_client = self.build_temp_client_with_proxy()?;
+
+        let response = pan_client
+            .post(&url)
+            .header("User-Agent", &self.web_user_agent)
+            .header("Cookie", merged_cookie_str)
+            .send()
+            .await;
+
+        let response = match response {
+            Ok(resp) => {
+                self.record_proxy_success();
+                resp
+            }
+            Err(e) => {
+                let err = anyhow::Error::from(e).context("清空回收站请求失败");
+                self.record_proxy_failure(&err);
+                return Err(err);
+            }
+        };
+
+        let response_text = response.text().await.context("读取清空回收站响应失败")?;
+        info!("清空回收站响应: {}", response_text);
+
+        let json: Value = serde_json::from_str(&response_text).context("解析清空回收站响应失败")?;
+        let errno = json["errno"].as_i64().unwrap_or(-1);
+        if errno != 0 {
+            anyhow::bail!("清空回收站失败: errno={}", errno);
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -3849,6 +4925,39 @@ mod tests {
         assert_eq!(client.bduss(), user_auth.bduss);
     }
 
+    #[test]
+    fn test_append_list_pages() {
+        let mut out = Vec::new();
+        assert!(!append_list_pages(&mut out, vec![vec![1, 2], vec![3, 4]], 2));
+        // 最后一页不满一页时停止，之后的空页丢弃
+        assert!(append_list_pages(&mut out, vec![vec![5], vec![], vec![]], 2));
+        assert_eq!(out, vec![1, 2, 3, 4, 5]);
+
+        // 恰好整页结束时下一批返回空页
+        let mut out = vec![1, 2];
+        assert!(append_list_pages(&mut out, vec![vec![]], 2));
+        assert_eq!(out, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_select_dlink_index() {
+        let urls: Vec<String> = [
+            "https://d.pcs.baidu.com/file/a",
+            "http://nb.cache.baidu.com/file/a",
+            "https://bjbgp01.baidupcs.com/file/a",
+        ]
+        .iter()
+        .map(|url| url.to_string())
+        .collect();
+
+        assert_eq!(NetdiskClient::select_dlink_index(&urls, 0), 0);
+        // nb.cache 自动跳到下一个链接
+        assert_eq!(NetdiskClient::select_dlink_index(&urls, 1), 2);
+        // 超出范围使用最后一个链接
+        assert_eq!(NetdiskClient::select_dlink_index(&urls, 9), 2);
+        assert_eq!(NetdiskClient::select_dlink_index(&urls[..2], 1), 1);
+    }
+
     #[test]
     fn test_default_user_agent() {
         let ua = NetdiskClient::default_mobile_user_agent();
@@ -3987,6 +5096,258 @@ mod tests {
         assert!(response.is_success());
         assert_eq!(response.errmsg, "");
         assert_eq!(response.request_id, 0);
+        assert!(response.info.is_empty());
+    }
+
+    #[test]
+    fn test_delete_files_api_response_partial_failure() {
+        use crate::netdisk::DeleteFilesApiResponse;
+
+        // errno=12 部分失败，info 中逐个返回路径结果
+        let json = r#"{"errno": 12, "info": [
+            {"errno": 0, "path": "/a.txt"},
+            {"errno": -9, "path": "/gone.txt"},
+            {"errno": 31066, "path": "/b.txt"}
+        ]}"#;
+        let response: DeleteFilesApiResponse = serde_json::from_str(json).unwrap();
+
+        assert!(!response.is_success());
+        assert_eq!(response.info.len(), 3);
+        // 文件不存在视为已删除，只有 /b.txt 失败
+        assert_eq!(response.failed_paths(), vec!["/b.txt".to_string()]);
+    }
+
+    #[test]
+    fn test_build_transfer_filelist() {
+        let sources = vec!["/a/file.txt".to_string(), "/a/sub/".to_string()];
+        let filelist = NetdiskClient::build_transfer_filelist(&sources, "/dest/");
+
+        let items = filelist.as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["path"], "/a/file.txt");
+        assert_eq!(items[0]["dest"], "/dest");
+        assert_eq!(items[0]["newname"], "file.txt");
+        assert_eq!(items[1]["newname"], "sub");
+
+        // 根目录不做裁剪
+        let filelist = NetdiskClient::build_transfer_filelist(&sources[..1], "/");
+        assert_eq!(filelist[0]["dest"], "/");
+    }
+
+    #[test]
+    fn test_search_response_has_more() {
+        let json = r#"{"errno": 0, "has_more": 1, "list": [
+            {"fs_id": 1, "path": "/a/report.pdf", "server_filename": "report.pdf", "size": 10,
+             "isdir": 0, "category": 4, "server_ctime": 0, "server_mtime": 0, "local_ctime": 0, "local_mtime": 0}
+        ]}"#;
+        let response: FileListResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.has_more, 1);
+        assert_eq!(response.list.len(), 1);
+
+        // 普通列表接口不返回 has_more
+        let response: FileListResponse = serde_json::from_str(r#"{"errno": 0}"#).unwrap();
+        assert_eq!(response.has_more, 0);
+    }
+
+    #[test]
+    fn test_recycle_list_response_parse() {
+        use crate::netdisk::RecycleListResponse;
+
+        let json = r#"{"list": [
+            {"fs_id": 1, "path": "/a/b.txt", "size": 10, "isdir": 0, "mtime": 1700000000, "md5": "abc"},
+            {"fs_id": 2, "path": "/dir", "server_filename": "dir", "isdir": 1, "server_mtime": 1700000001, "leftTime": 9}
+        ], "request_id": 1}"#;
+        let response: RecycleListResponse = serde_json::from_str(json).unwrap();
+
+        assert!(response.is_success());
+        assert_eq!(response.list.len(), 2);
+        assert_eq!(response.list[0].delete_time, 1700000000);
+        assert_eq!(response.list[1].delete_time, 1700000001);
+        assert_eq!(response.list[1].left_time, 9);
+
+        let json = r#"{"error_code": 31045, "error_msg": "user not exists"}"#;
+        let response: RecycleListResponse = serde_json::from_str(json).unwrap();
+        assert!(!response.is_success());
+    }
+
+    #[test]
+    fn test_file_operation_api_response_async_task() {
+        use crate::netdisk::FileOperationApiResponse;
+
+        let json = r#"{"errno": 0, "taskid": 123456789, "request_id": 1}"#;
+        let response: FileOperationApiResponse = serde_json::from_str(json).unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.taskid, 123456789);
+
+        let json = r#"{"errno": 12, "info": [{"errno": 0, "path": "/a"}, {"errno": -8, "path": "/b"}]}"#;
+        let response: FileOperationApiResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.failed_paths(), vec!["/b".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_shared_file_item_with_dlink() {
+        // fs_id/size 为字符串、携带 dlink 的分享文件条目
+        let item = serde_json::json!({
+            "fs_id": "123456",
+            "isdir": 0,
+            "path": "/share/a.txt",
+            "size": "1024",
+            "server_filename": "a.txt",
+            "dlink": "https://d.pcs.baidu.com/file/abc"
+        });
+        let file = NetdiskClient::parse_shared_file_item(&item);
+        assert_eq!(file.fs_id, 123456);
+        assert!(!file.is_dir);
+        assert_eq!(file.size, 1024);
+        assert_eq!(file.dlink.as_deref(), Some("https://d.pcs.baidu.com/file/abc"));
+
+        // 目录条目不带 dlink，空字符串视为无直链
+        let dir = serde_json::json!({
+            "fs_id": 7,
+            "isdir": "1",
+            "path": "/share/dir",
+            "server_filename": "dir",
+            "dlink": ""
+        });
+        let dir = NetdiskClient::parse_shared_file_item(&dir);
+        assert!(dir.is_dir);
+        assert!(dir.dlink.is_none());
+    }
+
+    #[test]
+    fn test_parse_quota() {
+        let quota = NetdiskClient::parse_quota(&serde_json::json!({
+            "errno": 0,
+            "total": 2_199_023_255_552u64,
+            "used": 1_099_511_627_776u64,
+            "free": 1_099_511_627_776u64
+        }))
+            .unwrap();
+        assert_eq!(quota.used, 1_099_511_627_776);
+        assert_eq!(quota.free, 1_099_511_627_776);
+
+        // 缺少 free 时按 total - used 计算
+        let quota = NetdiskClient::parse_quota(&serde_json::json!({"errno": 0, "total": 100, "used": 30})).unwrap();
+        assert_eq!(quota.free, 70);
+
+        assert!(NetdiskClient::parse_quota(&serde_json::json!({"errno": -6})).is_err());
+        assert!(NetdiskClient::parse_quota(&serde_json::json!({"errno": 0})).is_err());
+    }
+
+    #[test]
+    fn test_filemetas_thumbs() {
+        let response: crate::netdisk::FileMetasResponse = serde_json::from_value(serde_json::json!({
+            "errno": 0,
+            "list": [{
+                "fs_id": 123,
+                "path": "/相册/a.jpg",
+                "server_filename": "a.jpg",
+                "size": 1024,
+                "isdir": 0,
+                "server_ctime": 0,
+                "server_mtime": 0,
+                "thumbs": {
+                    "icon": "https://thumbnail0.baidupcs.com/icon",
+                    "url1": "https://thumbnail0.baidupcs.com/small",
+                    "url2": "",
+                    "url3": "https://thumbnail0.baidupcs.com/large"
+                }
+            }]
+        }))
+            .unwrap();
+
+        let thumbs = response.list[0].thumbs.as_ref().unwrap();
+        assert_eq!(thumbs.url(ThumbnailSize::Small), Some("https://thumbnail0.baidupcs.com/small"));
+        assert_eq!(thumbs.url(ThumbnailSize::Large), Some("https://thumbnail0.baidupcs.com/large"));
+        // 空链接视为没有该尺寸
+        assert_eq!(thumbs.url(ThumbnailSize::Medium), None);
+    }
+
+    #[test]
+    fn test_filemetas_dlink_to_locate_response() {
+        let metas: crate::netdisk::FileMetasResponse = serde_json::from_value(serde_json::json!({
+            "errno": 0,
+            "list": [{
+                "fs_id": 123,
+                "path": "/影视/a.mp4",
+                "server_filename": "a.mp4",
+                "size": 2048,
+                "isdir": 0,
+                "md5": "0cc175b9c0f1b6a831c399e269772661",
+                "server_ctime": 0,
+                "server_mtime": 0,
+                "dlink": "https://d.pcs.baidu.com/file/abc?fid=123"
+            }, {
+                "fs_id": 456,
+                "path": "/影视",
+                "server_filename": "影视",
+                "size": 0,
+                "isdir": 1,
+                "server_ctime": 0,
+                "server_mtime": 0,
+                "dlink": ""
+            }]
+        }))
+            .unwrap();
+
+        let response = LocateDownloadResponse::from(metas);
+        assert_eq!(response.list.len(), 2);
+
+        let file = &response.list[0];
+        assert_eq!(file.path, "/影视/a.mp4");
+        assert_eq!(file.size, 2048);
+        assert_eq!(file.md5.as_deref(), Some("0cc175b9c0f1b6a831c399e269772661"));
+        assert_eq!(file.best_download_url().unwrap().url, "https://d.pcs.baidu.com/file/abc?fid=123");
+
+        // 文件夹没有下载链接
+        assert_eq!(response.list[1].isdir, 1);
+        assert!(response.list[1].dlink.is_empty());
+    }
+
+    #[test]
+    fn test_streaming_url() {
+        assert_eq!(
+            NetdiskClient::streaming_url("/视频/a b.mp4"),
+            "https://pan.baidu.com/rest/2.0/xpan/file?method=streaming&path=%2F%E8%A7%86%E9%A2%91%2Fa%20b.mp4&type=M3U8_AUTO_720"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ensure_free_space_uses_cached_quota() {
+        let client = NetdiskClient::new(create_test_user_auth()).unwrap();
+        *client.quota_cache.lock() = Some((
+            std::time::Instant::now(),
+            QuotaInfo {
+                total: 1000,
+                used: 900,
+                free: 100,
+            },
+        ));
+
+        assert!(client.ensure_free_space(0).await.is_ok());
+        assert!(client.ensure_free_space(100).await.is_ok());
+        let err = client.ensure_free_space(101).await.unwrap_err();
+        assert_eq!(NetdiskError::find(&err), Some(NetdiskError::QuotaExceeded));
+        assert!(err.to_string().contains("网盘空间不足"));
+    }
+
+    #[test]
+    fn test_parse_share_captcha() {
+        let captcha = NetdiskClient::parse_share_captcha(&serde_json::json!({
+            "errno": 0,
+            "vcode_str": "abc123",
+            "vcode_img": "https://pan.baidu.com/genimage?abc123"
+        }))
+            .unwrap();
+        assert_eq!(captcha.vcode_str, "abc123");
+        assert_eq!(captcha.img_url, "https://pan.baidu.com/genimage?abc123");
+
+        // 缺少图片地址时按 vcode_str 拼接
+        let captcha = NetdiskClient::parse_share_captcha(&serde_json::json!({"errno": 0, "vcode_str": "xyz"})).unwrap();
+        assert_eq!(captcha.img_url, "https://pan.baidu.com/genimage?xyz");
+
+        assert!(NetdiskClient::parse_share_captcha(&serde_json::json!({"errno": -6})).is_err());
+        assert!(NetdiskClient::parse_share_captcha(&serde_json::json!({"errno": 0})).is_err());
     }
 }
 
@@ -4133,6 +5494,7 @@ mod transfer_preservation_tests {
                     from_paths: vec![],
                     error: Some("异步模式不在保留测试范围内".to_string()),
                     transferred_fs_ids: vec![],
+                    errno: None,
                 };
             }
 
@@ -4162,6 +5524,7 @@ mod transfer_preservation_tests {
                 from_paths,
                 error: None,
                 transferred_fs_ids,
+                errno: None,
             }
         } else if errno == 12 {
             // 部分错误
@@ -4181,6 +5544,7 @@ mod transfer_preservation_tests {
                             from_paths: vec![],
                             error: Some(format!("同名文件已存在: {}", filename)),
                             transferred_fs_ids: vec![],
+                            errno: Some(errno),
                         };
                     }
                 }
@@ -4199,6 +5563,7 @@ mod transfer_preservation_tests {
                         target_file_nums, target_file_nums_limit
                     )),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 };
             }
 
@@ -4208,6 +5573,7 @@ mod transfer_preservation_tests {
                 from_paths: vec![],
                 error: Some(format!("转存失败: {}", response_text)),
                 transferred_fs_ids: vec![],
+                errno: Some(errno),
             }
         } else if errno == 4 {
             // errno=4 + duplicated 字段 = 文件/文件夹重复
@@ -4234,6 +5600,7 @@ mod transfer_preservation_tests {
                     from_paths: vec![],
                     error: Some(error_msg),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 }
             } else {
                 let show_msg = json["show_msg"].as_str().unwrap_or("请求超时").to_string();
@@ -4243,6 +5610,7 @@ mod transfer_preservation_tests {
                     from_paths: vec![],
                     error: Some(show_msg),
                     transferred_fs_ids: vec![],
+                    errno: Some(errno),
                 }
             }
         } else {
@@ -4252,6 +5620,7 @@ mod transfer_preservation_tests {
                 from_paths: vec![],
                 error: Some(format!("转存失败: {}", response_text)),
                 transferred_fs_ids: vec![],
+                errno: Some(errno),
             }
         }
     }
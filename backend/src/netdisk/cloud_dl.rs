@@ -5,8 +5,10 @@
 //! - 任务信息结构体
 //! - 请求/响应类型
 //! - 自动下载配置
+//! - 源链接校验
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 // =====================================================
 // 任务状态枚举
@@ -150,6 +152,9 @@ pub struct CloudDlTaskInfo {
     pub file_list: Vec<CloudDlFileInfo>,
     /// 结果码
     pub result: i32,
+    /// 下载进度百分比（0.0 - 100.0，由 file_size/finished_size 计算）
+    #[serde(default)]
+    pub progress_percent: f32,
 }
 
 impl CloudDlTaskInfo {
@@ -168,6 +173,11 @@ impl CloudDlTaskInfo {
         ((self.finished_size as f64 / self.file_size as f64) * 100.0) as f32
     }
 
+    /// 根据当前大小重新计算 progress_percent 字段
+    pub fn refresh_progress(&mut self) {
+        self.progress_percent = self.progress_percent();
+    }
+
     /// 判断任务是否已完成
     pub fn is_finished(&self) -> bool {
         self.get_status().is_finished()
@@ -196,16 +206,100 @@ pub struct AddTaskRequest {
     /// 网盘保存路径（默认为根目录 "/"）
     #[serde(default = "default_save_path")]
     pub save_path: String,
-    /// 是否启用自动下载到本地
+    /// 是否启用自动下载到本地（未指定时使用全局配置 cloud_dl.auto_download）
     #[serde(default)]
-    pub auto_download: bool,
-    /// 本地下载目录（自动下载时使用）
+    pub auto_download: Option<bool>,
+    /// 本地下载目录（自动下载时使用，为空时使用全局配置的目录）
     pub local_download_path: Option<String>,
     /// 完成时是否询问下载目录
     #[serde(default)]
     pub ask_download_path: bool,
 }
 
+impl AddTaskRequest {
+    /// 解析自动下载配置
+    ///
+    /// 请求未指定 auto_download 时使用全局配置；本地目录依次取请求目录、
+    /// 全局 cloud_dl.local_dir、默认下载目录。未启用时返回 None
+    pub fn resolve_auto_download(
+        &self,
+        task_id: i64,
+        cloud_dl_config: &crate::config::CloudDlConfig,
+        default_dir: &std::path::Path,
+    ) -> Option<AutoDownloadConfig> {
+        if !self.auto_download.unwrap_or(cloud_dl_config.auto_download) {
+            return None;
+        }
+
+        let local_path = self
+            .local_download_path
+            .clone()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| {
+                cloud_dl_config
+                    .local_dir
+                    .as_deref()
+                    .unwrap_or(default_dir)
+                    .to_string_lossy()
+                    .to_string()
+            });
+
+        Some(AutoDownloadConfig::enabled(
+            task_id,
+            Some(local_path),
+            self.ask_download_path,
+        ))
+    }
+}
+
+/// 批量添加任务的最大链接数
+pub const BATCH_ADD_MAX_URLS: usize = 100;
+
+/// 批量添加任务时相邻请求的间隔（毫秒），避免触发百度频率限制
+pub const BATCH_ADD_INTERVAL_MS: u64 = 1000;
+
+/// 批量添加离线下载任务请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchAddTaskRequest {
+    /// 下载源链接列表
+    pub urls: Vec<String>,
+    /// 网盘保存路径（默认为根目录 "/"）
+    #[serde(default = "default_save_path")]
+    pub save_path: String,
+    /// 是否启用自动下载到本地（未指定时使用全局配置 cloud_dl.auto_download）
+    #[serde(default)]
+    pub auto_download: Option<bool>,
+    /// 本地下载目录（自动下载时使用，为空时使用全局配置的目录）
+    pub local_download_path: Option<String>,
+    /// 完成时是否询问下载目录
+    #[serde(default)]
+    pub ask_download_path: bool,
+}
+
+impl BatchAddTaskRequest {
+    /// 去除空白行和重复链接，保持原有顺序
+    pub fn normalized_urls(&self) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        self.urls
+            .iter()
+            .map(|url| url.trim())
+            .filter(|url| !url.is_empty() && seen.insert(url.to_string()))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 生成单个链接的添加请求（共用保存路径和自动下载设置）
+    pub fn task_request(&self, source_url: &str) -> AddTaskRequest {
+        AddTaskRequest {
+            source_url: source_url.to_string(),
+            save_path: self.save_path.clone(),
+            auto_download: self.auto_download,
+            local_download_path: self.local_download_path.clone(),
+            ask_download_path: self.ask_download_path,
+        }
+    }
+}
+
 /// 查询任务请求
 #[derive(Debug, Clone, Deserialize)]
 pub struct QueryTaskRequest {
@@ -244,6 +338,44 @@ fn default_status_filter() -> u32 {
 pub struct AddTaskResponse {
     /// 新创建的任务 ID
     pub task_id: i64,
+    /// 添加后查询到的任务详情（查询失败时为 null）
+    pub task: Option<CloudDlTaskInfo>,
+}
+
+/// 批量添加中单个链接的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchAddTaskItem {
+    /// 下载源链接
+    pub source_url: String,
+    /// 新创建的任务 ID（失败时为 null）
+    pub task_id: Option<i64>,
+    /// 失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 批量添加任务响应
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchAddTaskResponse {
+    /// 每个链接的结果（与请求顺序一致）
+    pub results: Vec<BatchAddTaskItem>,
+    /// 成功数量
+    pub success_count: usize,
+    /// 失败数量
+    pub failed_count: usize,
+}
+
+impl BatchAddTaskResponse {
+    /// 从逐条结果汇总
+    pub fn from_results(results: Vec<BatchAddTaskItem>) -> Self {
+        let success_count = results.iter().filter(|r| r.task_id.is_some()).count();
+        let failed_count = results.len() - success_count;
+        Self {
+            results,
+            success_count,
+            failed_count,
+        }
+    }
 }
 
 /// 任务列表响应
@@ -268,6 +400,9 @@ pub struct OperationResponse {
     /// 可选的消息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 操作后查询到的任务详情（用于前端立即刷新状态，任务已不存在或查询失败时省略）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<CloudDlTaskInfo>,
 }
 
 impl OperationResponse {
@@ -276,6 +411,7 @@ impl OperationResponse {
         Self {
             success: true,
             message: None,
+            task: None,
         }
     }
 
@@ -284,6 +420,7 @@ impl OperationResponse {
         Self {
             success: true,
             message: Some(message.into()),
+            task: None,
         }
     }
 
@@ -292,8 +429,15 @@ impl OperationResponse {
         Self {
             success: false,
             message: Some(message.into()),
+            task: None,
         }
     }
+
+    /// 附带操作后的任务详情
+    pub fn with_task(mut self, task: Option<CloudDlTaskInfo>) -> Self {
+        self.task = task;
+        self
+    }
 }
 
 // =====================================================
@@ -527,7 +671,7 @@ impl BaiduTaskInfo {
         let status: i32 = self.status.parse().unwrap_or(2);
         let status_enum = CloudDlTaskStatus::from_i32(status);
 
-        CloudDlTaskInfo {
+        let mut info = CloudDlTaskInfo {
             task_id: self.task_id.parse().unwrap_or(0),
             status,
             status_text: status_enum.to_text().to_string(),
@@ -549,7 +693,10 @@ impl BaiduTaskInfo {
                 })
                 .collect(),
             result: self.result,
-        }
+            progress_percent: 0.0,
+        };
+        info.refresh_progress();
+        info
     }
 }
 
@@ -624,6 +771,143 @@ impl BaiduOperationResponse {
     }
 }
 
+// =====================================================
+// 源链接校验
+// =====================================================
+
+/// 离线下载源链接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudDlSourceKind {
+    /// HTTP/HTTPS 链接
+    Http,
+    /// 磁力链接
+    Magnet,
+    /// ed2k 链接
+    Ed2k,
+}
+
+/// 校验后的离线下载源链接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudDlSource {
+    /// 链接类型
+    pub kind: CloudDlSourceKind,
+    /// 规范化后的链接（去除首尾空白、协议头统一小写）
+    pub url: String,
+}
+
+/// 添加离线下载任务错误
+#[derive(Debug, Error)]
+pub enum CloudDlAddTaskError {
+    /// 不支持的链接协议
+    #[error("不支持的链接类型: {0}（仅支持 HTTP/HTTPS、磁力链接和 ed2k 链接）")]
+    UnsupportedScheme(String),
+
+    /// 链接格式无效
+    #[error("链接格式无效: {0}")]
+    InvalidUrl(String),
+
+    /// 百度 API 拒绝添加任务
+    #[error("添加离线任务失败: {message}")]
+    ApiRejected { code: i32, message: String },
+}
+
+impl CloudDlAddTaskError {
+    /// 是否为请求参数错误（链接本身有问题，未调用 API）
+    pub fn is_invalid_input(&self) -> bool {
+        matches!(self, Self::UnsupportedScheme(_) | Self::InvalidUrl(_))
+    }
+}
+
+/// 校验并规范化离线下载源链接
+///
+/// - HTTP/HTTPS：必须能解析为带主机名的 URL
+/// - 磁力链接：以 `magnet:?` 开头（hash 标准化由客户端完成）
+/// - ed2k：必须符合 `ed2k://|file|文件名|大小|hash|/` 结构
+pub fn parse_source_url(source_url: &str) -> Result<CloudDlSource, CloudDlAddTaskError> {
+    let trimmed = source_url.trim();
+    if trimmed.is_empty() {
+        return Err(CloudDlAddTaskError::InvalidUrl("链接为空".to_string()));
+    }
+
+    let lower = trimmed.to_lowercase();
+    if lower.starts_with("magnet:") {
+        if !lower.starts_with("magnet:?") {
+            return Err(CloudDlAddTaskError::InvalidUrl(format!(
+                "磁力链接应以 magnet:? 开头: {}",
+                trimmed
+            )));
+        }
+        return Ok(CloudDlSource {
+            kind: CloudDlSourceKind::Magnet,
+            url: trimmed.to_string(),
+        });
+    }
+
+    if lower.starts_with("ed2k://") {
+        let url = format!("ed2k://{}", &trimmed["ed2k://".len()..]);
+        validate_ed2k_link(&url)?;
+        return Ok(CloudDlSource {
+            kind: CloudDlSourceKind::Ed2k,
+            url,
+        });
+    }
+
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        let parsed = reqwest::Url::parse(trimmed)
+            .map_err(|e| CloudDlAddTaskError::InvalidUrl(format!("{}: {}", trimmed, e)))?;
+        if parsed.host_str().is_none_or(|h| h.is_empty()) {
+            return Err(CloudDlAddTaskError::InvalidUrl(format!(
+                "链接缺少主机名: {}",
+                trimmed
+            )));
+        }
+        return Ok(CloudDlSource {
+            kind: CloudDlSourceKind::Http,
+            url: trimmed.to_string(),
+        });
+    }
+
+    let scheme = match trimmed.find(':') {
+        Some(idx) if idx > 0 => trimmed[..idx].to_string(),
+        _ => trimmed.to_string(),
+    };
+    Err(CloudDlAddTaskError::UnsupportedScheme(scheme))
+}
+
+/// 校验 ed2k 链接结构：`ed2k://|file|文件名|大小|hash|/`
+///
+/// hash 之后允许附加 `h=`、`p=` 等可选字段
+fn validate_ed2k_link(url: &str) -> Result<(), CloudDlAddTaskError> {
+    let invalid = |reason: &str| {
+        CloudDlAddTaskError::InvalidUrl(format!("ed2k 链接{}: {}", reason, url))
+    };
+
+    // body = "|file|name|size|hash|...|/"
+    let Some(body) = url["ed2k://".len()..]
+        .strip_prefix('|')
+        .and_then(|b| b.strip_suffix("|/"))
+    else {
+        return Err(invalid("应为 ed2k://|file|文件名|大小|hash|/ 格式"));
+    };
+
+    let parts: Vec<&str> = body.split('|').collect();
+    if parts.len() < 4 || !parts[0].eq_ignore_ascii_case("file") {
+        return Err(invalid("应为 ed2k://|file|文件名|大小|hash|/ 格式"));
+    }
+    if parts[1].is_empty() {
+        return Err(invalid("缺少文件名"));
+    }
+    if !parts[2].parse::<u64>().is_ok_and(|size| size > 0) {
+        return Err(invalid("文件大小无效"));
+    }
+    let hash = parts[3];
+    if hash.len() != 32 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid("hash 应为 32 位十六进制"));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -692,6 +976,7 @@ mod tests {
             od_type: 0,
             file_list: vec![],
             result: 0,
+            progress_percent: 0.0,
         };
 
         assert!((task.progress_percent() - 50.0).abs() < 0.01);
@@ -714,22 +999,69 @@ mod tests {
             od_type: 0,
             file_list: vec![],
             result: 0,
+            progress_percent: 0.0,
         };
 
         assert_eq!(task.progress_percent(), 0.0);
     }
 
+    #[test]
+    fn test_task_info_progress_serialized() {
+        let json = r#"{"errno": 0, "task_info": [{"task_id": "1", "status": "1", "file_size": "400", "finished_size": "100"}]}"#;
+        let resp: BaiduListTaskResponse = serde_json::from_str(json).unwrap();
+        let mut task = resp.task_info.into_iter().next().unwrap().into_task_info();
+        assert!((task.progress_percent - 25.0).abs() < 0.01);
+
+        // 补全详情后重新计算
+        task.finished_size = 300;
+        task.refresh_progress();
+        let value = serde_json::to_value(&task).unwrap();
+        assert!((value["progress_percent"].as_f64().unwrap() - 75.0).abs() < 0.01);
+    }
+
     #[test]
     fn test_add_task_request_default() {
         let json = r#"{"source_url": "http://example.com/file.zip"}"#;
         let req: AddTaskRequest = serde_json::from_str(json).unwrap();
         assert_eq!(req.source_url, "http://example.com/file.zip");
         assert_eq!(req.save_path, "/");
-        assert!(!req.auto_download);
+        assert_eq!(req.auto_download, None);
         assert!(req.local_download_path.is_none());
         assert!(!req.ask_download_path);
     }
 
+    #[test]
+    fn test_resolve_auto_download() {
+        let default_dir = std::path::Path::new("/downloads");
+        let mut global = crate::config::CloudDlConfig::default();
+        let json = r#"{"source_url": "http://example.com/file.zip"}"#;
+        let mut req: AddTaskRequest = serde_json::from_str(json).unwrap();
+
+        // 全局关闭且请求未指定：不自动下载
+        assert!(req.resolve_auto_download(1, &global, default_dir).is_none());
+
+        // 全局开启：使用全局目录，未配置时回退到默认下载目录
+        global.auto_download = true;
+        let config = req.resolve_auto_download(1, &global, default_dir).unwrap();
+        assert_eq!(config.local_path.as_deref(), Some("/downloads"));
+        global.local_dir = Some(std::path::PathBuf::from("/cloud"));
+        let config = req.resolve_auto_download(1, &global, default_dir).unwrap();
+        assert_eq!(config.local_path.as_deref(), Some("/cloud"));
+
+        // 请求显式关闭优先于全局配置
+        req.auto_download = Some(false);
+        assert!(req.resolve_auto_download(1, &global, default_dir).is_none());
+
+        // 请求显式开启并指定目录
+        global.auto_download = false;
+        req.auto_download = Some(true);
+        req.local_download_path = Some("/mine".to_string());
+        let config = req.resolve_auto_download(7, &global, default_dir).unwrap();
+        assert_eq!(config.task_id, 7);
+        assert!(config.enabled);
+        assert_eq!(config.local_path.as_deref(), Some("/mine"));
+    }
+
     #[test]
     fn test_auto_download_config() {
         let config = AutoDownloadConfig::enabled(123, Some("/downloads".to_string()), false);
@@ -805,4 +1137,123 @@ mod tests {
         assert_eq!(resp.total, 1);
         assert_eq!(resp.request_id, 12345);
     }
+
+    #[test]
+    fn test_parse_source_url_http() {
+        let source = parse_source_url("  https://example.com/file.zip ").unwrap();
+        assert_eq!(source.kind, CloudDlSourceKind::Http);
+        assert_eq!(source.url, "https://example.com/file.zip");
+
+        assert!(matches!(
+            parse_source_url("http://"),
+            Err(CloudDlAddTaskError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_source_url_ed2k() {
+        let link = "ED2K://|file|ubuntu.iso|1048576|0123456789ABCDEF0123456789abcdef|/";
+        let source = parse_source_url(link).unwrap();
+        assert_eq!(source.kind, CloudDlSourceKind::Ed2k);
+        assert!(source.url.starts_with("ed2k://|file|"));
+
+        // 带可选字段
+        let with_extra = "ed2k://|file|a.mkv|100|0123456789ABCDEF0123456789ABCDEF|h=ABC|/";
+        assert!(parse_source_url(with_extra).is_ok());
+
+        for bad in [
+            "ed2k://|file|a.mkv|100|0123456789ABCDEF0123456789ABCDEF|",
+            "ed2k://|server|1.2.3.4|4661|/",
+            "ed2k://|file||100|0123456789ABCDEF0123456789ABCDEF|/",
+            "ed2k://|file|a.mkv|abc|0123456789ABCDEF0123456789ABCDEF|/",
+            "ed2k://|file|a.mkv|100|XYZ|/",
+            "ed2k://|/",
+            "ed2k://||/",
+        ] {
+            let err = parse_source_url(bad).unwrap_err();
+            assert!(err.is_invalid_input(), "{}", bad);
+            assert!(matches!(err, CloudDlAddTaskError::InvalidUrl(_)));
+        }
+    }
+
+    #[test]
+    fn test_parse_source_url_unsupported_scheme() {
+        match parse_source_url("ftp://example.com/file") {
+            Err(CloudDlAddTaskError::UnsupportedScheme(scheme)) => assert_eq!(scheme, "ftp"),
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(
+            parse_source_url("thunder://abc"),
+            Err(CloudDlAddTaskError::UnsupportedScheme(_))
+        ));
+
+        let magnet = parse_source_url("magnet:?xt=urn:btih:abc").unwrap();
+        assert_eq!(magnet.kind, CloudDlSourceKind::Magnet);
+
+        let api_err = CloudDlAddTaskError::ApiRejected {
+            code: 36032,
+            message: "任务已存在".to_string(),
+        };
+        assert!(!api_err.is_invalid_input());
+    }
+
+    #[test]
+    fn test_operation_response_with_task() {
+        let json = serde_json::to_value(OperationResponse::success()).unwrap();
+        assert!(json.get("task").is_none());
+
+        let task = CloudDlTaskInfo {
+            task_id: 1,
+            status: 7,
+            status_text: "已取消".to_string(),
+            file_size: 1000,
+            finished_size: 0,
+            create_time: 0,
+            start_time: 0,
+            finish_time: 0,
+            save_path: "/".to_string(),
+            source_url: "magnet:?xt=urn:btih:abc".to_string(),
+            task_name: "abc".to_string(),
+            od_type: 0,
+            file_list: vec![],
+            result: 0,
+            progress_percent: 0.0,
+        };
+        let json = serde_json::to_value(OperationResponse::success().with_task(Some(task))).unwrap();
+        assert_eq!(json["task"]["task_id"], 1);
+    }
+
+    #[test]
+    fn test_batch_add_normalized_urls() {
+        let req: BatchAddTaskRequest = serde_json::from_value(serde_json::json!({
+            "urls": ["  magnet:?xt=urn:btih:abc ", "", "magnet:?xt=urn:btih:def", "magnet:?xt=urn:btih:abc"]
+        }))
+        .unwrap();
+
+        assert_eq!(req.save_path, "/");
+        assert_eq!(
+            req.normalized_urls(),
+            vec!["magnet:?xt=urn:btih:abc", "magnet:?xt=urn:btih:def"]
+        );
+        assert!(req.task_request("magnet:?xt=urn:btih:abc").auto_download.is_none());
+    }
+
+    #[test]
+    fn test_batch_add_response_counts() {
+        let response = BatchAddTaskResponse::from_results(vec![
+            BatchAddTaskItem {
+                source_url: "magnet:?xt=urn:btih:abc".to_string(),
+                task_id: Some(1),
+                error: None,
+            },
+            BatchAddTaskItem {
+                source_url: "ftp://example.com".to_string(),
+                task_id: None,
+                error: Some("不支持的链接协议: ftp".to_string()),
+            },
+        ]);
+
+        assert_eq!(response.success_count, 1);
+        assert_eq!(response.failed_count, 1);
+    }
 }
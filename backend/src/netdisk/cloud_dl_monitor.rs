@@ -556,6 +556,8 @@ impl CloudDlMonitor {
 
         let mut last_states: HashMap<i64, (i32, i64)> = HashMap::new();
         let mut unchanged_count = 0u32;
+        // 上次轮询时所有任务是否都已结束（终态任务无需继续轮询）
+        let mut all_finished = false;
 
         loop {
             // 检查是否应该停止
@@ -577,14 +579,15 @@ impl CloudDlMonitor {
                 self.auto_download_configs.read().await.len()
             );
 
-            if !has_subscribers && !has_auto_downloads {
-                // 完全空闲，等待新任务或停止信号
+            if !has_auto_downloads && (!has_subscribers || all_finished) {
+                // 完全空闲（或所有任务已结束），等待新任务或停止信号
                 debug!("无监听需求，等待新任务...");
 
                 // 使用 select 同时等待通知和停止信号
                 tokio::select! {
                     _ = self.new_task_notify.notified() => {
                         debug!("收到新任务通知");
+                        all_finished = false;
                     }
                     _ = tokio::time::sleep(Duration::from_secs(1)) => {
                         // 定期检查停止信号
@@ -635,36 +638,12 @@ impl CloudDlMonitor {
 
             // 执行查询
             let client_snap = self.client.read().unwrap().clone();
-            match client_snap.cloud_dl_list_task().await {
-                Ok(mut tasks) => {
-                    // 对进行中的任务查询详情以获取进度信息
-                    let running_task_ids: Vec<i64> = tasks
-                        .iter()
-                        .filter(|t| t.status == 1)
-                        .map(|t| t.task_id)
-                        .collect();
-
-                    if !running_task_ids.is_empty() {
-                        match client_snap.cloud_dl_query_task(&running_task_ids).await {
-                            Ok(details) => {
-                                let detail_map: std::collections::HashMap<i64, CloudDlTaskInfo> =
-                                    details.into_iter().map(|t| (t.task_id, t)).collect();
-
-                                for task in &mut tasks {
-                                    if let Some(detail) = detail_map.get(&task.task_id) {
-                                        task.file_size = detail.file_size;
-                                        task.finished_size = detail.finished_size;
-                                        task.start_time = detail.start_time;
-                                        task.finish_time = detail.finish_time;
-                                        task.file_list = detail.file_list.clone();
-                                    }
-                                }
-                                debug!("已更新 {} 个进行中任务的进度信息", detail_map.len());
-                            }
-                            Err(e) => {
-                                warn!("查询任务详情失败: {}", e);
-                            }
-                        }
+            match client_snap.cloud_dl_list_task_with_progress().await {
+                Ok(tasks) => {
+                    // 所有任务都处于终态时暂停轮询，直到有新任务或用户重新进入页面
+                    all_finished = tasks.iter().all(|t| t.is_finished());
+                    if all_finished {
+                        debug!("所有离线任务已结束，暂停轮询");
                     }
 
                     let mut has_changes = false;
@@ -714,11 +693,7 @@ impl CloudDlMonitor {
                         if has_subscribers && task.status == 1 {
                             if let Some((_, last_finished)) = last_state {
                                 if task.finished_size != *last_finished {
-                                    let progress = if task.file_size > 0 {
-                                        (task.finished_size as f32 / task.file_size as f32) * 100.0
-                                    } else {
-                                        0.0
-                                    };
+                                    let progress = task.progress_percent;
 
                                     info!(
                                         "离线下载进度变化: task_id={}, finished={} -> {}, progress={:.1}%",
@@ -895,6 +870,8 @@ impl CloudDlMonitor {
                         };
 
                         self.execute_auto_download(&task_with_details, local_path).await;
+                    } else {
+                        warn!("自动下载配置缺少本地目录，跳过自动下载: task_id={}", task.task_id);
                     }
                 }
             }
@@ -964,11 +941,7 @@ impl CloudDlMonitor {
         let save_path = task.save_path.trim_end_matches('/');
 
         // 🔥 构建需要下载的文件名集合（从 file_list 中获取）
-        let target_files: std::collections::HashSet<&str> = task
-            .file_list
-            .iter()
-            .map(|f| f.file_name.as_str())
-            .collect();
+        let target_files = top_level_entry_names(&task.file_list);
 
         info!(
             "自动下载: task_id={}, save_path={}, 目标文件: {:?}",
@@ -1010,6 +983,7 @@ impl CloudDlMonitor {
                                 target_dir,
                                 None,
                                 None,
+                                None,
                             ).await {
                                 Ok(folder_id) => {
                                     info!(
@@ -1038,6 +1012,8 @@ impl CloudDlMonitor {
                             file.server_filename.clone(),
                             file.size,
                             target_dir,
+                            file.md5.clone(),
+                            None,
                             None,
                         ).await {
                             Ok(task_id) => {
@@ -1147,6 +1123,11 @@ impl CloudDlMonitor {
         self.auto_download_configs.read().await.get(&task_id).cloned()
     }
 
+    /// 通知监听服务有新任务（恢复已暂停的轮询）
+    pub fn notify_new_task(&self) {
+        self.new_task_notify.notify_one();
+    }
+
     /// 增加订阅者
     ///
     /// 当用户打开离线下载页面时调用
@@ -1175,7 +1156,7 @@ impl CloudDlMonitor {
     /// 返回当前任务列表并推送刷新事件
     pub async fn trigger_refresh(&self) -> Result<Vec<CloudDlTaskInfo>> {
         let client_snap = self.client.read().unwrap().clone();
-        let tasks = client_snap.cloud_dl_list_task().await?;
+        let tasks = client_snap.cloud_dl_list_task_with_progress().await?;
 
         let event = CloudDlEvent::TaskListRefreshed {
             tasks: tasks.clone(),
@@ -1193,6 +1174,25 @@ impl CloudDlMonitor {
     }
 }
 
+/// 计算离线任务在 save_path 下对应的顶层条目名
+///
+/// 磁力任务的 file_list 可能带目录前缀（如 `Movie/part1.mkv`），
+/// 此时 save_path 下实际出现的是 `Movie` 文件夹，需按顶层名匹配后整体递归下载
+fn top_level_entry_names(
+    file_list: &[crate::netdisk::CloudDlFileInfo],
+) -> std::collections::HashSet<&str> {
+    file_list
+        .iter()
+        .filter_map(|f| {
+            f.file_name
+                .trim_start_matches('/')
+                .split('/')
+                .next()
+                .filter(|name| !name.is_empty())
+        })
+        .collect()
+}
+
 // =====================================================
 // 单元测试
 // =====================================================
@@ -1325,6 +1325,30 @@ mod tests {
             od_type: 0,
             file_list: vec![],
             result: 0,
+            progress_percent: 0.0,
         }
     }
+
+    #[test]
+    fn test_top_level_entry_names() {
+        let file_list = vec![
+            crate::netdisk::CloudDlFileInfo {
+                file_name: "single.iso".to_string(),
+                file_size: 1,
+            },
+            crate::netdisk::CloudDlFileInfo {
+                file_name: "Movie/part1.mkv".to_string(),
+                file_size: 1,
+            },
+            crate::netdisk::CloudDlFileInfo {
+                file_name: "Movie/sub/part2.mkv".to_string(),
+                file_size: 1,
+            },
+        ];
+
+        let names = top_level_entry_names(&file_list);
+        assert_eq!(names.len(), 2);
+        assert!(names.contains("single.iso"));
+        assert!(names.contains("Movie"));
+    }
 }
@@ -0,0 +1,104 @@
+//! 网盘 API 错误分类
+//!
+//! 将百度 API 返回的 errno 统一映射为 `NetdiskError`，客户端方法以 anyhow 上下文包装返回：
+//! 错误信息保持原有格式（包含 errno 和 errmsg），调用方通过 `NetdiskError::find` 取回类型化错误，
+//! 据此决定刷新登录态、稍后重试还是直接失败
+
+use thiserror::Error;
+
+/// 网盘 API 错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum NetdiskError {
+    /// 登录态失效（BDUSS/STOKEN 过期或网页会话失效）
+    #[error("登录已失效，请重新登录")]
+    AuthExpired,
+
+    /// 请求过于频繁，触发限流
+    #[error("请求过于频繁，请稍后再试")]
+    RateLimited,
+
+    /// 文件或目录不存在
+    #[error("文件或目录不存在")]
+    NotFound,
+
+    /// 需要验证码或安全验证
+    #[error("需要进行安全验证，请在网页端完成验证后重试")]
+    NeedVerify,
+
+    /// 网盘空间不足
+    #[error("网盘空间不足")]
+    QuotaExceeded,
+
+    /// 上传会话（upload_id）已失效，服务端已清理之前上传的分片
+    #[error("上传会话已过期")]
+    UploadIdExpired,
+
+    /// 未归类的 errno
+    #[error("百度 API 错误，错误码: {0}")]
+    Unknown(i64),
+}
+
+impl NetdiskError {
+    /// 从百度 API errno 转换（xpan 与 pcs 接口的错误码统一在此映射）
+    pub fn from_errno(errno: i64) -> Self {
+        match errno {
+            -6 | 111 | 31045 => Self::AuthExpired,
+            31034 | 31023 | 31024 => Self::RateLimited,
+            -9 | 31066 => Self::NotFound,
+            -19 | -62 | 132 | 8001 => Self::NeedVerify,
+            -10 | 31064 | 31083 | 31112 => Self::QuotaExceeded,
+            31363 => Self::UploadIdExpired,
+            _ => Self::Unknown(errno),
+        }
+    }
+
+    /// 构造客户端方法返回的错误：保留原有错误信息格式，并附带类型化错误供调用方判断
+    pub fn api_error(errno: i64, message: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(Self::from_errno(errno)).context(message.into())
+    }
+
+    /// 沿错误链查找网盘 API 错误
+    pub fn find(error: &anyhow::Error) -> Option<Self> {
+        error.chain().find_map(|cause| cause.downcast_ref::<Self>().copied())
+    }
+
+    /// 是否需要刷新登录态（预热或重新登录）后才能继续
+    pub fn requires_login(&self) -> bool {
+        matches!(self, Self::AuthExpired | Self::NeedVerify)
+    }
+
+    /// 是否为临时性错误，稍后重试即可恢复
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_errno() {
+        assert_eq!(NetdiskError::from_errno(-6), NetdiskError::AuthExpired);
+        assert_eq!(NetdiskError::from_errno(31034), NetdiskError::RateLimited);
+        assert_eq!(NetdiskError::from_errno(31066), NetdiskError::NotFound);
+        assert_eq!(NetdiskError::from_errno(-62), NetdiskError::NeedVerify);
+        assert_eq!(NetdiskError::from_errno(31112), NetdiskError::QuotaExceeded);
+        assert_eq!(NetdiskError::from_errno(31363), NetdiskError::UploadIdExpired);
+        assert_eq!(NetdiskError::from_errno(42), NetdiskError::Unknown(42));
+        assert!(NetdiskError::AuthExpired.requires_login());
+        assert!(!NetdiskError::NotFound.is_retryable());
+    }
+
+    #[test]
+    fn test_api_error_keeps_message() {
+        let err = NetdiskError::api_error(-6, "创建文件夹失败: errno=-6, msg=");
+        assert_eq!(err.to_string(), "创建文件夹失败: errno=-6, msg=");
+        assert_eq!(NetdiskError::find(&err), Some(NetdiskError::AuthExpired));
+
+        // 外层再包装上下文后仍能取回
+        let err = err.context("获取下载链接列表失败");
+        assert_eq!(NetdiskError::find(&err), Some(NetdiskError::AuthExpired));
+        assert_eq!(NetdiskError::find(&anyhow::anyhow!("网络错误")), None);
+    }
+}
@@ -3,15 +3,17 @@
 pub mod client;
 pub mod cloud_dl;
 pub mod cloud_dl_monitor;
+pub mod error;
 pub mod types;
 
-pub use client::NetdiskClient;
+pub use client::{set_user_agent_overrides, NetdiskClient};
 pub use cloud_dl::{
-    AddTaskRequest, AddTaskResponse, AutoDownloadConfig, ClearTasksResponse, CloudDlFileInfo,
-    CloudDlTaskInfo, CloudDlTaskStatus, ListTaskRequest, OperationResponse, QueryTaskRequest,
-    TaskListResponse,
+    parse_source_url, AddTaskRequest, AddTaskResponse, AutoDownloadConfig, BatchAddTaskItem,
+    BatchAddTaskRequest, BatchAddTaskResponse, ClearTasksResponse, CloudDlAddTaskError, CloudDlFileInfo, CloudDlSource, CloudDlSourceKind, CloudDlTaskInfo,
+    CloudDlTaskStatus, ListTaskRequest, OperationResponse, QueryTaskRequest, TaskListResponse,
 };
 pub use cloud_dl_monitor::{CloudDlEvent, CloudDlMonitor, PollingConfig, TaskProgressTracker};
+pub use error::NetdiskError;
 pub use types::*;
 
 // TODO: 后续实现
@@ -58,6 +58,59 @@ impl FileItem {
     }
 }
 
+/// 文件列表排序字段（对应 list 接口的 order 参数）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileListOrder {
+    /// 按文件名
+    #[default]
+    Name,
+    /// 按修改时间
+    Time,
+    /// 按文件大小
+    Size,
+}
+
+impl FileListOrder {
+    /// list 接口的 order 参数值
+    pub fn as_api_value(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Time => "time",
+            Self::Size => "size",
+        }
+    }
+}
+
+/// 文件类型筛选（在返回的文件列表上筛选）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileTypeFilter {
+    /// 文件夹
+    Folder,
+    /// 文件（非文件夹）
+    File,
+    /// 图片（category=3）
+    Image,
+    /// 视频（category=1）
+    Video,
+    /// 文档（category=4）
+    Doc,
+}
+
+impl FileTypeFilter {
+    /// 文件是否符合筛选条件
+    pub fn matches(&self, item: &FileItem) -> bool {
+        match self {
+            Self::Folder => item.is_directory(),
+            Self::File => item.is_file(),
+            Self::Image => item.is_file() && item.category == 3,
+            Self::Video => item.is_file() && item.category == 1,
+            Self::Doc => item.is_file() && item.category == 4,
+        }
+    }
+}
+
 /// 文件列表响应
 #[derive(Debug, Deserialize)]
 pub struct FileListResponse {
@@ -79,6 +132,10 @@ pub struct FileListResponse {
     /// GUID信息
     #[serde(default, rename = "guid_info")]
     pub guid_info: String,
+
+    /// 是否还有更多结果（仅搜索接口返回，1=有）
+    #[serde(default)]
+    pub has_more: i32,
 }
 
 /// 下载链接信息
@@ -121,6 +178,18 @@ pub struct LocateFileInfo {
     /// 文件路径
     pub path: String,
 
+    /// 文件大小
+    #[serde(default)]
+    pub size: u64,
+
+    /// 是否是目录 (0=文件, 1=目录)
+    #[serde(default)]
+    pub isdir: i32,
+
+    /// MD5（仅文件有效）
+    #[serde(default)]
+    pub md5: Option<String>,
+
     /// 下载链接列表
     #[serde(default)]
     pub dlink: Vec<DownloadUrl>,
@@ -438,6 +507,8 @@ pub enum UploadErrorKind {
     FileExists,
     /// 空间不足（不可重试）
     QuotaExceeded,
+    /// 上传会话已失效（不可重试，需要重新 precreate 并从头上传）
+    UploadIdExpired,
     /// 未知错误
     Unknown,
 }
@@ -467,6 +538,8 @@ impl UploadErrorKind {
             31061 | 31079 => UploadErrorKind::BadRequest,
             31190 => UploadErrorKind::FileExists,
             31064 | 31083 => UploadErrorKind::QuotaExceeded,
+            // 31363: 合并时分片缺失，upload_id 过期后服务端已清理之前上传的分片
+            31363 => UploadErrorKind::UploadIdExpired,
             _ => UploadErrorKind::Unknown,
         }
     }
@@ -785,6 +858,22 @@ pub struct DeleteFilesApiResponse {
     /// 验证场景（风控相关）
     #[serde(default)]
     pub verify_scene: Option<i32>,
+
+    /// 逐个路径的处理结果（errno=12 部分失败时返回）
+    #[serde(default)]
+    pub info: Vec<FileOperationInfo>,
+}
+
+/// 文件管理 API（filemanager）中单个路径的处理结果
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileOperationInfo {
+    /// 错误码（0表示成功，-9表示文件不存在）
+    #[serde(default)]
+    pub errno: i32,
+
+    /// 文件路径
+    #[serde(default)]
+    pub path: String,
 }
 
 impl DeleteFilesApiResponse {
@@ -792,6 +881,217 @@ impl DeleteFilesApiResponse {
     pub fn is_success(&self) -> bool {
         self.errno == 0
     }
+
+    /// 从 info 列表中提取删除失败的路径
+    ///
+    /// 文件不存在（errno=-9）视为已删除，保持删除操作的幂等性
+    pub fn failed_paths(&self) -> Vec<String> {
+        self.info
+            .iter()
+            .filter(|item| item.errno != 0 && item.errno != -9)
+            .map(|item| item.path.clone())
+            .collect()
+    }
+}
+
+/// 文件管理操作类型（filemanager 的 opera 参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileOperation {
+    Rename,
+    Move,
+    Copy,
+    /// 从回收站还原
+    Restore,
+}
+
+impl FileOperation {
+    /// 对应 API 的 opera 参数值
+    pub fn as_opera(&self) -> &'static str {
+        match self {
+            FileOperation::Rename => "rename",
+            FileOperation::Move => "move",
+            FileOperation::Copy => "copy",
+            FileOperation::Restore => "restore",
+        }
+    }
+
+    /// 提交文件列表使用的表单字段名（还原按 fs_id 提交）
+    pub fn form_field(&self) -> &'static str {
+        match self {
+            FileOperation::Restore => "fidlist",
+            _ => "filelist",
+        }
+    }
+
+    /// 用于日志和错误信息的中文名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            FileOperation::Rename => "重命名",
+            FileOperation::Move => "移动",
+            FileOperation::Copy => "复制",
+            FileOperation::Restore => "还原",
+        }
+    }
+}
+
+/// 文件管理操作响应（重命名/移动/复制）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOperationResponse {
+    /// 是否全部成功
+    pub success: bool,
+    /// 错误信息（如果有）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 操作失败的路径列表
+    #[serde(default)]
+    pub failed_paths: Vec<String>,
+    /// 成功处理的数量
+    pub success_count: usize,
+    /// API 错误码
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errno: Option<i32>,
+    /// 异步任务ID（大批量操作时由服务端返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<u64>,
+}
+
+impl FileOperationResponse {
+    /// 创建成功响应
+    pub fn success(success_count: usize, task_id: Option<u64>) -> Self {
+        Self {
+            success: true,
+            error: None,
+            failed_paths: Vec::new(),
+            success_count,
+            errno: Some(0),
+            task_id,
+        }
+    }
+
+    /// 创建部分成功响应
+    pub fn partial_success(success_count: usize, failed_paths: Vec<String>) -> Self {
+        Self {
+            success: false,
+            error: Some(format!("部分文件操作失败: {} 个", failed_paths.len())),
+            failed_paths,
+            success_count,
+            errno: Some(12),
+            task_id: None,
+        }
+    }
+
+    /// 创建失败响应
+    pub fn failure(error: String, errno: Option<i32>) -> Self {
+        Self {
+            success: false,
+            error: Some(error),
+            failed_paths: Vec::new(),
+            success_count: 0,
+            errno,
+            task_id: None,
+        }
+    }
+}
+
+/// 文件管理 API 原始响应（重命名/移动/复制）
+#[derive(Debug, Deserialize)]
+pub struct FileOperationApiResponse {
+    /// 错误码（0表示成功）
+    #[serde(default)]
+    pub errno: i32,
+
+    /// 错误信息
+    #[serde(default)]
+    pub errmsg: String,
+
+    /// 异步任务ID（async=2 且任务未立即完成时返回，0 表示同步完成）
+    #[serde(default)]
+    pub taskid: u64,
+
+    /// 逐个路径的处理结果
+    #[serde(default)]
+    pub info: Vec<FileOperationInfo>,
+}
+
+impl FileOperationApiResponse {
+    /// 是否成功
+    pub fn is_success(&self) -> bool {
+        self.errno == 0
+    }
+
+    /// 从 info 列表中提取操作失败的路径
+    pub fn failed_paths(&self) -> Vec<String> {
+        self.info
+            .iter()
+            .filter(|item| item.errno != 0)
+            .map(|item| item.path.clone())
+            .collect()
+    }
+}
+
+/// 回收站文件项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecycleItem {
+    /// 文件服务器ID
+    pub fs_id: u64,
+
+    /// 删除前的原始路径
+    pub path: String,
+
+    /// 服务器文件名（接口未返回时从路径中提取）
+    #[serde(default)]
+    pub server_filename: String,
+
+    /// 文件大小（字节）
+    #[serde(default)]
+    pub size: u64,
+
+    /// 是否是目录 (0=文件, 1=目录)
+    #[serde(default)]
+    pub isdir: i32,
+
+    /// 文件类别
+    #[serde(default)]
+    pub category: i32,
+
+    /// MD5（仅文件有效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+
+    /// 删除时间（回收站中文件的修改时间即删除时间）
+    #[serde(default, alias = "mtime", alias = "server_mtime")]
+    pub delete_time: i64,
+
+    /// 剩余保留天数
+    #[serde(default, alias = "leftTime")]
+    pub left_time: i64,
+}
+
+/// 回收站列表响应
+#[derive(Debug, Deserialize)]
+pub struct RecycleListResponse {
+    /// 错误码（0表示成功）
+    #[serde(default)]
+    pub errno: i32,
+
+    /// PCS 接口错误码（0表示成功）
+    #[serde(default)]
+    pub error_code: i32,
+
+    /// PCS 接口错误信息
+    #[serde(default)]
+    pub error_msg: String,
+
+    /// 回收站文件列表
+    #[serde(default)]
+    pub list: Vec<RecycleItem>,
+}
+
+impl RecycleListResponse {
+    /// 是否成功
+    pub fn is_success(&self) -> bool {
+        self.errno == 0 && self.error_code == 0
+    }
 }
 
 /// 文件元信息（包含 block_list）
@@ -826,6 +1126,55 @@ pub struct FileMetaInfo {
 
     /// 服务器修改时间
     pub server_mtime: i64,
+
+    /// 缩略图链接（仅请求 thumb=1 且为图片/视频时返回）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbs: Option<FileThumbs>,
+
+    /// 下载链接（仅请求 dlink=1 时返回）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlink: Option<String>,
+}
+
+/// 缩略图尺寸（对应 filemetas 返回的 thumbs 字段）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailSize {
+    /// 图标（约 60x60）
+    Icon,
+    /// 小图（约 140x90）
+    Small,
+    /// 中图（约 360x270）
+    #[default]
+    Medium,
+    /// 大图（约 850x580）
+    Large,
+}
+
+/// filemetas 返回的缩略图链接
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileThumbs {
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub url1: Option<String>,
+    #[serde(default)]
+    pub url2: Option<String>,
+    #[serde(default)]
+    pub url3: Option<String>,
+}
+
+impl FileThumbs {
+    /// 指定尺寸的缩略图链接
+    pub fn url(&self, size: ThumbnailSize) -> Option<&str> {
+        match size {
+            ThumbnailSize::Icon => self.icon.as_deref(),
+            ThumbnailSize::Small => self.url1.as_deref(),
+            ThumbnailSize::Medium => self.url2.as_deref(),
+            ThumbnailSize::Large => self.url3.as_deref(),
+        }
+        .filter(|url| !url.is_empty())
+    }
 }
 
 /// 文件元信息响应
@@ -849,3 +1198,83 @@ impl FileMetasResponse {
         self.errno == 0
     }
 }
+
+impl From<FileMetasResponse> for LocateDownloadResponse {
+    /// 将 filemetas（dlink=1）响应转换为按 fs_id 定位的下载响应
+    fn from(metas: FileMetasResponse) -> Self {
+        Self {
+            errno: metas.errno,
+            errmsg: metas.errmsg,
+            list: metas
+                .list
+                .into_iter()
+                .map(|meta| LocateFileInfo {
+                    fs_id: meta.fs_id,
+                    dlink: meta
+                        .dlink
+                        .filter(|url| !url.is_empty())
+                        .map(|url| DownloadUrl {
+                            url,
+                            rank: 0,
+                            size: meta.size,
+                        })
+                        .into_iter()
+                        .collect(),
+                    path: meta.path,
+                    size: meta.size,
+                    isdir: meta.isdir,
+                    md5: meta.md5,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 网盘空间配额（/api/quota）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuotaInfo {
+    /// 总空间（字节）
+    pub total: u64,
+    /// 已用空间（字节）
+    pub used: u64,
+    /// 剩余空间（字节）
+    pub free: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_list_order_and_type_filter() {
+        let order: FileListOrder = serde_json::from_str(r#""time""#).unwrap();
+        assert_eq!(order.as_api_value(), "time");
+        assert_eq!(FileListOrder::default().as_api_value(), "name");
+        assert!(serde_json::from_str::<FileListOrder>(r#""random""#).is_err());
+
+        let item = |isdir: i32, category: i32| FileItem {
+            fs_id: 1,
+            path: "/a".to_string(),
+            server_filename: "a".to_string(),
+            size: 0,
+            isdir,
+            category,
+            md5: None,
+            server_ctime: 0,
+            server_mtime: 0,
+            local_ctime: 0,
+            local_mtime: 0,
+        };
+        let folder = item(1, 6);
+        let video = item(0, 1);
+        let image = item(0, 3);
+
+        assert!(FileTypeFilter::Folder.matches(&folder));
+        assert!(!FileTypeFilter::File.matches(&folder));
+        assert!(FileTypeFilter::File.matches(&video));
+        assert!(FileTypeFilter::Video.matches(&video));
+        assert!(!FileTypeFilter::Video.matches(&image));
+        assert!(FileTypeFilter::Image.matches(&image));
+        assert!(!FileTypeFilter::Doc.matches(&image));
+    }
+}
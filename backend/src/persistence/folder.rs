@@ -3,14 +3,13 @@
 //! 该模块负责文件夹下载状态的持久化和恢复
 
 use std::collections::HashSet;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
-use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, warn};
+use tracing::{debug, warn};
 
-use crate::downloader::folder::{FolderDownload, FolderStatus, PendingFile};
+use crate::downloader::folder::{FlattenCollisionPolicy, FolderDownload, FolderStatus, PendingFile};
 
 /// 文件夹持久化状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +55,12 @@ pub struct FolderPersisted {
     /// 🔥 关联的转存任务 ID（如果此文件夹下载任务由转存任务自动创建）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub transfer_task_id: Option<String>,
+    /// 平铺下载策略（None 表示保留目录结构）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flatten: Option<FlattenCollisionPolicy>,
+    /// 平铺下载已分配的文件名（恢复后继续扫描时避免重名）
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub flattened_names: HashSet<String>,
 }
 
 impl FolderPersisted {
@@ -80,6 +85,8 @@ impl FolderPersisted {
             completed_at: folder.completed_at,
             error: folder.error.clone(),
             transfer_task_id: folder.transfer_task_id.clone(),
+            flatten: folder.flatten,
+            flattened_names: folder.flattened_names.clone(),
         }
     }
 
@@ -111,9 +118,12 @@ impl FolderPersisted {
             encrypted_folder_mappings: std::collections::HashMap::new(),
             counted_task_ids: std::collections::HashSet::new(),
             conflict_strategy: None,
+            flatten: self.flatten,
+            flattened_names: self.flattened_names.clone(),
             completed_downloaded_size: 0,
             failed_count: 0,
             failed_task_ids: std::collections::HashSet::new(),
+            md5_verified_count: 0,
         }
     }
 }
@@ -205,49 +215,9 @@ pub fn get_folder_history_path(wal_dir: &Path) -> PathBuf {
     wal_dir.join(FOLDER_HISTORY_FILE_NAME)
 }
 
-/// 添加单个文件夹到历史文件
+/// 加载旧版文件夹历史文件到 Vec
 ///
-/// 用于文件夹完成时立即归档
-pub fn add_folder_to_history(wal_dir: &Path, folder: &FolderPersisted) -> std::io::Result<()> {
-    // 检查文件夹是否已存在
-    let existing_ids = load_folder_history_ids(wal_dir)?;
-    if existing_ids.contains(&folder.id) {
-        debug!("文件夹已存在于历史中，跳过: {}", folder.id);
-        return Ok(());
-    }
-
-    append_folders_to_history_file(wal_dir, &[folder.clone()])
-}
-
-/// 追加文件夹到历史文件
-fn append_folders_to_history_file(wal_dir: &Path, folders: &[FolderPersisted]) -> std::io::Result<()> {
-    let history_path = get_folder_history_path(wal_dir);
-
-    let file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&history_path)?;
-
-    let mut writer = BufWriter::new(file);
-
-    for folder in folders {
-        let json = serde_json::to_string(folder)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-        writeln!(writer, "{}", json)?;
-    }
-
-    writer.flush()?;
-
-    debug!(
-        "已追加 {} 个文件夹到历史文件: {:?}",
-        folders.len(),
-        history_path
-    );
-
-    Ok(())
-}
-
-/// 加载文件夹历史文件到 Vec
+/// 文件夹历史已统一存储在历史数据库，仅供启动时一次性迁移使用
 pub fn load_folder_history(wal_dir: &Path) -> std::io::Result<Vec<FolderPersisted>> {
     let history_path = get_folder_history_path(wal_dir);
     let mut result = Vec::new();
@@ -281,207 +251,6 @@ pub fn load_folder_history(wal_dir: &Path) -> std::io::Result<Vec<FolderPersiste
     Ok(result)
 }
 
-/// 加载文件夹历史 ID 集合（用于去重）
-pub fn load_folder_history_ids(wal_dir: &Path) -> std::io::Result<HashSet<String>> {
-    let history_path = get_folder_history_path(wal_dir);
-    let mut ids = HashSet::new();
-
-    if !history_path.exists() {
-        return Ok(ids);
-    }
-
-    let file = std::fs::File::open(&history_path)?;
-    let reader = BufReader::new(file);
-
-    for line_result in reader.lines() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(e) => {
-                warn!("读取文件 {} 时遇到 IO 错误，跳过该行: {}", history_path.display(), e);
-                continue;
-            }
-        };
-        if let Ok(folder) = serde_json::from_str::<FolderPersisted>(&line) {
-            ids.insert(folder.id);
-        }
-    }
-
-    Ok(ids)
-}
-
-/// 从文件夹历史文件中删除指定任务
-pub fn remove_folder_from_history(wal_dir: &Path, folder_id: &str) -> std::io::Result<bool> {
-    let history_path = get_folder_history_path(wal_dir);
-
-    if !history_path.exists() {
-        return Ok(false);
-    }
-
-    let file = std::fs::File::open(&history_path)?;
-    let reader = BufReader::new(file);
-
-    let mut records: Vec<String> = Vec::new();
-    let mut found = false;
-
-    for line_result in reader.lines() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(e) => {
-                warn!("读取文件 {} 时遇到 IO 错误，跳过该行: {}", history_path.display(), e);
-                continue;
-            }
-        };
-        if !line.trim().is_empty() {
-            if let Ok(folder) = serde_json::from_str::<FolderPersisted>(&line) {
-                if folder.id == folder_id {
-                    found = true;
-                    continue;
-                }
-            }
-            records.push(line);
-        }
-    }
-
-    if !found {
-        return Ok(false);
-    }
-
-    // 使用临时文件 + 原子替换
-    let temp_path = wal_dir.join(format!("{}.tmp", FOLDER_HISTORY_FILE_NAME));
-
-    {
-        let file = std::fs::File::create(&temp_path)?;
-        let mut writer = BufWriter::new(file);
-        for record in &records {
-            writeln!(writer, "{}", record)?;
-        }
-        writer.flush()?;
-    }
-
-    std::fs::rename(&temp_path, &history_path)?;
-    info!("已从文件夹历史文件中删除: {}", folder_id);
-
-    Ok(true)
-}
-
-/// 归档已完成的文件夹到历史文件
-///
-/// 扫描 wal_dir/folders 中所有 folder_*.json 文件，
-/// 找出状态为 completed 的文件夹，追加到 folder_history.jsonl 文件中，
-/// 然后删除对应的 .json 文件
-pub fn archive_completed_folders(wal_dir: &Path) -> std::io::Result<usize> {
-    // 1. 加载已有历史文件夹 ID（用于去重）
-    let existing_ids = load_folder_history_ids(wal_dir)?;
-
-    // 2. 加载所有持久化的文件夹
-    let folders = load_all_folders(wal_dir)?;
-
-    let mut archived_count = 0;
-    let mut to_archive: Vec<FolderPersisted> = Vec::new();
-    let mut to_cleanup: Vec<String> = Vec::new();
-
-    for folder in folders {
-        // 跳过已存在于历史中的文件夹
-        if existing_ids.contains(&folder.id) {
-            debug!("文件夹已存在于历史中，跳过: {}", folder.id);
-            // 仍然需要清理 .json 文件
-            to_cleanup.push(folder.id);
-            continue;
-        }
-
-        // 只归档状态为 completed 的文件夹
-        if folder.status == FolderStatus::Completed {
-            to_archive.push(folder.clone());
-            to_cleanup.push(folder.id);
-        }
-    }
-
-    // 3. 追加到历史文件
-    if !to_archive.is_empty() {
-        append_folders_to_history_file(wal_dir, &to_archive)?;
-        archived_count = to_archive.len();
-        info!("已归档 {} 个已完成文件夹到历史文件", archived_count);
-    }
-
-    // 4. 清理已归档文件夹的 .json 文件
-    for folder_id in to_cleanup {
-        let folder_path = wal_dir
-            .join("folders")
-            .join(format!("folder_{}.json", folder_id));
-        if folder_path.exists() {
-            if let Err(e) = std::fs::remove_file(&folder_path) {
-                warn!("删除文件夹持久化文件失败: {:?}, 错误: {}", folder_path, e);
-            }
-        }
-    }
-
-    Ok(archived_count)
-}
-
-/// 清理过期的文件夹历史
-pub fn cleanup_expired_folder_history(wal_dir: &Path, retention_days: u64) -> std::io::Result<usize> {
-    let history_path = get_folder_history_path(wal_dir);
-
-    if !history_path.exists() {
-        return Ok(0);
-    }
-
-    let cutoff_timestamp = (Utc::now() - Duration::days(retention_days as i64)).timestamp();
-
-    let file = std::fs::File::open(&history_path)?;
-    let reader = BufReader::new(file);
-
-    let mut kept_records: Vec<String> = Vec::new();
-    let mut expired_count = 0;
-
-    for line_result in reader.lines() {
-        let line = match line_result {
-            Ok(l) => l,
-            Err(e) => {
-                warn!("读取文件 {} 时遇到 IO 错误，跳过该行: {}", history_path.display(), e);
-                continue;
-            }
-        };
-        if !line.trim().is_empty() {
-            if let Ok(folder) = serde_json::from_str::<FolderPersisted>(&line) {
-                let is_expired = folder
-                    .completed_at
-                    .map(|t| t < cutoff_timestamp)
-                    .unwrap_or(false);
-
-                if is_expired {
-                    expired_count += 1;
-                    continue;
-                }
-            }
-            kept_records.push(line);
-        }
-    }
-
-    if expired_count == 0 {
-        return Ok(0);
-    }
-
-    let temp_path = wal_dir.join(format!("{}.tmp", FOLDER_HISTORY_FILE_NAME));
-
-    {
-        let file = std::fs::File::create(&temp_path)?;
-        let mut writer = BufWriter::new(file);
-        for record in &kept_records {
-            writeln!(writer, "{}", record)?;
-        }
-        writer.flush()?;
-    }
-
-    std::fs::rename(&temp_path, &history_path)?;
-    info!(
-        "已清理 {} 个过期文件夹历史（超过 {} 天）",
-        expired_count, retention_days
-    );
-
-    Ok(expired_count)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,6 +22,35 @@ pub struct HistoryDbManager {
     conn: Mutex<Connection>,
 }
 
+/// 任务历史查询条件
+#[derive(Debug, Clone, Default)]
+pub struct TaskHistoryQuery {
+    /// 任务类型 (download, upload, transfer)，为空表示不限
+    pub task_type: Option<String>,
+    /// 任务状态 (completed, failed, etc.)，为空表示不限
+    pub status: Option<String>,
+    /// 路径（含文件名）子串，为空表示不限
+    pub keyword: Option<String>,
+    /// 是否排除备份任务
+    pub exclude_backup: bool,
+    /// 偏移量
+    pub offset: usize,
+    /// 每页数量
+    pub limit: usize,
+}
+
+/// 转义 LIKE 模式中的通配符（配合 `ESCAPE '\'` 使用）
+fn escape_like(keyword: &str) -> String {
+    let mut escaped = String::with_capacity(keyword.len());
+    for c in keyword.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 impl HistoryDbManager {
     /// 创建新的历史数据库管理器
     pub fn new(db_path: &Path) -> Result<Self> {
@@ -170,6 +199,10 @@ impl HistoryDbManager {
         let _ = conn.execute("ALTER TABLE task_history ADD COLUMN is_share_direct_download INTEGER", []);
         let _ = conn.execute("ALTER TABLE task_history ADD COLUMN temp_dir TEXT", []);
         let _ = conn.execute("ALTER TABLE task_history ADD COLUMN cleanup_status TEXT", []);
+        let _ = conn.execute("ALTER TABLE task_history ADD COLUMN download_cleanup INTEGER", []);
+        let _ = conn.execute("ALTER TABLE task_history ADD COLUMN transferred_count INTEGER", []);
+        let _ = conn.execute("ALTER TABLE task_history ADD COLUMN recursive INTEGER", []);
+        let _ = conn.execute("ALTER TABLE task_history ADD COLUMN share_subfolder TEXT", []);
 
         info!("历史数据库表初始化完成");
         Ok(())
@@ -209,7 +242,7 @@ impl HistoryDbManager {
                 is_backup, backup_config_id,
                 transfer_task_id, download_task_ids,
                 file_list_json, is_share_direct_download,
-                temp_dir, cleanup_status
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
             ) VALUES (
                 ?1, ?2, ?3, ?4, ?5, ?6,
                 ?7, ?8, ?9,
@@ -220,7 +253,7 @@ impl HistoryDbManager {
                 ?26, ?27,
                 ?28, ?29,
                 ?30, ?31,
-                ?32, ?33
+                ?32, ?33, ?34, ?35, ?36, ?37
             )
             "#,
             params![
@@ -257,6 +290,10 @@ impl HistoryDbManager {
                 metadata.is_share_direct_download.map(|b| if b { 1 } else { 0 }),
                 metadata.temp_dir,
                 metadata.cleanup_status.map(|s| serde_json::to_value(s).ok().and_then(|v| v.as_str().map(String::from))).flatten(),
+                metadata.download_cleanup.map(|b| if b { 1 } else { 0 }),
+                metadata.transferred_count.map(|c| c as i64),
+                metadata.recursive.map(|b| if b { 1 } else { 0 }),
+                metadata.share_subfolder.and_then(|n| serde_json::to_value(n).ok().and_then(|v| v.as_str().map(String::from))),
             ],
         )?;
 
@@ -291,7 +328,7 @@ impl HistoryDbManager {
                     is_backup, backup_config_id,
                     transfer_task_id, download_task_ids,
                     file_list_json, is_share_direct_download,
-                    temp_dir, cleanup_status
+                    temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
                 ) VALUES (
                     ?1, ?2, ?3, ?4, ?5, ?6,
                     ?7, ?8, ?9,
@@ -302,7 +339,7 @@ impl HistoryDbManager {
                     ?26, ?27,
                     ?28, ?29,
                     ?30, ?31,
-                    ?32, ?33
+                    ?32, ?33, ?34, ?35, ?36, ?37
                 )
                 "#,
             )?;
@@ -353,6 +390,10 @@ impl HistoryDbManager {
                     metadata.is_share_direct_download.map(|b| if b { 1 } else { 0 }),
                     metadata.temp_dir,
                     metadata.cleanup_status.map(|s| serde_json::to_value(s).ok().and_then(|v| v.as_str().map(String::from))).flatten(),
+                    metadata.download_cleanup.map(|b| if b { 1 } else { 0 }),
+                    metadata.transferred_count.map(|c| c as i64),
+                    metadata.recursive.map(|b| if b { 1 } else { 0 }),
+                    metadata.share_subfolder.and_then(|n| serde_json::to_value(n).ok().and_then(|v| v.as_str().map(String::from))),
                 ])?;
                 count += 1;
             }
@@ -381,7 +422,7 @@ impl HistoryDbManager {
                 group_id, group_root, relative_path,
                 is_backup, backup_config_id,
                 transfer_task_id, download_task_ids,
-                temp_dir, cleanup_status
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
             FROM task_history
             ORDER BY completed_at DESC
             "#,
@@ -422,6 +463,10 @@ impl HistoryDbManager {
                 download_task_ids: row.get(30)?,
                 temp_dir: row.get(31)?,
                 cleanup_status: row.get(32)?,
+                download_cleanup: row.get(33)?,
+                transferred_count: row.get(34)?,
+                recursive: row.get(35)?,
+                share_subfolder: row.get(36)?,
             })
         })?;
 
@@ -478,7 +523,7 @@ impl HistoryDbManager {
                     group_id, group_root, relative_path,
                     is_backup, backup_config_id,
                     transfer_task_id, download_task_ids,
-                    temp_dir, cleanup_status
+                    temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
                 FROM task_history
                 WHERE task_id = ?1
                 "#,
@@ -518,6 +563,10 @@ impl HistoryDbManager {
                         download_task_ids: row.get(30)?,
                         temp_dir: row.get(31)?,
                         cleanup_status: row.get(32)?,
+                        download_cleanup: row.get(33)?,
+                        transferred_count: row.get(34)?,
+                        recursive: row.get(35)?,
+                        share_subfolder: row.get(36)?,
                     })
                 },
             )
@@ -566,7 +615,7 @@ impl HistoryDbManager {
                 group_id, group_root, relative_path,
                 is_backup, backup_config_id,
                 transfer_task_id, download_task_ids,
-                temp_dir, cleanup_status
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
             FROM task_history
             ORDER BY completed_at DESC
             LIMIT ?1 OFFSET ?2
@@ -608,6 +657,10 @@ impl HistoryDbManager {
                 download_task_ids: row.get(30)?,
                 temp_dir: row.get(31)?,
                 cleanup_status: row.get(32)?,
+                download_cleanup: row.get(33)?,
+                transferred_count: row.get(34)?,
+                recursive: row.get(35)?,
+                share_subfolder: row.get(36)?,
             })
         })?;
 
@@ -666,7 +719,7 @@ impl HistoryDbManager {
                 group_id, group_root, relative_path,
                 is_backup, backup_config_id,
                 transfer_task_id, download_task_ids,
-                temp_dir, cleanup_status
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
             FROM task_history
             WHERE task_type = ?1 AND status = ?2
             ORDER BY completed_at DESC
@@ -709,6 +762,10 @@ impl HistoryDbManager {
                 download_task_ids: row.get(30)?,
                 temp_dir: row.get(31)?,
                 cleanup_status: row.get(32)?,
+                download_cleanup: row.get(33)?,
+                transferred_count: row.get(34)?,
+                recursive: row.get(35)?,
+                share_subfolder: row.get(36)?,
             })
         })?;
 
@@ -771,7 +828,7 @@ impl HistoryDbManager {
                 group_id, group_root, relative_path,
                 is_backup, backup_config_id,
                 transfer_task_id, download_task_ids,
-                temp_dir, cleanup_status
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
             FROM task_history
             WHERE task_type = ?1 AND status = ?2 {}
             ORDER BY completed_at DESC
@@ -816,6 +873,144 @@ impl HistoryDbManager {
                 download_task_ids: row.get(30)?,
                 temp_dir: row.get(31)?,
                 cleanup_status: row.get(32)?,
+                download_cleanup: row.get(33)?,
+                transferred_count: row.get(34)?,
+                recursive: row.get(35)?,
+                share_subfolder: row.get(36)?,
+            })
+        })?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            match row {
+                Ok(r) => match self.row_to_task_metadata(r) {
+                    Ok(metadata) => tasks.push(metadata),
+                    Err(e) => warn!("转换任务历史失败: {}", e),
+                },
+                Err(e) => warn!("读取任务历史行失败: {}", e),
+            }
+        }
+
+        Ok((tasks, total))
+    }
+
+    /// 按条件分页查询任务历史
+    ///
+    /// 过滤和分页均在 SQL 中完成，记录数再多也只读取当前页
+    ///
+    /// # Returns
+    /// * `(Vec<TaskMetadata>, usize)` - (任务列表, 符合条件的总数)
+    pub fn query_task_history(&self, query: &TaskHistoryQuery) -> Result<(Vec<TaskMetadata>, usize)> {
+        use rusqlite::types::Value;
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow!("获取数据库锁失败: {}", e))?;
+
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(task_type) = &query.task_type {
+            values.push(Value::Text(task_type.clone()));
+            conditions.push(format!("task_type = ?{}", values.len()));
+        }
+        if let Some(status) = &query.status {
+            values.push(Value::Text(status.clone()));
+            conditions.push(format!("status = ?{}", values.len()));
+        }
+        if let Some(keyword) = query.keyword.as_deref().filter(|k| !k.is_empty()) {
+            values.push(Value::Text(format!("%{}%", escape_like(keyword))));
+            let n = values.len();
+            conditions.push(format!(
+                "(remote_path LIKE ?{n} ESCAPE '\\' OR local_path LIKE ?{n} ESCAPE '\\' \
+                 OR source_path LIKE ?{n} ESCAPE '\\' OR target_path LIKE ?{n} ESCAPE '\\' \
+                 OR transfer_target_path LIKE ?{n} ESCAPE '\\' OR share_link LIKE ?{n} ESCAPE '\\')"
+            ));
+        }
+        if query.exclude_backup {
+            conditions.push("is_backup = 0".to_string());
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        // 获取总数
+        let count_sql = format!("SELECT COUNT(*) FROM task_history {}", where_clause);
+        let total: usize = conn.query_row(
+            &count_sql,
+            rusqlite::params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        // 获取分页数据
+        let query_sql = format!(
+            r#"
+            SELECT
+                task_id, task_type, status, created_at, updated_at, completed_at,
+                fs_id, remote_path, local_path,
+                source_path, target_path, upload_id,
+                share_link, share_pwd, transfer_target_path, transfer_status, transfer_file_name, auto_download, file_list_json, is_share_direct_download,
+                file_size, chunk_size, total_chunks, error_msg,
+                group_id, group_root, relative_path,
+                is_backup, backup_config_id,
+                transfer_task_id, download_task_ids,
+                temp_dir, cleanup_status, download_cleanup, transferred_count, recursive, share_subfolder
+            FROM task_history
+            {}
+            ORDER BY completed_at DESC
+            LIMIT ?{} OFFSET ?{}
+            "#,
+            where_clause,
+            values.len() + 1,
+            values.len() + 2
+        );
+        values.push(Value::Integer(query.limit as i64));
+        values.push(Value::Integer(query.offset as i64));
+
+        let mut stmt = conn.prepare(&query_sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values.iter()), |row| {
+            Ok(TaskHistoryRow {
+                task_id: row.get(0)?,
+                task_type: row.get(1)?,
+                status: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                completed_at: row.get(5)?,
+                fs_id: row.get(6)?,
+                remote_path: row.get(7)?,
+                local_path: row.get(8)?,
+                source_path: row.get(9)?,
+                target_path: row.get(10)?,
+                upload_id: row.get(11)?,
+                share_link: row.get(12)?,
+                share_pwd: row.get(13)?,
+                transfer_target_path: row.get(14)?,
+                transfer_status: row.get(15)?,
+                transfer_file_name: row.get(16)?,
+                auto_download: row.get(17)?,
+                file_list_json: row.get(18)?,
+                is_share_direct_download: row.get(19)?,
+                file_size: row.get(20)?,
+                chunk_size: row.get(21)?,
+                total_chunks: row.get(22)?,
+                error_msg: row.get(23)?,
+                group_id: row.get(24)?,
+                group_root: row.get(25)?,
+                relative_path: row.get(26)?,
+                is_backup: row.get(27)?,
+                backup_config_id: row.get(28)?,
+                transfer_task_id: row.get(29)?,
+                download_task_ids: row.get(30)?,
+                temp_dir: row.get(31)?,
+                cleanup_status: row.get(32)?,
+                download_cleanup: row.get(33)?,
+                transferred_count: row.get(34)?,
+                recursive: row.get(35)?,
+                share_subfolder: row.get(36)?,
             })
         })?;
 
@@ -835,23 +1030,37 @@ impl HistoryDbManager {
 
     /// 批量删除任务历史（按任务类型和状态）
     pub fn remove_tasks_by_type_and_status(&self, task_type: &str, status: &str) -> Result<usize> {
+        self.remove_task_ids_by_type_and_status(task_type, status)
+            .map(|ids| ids.len())
+    }
+
+    /// 批量删除任务历史（按任务类型和状态），返回被删除的任务 ID
+    pub fn remove_task_ids_by_type_and_status(
+        &self,
+        task_type: &str,
+        status: &str,
+    ) -> Result<Vec<String>> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| anyhow!("获取数据库锁失败: {}", e))?;
 
-        let deleted = conn.execute(
+        let ids = conn
+            .prepare("SELECT task_id FROM task_history WHERE task_type = ?1 AND status = ?2")?
+            .query_map(params![task_type, status], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        conn.execute(
             "DELETE FROM task_history WHERE task_type = ?1 AND status = ?2",
             params![task_type, status],
         )?;
 
-        if deleted > 0 {
+        if !ids.is_empty() {
             info!(
                 "已从历史数据库中删除 {} 个 {} 类型的 {} 状态任务",
-                deleted, task_type, status
+                ids.len(), task_type, status
             );
         }
-        Ok(deleted)
+        Ok(ids)
     }
 
     /// 从历史中删除任务
@@ -1141,22 +1350,26 @@ impl HistoryDbManager {
         Ok(deleted > 0)
     }
 
-    /// 删除所有已完成的文件夹历史
-    pub fn remove_completed_folders(&self) -> Result<usize> {
+    /// 删除所有已完成的文件夹历史，返回被删除的文件夹 ID
+    pub fn remove_completed_folders(&self) -> Result<Vec<String>> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| anyhow!("获取数据库锁失败: {}", e))?;
 
-        let deleted = conn.execute(
+        let ids = conn
+            .prepare("SELECT folder_id FROM folder_history WHERE status = 'completed'")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        conn.execute(
             "DELETE FROM folder_history WHERE status = 'completed'",
             [],
         )?;
 
-        if deleted > 0 {
-            info!("已从历史数据库中删除 {} 个已完成的文件夹", deleted);
+        if !ids.is_empty() {
+            info!("已从历史数据库中删除 {} 个已完成的文件夹", ids.len());
         }
-        Ok(deleted)
+        Ok(ids)
     }
 
     /// 清理过期的文件夹历史
@@ -1273,8 +1486,14 @@ impl HistoryDbManager {
             auto_download: row.auto_download.map(|v| v != 0),
             transfer_file_name: row.transfer_file_name,
             file_list_json: row.file_list_json,
+            transferred_count: row.transferred_count.map(|c| c as usize),
             // 分享直下字段
             is_share_direct_download: row.is_share_direct_download.map(|v| v != 0),
+            download_cleanup: row.download_cleanup.map(|v| v != 0),
+            recursive: row.recursive.map(|v| v != 0),
+            share_subfolder: row
+                .share_subfolder
+                .and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok()),
             temp_dir: row.temp_dir,
             cleanup_status: row.cleanup_status.and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok()),
             group_id: row.group_id,
@@ -1286,6 +1505,15 @@ impl HistoryDbManager {
             is_backup: row.is_backup.map(|v| v != 0).unwrap_or(false),
             backup_config_id: row.backup_config_id,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
             // 加密字段
             encrypt_enabled: false,
             is_encrypted: false,
@@ -1329,6 +1557,9 @@ impl HistoryDbManager {
             completed_at: row.completed_at,
             error: row.error,
             transfer_task_id: row.transfer_task_id,
+            // 历史记录只用于展示，不保存平铺信息
+            flatten: None,
+            flattened_names: Default::default(),
         })
     }
 }
@@ -1372,6 +1603,10 @@ struct TaskHistoryRow {
     download_task_ids: Option<String>,
     temp_dir: Option<String>,
     cleanup_status: Option<String>,
+    download_cleanup: Option<i64>,
+    transferred_count: Option<i64>,
+    recursive: Option<i64>,
+    share_subfolder: Option<String>,
 }
 
 /// 文件夹历史行
@@ -1642,3 +1877,194 @@ impl HistoryDbManager {
         Ok(deleted)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn download_metadata(task_id: &str, remote_path: &str, completed: bool) -> TaskMetadata {
+        let mut metadata = TaskMetadata::new_download(
+            task_id.to_string(),
+            1,
+            remote_path.to_string(),
+            PathBuf::from(format!("/downloads{}", remote_path)),
+            1024,
+            256,
+            4,
+            None,
+            None,
+        );
+        if completed {
+            metadata.mark_completed();
+        } else {
+            metadata.mark_failed();
+        }
+        metadata
+    }
+
+    #[test]
+    fn test_query_task_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = HistoryDbManager::new(&temp_dir.path().join("history.db")).unwrap();
+
+        for i in 0..5 {
+            db.add_task_to_history(&download_metadata(
+                &format!("movie_{}", i),
+                &format!("/videos/movie_{}.mkv", i),
+                true,
+            ))
+            .unwrap();
+        }
+        db.add_task_to_history(&download_metadata("doc", "/docs/100%_report.pdf", true))
+            .unwrap();
+        db.add_task_to_history(&download_metadata("broken", "/videos/broken.mkv", false))
+            .unwrap();
+
+        // 分页：总数为全部记录，当前页只返回 limit 条
+        let (tasks, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                task_type: Some("download".to_string()),
+                offset: 0,
+                limit: 3,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 7);
+        assert_eq!(tasks.len(), 3);
+
+        // 状态 + 关键字过滤
+        let (tasks, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                task_type: Some("download".to_string()),
+                status: Some("completed".to_string()),
+                keyword: Some("movie".to_string()),
+                offset: 4,
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(tasks.len(), 1);
+
+        // LIKE 通配符按字面匹配
+        let (tasks, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                keyword: Some("100%_".to_string()),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(tasks[0].task_id, "doc");
+
+        // 任务类型不匹配
+        let (_, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                task_type: Some("upload".to_string()),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 0);
+    }
+
+    #[test]
+    fn test_query_transfer_history_by_share_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = HistoryDbManager::new(&temp_dir.path().join("history.db")).unwrap();
+
+        for (task_id, share_link, target_path) in [
+            ("t1", "https://pan.baidu.com/s/1abcDEF", "/我的资源/电影"),
+            ("t2", "https://pan.baidu.com/s/1xyzUVW", "/我的资源/文档"),
+        ] {
+            let mut metadata = TaskMetadata::new_transfer(
+                task_id.to_string(),
+                share_link.to_string(),
+                None,
+                target_path.to_string(),
+                false,
+                None,
+            );
+            metadata.mark_completed();
+            db.add_task_to_history(&metadata).unwrap();
+        }
+
+        // 按分享短链搜索
+        let (tasks, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                task_type: Some("transfer".to_string()),
+                keyword: Some("1abcDEF".to_string()),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(tasks[0].task_id, "t1");
+
+        // 按转存目标路径搜索
+        let (tasks, total) = db
+            .query_task_history(&TaskHistoryQuery {
+                task_type: Some("transfer".to_string()),
+                keyword: Some("文档".to_string()),
+                limit: 10,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(tasks[0].task_id, "t2");
+    }
+
+    #[test]
+    fn test_remove_task_ids_by_type_and_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = HistoryDbManager::new(&temp_dir.path().join("history.db")).unwrap();
+
+        db.add_task_to_history(&download_metadata("done_1", "/a.txt", true)).unwrap();
+        db.add_task_to_history(&download_metadata("done_2", "/b.txt", true)).unwrap();
+        db.add_task_to_history(&download_metadata("failed", "/c.txt", false)).unwrap();
+
+        // 返回被删除的任务 ID，供调用方与内存中的任务去重计数
+        let mut ids = db
+            .remove_task_ids_by_type_and_status("download", "completed")
+            .unwrap();
+        ids.sort();
+        assert_eq!(ids, vec!["done_1".to_string(), "done_2".to_string()]);
+        assert!(db.task_exists_in_history("failed").unwrap());
+        assert!(db
+            .remove_task_ids_by_type_and_status("download", "completed")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_transfer_history_keeps_extra_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = HistoryDbManager::new(&temp_dir.path().join("history.db")).unwrap();
+
+        let mut metadata = TaskMetadata::new_transfer(
+            "t1".to_string(),
+            "https://pan.baidu.com/s/1abcDEF".to_string(),
+            None,
+            "/我的资源".to_string(),
+            true,
+            None,
+        );
+        metadata.set_download_cleanup(true);
+        metadata.set_transferred_count(3);
+        metadata.set_transfer_recursive(true);
+        metadata.set_share_subfolder(crate::transfer::types::ShareSubfolderNaming::ShortKey);
+        metadata.mark_completed();
+        db.add_task_to_history(&metadata).unwrap();
+
+        let restored = db.get_task_history("t1").unwrap().unwrap();
+        assert_eq!(restored.download_cleanup, Some(true));
+        assert_eq!(restored.transferred_count, Some(3));
+        assert_eq!(restored.recursive, Some(true));
+        assert_eq!(
+            restored.share_subfolder,
+            Some(crate::transfer::types::ShareSubfolderNaming::ShortKey)
+        );
+    }
+}
@@ -19,16 +19,20 @@ use std::time::Duration;
 use bit_set::BitSet;
 use chrono::Timelike;
 use dashmap::DashMap;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, watch};
 use tracing::{debug, error, info, warn};
 
 use crate::config::PersistenceConfig;
 
 use super::history;
-use super::history_db::HistoryDbManager;
-use super::metadata::{delete_task_files, save_metadata, update_metadata};
+use super::history_db::{HistoryDbManager, TaskHistoryQuery};
+use super::recovery::file_mtime_secs;
+use super::metadata::{delete_task_files, load_metadata, save_metadata, update_metadata};
 use super::types::{TaskMetadata, TaskPersistenceInfo, TaskPersistenceStatus, TaskType};
-use super::wal::{self, append_records, delete_wal_file, read_records};
+use super::wal::{self, append_records, append_records_compressed, delete_wal_file, read_records};
+
+/// 账号数据子目录名（WAL 目录和历史数据库所在目录下按 UID 分目录）
+const ACCOUNTS_DIR: &str = "accounts";
 
 /// 持久化管理器
 ///
@@ -47,6 +51,9 @@ pub struct PersistenceManager {
     /// 历史数据库管理器
     history_db: Option<Arc<HistoryDbManager>>,
 
+    /// 所属账号 UID（None 表示未登录时使用的全局目录）
+    account_uid: Option<u64>,
+
     /// 后台刷写任务句柄
     flush_task: Option<tokio::task::JoinHandle<()>>,
 
@@ -58,6 +65,9 @@ pub struct PersistenceManager {
 
     /// shutdown 信号发送端
     shutdown_tx: broadcast::Sender<()>,
+
+    /// 刷写间隔（毫秒）发送端，后台刷写循环据此动态调整定时器
+    flush_interval_tx: watch::Sender<u64>,
 }
 
 impl std::fmt::Debug for PersistenceManager {
@@ -108,6 +118,7 @@ impl PersistenceManager {
         };
 
         let (shutdown_tx, _) = broadcast::channel(1);
+        let (flush_interval_tx, _) = watch::channel(config.wal_flush_interval_ms);
 
         info!("持久化管理器已创建，WAL 目录: {:?}", wal_dir);
 
@@ -116,13 +127,101 @@ impl PersistenceManager {
             wal_dir,
             tasks: Arc::new(DashMap::new()),
             history_db,
+            account_uid: None,
             flush_task: None,
             cleanup_task: None,
             archive_task: None,
             shutdown_tx,
+            flush_interval_tx,
+        }
+    }
+
+    /// 创建账号专属的持久化管理器
+    ///
+    /// WAL 目录和历史数据库按 UID 隔离：`{wal_dir}/accounts/{uid}`、
+    /// `{db_path 所在目录}/accounts/{uid}/{db 文件名}`。
+    /// 升级前保存在全局目录中的任务和历史记录归属首个加载的账号，在其首次创建账号目录时迁移
+    pub fn new_for_account(config: PersistenceConfig, base_dir: &std::path::Path, uid: u64) -> Self {
+        let resolve = |path: &str| {
+            if std::path::Path::new(path).is_absolute() {
+                PathBuf::from(path)
+            } else {
+                base_dir.join(path)
+            }
+        };
+        let global_wal_dir = resolve(&config.wal_dir);
+        let global_db_path = resolve(&config.db_path);
+
+        let accounts_dir = global_wal_dir.join(ACCOUNTS_DIR);
+        let account_wal_dir = accounts_dir.join(uid.to_string());
+        let account_db_path = global_db_path
+            .parent()
+            .unwrap_or(base_dir)
+            .join(ACCOUNTS_DIR)
+            .join(uid.to_string())
+            .join(global_db_path.file_name().unwrap_or_else(|| "history.db".as_ref()));
+
+        let adopt_legacy = !accounts_dir.exists();
+
+        let mut account_config = config;
+        account_config.wal_dir = account_wal_dir.to_string_lossy().into_owned();
+        account_config.db_path = account_db_path.to_string_lossy().into_owned();
+        let mut manager = Self::new(account_config, base_dir);
+        manager.account_uid = Some(uid);
+
+        if adopt_legacy {
+            manager.adopt_legacy_data(&global_wal_dir, &global_db_path);
+        }
+
+        manager
+    }
+
+    /// 将全局目录中的任务文件和历史记录迁移到当前账号目录（只在首个账号目录创建时执行一次）
+    fn adopt_legacy_data(&self, global_wal_dir: &std::path::Path, global_db_path: &std::path::Path) {
+        let mut moved = 0usize;
+        if let Ok(entries) = std::fs::read_dir(global_wal_dir) {
+            for entry in entries.flatten() {
+                if entry.file_name() == ACCOUNTS_DIR {
+                    continue;
+                }
+                let target = self.wal_dir.join(entry.file_name());
+                match std::fs::rename(entry.path(), &target) {
+                    Ok(()) => moved += 1,
+                    Err(e) => warn!("迁移 WAL 文件失败: {:?} -> {:?}, 错误: {}", entry.path(), target, e),
+                }
+            }
+        }
+
+        let account_db = match self.history_db.as_ref() {
+            Some(db) if global_db_path.exists() => db,
+            _ => {
+                info!("已将 {} 个全局 WAL 文件迁移到账号 {:?} 目录", moved, self.account_uid);
+                return;
+            }
+        };
+        let copied = HistoryDbManager::new(global_db_path).and_then(|legacy| {
+            let tasks = account_db.add_tasks_to_history_batch(&legacy.load_all_task_history()?)?;
+            let folders =
+                account_db.add_folders_to_history_batch(&legacy.load_all_folder_history()?)?;
+            for config in legacy.load_all_cloud_dl_auto_download()? {
+                account_db.save_cloud_dl_auto_download(&config)?;
+            }
+            Ok((tasks, folders))
+        });
+        match copied {
+            Ok((tasks, folders)) => info!(
+                "已将全局数据迁移到账号 {:?}: WAL 文件 {} 个, 历史任务 {} 个, 历史文件夹 {} 个",
+                self.account_uid, moved, tasks, folders
+            ),
+            Err(e) => error!("迁移全局历史记录失败: {}", e),
         }
     }
 
+    /// 所属账号 UID
+    pub fn account_uid(&self) -> Option<u64> {
+        self.account_uid
+    }
+
     /// 获取 WAL 目录路径
     pub fn wal_dir(&self) -> &PathBuf {
         &self.wal_dir
@@ -133,6 +232,19 @@ impl PersistenceManager {
         &self.config
     }
 
+    /// 动态更新 WAL 刷写间隔
+    ///
+    /// 后台刷写循环在下一个周期改用新间隔，已缓存的记录保留在缓存中等待下次刷写
+    pub fn update_flush_interval(&mut self, flush_interval_ms: u64) {
+        let flush_interval_ms = flush_interval_ms.max(1);
+        if self.config.wal_flush_interval_ms == flush_interval_ms {
+            return;
+        }
+        self.config.wal_flush_interval_ms = flush_interval_ms;
+        self.flush_interval_tx.send_replace(flush_interval_ms);
+        info!("WAL 刷写间隔已更新: {}ms", flush_interval_ms);
+    }
+
     /// 获取历史数据库管理器引用
     pub fn history_db(&self) -> Option<&Arc<HistoryDbManager>> {
         self.history_db.as_ref()
@@ -196,10 +308,26 @@ impl PersistenceManager {
             })
     }
 
+    /// 按条件分页查询历史任务
+    ///
+    /// 历史数据库未启用或查询失败时返回 None
+    pub fn query_history_tasks(&self, query: &TaskHistoryQuery) -> Option<(Vec<TaskMetadata>, usize)> {
+        self.history_db
+            .as_ref()
+            .and_then(|db| db.query_task_history(query).ok())
+    }
+
     // ========================================================================
     // 启动和关闭
     // ========================================================================
 
+    /// 后台刷写任务是否在运行
+    pub fn is_running(&self) -> bool {
+        self.flush_task
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
     /// 启动后台刷写任务
     ///
     /// 启动一个独立的 tokio 任务，定期将 WAL 缓存刷写到磁盘
@@ -217,11 +345,12 @@ impl PersistenceManager {
 
         let tasks = Arc::clone(&self.tasks);
         let wal_dir = self.wal_dir.clone();
-        let flush_interval_ms = self.config.wal_flush_interval_ms;
+        let flush_interval_rx = self.flush_interval_tx.subscribe();
+        let compress = self.config.compress_wal;
         let shutdown_rx = self.shutdown_tx.subscribe();
 
         let handle = tokio::spawn(async move {
-            wal_flush_loop(tasks, wal_dir, flush_interval_ms, shutdown_rx).await;
+            wal_flush_loop(tasks, wal_dir, flush_interval_rx, compress, shutdown_rx).await;
         });
 
         self.flush_task = Some(handle);
@@ -238,6 +367,9 @@ impl PersistenceManager {
     }
 
     /// 从 JSONL 文件迁移到 SQLite 数据库（一次性迁移）
+    ///
+    /// 历史数据库是历史记录的唯一存储，迁移成功（或旧文件为空）后删除旧文件，
+    /// 迁移失败则保留旧文件，下次启动时重试
     fn migrate_jsonl_to_db(&self) {
         let history_db = match &self.history_db {
             Some(db) => db,
@@ -254,25 +386,18 @@ impl PersistenceManager {
             match history::load_history_cache(&self.wal_dir) {
                 Ok(cache) => {
                     let tasks: Vec<TaskMetadata> = cache.into_iter().map(|(_, v)| v).collect();
-                    if !tasks.is_empty() {
-                        match history_db.add_tasks_to_history_batch(&tasks) {
-                            Ok(count) => {
-                                info!("成功迁移 {} 条任务历史到数据库", count);
-                                // 迁移成功后重命名旧文件为 .bak
-                                let bak_path = history_jsonl_path.with_extension("jsonl.bak");
-                                if let Err(e) = std::fs::rename(&history_jsonl_path, &bak_path) {
-                                    warn!("重命名旧历史文件失败: {}", e);
-                                } else {
-                                    info!("已将旧历史文件重命名为: {:?}", bak_path);
-                                    // 删除备份文件
-                                    if let Err(e) = std::fs::remove_file(&bak_path) {
-                                        warn!("删除备份文件失败: {}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("迁移任务历史到数据库失败: {}", e);
-                            }
+                    let result = if tasks.is_empty() {
+                        Ok(0)
+                    } else {
+                        history_db.add_tasks_to_history_batch(&tasks)
+                    };
+                    match result {
+                        Ok(count) => {
+                            info!("成功迁移 {} 条任务历史到数据库", count);
+                            remove_legacy_history_file(&history_jsonl_path);
+                        }
+                        Err(e) => {
+                            error!("迁移任务历史到数据库失败: {}", e);
                         }
                     }
                 }
@@ -288,25 +413,18 @@ impl PersistenceManager {
             info!("检测到旧文件夹历史文件，开始迁移: {:?}", folder_history_jsonl_path);
             match super::folder::load_folder_history(&self.wal_dir) {
                 Ok(folders) => {
-                    if !folders.is_empty() {
-                        match history_db.add_folders_to_history_batch(&folders) {
-                            Ok(count) => {
-                                info!("成功迁移 {} 条文件夹历史到数据库", count);
-                                // 迁移成功后重命名旧文件为 .bak
-                                let bak_path = folder_history_jsonl_path.with_extension("jsonl.bak");
-                                if let Err(e) = std::fs::rename(&folder_history_jsonl_path, &bak_path) {
-                                    warn!("重命名旧文件夹历史文件失败: {}", e);
-                                } else {
-                                    info!("已将旧文件夹历史文件重命名为: {:?}", bak_path);
-                                    // 删除备份文件
-                                    if let Err(e) = std::fs::remove_file(&bak_path) {
-                                        warn!("删除备份文件失败: {}", e);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("迁移文件夹历史到数据库失败: {}", e);
-                            }
+                    let result = if folders.is_empty() {
+                        Ok(0)
+                    } else {
+                        history_db.add_folders_to_history_batch(&folders)
+                    };
+                    match result {
+                        Ok(count) => {
+                            info!("成功迁移 {} 条文件夹历史到数据库", count);
+                            remove_legacy_history_file(&folder_history_jsonl_path);
+                        }
+                        Err(e) => {
+                            error!("迁移文件夹历史到数据库失败: {}", e);
                         }
                     }
                 }
@@ -619,8 +737,11 @@ impl PersistenceManager {
             return Ok(());
         }
 
+        // 记录源文件修改时间，恢复时用于检测文件是否被修改
+        let source_mtime = file_mtime_secs(&source_path);
+
         // 创建元数据
-        let metadata = TaskMetadata::new_upload(
+        let mut metadata = TaskMetadata::new_upload(
             task_id.clone(),
             source_path,
             target_path,
@@ -631,6 +752,8 @@ impl PersistenceManager {
             encryption_key_version,
         );
 
+        metadata.source_mtime = source_mtime;
+
         // 保存元数据到文件
         save_metadata(&self.wal_dir, &metadata)?;
 
@@ -671,8 +794,11 @@ impl PersistenceManager {
             return Ok(());
         }
 
+        // 记录源文件修改时间，恢复时用于检测文件是否被修改
+        let source_mtime = file_mtime_secs(&source_path);
+
         // 创建备份任务元数据
-        let metadata = TaskMetadata::new_upload_backup(
+        let mut metadata = TaskMetadata::new_upload_backup(
             task_id.clone(),
             source_path,
             target_path,
@@ -684,6 +810,8 @@ impl PersistenceManager {
             encryption_key_version,
         );
 
+        metadata.source_mtime = source_mtime;
+
         // 保存元数据到文件
         save_metadata(&self.wal_dir, &metadata)?;
 
@@ -816,6 +944,21 @@ impl PersistenceManager {
         }
     }
 
+    /// 标记分片完成（下载任务，带分片数据 CRC32）
+    ///
+    /// CRC32 用于崩溃恢复时校验磁盘数据（persistence.verify_chunks_on_recovery）
+    pub fn on_chunk_completed_with_crc(&self, task_id: &str, chunk_index: usize, crc32: u32) {
+        if let Some(mut info) = self.tasks.get_mut(task_id) {
+            info.mark_chunk_completed_with_crc(chunk_index, Some(crc32));
+            debug!(
+                "分片完成(带CRC32): task_id={}, chunk_index={}, crc32={:08x}",
+                task_id, chunk_index, crc32
+            );
+        } else {
+            debug!("任务不存在，跳过分片完成标记(带CRC32): task_id={}", task_id);
+        }
+    }
+
     /// 标记分片完成（上传任务，带 MD5）
     ///
     /// # Arguments
@@ -1100,6 +1243,207 @@ impl PersistenceManager {
         Ok(())
     }
 
+    /// 重置上传任务的断点进度
+    ///
+    /// upload_id 在服务端失效后，已上传的分片无法再合并，需要清空 WAL 中的分片记录
+    /// 和元数据中的 upload_id，下次启动任务时重新 precreate 并从头上传
+    ///
+    /// # Arguments
+    /// * `task_id` - 任务 ID
+    pub fn reset_upload_progress(&self, task_id: &str) -> std::io::Result<()> {
+        if let Err(e) = delete_wal_file(&self.wal_dir, task_id) {
+            warn!("删除上传任务 WAL 文件失败: task_id={}, error={}", task_id, e);
+        }
+
+        let mut total_chunks = 0;
+        update_metadata(&self.wal_dir, task_id, |m| {
+            total_chunks = m.total_chunks.unwrap_or(0);
+            m.clear_upload_id();
+        })?;
+
+        // 替换内存中的持久化信息（丢弃尚未刷写的 WAL 缓存）
+        if self.tasks.contains_key(task_id) {
+            self.tasks.insert(
+                task_id.to_string(),
+                TaskPersistenceInfo::new_upload(task_id.to_string(), total_chunks),
+            );
+        }
+
+        info!("已重置上传任务断点进度: task_id={}", task_id);
+
+        Ok(())
+    }
+
+    /// 重置下载任务的断点进度
+    ///
+    /// 下载临时文件被删除（MD5 校验或重命名失败）后，WAL 中记录的已完成分片已无对应数据，
+    /// 需要清空 WAL 和内存中的分片记录，重试时从头下载
+    ///
+    /// # Arguments
+    /// * `task_id` - 任务 ID
+    pub fn reset_download_progress(&self, task_id: &str) -> std::io::Result<()> {
+        if let Err(e) = delete_wal_file(&self.wal_dir, task_id) {
+            warn!("删除下载任务 WAL 文件失败: task_id={}, error={}", task_id, e);
+        }
+
+        // 替换内存中的持久化信息（丢弃尚未刷写的 WAL 缓存）
+        if self.tasks.contains_key(task_id) {
+            let total_chunks = load_metadata(&self.wal_dir, task_id)
+                .and_then(|m| m.total_chunks)
+                .unwrap_or(0);
+            self.tasks.insert(
+                task_id.to_string(),
+                TaskPersistenceInfo::new_download(task_id.to_string(), total_chunks),
+            );
+        }
+
+        info!("已重置下载任务断点进度: task_id={}", task_id);
+
+        Ok(())
+    }
+
+    /// 校验下载任务的远端文件指纹（恢复已完成分片前调用）
+    ///
+    /// 远端文件大小或 ETag 与上次记录不一致时丢弃全部断点进度（WAL 和内存状态），
+    /// 并按新的分片规划更新元数据；无论是否变化都会记录本次的指纹
+    ///
+    /// # Returns
+    /// - `Some(reason)` - 远端文件已变化，断点进度已重置
+    /// - `None` - 未变化或无法判断
+    pub fn validate_download_remote(
+        &self,
+        task_id: &str,
+        file_size: u64,
+        chunk_size: u64,
+        total_chunks: usize,
+        remote_size: Option<u64>,
+        remote_etag: Option<String>,
+    ) -> std::io::Result<Option<String>> {
+        if remote_size.is_none() && remote_etag.is_none() {
+            return Ok(None);
+        }
+
+        let reason = load_metadata(&self.wal_dir, task_id)
+            .and_then(|m| m.remote_change_reason(remote_size, remote_etag.as_deref()));
+
+        if reason.is_some() {
+            if let Err(e) = delete_wal_file(&self.wal_dir, task_id) {
+                warn!("删除下载任务 WAL 文件失败: task_id={}, error={}", task_id, e);
+            }
+            if self.tasks.contains_key(task_id) {
+                self.tasks.insert(
+                    task_id.to_string(),
+                    TaskPersistenceInfo::new_download(task_id.to_string(), total_chunks),
+                );
+            }
+        }
+
+        let changed = reason.is_some();
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            if changed {
+                m.file_size = Some(file_size);
+                m.chunk_size = Some(chunk_size);
+                m.total_chunks = Some(total_chunks);
+            }
+            m.set_remote_fingerprint(remote_size, remote_etag);
+        })?;
+
+        if let Some(ref reason) = reason {
+            info!("已重置下载任务断点进度: task_id={}, 原因: {}", task_id, reason);
+        }
+
+        Ok(reason)
+    }
+
+    /// 记录下载任务的网盘文件 MD5（恢复后仍能校验完整性）
+    pub fn update_download_expected_md5(&self, task_id: &str, expected_md5: String) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_expected_md5(expected_md5);
+        })?;
+
+        Ok(())
+    }
+
+    /// 记录下载任务的批量下载批次 ID
+    pub fn update_download_batch_id(&self, task_id: &str, batch_id: String) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_batch_id(batch_id);
+        })?;
+
+        Ok(())
+    }
+
+    /// 记录免转存下载任务的分享直链
+    pub fn update_download_direct_dlink(&self, task_id: &str, direct_dlink: String) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_direct_dlink(direct_dlink);
+        })?;
+
+        Ok(())
+    }
+
+    /// 记录免转存下载任务的分享来源
+    pub fn update_download_share_source(
+        &self,
+        task_id: &str,
+        share_source: crate::transfer::ShareDirectSource,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_share_source(share_source);
+        })?;
+
+        Ok(())
+    }
+
+    /// 记录下载任务的单任务限速（暂停/重启后恢复）
+    pub fn update_download_speed_limit(
+        &self,
+        task_id: &str,
+        speed_limit_bytes_per_sec: Option<u64>,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_speed_limit(speed_limit_bytes_per_sec);
+        })?;
+
+        Ok(())
+    }
+
+    /// 更新下载任务实际使用的 CDN 主机（节点变化时调用）
+    pub fn update_download_cdn_host(&self, task_id: &str, cdn_host: String) -> std::io::Result<()> {
+        debug!("已更新下载 CDN 主机: task_id={}, host={}", task_id, cdn_host);
+
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_cdn_host(cdn_host);
+        })?;
+
+        Ok(())
+    }
+
+    /// 更新上传任务源文件信息（源文件被修改后重新上传时调用）
+    ///
+    /// # Arguments
+    /// * `task_id` - 任务 ID
+    /// * `file_size` - 当前文件大小
+    /// * `source_mtime` - 当前文件修改时间（Unix 秒）
+    pub fn update_upload_source_info(
+        &self,
+        task_id: &str,
+        file_size: u64,
+        source_mtime: Option<i64>,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.file_size = Some(file_size);
+            m.source_mtime = source_mtime;
+        })?;
+
+        debug!(
+            "已更新上传源文件信息: task_id={}, file_size={}",
+            task_id, file_size
+        );
+
+        Ok(())
+    }
+
     /// 更新分享直下相关字段
     ///
     /// # Arguments
@@ -1124,6 +1468,71 @@ impl PersistenceManager {
         Ok(())
     }
 
+    /// 标记转存任务为「转存→下载→删除网盘副本」任务
+    pub fn update_transfer_download_cleanup(
+        &self,
+        task_id: &str,
+        download_cleanup: bool,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_download_cleanup(download_cleanup);
+        })?;
+
+        debug!(
+            "已更新下载后清理标记: task_id={}, download_cleanup={}",
+            task_id, download_cleanup
+        );
+
+        Ok(())
+    }
+
+    /// 标记转存任务为递归展开文件夹转存
+    pub fn update_transfer_recursive(&self, task_id: &str, recursive: bool) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_transfer_recursive(recursive);
+        })?;
+
+        debug!("已更新递归转存标记: task_id={}, recursive={}", task_id, recursive);
+
+        Ok(())
+    }
+
+    /// 更新转存任务自动下载时的分享子文件夹命名方式
+    pub fn update_transfer_share_subfolder(
+        &self,
+        task_id: &str,
+        share_subfolder: crate::transfer::types::ShareSubfolderNaming,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_share_subfolder(share_subfolder);
+        })?;
+
+        debug!(
+            "已更新分享子文件夹命名: task_id={}, share_subfolder={:?}",
+            task_id, share_subfolder
+        );
+
+        Ok(())
+    }
+
+    /// 更新转存任务实际转存成功的文件数量
+    pub fn update_transferred_count(
+        &self,
+        task_id: &str,
+        transferred_count: usize,
+    ) -> std::io::Result<()> {
+        update_metadata(&self.wal_dir, task_id, move |m| {
+            m.set_transferred_count(transferred_count);
+        })?;
+
+        debug!(
+            "已更新转存成功数量: task_id={}, transferred_count={}",
+            task_id, transferred_count
+        );
+
+        Ok(())
+    }
+
     /// 更新临时目录清理状态
     ///
     /// # Arguments
@@ -1388,7 +1797,7 @@ impl PersistenceManager {
     ///
     /// 用于测试或强制刷写场景
     pub async fn flush_all(&self) {
-        flush_all_tasks(&self.tasks, &self.wal_dir).await;
+        flush_all_tasks(&self.tasks, &self.wal_dir, self.config.compress_wal).await;
     }
 }
 
@@ -1398,25 +1807,33 @@ impl PersistenceManager {
 
 /// WAL 刷写循环
 ///
-/// 定期将所有任务的 WAL 缓存刷写到磁盘
+/// 定期将所有任务的 WAL 缓存刷写到磁盘，刷写间隔变更时重建定时器
 async fn wal_flush_loop(
     tasks: Arc<DashMap<String, TaskPersistenceInfo>>,
     wal_dir: PathBuf,
-    flush_interval_ms: u64,
+    mut flush_interval_rx: watch::Receiver<u64>,
+    compress: bool,
     mut shutdown_rx: broadcast::Receiver<()>,
 ) {
+    let flush_interval_ms = *flush_interval_rx.borrow_and_update();
     let mut interval = tokio::time::interval(Duration::from_millis(flush_interval_ms));
 
     loop {
         tokio::select! {
             _ = interval.tick() => {
                 // 正常刷写
-                flush_all_tasks(&tasks, &wal_dir).await;
+                flush_all_tasks(&tasks, &wal_dir, compress).await;
+            }
+            Ok(()) = flush_interval_rx.changed() => {
+                // 🔥 间隔变更：从现在起按新间隔计时，缓存中的记录在下个周期刷写
+                let period = Duration::from_millis(*flush_interval_rx.borrow_and_update());
+                interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                debug!("WAL 刷写定时器已重建: {:?}", period);
             }
             _ = shutdown_rx.recv() => {
                 // 收到关闭信号，执行最终刷写
                 info!("收到关闭信号，执行最终刷写");
-                flush_all_tasks(&tasks, &wal_dir).await;
+                flush_all_tasks(&tasks, &wal_dir, compress).await;
                 break;
             }
         }
@@ -1426,7 +1843,13 @@ async fn wal_flush_loop(
 }
 
 /// 刷写所有任务的 WAL 缓存
-async fn flush_all_tasks(tasks: &DashMap<String, TaskPersistenceInfo>, wal_dir: &PathBuf) {
+///
+/// `compress` 为 true 时写入压缩模式（见 [`append_records_compressed`]）
+async fn flush_all_tasks(
+    tasks: &DashMap<String, TaskPersistenceInfo>,
+    wal_dir: &PathBuf,
+    compress: bool,
+) {
     let mut flushed_count = 0;
     let mut record_count = 0;
 
@@ -1442,7 +1865,12 @@ async fn flush_all_tasks(tasks: &DashMap<String, TaskPersistenceInfo>, wal_dir:
             record_count += records.len();
 
             // 刷写到磁盘
-            if let Err(e) = append_records(wal_dir, task_id, &records) {
+            let result = if compress {
+                append_records_compressed(wal_dir, task_id, &records)
+            } else {
+                append_records(wal_dir, task_id, &records)
+            };
+            if let Err(e) = result {
                 error!("WAL 刷写失败: task_id={}, 错误: {}", task_id, e);
                 // 失败时将记录放回缓存**头部**，保持时序正确性
                 // 若 push 到尾部，新到达的记录会排在旧记录前面，
@@ -1695,6 +2123,14 @@ async fn cleanup_expired_from_db(
 // 单元测试
 // ============================================================================
 
+/// 删除已迁移到数据库的旧版历史文件
+fn remove_legacy_history_file(path: &std::path::Path) {
+    match std::fs::remove_file(path) {
+        Ok(()) => info!("已删除旧历史文件: {:?}", path),
+        Err(e) => warn!("删除旧历史文件失败: {:?}, 错误: {}", path, e),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1716,6 +2152,8 @@ mod tests {
             history_archive_hour: 2,
             history_archive_minute: 0,
             history_retention_days: 30,
+            verify_chunks_on_recovery: false,
+            compress_wal: false,
         }
     }
 
@@ -2011,6 +2449,167 @@ mod tests {
         assert!(metadata.upload_id_created_at.is_some());
     }
 
+    #[test]
+    fn test_reset_upload_progress() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let manager = PersistenceManager::new(config, temp_dir.path());
+
+        manager
+            .register_upload_task(
+                "up_reset".to_string(),
+                PathBuf::from("/local"),
+                "/remote".to_string(),
+                1024,
+                256,
+                4,
+                None,  // encrypt_enabled
+                None,  // encryption_key_version
+            )
+            .unwrap();
+        manager
+            .update_upload_id("up_reset", "expired_upload_id".to_string())
+            .unwrap();
+        manager.on_chunk_completed_with_md5("up_reset", 0, "md5_0".to_string());
+        manager.on_chunk_completed_with_md5("up_reset", 1, "md5_1".to_string());
+        assert_eq!(manager.get_completed_count("up_reset"), Some(2));
+
+        manager.reset_upload_progress("up_reset").unwrap();
+
+        // 分片进度和 upload_id 均已清空，分片数保持不变
+        assert_eq!(manager.get_completed_count("up_reset"), Some(0));
+        assert_eq!(manager.get_chunk_md5s("up_reset").map(|m| m.len()), Some(4));
+        let metadata = metadata::load_metadata(&manager.wal_dir, "up_reset").unwrap();
+        assert!(metadata.upload_id.is_none());
+        assert!(metadata.upload_id_created_at.is_none());
+    }
+
+    #[test]
+    fn test_reset_download_progress() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let manager = PersistenceManager::new(config, temp_dir.path());
+
+        manager
+            .register_download_task(
+                "dl_reset".to_string(),
+                12345,
+                "/remote/file.txt".to_string(),
+                PathBuf::from("/local/file.txt"),
+                1024,
+                256,
+                4,
+                None,
+                None,
+                None,
+                false,
+                None,
+                None,  // is_encrypted
+                None,  // encryption_key_version
+                None,  // transfer_task_id
+            )
+            .unwrap();
+        for chunk_index in 0..4 {
+            manager.on_chunk_completed("dl_reset", chunk_index);
+        }
+        assert_eq!(manager.get_completed_count("dl_reset"), Some(4));
+
+        manager.reset_download_progress("dl_reset").unwrap();
+
+        // 已完成分片全部清空，重试时从头下载
+        assert_eq!(manager.get_completed_count("dl_reset"), Some(0));
+        assert_eq!(manager.get_pending_chunks("dl_reset", 4), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_new_for_account_isolates_and_adopts_legacy() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+
+        // 升级前的全局目录：一个未完成任务和一条历史记录
+        let legacy = PersistenceManager::new(config.clone(), temp_dir.path());
+        let active = TaskMetadata::new_download(
+            "dl_active".to_string(), 1, "/a".to_string(), PathBuf::from("/local/a"), 4, 4, 1, None, None,
+        );
+        let archived = TaskMetadata::new_download(
+            "dl_archived".to_string(), 2, "/b".to_string(), PathBuf::from("/local/b"), 4, 4, 1, None, None,
+        );
+        metadata::save_metadata(legacy.wal_dir(), &active).unwrap();
+        legacy.history_db().unwrap().add_task_to_history(&archived).unwrap();
+        drop(legacy);
+
+        // 首个加载的账号接管全局数据
+        let first = PersistenceManager::new_for_account(config.clone(), temp_dir.path(), 1);
+        assert_eq!(first.account_uid(), Some(1));
+        assert!(first.wal_dir().ends_with("wal/accounts/1"));
+        assert!(metadata::metadata_exists(first.wal_dir(), "dl_active"));
+        assert!(!metadata::metadata_exists(&temp_dir.path().join("wal"), "dl_active"));
+        assert!(first.get_history_task("dl_archived").is_some());
+
+        // 其他账号使用独立的空目录
+        let second = PersistenceManager::new_for_account(config, temp_dir.path(), 2);
+        assert!(!metadata::metadata_exists(second.wal_dir(), "dl_active"));
+        assert!(second.get_history_task("dl_archived").is_none());
+    }
+
+    #[test]
+    fn test_validate_download_remote() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let manager = PersistenceManager::new(config, temp_dir.path());
+
+        manager
+            .register_download_task(
+                "dl_remote".to_string(),
+                12345,
+                "/remote/file.txt".to_string(),
+                PathBuf::from("/local/file.txt"),
+                1024,
+                256,
+                4,
+                None,
+                None,
+                None,
+                false,
+                None,
+                None,  // is_encrypted
+                None,  // encryption_key_version
+                None,  // transfer_task_id
+            )
+            .unwrap();
+
+        // 首次探测只记录指纹
+        let etag = Some("\"etag-1\"".to_string());
+        assert_eq!(
+            manager
+                .validate_download_remote("dl_remote", 1024, 256, 4, Some(1024), etag.clone())
+                .unwrap(),
+            None
+        );
+        manager.on_chunk_completed("dl_remote", 0);
+        manager.on_chunk_completed("dl_remote", 1);
+
+        // 远端未变化：保留断点
+        assert_eq!(
+            manager
+                .validate_download_remote("dl_remote", 1024, 256, 4, Some(1024), etag)
+                .unwrap(),
+            None
+        );
+        assert_eq!(manager.get_completed_count("dl_remote"), Some(2));
+
+        // 远端大小变化：清空断点并按新的分片规划更新元数据
+        let reason = manager
+            .validate_download_remote("dl_remote", 2048, 256, 8, Some(2048), Some("\"etag-2\"".to_string()))
+            .unwrap();
+        assert!(reason.is_some());
+        assert_eq!(manager.get_completed_count("dl_remote"), Some(0));
+        let metadata = metadata::load_metadata(&manager.wal_dir, "dl_remote").unwrap();
+        assert_eq!(metadata.file_size, Some(2048));
+        assert_eq!(metadata.total_chunks, Some(8));
+        assert_eq!(metadata.remote_etag.as_deref(), Some("\"etag-2\""));
+    }
+
     #[tokio::test]
     async fn test_flush_all() {
         let temp_dir = setup_temp_dir();
@@ -2147,6 +2746,51 @@ mod tests {
         assert!(wal::wal_exists(&manager.wal_dir, "dl_006"));
     }
 
+    #[tokio::test]
+    async fn test_update_flush_interval() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let mut manager = PersistenceManager::new(config, temp_dir.path());
+        manager.start();
+
+        manager
+            .register_download_task(
+                "dl_interval".to_string(),
+                111,
+                "/path".to_string(),
+                PathBuf::from("/local"),
+                1024,
+                256,
+                4,
+                None,
+                None,
+                None,
+                false,
+                None,
+                None,  // is_encrypted
+                None,  // encryption_key_version
+                None
+            )
+            .unwrap();
+
+        // 调大间隔后不会再周期刷写，记录保留在缓存中
+        manager.update_flush_interval(60_000);
+        assert_eq!(manager.config().wal_flush_interval_ms, 60_000);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        manager.on_chunk_completed("dl_interval", 0);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!wal::wal_exists(&manager.wal_dir, "dl_interval"));
+
+        // 调小间隔后在下个周期刷写，缓存记录不丢失
+        manager.update_flush_interval(50);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let records = read_records(&manager.wal_dir, "dl_interval").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].chunk_index, 0);
+
+        manager.shutdown().await;
+    }
+
     /// 回归测试：restore_task_state 之后再调用 register_download_task
     /// 不能覆盖已恢复的 completed_chunks / partial_progress，
     /// 也不能重写 .meta 文件（保留原始 created_at）
@@ -2325,4 +2969,63 @@ mod tests {
             "冷恢复后，分片 #5 的 partial_progress 应被恢复"
         );
     }
+
+    #[test]
+    fn test_migrate_jsonl_to_db() {
+        use std::io::Write;
+
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let manager = PersistenceManager::new(config, temp_dir.path());
+
+        // 写入旧版历史文件
+        let mut task = TaskMetadata::new_download(
+            "dl_legacy".to_string(),
+            1,
+            "/legacy.txt".to_string(),
+            PathBuf::from("/local/legacy.txt"),
+            1024,
+            256,
+            4,
+            None,
+            None,
+        );
+        task.mark_completed();
+        let history_path = history::get_history_path(&manager.wal_dir);
+        let mut file = std::fs::File::create(&history_path).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&task).unwrap()).unwrap();
+
+        let folder = crate::downloader::folder::FolderDownload::new(
+            "/legacy_folder".to_string(),
+            PathBuf::from("/local/legacy_folder"),
+        );
+        let persisted = super::super::folder::FolderPersisted::from_folder(&folder);
+        let folder_history_path = super::super::folder::get_folder_history_path(&manager.wal_dir);
+        let mut file = std::fs::File::create(&folder_history_path).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&persisted).unwrap()).unwrap();
+
+        manager.migrate_jsonl_to_db();
+
+        // 迁移后旧文件删除，历史只存在于数据库
+        assert!(!history_path.exists());
+        assert!(!folder_history_path.exists());
+        let db = manager.history_db().unwrap();
+        assert!(db.task_exists_in_history("dl_legacy").unwrap());
+        let folders = db.load_all_folder_history().unwrap();
+        assert!(folders.iter().any(|f| f.id == persisted.id));
+    }
+
+    #[test]
+    fn test_migrate_empty_jsonl_removes_file() {
+        let temp_dir = setup_temp_dir();
+        let config = create_test_config();
+        let manager = PersistenceManager::new(config, temp_dir.path());
+
+        let history_path = history::get_history_path(&manager.wal_dir);
+        std::fs::write(&history_path, "").unwrap();
+
+        manager.migrate_jsonl_to_db();
+
+        assert!(!history_path.exists());
+    }
 }
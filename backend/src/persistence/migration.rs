@@ -0,0 +1,221 @@
+//! 任务列表导出/导入
+//!
+//! 用于在机器之间迁移未完成的下载、上传、转存任务：
+//! - 导出：收集 WAL 目录中所有未完成任务的元数据（不包含文件数据和分片进度）
+//! - 导入：校验元数据完整性，重置进度后按恢复流程重建任务（暂停状态）
+//!
+//! 文件数据不随任务迁移，导入后的下载/上传任务会从头开始
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::metadata::scan_all_metadata;
+use super::types::{TaskMetadata, TaskPersistenceStatus, TaskType};
+
+/// 导出文件格式版本
+pub const TASK_EXPORT_VERSION: u32 = 1;
+
+/// 任务导出包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskExportBundle {
+    /// 格式版本
+    pub version: u32,
+    /// 导出时间
+    pub exported_at: DateTime<Utc>,
+    /// 任务元数据列表（按创建时间排序）
+    pub tasks: Vec<TaskMetadata>,
+}
+
+/// 导出 WAL 目录中所有未完成的任务（已完成但尚未归档的任务不导出）
+pub fn export_tasks(wal_dir: &Path) -> std::io::Result<TaskExportBundle> {
+    let mut tasks: Vec<TaskMetadata> = scan_all_metadata(wal_dir)?
+        .into_iter()
+        .filter(|m| m.status != Some(TaskPersistenceStatus::Completed))
+        .collect();
+    tasks.sort_by_key(|m| m.created_at);
+
+    Ok(TaskExportBundle {
+        version: TASK_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        tasks,
+    })
+}
+
+/// 是否为普通 UUID（带或不带连字符，仅包含十六进制字符和 -）
+fn is_plain_uuid(id: &str) -> bool {
+    id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') && uuid::Uuid::parse_str(id).is_ok()
+}
+
+/// 检查导入的任务元数据能否重建任务
+///
+/// # Returns
+/// - `Ok(())` - 可以导入
+/// - `Err(reason)` - 无法重建的原因
+pub fn validate_import(metadata: &TaskMetadata) -> Result<(), String> {
+    if metadata.task_id.trim().is_empty() {
+        return Err("缺少任务 ID".to_string());
+    }
+    // 任务 ID 会拼接为 WAL 目录下的文件名，只接受 UUID（不含路径分隔符和 ..）
+    if !is_plain_uuid(&metadata.task_id) {
+        return Err("任务 ID 不是有效的 UUID".to_string());
+    }
+    if metadata.status == Some(TaskPersistenceStatus::Completed) {
+        return Err("任务已完成".to_string());
+    }
+    if metadata.is_backup {
+        return Err("备份任务依赖自动备份配置，不支持迁移".to_string());
+    }
+
+    fn non_empty(value: Option<&str>) -> bool {
+        value.is_some_and(|s| !s.trim().is_empty())
+    }
+
+    match metadata.task_type {
+        TaskType::Download => {
+            if metadata.group_id.is_some() {
+                return Err("文件夹下载子任务不支持单独迁移".to_string());
+            }
+            if metadata.fs_id.is_none_or(|fs_id| fs_id == 0) {
+                return Err("缺少 fs_id".to_string());
+            }
+            if !non_empty(metadata.remote_path.as_deref()) {
+                return Err("缺少远程路径".to_string());
+            }
+            if metadata.local_path.is_none() {
+                return Err("缺少本地保存路径".to_string());
+            }
+        }
+        TaskType::Upload => {
+            if metadata.source_path.is_none() {
+                return Err("缺少本地源文件路径".to_string());
+            }
+            if !non_empty(metadata.target_path.as_deref()) {
+                return Err("缺少远程目标路径".to_string());
+            }
+        }
+        TaskType::Transfer => {
+            if !non_empty(metadata.share_link.as_deref()) {
+                return Err("缺少分享链接".to_string());
+            }
+            if !non_empty(metadata.transfer_target_path.as_deref()) {
+                return Err("缺少转存目标路径".to_string());
+            }
+        }
+    }
+
+    if metadata.task_type != TaskType::Transfer
+        && (metadata.file_size.is_none()
+            || metadata.chunk_size.is_none()
+            || metadata.total_chunks.is_none())
+    {
+        return Err("缺少文件大小或分片信息".to_string());
+    }
+
+    Ok(())
+}
+
+/// 将导入的元数据重置为可在新机器上恢复的状态
+///
+/// - 下载/上传任务：标记为暂停，清除 upload_id（分片进度不随任务迁移）
+/// - 转存任务：已转存的保留"已转存"状态，其余状态在恢复时标记为失败等待用户重试；
+///   分享直下的临时目录不迁移
+pub fn prepare_import(mut metadata: TaskMetadata) -> TaskMetadata {
+    metadata.updated_at = Utc::now();
+    metadata.completed_at = None;
+    metadata.error_msg = None;
+
+    match metadata.task_type {
+        TaskType::Download | TaskType::Upload => {
+            metadata.status = Some(TaskPersistenceStatus::Paused);
+            metadata.upload_id = None;
+            metadata.upload_id_created_at = None;
+        }
+        TaskType::Transfer => {
+            let transferred = matches!(
+                metadata.transfer_status.as_deref(),
+                Some("transferred" | "downloading" | "cleaning")
+            );
+            metadata.transfer_status = transferred.then(|| "transferred".to_string());
+            metadata.download_task_ids.clear();
+            metadata.is_share_direct_download = None;
+            metadata.temp_dir = None;
+            metadata.cleanup_status = None;
+        }
+    }
+
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::metadata::save_metadata;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn download_metadata(task_id: &str) -> TaskMetadata {
+        TaskMetadata::new_download(
+            task_id.to_string(),
+            12345,
+            "/remote/file.bin".to_string(),
+            PathBuf::from("/local/file.bin"),
+            1024,
+            256,
+            4,
+            None,
+            None,
+        )
+    }
+
+    const ACTIVE_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    #[test]
+    fn test_export_and_validate() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = temp_dir.path();
+
+        save_metadata(wal_dir, &download_metadata(ACTIVE_ID)).unwrap();
+        let mut completed = download_metadata("dl_done");
+        completed.status = Some(TaskPersistenceStatus::Completed);
+        save_metadata(wal_dir, &completed).unwrap();
+
+        let bundle = export_tasks(wal_dir).unwrap();
+        assert_eq!(bundle.version, TASK_EXPORT_VERSION);
+        assert_eq!(bundle.tasks.len(), 1);
+        assert_eq!(bundle.tasks[0].task_id, ACTIVE_ID);
+        assert!(validate_import(&bundle.tasks[0]).is_ok());
+
+        // 任务 ID 必须是 UUID，防止拼接出 WAL 目录之外的路径
+        for bad_id in ["../../etc/passwd", "a/b", "dl_active", "{67e55044-10b1-426f-9247-bb680e5fe0c8}"] {
+            assert!(validate_import(&download_metadata(bad_id)).is_err(), "{}", bad_id);
+        }
+        assert!(validate_import(&download_metadata("67e5504410b1426f9247bb680e5fe0c8")).is_ok());
+
+        // 缺少 fs_id / 远程路径的任务无法重建
+        let mut missing = download_metadata(ACTIVE_ID);
+        missing.fs_id = None;
+        assert!(validate_import(&missing).is_err());
+        let mut missing = download_metadata(ACTIVE_ID);
+        missing.remote_path = Some(String::new());
+        assert!(validate_import(&missing).is_err());
+
+        // 备份任务和文件夹子任务跳过
+        let mut backup = download_metadata(ACTIVE_ID);
+        backup.is_backup = true;
+        assert!(validate_import(&backup).is_err());
+        let mut subtask = download_metadata(ACTIVE_ID);
+        subtask.group_id = Some("folder_1".to_string());
+        assert!(validate_import(&subtask).is_err());
+    }
+
+    #[test]
+    fn test_prepare_import() {
+        let mut metadata = download_metadata("dl_001");
+        metadata.status = Some(TaskPersistenceStatus::Downloading);
+        metadata.error_msg = Some("网络错误".to_string());
+        let prepared = prepare_import(metadata);
+        assert_eq!(prepared.status, Some(TaskPersistenceStatus::Paused));
+        assert!(prepared.error_msg.is_none());
+    }
+}
@@ -58,6 +58,7 @@ pub mod history;
 pub mod history_db;
 pub mod manager;
 pub mod metadata;
+pub mod migration;
 pub mod recovery;
 pub mod types;
 pub mod wal;
@@ -67,8 +68,9 @@ pub use types::{TaskMetadata, TaskPersistenceInfo, TaskPersistenceStatus, TaskTy
 
 // 导出 WAL 操作
 pub use wal::{
-    append_records, delete_wal_file, ensure_wal_dir, get_wal_path, read_records, scan_wal_task_ids,
-    wal_exists, WalReader, WalWriter,
+    append_records, append_records_compressed, compact_wal, delete_wal_file, ensure_wal_dir,
+    get_compressed_wal_path, get_wal_path, read_records, scan_wal_task_ids, wal_exists, WalReader,
+    WalWriter,
 };
 
 // 导出元数据操作
@@ -80,26 +82,25 @@ pub use metadata::{
 // 导出持久化管理器
 pub use manager::PersistenceManager;
 
+// 导出任务迁移（导出/导入）
+pub use migration::{export_tasks, prepare_import, validate_import, TaskExportBundle};
+
 // 导出恢复模块
 pub use recovery::{
     cleanup_completed_tasks, cleanup_completed_tasks_with_db, cleanup_expired_tasks,
-    cleanup_invalid_tasks, scan_recoverable_tasks, DownloadRecoveryInfo, RecoveredTask,
-    RecoveryScanResult, TransferRecoveryInfo, UploadRecoveryInfo,
+    cleanup_invalid_tasks, file_mtime_secs, file_region_crc32, scan_recoverable_tasks,
+    DownloadRecoveryInfo,
+    RecoveredTask, RecoveryScanResult, TransferRecoveryInfo, UploadRecoveryInfo,
 };
 
-// 导出历史归档模块
-pub use history::{
-    add_to_history, archive_completed_tasks, cleanup_expired_history, get_history_path,
-    load_history_cache, load_history_task_ids, remove_from_history_file,
-    remove_tasks_by_group_from_history,
-};
+// 导出旧版历史文件读取（仅用于迁移到历史数据库）
+pub use history::{get_history_path, load_history_cache};
 
 // 导出文件夹持久化模块
 pub use folder::{
-    add_folder_to_history, archive_completed_folders, cleanup_expired_folder_history,
     delete_folder, get_folder_history_path, load_all_folders, load_folder, load_folder_history,
-    load_folder_history_ids, remove_folder_from_history, save_folder, FolderPersisted,
+    save_folder, FolderPersisted,
 };
 
 // 导出历史数据库模块
-pub use history_db::{CloudDlAutoDownloadConfig, HistoryDbManager};
+pub use history_db::{CloudDlAutoDownloadConfig, HistoryDbManager, TaskHistoryQuery};
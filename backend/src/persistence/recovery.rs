@@ -40,6 +40,9 @@ pub struct RecoveredTask {
 
     /// 分片内部分下载进度（chunk_index → bytes_downloaded，从 WAL 恢复）
     pub partial_progress: HashMap<usize, u64>,
+
+    /// 带 CRC32 的已完成分片（chunk_index, crc32），按 WAL 写入顺序（仅下载任务）
+    pub chunk_crcs: Vec<(usize, u32)>,
 }
 
 impl RecoveredTask {
@@ -160,6 +163,7 @@ pub fn scan_recoverable_tasks(wal_dir: &Path) -> std::io::Result<RecoveryScanRes
             None
         };
         let mut partial_progress: HashMap<usize, u64> = HashMap::new();
+        let mut chunk_crcs: Vec<(usize, u32)> = Vec::new();
 
         for record in &records {
             if record.is_partial() {
@@ -173,6 +177,10 @@ pub fn scan_recoverable_tasks(wal_dir: &Path) -> std::io::Result<RecoveryScanRes
                 // 分片已完成，清除对应的部分进度
                 partial_progress.remove(&record.chunk_index);
 
+                if let Some(crc) = record.crc32 {
+                    chunk_crcs.push((record.chunk_index, crc));
+                }
+
                 // 保存上传任务的 MD5
                 if let Some(ref mut md5s) = chunk_md5s {
                     if record.chunk_index < md5s.len() {
@@ -187,6 +195,7 @@ pub fn scan_recoverable_tasks(wal_dir: &Path) -> std::io::Result<RecoveryScanRes
             completed_chunks,
             chunk_md5s,
             partial_progress,
+            chunk_crcs,
         };
 
         // 检查是否已完成所有分片
@@ -342,7 +351,7 @@ pub fn cleanup_completed_tasks_with_db(
             // 确保标记为已完成
             metadata.mark_completed();
 
-            // 优先归档到数据库
+            // 历史记录统一存储在数据库
             if let Some(db) = history_db {
                 match db.add_task_to_history(&metadata) {
                     Ok(()) => {
@@ -354,17 +363,7 @@ pub fn cleanup_completed_tasks_with_db(
                     }
                 }
             } else {
-                // 回退到文件归档
-                use super::history::add_to_history;
-                match add_to_history(wal_dir, &metadata) {
-                    Ok(()) => {
-                        archived += 1;
-                        debug!("已归档已完成任务到历史文件: {}", task_id);
-                    }
-                    Err(e) => {
-                        warn!("归档任务 {} 到历史文件失败: {}", task_id, e);
-                    }
-                }
+                warn!("历史数据库不可用，任务 {} 不归档到历史记录", task_id);
             }
         }
 
@@ -499,6 +498,12 @@ pub struct TransferRecoveryInfo {
     pub temp_dir: Option<String>,
     /// 是否为分享直下任务
     pub is_share_direct_download: bool,
+    /// 是否为「转存→下载→删除网盘副本」任务
+    pub download_cleanup: bool,
+    /// 是否递归展开文件夹转存
+    pub recursive: bool,
+    /// 自动下载时的分享子文件夹命名方式
+    pub share_subfolder: Option<crate::transfer::types::ShareSubfolderNaming>,
     /// 文件列表 JSON
     pub file_list_json: Option<String>,
 }
@@ -518,6 +523,9 @@ impl TransferRecoveryInfo {
             created_at: metadata.created_at.timestamp(),
             temp_dir: metadata.temp_dir.clone(),
             is_share_direct_download: metadata.is_share_direct_download.unwrap_or(false),
+            download_cleanup: metadata.download_cleanup.unwrap_or(false),
+            recursive: metadata.recursive.unwrap_or(false),
+            share_subfolder: metadata.share_subfolder,
             file_list_json: metadata.file_list_json.clone(),
         })
     }
@@ -569,6 +577,22 @@ pub struct DownloadRecoveryInfo {
     // === 分片内断点续传 ===
     /// 分片内部分下载进度（chunk_index → bytes_downloaded）
     pub partial_progress: HashMap<usize, u64>,
+    // === 崩溃恢复校验 ===
+    /// 带 CRC32 的已完成分片（chunk_index, crc32），按 WAL 写入顺序
+    pub chunk_crcs: Vec<(usize, u32)>,
+    // === CDN 节点 ===
+    /// 上次实际提供下载的 CDN 主机
+    pub cdn_host: Option<String>,
+    /// 网盘文件 MD5（下载完成后校验）
+    pub expected_md5: Option<String>,
+    /// 批量下载批次 ID
+    pub batch_id: Option<String>,
+    /// 分享直链（免转存直接下载任务）
+    pub direct_dlink: Option<String>,
+    /// 直链所属的分享来源
+    pub share_source: Option<crate::transfer::ShareDirectSource>,
+    /// 单任务限速（字节/秒）
+    pub speed_limit_bytes_per_sec: Option<u64>,
 }
 
 impl DownloadRecoveryInfo {
@@ -600,6 +624,13 @@ impl DownloadRecoveryInfo {
             encryption_key_version: metadata.encryption_key_version,
             // 恢复分片内部分进度
             partial_progress: recovered.partial_progress.clone(),
+            chunk_crcs: recovered.chunk_crcs.clone(),
+            cdn_host: metadata.cdn_host.clone(),
+            expected_md5: metadata.expected_md5.clone(),
+            batch_id: metadata.batch_id.clone(),
+            direct_dlink: metadata.direct_dlink.clone(),
+            share_source: metadata.share_source.clone(),
+            speed_limit_bytes_per_sec: metadata.speed_limit_bytes_per_sec,
         })
     }
 
@@ -609,6 +640,63 @@ impl DownloadRecoveryInfo {
             .filter(|&i| !self.completed_chunks.contains(i))
             .collect()
     }
+
+    /// 校验最近完成的分片（崩溃时最可能写入不完整）
+    ///
+    /// 对 WAL 中最后 `max_chunks` 条带 CRC32 的完成记录，重新读取 `data_path` 对应区域并比对 CRC32，
+    /// 不一致或读取失败的分片从已完成集合中移除（恢复后重新下载）
+    ///
+    /// 返回被重新排队的分片索引
+    pub fn verify_recent_chunks(&mut self, data_path: &Path, max_chunks: usize) -> Vec<usize> {
+        let mut mismatched = Vec::new();
+        for &(index, expected) in self.chunk_crcs.iter().rev().take(max_chunks) {
+            if !self.completed_chunks.contains(index) || self.chunk_size == 0 {
+                continue;
+            }
+            let offset = index as u64 * self.chunk_size;
+            let len = self.chunk_size.min(self.file_size.saturating_sub(offset));
+            match file_region_crc32(data_path, offset, len) {
+                Ok(actual) if actual == expected => {}
+                Ok(actual) => {
+                    warn!(
+                        "任务 {} 分片 #{} CRC32 不一致 (期望 {:08x}, 实际 {:08x})，重新下载",
+                        self.task_id, index, expected, actual
+                    );
+                    mismatched.push(index);
+                }
+                Err(e) => {
+                    warn!(
+                        "任务 {} 分片 #{} 读取失败，重新下载: {}",
+                        self.task_id, index, e
+                    );
+                    mismatched.push(index);
+                }
+            }
+        }
+        for index in &mismatched {
+            self.completed_chunks.remove(*index);
+        }
+        mismatched
+    }
+}
+
+/// 计算文件指定区域的 CRC32（区域超出文件末尾时返回 UnexpectedEof）
+pub fn file_region_crc32(path: &Path, offset: u64, len: u64) -> std::io::Result<u32> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut hasher = crc32fast::Hasher::new();
+    let mut remaining = len;
+    let mut buf = vec![0u8; 256 * 1024];
+    while remaining > 0 {
+        let to_read = remaining.min(buf.len() as u64) as usize;
+        file.read_exact(&mut buf[..to_read])?;
+        hasher.update(&buf[..to_read]);
+        remaining -= to_read as u64;
+    }
+    Ok(hasher.finalize())
 }
 
 /// 恢复上传任务信息
@@ -648,6 +736,20 @@ pub struct UploadRecoveryInfo {
     pub encryption_key_version: Option<u32>,
     /// 加密前的原始远程路径（用于重启后重建去重索引）
     pub original_remote_path: Option<String>,
+    /// 注册任务时源文件的修改时间（Unix 秒，旧版本元数据为 None）
+    pub source_mtime: Option<i64>,
+}
+
+/// 读取文件修改时间（Unix 秒）
+pub fn file_mtime_secs(path: &Path) -> Option<i64> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64
+        })
 }
 
 impl UploadRecoveryInfo {
@@ -676,9 +778,37 @@ impl UploadRecoveryInfo {
             encrypt_enabled: metadata.encrypt_enabled,
             encryption_key_version: metadata.encryption_key_version,
             original_remote_path: metadata.original_remote_path.clone(),
+            source_mtime: metadata.source_mtime,
         })
     }
 
+    /// 检查源文件自上次上传以来是否被修改
+    ///
+    /// 比较当前文件大小和修改时间与注册时记录的值（旧版本元数据没有 mtime，只比较大小）
+    ///
+    /// # 返回
+    /// - `Some(reason)`: 文件已变化，断点不可用
+    /// - `None`: 文件未变化或无法读取（由调用方处理文件不存在）
+    pub fn source_changed_reason(&self) -> Option<String> {
+        let current_size = std::fs::metadata(&self.source_path).ok()?.len();
+        if current_size != self.file_size {
+            return Some(format!(
+                "源文件大小已变化（{} → {} 字节），重新上传",
+                self.file_size, current_size
+            ));
+        }
+
+        if let (Some(saved), Some(current)) =
+            (self.source_mtime, file_mtime_secs(&self.source_path))
+        {
+            if saved != current {
+                return Some("源文件修改时间已变化，重新上传".to_string());
+            }
+        }
+
+        None
+    }
+
     /// 获取未完成的分片索引列表
     pub fn pending_chunks(&self) -> Vec<usize> {
         (0..self.total_chunks)
@@ -970,6 +1100,7 @@ mod tests {
             completed_chunks,
             chunk_md5s: None,
             partial_progress: HashMap::new(),
+            chunk_crcs: Vec::new(),
         };
 
         let info = DownloadRecoveryInfo::from_recovered(&recovered).unwrap();
@@ -979,4 +1110,96 @@ mod tests {
         assert_eq!(info.total_chunks, 4);
         assert_eq!(info.pending_chunks(), vec![1, 3]);
     }
+
+    #[test]
+    fn test_verify_recent_chunks() {
+        let temp_dir = setup_temp_dir();
+        let data_path = temp_dir.path().join("file.bin");
+
+        // 3 个分片（每片 4 字节，最后一片 2 字节）
+        let data: Vec<u8> = (0u8..10).collect();
+        std::fs::write(&data_path, &data).unwrap();
+
+        let metadata = TaskMetadata::new_download(
+            "dl_verify".to_string(),
+            1,
+            "/remote/file.bin".to_string(),
+            data_path.clone(),
+            10,
+            4,
+            3,
+            None,
+            None,
+        );
+        let mut completed_chunks = BitSet::with_capacity(3);
+        completed_chunks.insert(0);
+        completed_chunks.insert(1);
+        completed_chunks.insert(2);
+
+        let recovered = RecoveredTask {
+            metadata,
+            completed_chunks,
+            chunk_md5s: None,
+            partial_progress: HashMap::new(),
+            chunk_crcs: vec![
+                (0, crc32fast::hash(&data[0..4])),
+                // 分片 1 的磁盘数据与记录不一致（模拟崩溃时写入不完整）
+                (1, crc32fast::hash(&[0u8; 4])),
+                (2, crc32fast::hash(&data[8..10])),
+            ],
+        };
+        let mut info = DownloadRecoveryInfo::from_recovered(&recovered).unwrap();
+
+        assert_eq!(info.verify_recent_chunks(&data_path, 8), vec![1]);
+        assert_eq!(info.pending_chunks(), vec![1]);
+
+        // 只校验最后 N 条记录：分片 0 损坏但不在校验范围内
+        std::fs::write(&data_path, [0u8; 10]).unwrap();
+        info.completed_chunks.insert(1);
+        assert_eq!(info.verify_recent_chunks(&data_path, 1), vec![2]);
+    }
+
+    #[test]
+    fn test_upload_source_changed_reason() {
+        let temp_dir = setup_temp_dir();
+        let source = temp_dir.path().join("source.bin");
+        std::fs::write(&source, vec![0u8; 100]).unwrap();
+
+        let mut info = UploadRecoveryInfo {
+            task_id: "up_changed".to_string(),
+            source_path: source.clone(),
+            target_path: "/remote/source.bin".to_string(),
+            file_size: 100,
+            chunk_size: 50,
+            total_chunks: 2,
+            completed_chunks: BitSet::new(),
+            chunk_md5s: vec![None; 2],
+            upload_id: Some("upload_id".to_string()),
+            created_at: 0,
+            is_backup: false,
+            backup_config_id: None,
+            encrypt_enabled: false,
+            encryption_key_version: None,
+            original_remote_path: None,
+            source_mtime: file_mtime_secs(&source),
+        };
+
+        // 大小和 mtime 一致
+        assert!(info.source_changed_reason().is_none());
+
+        // 旧版本元数据没有 mtime，只比较大小
+        info.source_mtime = None;
+        assert!(info.source_changed_reason().is_none());
+
+        // mtime 不一致
+        info.source_mtime = file_mtime_secs(&source).map(|t| t - 60);
+        assert!(info.source_changed_reason().is_some());
+
+        // 大小不一致
+        info.source_mtime = None;
+        std::fs::write(&source, vec![0u8; 120]).unwrap();
+        let reason = info.source_changed_reason().unwrap();
+        assert!(reason.contains("100"));
+        assert!(reason.contains("120"));
+    }
 }
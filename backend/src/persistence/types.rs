@@ -10,7 +10,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use tracing::warn;
 
-use crate::transfer::types::CleanupStatus;
+use crate::transfer::types::{CleanupStatus, ShareSubfolderNaming};
 
 /// 任务持久化状态
 ///
@@ -202,6 +202,10 @@ pub struct TaskMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub file_list_json: Option<String>,
 
+    /// 实际转存成功的文件数量（历史记录展示用，部分转存时小于文件总数）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transferred_count: Option<usize>,
+
     // === 分享直下相关字段 ===
     /// 是否为分享直下任务
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -211,6 +215,18 @@ pub struct TaskMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub temp_dir: Option<String>,
 
+    /// 是否为「转存→下载→删除网盘副本」任务（下载成功且 MD5 校验后才清理临时目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_cleanup: Option<bool>,
+
+    /// 是否递归展开分享中的文件夹后逐个转存
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+
+    /// 自动下载时按分享创建本地子文件夹的命名方式（未设置时直接下载到目标目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_subfolder: Option<ShareSubfolderNaming>,
+
     /// 临时目录清理状态（分享直下任务专用，仅后端诊断使用）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cleanup_status: Option<CleanupStatus>,
@@ -268,6 +284,46 @@ pub struct TaskMetadata {
     /// 非加密模式下为 None（target_path 即为原始路径）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub original_remote_path: Option<String>,
+
+    // === 上传源文件校验字段 ===
+    /// 注册任务时源文件的修改时间（Unix 秒，上传任务）
+    /// 恢复时与当前文件比较，不一致说明文件已被修改，需放弃断点重新上传
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_mtime: Option<i64>,
+
+    // === 下载远端文件校验字段 ===
+    /// 上次探测时远端报告的文件总大小（下载任务）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_size: Option<u64>,
+
+    /// 上次探测时远端返回的 ETag（下载任务）
+    /// 恢复时与本次探测结果比较，不一致说明远端文件已变化，需放弃断点重新下载
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_etag: Option<String>,
+
+    /// 实际提供下载的 CDN 主机（下载任务，刷新切换节点后更新）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn_host: Option<String>,
+
+    /// 网盘文件 MD5（下载任务，下载完成后校验完整性）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_md5: Option<String>,
+
+    /// 批量下载批次 ID（下载任务，同一次批量请求创建的任务共享）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+
+    /// 分享直链（免转存直接下载任务，恢复后继续使用直链下载）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_dlink: Option<String>,
+
+    /// 直链所属的分享来源（刷新直链时重新列出分享目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_source: Option<crate::transfer::ShareDirectSource>,
+
+    /// 单任务限速（下载任务，字节/秒，None 表示不限速）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_limit_bytes_per_sec: Option<u64>,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -326,6 +382,10 @@ impl TaskMetadata {
             file_list_json: None,
             // 分享直下字段
             is_share_direct_download: None,
+            download_cleanup: None,
+            transferred_count: None,
+            recursive: None,
+            share_subfolder: None,
             temp_dir: None,
             cleanup_status: None,
             group_id: None,
@@ -341,6 +401,15 @@ impl TaskMetadata {
             is_encrypted: is_encrypted.unwrap_or(false),
             encryption_key_version,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         }
     }
 
@@ -397,6 +466,10 @@ impl TaskMetadata {
             file_list_json: None,
             // 分享直下字段
             is_share_direct_download: None,
+            download_cleanup: None,
+            transferred_count: None,
+            recursive: None,
+            share_subfolder: None,
             temp_dir: None,
             cleanup_status: None,
             group_id: None,
@@ -412,6 +485,15 @@ impl TaskMetadata {
             is_encrypted: is_encrypted.unwrap_or(false),
             encryption_key_version,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         }
     }
 
@@ -464,6 +546,10 @@ impl TaskMetadata {
             file_list_json: None,
             // 分享直下字段
             is_share_direct_download: None,
+            download_cleanup: None,
+            transferred_count: None,
+            recursive: None,
+            share_subfolder: None,
             temp_dir: None,
             cleanup_status: None,
             group_id: None,
@@ -479,6 +565,15 @@ impl TaskMetadata {
             is_encrypted: false,
             encryption_key_version,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         }
     }
 
@@ -533,6 +628,10 @@ impl TaskMetadata {
             file_list_json: None,
             // 分享直下字段
             is_share_direct_download: None,
+            download_cleanup: None,
+            transferred_count: None,
+            recursive: None,
+            share_subfolder: None,
             temp_dir: None,
             cleanup_status: None,
             group_id: None,
@@ -548,6 +647,15 @@ impl TaskMetadata {
             is_encrypted: false,
             encryption_key_version,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         }
     }
 
@@ -596,6 +704,10 @@ impl TaskMetadata {
             file_list_json: None,
             // 分享直下字段
             is_share_direct_download: None,
+            download_cleanup: None,
+            transferred_count: None,
+            recursive: None,
+            share_subfolder: None,
             temp_dir: None,
             cleanup_status: None,
             group_id: None,
@@ -611,6 +723,15 @@ impl TaskMetadata {
             is_encrypted: false,
             encryption_key_version: None,
             original_remote_path: None,
+            source_mtime: None,
+            remote_size: None,
+            remote_etag: None,
+            cdn_host: None,
+            expected_md5: None,
+            batch_id: None,
+            direct_dlink: None,
+            share_source: None,
+            speed_limit_bytes_per_sec: None,
         }
     }
 
@@ -626,6 +747,13 @@ impl TaskMetadata {
         self.touch();
     }
 
+    /// 清除上传 ID（upload_id 失效后重新预创建）
+    pub fn clear_upload_id(&mut self) {
+        self.upload_id = None;
+        self.upload_id_created_at = None;
+        self.touch();
+    }
+
     /// 设置转存状态
     pub fn set_transfer_status(&mut self, status: &str) {
         self.transfer_status = Some(status.to_string());
@@ -722,6 +850,67 @@ impl TaskMetadata {
         self.touch();
     }
 
+    /// 比较本次探测的远端文件指纹，返回变化原因（未变化或无法判断时返回 None）
+    ///
+    /// 大小优先与上次记录的远端大小比较，旧任务没有记录时与注册时的文件大小比较；
+    /// ETag 只在两次都有值时比较
+    pub fn remote_change_reason(&self, remote_size: Option<u64>, remote_etag: Option<&str>) -> Option<String> {
+        if let (Some(new_size), Some(old_size)) = (remote_size, self.remote_size.or(self.file_size)) {
+            if new_size != old_size {
+                return Some(format!("远端文件大小已变化: {} -> {}", old_size, new_size));
+            }
+        }
+        if let (Some(new_etag), Some(old_etag)) = (remote_etag, self.remote_etag.as_deref()) {
+            if new_etag != old_etag {
+                return Some(format!("远端文件 ETag 已变化: {} -> {}", old_etag, new_etag));
+            }
+        }
+        None
+    }
+
+    /// 记录远端文件指纹
+    pub fn set_remote_fingerprint(&mut self, remote_size: Option<u64>, remote_etag: Option<String>) {
+        self.remote_size = remote_size;
+        self.remote_etag = remote_etag;
+        self.touch();
+    }
+
+    /// 记录网盘文件 MD5（下载完成后校验）
+    pub fn set_expected_md5(&mut self, expected_md5: String) {
+        self.expected_md5 = Some(expected_md5);
+        self.touch();
+    }
+
+    /// 记录批量下载批次 ID
+    pub fn set_batch_id(&mut self, batch_id: String) {
+        self.batch_id = Some(batch_id);
+        self.touch();
+    }
+
+    /// 记录分享直链（刷新直链后更新）
+    pub fn set_direct_dlink(&mut self, direct_dlink: String) {
+        self.direct_dlink = Some(direct_dlink);
+        self.touch();
+    }
+
+    /// 记录直链所属的分享来源
+    pub fn set_share_source(&mut self, share_source: crate::transfer::ShareDirectSource) {
+        self.share_source = Some(share_source);
+        self.touch();
+    }
+
+    /// 记录单任务限速（None 表示不限速）
+    pub fn set_speed_limit(&mut self, speed_limit_bytes_per_sec: Option<u64>) {
+        self.speed_limit_bytes_per_sec = speed_limit_bytes_per_sec;
+        self.touch();
+    }
+
+    /// 记录实际提供下载的 CDN 主机
+    pub fn set_cdn_host(&mut self, cdn_host: String) {
+        self.cdn_host = Some(cdn_host);
+        self.touch();
+    }
+
     /// 设置分享直下相关字段
     ///
     /// # Arguments
@@ -737,6 +926,30 @@ impl TaskMetadata {
         self.touch();
     }
 
+    /// 标记为「转存→下载→删除网盘副本」任务
+    pub fn set_download_cleanup(&mut self, download_cleanup: bool) {
+        self.download_cleanup = Some(download_cleanup);
+        self.touch();
+    }
+
+    /// 标记为递归展开文件夹转存的任务
+    pub fn set_transfer_recursive(&mut self, recursive: bool) {
+        self.recursive = Some(recursive);
+        self.touch();
+    }
+
+    /// 设置自动下载时的分享子文件夹命名方式
+    pub fn set_share_subfolder(&mut self, share_subfolder: ShareSubfolderNaming) {
+        self.share_subfolder = Some(share_subfolder);
+        self.touch();
+    }
+
+    /// 设置实际转存成功的文件数量
+    pub fn set_transferred_count(&mut self, transferred_count: usize) {
+        self.transferred_count = Some(transferred_count);
+        self.touch();
+    }
+
     /// 设置临时目录清理状态
     pub fn set_cleanup_status(&mut self, status: CleanupStatus) {
         self.cleanup_status = Some(status);
@@ -757,7 +970,7 @@ impl TaskMetadata {
 /// WAL 记录
 ///
 /// 每条记录占一行，格式为：
-/// - 完成记录：`{chunk_index},{md5},{timestamp_ms}`
+/// - 完成记录：`{chunk_index},{md5},{timestamp_ms}`（下载任务可追加 `,{crc32}`，8 位十六进制）
 /// - 部分进度记录：`P,{chunk_index},{bytes_downloaded},{timestamp_ms}`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalRecord {
@@ -774,6 +987,10 @@ pub struct WalRecord {
     /// 分片内已下载字节数（仅 partial progress 记录）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bytes_downloaded: Option<u64>,
+
+    /// 分片数据 CRC32（仅下载任务，开启 verify_chunks_on_recovery 时记录，用于崩溃恢复校验）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crc32: Option<u32>,
 }
 
 impl WalRecord {
@@ -784,6 +1001,7 @@ impl WalRecord {
             md5: None,
             timestamp_ms: Utc::now().timestamp_millis(),
             bytes_downloaded: None,
+            crc32: None,
         }
     }
 
@@ -794,6 +1012,7 @@ impl WalRecord {
             md5: Some(md5),
             timestamp_ms: Utc::now().timestamp_millis(),
             bytes_downloaded: None,
+            crc32: None,
         }
     }
 
@@ -804,6 +1023,15 @@ impl WalRecord {
             md5: None,
             timestamp_ms: Utc::now().timestamp_millis(),
             bytes_downloaded: Some(bytes_downloaded),
+            crc32: None,
+        }
+    }
+
+    /// 创建带 CRC32 的下载任务 WAL 记录（分片完成）
+    pub fn new_download_with_crc(chunk_index: usize, crc32: u32) -> Self {
+        Self {
+            crc32: Some(crc32),
+            ..Self::new_download(chunk_index)
         }
     }
 
@@ -814,11 +1042,19 @@ impl WalRecord {
 
     /// 序列化为 WAL 行格式
     ///
-    /// 完成记录：`{chunk_index},{md5},{timestamp_ms}`
+    /// 完成记录：`{chunk_index},{md5},{timestamp_ms}`，带 CRC32 时为 `{chunk_index},{md5},{timestamp_ms},{crc32}`
     /// 部分进度：`P,{chunk_index},{bytes_downloaded},{timestamp_ms}`
     pub fn to_wal_line(&self) -> String {
         if let Some(bytes) = self.bytes_downloaded {
             format!("P,{},{},{}", self.chunk_index, bytes, self.timestamp_ms)
+        } else if let Some(crc) = self.crc32 {
+            format!(
+                "{},{},{},{:08x}",
+                self.chunk_index,
+                self.md5.as_deref().unwrap_or(""),
+                self.timestamp_ms,
+                crc
+            )
         } else {
             format!(
                 "{},{},{}",
@@ -833,6 +1069,7 @@ impl WalRecord {
     ///
     /// 支持格式：
     /// - `P,{chunk_index},{bytes_downloaded},{timestamp_ms}` - 部分进度记录
+    /// - `{chunk_index},{md5},{timestamp_ms},{crc32}` - 带 CRC32 的分片完成记录
     /// - `{chunk_index},{md5},{timestamp_ms}` - 完整格式（分片完成）
     /// - `{chunk_index},{md5}` - 旧格式（无时间戳）
     /// - `{chunk_index}` - 最简格式
@@ -861,6 +1098,7 @@ impl WalRecord {
                 md5: None,
                 timestamp_ms,
                 bytes_downloaded: Some(bytes_downloaded),
+                crc32: None,
             });
         }
 
@@ -881,11 +1119,15 @@ impl WalRecord {
             Utc::now().timestamp_millis()
         };
 
+        // CRC32 无法解析时视为未记录（恢复时跳过校验）
+        let crc32 = parts.get(3).and_then(|c| u32::from_str_radix(c, 16).ok());
+
         Some(Self {
             chunk_index,
             md5,
             timestamp_ms,
             bytes_downloaded: None,
+            crc32,
         })
     }
 }
@@ -962,6 +1204,11 @@ impl TaskPersistenceInfo {
     ///
     /// 只有当分片是新完成时才添加到 WAL 缓存，避免重复记录
     pub fn mark_chunk_completed(&mut self, chunk_index: usize) {
+        self.mark_chunk_completed_with_crc(chunk_index, None);
+    }
+
+    /// 标记分片完成（下载任务，可附带分片数据 CRC32 用于崩溃恢复校验）
+    pub fn mark_chunk_completed_with_crc(&mut self, chunk_index: usize, crc32: Option<u32>) {
         // 检查分片是否已经完成
         // insert() 返回 true 表示新插入，false 表示已存在
         let is_new = self.completed_chunks.insert(chunk_index);
@@ -970,7 +1217,10 @@ impl TaskPersistenceInfo {
 
         if is_new {
             // 只有新完成的分片才添加到 WAL 缓存
-            let record = WalRecord::new_download(chunk_index);
+            let record = match crc32 {
+                Some(crc) => WalRecord::new_download_with_crc(chunk_index, crc),
+                None => WalRecord::new_download(chunk_index),
+            };
             self.wal_cache.lock().push(record);
         } else {
             // 分片已经完成过，可能是重复调用，记录警告
@@ -1102,6 +1352,50 @@ mod tests {
         assert_eq!(record.md5, None);
     }
 
+    #[test]
+    fn test_remote_change_reason() {
+        let mut metadata = TaskMetadata::new_download(
+            "task-1".to_string(),
+            1,
+            "/a.mp4".to_string(),
+            PathBuf::from("/tmp/a.mp4"),
+            1000,
+            100,
+            10,
+            None,
+            None,
+        );
+
+        // 旧任务没有远端记录：与注册时的文件大小比较
+        assert_eq!(metadata.remote_change_reason(Some(1000), Some("\"e1\"")), None);
+        assert!(metadata.remote_change_reason(Some(2000), None).is_some());
+
+        metadata.set_remote_fingerprint(Some(1000), Some("\"e1\"".to_string()));
+        assert_eq!(metadata.remote_change_reason(Some(1000), Some("\"e1\"")), None);
+        assert_eq!(metadata.remote_change_reason(None, None), None);
+        assert!(metadata
+            .remote_change_reason(Some(1000), Some("\"e2\""))
+            .unwrap()
+            .contains("ETag"));
+    }
+
+    #[test]
+    fn test_wal_record_crc32_roundtrip() {
+        let record = WalRecord::new_download_with_crc(4, 0x0badf00d);
+        let line = record.to_wal_line();
+        assert!(line.starts_with("4,,"));
+        assert!(line.ends_with(",0badf00d"));
+
+        let parsed = WalRecord::from_wal_line(&line).unwrap();
+        assert_eq!(parsed.chunk_index, 4);
+        assert_eq!(parsed.md5, None);
+        assert_eq!(parsed.crc32, Some(0x0badf00d));
+
+        // 旧格式无 CRC32
+        let parsed = WalRecord::from_wal_line("4,,1700000000000").unwrap();
+        assert_eq!(parsed.crc32, None);
+    }
+
     #[test]
     fn test_task_persistence_info() {
         let mut info = TaskPersistenceInfo::new_download("task1".to_string(), 10);
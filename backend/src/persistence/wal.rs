@@ -12,11 +12,21 @@
 //! - `chunk_index`: 分片索引（0-based）
 //! - `md5`: 分片 MD5（上传任务需要，下载任务为空）
 //! - `timestamp_ms`: 记录时间戳（Unix 毫秒）
+//!
+//! ## 压缩格式
+//!
+//! 开启 `persistence.compress_wal` 后，新记录仍先追加到 `{task_id}.wal`，
+//! 当其超过 [`COMPACT_THRESHOLD_BYTES`] 时合并重写到 gzip 压缩的 `{task_id}.wal.gz`，
+//! 避免每次刷写都重新压缩。读取时依次读取 `.wal.gz` 和 `.wal`，两种格式可以共存
 
+use std::collections::BTreeSet;
 use std::fs::{self, File, OpenOptions};
-use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use tracing::{debug, error, warn};
 
 use super::types::WalRecord;
@@ -24,6 +34,12 @@ use super::types::WalRecord;
 /// WAL 文件扩展名
 const WAL_EXTENSION: &str = "wal";
 
+/// 压缩 WAL 文件扩展名
+const COMPRESSED_WAL_EXTENSION: &str = "wal.gz";
+
+/// 压缩模式下未压缩 WAL 超过该大小时合并到 `.wal.gz`
+pub const COMPACT_THRESHOLD_BYTES: u64 = 64 * 1024;
+
 // ============================================================================
 // 辅助函数
 // ============================================================================
@@ -40,16 +56,21 @@ pub fn get_wal_path(wal_dir: &Path, task_id: &str) -> PathBuf {
     wal_dir.join(format!("{}.{}", task_id, WAL_EXTENSION))
 }
 
-/// 检查 WAL 文件是否存在
+/// 获取压缩 WAL 文件路径：`{wal_dir}/{task_id}.wal.gz`
+pub fn get_compressed_wal_path(wal_dir: &Path, task_id: &str) -> PathBuf {
+    wal_dir.join(format!("{}.{}", task_id, COMPRESSED_WAL_EXTENSION))
+}
+
+/// 检查 WAL 文件是否存在（`.wal` 或 `.wal.gz`）
 ///
 /// # Arguments
 /// * `wal_dir` - WAL 目录
 /// * `task_id` - 任务 ID
 pub fn wal_exists(wal_dir: &Path, task_id: &str) -> bool {
-    get_wal_path(wal_dir, task_id).exists()
+    get_wal_path(wal_dir, task_id).exists() || get_compressed_wal_path(wal_dir, task_id).exists()
 }
 
-/// 删除 WAL 文件
+/// 删除 WAL 文件（`.wal` 和 `.wal.gz`）
 ///
 /// # Arguments
 /// * `wal_dir` - WAL 目录
@@ -60,14 +81,15 @@ pub fn wal_exists(wal_dir: &Path, task_id: &str) -> bool {
 /// - `Ok(false)` - 文件不存在
 /// - `Err` - 删除失败
 pub fn delete_wal_file(wal_dir: &Path, task_id: &str) -> io::Result<bool> {
-    let path = get_wal_path(wal_dir, task_id);
-    if path.exists() {
-        fs::remove_file(&path)?;
-        debug!("已删除 WAL 文件: {:?}", path);
-        Ok(true)
-    } else {
-        Ok(false)
+    let mut deleted = false;
+    for path in [get_wal_path(wal_dir, task_id), get_compressed_wal_path(wal_dir, task_id)] {
+        if path.exists() {
+            fs::remove_file(&path)?;
+            debug!("已删除 WAL 文件: {:?}", path);
+            deleted = true;
+        }
     }
+    Ok(deleted)
 }
 
 /// 确保 WAL 目录存在
@@ -266,12 +288,18 @@ impl WalReader {
     /// 读取所有记录
     ///
     /// 容错处理：跳过无法解析的行，并记录警告日志
+    /// 扩展名为 `.gz` 时按 gzip 解压读取
     ///
     /// # Returns
     /// 成功解析的所有记录列表
     pub fn read_all(&self) -> io::Result<Vec<WalRecord>> {
         let file = File::open(&self.path)?;
-        let reader = BufReader::new(file);
+        let source: Box<dyn Read> = if self.path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(MultiGzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        let reader = BufReader::new(source);
 
         let mut records = Vec::new();
         let mut line_number = 0;
@@ -363,6 +391,60 @@ pub fn append_records(wal_dir: &Path, task_id: &str, records: &[WalRecord]) -> i
     Ok(())
 }
 
+/// 批量追加记录到 WAL 文件（压缩模式）
+///
+/// 记录先追加到未压缩的 `.wal`，超过 [`COMPACT_THRESHOLD_BYTES`] 后合并到 `.wal.gz`
+pub fn append_records_compressed(
+    wal_dir: &Path,
+    task_id: &str,
+    records: &[WalRecord],
+) -> io::Result<()> {
+    append_records(wal_dir, task_id, records)?;
+
+    let plain_len = fs::metadata(get_wal_path(wal_dir, task_id))
+        .map(|m| m.len())
+        .unwrap_or(0);
+    if plain_len >= COMPACT_THRESHOLD_BYTES {
+        compact_wal(wal_dir, task_id)?;
+    }
+    Ok(())
+}
+
+/// 将任务的全部 WAL 记录（`.wal.gz` + `.wal`）重写为单个 `.wal.gz`
+///
+/// 先写入临时文件再原子重命名，最后删除未压缩的 `.wal`；
+/// 若在删除前崩溃，重复的记录在恢复时按相同顺序回放，结果不变
+pub fn compact_wal(wal_dir: &Path, task_id: &str) -> io::Result<()> {
+    let records = read_records(wal_dir, task_id)?;
+
+    let gz_path = get_compressed_wal_path(wal_dir, task_id);
+    let tmp_path = gz_path.with_extension("gz.tmp");
+    {
+        let file = File::create(&tmp_path)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        for record in &records {
+            writeln!(encoder, "{}", record.to_wal_line())?;
+        }
+        let mut writer = encoder.finish()?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    fs::rename(&tmp_path, &gz_path)?;
+
+    let plain_path = get_wal_path(wal_dir, task_id);
+    if plain_path.exists() {
+        fs::remove_file(&plain_path)?;
+    }
+
+    debug!(
+        "已压缩 WAL: task_id={}, 记录数={}, 路径={:?}",
+        task_id,
+        records.len(),
+        gz_path
+    );
+    Ok(())
+}
+
 /// 读取 WAL 文件中的所有记录
 ///
 /// 这是一个便捷函数
@@ -375,8 +457,19 @@ pub fn append_records(wal_dir: &Path, task_id: &str, records: &[WalRecord]) -> i
 /// - `Ok(Vec<WalRecord>)` - 成功读取的记录
 /// - `Err` - 文件不存在或读取失败
 pub fn read_records(wal_dir: &Path, task_id: &str) -> io::Result<Vec<WalRecord>> {
-    let reader = WalReader::from_task(wal_dir, task_id)?;
-    reader.read_all()
+    let gz_path = get_compressed_wal_path(wal_dir, task_id);
+    if !gz_path.exists() {
+        let reader = WalReader::from_task(wal_dir, task_id)?;
+        return reader.read_all();
+    }
+
+    // 压缩部分在前，未合并的增量在后
+    let mut records = WalReader::new(&gz_path)?.read_all()?;
+    let plain_path = get_wal_path(wal_dir, task_id);
+    if plain_path.exists() {
+        records.extend(WalReader::new(&plain_path)?.read_all()?);
+    }
+    Ok(records)
 }
 
 /// 扫描 WAL 目录中的所有任务 ID
@@ -385,34 +478,33 @@ pub fn read_records(wal_dir: &Path, task_id: &str) -> io::Result<Vec<WalRecord>>
 /// * `wal_dir` - WAL 目录
 ///
 /// # Returns
-/// 所有 WAL 文件对应的任务 ID 列表
+/// 所有 WAL 文件（`.wal` 或 `.wal.gz`）对应的任务 ID 列表（去重）
 pub fn scan_wal_task_ids(wal_dir: &Path) -> io::Result<Vec<String>> {
     if !wal_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut task_ids = Vec::new();
+    let mut task_ids = BTreeSet::new();
 
     for entry in fs::read_dir(wal_dir)? {
         let entry = entry?;
         let path = entry.path();
 
         if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == WAL_EXTENSION {
-                    if let Some(stem) = path.file_stem() {
-                        if let Some(task_id) = stem.to_str() {
-                            task_ids.push(task_id.to_string());
-                        }
-                    }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                let task_id = name
+                    .strip_suffix(&format!(".{}", COMPRESSED_WAL_EXTENSION))
+                    .or_else(|| name.strip_suffix(&format!(".{}", WAL_EXTENSION)));
+                if let Some(task_id) = task_id {
+                    task_ids.insert(task_id.to_string());
                 }
             }
         }
     }
 
-    debug!("扫描到 {} 个 WAL 文件", task_ids.len());
+    debug!("扫描到 {} 个 WAL 任务", task_ids.len());
 
-    Ok(task_ids)
+    Ok(task_ids.into_iter().collect())
 }
 
 // ============================================================================
@@ -592,6 +684,40 @@ mod tests {
 
         assert_eq!(task_ids.len(), 3);
         assert_eq!(task_ids, vec!["task_a", "task_b", "task_c"]);
+
+        // 压缩 WAL 与未压缩 WAL 共存时只返回一次
+        File::create(wal_dir.join("task_a.wal.gz")).unwrap();
+        File::create(wal_dir.join("task_e.wal.gz")).unwrap();
+        let task_ids = scan_wal_task_ids(wal_dir).unwrap();
+        assert_eq!(task_ids, vec!["task_a", "task_b", "task_c", "task_e"]);
+    }
+
+    #[test]
+    fn test_compressed_wal_roundtrip() {
+        let temp_dir = setup_temp_dir();
+        let wal_dir = temp_dir.path();
+
+        // 写入足够多的记录触发合并
+        let records: Vec<WalRecord> = (0..5000).map(WalRecord::new_download).collect();
+        append_records_compressed(wal_dir, "task_gz", &records).unwrap();
+        assert!(get_compressed_wal_path(wal_dir, "task_gz").exists());
+        assert!(!get_wal_path(wal_dir, "task_gz").exists());
+
+        // 未达到阈值的增量保留在未压缩 WAL 中
+        append_records_compressed(wal_dir, "task_gz", &[WalRecord::new_partial(5000, 1024)])
+            .unwrap();
+        assert!(get_wal_path(wal_dir, "task_gz").exists());
+
+        let read = read_records(wal_dir, "task_gz").unwrap();
+        assert_eq!(read.len(), 5001);
+        assert_eq!(read[0].chunk_index, 0);
+        assert_eq!(read[4999].chunk_index, 4999);
+        assert_eq!(read[5000].bytes_downloaded, Some(1024));
+
+        // 删除时两种文件都会清理
+        assert!(wal_exists(wal_dir, "task_gz"));
+        assert!(delete_wal_file(wal_dir, "task_gz").unwrap());
+        assert!(!wal_exists(wal_dir, "task_gz"));
     }
 
     #[test]
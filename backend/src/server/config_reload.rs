@@ -0,0 +1,228 @@
+// 配置热重载
+//
+// 支持两种触发方式：
+// - Unix 下收到 SIGHUP 信号
+// - 配置文件 config/app.toml 被修改（notify 文件监听）
+//
+// 重载时重新读取并验证配置，可动态生效的字段立即应用（不影响正在进行的任务），
+// 其余字段记录"需要重启"日志
+
+use crate::config::AppConfig;
+use crate::server::handlers::config::{apply_runtime_config, validate_config_values, warn_vip_limits};
+use crate::server::AppState;
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+/// 配置文件路径
+const CONFIG_PATH: &str = "config/app.toml";
+
+/// 文件变更防抖时间（编辑器保存时通常会连续触发多个事件）
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 重载触发来源
+#[derive(Debug, Clone, Copy)]
+enum ReloadTrigger {
+    Signal,
+    FileChanged,
+}
+
+/// 比较新旧配置，返回需要重启才能生效的字段
+pub fn restart_required_changes(old: &AppConfig, new: &AppConfig) -> Vec<&'static str> {
+    fn differs<T: serde::Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+    }
+
+    let mut changes = Vec::new();
+    if old.server.host != new.server.host {
+        changes.push("server.host");
+    }
+    if old.server.port != new.server.port {
+        changes.push("server.port");
+    }
+    if old.server.cors_origins != new.server.cors_origins {
+        changes.push("server.cors_origins");
+    }
+    if old.log.enabled != new.log.enabled
+        || old.log.log_dir != new.log.log_dir
+        || old.log.max_file_size != new.log.max_file_size
+        || old.log.format != new.log.format
+    {
+        changes.push("log（级别以外的字段）");
+    }
+    // wal_flush_interval_ms 可动态生效，不参与比较
+    let mut new_persistence = new.persistence.clone();
+    new_persistence.wal_flush_interval_ms = old.persistence.wal_flush_interval_ms;
+    if differs(&old.persistence, &new_persistence) {
+        changes.push("persistence");
+    }
+    if old.network.mobile_user_agent != new.network.mobile_user_agent
+        || old.network.web_user_agent != new.network.web_user_agent
+    {
+        changes.push("network.mobile_user_agent / network.web_user_agent");
+    }
+    if differs(&old.web_auth, &new.web_auth) {
+        changes.push("web_auth");
+    }
+    changes
+}
+
+/// 从配置文件重新加载配置并动态应用
+///
+/// 返回 `Ok(false)` 表示文件内容与当前配置一致（例如 API 更新配置时自身写入的文件），未做任何变更；
+/// 验证失败时保留当前配置并返回错误
+pub async fn reload_config(app_state: &AppState) -> Result<bool> {
+    let mut new_config = AppConfig::load_from_file(CONFIG_PATH).await?;
+    // 与启动时保持一致：环境变量优先于配置文件
+    new_config.apply_env_overrides();
+
+    let old_config = app_state.config.read().await.clone();
+    if serde_json::to_value(&old_config)? == serde_json::to_value(&new_config)? {
+        return Ok(false);
+    }
+
+    // 验证新配置（下载路径格式已在 load_from_file 中验证，失败则保留旧配置）
+    validate_config_values(&new_config).map_err(|e| anyhow::anyhow!(e))?;
+    warn_vip_limits(app_state, &new_config).await;
+    new_config
+        .network
+        .proxy
+        .validate()
+        .map_err(|e| anyhow::anyhow!("代理配置验证失败: {}", e))?;
+    new_config
+        .filesystem
+        .ensure_path_allowed(&new_config.download.download_dir, "download.download_dir")?;
+    ensure_dir_usable(&new_config.download.download_dir).await?;
+
+    for field in restart_required_changes(&old_config, &new_config) {
+        warn!("配置项 {} 已变更，需要重启服务后生效", field);
+    }
+
+    *app_state.config.write().await = new_config.clone();
+    apply_runtime_config(app_state, &old_config, &new_config).await;
+
+    Ok(true)
+}
+
+/// 确保下载目录存在且为目录（不存在时尝试创建）
+async fn ensure_dir_usable(dir: &Path) -> Result<()> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .with_context(|| format!("无法创建下载目录: {:?}", dir))?;
+    let metadata = tokio::fs::metadata(dir)
+        .await
+        .with_context(|| format!("无法访问下载目录: {:?}", dir))?;
+    if !metadata.is_dir() {
+        anyhow::bail!("下载路径不是目录: {:?}", dir);
+    }
+    Ok(())
+}
+
+/// 启动配置热重载后台任务
+///
+/// 信号和文件变更事件汇入同一通道，由单个任务串行处理，避免并发重载
+pub fn spawn_config_reload_tasks(app_state: AppState) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<ReloadTrigger>();
+
+    #[cfg(unix)]
+    {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            use tokio::signal::unix::{signal, SignalKind};
+            let mut hangup = match signal(SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("注册 SIGHUP 信号处理失败，信号触发的配置重载不可用: {}", e);
+                    return;
+                }
+            };
+            while hangup.recv().await.is_some() {
+                if tx.send(ReloadTrigger::Signal).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // 监听配置目录（编辑器常以"写临时文件再重命名"的方式保存，直接监听文件会丢失后续事件）
+    let watcher = {
+        let tx = tx.clone();
+        notify::recommended_watcher(move |res: std::result::Result<Event, notify::Error>| {
+            match res {
+                Ok(event) => {
+                    let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                        && event.paths.iter().any(|p| p.ends_with("app.toml"));
+                    if relevant {
+                        let _ = tx.send(ReloadTrigger::FileChanged);
+                    }
+                }
+                Err(e) => warn!("配置文件监听错误: {}", e),
+            }
+        })
+        .and_then(|mut watcher| {
+            let dir = Path::new(CONFIG_PATH).parent().unwrap_or(Path::new("."));
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        })
+    };
+    let watcher = match watcher {
+        Ok(w) => Some(w),
+        Err(e) => {
+            warn!("启动配置文件监听失败，文件变更触发的配置重载不可用: {}", e);
+            None
+        }
+    };
+    drop(tx);
+
+    tokio::spawn(async move {
+        // watcher 需在任务中保持存活
+        let _watcher = watcher;
+        while let Some(trigger) = rx.recv().await {
+            // 防抖：合并短时间内的连续事件
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            match reload_config(&app_state).await {
+                Ok(true) => info!("✓ 配置已热重载 (触发来源: {:?})", trigger),
+                Ok(false) => {
+                    if matches!(trigger, ReloadTrigger::Signal) {
+                        info!("收到 SIGHUP，配置文件无变化");
+                    }
+                }
+                Err(e) => error!("配置热重载失败，保留当前配置: {:#}", e),
+            }
+        }
+    });
+
+    info!("配置热重载已启用 (SIGHUP / 文件监听: {})", CONFIG_PATH);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_required_changes() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        assert!(restart_required_changes(&old, &new).is_empty());
+
+        // 可动态生效的字段不需要重启
+        new.download.max_global_threads += 1;
+        new.download.max_concurrent_tasks += 1;
+        new.log.level = "debug".to_string();
+        new.log.retention_days += 1;
+        new.persistence.wal_flush_interval_ms += 100;
+        assert!(restart_required_changes(&old, &new).is_empty());
+
+        new.server.port += 1;
+        new.log.max_file_size += 1;
+        assert_eq!(
+            restart_required_changes(&old, &new),
+            vec!["server.port", "log（级别以外的字段）"]
+        );
+    }
+}
@@ -23,6 +23,8 @@ pub enum ApiError {
     BadRequest(String),
     /// 冲突
     Conflict(String),
+    /// 禁止访问（如只读模式下的破坏性操作）
+    Forbidden(String),
 }
 
 impl fmt::Display for ApiError {
@@ -34,6 +36,7 @@ impl fmt::Display for ApiError {
             ApiError::NotFound(msg) => write!(f, "Not found: {}", msg),
             ApiError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             ApiError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            ApiError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
         }
     }
 }
@@ -81,6 +84,10 @@ impl IntoResponse for ApiError {
                 tracing::debug!("Conflict: {}", msg);
                 (StatusCode::CONFLICT, 409, msg, None)
             }
+            ApiError::Forbidden(msg) => {
+                tracing::warn!("Forbidden: {}", msg);
+                (StatusCode::FORBIDDEN, 403, msg, None)
+            }
         };
 
         let body = Json(ErrorResponse {
@@ -0,0 +1,127 @@
+//! 传输指标计数器
+//!
+//! 进程级累计计数（重启后归零），供 `/metrics` 以 Prometheus 文本格式导出：
+//! - 任务成功/失败数：由事件总线在发布 Completed/Failed 事件时累加（不含自动备份任务）
+//! - 已下载字节数：下载引擎每写入一段数据累加
+//! - CDN 链接刷新次数：下载引擎刷新链接成功时累加
+
+use super::types::{DownloadEvent, TaskEvent, UploadEvent};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// 全局指标实例
+static GLOBAL_METRICS: OnceLock<Arc<TransferMetrics>> = OnceLock::new();
+
+/// 传输指标
+#[derive(Debug, Default)]
+pub struct TransferMetrics {
+    /// 累计下载字节数
+    downloaded_bytes: AtomicU64,
+    /// 下载成功任务数
+    downloads_completed: AtomicU64,
+    /// 下载失败任务数
+    downloads_failed: AtomicU64,
+    /// 上传成功任务数
+    uploads_completed: AtomicU64,
+    /// 上传失败任务数
+    uploads_failed: AtomicU64,
+    /// CDN 链接刷新次数
+    cdn_refreshes: AtomicU64,
+}
+
+/// 指标快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferMetricsSnapshot {
+    pub downloaded_bytes: u64,
+    pub downloads_completed: u64,
+    pub downloads_failed: u64,
+    pub uploads_completed: u64,
+    pub uploads_failed: u64,
+    pub cdn_refreshes: u64,
+}
+
+impl TransferMetrics {
+    /// 获取全局指标实例
+    pub fn global() -> Arc<Self> {
+        GLOBAL_METRICS
+            .get_or_init(|| Arc::new(Self::default()))
+            .clone()
+    }
+
+    /// 累加已下载字节数
+    pub fn add_downloaded_bytes(&self, bytes: u64) {
+        self.downloaded_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// 记录一次 CDN 链接刷新
+    pub fn record_cdn_refresh(&self) {
+        self.cdn_refreshes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 根据任务事件更新成功/失败计数
+    pub fn observe_event(&self, event: &TaskEvent) {
+        let counter = match event {
+            TaskEvent::Download(DownloadEvent::Completed { .. }) => &self.downloads_completed,
+            TaskEvent::Download(DownloadEvent::Failed { .. }) => &self.downloads_failed,
+            TaskEvent::Upload(UploadEvent::Completed { .. }) => &self.uploads_completed,
+            TaskEvent::Upload(UploadEvent::Failed { .. }) => &self.uploads_failed,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 获取当前计数
+    pub fn snapshot(&self) -> TransferMetricsSnapshot {
+        TransferMetricsSnapshot {
+            downloaded_bytes: self.downloaded_bytes.load(Ordering::Relaxed),
+            downloads_completed: self.downloads_completed.load(Ordering::Relaxed),
+            downloads_failed: self.downloads_failed.load(Ordering::Relaxed),
+            uploads_completed: self.uploads_completed.load(Ordering::Relaxed),
+            uploads_failed: self.uploads_failed.load(Ordering::Relaxed),
+            cdn_refreshes: self.cdn_refreshes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_event() {
+        let metrics = TransferMetrics::default();
+
+        metrics.observe_event(&TaskEvent::Download(DownloadEvent::Completed {
+            task_id: "t1".to_string(),
+            completed_at: 0,
+            group_id: None,
+            is_backup: false,
+        }));
+        metrics.observe_event(&TaskEvent::Download(DownloadEvent::Failed {
+            task_id: "t2".to_string(),
+            error: "err".to_string(),
+            group_id: None,
+            is_backup: false,
+        }));
+        metrics.observe_event(&TaskEvent::Download(DownloadEvent::Paused {
+            task_id: "t3".to_string(),
+            group_id: None,
+            is_backup: false,
+        }));
+        metrics.add_downloaded_bytes(1024);
+        metrics.add_downloaded_bytes(512);
+        metrics.record_cdn_refresh();
+
+        assert_eq!(
+            metrics.snapshot(),
+            TransferMetricsSnapshot {
+                downloaded_bytes: 1536,
+                downloads_completed: 1,
+                downloads_failed: 1,
+                uploads_completed: 0,
+                uploads_failed: 0,
+                cdn_refreshes: 1,
+            }
+        );
+    }
+}
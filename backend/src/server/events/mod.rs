@@ -3,10 +3,13 @@
 //! 定义 WebSocket 事件类型和相关工具
 //! - `types.rs`: 定义所有任务事件类型（Download/Upload/Transfer/Folder）
 //! - `throttle.rs`: 事件节流相关工具，用于控制进度事件的发布频率
+//! - `metrics.rs`: 传输指标计数器（供 /metrics 导出）
 
+mod metrics;
 mod throttle;
 mod types;
 
+pub use metrics::*;
 pub use throttle::*;
 pub use types::*;
 
@@ -2,13 +2,118 @@
 //!
 //! 用于控制进度事件的发布频率，避免事件风暴
 //! 支持时间间隔节流（建议 200-250）
+//!
+//! 所有节流器共享一个全局限速器：
+//! - 活跃任务数超过阈值时按比例拉伸单任务间隔
+//! - 全局每秒事件数上限（`server.max_events_per_sec`）
 
 use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 /// 默认节流间隔（毫秒）
 pub const DEFAULT_THROTTLE_INTERVAL_MS: u64 = 200;
 
+/// 活跃任务数超过该值后开始拉伸节流间隔
+pub const ADAPTIVE_TASK_THRESHOLD: usize = 20;
+
+/// 全局限速器实例
+static GLOBAL_RATE_LIMITER: OnceLock<Arc<ProgressRateLimiter>> = OnceLock::new();
+
+/// 进度事件全局限速器
+///
+/// 由同一组内的所有 `ProgressThrottler` 共享：
+/// 1. 统计存活的节流器数量（每个传输中的任务持有一个），用于拉伸单任务间隔
+/// 2. 按 1 秒固定窗口限制所有任务合计的进度事件数
+#[derive(Debug)]
+pub struct ProgressRateLimiter {
+    /// 存活的节流器数量
+    active_throttlers: AtomicUsize,
+    /// 每秒最大事件数（0 表示不限制）
+    max_events_per_sec: AtomicU64,
+    /// 当前窗口 (窗口开始时间, 窗口内已发布事件数)
+    window: Mutex<(Instant, u64)>,
+}
+
+impl ProgressRateLimiter {
+    /// 创建限速器
+    ///
+    /// # 参数
+    /// * `max_events_per_sec` - 每秒最大事件数，0 表示不限制
+    pub fn new(max_events_per_sec: u64) -> Self {
+        Self {
+            active_throttlers: AtomicUsize::new(0),
+            max_events_per_sec: AtomicU64::new(max_events_per_sec),
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// 获取全局限速器（默认不限制，启动时由配置设置上限）
+    pub fn global() -> Arc<Self> {
+        GLOBAL_RATE_LIMITER
+            .get_or_init(|| Arc::new(Self::new(0)))
+            .clone()
+    }
+
+    /// 设置每秒最大事件数（0 表示不限制）
+    pub fn set_max_events_per_sec(&self, max_events_per_sec: u64) {
+        self.max_events_per_sec
+            .store(max_events_per_sec, Ordering::Relaxed);
+    }
+
+    /// 获取每秒最大事件数
+    pub fn max_events_per_sec(&self) -> u64 {
+        self.max_events_per_sec.load(Ordering::Relaxed)
+    }
+
+    /// 获取存活的节流器数量
+    pub fn active_throttlers(&self) -> usize {
+        self.active_throttlers.load(Ordering::Relaxed)
+    }
+
+    /// 根据活跃任务数拉伸基础间隔
+    ///
+    /// 不超过 `ADAPTIVE_TASK_THRESHOLD` 时保持原间隔，
+    /// 超过后按 `活跃数 / 阈值` 等比放大，使总事件频率维持在阈值对应的水平
+    pub fn scaled_interval(&self, base: Duration) -> Duration {
+        let active = self.active_throttlers();
+        if active <= ADAPTIVE_TASK_THRESHOLD {
+            base
+        } else {
+            base.mul_f64(active as f64 / ADAPTIVE_TASK_THRESHOLD as f64)
+        }
+    }
+
+    /// 尝试占用一个全局事件名额
+    fn try_acquire(&self) -> bool {
+        let limit = self.max_events_per_sec();
+        let mut window = self.window.lock();
+        Self::roll_window(&mut window);
+
+        if limit > 0 && window.1 >= limit {
+            return false;
+        }
+        window.1 += 1;
+        true
+    }
+
+    /// 记录一次强制发布的事件（计入窗口但不受上限约束）
+    fn record(&self) {
+        let mut window = self.window.lock();
+        Self::roll_window(&mut window);
+        window.1 += 1;
+    }
+
+    /// 超过 1 秒则开启新窗口
+    fn roll_window(window: &mut (Instant, u64)) {
+        let now = Instant::now();
+        if now.duration_since(window.0) >= Duration::from_secs(1) {
+            *window = (now, 0);
+        }
+    }
+}
+
 /// 进度事件节流器
 ///
 /// 线程安全的时间节流器
@@ -17,19 +122,28 @@ pub const DEFAULT_THROTTLE_INTERVAL_MS: u64 = 200;
 pub struct ProgressThrottler {
     /// 上次发布事件的时间
     last_emit: Mutex<Option<Instant>>,
-    /// 节流间隔
+    /// 节流间隔（基础值，实际间隔随活跃任务数拉伸）
     interval: Duration,
+    /// 共享限速器
+    limiter: Arc<ProgressRateLimiter>,
 }
 
 impl ProgressThrottler {
-    /// 创建新的节流器
+    /// 创建新的节流器（使用全局限速器）
     ///
     /// # 参数
     /// * `interval` - 最小发布间隔
     pub fn new(interval: Duration) -> Self {
+        Self::with_limiter(interval, ProgressRateLimiter::global())
+    }
+
+    /// 使用指定限速器创建节流器
+    pub fn with_limiter(interval: Duration, limiter: Arc<ProgressRateLimiter>) -> Self {
+        limiter.active_throttlers.fetch_add(1, Ordering::Relaxed);
         Self {
             last_emit: Mutex::new(None),
             interval,
+            limiter,
         }
     }
 
@@ -45,25 +159,33 @@ impl ProgressThrottler {
 
     /// 检查是否应该发布事件
     ///
-    /// 如果距离上次发布已超过节流间隔，返回 true 并更新时间戳
-    /// 否则返回 false
+    /// 如果距离上次发布已超过节流间隔（按活跃任务数拉伸）且全局限速未触发，
+    /// 返回 true 并更新时间戳，否则返回 false
     pub fn should_emit(&self) -> bool {
         let now = Instant::now();
+        let interval = self.limiter.scaled_interval(self.interval);
         let mut last = self.last_emit.lock();
 
-        match *last {
-            Some(last_time) if now.duration_since(last_time) < self.interval => false,
-            _ => {
-                *last = Some(now);
-                true
+        if let Some(last_time) = *last {
+            if now.duration_since(last_time) < interval {
+                return false;
             }
         }
+
+        // 全局名额已用完时不更新时间戳，下次更新进度时重试
+        if !self.limiter.try_acquire() {
+            return false;
+        }
+
+        *last = Some(now);
+        true
     }
 
     /// 强制发布（用于最后一次更新或完成时）
     ///
-    /// 不检查时间间隔，直接更新时间戳并返回 true
+    /// 不检查时间间隔和全局上限，直接更新时间戳并返回 true
     pub fn force_emit(&self) -> bool {
+        self.limiter.record();
         *self.last_emit.lock() = Some(Instant::now());
         true
     }
@@ -82,10 +204,17 @@ impl Default for ProgressThrottler {
 
 impl Clone for ProgressThrottler {
     fn clone(&self) -> Self {
-        Self {
-            last_emit: Mutex::new(*self.last_emit.lock()),
-            interval: self.interval,
-        }
+        let cloned = Self::with_limiter(self.interval, self.limiter.clone());
+        *cloned.last_emit.lock() = *self.last_emit.lock();
+        cloned
+    }
+}
+
+impl Drop for ProgressThrottler {
+    fn drop(&mut self) {
+        self.limiter
+            .active_throttlers
+            .fetch_sub(1, Ordering::Relaxed);
     }
 }
 
@@ -94,9 +223,17 @@ mod tests {
     use super::*;
     use std::thread;
 
+    /// 使用独立限速器创建节流器，避免与其他测试共享全局计数
+    fn isolated(interval_ms: u64) -> ProgressThrottler {
+        ProgressThrottler::with_limiter(
+            Duration::from_millis(interval_ms),
+            Arc::new(ProgressRateLimiter::new(0)),
+        )
+    }
+
     #[test]
     fn test_throttler_basic() {
-        let throttler = ProgressThrottler::with_millis(100);
+        let throttler = isolated(100);
 
         // 第一次应该发布
         assert!(throttler.should_emit());
@@ -107,7 +244,7 @@ mod tests {
 
     #[test]
     fn test_throttler_after_interval() {
-        let throttler = ProgressThrottler::with_millis(50);
+        let throttler = isolated(50);
 
         assert!(throttler.should_emit());
 
@@ -120,7 +257,7 @@ mod tests {
 
     #[test]
     fn test_force_emit() {
-        let throttler = ProgressThrottler::with_millis(1000);
+        let throttler = isolated(1000);
 
         assert!(throttler.should_emit());
         assert!(!throttler.should_emit());
@@ -131,7 +268,7 @@ mod tests {
 
     #[test]
     fn test_reset() {
-        let throttler = ProgressThrottler::with_millis(1000);
+        let throttler = isolated(1000);
 
         throttler.should_emit();
         assert!(!throttler.should_emit());
@@ -140,4 +277,65 @@ mod tests {
         throttler.reset();
         assert!(throttler.should_emit());
     }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let limiter = Arc::new(ProgressRateLimiter::new(0));
+        let base = Duration::from_millis(500);
+
+        let throttlers: Vec<_> = (0..10)
+            .map(|_| ProgressThrottler::with_limiter(base, limiter.clone()))
+            .collect();
+        assert_eq!(limiter.active_throttlers(), 10);
+        assert_eq!(limiter.scaled_interval(base), base);
+
+        let more: Vec<_> = (0..40)
+            .map(|_| ProgressThrottler::with_limiter(base, limiter.clone()))
+            .collect();
+        assert_eq!(limiter.active_throttlers(), 50);
+        assert_eq!(limiter.scaled_interval(base), Duration::from_millis(1250));
+
+        // 任务结束后节流器释放，间隔恢复
+        drop(more);
+        drop(throttlers);
+        assert_eq!(limiter.active_throttlers(), 0);
+        assert_eq!(limiter.scaled_interval(base), base);
+    }
+
+    #[test]
+    fn test_global_rate_limit_with_50_tasks() {
+        let limiter = Arc::new(ProgressRateLimiter::new(100));
+        // 50 个任务，基础间隔 10ms（拉伸后 25ms），不限速时约 2000 事件/秒
+        let throttlers: Vec<_> = (0..50)
+            .map(|_| ProgressThrottler::with_limiter(Duration::from_millis(10), limiter.clone()))
+            .collect();
+
+        let start = Instant::now();
+        let mut emitted = 0u64;
+        while start.elapsed() < Duration::from_millis(300) {
+            for throttler in &throttlers {
+                if throttler.should_emit() {
+                    emitted += 1;
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // 300ms 落在同一个 1 秒窗口内，总数不能超过上限
+        assert!(emitted > 0);
+        assert!(emitted <= 100, "emitted {} events, limit 100/s", emitted);
+    }
+
+    #[test]
+    fn test_force_emit_bypasses_global_limit() {
+        let limiter = Arc::new(ProgressRateLimiter::new(1));
+        let a = ProgressThrottler::with_limiter(Duration::from_millis(0), limiter.clone());
+        let b = ProgressThrottler::with_limiter(Duration::from_millis(0), limiter);
+
+        assert!(a.should_emit());
+        // 全局名额已满
+        assert!(!b.should_emit());
+        // 完成事件仍然发布
+        assert!(b.force_emit());
+    }
 }
@@ -33,6 +33,9 @@ pub enum DownloadEvent {
         /// 原始文件名（加密文件解密后的文件名）
         #[serde(default, skip_serializing_if = "Option::is_none")]
         original_filename: Option<String>,
+        /// 批量下载批次 ID（同一次批量请求创建的任务共享）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        batch_id: Option<String>,
     },
     /// 任务跳过（文件已存在）
     Skipped {
@@ -47,6 +50,12 @@ pub enum DownloadEvent {
         total_size: u64,
         speed: u64,
         progress: f64,
+        /// 预计剩余时间（秒），速度为 0 或总大小未知时为 null
+        #[serde(default)]
+        eta_secs: Option<u64>,
+        /// 实际提供下载的 CDN 主机
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cdn_host: Option<String>,
         group_id: Option<String>,
         /// 是否为自动备份任务
         #[serde(default)]
@@ -130,6 +139,28 @@ pub enum DownloadEvent {
         #[serde(default)]
         is_backup: bool,
     },
+    /// 完整性校验失败（MD5 不一致）
+    IntegrityFailed {
+        task_id: String,
+        /// 网盘元数据中的 MD5
+        expected: String,
+        /// 本地文件实际 MD5
+        actual: String,
+        group_id: Option<String>,
+        /// 是否为自动备份任务
+        #[serde(default)]
+        is_backup: bool,
+    },
+    /// 任务级自动重试（可恢复错误导致任务失败后重新获取链接并重新调度）
+    Retrying {
+        task_id: String,
+        /// 第几次重试（从 1 开始）
+        attempt: u32,
+        /// 最大重试次数
+        max_attempts: u32,
+        /// 触发重试的错误
+        error: String,
+    },
 }
 
 impl DownloadEvent {
@@ -147,6 +178,8 @@ impl DownloadEvent {
             DownloadEvent::Deleted { task_id, .. } => task_id,
             DownloadEvent::DecryptProgress { task_id, .. } => task_id,
             DownloadEvent::DecryptCompleted { task_id, .. } => task_id,
+            DownloadEvent::IntegrityFailed { task_id, .. } => task_id,
+            DownloadEvent::Retrying { task_id, .. } => task_id,
         }
     }
 
@@ -164,6 +197,8 @@ impl DownloadEvent {
             DownloadEvent::Deleted { group_id, .. } => group_id.as_deref(),
             DownloadEvent::DecryptProgress { group_id, .. } => group_id.as_deref(),
             DownloadEvent::DecryptCompleted { group_id, .. } => group_id.as_deref(),
+            DownloadEvent::IntegrityFailed { group_id, .. } => group_id.as_deref(),
+            DownloadEvent::Retrying { .. } => None,
         }
     }
 
@@ -181,6 +216,8 @@ impl DownloadEvent {
             DownloadEvent::Resumed { .. } => EventPriority::Medium,
             DownloadEvent::Deleted { .. } => EventPriority::High,
             DownloadEvent::DecryptCompleted { .. } => EventPriority::High,
+            DownloadEvent::IntegrityFailed { .. } => EventPriority::High,
+            DownloadEvent::Retrying { .. } => EventPriority::Medium,
         }
     }
 
@@ -198,6 +235,8 @@ impl DownloadEvent {
             DownloadEvent::Deleted { .. } => "deleted",
             DownloadEvent::DecryptProgress { .. } => "decrypt_progress",
             DownloadEvent::DecryptCompleted { .. } => "decrypt_completed",
+            DownloadEvent::IntegrityFailed { .. } => "integrity_failed",
+            DownloadEvent::Retrying { .. } => "retrying",
         }
     }
 
@@ -215,6 +254,8 @@ impl DownloadEvent {
             DownloadEvent::Deleted { is_backup, .. } => *is_backup,
             DownloadEvent::DecryptProgress { is_backup, .. } => *is_backup,
             DownloadEvent::DecryptCompleted { is_backup, .. } => *is_backup,
+            DownloadEvent::IntegrityFailed { is_backup, .. } => *is_backup,
+            DownloadEvent::Retrying { .. } => false,
         }
     }
 }
@@ -335,6 +376,9 @@ pub enum UploadEvent {
         total_size: u64,
         speed: u64,
         progress: f64,
+        /// 预计剩余时间（秒），速度为 0 或总大小未知时为 null
+        #[serde(default)]
+        eta_secs: Option<u64>,
         completed_chunks: usize,
         total_chunks: usize,
         /// 是否为自动备份任务
@@ -419,6 +463,15 @@ pub enum UploadEvent {
         remote_path: String,
         reason: String,
     },
+    /// 断点进度已重置（源文件被修改等原因，需要重新上传）
+    ProgressReset {
+        task_id: String,
+        /// 重置原因
+        reason: String,
+        /// 是否为自动备份任务
+        #[serde(default)]
+        is_backup: bool,
+    },
 }
 
 impl UploadEvent {
@@ -436,6 +489,7 @@ impl UploadEvent {
             UploadEvent::EncryptProgress { task_id, .. } => task_id,
             UploadEvent::EncryptCompleted { task_id, .. } => task_id,
             UploadEvent::Skipped { task_id, .. } => task_id,
+            UploadEvent::ProgressReset { task_id, .. } => task_id,
         }
     }
 
@@ -452,7 +506,8 @@ impl UploadEvent {
             | UploadEvent::Resumed { .. }
             | UploadEvent::Deleted { .. }
             | UploadEvent::EncryptCompleted { .. }
-            | UploadEvent::Skipped { .. } => EventPriority::High,
+            | UploadEvent::Skipped { .. }
+            | UploadEvent::ProgressReset { .. } => EventPriority::High,
         }
     }
 
@@ -470,6 +525,7 @@ impl UploadEvent {
             UploadEvent::EncryptProgress { .. } => "encrypt_progress",
             UploadEvent::EncryptCompleted { .. } => "encrypt_completed",
             UploadEvent::Skipped { .. } => "skipped",
+            UploadEvent::ProgressReset { .. } => "progress_reset",
         }
     }
 
@@ -487,6 +543,7 @@ impl UploadEvent {
             UploadEvent::EncryptProgress { is_backup, .. } => *is_backup,
             UploadEvent::EncryptCompleted { is_backup, .. } => *is_backup,
             UploadEvent::Skipped { .. } => false, // Skipped events are not backup tasks
+            UploadEvent::ProgressReset { is_backup, .. } => *is_backup,
         }
     }
 }
@@ -1079,6 +1136,8 @@ mod tests {
             total_size: 2000,
             speed: 500,
             progress: 50.0,
+            eta_secs: Some(2),
+            cdn_host: Some("d.pcs.baidu.com".to_string()),
             group_id: None,
             is_backup: false,
         };
@@ -1086,6 +1145,8 @@ mod tests {
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("progress"));
         assert!(json.contains("test-123"));
+        assert!(json.contains("\"eta_secs\":2"));
+        assert!(json.contains("\"cdn_host\":\"d.pcs.baidu.com\""));
     }
 
     #[test]
@@ -1099,11 +1160,31 @@ mod tests {
             group_id: None,
             is_backup: false,
             original_filename: None,
+            batch_id: Some("batch-1".to_string()),
         });
 
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("download"));
         assert!(json.contains("created"));
+        assert!(json.contains("\"batch_id\":\"batch-1\""));
+    }
+
+    #[test]
+    fn test_integrity_failed_event_serialization() {
+        let event = DownloadEvent::IntegrityFailed {
+            task_id: "test-123".to_string(),
+            expected: "d41d8cd98f00b204e9800998ecf8427e".to_string(),
+            actual: "0cc175b9c0f1b6a831c399e269772661".to_string(),
+            group_id: None,
+            is_backup: false,
+        };
+
+        assert_eq!(event.event_type_name(), "integrity_failed");
+        assert_eq!(event.priority(), EventPriority::High);
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("integrity_failed"));
+        assert!(json.contains("d41d8cd98f00b204e9800998ecf8427e"));
     }
 
     #[test]
@@ -1114,6 +1195,8 @@ mod tests {
             total_size: 0,
             speed: 0,
             progress: 0.0,
+            eta_secs: None,
+            cdn_host: None,
             group_id: None,
             is_backup: false,
         };
@@ -1,10 +1,10 @@
 // 认证API处理器
 
-use crate::auth::{CookieLoginAuth, CookieLoginApiRequest, QRCode, QRCodeStatus};
+use crate::auth::{
+    CookieLoginAuth, CookieLoginApiRequest, ImportCredentialsRequest, QRCode, QRCodeStatus,
+};
 use crate::common::ProxyType;
 use crate::server::AppState;
-use crate::transfer::TransferManager;
-use crate::uploader::UploadManager;
 use axum::{
     extract::{Query, State},
     http::StatusCode,
@@ -12,7 +12,6 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
 use tracing::{error, info, warn};
 
 /// 统一API响应格式
@@ -155,207 +154,12 @@ pub async fn qrcode_status(
                     user.bduss.len()
                 );
 
-                // 初始化用户资源（网盘客户端和下载管理器）
-                *state.current_user.write().await = Some(user.clone());
-
-                // 初始化网盘客户端
-                let config_guard = state.config.read().await;
-                let proxy_config = if config_guard.network.proxy.proxy_type != ProxyType::None {
-                    Some(config_guard.network.proxy.clone())
-                } else {
-                    None
-                };
-                drop(config_guard);
-
-                // 设置代理回退管理器的用户代理配置
-                state.fallback_mgr
-                    .set_user_proxy_config(proxy_config.clone())
-                    .await;
-
-                let fallback_for_client = if proxy_config.is_some() {
-                    Some(Arc::clone(&state.fallback_mgr))
-                } else {
-                    None
-                };
-                let client = match crate::netdisk::NetdiskClient::new_with_proxy(user.clone(), proxy_config.as_ref(), fallback_for_client.clone()) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        error!("初始化网盘客户端失败: {}", e);
-                        return Ok(Json(ApiResponse::success(status)));
-                    }
-                };
-
-                // 执行预热并保存预热 Cookie
-                info!("登录成功,开始预热会话...");
-                let mut updated_user = user.clone();
-                match client.warmup_and_get_cookies().await {
-                    Ok((panpsc, csrf_token, bdstoken, stoken)) => {
-                        info!("预热成功,保存预热 Cookie 到 session.json");
-                        if panpsc.is_some() {
-                            updated_user.panpsc = panpsc;
-                        }
-                        if csrf_token.is_some() {
-                            updated_user.csrf_token = csrf_token;
-                        }
-                        updated_user.bdstoken = bdstoken;
-                        // 预热时下发的 STOKEN 优先于登录时获取的
-                        if stoken.is_some() {
-                            updated_user.stoken = stoken;
-                        }
-
-                        // 更新内存和持久化
-                        *state.current_user.write().await = Some(updated_user.clone());
-                        if let Err(e) = session.save_session(&updated_user).await {
-                            error!("保存预热 Cookie 失败: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("预热失败(继续使用未预热的客户端): {}", e);
-                    }
-                }
-
-                let client_arc = Arc::new(client.clone());
-                *state.netdisk_client.write().await = Some(client.clone());
-
-                // 初始化下载管理器
-                let config = state.config.read().await;
-                let download_dir = config.download.download_dir.clone();
-                let max_global_threads = config.download.max_global_threads;
-                let max_concurrent_tasks = config.download.max_concurrent_tasks;
-                let max_retries = config.download.max_retries;
-                let upload_config = config.upload.clone();
-                let transfer_config = config.transfer.clone();
-                drop(config);
-
-                // 获取持久化管理器引用
-                let pm_arc = Arc::clone(&state.persistence_manager);
-
-                match crate::downloader::DownloadManager::with_config(
-                    updated_user.clone(),
-                    download_dir,
-                    max_global_threads,
-                    max_concurrent_tasks,
-                    max_retries,
-                    proxy_config.as_ref(),
-                    fallback_for_client,
-                ) {
-                    Ok(mut manager) => {
-                        // 设置持久化管理器
-                        manager.set_persistence_manager(Arc::clone(&pm_arc));
-
-                        // 设置 WebSocket 管理器
-                        manager.set_ws_manager(Arc::clone(&state.ws_manager)).await;
-
-                        let manager_arc = Arc::new(manager);
-                        *state.download_manager.write().await = Some(Arc::clone(&manager_arc));
-
-                        // 设置文件夹下载管理器的依赖
-                        state
-                            .folder_download_manager
-                            .set_download_manager(Arc::clone(&manager_arc))
-                            .await;
-                        state
-                            .folder_download_manager
-                            .set_netdisk_client(client_arc)
-                            .await;
-
-                        // 设置文件夹下载管理器的 WAL 目录
-                        let wal_dir = pm_arc.lock().await.wal_dir().clone();
-                        state.folder_download_manager.set_wal_dir(wal_dir).await;
-
-                        // 设置文件夹下载管理器的持久化管理器（用于加载历史文件夹）
-                        state
-                            .folder_download_manager
-                            .set_persistence_manager(Arc::clone(&pm_arc))
-                            .await;
-
-                        // 设置文件夹下载管理器的 WebSocket 管理器
-                        state
-                            .folder_download_manager
-                            .set_ws_manager(Arc::clone(&state.ws_manager))
-                            .await;
-
-                        // 设置下载管理器对文件夹管理器的引用（用于回收借调槽位）
-                        manager_arc
-                            .set_folder_manager(Arc::clone(&state.folder_download_manager))
-                            .await;
-
-                        info!("✅ 下载管理器初始化成功");
-
-                        // 初始化上传管理器（使用配置参数）
-                        let config_dir = std::path::Path::new("config");
-                        let upload_manager =
-                            UploadManager::new_with_config(client.clone(), &updated_user, &upload_config, config_dir);
-                        let upload_manager_arc = Arc::new(upload_manager);
-
-                        // 设置上传管理器的持久化管理器
-                        upload_manager_arc
-                            .set_persistence_manager(Arc::clone(&pm_arc))
-                            .await;
-
-                        // 设置上传管理器的 WebSocket 管理器
-                        upload_manager_arc
-                            .set_ws_manager(Arc::clone(&state.ws_manager))
-                            .await;
-
-                        // 🔥 设置备份记录管理器（用于文件夹名加密映射）
-                        upload_manager_arc
-                            .set_backup_record_manager(Arc::clone(&state.backup_record_manager))
-                            .await;
-
-                        *state.upload_manager.write().await = Some(Arc::clone(&upload_manager_arc));
-                        info!("✅ 上传管理器初始化成功");
-
-                        // 初始化转存管理器
-                        let transfer_manager = TransferManager::new(
-                            Arc::new(std::sync::RwLock::new(client)),
-                            transfer_config,
-                            Arc::clone(&state.config),
-                        );
-                        let transfer_manager_arc = Arc::new(transfer_manager);
-
-                        // 设置下载管理器（用于自动下载功能）
-                        transfer_manager_arc
-                            .set_download_manager(Arc::clone(&manager_arc))
-                            .await;
-
-                        // 设置文件夹下载管理器（用于自动下载文件夹）
-                        transfer_manager_arc
-                            .set_folder_download_manager(Arc::clone(&state.folder_download_manager))
-                            .await;
-
-                        // 设置转存管理器的持久化管理器
-                        transfer_manager_arc
-                            .set_persistence_manager(Arc::clone(&pm_arc))
-                            .await;
-
-                        // 设置转存管理器的 WebSocket 管理器
-                        transfer_manager_arc
-                            .set_ws_manager(Arc::clone(&state.ws_manager))
-                            .await;
-
-                        *state.transfer_manager.write().await = Some(Arc::clone(&transfer_manager_arc));
-                        info!("✅ 转存管理器初始化成功");
-
-                        // 启动 WebSocket 批量发送器
-                        Arc::clone(&state.ws_manager).start_batch_sender();
-                        info!("✅ WebSocket 批量发送器已启动");
-
-                        // 🔥 启动内存监控器
-                        Arc::clone(&state.memory_monitor).start();
-                        info!("✅ 内存监控器已启动");
-
-                        // 🔥 初始化自动备份管理器
-                        state.init_autobackup_manager().await;
-                        info!("✅ 自动备份管理器初始化完成");
-
-                        // 🔥 初始化离线下载监听服务
-                        state.init_cloud_dl_monitor().await;
-                        info!("✅ 离线下载监听服务初始化完成");
-                    }
-                    Err(e) => {
-                        error!("❌ 初始化下载管理器失败: {}", e);
-                    }
+                // 🔥 激活账号：停用旧账号的管理器、切换持久化目录、初始化各管理器并恢复任务
+                // （先释放 session 锁，预热后保存会话时需要再次获取）
+                drop(session);
+                match state.activate_account(user.clone()).await {
+                    Ok(_) => info!("✅ 用户资源初始化成功"),
+                    Err(e) => error!("❌ 初始化用户资源失败: {}", e),
                 }
             }
 
@@ -439,6 +243,25 @@ pub async fn get_current_user(
     }
 }
 
+/// 获取网盘空间配额（用于展示空间使用情况）
+///
+/// GET /api/v1/user/quota
+pub async fn get_user_quota(
+    State(state): State<AppState>,
+) -> Json<ApiResponse<crate::netdisk::QuotaInfo>> {
+    let Some(client) = state.netdisk_client.read().await.clone() else {
+        return Json(ApiResponse::error(401, "未登录或客户端未初始化".to_string()));
+    };
+
+    match client.get_quota().await {
+        Ok(quota) => Json(ApiResponse::success(quota)),
+        Err(e) => {
+            error!("获取网盘配额失败: {:?}", e);
+            Json(ApiResponse::error(500, format!("获取网盘配额失败: {}", e)))
+        }
+    }
+}
+
 /// 登出
 ///
 /// POST /api/v1/auth/logout
@@ -459,6 +282,9 @@ pub async fn logout(State(state): State<AppState>) -> Result<impl IntoResponse,
 
     info!("已清除 current_user 和 netdisk_client");
 
+    // 4. 清除配置中的激活账号（其他已保存账号仍可切换）
+    state.persist_active_uid(None).await;
+
     match clear_result {
         Ok(_) => {
             info!("登出成功");
@@ -493,18 +319,8 @@ pub async fn cookie_login(
         )));
     }
 
-    // 读取当前代理配置
-    let proxy_config = {
-        let config_guard = state.config.read().await;
-        if config_guard.network.proxy.proxy_type != ProxyType::None {
-            Some(config_guard.network.proxy.clone())
-        } else {
-            None
-        }
-    };
-
     // 创建 Cookie 登录客户端（复用代理配置）
-    let cookie_auth = match CookieLoginAuth::new_with_proxy(proxy_config.as_ref()) {
+    let cookie_auth = match new_cookie_auth(&state).await {
         Ok(a) => a,
         Err(e) => {
             error!("创建 Cookie 登录客户端失败: {}", e);
@@ -532,15 +348,33 @@ pub async fn cookie_login(
         user.uid, user.username
     );
 
+    Ok(finish_cookie_login(&state, user).await)
+}
+
+/// 创建 Cookie 登录客户端（使用当前代理配置）
+async fn new_cookie_auth(state: &AppState) -> anyhow::Result<CookieLoginAuth> {
+    let proxy_config = {
+        let config_guard = state.config.read().await;
+        if config_guard.network.proxy.proxy_type != ProxyType::None {
+            Some(config_guard.network.proxy.clone())
+        } else {
+            None
+        }
+    };
+    CookieLoginAuth::new_with_proxy(proxy_config.as_ref())
+}
+
+/// Cookie / 凭证登录验证通过后的公共流程：保存会话、初始化所有管理器并生成响应
+async fn finish_cookie_login(
+    state: &AppState,
+    user: crate::auth::UserAuth,
+) -> Json<ApiResponse<crate::auth::UserAuth>> {
     // 保存会话（先释放锁再调用 load_initial_session，避免死锁）
     {
         let mut session = state.session_manager.lock().await;
         if let Err(e) = session.save_session(&user).await {
             error!("保存会话失败: {}", e);
-            return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
-                500,
-                format!("保存会话失败: {}", e),
-            )));
+            return Json(ApiResponse::error(500, format!("保存会话失败: {}", e)));
         }
         *state.current_user.write().await = Some(user.clone());
         info!("✅ 会话保存成功");
@@ -591,5 +425,192 @@ pub async fn cookie_login(
             has_ptoken, final_user.panpsc.is_some());
     }
 
-    Ok(Json(ApiResponse::success_with_message(final_user, message)))
+    Json(ApiResponse::success_with_message(final_user, message))
+}
+
+/// 导入凭证登录
+///
+/// POST /api/v1/auth/import
+///
+/// 接受单独的 BDUSS / STOKEN / PTOKEN，先通过 verify_bduss 区分凭证失效与网络错误，
+/// 再解析用户信息、保存会话并初始化所有管理器（含预热获取 PANPSC / bdstoken）。
+pub async fn import_credentials(
+    State(state): State<AppState>,
+    Json(req): Json<ImportCredentialsRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    info!("API: 导入凭证登录");
+
+    let bduss = req.bduss.trim();
+    if bduss.is_empty() {
+        return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+            400,
+            "bduss 字段不能为空".to_string(),
+        )));
+    }
+
+    // 先验证 BDUSS，区分凭证失效与网络错误
+    match state.qrcode_auth.read().await.verify_bduss(bduss).await {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!("导入的 BDUSS 无效或已过期");
+            return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+                401,
+                "BDUSS 无效或已过期，请重新从浏览器获取".to_string(),
+            )));
+        }
+        Err(e) => {
+            error!("验证导入的 BDUSS 失败: {}", e);
+            return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+                502,
+                format!("验证 BDUSS 时网络错误: {}", e),
+            )));
+        }
+    }
+
+    let cookie_auth = match new_cookie_auth(&state).await {
+        Ok(a) => a,
+        Err(e) => {
+            error!("创建 Cookie 登录客户端失败: {}", e);
+            return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+                500,
+                format!("创建客户端失败: {}", e),
+            )));
+        }
+    };
+
+    let user = match cookie_auth
+        .login_with_tokens(bduss, req.stoken.as_deref(), req.ptoken.as_deref())
+        .await
+    {
+        Ok(u) => u,
+        Err(e) => {
+            error!("导入凭证登录失败: {}", e);
+            return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+                400,
+                format!("{}", e),
+            )));
+        }
+    };
+
+    info!(
+        "凭证验证成功: UID={}, 用户名={}，开始初始化会话...",
+        user.uid, user.username
+    );
+
+    Ok(finish_cookie_login(&state, user).await)
+}
+
+/// 切换账号请求
+#[derive(Debug, Deserialize)]
+pub struct SwitchAccountRequest {
+    /// 目标账号 UID（必须已登录保存过会话）
+    pub uid: u64,
+}
+
+/// 已保存账号信息（不包含凭证）
+#[derive(Debug, Serialize)]
+pub struct AccountInfo {
+    pub uid: u64,
+    pub username: String,
+    pub nickname: Option<String>,
+    pub avatar_url: Option<String>,
+    pub vip_type: Option<u32>,
+    /// 是否为当前激活账号
+    pub is_active: bool,
+}
+
+/// 获取已保存的账号列表
+///
+/// GET /api/v1/auth/accounts
+pub async fn list_accounts(State(state): State<AppState>) -> Result<impl IntoResponse, StatusCode> {
+    info!("API: 获取已保存账号列表");
+
+    let active_uid = state.current_user.read().await.as_ref().map(|u| u.uid);
+
+    match state.list_accounts().await {
+        Ok(accounts) => {
+            let accounts: Vec<AccountInfo> = accounts
+                .into_iter()
+                .map(|a| AccountInfo {
+                    is_active: Some(a.uid) == active_uid,
+                    uid: a.uid,
+                    username: a.username,
+                    nickname: a.nickname,
+                    avatar_url: a.avatar_url,
+                    vip_type: a.vip_type,
+                })
+                .collect();
+            Ok(Json(ApiResponse::success(accounts)))
+        }
+        Err(e) => {
+            error!("获取账号列表失败: {}", e);
+            Ok(Json(ApiResponse::<Vec<AccountInfo>>::error(
+                500,
+                format!("获取账号列表失败: {}", e),
+            )))
+        }
+    }
+}
+
+/// 切换账号
+///
+/// POST /api/v1/auth/switch
+///
+/// 切换到已保存会话的账号，重建网盘客户端和下载/上传/转存管理器
+pub async fn switch_account(
+    State(state): State<AppState>,
+    Json(req): Json<SwitchAccountRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    info!("API: 切换账号: UID={}", req.uid);
+
+    if state.current_user.read().await.as_ref().map(|u| u.uid) == Some(req.uid) {
+        return Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+            400,
+            "已是当前账号".to_string(),
+        )));
+    }
+
+    match state.switch_account(req.uid).await {
+        Ok(user) => {
+            info!("✅ 账号切换成功: UID={}", user.uid);
+            Ok(Json(ApiResponse::success(user)))
+        }
+        Err(e) => {
+            error!("切换账号失败: {}", e);
+            Ok(Json(ApiResponse::<crate::auth::UserAuth>::error(
+                400,
+                format!("切换账号失败: {}", e),
+            )))
+        }
+    }
+}
+
+/// 登录态状态响应
+#[derive(Debug, Serialize)]
+pub struct AuthStatusResponse {
+    /// 是否已登录
+    pub logged_in: bool,
+    /// 登录态是否有效（后台检测 BDUSS 结果）
+    pub auth_valid: bool,
+    /// 当前账号 UID
+    pub uid: Option<u64>,
+    /// 上次检测时间（Unix 时间戳，秒）
+    pub last_checked_at: Option<i64>,
+}
+
+/// 获取登录态状态
+///
+/// GET /api/v1/auth/status
+pub async fn get_auth_status(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<AuthStatusResponse>>, StatusCode> {
+    let uid = state.current_user.read().await.as_ref().map(|u| u.uid);
+    let logged_in = uid.is_some();
+
+    Ok(Json(ApiResponse::success(AuthStatusResponse {
+        logged_in,
+        auth_valid: logged_in && state.auth_valid.load(std::sync::atomic::Ordering::SeqCst),
+        uid,
+        last_checked_at: *state.auth_last_checked_at.read().await,
+    })))
 }
@@ -2,16 +2,21 @@
 //!
 //! 本模块提供离线下载功能的 HTTP API 接口，包括：
 //! - 添加离线下载任务
+//! - 批量添加离线下载任务
 //! - 查询任务列表
 //! - 查询单个任务详情
 //! - 取消任务
 //! - 删除任务
 //! - 清空任务记录
 //! - 手动刷新任务列表
+//!
+//! 添加、取消、删除任务后会重新查询任务详情并随响应返回，前端无需等待下一次轮询即可刷新状态。
+//! 百度离线下载接口不支持暂停/继续，任务生命周期只能通过取消和删除控制。
 
 use crate::netdisk::cloud_dl::{
-    AddTaskRequest, AddTaskResponse, AutoDownloadConfig, ClearTasksResponse, CloudDlTaskInfo,
-    OperationResponse, TaskListResponse,
+    AddTaskRequest, AddTaskResponse, BatchAddTaskItem, BatchAddTaskRequest, BatchAddTaskResponse,
+    ClearTasksResponse, CloudDlAddTaskError, CloudDlTaskInfo, OperationResponse, TaskListResponse,
+    BATCH_ADD_INTERVAL_MS, BATCH_ADD_MAX_URLS,
 };
 use crate::server::handlers::ApiResponse;
 use crate::server::AppState;
@@ -22,6 +27,20 @@ use axum::{
 };
 use tracing::{error, info, warn};
 
+/// 操作后重新查询任务详情（查询失败或任务已不存在时返回 None，不影响操作结果）
+async fn query_task_snapshot(
+    client: &crate::netdisk::NetdiskClient,
+    task_id: i64,
+) -> Option<CloudDlTaskInfo> {
+    match client.cloud_dl_query_task(&[task_id]).await {
+        Ok(tasks) => tasks.into_iter().next(),
+        Err(e) => {
+            warn!("操作后查询离线下载任务详情失败: task_id={}, 错误={}", task_id, e);
+            None
+        }
+    }
+}
+
 // =====================================================
 // 添加任务
 // =====================================================
@@ -47,7 +66,8 @@ use tracing::{error, info, warn};
 ///     "code": 0,
 ///     "message": "Success",
 ///     "data": {
-///         "task_id": 123456789
+///         "task_id": 123456789,
+///         "task": { ... }
 ///     }
 /// }
 /// ```
@@ -56,7 +76,7 @@ pub async fn add_task(
     Json(req): Json<AddTaskRequest>,
 ) -> Result<Json<ApiResponse<AddTaskResponse>>, StatusCode> {
     info!(
-        "API: 添加离线下载任务 source_url={}, save_path={}, auto_download={}, local_download_path={:?}, ask_download_path={}",
+        "API: 添加离线下载任务 source_url={}, save_path={}, auto_download={:?}, local_download_path={:?}, ask_download_path={}",
         req.source_url, req.save_path, req.auto_download, req.local_download_path, req.ask_download_path
     );
 
@@ -77,29 +97,40 @@ pub async fn add_task(
         Ok(task_id) => {
             info!("添加离线下载任务成功: task_id={}", task_id);
 
-            // 如果启用了自动下载，注册自动下载配置到监听服务
-            if req.auto_download {
-                let auto_config = AutoDownloadConfig::enabled(
-                    task_id,
-                    req.local_download_path.clone(),
-                    req.ask_download_path,
-                );
+            // 唤醒监听服务（所有任务结束后轮询会暂停）
+            if let Some(ref monitor) = *state.cloud_dl_monitor.read().await {
+                monitor.notify_new_task();
+            }
 
+            // 如果启用了自动下载（请求指定或全局配置），注册自动下载配置到监听服务
+            let auto_config = {
+                let config = state.config.read().await;
+                req.resolve_auto_download(task_id, &config.cloud_dl, &config.download.download_dir)
+            };
+            if let Some(auto_config) = auto_config {
                 // 获取离线下载监听服务并注册配置
                 if let Some(ref monitor) = *state.cloud_dl_monitor.read().await {
-                    monitor.register_auto_download(task_id, auto_config).await;
                     info!(
                         "已注册自动下载配置: task_id={}, local_path={:?}, ask_each_time={}",
-                        task_id, req.local_download_path, req.ask_download_path
+                        task_id, auto_config.local_path, auto_config.ask_each_time
                     );
+                    monitor.register_auto_download(task_id, auto_config).await;
                 } else {
                     warn!("离线下载监听服务未初始化，无法注册自动下载配置");
                 }
             }
 
-            Ok(Json(ApiResponse::success(AddTaskResponse { task_id })))
+            let task = query_task_snapshot(client, task_id).await;
+            Ok(Json(ApiResponse::success(AddTaskResponse { task_id, task })))
         }
         Err(e) => {
+            // 链接不合法属于请求错误，返回 400
+            if let Some(add_err) = e.downcast_ref::<CloudDlAddTaskError>() {
+                if add_err.is_invalid_input() {
+                    warn!("离线下载链接校验失败: {}", add_err);
+                    return Ok(Json(ApiResponse::error(400, add_err.to_string())));
+                }
+            }
             error!("添加离线下载任务失败: {}", e);
             Ok(Json(ApiResponse::error(
                 500,
@@ -109,6 +140,132 @@ pub async fn add_task(
     }
 }
 
+// =====================================================
+// 批量添加任务
+// =====================================================
+
+/// 批量添加离线下载任务
+///
+/// POST /api/v1/cloud-dl/tasks/batch
+///
+/// 逐个添加链接（相邻请求间隔 BATCH_ADD_INTERVAL_MS），单个链接失败不影响其他链接。
+/// 空白行和重复链接会被忽略，保存路径和自动下载设置对所有链接生效。
+///
+/// # 请求体
+/// ```json
+/// {
+///     "urls": ["magnet:?xt=urn:btih:...", "magnet:?xt=urn:btih:..."],
+///     "save_path": "/downloads",
+///     "auto_download": true
+/// }
+/// ```
+///
+/// # 响应
+/// ```json
+/// {
+///     "code": 0,
+///     "message": "Success",
+///     "data": {
+///         "results": [
+///             { "source_url": "magnet:?xt=urn:btih:...", "task_id": 123456789 },
+///             { "source_url": "magnet:?xt=urn:btih:...", "task_id": null, "error": "..." }
+///         ],
+///         "success_count": 1,
+///         "failed_count": 1
+///     }
+/// }
+/// ```
+pub async fn batch_add_tasks(
+    State(state): State<AppState>,
+    Json(req): Json<BatchAddTaskRequest>,
+) -> Result<Json<ApiResponse<BatchAddTaskResponse>>, StatusCode> {
+    let urls = req.normalized_urls();
+    info!(
+        "API: 批量添加离线下载任务 count={}, save_path={}",
+        urls.len(),
+        req.save_path
+    );
+
+    if urls.is_empty() {
+        return Ok(Json(ApiResponse::error(400, "下载链接列表为空".to_string())));
+    }
+    if urls.len() > BATCH_ADD_MAX_URLS {
+        return Ok(Json(ApiResponse::error(
+            400,
+            format!("单次最多添加 {} 个链接，当前 {} 个", BATCH_ADD_MAX_URLS, urls.len()),
+        )));
+    }
+
+    // 克隆客户端，避免在逐个添加期间长时间持有锁
+    let client = match state.netdisk_client.read().await.clone() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    let mut results = Vec::with_capacity(urls.len());
+    for (index, url) in urls.iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(BATCH_ADD_INTERVAL_MS)).await;
+        }
+
+        match client.cloud_dl_add_task(url, &req.save_path).await {
+            Ok(task_id) => {
+                info!("批量添加离线下载任务成功: task_id={}, url={}", task_id, url);
+
+                let auto_config = {
+                    let config = state.config.read().await;
+                    req.task_request(url).resolve_auto_download(
+                        task_id,
+                        &config.cloud_dl,
+                        &config.download.download_dir,
+                    )
+                };
+                if let Some(auto_config) = auto_config {
+                    if let Some(ref monitor) = *state.cloud_dl_monitor.read().await {
+                        monitor.register_auto_download(task_id, auto_config).await;
+                    } else {
+                        warn!("离线下载监听服务未初始化，无法注册自动下载配置");
+                    }
+                }
+
+                results.push(BatchAddTaskItem {
+                    source_url: url.clone(),
+                    task_id: Some(task_id),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                warn!("批量添加离线下载任务失败: url={}, 错误={}", url, e);
+                results.push(BatchAddTaskItem {
+                    source_url: url.clone(),
+                    task_id: None,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    let response = BatchAddTaskResponse::from_results(results);
+    info!(
+        "批量添加离线下载任务完成: 成功 {} 个, 失败 {} 个",
+        response.success_count, response.failed_count
+    );
+
+    // 唤醒监听服务（所有任务结束后轮询会暂停）
+    if response.success_count > 0 {
+        if let Some(ref monitor) = *state.cloud_dl_monitor.read().await {
+            monitor.notify_new_task();
+        }
+    }
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
 // =====================================================
 // 查询任务列表
 // =====================================================
@@ -144,8 +301,8 @@ pub async fn list_tasks(
         }
     };
 
-    // 调用 API 获取任务列表
-    match client.cloud_dl_list_task().await {
+    // 调用 API 获取任务列表（含进行中任务的进度）
+    match client.cloud_dl_list_task_with_progress().await {
         Ok(tasks) => {
             info!("获取离线下载任务列表成功: {} 个任务", tasks.len());
             Ok(Json(ApiResponse::success(TaskListResponse { tasks })))
@@ -234,7 +391,8 @@ pub async fn query_task(
 ///     "code": 0,
 ///     "message": "Success",
 ///     "data": {
-///         "success": true
+///         "success": true,
+///         "task": { ... }
 ///     }
 /// }
 /// ```
@@ -260,7 +418,8 @@ pub async fn cancel_task(
     match client.cloud_dl_cancel_task(task_id).await {
         Ok(()) => {
             info!("取消离线下载任务成功: task_id={}", task_id);
-            Ok(Json(ApiResponse::success(OperationResponse::success())))
+            let task = query_task_snapshot(client, task_id).await;
+            Ok(Json(ApiResponse::success(OperationResponse::success().with_task(task))))
         }
         Err(e) => {
             error!("取消离线下载任务失败: {}", e);
@@ -289,7 +448,8 @@ pub async fn cancel_task(
 ///     "code": 0,
 ///     "message": "Success",
 ///     "data": {
-///         "success": true
+///         "success": true,
+///         "task": { ... }
 ///     }
 /// }
 /// ```
@@ -315,7 +475,8 @@ pub async fn delete_task(
     match client.cloud_dl_delete_task(task_id).await {
         Ok(()) => {
             info!("删除离线下载任务成功: task_id={}", task_id);
-            Ok(Json(ApiResponse::success(OperationResponse::success())))
+            let task = query_task_snapshot(client, task_id).await;
+            Ok(Json(ApiResponse::success(OperationResponse::success().with_task(task))))
         }
         Err(e) => {
             error!("删除离线下载任务失败: {}", e);
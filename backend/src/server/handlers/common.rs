@@ -37,3 +37,58 @@ impl BatchOperationResponse {
         }
     }
 }
+
+/// 历史任务分页查询参数
+#[derive(Debug, Deserialize)]
+pub struct HistoryQueryParams {
+    /// 页码（从 1 开始）
+    #[serde(default = "default_history_page")]
+    pub page: usize,
+    /// 每页数量（最大 200）
+    #[serde(default = "default_history_size")]
+    pub size: usize,
+    /// 状态过滤
+    #[serde(default)]
+    pub status: Option<String>,
+    /// 文件名（路径）子串搜索
+    #[serde(default)]
+    pub q: Option<String>,
+}
+
+fn default_history_page() -> usize {
+    1
+}
+
+fn default_history_size() -> usize {
+    20
+}
+
+/// 每页最大数量
+const MAX_HISTORY_PAGE_SIZE: usize = 200;
+
+impl HistoryQueryParams {
+    /// 转换为历史数据库查询条件（任务类型由各管理器填充）
+    pub fn to_query(&self) -> crate::persistence::TaskHistoryQuery {
+        let size = self.size.clamp(1, MAX_HISTORY_PAGE_SIZE);
+        crate::persistence::TaskHistoryQuery {
+            status: self.status.clone().filter(|s| !s.is_empty()),
+            keyword: self.q.clone().filter(|q| !q.is_empty()),
+            offset: self.page.max(1).saturating_sub(1) * size,
+            limit: size,
+            ..Default::default()
+        }
+    }
+}
+
+/// 历史任务分页响应
+#[derive(Debug, Serialize)]
+pub struct HistoryPageResponse<T> {
+    /// 符合条件的总数
+    pub total: usize,
+    /// 当前页码
+    pub page: usize,
+    /// 每页数量
+    pub size: usize,
+    /// 当前页任务
+    pub tasks: Vec<T>,
+}
@@ -2,8 +2,10 @@
 
 use crate::config::{
     AppConfig, DownloadConfig, PathValidationResult, VipRecommendedConfig, VipType,
+    TRANSFER_BEHAVIOR_AND_DOWNLOAD, TRANSFER_BEHAVIOR_DOWNLOAD_CLEANUP, TRANSFER_BEHAVIOR_ONLY,
 };
 use crate::server::error::{ApiError, ApiResult};
+use crate::server::read_only::READ_ONLY_MESSAGE;
 use axum::{extract::State, response::Json};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -116,6 +118,18 @@ pub async fn reset_to_recommended(
         manager
             .update_download_dir(config.download.download_dir.clone())
             .await;
+        // 🔥 更新全局限速和 MD5 校验开关
+        manager.update_global_speed_limit(config.download.global_speed_limit_kbps);
+        manager.update_verify_md5(config.download.verify_md5_after_download);
+        manager.update_max_task_retries(config.download.max_retries);
+        manager.update_rate_limit_config(config.download.rate_limit.clone());
+        manager.update_adaptive_concurrency_config(config.download.adaptive_concurrency.clone());
+        manager.update_completion_webhook(
+            config.download.completion_webhook_url.clone(),
+            config.download.completion_webhook_secret.clone(),
+        );
+        manager.update_download_schedule(&config.schedule);
+        manager.apply_download_schedule().await;
         info!(
             "✓ 下载管理器已更新为推荐配置: 线程数={}, 最大任务数={}, 下载目录={:?}",
             config.download.max_global_threads,
@@ -150,50 +164,90 @@ pub async fn reset_to_recommended(
     Ok(Json(ApiResponse::success("已恢复为推荐配置".to_string())))
 }
 
-/// PUT /api/v1/config
-/// 更新配置
-pub async fn update_config(
-    State(app_state): State<crate::server::AppState>,
-    Json(new_config): Json<AppConfig>,
-) -> ApiResult<Json<ApiResponse<ConfigUpdateResponse>>> {
-    info!("更新应用配置");
+/// 按当前用户的 VIP 等级检查线程数等配置（API 更新和配置文件热重载共用）
+///
+/// 只记录警告不阻止生效，用户可能有特殊需求
+pub(crate) async fn warn_vip_limits(app_state: &crate::server::AppState, config: &AppConfig) {
+    let vip_type_value = app_state
+        .current_user
+        .read()
+        .await
+        .as_ref()
+        .and_then(|u| u.vip_type)
+        .unwrap_or(0);
+    let vip_type = VipType::from_u32(vip_type_value);
 
-    // 基本验证
-    if new_config.download.max_global_threads == 0 {
-        return Err(ApiError::BadRequest("线程数必须大于0".to_string()));
+    if let Err(warning) = config.download.validate_for_vip(vip_type) {
+        warn!("配置验证警告: {}", warning);
     }
+}
 
-    if new_config.download.chunk_size_mb == 0 {
-        return Err(ApiError::BadRequest("分片大小必须大于0".to_string()));
+/// 基本数值验证（API 更新和配置文件热重载共用）
+pub(crate) fn validate_config_values(config: &AppConfig) -> Result<(), String> {
+    if config.download.max_global_threads == 0 {
+        return Err("线程数必须大于0".to_string());
     }
 
-    if new_config.download.max_concurrent_tasks == 0 {
-        return Err(ApiError::BadRequest("最大同时下载数必须大于0".to_string()));
+    if config.download.chunk_size_mb == 0 {
+        return Err("分片大小必须大于0".to_string());
     }
 
-    // 获取当前用户的 VIP 类型并验证
-    let current_user = app_state.current_user.read().await;
-    let vip_type_value = current_user.as_ref().and_then(|u| u.vip_type).unwrap_or(0);
-    let vip_type = VipType::from_u32(vip_type_value);
-    drop(current_user);
+    if config.download.max_concurrent_tasks == 0 {
+        return Err("最大同时下载数必须大于0".to_string());
+    }
 
-    // 验证配置安全性（生成警告但不阻止）
-    if let Err(warning) = new_config.download.validate_for_vip(vip_type) {
-        warn!("配置验证警告: {}", warning);
-        // 注意：这里只是警告，不阻止用户设置，因为用户可能有特殊需求
+    if config.transfer.max_concurrent_transfers == 0 {
+        return Err("最大同时转存数必须大于0".to_string());
+    }
+
+    if config.persistence.wal_flush_interval_ms == 0 {
+        return Err("WAL 刷写间隔必须大于0".to_string());
+    }
+
+    config.network.validate_user_agents()?;
+    config.download.validate_completion_webhook()?;
+    config.download.validate_type_dirs().map_err(|e| e.to_string())?;
+    config.schedule.validate()?;
+
+    if config.network.request_timeout_secs == 0 {
+        return Err("接口请求超时必须大于0".to_string());
     }
 
-    // 代理配置验证
-    if let Err(e) = new_config.network.proxy.validate() {
+    Ok(())
+}
+
+/// PUT /api/v1/config
+/// 更新配置
+pub async fn update_config(
+    State(app_state): State<crate::server::AppState>,
+    Json(mut new_config): Json<AppConfig>,
+) -> ApiResult<Json<ApiResponse<ConfigUpdateResponse>>> {
+    info!("更新应用配置");
+
+    // 基本验证
+    validate_config_values(&new_config).map_err(ApiError::BadRequest)?;
+
+    // 按当前用户的 VIP 类型验证配置安全性（生成警告但不阻止）
+    warn_vip_limits(&app_state, &new_config).await;
+
+    // 代理配置验证（设置了 url 时先展开为结构化字段）
+    if let Err(e) = new_config
+        .network
+        .proxy
+        .resolve_url()
+        .and_then(|()| new_config.network.proxy.validate())
+    {
         return Err(ApiError::BadRequest(format!("代理配置验证失败: {}", e)));
     }
 
-    // ⚠️ 时序关键：在覆盖配置之前读取旧代理配置，用于变更检测
-    let old_proxy = {
-        let old_config = app_state.config.read().await;
-        old_config.network.proxy.clone()
-    };
-    let proxy_changed = old_proxy != new_config.network.proxy;
+    // ⚠️ 时序关键：在覆盖配置之前读取旧配置，用于变更检测
+    let old_config = app_state.config.read().await.clone();
+
+    // 🔥 只读模式下禁止修改安全配置（包括关闭只读模式本身，需修改配置文件后重载）
+    if old_config.security.read_only && new_config.security != old_config.security {
+        warn!("只读模式拒绝修改安全配置");
+        return Err(ApiError::Forbidden(READ_ONLY_MESSAGE.to_string()));
+    }
 
     // 保存到文件（包含完整的路径验证）
     let validation_result = new_config
@@ -204,6 +258,69 @@ pub async fn update_config(
     // 更新内存中的配置
     *app_state.config.write().await = new_config.clone();
 
+    // 🔧 动态应用运行时配置（无需重启，不影响正在进行的任务）
+    apply_runtime_config(&app_state, &old_config, &new_config).await;
+
+    info!("配置更新成功");
+
+    let response = ConfigUpdateResponse {
+        message: "配置已更新".to_string(),
+        path_validation: validation_result,
+    };
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 将新配置动态应用到各运行中组件（无需重启，不影响正在进行的任务）
+///
+/// 调用方需先将 `new_config` 写入 `app_state.config`；`old_config` 仅用于变更检测
+pub(crate) async fn apply_runtime_config(
+    app_state: &crate::server::AppState,
+    old_config: &AppConfig,
+    new_config: &AppConfig,
+) {
+    let proxy_changed = old_config.network.proxy != new_config.network.proxy;
+
+    // 🔧 动态更新日志级别
+    if old_config.log.level != new_config.log.level {
+        match crate::logging::update_log_level(&new_config.log.level) {
+            Ok(()) => info!("✓ 日志级别已动态更新: {}", new_config.log.level),
+            Err(e) => warn!("日志级别更新失败: {}", e),
+        }
+    }
+
+    // 🔧 动态更新日志保留天数（下一次清理或滚动时生效）
+    if old_config.log.retention_days != new_config.log.retention_days {
+        crate::logging::update_log_retention_days(new_config.log.retention_days);
+        info!("✓ 日志保留天数已动态更新: {} 天", new_config.log.retention_days);
+    }
+
+    // 🔧 动态更新全局进度事件上限
+    crate::server::events::ProgressRateLimiter::global()
+        .set_max_events_per_sec(new_config.server.max_events_per_sec);
+
+    // 🔧 更新 User-Agent 覆盖（对之后创建的网盘客户端生效）
+    crate::netdisk::set_user_agent_overrides(
+        new_config.network.mobile_user_agent.clone(),
+        new_config.network.web_user_agent.clone(),
+    );
+
+    // 🔧 更新 HTTP 超时（连接/接口超时对之后创建的客户端生效，分片超时立即生效）
+    crate::common::set_http_timeouts(
+        new_config.network.connect_timeout_secs,
+        new_config.network.request_timeout_secs,
+        new_config.network.chunk_timeout_secs,
+    );
+
+    // 🔧 动态更新 WAL 刷写间隔
+    if old_config.persistence.wal_flush_interval_ms != new_config.persistence.wal_flush_interval_ms {
+        app_state
+            .persistence_manager
+            .lock()
+            .await
+            .update_flush_interval(new_config.persistence.wal_flush_interval_ms);
+    }
+
     // 🔧 动态更新下载管理器配置（无需重启，不影响正在进行的任务）
     let manager_guard = app_state.download_manager.read().await;
     if let Some(manager) = manager_guard.as_ref() {
@@ -215,6 +332,29 @@ pub async fn update_config(
         manager
             .update_download_dir(new_config.download.download_dir.clone())
             .await;
+        // 🔥 更新全局限速和 MD5 校验开关
+        manager.update_global_speed_limit(new_config.download.global_speed_limit_kbps);
+        manager.update_verify_md5(new_config.download.verify_md5_after_download);
+        manager.update_max_task_retries(new_config.download.max_retries);
+        manager.update_rate_limit_config(new_config.download.rate_limit.clone());
+        manager.update_adaptive_concurrency_config(new_config.download.adaptive_concurrency.clone());
+        manager.update_completion_webhook(
+            new_config.download.completion_webhook_url.clone(),
+            new_config.download.completion_webhook_secret.clone(),
+        );
+        manager.update_download_schedule(&new_config.schedule);
+        manager.apply_download_schedule().await;
+        manager.update_min_free_space_mb(new_config.download.min_free_space_mb);
+        manager.update_max_chunks_per_task(new_config.download.max_chunks_per_task);
+        manager.update_dedup_local(new_config.download.dedup_local, new_config.download.dedup_hardlink);
+        manager.update_max_chunks_per_host(new_config.download.max_chunks_per_host);
+        manager.update_max_stall_secs(new_config.download.max_stall_secs);
+        manager
+            .update_type_dirs(new_config.download.type_dirs.clone())
+            .await;
+        manager
+            .update_filesystem_config(new_config.filesystem.clone())
+            .await;
         info!(
             "✓ 下载管理器配置已动态更新: 线程数={}, 最大任务数={}, 下载目录={:?}",
             new_config.download.max_global_threads,
@@ -253,6 +393,15 @@ pub async fn update_config(
     }
     drop(upload_manager_guard);
 
+    // 🔧 动态更新转存管理器配置（并发上限调大时立即放行排队任务）
+    if let Some(transfer_manager) = app_state.transfer_manager.read().await.as_ref() {
+        transfer_manager.update_config(new_config.transfer.clone()).await;
+        info!(
+            "✓ 转存管理器配置已动态更新: 最大同时转存数={}",
+            new_config.transfer.max_concurrent_transfers
+        );
+    }
+
     // 🔧 代理配置热更新（如果代理配置发生变更）
     if proxy_changed {
         let proxy = &new_config.network.proxy;
@@ -393,15 +542,6 @@ pub async fn update_config(
             }
         }
     }
-
-    info!("配置更新成功");
-
-    let response = ConfigUpdateResponse {
-        message: "配置已更新".to_string(),
-        path_validation: validation_result,
-    };
-
-    Ok(Json(ApiResponse::success(response)))
 }
 
 /// 更新最近目录请求
@@ -433,8 +573,8 @@ pub async fn update_recent_dir(
     // 根据类型更新对应的最近目录
     match req.dir_type.as_str() {
         "download" => {
-            config.download.recent_directory = Some(path);
-            info!("已更新下载最近目录: {:?}", config.download.recent_directory);
+            config.download.push_recent_directory(path);
+            info!("已更新下载最近目录: {:?}", config.download.recent_directories.first());
         }
         "upload" => {
             config.upload.recent_directory = Some(path);
@@ -460,6 +600,40 @@ pub async fn update_recent_dir(
     Ok(Json(ApiResponse::success("最近目录已更新".to_string())))
 }
 
+/// GET /api/v1/config/recent-dirs
+/// 获取最近使用的下载目录（最近使用的在前）
+pub async fn get_recent_dirs(
+    State(app_state): State<crate::server::AppState>,
+) -> Json<ApiResponse<Vec<String>>> {
+    let dirs = app_state
+        .config
+        .read()
+        .await
+        .download
+        .recent_directories
+        .iter()
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .collect();
+    Json(ApiResponse::success(dirs))
+}
+
+/// 创建下载任务时记录最近使用的下载目录（目录已在最前时不写配置文件）
+pub(crate) async fn remember_recent_download_dir(
+    app_state: &crate::server::AppState,
+    dir: &std::path::Path,
+) {
+    let mut config = app_state.config.read().await.clone();
+    if !config.download.push_recent_directory(dir.to_path_buf()) {
+        return;
+    }
+
+    if let Err(e) = config.save_to_file("config/app.toml").await {
+        warn!("保存最近下载目录失败: {}", e);
+        return;
+    }
+    app_state.config.write().await.download.recent_directories = config.download.recent_directories;
+}
+
 /// 设置默认下载目录请求
 #[derive(Debug, Deserialize)]
 pub struct SetDefaultDirRequest {
@@ -606,7 +780,7 @@ pub async fn get_transfer_config(
 /// 更新转存配置请求
 #[derive(Debug, Deserialize)]
 pub struct UpdateTransferConfigRequest {
-    /// 默认行为: "transfer_only" 或 "transfer_and_download"
+    /// 默认行为: "transfer_only"、"transfer_and_download" 或 "transfer_download_cleanup"
     pub default_behavior: Option<String>,
     /// 最近使用的网盘目录 fs_id
     pub recent_save_fs_id: Option<u64>,
@@ -624,9 +798,15 @@ pub async fn update_transfer_config(
 
     // 验证 default_behavior
     if let Some(ref behavior) = req.default_behavior {
-        if behavior != "transfer_only" && behavior != "transfer_and_download" {
+        if ![
+            TRANSFER_BEHAVIOR_ONLY,
+            TRANSFER_BEHAVIOR_AND_DOWNLOAD,
+            TRANSFER_BEHAVIOR_DOWNLOAD_CLEANUP,
+        ]
+        .contains(&behavior.as_str())
+        {
             return Err(ApiError::BadRequest(format!(
-                "无效的默认行为: {}，必须是 'transfer_only' 或 'transfer_and_download'",
+                "无效的默认行为: {}，必须是 'transfer_only'、'transfer_and_download' 或 'transfer_download_cleanup'",
                 behavior
             )));
         }
@@ -703,10 +883,10 @@ pub async fn get_proxy_status(
 /// POST /api/v1/proxy/test
 /// 纯无副作用：不修改 AppState，不影响当前代理状态
 pub async fn test_proxy_connection(
-    Json(proxy): Json<crate::common::ProxyConfig>,
+    Json(mut proxy): Json<crate::common::ProxyConfig>,
 ) -> ApiResult<Json<ApiResponse<ProxyTestResponse>>> {
     // 验证配置
-    if let Err(e) = proxy.validate() {
+    if let Err(e) = proxy.resolve_url().and_then(|()| proxy.validate()) {
         return Err(ApiError::BadRequest(format!("代理配置验证失败: {}", e)));
     }
     if proxy.proxy_type == crate::common::ProxyType::None {
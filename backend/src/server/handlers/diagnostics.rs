@@ -0,0 +1,226 @@
+// 诊断 API 处理器
+
+use crate::downloader::speed_test::{SPEED_TEST_DEFAULT_BYTES, SPEED_TEST_MAX_BYTES};
+use crate::downloader::SpeedTestResult;
+use crate::netdisk::{FileItem, NetdiskClient};
+use crate::server::AppState;
+use crate::sign::LocateSign;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use super::ApiResponse;
+
+/// 自动选择测速文件时扫描的根目录文件数
+const SAMPLE_LIST_PAGE_SIZE: u32 = 100;
+
+/// 测速请求（所有字段可选）
+#[derive(Debug, Default, Deserialize)]
+pub struct SpeedTestRequest {
+    /// 测速文件路径（优先使用）
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 测速文件 fs_id（未指定 path 时在网盘根目录中查找）
+    #[serde(default)]
+    pub fs_id: Option<u64>,
+    /// 测速下载量（MB，默认 8，最大 64）
+    #[serde(default)]
+    pub size_mb: Option<u64>,
+}
+
+/// 从文件列表中选择测速文件
+///
+/// 指定 fs_id 时按 fs_id 查找；否则优先选择不小于测速量的最小文件，都不够大时选择最大的文件
+fn pick_sample_file(list: &[FileItem], fs_id: Option<u64>, test_bytes: u64) -> Option<&FileItem> {
+    let mut files = list
+        .iter()
+        .filter(|item| !item.is_directory() && item.size > 0);
+
+    if let Some(fs_id) = fs_id {
+        return files.find(|item| item.fs_id == fs_id);
+    }
+
+    let files: Vec<&FileItem> = files.collect();
+    files
+        .iter()
+        .filter(|item| item.size >= test_bytes)
+        .min_by_key(|item| item.size)
+        .or_else(|| files.iter().max_by_key(|item| item.size))
+        .copied()
+}
+
+/// POST /api/v1/diagnostics/speedtest
+/// 下载测速：下载一小段数据测量当前可达速度，返回 MB/s 和提供数据的 CDN 主机
+pub async fn speed_test(
+    State(app_state): State<AppState>,
+    req: Option<Json<SpeedTestRequest>>,
+) -> Json<ApiResponse<SpeedTestResult>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let test_bytes = req
+        .size_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024))
+        .unwrap_or(SPEED_TEST_DEFAULT_BYTES)
+        .clamp(1, SPEED_TEST_MAX_BYTES);
+
+    let Some(download_manager) = app_state.download_manager.read().await.clone() else {
+        return Json(ApiResponse::error(500, "下载管理器未初始化".to_string()));
+    };
+
+    let remote_path = match req.path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => path,
+        None => {
+            let client = app_state.netdisk_client.read().await.clone();
+            let Some(client) = client else {
+                return Json(ApiResponse::error(401, "未登录或客户端未初始化".to_string()));
+            };
+            let list = match client.get_file_list("/", 1, SAMPLE_LIST_PAGE_SIZE).await {
+                Ok(response) => response.list,
+                Err(e) => {
+                    error!("测速：获取网盘根目录文件列表失败: {:?}", e);
+                    return Json(ApiResponse::error(500, format!("获取文件列表失败: {}", e)));
+                }
+            };
+            match pick_sample_file(&list, req.fs_id, test_bytes) {
+                Some(item) => item.path.clone(),
+                None => {
+                    return Json(ApiResponse::error(
+                        400,
+                        "网盘根目录中未找到可用于测速的文件，请指定 path".to_string(),
+                    ));
+                }
+            }
+        }
+    };
+
+    info!("开始下载测速: {} ({} bytes)", remote_path, test_bytes);
+    match download_manager.speed_test(&remote_path, test_bytes).await {
+        Ok(result) => Json(ApiResponse::success(result)),
+        Err(e) => {
+            error!("下载测速失败: {:?}", e);
+            Json(ApiResponse::error(500, format!("测速失败: {:#}", e)))
+        }
+    }
+}
+
+/// 签名诊断请求参数
+#[derive(Debug, Deserialize)]
+pub struct SignDiagnosticsQuery {
+    /// 网盘文件路径（用于生成 Locate 请求 URL）
+    pub path: String,
+}
+
+/// 签名诊断结果（BDUSS 已脱敏）
+#[derive(Debug, Serialize)]
+pub struct SignDiagnostics {
+    pub uid: u64,
+    /// 脱敏后的 BDUSS（仅保留首尾各 4 位）
+    pub bduss: String,
+    /// 签名使用的设备 UID（会话中持久化的值）
+    pub devuid: String,
+    /// 签名时间戳（秒）
+    pub time: i64,
+    pub rand: String,
+    /// LocateSign::url_params 输出
+    pub url_params: String,
+    /// 完整的 Locate 请求 URL
+    pub locate_url: String,
+}
+
+/// 脱敏凭证：仅保留首尾各 4 位，过短时全部隐藏
+pub(crate) fn redact_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "***".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}***{}", head, tail)
+}
+
+/// GET /api/v1/diagnostics/sign?path=
+/// 签名诊断：返回当前账号生成的 Locate 签名参数，用于排查获取下载链接失败
+///
+/// 需在配置中开启 security.sign_diagnostics，只读模式下不可用
+pub async fn sign_diagnostics(
+    State(app_state): State<AppState>,
+    Query(query): Query<SignDiagnosticsQuery>,
+) -> Result<Json<ApiResponse<SignDiagnostics>>, StatusCode> {
+    if !app_state.config.read().await.security.sign_diagnostics_enabled() {
+        warn!("签名诊断接口未开启，拒绝请求");
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let client = app_state.netdisk_client.read().await.clone();
+    let Some(client) = client else {
+        return Ok(Json(ApiResponse::error(401, "未登录或客户端未初始化".to_string())));
+    };
+
+    let bduss = client.bduss();
+    let sign = LocateSign::with_devuid(client.uid(), bduss, client.user_auth().devuid());
+    let locate_url = NetdiskClient::locate_download_url(&query.path, &sign);
+
+    Ok(Json(ApiResponse::success(SignDiagnostics {
+        uid: client.uid(),
+        bduss: redact_secret(bduss),
+        devuid: sign.devuid.clone(),
+        time: sign.time,
+        rand: sign.rand.clone(),
+        url_params: sign.url_params(),
+        locate_url,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(fs_id: u64, size: u64, isdir: i32) -> FileItem {
+        FileItem {
+            fs_id,
+            path: format!("/file{}", fs_id),
+            server_filename: format!("file{}", fs_id),
+            size,
+            isdir,
+            category: 0,
+            md5: None,
+            server_ctime: 0,
+            server_mtime: 0,
+            local_ctime: 0,
+            local_mtime: 0,
+        }
+    }
+
+    #[test]
+    fn test_pick_sample_file() {
+        let list = vec![file(1, 100, 0), file(2, 0, 1), file(3, 20_000, 0), file(4, 10_000, 0)];
+
+        assert_eq!(pick_sample_file(&list, None, 5_000).unwrap().fs_id, 4);
+        // 都不够大时选择最大的文件
+        assert_eq!(pick_sample_file(&list, None, 50_000).unwrap().fs_id, 3);
+        assert_eq!(pick_sample_file(&list, Some(1), 5_000).unwrap().fs_id, 1);
+        // 目录不能用于测速
+        assert!(pick_sample_file(&list, Some(2), 5_000).is_none());
+        assert!(pick_sample_file(&[], None, 5_000).is_none());
+    }
+
+    #[test]
+    fn test_redact_secret() {
+        assert_eq!(redact_secret("abcdefghijklmnop"), "abcd***mnop");
+        assert_eq!(redact_secret("short"), "***");
+        assert_eq!(redact_secret(""), "***");
+    }
+
+    #[test]
+    fn test_sign_diagnostics_gate() {
+        let mut security = crate::config::SecurityConfig::default();
+        assert!(!security.sign_diagnostics_enabled());
+        security.sign_diagnostics = true;
+        assert!(security.sign_diagnostics_enabled());
+        security.read_only = true;
+        assert!(!security.sign_diagnostics_enabled());
+    }
+}
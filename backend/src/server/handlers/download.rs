@@ -1,4 +1,6 @@
-use crate::downloader::{DownloadConflictStrategy, DownloadTask};
+use crate::downloader::{
+    DownloadConflictStrategy, DownloadPriority, DownloadTask, FlattenCollisionPolicy, SlotDebugInfo,
+};
 use crate::server::AppState;
 use axum::{
     extract::{Path, State},
@@ -14,12 +16,25 @@ use super::ApiResponse;
 #[derive(Debug, Deserialize)]
 pub struct CreateDownloadRequest {
     pub fs_id: u64,
+    /// 网盘路径（可选，未指定时按 fs_id 解析路径、文件名和大小）
+    #[serde(default)]
     pub remote_path: String,
+    #[serde(default)]
     pub filename: String,
+    #[serde(default)]
     pub total_size: u64,
+    /// 网盘文件 MD5（可选，用于下载完成后校验完整性）
+    #[serde(default)]
+    pub md5: Option<String>,
     /// 冲突策略（可选，未指定则使用默认值）
     #[serde(default)]
     pub conflict_strategy: Option<DownloadConflictStrategy>,
+    /// 首选下载链接索引（可选，用于避开有问题的 CDN 节点，未指定则使用全部可用链接）
+    #[serde(default)]
+    pub dlink_prefer: Option<usize>,
+    /// 单任务最大并发分片数（可选，覆盖按文件大小计算的值，超过会员等级上限时按上限处理，过高可能导致限速）
+    #[serde(default)]
+    pub max_chunks_override: Option<usize>,
 }
 
 // ============================================
@@ -41,6 +56,9 @@ pub struct BatchDownloadItem {
     pub size: Option<u64>,
     /// 原始名称（加密文件/文件夹的还原名称）
     pub original_name: Option<String>,
+    /// 文件 MD5（文件列表返回，下载完成后校验）
+    #[serde(default)]
+    pub md5: Option<String>,
 }
 
 /// 批量下载请求
@@ -53,11 +71,19 @@ pub struct CreateBatchDownloadRequest {
     /// 冲突策略（可选，未指定则使用默认值）
     #[serde(default)]
     pub conflict_strategy: Option<DownloadConflictStrategy>,
+    /// 文件夹是否平铺下载（所有文件直接放在本地文件夹下，默认保留目录结构）
+    #[serde(default)]
+    pub flatten: bool,
+    /// 平铺下载时不同子目录下同名文件的处理方式（默认追加序号）
+    #[serde(default)]
+    pub flatten_collision: FlattenCollisionPolicy,
 }
 
 /// 批量下载响应
 #[derive(Debug, Serialize)]
 pub struct BatchDownloadResponse {
+    /// 批次ID（本次创建的单文件任务共享）
+    pub batch_id: String,
     /// 成功创建的单文件任务ID列表
     pub task_ids: Vec<String>,
     /// 成功创建的文件夹任务ID列表
@@ -79,8 +105,16 @@ pub struct BatchDownloadError {
 /// 创建下载任务
 pub async fn create_download(
     State(app_state): State<AppState>,
-    Json(req): Json<CreateDownloadRequest>,
+    Json(mut req): Json<CreateDownloadRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    // 🔥 只指定 fs_id 时按 fs_id 解析文件信息
+    if req.remote_path.trim().is_empty() {
+        if let Err(msg) = resolve_fs_id_target(&app_state, &mut req).await {
+            warn!("按 fs_id 创建下载任务失败: fs_id={}, {}", req.fs_id, msg);
+            return Ok(Json(ApiResponse::error(400, msg)));
+        }
+    }
+
     // 获取下载管理器
     let download_manager = app_state
         .download_manager
@@ -96,7 +130,7 @@ pub async fn create_download(
     });
 
     match download_manager
-        .create_task(req.fs_id, req.remote_path, req.filename, req.total_size, conflict_strategy)
+        .create_task(req.fs_id, req.remote_path, req.filename, req.total_size, req.md5, conflict_strategy)
         .await
     {
         Ok(task_id) => {
@@ -111,6 +145,32 @@ pub async fn create_download(
 
             info!("创建下载任务成功: {}", task_id);
 
+            // 🔥 跳过策略下已有文件一致时任务已直接标记为完成，无需启动
+            if download_manager.is_task_completed(&task_id).await {
+                return Ok(Json(ApiResponse::success_with_message(
+                    task_id,
+                    "文件已存在且内容一致，已标记为完成"
+                )));
+            }
+
+            // 🔥 首选节点需在启动前设置，准备调度时读取
+            if req.dlink_prefer.is_some() {
+                if let Err(e) = download_manager
+                    .set_task_dlink_prefer(&task_id, req.dlink_prefer)
+                    .await
+                {
+                    warn!("设置首选下载链接失败: {:?}", e);
+                }
+            }
+            if req.max_chunks_override.is_some() {
+                if let Err(e) = download_manager
+                    .set_task_max_chunks(&task_id, req.max_chunks_override)
+                    .await
+                {
+                    warn!("设置最大并发分片数失败: {:?}", e);
+                }
+            }
+
             // 自动开始下载
             if let Err(e) = download_manager.start_task(&task_id).await {
                 error!("启动下载任务失败: {:?}", e);
@@ -125,6 +185,40 @@ pub async fn create_download(
     }
 }
 
+/// 按 fs_id 补全下载请求的路径、文件名、大小和 MD5（请求中未指定路径时调用）
+async fn resolve_fs_id_target(app_state: &AppState, req: &mut CreateDownloadRequest) -> Result<(), String> {
+    let client = app_state.netdisk_client.read().await.clone();
+    let Some(client) = client else {
+        return Err("未登录或客户端未初始化".to_string());
+    };
+
+    let response = client
+        .get_locate_download_urls(&[req.fs_id])
+        .await
+        .map_err(|e| format!("获取文件信息失败: {}", e))?;
+    let info = response
+        .list
+        .into_iter()
+        .find(|info| info.fs_id == req.fs_id)
+        .ok_or_else(|| "文件不存在".to_string())?;
+    if info.isdir == 1 {
+        return Err("不支持下载文件夹，请使用文件夹下载".to_string());
+    }
+
+    info!("按 fs_id 解析下载文件: fs_id={}, path={}", req.fs_id, info.path);
+    if req.filename.is_empty() {
+        req.filename = info.path.rsplit('/').next().unwrap_or_default().to_string();
+    }
+    if req.total_size == 0 {
+        req.total_size = info.size;
+    }
+    if req.md5.is_none() {
+        req.md5 = info.md5.filter(|md5| !md5.is_empty());
+    }
+    req.remote_path = info.path;
+    Ok(())
+}
+
 /// GET /api/v1/downloads
 /// 获取所有下载任务
 pub async fn get_all_downloads(
@@ -141,6 +235,102 @@ pub async fn get_all_downloads(
     Ok(Json(ApiResponse::success(tasks)))
 }
 
+/// GET /api/v1/downloads/history?page=&size=&status=&q=
+/// 分页查询下载历史（直接查询历史数据库）
+pub async fn get_download_history(
+    State(app_state): State<AppState>,
+    axum::extract::Query(params): axum::extract::Query<HistoryQueryParams>,
+) -> Result<Json<ApiResponse<HistoryPageResponse<DownloadTask>>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let query = params.to_query();
+    let size = query.limit;
+    let (tasks, total) = download_manager.query_history(query).await;
+    Ok(Json(ApiResponse::success(HistoryPageResponse {
+        total,
+        page: params.page.max(1),
+        size,
+        tasks,
+    })))
+}
+
+/// 等待队列条目
+#[derive(Debug, Serialize)]
+pub struct WaitingQueueItem {
+    /// 队列位置（从 0 开始，越小越先启动）
+    pub position: usize,
+    pub task_id: String,
+    /// 文件名（加密文件优先显示原始文件名）
+    pub filename: String,
+    /// 文件夹下载组ID（单文件任务为 None）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// 文件夹根路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_root: Option<String>,
+    pub priority: DownloadPriority,
+    pub is_backup: bool,
+}
+
+/// GET /api/v1/downloads/queue
+/// 🔥 获取等待队列内容（按启动顺序），用于查看任务为何尚未开始
+pub async fn get_download_queue(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<WaitingQueueItem>>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let items = download_manager
+        .get_waiting_queue()
+        .await
+        .into_iter()
+        .enumerate()
+        .map(|(position, task)| {
+            let filename = task.original_filename.clone().unwrap_or_else(|| {
+                task.local_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| task.remote_path.clone())
+            });
+            WaitingQueueItem {
+                position,
+                task_id: task.id,
+                filename,
+                group_id: task.group_id,
+                group_root: task.group_root,
+                priority: task.priority,
+                is_backup: task.is_backup,
+            }
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(items)))
+}
+
+/// GET /api/v1/downloads/debug/slots
+/// 🔥 获取调度调试信息：各活跃任务的槽位、是否借调位、分片数、速度，以及任务位池占用
+pub async fn get_download_slot_debug(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<SlotDebugInfo>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(download_manager.get_slot_debug_info().await)))
+}
+
 /// GET /api/v1/downloads/active
 /// 🔥 获取活跃的下载任务（用于降级轮询）
 pub async fn get_active_downloads(
@@ -188,6 +378,28 @@ pub async fn get_download(
     }
 }
 
+/// 任务日志查询参数
+#[derive(Debug, Deserialize)]
+pub struct TaskLogsQuery {
+    /// 返回最近多少行（默认 100，最多 TASK_LOG_CAPACITY）
+    #[serde(default = "default_task_log_limit")]
+    pub limit: usize,
+}
+
+fn default_task_log_limit() -> usize {
+    100
+}
+
+/// GET /api/v1/downloads/:id/logs
+/// 获取任务最近的日志（仅内存中保留的部分，重启后清空）
+pub async fn get_download_logs(
+    Path(task_id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<TaskLogsQuery>,
+) -> Json<ApiResponse<Vec<String>>> {
+    let limit = query.limit.min(crate::logging::TASK_LOG_CAPACITY);
+    Json(ApiResponse::success(crate::logging::tail_task_logs(&task_id, limit)))
+}
+
 /// POST /api/v1/downloads/:id/pause
 /// 暂停下载任务
 pub async fn pause_download(
@@ -242,6 +454,180 @@ pub async fn resume_download(
     }
 }
 
+/// 设置任务限速请求
+#[derive(Debug, Deserialize)]
+pub struct SetDownloadLimitRequest {
+    /// 限速值（KB/s），0 或 null 表示不限速
+    #[serde(default)]
+    pub limit_kbps: Option<u64>,
+}
+
+/// POST /api/v1/downloads/:id/limit
+/// 设置下载任务限速（实时生效）
+pub async fn set_download_limit(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<String>,
+    Json(req): Json<SetDownloadLimitRequest>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match download_manager
+        .set_task_speed_limit(&task_id, req.limit_kbps)
+        .await
+    {
+        Ok(_) => Ok(Json(ApiResponse::success("Speed limit updated".to_string()))),
+        Err(e) => {
+            warn!("设置下载任务限速失败: {:?}", e);
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// 设置任务优先级请求
+#[derive(Debug, Deserialize)]
+pub struct SetDownloadPriorityRequest {
+    /// 优先级：high / normal / low
+    pub priority: DownloadPriority,
+}
+
+/// POST /api/v1/downloads/:id/priority
+/// 设置下载任务排队优先级（等待中的任务立即重新排队）
+pub async fn set_download_priority(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<String>,
+    Json(req): Json<SetDownloadPriorityRequest>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match download_manager
+        .set_task_priority(&task_id, req.priority)
+        .await
+    {
+        Ok(_) => Ok(Json(ApiResponse::success("Priority updated".to_string()))),
+        Err(e) => {
+            warn!("设置下载任务优先级失败: {:?}", e);
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// 调整等待队列位置请求
+#[derive(Debug, Deserialize)]
+pub struct ReorderDownloadRequest {
+    /// 目标位置（从 0 开始，超出范围时移到队尾）
+    pub position: usize,
+}
+
+/// POST /api/v1/downloads/:id/reorder
+/// 调整任务在等待队列中的位置，返回调整后的队列顺序（任务ID列表）
+pub async fn reorder_download(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<String>,
+    Json(req): Json<ReorderDownloadRequest>,
+) -> Result<Json<ApiResponse<Vec<String>>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match download_manager.move_in_queue(&task_id, req.position).await {
+        Ok(queue) => Ok(Json(ApiResponse::success(queue))),
+        Err(e) => {
+            warn!("调整等待队列位置失败: {:?}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// 修改下载目录请求
+#[derive(Debug, Deserialize)]
+pub struct MoveDownloadDirRequest {
+    /// 新的本地下载目录
+    pub dir: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoveDownloadDirResponse {
+    /// 修改后的本地保存路径
+    pub local_path: String,
+}
+
+/// POST /api/v1/downloads/:id/move-dir
+/// 修改已暂停/已失败任务的本地目录（下载中的任务需先暂停）
+pub async fn move_download_dir(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<String>,
+    Json(req): Json<MoveDownloadDirRequest>,
+) -> Result<Json<ApiResponse<MoveDownloadDirResponse>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let dir = req.dir.trim();
+    if dir.is_empty() {
+        return Ok(Json(ApiResponse::error(400, "下载目录不能为空".to_string())));
+    }
+
+    match download_manager
+        .update_task_dir(&task_id, std::path::Path::new(dir))
+        .await
+    {
+        Ok(local_path) => Ok(Json(ApiResponse::success(MoveDownloadDirResponse {
+            local_path: local_path.to_string_lossy().to_string(),
+        }))),
+        Err(e) => {
+            warn!("修改下载目录失败: {:?}", e);
+            Ok(Json(ApiResponse::error(400, e.to_string())))
+        }
+    }
+}
+
+/// 刷新下载链接响应
+#[derive(Debug, Serialize)]
+pub struct RefreshDownloadUrlResponse {
+    /// 新增/更新的链接数量
+    pub refreshed_count: usize,
+}
+
+/// POST /api/v1/downloads/:id/refresh-url
+/// 手动刷新下载任务的 CDN 链接
+pub async fn refresh_download_url(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<String>,
+) -> Result<Json<ApiResponse<RefreshDownloadUrlResponse>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match download_manager.refresh_task_url(&task_id).await {
+        Ok(refreshed_count) => Ok(Json(ApiResponse::success(RefreshDownloadUrlResponse {
+            refreshed_count,
+        }))),
+        Err(e) => {
+            warn!("刷新下载链接失败: {:?}", e);
+            Ok(Json(ApiResponse::error(400, e.to_string())))
+        }
+    }
+}
+
 /// DELETE /api/v1/downloads/:id
 /// 删除下载任务
 #[derive(Debug, Deserialize)]
@@ -277,6 +663,41 @@ pub async fn delete_download(
     }
 }
 
+/// DELETE /api/v1/downloads/group/:group_id
+/// 删除同一 group（文件夹）的所有下载任务
+#[derive(Debug, Deserialize)]
+pub struct DeleteGroupQuery {
+    #[serde(default)]
+    pub delete_files: bool,
+}
+
+pub async fn delete_download_group(
+    State(app_state): State<AppState>,
+    Path(group_id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<DeleteGroupQuery>,
+) -> Result<Json<ApiResponse<usize>>, StatusCode> {
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match download_manager
+        .delete_group(&group_id, query.delete_files)
+        .await
+    {
+        Ok(count) => {
+            info!("批量删除 group {} 成功: {} 个任务", group_id, count);
+            Ok(Json(ApiResponse::success(count)))
+        }
+        Err(e) => {
+            error!("批量删除 group {} 失败: {:?}", group_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// DELETE /api/v1/downloads/clear/completed
 /// 清除已完成的任务
 pub async fn clear_completed(
@@ -329,8 +750,20 @@ pub async fn create_batch_download(
         req.target_dir
     );
 
-    // 验证目标目录
+    // 获取下载管理器
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 验证目标目录（先校验 allowed_paths 白名单，通过后才创建目录）
     let target_dir = std::path::PathBuf::from(&req.target_dir);
+    if let Err(e) = download_manager.ensure_download_dir_allowed(&target_dir).await {
+        warn!("批量下载目标目录不在白名单内: {:?}, 错误: {}", target_dir, e);
+        return Ok(Json(ApiResponse::error(400, e.to_string())));
+    }
     if !target_dir.exists() {
         // 尝试创建目录
         if let Err(e) = std::fs::create_dir_all(&target_dir) {
@@ -346,15 +779,11 @@ pub async fn create_batch_download(
         Some(config.conflict_strategy.default_download_strategy)
     });
 
-    // 获取下载管理器
-    let download_manager = app_state
-        .download_manager
-        .read()
-        .await
-        .clone()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
-
     let folder_download_manager = &app_state.folder_download_manager;
+    let flatten = req.flatten.then_some(req.flatten_collision);
+
+    // 本次批量请求创建的单文件任务共享同一批次ID
+    let batch_id = uuid::Uuid::new_v4().to_string();
 
     let mut task_ids = Vec::new();
     let mut folder_task_ids = Vec::new();
@@ -365,7 +794,13 @@ pub async fn create_batch_download(
         if item.is_dir {
             // 文件夹下载
             match folder_download_manager
-                .create_folder_download_with_dir(item.path.clone(), &target_dir, item.original_name.clone(), conflict_strategy)
+                .create_folder_download_with_dir(
+                    item.path.clone(),
+                    &target_dir,
+                    item.original_name.clone(),
+                    conflict_strategy,
+                    flatten,
+                )
                 .await
             {
                 Ok(folder_id) => {
@@ -391,6 +826,8 @@ pub async fn create_batch_download(
                     item.name.clone(),
                     file_size,
                     &target_dir,
+                    item.md5.clone(),
+                    Some(batch_id.clone()),
                     conflict_strategy,
                 )
                 .await
@@ -405,7 +842,13 @@ pub async fn create_batch_download(
 
                     info!("创建下载任务成功: {}, ID: {}", item.path, task_id);
 
-                    // 自动开始下载
+                    // 🔥 已有文件一致时任务已直接标记为完成，无需启动
+                    if download_manager.is_task_completed(&task_id).await {
+                        task_ids.push(task_id);
+                        continue;
+                    }
+
+                    // 自动开始下载（超出最大并发数时进入等待队列）
                     if let Err(e) = download_manager.start_task(&task_id).await {
                         warn!("启动下载任务失败: {:?}", e);
                     }
@@ -424,13 +867,20 @@ pub async fn create_batch_download(
     }
 
     info!(
-        "批量下载完成: {} 个文件任务, {} 个文件夹任务, {} 个失败",
+        "批量下载完成: batch_id={}, {} 个文件任务, {} 个文件夹任务, {} 个失败",
+        batch_id,
         task_ids.len(),
         folder_task_ids.len(),
         failed.len()
     );
 
+    // 🔥 有任务创建成功时记录最近使用的下载目录
+    if !task_ids.is_empty() || !folder_task_ids.is_empty() {
+        super::remember_recent_download_dir(&app_state, &target_dir).await;
+    }
+
     Ok(Json(ApiResponse::success(BatchDownloadResponse {
+        batch_id,
         task_ids,
         folder_task_ids,
         failed,
@@ -439,7 +889,10 @@ pub async fn create_batch_download(
 
 // ==================== 批量操作 ====================
 
-use super::common::{BatchOperationRequest, BatchOperationItem, BatchOperationResponse};
+use super::common::{
+    BatchOperationItem, BatchOperationRequest, BatchOperationResponse, HistoryPageResponse,
+    HistoryQueryParams,
+};
 
 /// POST /api/v1/downloads/batch/pause
 pub async fn batch_pause_downloads(
@@ -483,6 +936,36 @@ pub async fn batch_resume_downloads(
     Ok(Json(ApiResponse::success(BatchOperationResponse::from_results(results))))
 }
 
+/// POST /api/v1/downloads/pause-all
+///
+/// 一键暂停全部下载（单文件任务和文件夹），暂停后等待队列不会自动启动，需调用 resume-all 恢复
+pub async fn pause_all_downloads(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<BatchOperationResponse>>, StatusCode> {
+    let mgr = app_state.download_manager.read().await.clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let results: Vec<BatchOperationItem> = mgr.pause_all().await.into_iter()
+        .map(|(id, ok, err)| BatchOperationItem { task_id: id, success: ok, error: err })
+        .collect();
+    Ok(Json(ApiResponse::success(BatchOperationResponse::from_results(results))))
+}
+
+/// POST /api/v1/downloads/resume-all
+///
+/// 按一键暂停时的原顺序恢复全部下载
+pub async fn resume_all_downloads(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<BatchOperationResponse>>, StatusCode> {
+    let mgr = app_state.download_manager.read().await.clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let results: Vec<BatchOperationItem> = mgr.resume_all().await.into_iter()
+        .map(|(id, ok, err)| BatchOperationItem { task_id: id, success: ok, error: err })
+        .collect();
+    Ok(Json(ApiResponse::success(BatchOperationResponse::from_results(results))))
+}
+
 /// POST /api/v1/downloads/batch/delete
 pub async fn batch_delete_downloads(
     State(app_state): State<AppState>,
@@ -492,6 +975,10 @@ pub async fn batch_delete_downloads(
         .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let delete_files = req.delete_files.unwrap_or(false);
+    // 🔥 只读模式下禁止删除本地文件（delete_files 位于请求体，中间件无法判断）
+    if delete_files && crate::server::read_only::is_read_only(&app_state).await {
+        return Err(StatusCode::FORBIDDEN);
+    }
     let ids = if req.all == Some(true) {
         mgr.get_all_task_ids().await
     } else {
@@ -1,7 +1,11 @@
 // 文件API处理器
 
 use crate::encryption::EncryptionService;
-use crate::netdisk::FileItem;
+use crate::downloader::engine::DOWNLOAD_USER_AGENT;
+use crate::netdisk::{
+    FileItem, FileListOrder, FileTypeFilter, NetdiskClient, NetdiskError, ThumbnailSize,
+};
+use crate::server::handlers::diagnostics::redact_secret;
 use crate::server::handlers::ApiResponse;
 use crate::server::AppState;
 use axum::{
@@ -10,7 +14,7 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{error, info, warn};
 
 /// 文件列表查询参数
@@ -25,8 +29,26 @@ pub struct FileListQuery {
     /// 每页数量
     #[serde(default = "default_page_size")]
     pub page_size: u32,
+    /// 是否获取目录下全部文件（1=是，忽略 page/page_size）
+    #[serde(default)]
+    pub all: u8,
+    /// 获取全部文件时的并发页数（默认 2，最大 4）
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// 排序字段（name|time|size，默认 name）
+    #[serde(default)]
+    pub order: FileListOrder,
+    /// 是否降序（1=是, 0=否）
+    #[serde(default)]
+    pub desc: u8,
+    /// 文件类型筛选（folder|file|image|video|doc，在当前页结果上筛选）
+    #[serde(default, rename = "type")]
+    pub file_type: Option<FileTypeFilter>,
 }
 
+/// 获取全部文件时的默认并发页数
+const DEFAULT_LIST_ALL_CONCURRENCY: usize = 2;
+
 fn default_dir() -> String {
     "/".to_string()
 }
@@ -75,6 +97,8 @@ pub struct FileListData {
 /// 获取文件列表
 ///
 /// GET /api/v1/files?dir=/&page=1&page_size=100
+/// GET /api/v1/files?dir=/&all=1（自动翻页返回全部文件）
+/// GET /api/v1/files?dir=/&order=time&desc=1&type=video
 pub async fn get_file_list(
     State(state): State<AppState>,
     Query(params): Query<FileListQuery>,
@@ -82,8 +106,8 @@ pub async fn get_file_list(
     info!("API: 获取文件列表 dir={}, page={}", params.dir, params.page);
 
     // 使用单例网盘客户端
-    let client_lock = state.netdisk_client.read().await;
-    let client = match client_lock.as_ref() {
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
         Some(c) => c,
         None => {
             return Ok(Json(ApiResponse::error(
@@ -93,26 +117,40 @@ pub async fn get_file_list(
         }
     };
 
+    let list_all = params.all == 1;
+    let desc = params.desc == 1;
+
     // 获取文件列表
-    match client
-        .get_file_list(&params.dir, params.page, params.page_size)
-        .await
-    {
-        Ok(file_list) => {
-            let total = file_list.list.len();
-            let has_more = total >= params.page_size as usize;
+    let result = if list_all {
+        let concurrency = params.concurrency.unwrap_or(DEFAULT_LIST_ALL_CONCURRENCY);
+        client
+            .list_dir_all(&params.dir, params.order, desc, concurrency)
+            .await
+    } else {
+        client
+            .get_file_list_ordered(&params.dir, params.page, params.page_size, params.order, desc)
+            .await
+            .map(|response| response.list)
+    };
+
+    match result {
+        Ok(mut list) => {
+            // 是否还有更多按筛选前的数量判断
+            let has_more = !list_all && list.len() >= params.page_size as usize;
+            if let Some(file_type) = params.file_type {
+                list.retain(|item| file_type.matches(item));
+            }
+            let total = list.len();
 
             // 筛选出加密文件名（UUID.dat 格式）
-            let encrypted_names: Vec<String> = file_list
-                .list
+            let encrypted_names: Vec<String> = list
                 .iter()
                 .filter(|f| is_encrypted_filename(&f.server_filename))
                 .map(|f| f.server_filename.clone())
                 .collect();
 
             // 筛选出加密文件夹名（纯 UUID 格式）
-            let encrypted_folder_names: Vec<String> = file_list
-                .list
+            let encrypted_folder_names: Vec<String> = list
                 .iter()
                 .filter(|f| f.isdir == 1 && is_encrypted_folder_name(&f.server_filename))
                 .map(|f| f.server_filename.clone())
@@ -125,8 +163,7 @@ pub async fn get_file_list(
             let folder_map = query_folder_mappings(&state, &params.dir, &encrypted_folder_names);
 
             // 构建带加密信息的文件列表
-            let list_with_encryption: Vec<FileItemWithEncryption> = file_list
-                .list
+            let list_with_encryption: Vec<FileItemWithEncryption> = list
                 .into_iter()
                 .map(|file| {
                     // 检查是否为加密文件夹
@@ -171,7 +208,7 @@ pub async fn get_file_list(
             let data = FileListData {
                 list: list_with_encryption,
                 dir: params.dir.clone(),
-                page: params.page,
+                page: if list_all { 1 } else { params.page },
                 total,
                 has_more,
             };
@@ -289,8 +326,8 @@ pub async fn get_download_url(
     );
 
     // 使用单例网盘客户端
-    let client_lock = state.netdisk_client.read().await;
-    let client = match client_lock.as_ref() {
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
         Some(c) => c,
         None => {
             return Ok(Json(ApiResponse::error(
@@ -321,6 +358,199 @@ pub async fn get_download_url(
     }
 }
 
+/// 直链查询参数
+#[derive(Debug, Deserialize)]
+pub struct DirectUrlQuery {
+    /// 文件路径（与 fs_id 至少指定一个）
+    #[serde(default)]
+    pub path: String,
+    /// 文件服务器ID（指定时优先按 fs_id 获取，失败时回退到路径）
+    #[serde(default)]
+    pub fs_id: Option<u64>,
+    /// 首选链接索引（默认 0，超出范围时使用最后一个）
+    #[serde(default)]
+    pub prefer: usize,
+    /// 返回完整的登录凭证（默认 false，Cookie 中的 BDUSS 会被脱敏）
+    #[serde(default)]
+    pub include_credentials: bool,
+}
+
+/// 直链响应
+#[derive(Debug, Serialize)]
+pub struct DirectUrlData {
+    /// CDN 直链
+    pub url: String,
+    /// 实际选中的链接索引
+    pub url_index: usize,
+    /// 可用链接总数
+    pub url_count: usize,
+    /// 使用直链时必须携带的请求头（未指定 include_credentials 时 BDUSS 已脱敏）
+    pub headers: BTreeMap<String, String>,
+    /// 提示信息
+    pub warning: String,
+}
+
+/// 直链有效期提示
+const DIRECT_URL_WARNING: &str =
+    "直链有效期较短（通常数小时），过期后请重新获取；请求头中包含登录凭证，请勿分享";
+
+/// 请求头中的 BDUSS 已脱敏时附加的提示
+const REDACTED_CREDENTIALS_HINT: &str =
+    "请求头中的 BDUSS 已脱敏，需要可直接使用的请求头时请指定 include_credentials=true";
+
+/// 外部下载器使用直链时必须携带的请求头
+///
+/// 登录凭证默认脱敏，仅在调用方显式指定 `include_credentials` 时返回完整 BDUSS
+pub(crate) fn direct_download_headers(
+    bduss: &str,
+    include_credentials: bool,
+) -> BTreeMap<String, String> {
+    let bduss = if include_credentials {
+        bduss.to_string()
+    } else {
+        redact_secret(bduss)
+    };
+    BTreeMap::from([
+        ("Cookie".to_string(), format!("BDUSS={}", bduss)),
+        ("User-Agent".to_string(), DOWNLOAD_USER_AGENT.to_string()),
+    ])
+}
+
+/// 直链提示信息（凭证已脱敏时附加说明）
+pub(crate) fn direct_url_warning(include_credentials: bool) -> String {
+    if include_credentials {
+        DIRECT_URL_WARNING.to_string()
+    } else {
+        format!("{}；{}", DIRECT_URL_WARNING, REDACTED_CREDENTIALS_HINT)
+    }
+}
+
+/// 获取文件直链（不创建下载任务），供 aria2、浏览器等外部下载器使用
+///
+/// GET /api/v1/files/direct-url?path=/apps/test/file.zip&prefer=0
+/// GET /api/v1/files/direct-url?fs_id=123456&prefer=0&include_credentials=true
+///
+/// 返回的 Cookie 默认脱敏，指定 include_credentials=true 时才包含完整 BDUSS
+pub async fn get_direct_url(
+    State(state): State<AppState>,
+    Query(params): Query<DirectUrlQuery>,
+) -> Result<Json<ApiResponse<DirectUrlData>>, StatusCode> {
+    info!(
+        "API: 获取直链 path={}, fs_id={:?}, prefer={}",
+        params.path, params.fs_id, params.prefer
+    );
+
+    if params.path.is_empty() && params.fs_id.is_none() {
+        return Ok(Json(ApiResponse::error(
+            400,
+            "请指定文件路径或 fs_id".to_string(),
+        )));
+    }
+
+    let client = state.netdisk_client.read().await.clone();
+    let Some(client) = client else {
+        return Ok(Json(ApiResponse::error(
+            401,
+            "未登录或客户端未初始化".to_string(),
+        )));
+    };
+
+    let result = match params.fs_id {
+        Some(fs_id) => {
+            let path = Some(params.path.as_str()).filter(|path| !path.is_empty());
+            client
+                .get_locate_download_url_by_fs_id(fs_id, path)
+                .await
+                .map(|(_, urls)| urls)
+        }
+        None => client.get_locate_download_url(&params.path).await,
+    };
+    let urls = match result {
+        Ok(urls) if !urls.is_empty() => urls,
+        Ok(_) => {
+            return Ok(Json(ApiResponse::error(
+                404,
+                "未找到可用的下载链接".to_string(),
+            )));
+        }
+        Err(e) => {
+            error!("获取直链失败: {}", e);
+            return Ok(Json(ApiResponse::error(
+                500,
+                format!("获取下载链接失败: {}", e),
+            )));
+        }
+    };
+
+    let url_index = NetdiskClient::select_dlink_index(&urls, params.prefer);
+    let headers = direct_download_headers(client.bduss(), params.include_credentials);
+
+    Ok(Json(ApiResponse::success(DirectUrlData {
+        url: urls[url_index].clone(),
+        url_index,
+        url_count: urls.len(),
+        headers,
+        warning: direct_url_warning(params.include_credentials),
+    })))
+}
+
+/// 缩略图查询参数
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailQuery {
+    /// 文件服务器ID
+    pub fs_id: u64,
+    /// 缩略图尺寸（icon/small/medium/large，默认 medium）
+    #[serde(default)]
+    pub size: ThumbnailSize,
+}
+
+/// 缩略图响应
+#[derive(Debug, Serialize)]
+pub struct ThumbnailData {
+    /// 文件服务器ID
+    pub fs_id: u64,
+    /// 缩略图尺寸
+    pub size: ThumbnailSize,
+    /// 缩略图URL（带签名，短时间内有效）
+    pub url: String,
+}
+
+/// 获取图片/视频缩略图链接
+///
+/// GET /api/v1/files/thumbnail?fs_id=123456&size=medium
+///
+/// 只查询文件元信息，不获取下载链接，不计入下载次数
+pub async fn get_thumbnail(
+    State(state): State<AppState>,
+    Query(params): Query<ThumbnailQuery>,
+) -> Result<Json<ApiResponse<ThumbnailData>>, StatusCode> {
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    match client.get_thumbnail_url(params.fs_id, params.size).await {
+        Ok(url) => Ok(Json(ApiResponse::success(ThumbnailData {
+            fs_id: params.fs_id,
+            size: params.size,
+            url,
+        }))),
+        Err(e) => {
+            warn!("获取缩略图失败: fs_id={}, 错误={}", params.fs_id, e);
+            Ok(Json(ApiResponse::error(
+                500,
+                format!("获取缩略图失败: {}", e),
+            )))
+        }
+    }
+}
+
 /// 创建文件夹请求体
 #[derive(Debug, Deserialize)]
 pub struct CreateFolderRequest {
@@ -358,8 +588,8 @@ pub async fn create_folder(
     }
 
     // 使用单例网盘客户端
-    let client_lock = state.netdisk_client.read().await;
-    let client = match client_lock.as_ref() {
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
         Some(c) => c,
         None => {
             return Ok(Json(ApiResponse::error(
@@ -381,19 +611,17 @@ pub async fn create_folder(
             Ok(Json(ApiResponse::success(data)))
         }
         Err(e) => {
-            let error_msg = e.to_string();
-
-            // 检查是否是 errno=-6，需要预热重试
-            if error_msg.contains("errno=-6") {
-                warn!("创建文件夹遇到 errno=-6，触发预热重试...");
+            // 检查是否是登录态失效（errno=-6 等），需要预热重试
+            if NetdiskError::find(&e) == Some(NetdiskError::AuthExpired) {
+                warn!("创建文件夹遇到登录态失效，触发预热重试: {}", e);
 
                 // 触发预热
                 match state.trigger_warmup().await {
                     Ok(true) => {
                         info!("预热成功，重试创建文件夹...");
                         // 重新获取客户端（预热后可能更新了）
-                        let client = state.netdisk_client.read().await;
-                        if let Some(ref c) = *client {
+                        let client = state.netdisk_client.read().await.clone();
+                        if let Some(ref c) = client {
                             match c.create_folder(&request.path).await {
                                 Ok(response) => {
                                     let data = CreateFolderData {
@@ -431,3 +659,484 @@ pub async fn create_folder(
         }
     }
 }
+
+/// 删除文件请求
+#[derive(Debug, Deserialize)]
+pub struct DeleteFilesRequest {
+    /// 要删除的文件/目录路径列表
+    pub paths: Vec<String>,
+}
+
+/// 单个路径的删除结果
+#[derive(Debug, Serialize)]
+pub struct DeletePathResult {
+    pub path: String,
+    pub success: bool,
+}
+
+/// 删除文件响应
+#[derive(Debug, Serialize)]
+pub struct DeleteFilesData {
+    /// 成功删除的数量
+    pub deleted_count: usize,
+    /// 删除失败的路径列表
+    pub failed_paths: Vec<String>,
+    /// 每个路径的删除结果
+    pub results: Vec<DeletePathResult>,
+}
+
+/// 删除网盘文件
+///
+/// DELETE /api/v1/files
+/// Body: { "paths": ["/a.txt"] }
+pub async fn delete_files(
+    State(state): State<AppState>,
+    Json(request): Json<DeleteFilesRequest>,
+) -> Result<Json<ApiResponse<DeleteFilesData>>, StatusCode> {
+    info!("API: 删除文件 paths={:?}", request.paths);
+
+    if request.paths.is_empty() {
+        return Ok(Json(ApiResponse::error(400, "路径列表不能为空".to_string())));
+    }
+
+    if let Some(invalid) = request.paths.iter().find(|p| !p.starts_with('/')) {
+        return Ok(Json(ApiResponse::error(
+            400,
+            format!("路径必须以 / 开头: {}", invalid),
+        )));
+    }
+
+    // 使用单例网盘客户端
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    match client.delete_files(&request.paths).await {
+        Ok(response) => {
+            // 整体失败（非部分失败）直接返回错误
+            if !response.success && response.failed_paths.is_empty() {
+                let error_msg = response.error.unwrap_or_else(|| "删除失败".to_string());
+                error!("删除文件失败: {}", error_msg);
+                return Ok(Json(ApiResponse::error(
+                    500,
+                    format!("删除文件失败: {}", error_msg),
+                )));
+            }
+
+            let results = request
+                .paths
+                .iter()
+                .map(|path| DeletePathResult {
+                    path: path.clone(),
+                    success: !response.failed_paths.contains(path),
+                })
+                .collect();
+
+            info!(
+                "删除文件完成: 成功={}, 失败={}",
+                response.deleted_count,
+                response.failed_paths.len()
+            );
+
+            Ok(Json(ApiResponse::success(DeleteFilesData {
+                deleted_count: response.deleted_count,
+                failed_paths: response.failed_paths,
+                results,
+            })))
+        }
+        Err(e) => {
+            error!("删除文件失败: {}", e);
+            Ok(Json(ApiResponse::error(
+                500,
+                format!("删除文件失败: {}", e),
+            )))
+        }
+    }
+}
+
+/// 重命名文件请求
+#[derive(Debug, Deserialize)]
+pub struct RenameFileRequest {
+    /// 原文件完整路径
+    pub path: String,
+    /// 新文件名（不含路径）
+    pub new_name: String,
+}
+
+/// 移动/复制文件请求
+#[derive(Debug, Deserialize)]
+pub struct TransferFilesRequest {
+    /// 源文件完整路径列表
+    pub sources: Vec<String>,
+    /// 目标目录
+    pub dest_dir: String,
+}
+
+/// 文件管理操作响应（重命名/移动/复制）
+#[derive(Debug, Serialize)]
+pub struct FileOperationData {
+    /// 成功处理的数量
+    pub success_count: usize,
+    /// 操作失败的路径列表
+    pub failed_paths: Vec<String>,
+    /// 异步任务ID（大批量操作时存在，已等待完成）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<u64>,
+}
+
+/// 重命名网盘文件
+///
+/// POST /api/v1/files/rename
+/// Body: { "path": "/a.txt", "new_name": "b.txt" }
+pub async fn rename_file(
+    State(state): State<AppState>,
+    Json(request): Json<RenameFileRequest>,
+) -> Result<Json<ApiResponse<FileOperationData>>, StatusCode> {
+    info!("API: 重命名文件 path={}, new_name={}", request.path, request.new_name);
+
+    if !request.path.starts_with('/') {
+        return Ok(Json(ApiResponse::error(
+            400,
+            "路径必须以 / 开头".to_string(),
+        )));
+    }
+
+    let new_name = request.new_name.trim();
+    if new_name.is_empty() || new_name.contains('/') {
+        return Ok(Json(ApiResponse::error(
+            400,
+            "新文件名不能为空且不能包含 /".to_string(),
+        )));
+    }
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    let result = client.rename_file(&request.path, new_name).await;
+    Ok(Json(file_operation_result("重命名", result)))
+}
+
+/// 移动网盘文件
+///
+/// POST /api/v1/files/move
+/// Body: { "sources": ["/a.txt"], "dest_dir": "/dir" }
+pub async fn move_files(
+    State(state): State<AppState>,
+    Json(request): Json<TransferFilesRequest>,
+) -> Result<Json<ApiResponse<FileOperationData>>, StatusCode> {
+    info!("API: 移动文件 sources={:?}, dest_dir={}", request.sources, request.dest_dir);
+
+    if let Some(msg) = validate_transfer_request(&request) {
+        return Ok(Json(ApiResponse::error(400, msg)));
+    }
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    let result = client.move_files(&request.sources, &request.dest_dir).await;
+    Ok(Json(file_operation_result("移动", result)))
+}
+
+/// 复制网盘文件
+///
+/// POST /api/v1/files/copy
+/// Body: { "sources": ["/a.txt"], "dest_dir": "/dir" }
+pub async fn copy_files(
+    State(state): State<AppState>,
+    Json(request): Json<TransferFilesRequest>,
+) -> Result<Json<ApiResponse<FileOperationData>>, StatusCode> {
+    info!("API: 复制文件 sources={:?}, dest_dir={}", request.sources, request.dest_dir);
+
+    if let Some(msg) = validate_transfer_request(&request) {
+        return Ok(Json(ApiResponse::error(400, msg)));
+    }
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    let result = client.copy_files(&request.sources, &request.dest_dir).await;
+    Ok(Json(file_operation_result("复制", result)))
+}
+
+/// 校验移动/复制请求参数，返回错误信息
+fn validate_transfer_request(request: &TransferFilesRequest) -> Option<String> {
+    if request.sources.is_empty() {
+        return Some("源路径列表不能为空".to_string());
+    }
+    if !request.dest_dir.starts_with('/') {
+        return Some("目标目录必须以 / 开头".to_string());
+    }
+    if let Some(invalid) = request.sources.iter().find(|p| !p.starts_with('/')) {
+        return Some(format!("路径必须以 / 开头: {}", invalid));
+    }
+    None
+}
+
+/// 将文件管理操作结果转换为 API 响应
+fn file_operation_result(
+    op_name: &str,
+    result: anyhow::Result<crate::netdisk::FileOperationResponse>,
+) -> ApiResponse<FileOperationData> {
+    match result {
+        // 整体失败（非部分失败）直接返回错误
+        Ok(response) if !response.success && response.failed_paths.is_empty() => {
+            let error_msg = response.error.unwrap_or_else(|| format!("{}失败", op_name));
+            error!("{}文件失败: {}", op_name, error_msg);
+            ApiResponse::error(500, format!("{}文件失败: {}", op_name, error_msg))
+        }
+        Ok(response) => {
+            info!(
+                "{}文件完成: 成功={}, 失败={}",
+                op_name,
+                response.success_count,
+                response.failed_paths.len()
+            );
+            ApiResponse::success(FileOperationData {
+                success_count: response.success_count,
+                failed_paths: response.failed_paths,
+                task_id: response.task_id,
+            })
+        }
+        Err(e) => {
+            error!("{}文件失败: {}", op_name, e);
+            ApiResponse::error(500, format!("{}文件失败: {}", op_name, e))
+        }
+    }
+}
+
+/// 回收站列表查询参数
+#[derive(Debug, Deserialize)]
+pub struct RecycleListQuery {
+    /// 页码（从1开始）
+    #[serde(default = "default_page")]
+    pub page: u32,
+    /// 每页数量
+    #[serde(default = "default_recycle_num")]
+    pub num: u32,
+}
+
+fn default_recycle_num() -> u32 {
+    100
+}
+
+/// 回收站列表响应
+#[derive(Debug, Serialize)]
+pub struct RecycleListData {
+    /// 回收站文件列表（path 为删除前的原始路径）
+    pub list: Vec<crate::netdisk::RecycleItem>,
+    /// 当前页码
+    pub page: u32,
+    /// 是否还有更多
+    pub has_more: bool,
+}
+
+/// 还原回收站文件请求
+#[derive(Debug, Deserialize)]
+pub struct RestoreRecycleRequest {
+    /// 回收站中文件的 fs_id 列表
+    pub fs_ids: Vec<u64>,
+}
+
+/// 获取回收站列表
+///
+/// GET /api/v1/recycle?page=1&num=100
+pub async fn list_recycle_bin(
+    State(state): State<AppState>,
+    Query(params): Query<RecycleListQuery>,
+) -> Result<Json<ApiResponse<RecycleListData>>, StatusCode> {
+    info!("API: 获取回收站列表 page={}, num={}", params.page, params.num);
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    match client.list_recycle_bin(params.page, params.num).await {
+        Ok(list) => {
+            let has_more = list.len() as u32 >= params.num;
+            Ok(Json(ApiResponse::success(RecycleListData {
+                list,
+                page: params.page,
+                has_more,
+            })))
+        }
+        Err(e) => {
+            error!("获取回收站列表失败: {}", e);
+            Ok(Json(ApiResponse::error(
+                500,
+                format!("获取回收站列表失败: {}", e),
+            )))
+        }
+    }
+}
+
+/// 从回收站还原文件
+///
+/// POST /api/v1/recycle/restore
+/// Body: { "fs_ids": [123] }
+pub async fn restore_recycle_files(
+    State(state): State<AppState>,
+    Json(request): Json<RestoreRecycleRequest>,
+) -> Result<Json<ApiResponse<FileOperationData>>, StatusCode> {
+    info!("API: 还原回收站文件 fs_ids={:?}", request.fs_ids);
+
+    if request.fs_ids.is_empty() {
+        return Ok(Json(ApiResponse::error(400, "fs_id 列表不能为空".to_string())));
+    }
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    let result = client.restore_from_recycle(&request.fs_ids).await;
+    Ok(Json(file_operation_result("还原", result)))
+}
+
+/// 清空回收站
+///
+/// DELETE /api/v1/recycle
+pub async fn clean_recycle_bin(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    info!("API: 清空回收站");
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    match client.clean_recycle_bin().await {
+        Ok(()) => Ok(Json(ApiResponse::success(()))),
+        Err(e) => {
+            error!("清空回收站失败: {}", e);
+            Ok(Json(ApiResponse::error(
+                500,
+                format!("清空回收站失败: {}", e),
+            )))
+        }
+    }
+}
+
+/// 文件搜索查询参数
+#[derive(Debug, Deserialize)]
+pub struct SearchFilesQuery {
+    /// 搜索关键词
+    pub key: String,
+    /// 搜索目录
+    #[serde(default = "default_dir")]
+    pub dir: String,
+    /// 是否递归搜索子目录（1=是, 0=否）
+    #[serde(default)]
+    pub recursive: u8,
+    /// 起始页码
+    #[serde(default = "default_page")]
+    pub page: u32,
+}
+
+/// 文件搜索响应
+#[derive(Debug, Serialize)]
+pub struct SearchFilesData {
+    /// 匹配的文件列表
+    pub list: Vec<FileItem>,
+    /// 搜索关键词
+    pub key: String,
+    /// 搜索目录
+    pub dir: String,
+}
+
+/// 搜索网盘文件
+///
+/// GET /api/v1/files/search?key=xxx&dir=/&recursive=1
+pub async fn search_files(
+    State(state): State<AppState>,
+    Query(params): Query<SearchFilesQuery>,
+) -> Result<Json<ApiResponse<SearchFilesData>>, StatusCode> {
+    info!(
+        "API: 搜索文件 key={}, dir={}, recursive={}",
+        params.key, params.dir, params.recursive
+    );
+
+    let key = params.key.trim();
+    if key.is_empty() {
+        return Ok(Json(ApiResponse::error(400, "搜索关键词不能为空".to_string())));
+    }
+
+    let client_opt = state.netdisk_client.read().await.clone();
+    let client = match client_opt.as_ref() {
+        Some(c) => c,
+        None => {
+            return Ok(Json(ApiResponse::error(
+                401,
+                "未登录或客户端未初始化".to_string(),
+            )));
+        }
+    };
+
+    match client
+        .search_files(key, &params.dir, params.recursive == 1, params.page)
+        .await
+    {
+        Ok(list) => Ok(Json(ApiResponse::success(SearchFilesData {
+            list,
+            key: key.to_string(),
+            dir: params.dir,
+        }))),
+        Err(e) => {
+            error!("搜索文件失败: {}", e);
+            Ok(Json(ApiResponse::error(
+                500,
+                format!("搜索文件失败: {}", e),
+            )))
+        }
+    }
+}
@@ -9,7 +9,7 @@ use axum::{
 use serde::Serialize;
 
 use crate::filesystem::{
-    FilesystemConfig, FilesystemService, FsError, FsErrorCode, GotoRequest,
+    DrivesResponse, FilesystemConfig, FilesystemService, FsError, FsErrorCode, GotoRequest,
     GotoResponse, ListRequest, ListResponse, RootsResponse, ValidateRequest, ValidateResponse,
 };
 use crate::server::state::AppState;
@@ -98,3 +98,13 @@ pub async fn get_roots(
     let response = service.get_roots_with_default()?;
     Ok(Json(ApiResponse::success(response)))
 }
+
+/// GET /api/v1/fs/drives
+/// 获取驱动器/挂载点列表及总容量、可用空间
+pub async fn get_drives(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<DrivesResponse>>, FsError> {
+    let service = create_fs_service(app_state.config.read().await.filesystem.clone());
+    let response = service.get_drives()?;
+    Ok(Json(ApiResponse::success(response)))
+}
@@ -1,6 +1,8 @@
 //! 文件夹下载 API 处理器
 
-use crate::downloader::{DownloadConflictStrategy, DownloadTask, FolderDownload, TaskStatus};
+use crate::downloader::{
+    DownloadConflictStrategy, DownloadTask, FlattenCollisionPolicy, FolderDownload, TaskStatus,
+};
 use crate::server::AppState;
 use axum::{
     extract::{Path, Query, State},
@@ -22,6 +24,12 @@ pub struct CreateFolderDownloadRequest {
     /// 冲突策略（可选，未指定则使用默认值）
     #[serde(default)]
     pub conflict_strategy: Option<DownloadConflictStrategy>,
+    /// 是否平铺下载（所有文件直接放在本地文件夹下，默认保留目录结构）
+    #[serde(default)]
+    pub flatten: bool,
+    /// 平铺下载时不同子目录下同名文件的处理方式（默认追加序号）
+    #[serde(default)]
+    pub flatten_collision: FlattenCollisionPolicy,
 }
 
 /// 删除文件夹下载请求参数
@@ -49,6 +57,70 @@ pub enum DownloadItem {
     },
 }
 
+/// 文件夹内单个文件的下载进度
+#[derive(Debug, Serialize, Clone)]
+pub struct FolderFileProgress {
+    /// 子任务ID
+    pub task_id: String,
+    /// 文件名
+    pub file_name: String,
+    /// 相对于文件夹根目录的路径
+    pub relative_path: String,
+    pub status: TaskStatus,
+    pub downloaded_size: u64,
+    pub total_size: u64,
+    /// 下载速度 (bytes/s)
+    pub speed: u64,
+    pub error: Option<String>,
+}
+
+impl FolderFileProgress {
+    fn from_task(task: &DownloadTask) -> Self {
+        let file_name = task
+            .remote_path
+            .rsplit('/')
+            .next()
+            .unwrap_or(&task.remote_path)
+            .to_string();
+        let relative_path = task
+            .relative_path
+            .clone()
+            .unwrap_or_else(|| file_name.clone());
+
+        Self {
+            task_id: task.id.clone(),
+            file_name,
+            relative_path,
+            status: task.status.clone(),
+            downloaded_size: task.downloaded_size,
+            total_size: task.total_size,
+            speed: task.speed,
+            error: task.error.clone(),
+        }
+    }
+}
+
+/// 文件列表中的状态排序：进行中的排在前面，已完成的排在最后
+fn file_status_rank(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Downloading => 0,
+        TaskStatus::Decrypting => 1,
+        TaskStatus::Pending => 2,
+        TaskStatus::Paused => 3,
+        TaskStatus::Failed => 4,
+        TaskStatus::Completed => 5,
+    }
+}
+
+/// 按状态、再按相对路径排序
+fn sort_folder_files(files: &mut [FolderFileProgress]) {
+    files.sort_by(|a, b| {
+        file_status_rank(&a.status)
+            .cmp(&file_status_rank(&b.status))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+}
+
 impl DownloadItem {
     fn created_at(&self) -> i64 {
         match self {
@@ -64,7 +136,12 @@ pub async fn create_folder_download(
     State(app_state): State<AppState>,
     Json(req): Json<CreateFolderDownloadRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    info!("创建文件夹下载: {}, original_name: {:?}", req.path, req.original_name);
+    // 未启用平铺时保留目录结构
+    let flatten = req.flatten.then_some(req.flatten_collision);
+    info!(
+        "创建文件夹下载: {}, original_name: {:?}, flatten: {:?}",
+        req.path, req.original_name, flatten
+    );
 
     // 如果未指定策略，从 AppConfig 读取默认值
     let conflict_strategy = req.conflict_strategy.or_else(|| {
@@ -74,7 +151,7 @@ pub async fn create_folder_download(
 
     match app_state
         .folder_download_manager
-        .create_folder_download_with_name(req.path, req.original_name, conflict_strategy)
+        .create_folder_download_with_name(req.path, req.original_name, conflict_strategy, flatten)
         .await
     {
         Ok(folder_id) => Ok(Json(ApiResponse::success(folder_id))),
@@ -110,6 +187,39 @@ pub async fn get_folder_download(
     }
 }
 
+/// GET /api/v1/downloads/folder/:id/files
+/// 获取文件夹内各文件的下载进度（仅包含内存中的子任务，已完成并清理的子任务不在其中）
+pub async fn get_folder_download_files(
+    State(app_state): State<AppState>,
+    Path(folder_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<FolderFileProgress>>>, StatusCode> {
+    if app_state
+        .folder_download_manager
+        .get_folder(&folder_id)
+        .await
+        .is_none()
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let download_manager = app_state
+        .download_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut files: Vec<FolderFileProgress> = download_manager
+        .get_tasks_by_group(&folder_id)
+        .await
+        .iter()
+        .map(FolderFileProgress::from_task)
+        .collect();
+    sort_folder_files(&mut files);
+
+    Ok(Json(ApiResponse::success(files)))
+}
+
 /// GET /api/v1/downloads/all
 /// 获取所有下载（文件+文件夹混合，按创建时间排序）
 pub async fn get_all_downloads_mixed(
@@ -295,4 +405,33 @@ mod tests {
         assert_eq!(folder.completed_count, 6);
         assert_eq!(folder.completed_downloaded_size, 6_000);
     }
+
+    #[test]
+    fn folder_files_sorted_by_status_then_path() {
+        let make = |remote: &str, relative: &str, status: TaskStatus| {
+            let mut task = DownloadTask::new_with_group(
+                1,
+                remote.to_string(),
+                PathBuf::from("/tmp").join(relative),
+                100,
+                "group".to_string(),
+                "/root".to_string(),
+                relative.to_string(),
+            );
+            task.status = status;
+            FolderFileProgress::from_task(&task)
+        };
+
+        let mut files = vec![
+            make("/root/b.txt", "b.txt", TaskStatus::Completed),
+            make("/root/sub/c.txt", "sub/c.txt", TaskStatus::Pending),
+            make("/root/a.txt", "a.txt", TaskStatus::Pending),
+            make("/root/d.txt", "d.txt", TaskStatus::Downloading),
+        ];
+        sort_folder_files(&mut files);
+
+        let order: Vec<&str> = files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(order, vec!["d.txt", "a.txt", "sub/c.txt", "b.txt"]);
+        assert_eq!(files[2].file_name, "c.txt");
+    }
 }
@@ -0,0 +1,109 @@
+// 就绪检查 API 处理器
+
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+use crate::server::AppState;
+use axum::{extract::State, http::StatusCode, response::Json};
+use serde::Serialize;
+
+use super::config::validate_config_values;
+
+/// 各组件就绪状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadinessComponents {
+    /// 启动时会话加载及全局服务启动已完成
+    pub session_loaded: bool,
+    /// 是否已登录（仅供参考，未登录不影响就绪）
+    pub logged_in: bool,
+    /// 持久化后台刷写任务在运行
+    pub persistence_started: bool,
+    /// 当前配置通过校验
+    pub config_valid: bool,
+    /// 下载目录可写
+    pub download_dir_writable: bool,
+}
+
+impl ReadinessComponents {
+    /// 是否就绪（登录状态不参与判断，未登录时仍需能访问前端完成登录）
+    pub fn is_ready(&self) -> bool {
+        self.session_loaded
+            && self.persistence_started
+            && self.config_valid
+            && self.download_dir_writable
+    }
+}
+
+/// 就绪检查响应
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub components: ReadinessComponents,
+}
+
+/// 写入测试文件检查目录是否可写
+async fn is_dir_writable(dir: &Path) -> bool {
+    let test_file = dir.join(".baidu_ready_test");
+    match tokio::fs::write(&test_file, b"ready").await {
+        Ok(_) => {
+            let _ = tokio::fs::remove_file(&test_file).await;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// GET /ready
+/// 就绪检查（与存活检查 /health 区分），未就绪时返回 503
+pub async fn get_readiness(
+    State(app_state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let config = app_state.config.read().await.clone();
+
+    let components = ReadinessComponents {
+        session_loaded: app_state.session_loaded.load(Ordering::SeqCst),
+        logged_in: app_state.current_user.read().await.is_some(),
+        persistence_started: app_state.persistence_manager.lock().await.is_running(),
+        config_valid: validate_config_values(&config).is_ok(),
+        download_dir_writable: is_dir_writable(&config.download.download_dir).await,
+    };
+
+    let ready = components.is_ready();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(ReadinessResponse { ready, components }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_ignores_login() {
+        let mut components = ReadinessComponents {
+            session_loaded: true,
+            logged_in: false,
+            persistence_started: true,
+            config_valid: true,
+            download_dir_writable: true,
+        };
+        assert!(components.is_ready());
+
+        components.download_dir_writable = false;
+        assert!(!components.is_ready());
+
+        assert!(!ReadinessComponents::default().is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_is_dir_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(is_dir_writable(dir.path()).await);
+        assert!(!dir.path().join(".baidu_ready_test").exists());
+        assert!(!is_dir_writable(&dir.path().join("missing")).await);
+    }
+}
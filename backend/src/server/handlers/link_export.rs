@@ -0,0 +1,302 @@
+//! 下载链接导出 API 处理器
+//!
+//! 将选中文件的直链导出给外部下载工具：
+//! - POST /api/v1/export/aria2: aria2 输入文件（`aria2c -i`），每个链接附带 header/out 选项
+//! - POST /api/v1/export/curl: 等价的 curl 命令（每行一条）
+//!
+//! 直链有效期较短，导出内容需尽快使用；请求头中的 BDUSS 默认脱敏，
+//! 指定 `include_credentials` 时才导出完整凭证
+
+use std::collections::{BTreeMap, HashSet};
+
+use axum::{extract::State, response::Json};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::netdisk::NetdiskClient;
+use crate::server::AppState;
+
+use super::file::{direct_download_headers, direct_url_warning};
+use super::ApiResponse;
+
+/// 单次最多导出的文件数（每个文件都要请求一次下载链接）
+const EXPORT_MAX_ITEMS: usize = 200;
+/// filemetas 接口单次最多查询的 fs_id 数
+const FILEMETAS_BATCH_SIZE: usize = 100;
+
+/// 链接导出请求（paths 和 fs_ids 可同时指定）
+#[derive(Debug, Deserialize)]
+pub struct LinkExportRequest {
+    /// 网盘文件路径
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// 文件 fs_id（通过文件元信息解析为路径）
+    #[serde(default)]
+    pub fs_ids: Vec<u64>,
+    /// 首选链接索引（默认 0）
+    #[serde(default)]
+    pub prefer: usize,
+    /// 导出完整的登录凭证（默认 false，Cookie 中的 BDUSS 会被脱敏）
+    #[serde(default)]
+    pub include_credentials: bool,
+}
+
+/// 导出失败的文件
+#[derive(Debug, Serialize)]
+pub struct LinkExportFailure {
+    /// 文件路径或 fs_id
+    pub target: String,
+    pub error: String,
+}
+
+/// 链接导出响应
+#[derive(Debug, Serialize)]
+pub struct LinkExportResponse {
+    /// 导出内容（aria2 输入文件或 curl 命令）
+    pub content: String,
+    /// 成功导出的文件数
+    pub count: usize,
+    pub failed: Vec<LinkExportFailure>,
+    /// 有效期提示
+    pub warning: String,
+}
+
+/// 已解析直链的文件
+#[derive(Debug)]
+struct ResolvedLink {
+    file_name: String,
+    url: String,
+}
+
+/// 网盘路径中的文件名
+fn file_name_of(path: &str) -> String {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// 将 fs_id 解析为文件路径（文件夹和不存在的文件计入失败）
+async fn resolve_fs_ids(
+    client: &NetdiskClient,
+    fs_ids: &[u64],
+    failed: &mut Vec<LinkExportFailure>,
+) -> Vec<String> {
+    let mut paths = Vec::new();
+    for batch in fs_ids.chunks(FILEMETAS_BATCH_SIZE) {
+        let metas = match client.filemetas_by_fs_ids(batch).await {
+            Ok(metas) if metas.errno == 0 => metas.list,
+            Ok(metas) => {
+                let error = format!("获取文件信息失败: errno={} {}", metas.errno, metas.errmsg);
+                failed.extend(batch.iter().map(|fs_id| LinkExportFailure {
+                    target: fs_id.to_string(),
+                    error: error.clone(),
+                }));
+                continue;
+            }
+            Err(e) => {
+                let error = format!("获取文件信息失败: {}", e);
+                failed.extend(batch.iter().map(|fs_id| LinkExportFailure {
+                    target: fs_id.to_string(),
+                    error: error.clone(),
+                }));
+                continue;
+            }
+        };
+
+        for fs_id in batch {
+            match metas.iter().find(|meta| meta.fs_id == *fs_id) {
+                Some(meta) if meta.isdir == 1 => failed.push(LinkExportFailure {
+                    target: meta.path.clone(),
+                    error: "不支持导出文件夹".to_string(),
+                }),
+                Some(meta) => paths.push(meta.path.clone()),
+                None => failed.push(LinkExportFailure {
+                    target: fs_id.to_string(),
+                    error: "文件不存在".to_string(),
+                }),
+            }
+        }
+    }
+    paths
+}
+
+/// 解析请求中所有文件的直链
+async fn resolve_links(
+    app_state: &AppState,
+    req: LinkExportRequest,
+) -> Result<(NetdiskClient, Vec<ResolvedLink>, Vec<LinkExportFailure>), ApiResponse<LinkExportResponse>>
+{
+    let client = app_state.netdisk_client.read().await.clone();
+    let Some(client) = client else {
+        return Err(ApiResponse::error(401, "未登录或客户端未初始化".to_string()));
+    };
+
+    if req.paths.len() + req.fs_ids.len() > EXPORT_MAX_ITEMS {
+        return Err(ApiResponse::error(
+            400,
+            format!("单次最多导出 {} 个文件", EXPORT_MAX_ITEMS),
+        ));
+    }
+
+    let mut failed = Vec::new();
+    let mut paths: Vec<String> = req
+        .paths
+        .iter()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect();
+    paths.extend(resolve_fs_ids(&client, &req.fs_ids, &mut failed).await);
+
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(path.clone()));
+    if paths.is_empty() && failed.is_empty() {
+        return Err(ApiResponse::error(400, "请选择要导出的文件".to_string()));
+    }
+
+    let mut links = Vec::with_capacity(paths.len());
+    for path in paths {
+        match client.get_download_url(&path, req.prefer).await {
+            Ok(url) => links.push(ResolvedLink {
+                file_name: file_name_of(&path),
+                url,
+            }),
+            Err(e) => {
+                warn!("导出链接失败: path={}, 错误: {}", path, e);
+                failed.push(LinkExportFailure {
+                    target: path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok((client, links, failed))
+}
+
+/// 生成 aria2 输入文件内容
+fn render_aria2(links: &[ResolvedLink], headers: &BTreeMap<String, String>) -> String {
+    let mut content = String::new();
+    for link in links {
+        content.push_str(&link.url);
+        content.push('\n');
+        for (name, value) in headers {
+            content.push_str(&format!("  header={}: {}\n", name, value));
+        }
+        content.push_str(&format!("  out={}\n", link.file_name));
+    }
+    content
+}
+
+/// 单引号包裹 shell 参数
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 生成 curl 命令（每个文件一行，支持断点续传）
+fn render_curl(links: &[ResolvedLink], headers: &BTreeMap<String, String>) -> String {
+    let header_args: String = headers
+        .iter()
+        .map(|(name, value)| format!(" -H {}", shell_quote(&format!("{}: {}", name, value))))
+        .collect();
+    links
+        .iter()
+        .map(|link| {
+            format!(
+                "curl -L -C -{} -o {} {}\n",
+                header_args,
+                shell_quote(&link.file_name),
+                shell_quote(&link.url)
+            )
+        })
+        .collect()
+}
+
+/// 解析链接并用指定格式渲染
+async fn export_links(
+    app_state: AppState,
+    req: LinkExportRequest,
+    render: fn(&[ResolvedLink], &BTreeMap<String, String>) -> String,
+) -> Json<ApiResponse<LinkExportResponse>> {
+    let include_credentials = req.include_credentials;
+    let (client, links, failed) = match resolve_links(&app_state, req).await {
+        Ok(resolved) => resolved,
+        Err(response) => return Json(response),
+    };
+
+    info!("导出下载链接: 成功 {}, 失败 {}", links.len(), failed.len());
+    let headers = direct_download_headers(client.bduss(), include_credentials);
+    Json(ApiResponse::success(LinkExportResponse {
+        content: render(&links, &headers),
+        count: links.len(),
+        failed,
+        warning: direct_url_warning(include_credentials),
+    }))
+}
+
+/// POST /api/v1/export/aria2
+/// 导出 aria2 输入文件（aria2c -i 使用）
+pub async fn export_aria2(
+    State(app_state): State<AppState>,
+    Json(req): Json<LinkExportRequest>,
+) -> Json<ApiResponse<LinkExportResponse>> {
+    export_links(app_state, req, render_aria2).await
+}
+
+/// POST /api/v1/export/curl
+/// 导出 curl 下载命令
+pub async fn export_curl(
+    State(app_state): State<AppState>,
+    Json(req): Json<LinkExportRequest>,
+) -> Json<ApiResponse<LinkExportResponse>> {
+    export_links(app_state, req, render_curl).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<ResolvedLink>, BTreeMap<String, String>) {
+        let links = vec![ResolvedLink {
+            file_name: file_name_of("/影视/it's.mp4"),
+            url: "https://d.pcs.baidu.com/file/abc?fid=1&sign=x".to_string(),
+        }];
+        let headers = BTreeMap::from([
+            ("Cookie".to_string(), "BDUSS=secret".to_string()),
+            ("User-Agent".to_string(), "netdisk".to_string()),
+        ]);
+        (links, headers)
+    }
+
+    #[test]
+    fn test_render_aria2() {
+        let (links, headers) = sample();
+        assert_eq!(
+            render_aria2(&links, &headers),
+            "https://d.pcs.baidu.com/file/abc?fid=1&sign=x\n\
+             \x20 header=Cookie: BDUSS=secret\n\
+             \x20 header=User-Agent: netdisk\n\
+             \x20 out=it's.mp4\n"
+        );
+    }
+
+    #[test]
+    fn test_render_curl_quotes_arguments() {
+        let (links, headers) = sample();
+        assert_eq!(
+            render_curl(&links, &headers),
+            "curl -L -C - -H 'Cookie: BDUSS=secret' -H 'User-Agent: netdisk' \
+             -o 'it'\\''s.mp4' 'https://d.pcs.baidu.com/file/abc?fid=1&sign=x'\n"
+        );
+    }
+    #[test]
+    fn test_headers_redact_bduss_by_default() {
+        let headers = direct_download_headers("abcdefghijklmnop", false);
+        assert_eq!(headers["Cookie"], "BDUSS=abcd***mnop");
+        assert!(direct_url_warning(false).contains("include_credentials"));
+
+        let headers = direct_download_headers("abcdefghijklmnop", true);
+        assert_eq!(headers["Cookie"], "BDUSS=abcdefghijklmnop");
+    }
+}
@@ -0,0 +1,150 @@
+// Prometheus 指标 API 处理器
+
+use std::fmt::Write;
+
+use crate::server::events::{TransferMetrics, TransferMetricsSnapshot};
+use crate::server::AppState;
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+};
+
+use super::stats::{collect_dashboard_stats, DashboardStats};
+
+/// Prometheus 文本格式的 Content-Type
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 写入一个指标（HELP/TYPE 头 + 样本行）
+///
+/// `samples` 为 (标签, 值)，标签为空时不输出花括号
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(&str, f64)]) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    }
+}
+
+/// 渲染 Prometheus 文本格式
+fn render_metrics(counters: &TransferMetricsSnapshot, stats: &DashboardStats) -> String {
+    let mut out = String::new();
+
+    write_metric(
+        &mut out,
+        "baidupcs_downloaded_bytes_total",
+        "counter",
+        "Total bytes downloaded since start",
+        &[("", counters.downloaded_bytes as f64)],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_tasks_finished_total",
+        "counter",
+        "Finished transfer tasks by direction and result",
+        &[
+            (r#"direction="download",result="completed""#, counters.downloads_completed as f64),
+            (r#"direction="download",result="failed""#, counters.downloads_failed as f64),
+            (r#"direction="upload",result="completed""#, counters.uploads_completed as f64),
+            (r#"direction="upload",result="failed""#, counters.uploads_failed as f64),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_cdn_refreshes_total",
+        "counter",
+        "Successful CDN download link refreshes",
+        &[("", counters.cdn_refreshes as f64)],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_active_tasks",
+        "gauge",
+        "Transfer tasks currently running",
+        &[
+            (r#"direction="download""#, stats.download.active_tasks as f64),
+            (r#"direction="upload""#, stats.upload.active_tasks as f64),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_queued_tasks",
+        "gauge",
+        "Transfer tasks waiting in queue",
+        &[
+            (r#"direction="download""#, stats.download.queued_tasks as f64),
+            (r#"direction="upload""#, stats.upload.queued_tasks as f64),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_speed_bytes_per_second",
+        "gauge",
+        "Current aggregate transfer speed",
+        &[
+            (r#"direction="download""#, stats.download.speed as f64),
+            (r#"direction="upload""#, stats.upload.speed as f64),
+        ],
+    );
+    write_metric(
+        &mut out,
+        "baidupcs_thread_utilization",
+        "gauge",
+        "Chunk thread utilization across uploads and downloads (0-1)",
+        &[("", stats.thread_utilization)],
+    );
+
+    out
+}
+
+/// GET /metrics
+/// 以 Prometheus 文本格式导出传输指标
+pub async fn get_metrics(State(app_state): State<AppState>) -> impl IntoResponse {
+    let download_manager = app_state.download_manager.read().await.clone();
+    let upload_manager = app_state.upload_manager.read().await.clone();
+    let stats = collect_dashboard_stats(download_manager, upload_manager).await;
+    let counters = TransferMetrics::global().snapshot();
+
+    (
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        render_metrics(&counters, &stats),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let counters = TransferMetricsSnapshot {
+            downloaded_bytes: 4096,
+            downloads_completed: 3,
+            downloads_failed: 1,
+            cdn_refreshes: 2,
+            ..Default::default()
+        };
+        let mut stats = DashboardStats::default();
+        stats.download.active_tasks = 2;
+        stats.upload.queued_tasks = 5;
+        stats.thread_utilization = 0.5;
+
+        let text = render_metrics(&counters, &stats);
+
+        assert!(text.contains("# TYPE baidupcs_downloaded_bytes_total counter\nbaidupcs_downloaded_bytes_total 4096\n"));
+        assert!(text.contains(r#"baidupcs_tasks_finished_total{direction="download",result="completed"} 3"#));
+        assert!(text.contains(r#"baidupcs_tasks_finished_total{direction="download",result="failed"} 1"#));
+        assert!(text.contains("baidupcs_cdn_refreshes_total 2\n"));
+        assert!(text.contains(r#"baidupcs_active_tasks{direction="download"} 2"#));
+        assert!(text.contains(r#"baidupcs_queued_tasks{direction="upload"} 5"#));
+        assert!(text.contains("baidupcs_thread_utilization 0.5\n"));
+        // 每个样本行都以指标名开头，注释行以 # 开头
+        assert!(text
+            .lines()
+            .all(|line| line.starts_with("# ") || line.starts_with("baidupcs_")));
+    }
+}
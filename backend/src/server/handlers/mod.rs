@@ -5,23 +5,35 @@ pub mod autobackup;
 pub mod cloud_dl;
 pub mod common;
 pub mod config;
+pub mod diagnostics;
 pub mod download;
 pub mod encryption_export;
 pub mod file;
 pub mod filesystem;
 pub mod folder_download;
+pub mod health;
+pub mod link_export;
+pub mod metrics;
 pub mod share;
+pub mod stats;
+pub mod task_migration;
 pub mod transfer;
 pub mod upload;
 
 pub use auth::*;
 pub use config::*;
+pub use diagnostics::*;
 pub use download::*;
 pub use encryption_export::{export_bundle, export_keys, export_mapping};
 pub use file::*;
 // 只导出需要的函数，避免 ApiResponse 冲突
-pub use filesystem::{get_roots, goto_path, list_directory, validate_path};
+pub use filesystem::{get_drives, get_roots, goto_path, list_directory, validate_path};
 pub use folder_download::*;
+pub use health::*;
+pub use link_export::{export_aria2, export_curl};
+pub use metrics::get_metrics;
 pub use share::*;
+pub use stats::*;
+pub use task_migration::{export_task_list, import_task_list};
 pub use transfer::*;
 pub use upload::*;
@@ -1,5 +1,6 @@
 // 分享API处理器
 
+use crate::netdisk::NetdiskError;
 use crate::server::handlers::ApiResponse;
 use crate::server::AppState;
 use axum::{
@@ -250,8 +251,8 @@ pub async fn create_share(
                 info!("分享创建成功: shareid={}, pwd={}", result.shareid, result.pwd);
                 Ok(Json(ApiResponse::success(result)))
             } else {
-                // 检查是否需要预热重试 (errno=-6)
-                if response.errno == -6 {
+                // 检查是否需要预热重试 (errno=-6 等登录态失效)
+                if NetdiskError::from_errno(response.errno as i64) == NetdiskError::AuthExpired {
                     warn!("分享创建遇到 errno=-6，触发预热重试...");
                     drop(client_lock); // 释放读锁
 
@@ -0,0 +1,115 @@
+// 传输统计 API 处理器
+
+use std::sync::Arc;
+
+use crate::downloader::{DownloadManager, ScheduleStatus};
+use crate::server::AppState;
+use crate::uploader::UploadManager;
+use axum::{extract::State, response::Json};
+use serde::Serialize;
+
+use super::ApiResponse;
+
+/// 单个方向（上传或下载）的传输统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransferStats {
+    /// 活跃任务数（等待槽位之外的进行中任务）
+    pub active_tasks: usize,
+    /// 等待队列中的任务数
+    pub queued_tasks: usize,
+    /// 总速度 (bytes/s)
+    pub speed: u64,
+    /// 活跃分片线程数
+    pub active_threads: usize,
+    /// 最大分片线程数
+    pub max_threads: usize,
+}
+
+/// 全局传输统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardStats {
+    /// 下载统计
+    pub download: TransferStats,
+    /// 上传统计
+    pub upload: TransferStats,
+    /// 全局线程利用率（0.0 ~ 1.0，上传下载线程合计）
+    pub thread_utilization: f64,
+    /// 下载时间段调度状态（下载管理器未初始化时为 null）
+    pub download_schedule: Option<ScheduleStatus>,
+}
+
+/// 计算线程利用率，最大线程数为 0 时返回 0
+fn thread_utilization(active_threads: usize, max_threads: usize) -> f64 {
+    if max_threads == 0 {
+        0.0
+    } else {
+        (active_threads as f64 / max_threads as f64).min(1.0)
+    }
+}
+
+/// 汇总上传/下载统计（管理器未初始化时对应部分为全 0）
+///
+/// 供 /stats 接口和 WebSocket 统计心跳共用
+pub async fn collect_dashboard_stats(
+    download_manager: Option<Arc<DownloadManager>>,
+    upload_manager: Option<Arc<UploadManager>>,
+) -> DashboardStats {
+    let mut stats = DashboardStats::default();
+
+    if let Some(download_manager) = download_manager {
+        let (active_threads, max_threads) = download_manager.get_thread_pool_stats();
+        stats.download = TransferStats {
+            active_tasks: download_manager.active_count().await,
+            queued_tasks: download_manager.waiting_count().await,
+            speed: download_manager.total_speed().await,
+            active_threads,
+            max_threads,
+        };
+        stats.download_schedule = Some(download_manager.schedule_status());
+    }
+
+    if let Some(upload_manager) = upload_manager {
+        let (active_threads, max_threads) = upload_manager.get_thread_pool_stats();
+        stats.upload = TransferStats {
+            active_tasks: upload_manager.active_task_count(),
+            queued_tasks: upload_manager.waiting_count().await,
+            speed: upload_manager.total_speed().await,
+            active_threads,
+            max_threads,
+        };
+    }
+
+    stats.thread_utilization = thread_utilization(
+        stats.download.active_threads + stats.upload.active_threads,
+        stats.download.max_threads + stats.upload.max_threads,
+    );
+
+    stats
+}
+
+/// GET /api/v1/stats
+/// 获取上传/下载综合统计（供仪表盘单次轮询）
+///
+/// 管理器未初始化（未登录）时对应部分返回全 0
+pub async fn get_dashboard_stats(
+    State(app_state): State<AppState>,
+) -> Json<ApiResponse<DashboardStats>> {
+    let download_manager = app_state.download_manager.read().await.clone();
+    let upload_manager = app_state.upload_manager.read().await.clone();
+    Json(ApiResponse::success(
+        collect_dashboard_stats(download_manager, upload_manager).await,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_utilization() {
+        assert_eq!(thread_utilization(0, 0), 0.0);
+        assert_eq!(thread_utilization(5, 10), 0.5);
+        // 线程数调小后活跃数可能暂时超过上限
+        assert_eq!(thread_utilization(12, 10), 1.0);
+    }
+}
@@ -0,0 +1,189 @@
+//! 任务迁移 API 处理器
+//!
+//! - GET /api/v1/tasks/export: 导出未完成的下载/上传/转存任务元数据（JSON）
+//! - POST /api/v1/tasks/import: 导入任务，以暂停状态重建并返回汇总
+
+use axum::{extract::State, http::StatusCode, response::Json};
+use bit_set::BitSet;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::downloader::DownloadManager;
+use crate::persistence::{
+    delete_task_files, export_tasks, metadata_exists, prepare_import, save_metadata,
+    validate_import, DownloadRecoveryInfo, RecoveredTask, TaskExportBundle, TaskMetadata,
+    TaskType, TransferRecoveryInfo, UploadRecoveryInfo,
+};
+use crate::server::read_only::{is_read_only, READ_ONLY_MESSAGE};
+use crate::server::AppState;
+use crate::transfer::TransferManager;
+use crate::uploader::UploadManager;
+
+use super::ApiResponse;
+
+/// 跳过的导入任务
+#[derive(Debug, Serialize)]
+pub struct SkippedImportTask {
+    pub task_id: String,
+    pub task_type: TaskType,
+    /// 跳过原因
+    pub reason: String,
+}
+
+/// 任务导入结果
+#[derive(Debug, Serialize)]
+pub struct TaskImportResponse {
+    /// 成功导入的任务数
+    pub imported: usize,
+    /// 跳过的任务及原因
+    pub skipped: Vec<SkippedImportTask>,
+}
+
+/// GET /api/v1/tasks/export
+/// 导出未完成任务的元数据（不包含文件数据）
+pub async fn export_task_list(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<TaskExportBundle>>, StatusCode> {
+    let wal_dir = app_state.persistence_manager.lock().await.wal_dir().clone();
+
+    match export_tasks(&wal_dir) {
+        Ok(bundle) => {
+            info!("导出任务列表: {} 个任务", bundle.tasks.len());
+            Ok(Json(ApiResponse::success(bundle)))
+        }
+        Err(e) => {
+            error!("导出任务列表失败: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// POST /api/v1/tasks/import
+/// 导入任务列表，以暂停状态重建（已存在或无法重建的任务跳过）
+pub async fn import_task_list(
+    State(app_state): State<AppState>,
+    Json(bundle): Json<TaskExportBundle>,
+) -> Result<Json<ApiResponse<TaskImportResponse>>, StatusCode> {
+    info!(
+        "导入任务列表: {} 个任务 (version={}, exported_at={})",
+        bundle.tasks.len(),
+        bundle.version,
+        bundle.exported_at
+    );
+
+    let wal_dir = app_state.persistence_manager.lock().await.wal_dir().clone();
+    let read_only = is_read_only(&app_state).await;
+    let filesystem_config = app_state.config.read().await.filesystem.clone();
+    let download_manager = app_state.download_manager.read().await.clone();
+    let upload_manager = app_state.upload_manager.read().await.clone();
+    let transfer_manager = app_state.transfer_manager.read().await.clone();
+
+    let mut response = TaskImportResponse {
+        imported: 0,
+        skipped: Vec::new(),
+    };
+
+    for metadata in bundle.tasks {
+        let task_id = metadata.task_id.clone();
+        let task_type = metadata.task_type;
+
+        let result = async {
+            validate_import(&metadata)?;
+            // 🔥 只读模式下不创建上传和转存任务
+            if read_only && task_type != TaskType::Download {
+                return Err(READ_ONLY_MESSAGE.to_string());
+            }
+            // 🔥 下载保存路径必须位于 allowed_paths 白名单内（与创建下载任务一致）
+            if let Some(local_path) = metadata.local_path.as_deref() {
+                let target_dir = local_path.parent().unwrap_or(local_path);
+                filesystem_config
+                    .ensure_path_allowed(target_dir, "下载目录")
+                    .map_err(|e| e.to_string())?;
+            }
+            if metadata_exists(&wal_dir, &task_id) {
+                return Err("任务已存在".to_string());
+            }
+
+            let metadata = prepare_import(metadata);
+            save_metadata(&wal_dir, &metadata).map_err(|e| format!("保存元数据失败: {}", e))?;
+
+            let restored = restore_imported(
+                metadata,
+                &download_manager,
+                &upload_manager,
+                &transfer_manager,
+            )
+            .await;
+            if restored.is_err() {
+                // 重建失败时清理刚写入的元数据，避免下次启动被自动恢复
+                if let Err(e) = delete_task_files(&wal_dir, &task_id) {
+                    warn!("清理导入失败的任务文件失败: task_id={}, 错误: {}", task_id, e);
+                }
+            }
+            restored
+        }
+        .await;
+
+        match result {
+            Ok(()) => response.imported += 1,
+            Err(reason) => {
+                warn!("跳过导入任务: task_id={}, 原因: {}", task_id, reason);
+                response.skipped.push(SkippedImportTask {
+                    task_id,
+                    task_type,
+                    reason,
+                });
+            }
+        }
+    }
+
+    info!(
+        "任务导入完成: 成功 {} 个, 跳过 {} 个",
+        response.imported,
+        response.skipped.len()
+    );
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 通过恢复流程重建导入的任务（分片进度为空，初始为暂停状态）
+async fn restore_imported(
+    metadata: TaskMetadata,
+    download_manager: &Option<Arc<DownloadManager>>,
+    upload_manager: &Option<Arc<UploadManager>>,
+    transfer_manager: &Option<Arc<TransferManager>>,
+) -> Result<(), String> {
+    const NOT_READY: &str = "任务管理器未初始化，请先登录";
+    const INCOMPLETE: &str = "元数据不完整";
+
+    let task_type = metadata.task_type;
+    let total_chunks = metadata.total_chunks.unwrap_or(0);
+    let recovered = RecoveredTask {
+        metadata,
+        completed_chunks: BitSet::new(),
+        chunk_md5s: (task_type == TaskType::Upload).then(|| vec![None; total_chunks]),
+        partial_progress: HashMap::new(),
+        chunk_crcs: Vec::new(),
+    };
+
+    match task_type {
+        TaskType::Download => {
+            let manager = download_manager.as_ref().ok_or(NOT_READY)?;
+            let info = DownloadRecoveryInfo::from_recovered(&recovered).ok_or(INCOMPLETE)?;
+            manager.restore_task(info).await.map_err(|e| e.to_string())?;
+        }
+        TaskType::Upload => {
+            let manager = upload_manager.as_ref().ok_or(NOT_READY)?;
+            let info = UploadRecoveryInfo::from_recovered(&recovered).ok_or(INCOMPLETE)?;
+            manager.restore_task(info).await.map_err(|e| e.to_string())?;
+        }
+        TaskType::Transfer => {
+            let manager = transfer_manager.as_ref().ok_or(NOT_READY)?;
+            let info = TransferRecoveryInfo::from_recovered(&recovered).ok_or(INCOMPLETE)?;
+            manager.restore_task(info).await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
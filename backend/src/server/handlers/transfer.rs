@@ -1,7 +1,8 @@
 // 转存 API 处理器
 
+use super::common::{HistoryPageResponse, HistoryQueryParams};
 use crate::server::AppState;
-use crate::transfer::{TransferStatus, TransferTask};
+use crate::transfer::{ShareInspectResult, TransferStatus, TransferTask};
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -35,6 +36,15 @@ impl<T: Serialize> TransferApiResponse<T> {
             data: None,
         }
     }
+
+    /// 错误响应附带数据（如需要验证码时返回验证码信息）
+    pub fn error_with_data(code: i32, message: impl Into<String>, data: T) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: Some(data),
+        }
+    }
 }
 
 /// 业务响应码
@@ -57,6 +67,10 @@ pub mod error_codes {
     pub const TRANSFER_FAILED: i32 = 1008;
     /// 下载失败
     pub const DOWNLOAD_FAILED: i32 = 1009;
+    /// 需要输入验证码
+    pub const CAPTCHA_REQUIRED: i32 = 1010;
+    /// 验证码错误
+    pub const INVALID_CAPTCHA: i32 = 1011;
 }
 
 // ============================================
@@ -84,6 +98,9 @@ pub struct CreateTransferRequest {
     /// 分享直下任务会自动创建临时目录，下载完成后自动清理
     #[serde(default)]
     pub is_share_direct_download: bool,
+    /// 是否转存到临时目录、下载后删除网盘副本（不传且未指定 auto_download 时使用全局配置）
+    #[serde(default)]
+    pub download_cleanup: Option<bool>,
     /// 用户选择的文件 fs_id 列表（可选）
     /// 为空或未提供时转存所有文件（向后兼容）
     #[serde(default)]
@@ -93,6 +110,12 @@ pub struct CreateTransferRequest {
     /// 解决子目录选择场景下后端无法从根目录文件列表中匹配到子文件信息的问题
     #[serde(default)]
     pub selected_files: Option<Vec<crate::transfer::SharedFileInfo>>,
+    /// 自动下载时按分享创建本地子文件夹（title=按分享标题, short_key=按短链 ID，不传则不创建）
+    #[serde(default)]
+    pub share_subfolder: Option<crate::transfer::ShareSubfolderNaming>,
+    /// 递归展开选中的文件夹后按文件分批转存（保留目录结构，适用于超过单次转存文件数上限的大文件夹）
+    #[serde(default)]
+    pub recursive: bool,
 }
 
 /// 创建转存任务响应
@@ -104,6 +127,9 @@ pub struct CreateTransferResponse {
     pub status: Option<TransferStatus>,
     /// 是否需要提取码
     pub need_password: bool,
+    /// 需要输入验证码时返回的验证码图片和令牌
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha: Option<crate::transfer::ShareCaptcha>,
 }
 
 /// 转存任务列表查询参数
@@ -124,42 +150,66 @@ pub struct TransferListResponse {
 // API 处理器
 // ============================================
 
-/// POST /api/v1/transfers
-/// 创建转存任务
-pub async fn create_transfer(
-    State(app_state): State<AppState>,
-    Json(req): Json<CreateTransferRequest>,
-) -> Json<TransferApiResponse<CreateTransferResponse>> {
-    // 获取转存管理器
-    let transfer_manager = {
-        let guard = app_state.transfer_manager.read().await;
-        match guard.clone() {
-            Some(tm) => tm,
-            None => {
-                error!("转存管理器未初始化");
-                return Json(TransferApiResponse::error(
-                    error_codes::MANAGER_NOT_READY,
-                    "转存管理器未初始化，请先登录",
-                ));
-            }
+/// 提交分享验证码请求（连同原始创建转存请求一起提交）
+#[derive(Debug, Deserialize)]
+pub struct SubmitCaptchaRequest {
+    #[serde(flatten)]
+    pub request: CreateTransferRequest,
+    /// 用户识别出的验证码
+    pub vcode: String,
+    /// 验证码令牌（创建转存返回的 captcha.vcode_str）
+    pub vcode_str: String,
+}
+
+impl CreateTransferRequest {
+    fn into_manager_request(self) -> crate::transfer::manager::CreateTransferRequest {
+        crate::transfer::manager::CreateTransferRequest {
+            share_url: self.share_url,
+            password: self.password,
+            save_path: self.save_path,
+            save_fs_id: self.save_fs_id,
+            auto_download: self.auto_download,
+            local_download_path: self.local_download_path,
+            is_share_direct_download: self.is_share_direct_download,
+            download_cleanup: self.download_cleanup,
+            selected_fs_ids: self.selected_fs_ids,
+            selected_files: self.selected_files,
+            share_subfolder: self.share_subfolder,
+            recursive: self.recursive,
+            captcha: None,
         }
-    };
+    }
+}
 
-    // 创建转存请求
-    let create_request = crate::transfer::manager::CreateTransferRequest {
-        share_url: req.share_url,
-        password: req.password,
-        save_path: req.save_path,
-        save_fs_id: req.save_fs_id,
-        auto_download: req.auto_download,
-        local_download_path: req.local_download_path,
-        is_share_direct_download: req.is_share_direct_download,
-        selected_fs_ids: req.selected_fs_ids,
-        selected_files: req.selected_files,
-    };
+/// 验证码相关错误的业务码（验证码错误 / 需要验证码）
+fn captcha_error_code(err: &crate::transfer::TransferError) -> i32 {
+    match err {
+        crate::transfer::TransferError::CaptchaIncorrect(_) => error_codes::INVALID_CAPTCHA,
+        _ => error_codes::CAPTCHA_REQUIRED,
+    }
+}
 
-    // 创建任务
-    match transfer_manager.create_task(create_request).await {
+/// 请求中携带的分享验证码（vcode 和 vcode_str 同时提供时有效）
+fn captcha_answer(
+    vcode: Option<String>,
+    vcode_str: Option<String>,
+) -> Option<crate::transfer::ShareCaptchaAnswer> {
+    match (vcode, vcode_str) {
+        (Some(vcode), Some(vcode_str)) if !vcode.trim().is_empty() => {
+            Some(crate::transfer::ShareCaptchaAnswer {
+                vcode: vcode.trim().to_string(),
+                vcode_str,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 将转存管理器的创建结果转换为 API 响应
+fn create_transfer_response(
+    result: anyhow::Result<crate::transfer::manager::CreateTransferResponse>,
+) -> Json<TransferApiResponse<CreateTransferResponse>> {
+    match result {
         Ok(response) => {
             if response.need_password {
                 return Json(TransferApiResponse::error(
@@ -168,6 +218,20 @@ pub async fn create_transfer(
                 ));
             }
 
+            // 🔥 需要验证码：通过 data.captcha 返回验证码图片和令牌
+            if let Some(err) = response.captcha_error {
+                return Json(TransferApiResponse::error_with_data(
+                    captcha_error_code(&err),
+                    err.to_string(),
+                    CreateTransferResponse {
+                        task_id: None,
+                        status: None,
+                        need_password: false,
+                        captcha: err.captcha().cloned(),
+                    },
+                ));
+            }
+
             if let Some(ref err) = response.error {
                 // 根据错误内容返回不同的错误码
                 let code = if err.contains("需要密码") || err.contains("需要提取码") {
@@ -192,6 +256,7 @@ pub async fn create_transfer(
                 task_id: response.task_id,
                 status: response.status,
                 need_password: false,
+                captcha: None,
             }))
         }
         Err(e) => {
@@ -217,6 +282,175 @@ pub async fn create_transfer(
     }
 }
 
+/// POST /api/v1/transfers
+/// 创建转存任务
+pub async fn create_transfer(
+    State(app_state): State<AppState>,
+    Json(req): Json<CreateTransferRequest>,
+) -> Json<TransferApiResponse<CreateTransferResponse>> {
+    // 获取转存管理器
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        match guard.clone() {
+            Some(tm) => tm,
+            None => {
+                error!("转存管理器未初始化");
+                return Json(TransferApiResponse::error(
+                    error_codes::MANAGER_NOT_READY,
+                    "转存管理器未初始化，请先登录",
+                ));
+            }
+        }
+    };
+
+    // 创建任务
+    create_transfer_response(
+        transfer_manager
+            .create_task(req.into_manager_request())
+            .await,
+    )
+}
+
+/// POST /api/v1/transfers/captcha
+/// 提交分享验证码：携带验证码重新验证提取码并创建转存任务
+///
+/// 验证码错误时返回 INVALID_CAPTCHA 和新的验证码
+pub async fn submit_share_captcha(
+    State(app_state): State<AppState>,
+    Json(req): Json<SubmitCaptchaRequest>,
+) -> Json<TransferApiResponse<CreateTransferResponse>> {
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        match guard.clone() {
+            Some(tm) => tm,
+            None => {
+                error!("转存管理器未初始化");
+                return Json(TransferApiResponse::error(
+                    error_codes::MANAGER_NOT_READY,
+                    "转存管理器未初始化，请先登录",
+                ));
+            }
+        }
+    };
+
+    let answer = crate::transfer::ShareCaptchaAnswer {
+        vcode: req.vcode.trim().to_string(),
+        vcode_str: req.vcode_str,
+    };
+    create_transfer_response(
+        transfer_manager
+            .retry_with_captcha(req.request.into_manager_request(), answer)
+            .await,
+    )
+}
+
+/// 免转存直接下载请求
+#[derive(Debug, Deserialize)]
+pub struct DirectDownloadRequest {
+    /// 分享链接
+    pub share_url: String,
+    /// 提取码（可选）
+    pub password: Option<String>,
+    /// 用户选择的文件完整信息列表（可选，为空时下载分享根目录下的全部文件）
+    #[serde(default)]
+    pub selected_files: Option<Vec<crate::transfer::SharedFileInfo>>,
+    /// 本地下载路径（可选，默认使用下载配置中的目录）
+    pub local_download_path: Option<String>,
+    /// 用户识别出的验证码（上次返回 CAPTCHA_REQUIRED 时提交）
+    pub vcode: Option<String>,
+    /// 验证码令牌（上次返回的 captcha.vcode_str）
+    pub vcode_str: Option<String>,
+}
+
+/// 免转存直接下载响应
+#[derive(Debug, Default, Serialize)]
+pub struct DirectDownloadResponse {
+    /// 创建的下载任务 ID 列表
+    pub task_ids: Vec<String>,
+    /// 未获取到直链或创建任务失败的文件路径
+    pub failed_paths: Vec<String>,
+    /// 需要输入验证码时返回验证码图片和令牌
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha: Option<crate::transfer::ShareCaptcha>,
+}
+
+/// POST /api/v1/transfers/direct-download
+/// 免转存直接下载分享文件（不转存到自己的网盘）
+pub async fn direct_download_share(
+    State(app_state): State<AppState>,
+    Json(req): Json<DirectDownloadRequest>,
+) -> Json<TransferApiResponse<DirectDownloadResponse>> {
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        match guard.clone() {
+            Some(tm) => tm,
+            None => {
+                error!("转存管理器未初始化");
+                return Json(TransferApiResponse::error(
+                    error_codes::MANAGER_NOT_READY,
+                    "转存管理器未初始化，请先登录",
+                ));
+            }
+        }
+    };
+
+    let request = crate::transfer::manager::DirectDownloadRequest {
+        share_url: req.share_url,
+        password: req.password,
+        selected_files: req.selected_files,
+        local_download_path: req.local_download_path,
+        captcha: captcha_answer(req.vcode, req.vcode_str),
+    };
+
+    match transfer_manager.create_direct_download(request).await {
+        Ok(result) => {
+            info!("免转存直接下载创建成功: {} 个任务", result.task_ids.len());
+            Json(TransferApiResponse::success(DirectDownloadResponse {
+                task_ids: result.task_ids,
+                failed_paths: result.failed_paths,
+                captcha: None,
+            }))
+        }
+        Err(e) => {
+            let err_msg = e.to_string();
+            error!("免转存直接下载失败: {:?}", err_msg);
+
+            // 🔥 需要验证码：通过 data.captcha 返回验证码图片和令牌
+            if let Some(err) =
+                crate::transfer::TransferError::find(&e).filter(|err| err.captcha().is_some())
+            {
+                return Json(TransferApiResponse::error_with_data(
+                    captcha_error_code(&err),
+                    err_msg,
+                    DirectDownloadResponse {
+                        captcha: err.captcha().cloned(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
+            let code = if err_msg.contains("需要密码")
+                || err_msg.contains("need password")
+                || err_msg.contains("需要提取码")
+            {
+                error_codes::NEED_PASSWORD
+            } else if err_msg.contains("提取码错误") {
+                error_codes::INVALID_PASSWORD
+            } else if err_msg.contains("验证码") {
+                error_codes::CAPTCHA_REQUIRED
+            } else if err_msg.contains("已失效") {
+                error_codes::SHARE_EXPIRED
+            } else if err_msg.contains("不存在") {
+                error_codes::SHARE_NOT_FOUND
+            } else {
+                error_codes::DOWNLOAD_FAILED
+            };
+
+            Json(TransferApiResponse::error(code, err_msg))
+        }
+    }
+}
+
 /// GET /api/v1/transfers
 /// 获取所有转存任务
 /// 支持查询参数：is_share_direct_download (可选，过滤分享直下任务)
@@ -247,6 +481,42 @@ pub async fn get_all_transfers(
     })))
 }
 
+/// GET /api/v1/transfers/history?page=&size=&status=&q=
+/// 分页查询转存历史（直接查询历史数据库），q 匹配分享链接或转存目标路径
+pub async fn get_transfer_history(
+    State(app_state): State<AppState>,
+    Query(params): Query<HistoryQueryParams>,
+) -> Result<Json<TransferApiResponse<HistoryPageResponse<TransferTask>>>, StatusCode> {
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        guard.clone().ok_or(StatusCode::SERVICE_UNAVAILABLE)?
+    };
+
+    let query = params.to_query();
+    let size = query.limit;
+    let (tasks, total) = transfer_manager.query_history(query).await;
+    Ok(Json(TransferApiResponse::success(HistoryPageResponse {
+        total,
+        page: params.page.max(1),
+        size,
+        tasks,
+    })))
+}
+
+/// DELETE /api/v1/transfers/clear/completed
+/// 清除已完成的转存任务（含历史记录）
+pub async fn clear_completed_transfers(
+    State(app_state): State<AppState>,
+) -> Result<Json<TransferApiResponse<usize>>, StatusCode> {
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        guard.clone().ok_or(StatusCode::SERVICE_UNAVAILABLE)?
+    };
+
+    let count = transfer_manager.clear_completed().await;
+    Ok(Json(TransferApiResponse::success(count)))
+}
+
 /// GET /api/v1/transfers/:id
 /// 获取单个转存任务
 pub async fn get_transfer(
@@ -359,15 +629,22 @@ pub struct PreviewShareRequest {
     pub page: Option<u32>,
     /// 每页数量（默认 100）
     pub num: Option<u32>,
+    /// 用户识别出的验证码（上次返回 CAPTCHA_REQUIRED 时提交）
+    pub vcode: Option<String>,
+    /// 验证码令牌（上次返回的 captcha.vcode_str）
+    pub vcode_str: Option<String>,
 }
 
 /// 预览分享文件响应
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 pub struct PreviewShareResponse {
     pub files: Vec<crate::transfer::SharedFileInfo>,
     /// 分享信息（用于后续目录导航，首次预览时返回）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub share_info: Option<PreviewShareInfo>,
+    /// 需要输入验证码时返回验证码图片和令牌
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha: Option<crate::transfer::ShareCaptcha>,
 }
 
 /// 预览时返回的分享信息（前端缓存后用于目录导航）
@@ -404,7 +681,13 @@ pub async fn preview_share_files(
     let num = req.num.unwrap_or(100);
 
     match transfer_manager
-        .preview_share(&req.share_url, req.password, page, num)
+        .preview_share(
+            &req.share_url,
+            req.password,
+            captcha_answer(req.vcode, req.vcode_str),
+            page,
+            num,
+        )
         .await
     {
         Ok(result) => {
@@ -417,17 +700,37 @@ pub async fn preview_share_files(
                     uk: result.uk,
                     bdstoken: result.bdstoken,
                 }),
+                captcha: None,
             }))
         }
         Err(e) => {
             let err_msg = e.to_string();
             error!("预览分享文件失败: {}", err_msg);
 
+            // 🔥 需要验证码：通过 data.captcha 返回验证码图片和令牌
+            if let Some(err) =
+                crate::transfer::TransferError::find(&e).filter(|err| err.captcha().is_some())
+            {
+                return Json(TransferApiResponse::error_with_data(
+                    captcha_error_code(&err),
+                    err_msg,
+                    PreviewShareResponse {
+                        captcha: err.captcha().cloned(),
+                        ..Default::default()
+                    },
+                ));
+            }
+
             // 根据错误内容返回不同的错误码（与 create_transfer 一致）
-            let code = if err_msg.contains("需要密码") || err_msg.contains("need password") || err_msg.contains("需要提取码") {
+            let code = if err_msg.contains("需要密码")
+                || err_msg.contains("need password")
+                || err_msg.contains("需要提取码")
+            {
                 error_codes::NEED_PASSWORD
             } else if err_msg.contains("提取码错误") || err_msg.contains("-9") {
                 error_codes::INVALID_PASSWORD
+            } else if err_msg.contains("验证码") {
+                error_codes::CAPTCHA_REQUIRED
             } else if err_msg.contains("已失效") || err_msg.contains("expired") {
                 error_codes::SHARE_EXPIRED
             } else if err_msg.contains("不存在") || err_msg.contains("not found") {
@@ -441,6 +744,42 @@ pub async fn preview_share_files(
     }
 }
 
+/// 检查分享链接请求
+#[derive(Debug, Deserialize)]
+pub struct InspectShareRequest {
+    /// 分享链接
+    pub share_url: String,
+}
+
+/// POST /api/v1/transfers/inspect
+/// 检查分享链接是否有效、是否需要提取码（不验证提取码、不转存）
+pub async fn inspect_share(
+    State(app_state): State<AppState>,
+    Json(req): Json<InspectShareRequest>,
+) -> Json<TransferApiResponse<ShareInspectResult>> {
+    let transfer_manager = {
+        let guard = app_state.transfer_manager.read().await;
+        match guard.clone() {
+            Some(tm) => tm,
+            None => {
+                error!("转存管理器未初始化");
+                return Json(TransferApiResponse::error(
+                    error_codes::MANAGER_NOT_READY,
+                    "转存管理器未初始化，请先登录",
+                ));
+            }
+        }
+    };
+
+    match transfer_manager.inspect_share(&req.share_url).await {
+        Ok(result) => Json(TransferApiResponse::success(result)),
+        Err(e) => {
+            error!("检查分享链接失败: {:?}", e);
+            Json(TransferApiResponse::error(-1, e.to_string()))
+        }
+    }
+}
+
 /// 浏览分享子目录请求
 #[derive(Debug, Deserialize)]
 pub struct PreviewShareDirRequest {
@@ -485,12 +824,28 @@ pub async fn preview_share_dir(
     let num = req.num.unwrap_or(100);
 
     match transfer_manager
-        .preview_share_dir(&req.short_key, &req.shareid, &req.uk, &req.bdstoken, &req.dir, page, num)
+        .preview_share_dir(
+            &req.short_key,
+            &req.shareid,
+            &req.uk,
+            &req.bdstoken,
+            &req.dir,
+            page,
+            num,
+        )
         .await
     {
         Ok(files) => {
-            info!("浏览分享子目录成功: {} 个文件, dir={}", files.len(), req.dir);
-            Json(TransferApiResponse::success(PreviewShareResponse { files, share_info: None }))
+            info!(
+                "浏览分享子目录成功: {} 个文件, dir={}",
+                files.len(),
+                req.dir
+            );
+            Json(TransferApiResponse::success(PreviewShareResponse {
+                files,
+                share_info: None,
+                captcha: None,
+            }))
         }
         Err(e) => {
             let err_msg = e.to_string();
@@ -1,7 +1,10 @@
 use crate::filesystem::{FilesystemConfig, PathGuard};
 use crate::server::error::{ApiError, ApiResult};
 use crate::server::AppState;
-use crate::uploader::{ScanOptions, ScanTaskStatus, UploadConflictStrategy, UploadTask};
+use crate::uploader::{
+    RapidUploadProbeResult, ScanOptions, ScanTaskStatus, UploadConflictStrategy, UploadGroupProgress,
+    UploadTask,
+};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -32,12 +35,17 @@ pub struct CreateUploadRequest {
 #[derive(Debug, Deserialize)]
 pub struct CreateFolderUploadRequest {
     /// 本地文件夹路径
+    #[serde(alias = "local_dir")]
     pub local_folder: String,
     /// 网盘目标文件夹路径
+    #[serde(alias = "remote_dir")]
     pub remote_folder: String,
     /// 扫描选项（可选）
     #[serde(default)]
     pub scan_options: Option<FolderScanOptions>,
+    /// 是否跳过隐藏文件（可选，优先于 scan_options，未指定则使用 UploadConfig::skip_hidden_files）
+    #[serde(default)]
+    pub skip_hidden: Option<bool>,
     /// 是否启用加密（可选，默认 false）
     #[serde(default)]
     pub encrypt: bool,
@@ -56,13 +64,9 @@ pub struct FolderScanOptions {
     pub max_file_size: Option<u64>,
     /// 最大文件数量
     pub max_files: Option<usize>,
-    /// 跳过隐藏文件
-    #[serde(default = "default_skip_hidden")]
-    pub skip_hidden: bool,
-}
-
-fn default_skip_hidden() -> bool {
-    true
+    /// 跳过隐藏文件（未指定则使用 UploadConfig::skip_hidden_files）
+    #[serde(default)]
+    pub skip_hidden: Option<bool>,
 }
 
 fn create_path_guard(config: &FilesystemConfig) -> PathGuard {
@@ -105,7 +109,7 @@ impl From<FolderScanOptions> for ScanOptions {
             follow_symlinks: options.follow_symlinks,
             max_file_size: options.max_file_size,
             max_files: options.max_files,
-            skip_hidden: options.skip_hidden,
+            skip_hidden: options.skip_hidden.unwrap_or_default(),
             allowed_paths: vec![],
         }
     }
@@ -139,6 +143,9 @@ pub struct ScanStatusResponse {
     pub created_tasks: usize,
     pub skipped_duplicates: usize,
     pub total_size: u64,
+    /// 已创建上传任务的聚合进度（尚未创建任何任务时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_progress: Option<UploadGroupProgress>,
 }
 
 /// POST /api/v1/uploads
@@ -194,6 +201,42 @@ pub async fn create_upload(
     }
 }
 
+/// 秒传预检请求
+#[derive(Debug, Deserialize)]
+pub struct CheckRapidUploadRequest {
+    /// 本地文件路径
+    pub local_path: String,
+}
+
+/// POST /api/v1/uploads/check-rapid
+/// 秒传预检：计算哈希并调用 precreate，只返回是否命中秒传，不创建上传任务
+/// 命中秒传时网盘会创建探测文件，预检后立即删除（删除的探测文件会出现在回收站）
+pub async fn check_rapid_upload(
+    State(app_state): State<AppState>,
+    Json(req): Json<CheckRapidUploadRequest>,
+) -> ApiResult<Json<ApiResponse<RapidUploadProbeResult>>> {
+    let upload_manager = app_state
+        .upload_manager
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| ApiError::Internal(anyhow::anyhow!("上传管理器未初始化")))?;
+
+    let local_path = {
+        let config = app_state.config.read().await;
+        let guard = create_path_guard(&config.filesystem);
+        validate_upload_file_path(&guard, &req.local_path)?
+    };
+
+    match upload_manager.check_rapid_upload(&local_path).await {
+        Ok(result) => Ok(Json(ApiResponse::success(result))),
+        Err(e) => {
+            error!("秒传预检失败: {:?}", e);
+            Err(ApiError::Internal(anyhow::anyhow!(e.to_string())))
+        }
+    }
+}
+
 /// POST /api/v1/uploads/folder
 /// 创建文件夹上传任务（异步扫描模式）
 pub async fn create_folder_upload(
@@ -211,7 +254,10 @@ pub async fn create_folder_upload(
     // 获取配置
     let config = app_state.config.read().await;
     let guard = create_path_guard(&config.filesystem);
-    let skip_hidden_files = config.upload.skip_hidden_files;
+    let skip_hidden_files = req
+        .skip_hidden
+        .or(req.scan_options.as_ref().and_then(|o| o.skip_hidden))
+        .unwrap_or(config.upload.skip_hidden_files);
     // 如果未指定策略，从 AppConfig 读取默认值
     let conflict_strategy = req
         .conflict_strategy
@@ -236,6 +282,7 @@ pub async fn create_folder_upload(
 
     let scan_options = if let Some(opts) = req.scan_options {
         let mut opts: ScanOptions = opts.into();
+        opts.skip_hidden = skip_hidden_files;
         opts.allowed_paths = symlink_allowed_paths;
         Some(opts)
     } else {
@@ -346,6 +393,30 @@ pub async fn get_all_uploads(
     Ok(Json(ApiResponse::success(tasks)))
 }
 
+/// GET /api/v1/uploads/history?page=&size=&status=&q=
+/// 分页查询上传历史（直接查询历史数据库）
+pub async fn get_upload_history(
+    State(app_state): State<AppState>,
+    axum::extract::Query(params): axum::extract::Query<HistoryQueryParams>,
+) -> Result<Json<ApiResponse<HistoryPageResponse<UploadTask>>>, StatusCode> {
+    let upload_manager = app_state
+        .upload_manager
+        .read()
+        .await
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let query = params.to_query();
+    let size = query.limit;
+    let (tasks, total) = upload_manager.query_history(query).await;
+    Ok(Json(ApiResponse::success(HistoryPageResponse {
+        total,
+        page: params.page.max(1),
+        size,
+        tasks,
+    })))
+}
+
 /// GET /api/v1/uploads/:id
 /// 获取指定上传任务
 pub async fn get_upload(
@@ -493,6 +564,10 @@ pub async fn get_scan_status(
 
     match scan_manager.get_scan_status(&scan_task_id) {
         Some(info) => {
+            let upload_progress = match app_state.upload_manager.read().await.clone() {
+                Some(upload_manager) => upload_manager.get_group_progress(&info.scan_task_id).await,
+                None => None,
+            };
             let status_str = match info.status {
                 ScanTaskStatus::Scanning => "scanning",
                 ScanTaskStatus::Completed => "completed",
@@ -506,6 +581,7 @@ pub async fn get_scan_status(
                 created_tasks: info.created_tasks,
                 skipped_duplicates: info.skipped_duplicates,
                 total_size: info.total_size,
+                upload_progress,
             })))
         }
         None => Err(StatusCode::NOT_FOUND),
@@ -535,7 +611,10 @@ pub async fn cancel_scan(
 
 // ==================== 批量操作 ====================
 
-use super::common::{BatchOperationItem, BatchOperationRequest, BatchOperationResponse};
+use super::common::{
+    BatchOperationItem, BatchOperationRequest, BatchOperationResponse, HistoryPageResponse,
+    HistoryQueryParams,
+};
 
 /// POST /api/v1/uploads/batch/pause
 pub async fn batch_pause_uploads(
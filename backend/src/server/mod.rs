@@ -1,8 +1,10 @@
 // Web服务器模块
 
+pub mod config_reload;
 pub mod error;
 pub mod events;
 pub mod handlers;
+pub mod read_only;
 pub mod state;
 pub mod websocket;
 
@@ -0,0 +1,155 @@
+// 只读（安全）模式
+//
+// 开启 security.read_only 后，拦截删除/移动/重命名网盘文件、回收站操作、删除本地文件、上传、转存、
+// 离线下载任务删除、取消分享、创建备份配置等破坏性操作，返回 403；下载、浏览等操作不受影响。
+// 修改 security 配置（包括关闭只读模式）同样被拒绝，由配置处理器检查请求体
+
+use crate::server::handlers::ApiResponse;
+use crate::server::AppState;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::warn;
+
+/// 只读模式拒绝提示
+pub const READ_ONLY_MESSAGE: &str = "只读模式已启用，禁止执行该操作";
+
+/// 是否启用了只读模式（供需要检查请求体的处理器复用）
+pub async fn is_read_only(app_state: &AppState) -> bool {
+    app_state.config.read().await.security.read_only
+}
+
+/// 查询参数中的布尔开关是否为 true
+fn query_flag(query: Option<&str>, name: &str) -> bool {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .any(|(key, value)| key == name && value == "true")
+}
+
+/// 判断请求是否为破坏性操作（路径为 /api/v1 下的相对路径）
+///
+/// 批量删除下载任务的 delete_files 位于请求体中，由处理器通过 [`is_read_only`] 自行检查；
+/// 导入任务列表时同理，由处理器逐个跳过其中的上传、转存任务（下载任务照常导入）
+pub fn is_destructive_request(method: &Method, path: &str, query: Option<&str>) -> bool {
+    let path = path.trim_end_matches('/');
+    match *method {
+        // 删除网盘文件 / 清空回收站
+        Method::DELETE if path == "/files" || path == "/recycle" => true,
+        // 移动、重命名网盘文件 / 从回收站还原（会覆盖或改变网盘现有文件）
+        Method::POST if matches!(path, "/files/move" | "/files/rename" | "/recycle/restore") => true,
+        // 删除、清空离线下载任务
+        Method::DELETE if path == "/cloud-dl/tasks" || path.starts_with("/cloud-dl/tasks/") => true,
+        // 取消分享
+        Method::DELETE if path == "/shares" => true,
+        Method::POST if path == "/shares/cancel" => true,
+        // 取消文件夹下载 / 按 group 批量删除任务，并删除本地文件
+        Method::DELETE
+            if path.starts_with("/downloads/folder/") || path.starts_with("/downloads/group/") =>
+        {
+            query_flag(query, "delete_files")
+        }
+        // 删除下载任务并删除本地文件
+        Method::DELETE if path.starts_with("/downloads/") => query_flag(query, "delete_file"),
+        // 创建上传任务（秒传检查、扫描状态等查询类接口仍允许）
+        Method::POST if matches!(path, "/uploads" | "/uploads/folder" | "/uploads/batch") => true,
+        // 转存（含分享直下，会先转存到网盘）/ 清理网盘中的转存临时目录
+        Method::POST
+            if matches!(
+                path,
+                "/transfers" | "/transfers/direct-download" | "/transfers/cleanup"
+            ) =>
+        {
+            true
+        }
+        // 创建自动备份配置（上传备份会写入网盘）
+        Method::POST if path == "/autobackup/configs" => true,
+        _ => false,
+    }
+}
+
+/// 只读模式中间件
+pub async fn read_only_middleware(
+    State(app_state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let destructive = is_destructive_request(
+        request.method(),
+        request.uri().path(),
+        request.uri().query(),
+    );
+    if destructive && is_read_only(&app_state).await {
+        warn!(
+            "只读模式拒绝请求: {} {}",
+            request.method(),
+            request.uri().path()
+        );
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error(403, READ_ONLY_MESSAGE.to_string())),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_destructive_request() {
+        assert!(is_destructive_request(&Method::DELETE, "/files", None));
+        assert!(is_destructive_request(&Method::DELETE, "/recycle", None));
+        assert!(is_destructive_request(&Method::POST, "/uploads", None));
+        assert!(is_destructive_request(&Method::POST, "/uploads/batch", None));
+        assert!(is_destructive_request(&Method::POST, "/transfers", None));
+        assert!(is_destructive_request(&Method::POST, "/transfers/cleanup", None));
+        assert!(is_destructive_request(&Method::POST, "/files/move", None));
+        assert!(is_destructive_request(&Method::POST, "/files/rename", None));
+        assert!(is_destructive_request(&Method::POST, "/recycle/restore", None));
+        assert!(is_destructive_request(&Method::DELETE, "/cloud-dl/tasks/123", None));
+        assert!(is_destructive_request(&Method::DELETE, "/cloud-dl/tasks/clear", None));
+        assert!(is_destructive_request(&Method::DELETE, "/shares", None));
+        assert!(is_destructive_request(&Method::POST, "/shares/cancel", None));
+        assert!(is_destructive_request(&Method::POST, "/autobackup/configs", None));
+        assert!(is_destructive_request(
+            &Method::DELETE,
+            "/downloads/abc",
+            Some("delete_file=true")
+        ));
+        assert!(is_destructive_request(
+            &Method::DELETE,
+            "/downloads/folder/abc",
+            Some("delete_files=true")
+        ));
+        assert!(is_destructive_request(
+            &Method::DELETE,
+            "/downloads/group/abc",
+            Some("delete_files=true")
+        ));
+
+        // 下载、浏览和仅删除任务记录不受影响
+        assert!(!is_destructive_request(&Method::GET, "/files", None));
+        assert!(!is_destructive_request(&Method::POST, "/downloads", None));
+        assert!(!is_destructive_request(&Method::POST, "/uploads/check-rapid", None));
+        assert!(!is_destructive_request(&Method::POST, "/transfers/preview", None));
+        assert!(!is_destructive_request(&Method::GET, "/cloud-dl/tasks", None));
+        assert!(!is_destructive_request(&Method::GET, "/shares", None));
+        assert!(!is_destructive_request(&Method::GET, "/autobackup/configs", None));
+        assert!(!is_destructive_request(&Method::DELETE, "/downloads/abc", None));
+        assert!(!is_destructive_request(&Method::DELETE, "/downloads/group/abc", None));
+        assert!(!is_destructive_request(
+            &Method::DELETE,
+            "/downloads/abc",
+            Some("delete_file=false")
+        ));
+    }
+}
@@ -14,11 +14,16 @@ use crate::persistence::{
     cleanup_completed_tasks, cleanup_invalid_tasks, scan_recoverable_tasks, DownloadRecoveryInfo,
     PersistenceManager, TransferRecoveryInfo, UploadRecoveryInfo,
 };
-use crate::server::websocket::WebSocketManager;
+use crate::server::events::ProgressRateLimiter;
+use crate::server::websocket::{WebSocketManager, WsServerMessage};
 use crate::transfer::TransferManager;
 use crate::uploader::{ScanManager, UploadManager};
+use anyhow::Context;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
 /// 应用全局状态
@@ -60,6 +65,40 @@ pub struct AppState {
     pub fallback_mgr: Arc<ProxyFallbackManager>,
     /// 🔥 扫描管理器（用户登录后创建）
     pub scan_manager: Arc<RwLock<Option<Arc<ScanManager>>>>,
+    /// 🔥 登录态是否有效（后台定期检测 BDUSS，失效时置为 false）
+    pub auth_valid: Arc<AtomicBool>,
+    /// 🔥 上次登录态检测时间（Unix 时间戳，秒）
+    pub auth_last_checked_at: Arc<RwLock<Option<i64>>>,
+    /// 🔥 启动时会话加载及全局服务启动是否已完成（未登录也视为完成）
+    pub session_loaded: Arc<AtomicBool>,
+    /// 🔥 登录态检测后台任务句柄
+    auth_check_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// 🔥 下载时间段检查后台任务句柄
+    schedule_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+/// 网络错误时登录态检测间隔的最大放大倍数
+const AUTH_CHECK_MAX_BACKOFF_FACTOR: u64 = 8;
+
+/// 下载时间段检查间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 关闭时等待分片写入结束的最长时间
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 计算下一次登录态检测的等待时间（秒）
+///
+/// - 检测有结果（有效/失效）：恢复为基础间隔
+/// - 网络错误：在当前间隔基础上翻倍退避，最多为基础间隔的 8 倍，避免断网时频繁请求
+fn next_auth_check_delay(base_secs: u64, current_secs: u64, network_error: bool) -> u64 {
+    if network_error {
+        current_secs
+            .max(base_secs)
+            .saturating_mul(2)
+            .min(base_secs.saturating_mul(AUTH_CHECK_MAX_BACKOFF_FACTOR))
+    } else {
+        base_secs
+    }
 }
 
 impl AppState {
@@ -68,6 +107,22 @@ impl AppState {
         // 加载配置
         let config = AppConfig::load_or_default("config/app.toml").await;
 
+        // 🔥 设置全局进度事件上限
+        ProgressRateLimiter::global().set_max_events_per_sec(config.server.max_events_per_sec);
+
+        // 🔥 设置网盘客户端 User-Agent 覆盖（需在创建任何 NetdiskClient 之前）
+        crate::netdisk::set_user_agent_overrides(
+            config.network.mobile_user_agent.clone(),
+            config.network.web_user_agent.clone(),
+        );
+
+        // 🔥 设置 HTTP 超时（需在创建任何 HTTP 客户端之前）
+        crate::common::set_http_timeouts(
+            config.network.connect_timeout_secs,
+            config.network.request_timeout_secs,
+            config.network.chunk_timeout_secs,
+        );
+
         // 创建文件夹下载管理器
         let folder_download_manager = Arc::new(FolderDownloadManager::new(
             config.download.download_dir.clone().into(),
@@ -103,9 +158,13 @@ impl AppState {
         // 🔥 创建代理故障回退管理器
         let fallback_mgr = Arc::new(ProxyFallbackManager::new());
 
+        // 🔥 创建会话管理器，按配置恢复激活账号
+        let mut session_manager = SessionManager::default();
+        session_manager.set_active_uid(config.account.active_uid);
+
         Ok(Self {
             qrcode_auth: Arc::new(RwLock::new(QRCodeAuth::new_with_proxy(proxy_config)?)),
-            session_manager: Arc::new(Mutex::new(SessionManager::default())),
+            session_manager: Arc::new(Mutex::new(session_manager)),
             current_user: Arc::new(RwLock::new(None)),
             netdisk_client: Arc::new(RwLock::new(None)),
             download_manager: Arc::new(RwLock::new(None)),
@@ -122,280 +181,587 @@ impl AppState {
             cloud_dl_monitor: Arc::new(RwLock::new(None)),
             fallback_mgr,
             scan_manager: Arc::new(RwLock::new(None)),
+            auth_valid: Arc::new(AtomicBool::new(true)),
+            auth_last_checked_at: Arc::new(RwLock::new(None)),
+            session_loaded: Arc::new(AtomicBool::new(false)),
+            auth_check_handle: Arc::new(Mutex::new(None)),
+            schedule_handle: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// 启动全局传输统计心跳
+    ///
+    /// 每次推送时重新读取管理器，登录/登出后无需重启
+    fn start_stats_heartbeat(&self) {
+        let download_manager = Arc::clone(&self.download_manager);
+        let upload_manager = Arc::clone(&self.upload_manager);
+
+        Arc::clone(&self.ws_manager).start_stats_sender(move || {
+            let download_manager = Arc::clone(&download_manager);
+            let upload_manager = Arc::clone(&upload_manager);
+            async move {
+                let download = download_manager.read().await.clone();
+                let upload = upload_manager.read().await.clone();
+                let stats = crate::server::handlers::collect_dashboard_stats(download, upload).await;
+                WsServerMessage::stats(
+                    stats.download.speed,
+                    stats.upload.speed,
+                    stats.download.active_tasks + stats.upload.active_tasks,
+                    stats.download.queued_tasks + stats.upload.queued_tasks,
+                )
+            }
+        });
+    }
+
     /// 初始化时加载会话
+    ///
+    /// 按配置中的激活账号加载会话并初始化账号资源，随后恢复任务并启动全局服务
     pub async fn load_initial_session(&self) -> anyhow::Result<()> {
-        // 🔥 获取持久化管理器的 Arc 引用（直接使用已启动的实例）
-        let pm_arc = Arc::clone(&self.persistence_manager);
+        let session = self.session_manager.lock().await.get_session().await?;
+        if let Some(user_auth) = session {
+            let (_, _, transfer_manager_arc) = self.activate_account(user_auth).await?;
 
-        let mut session_manager = self.session_manager.lock().await;
-        if let Some(mut user_auth) = session_manager.get_session().await? {
-            *self.current_user.write().await = Some(user_auth.clone());
+            // 🔥 启动时清理孤立临时目录（如果配置启用）
+            transfer_manager_arc.cleanup_orphaned_on_startup_if_enabled().await;
+        }
 
-            // 初始化网盘客户端
-            let config_guard = self.config.read().await;
-            let proxy_config_for_client = if config_guard.network.proxy.proxy_type != ProxyType::None
-                && !self.fallback_mgr.is_fallen_back()
-            {
-                Some(config_guard.network.proxy.clone())
-            } else {
-                None
-            };
-            drop(config_guard);
+        // 🔥 启动 WebSocket 批量发送器
+        Arc::clone(&self.ws_manager).start_batch_sender();
+        info!("WebSocket 批量发送器已启动");
 
-            let fallback_for_client = if proxy_config_for_client.is_some() {
-                Some(Arc::clone(&self.fallback_mgr))
-            } else {
-                None
-            };
-            let client = NetdiskClient::new_with_proxy(
-                user_auth.clone(),
-                proxy_config_for_client.as_ref(),
-                fallback_for_client.clone(),
-            )?;
+        // 🔥 启动 WebSocket 统计心跳（仅推送给订阅了 stats 的连接）
+        self.start_stats_heartbeat();
 
-            // 设置代理回退管理器的用户代理配置
-            // 🔥 始终从原始配置读取，而非 proxy_config_for_client（回退时后者为 None）
-            {
-                let cfg = self.config.read().await;
-                if cfg.network.proxy.proxy_type != ProxyType::None {
-                    self.fallback_mgr
-                        .set_user_proxy_config(Some(cfg.network.proxy.clone()))
-                        .await;
+        // 🔥 启动内存监控器
+        Arc::clone(&self.memory_monitor).start();
+        info!("内存监控器已启动");
+
+        // 🔥 初始化自动备份管理器
+        self.init_autobackup_manager().await;
+
+        // 🔥 启动登录态检测任务
+        self.start_auth_check_task().await;
+
+        // 🔥 启动下载时间段检查任务
+        self.start_schedule_task().await;
+
+        // 🔥 标记启动流程完成（/ready 据此判断就绪）
+        self.session_loaded.store(true, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// 🔥 启动下载时间段检查后台任务
+    ///
+    /// 定期按 `[schedule]` 配置暂停/恢复下载，下载管理器未初始化（未登录）时跳过
+    async fn start_schedule_task(&self) {
+        let state = self.clone();
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Some(dm) = state.download_manager.read().await.clone() {
+                    dm.apply_download_schedule().await;
                 }
             }
+        });
 
-            // 预热过期时间（2小时 = 7200秒）
-            const WARMUP_EXPIRE_SECS: i64 = 86400;
-
-            // 检查是否需要预热：
-            // 1. 预热数据不存在
-            // 2. 或者预热数据已过期（超过24小时）
-            //
-            let need_warmup = if user_auth.panpsc.is_none()
-                || user_auth.csrf_token.is_none()
-                || user_auth.bdstoken.is_none()
-            {
-                info!("服务启动检测到会话未预热,开始预热...");
-                true
-            } else if let Some(last_warmup) = user_auth.last_warmup_at {
-                let now = chrono::Utc::now().timestamp();
-                let elapsed = now - last_warmup;
-                if elapsed > WARMUP_EXPIRE_SECS {
-                    info!(
-                        "防止预热数据过期({}秒前),清除旧数据并重新预热...",
-                        elapsed
-                    );
-                    // 清除过期的预热数据
-                    user_auth.panpsc = None;
-                    user_auth.csrf_token = None;
-                    user_auth.bdstoken = None;
-                    true
-                } else {
-                    info!(
-                        "检测到已有预热 Cookie({}秒前预热),跳过预热",
-                        elapsed
-                    );
-                    false
+        if let Some(old) = self.schedule_handle.lock().await.replace(handle) {
+            old.abort();
+        }
+    }
+
+    /// 🔥 启动登录态检测后台任务
+    ///
+    /// 按 `auth.auth_check_interval_secs` 定期调用 verify_bduss，
+    /// BDUSS 失效时置 auth_valid 为 false 并广播 SessionExpired（仅在状态变化时广播一次）。
+    /// 网络错误不视为失效，按指数退避延后下一次检测。重复调用会替换旧任务
+    pub async fn start_auth_check_task(&self) {
+        let base_secs = self.config.read().await.auth.auth_check_interval_secs;
+        if base_secs == 0 {
+            info!("登录态检测已禁用");
+            return;
+        }
+
+        let state = self.clone();
+        let handle = tokio::spawn(async move {
+            let mut delay_secs = base_secs;
+            loop {
+                tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+                delay_secs = state.check_auth_once(base_secs, delay_secs).await;
+            }
+        });
+
+        if let Some(old) = self.auth_check_handle.lock().await.replace(handle) {
+            old.abort();
+        }
+        info!("登录态检测任务已启动，间隔 {} 秒", base_secs);
+    }
+
+    /// 执行一次登录态检测，返回下一次检测的等待时间（秒）
+    async fn check_auth_once(&self, base_secs: u64, current_secs: u64) -> u64 {
+        let user = match self.current_user.read().await.clone() {
+            Some(u) => u,
+            None => return base_secs,
+        };
+
+        let result = self
+            .qrcode_auth
+            .read()
+            .await
+            .verify_bduss(&user.bduss)
+            .await;
+
+        // 检测期间切换了账号或已登出，丢弃本次结果
+        let still_current = self
+            .current_user
+            .read()
+            .await
+            .as_ref()
+            .map(|u| u.uid == user.uid && u.bduss == user.bduss)
+            .unwrap_or(false);
+        if !still_current {
+            return base_secs;
+        }
+
+        *self.auth_last_checked_at.write().await = Some(chrono::Utc::now().timestamp());
+
+        match result {
+            Ok(true) => {
+                if !self.auth_valid.swap(true, Ordering::SeqCst) {
+                    info!("登录态已恢复有效: UID={}", user.uid);
                 }
-            } else {
-                // 有预热数据但没有时间戳（旧版本数据），执行预热
-                info!("预热数据缺少时间戳,重新预热...");
+                next_auth_check_delay(base_secs, current_secs, false)
+            }
+            Ok(false) => {
+                if self.auth_valid.swap(false, Ordering::SeqCst) {
+                    warn!("检测到登录态失效: UID={}，通知前端重新登录", user.uid);
+                    self.ws_manager.broadcast(WsServerMessage::session_expired(
+                        user.uid,
+                        "登录已过期，请重新登录",
+                    ));
+                }
+                next_auth_check_delay(base_secs, current_secs, false)
+            }
+            Err(e) => {
+                let next = next_auth_check_delay(base_secs, current_secs, true);
+                warn!("登录态检测遇到网络错误: {}，{} 秒后重试", e, next);
+                next
+            }
+        }
+    }
+
+    /// 🔥 初始化账号相关资源
+    ///
+    /// 创建网盘客户端（含会话预热）、下载/上传/扫描/转存管理器和离线下载监听服务，
+    /// 替换 AppState 中已有的实例。启动加载会话和切换账号时调用
+    async fn init_user_resources(
+        &self,
+        mut user_auth: UserAuth,
+    ) -> anyhow::Result<(Arc<DownloadManager>, Arc<UploadManager>, Arc<TransferManager>)> {
+        // 🔥 停用旧账号的管理器，持久化管理器切换到该账号的目录
+        self.prepare_account(user_auth.uid).await;
+
+        let pm_arc = Arc::clone(&self.persistence_manager);
+
+        // 🔥 记录激活账号，重启后自动加载
+        self.persist_active_uid(Some(user_auth.uid)).await;
+
+        *self.current_user.write().await = Some(user_auth.clone());
+        self.auth_valid.store(true, Ordering::SeqCst);
+
+        // 初始化网盘客户端
+        let config_guard = self.config.read().await;
+        let proxy_config_for_client = if config_guard.network.proxy.proxy_type != ProxyType::None
+            && !self.fallback_mgr.is_fallen_back()
+        {
+            Some(config_guard.network.proxy.clone())
+        } else {
+            None
+        };
+        drop(config_guard);
+
+        let fallback_for_client = if proxy_config_for_client.is_some() {
+            Some(Arc::clone(&self.fallback_mgr))
+        } else {
+            None
+        };
+        let client = NetdiskClient::new_with_proxy(
+            user_auth.clone(),
+            proxy_config_for_client.as_ref(),
+            fallback_for_client.clone(),
+        )?;
+
+        // 设置代理回退管理器的用户代理配置
+        // 🔥 始终从原始配置读取，而非 proxy_config_for_client（回退时后者为 None）
+        {
+            let cfg = self.config.read().await;
+            if cfg.network.proxy.proxy_type != ProxyType::None {
+                self.fallback_mgr
+                    .set_user_proxy_config(Some(cfg.network.proxy.clone()))
+                    .await;
+            }
+        }
+
+        // 预热过期时间（2小时 = 7200秒）
+        const WARMUP_EXPIRE_SECS: i64 = 86400;
+
+        // 检查是否需要预热：
+        // 1. 预热数据不存在
+        // 2. 或者预热数据已过期（超过24小时）
+        //
+        let need_warmup = if user_auth.panpsc.is_none()
+            || user_auth.csrf_token.is_none()
+            || user_auth.bdstoken.is_none()
+        {
+            info!("服务启动检测到会话未预热,开始预热...");
+            true
+        } else if let Some(last_warmup) = user_auth.last_warmup_at {
+            let now = chrono::Utc::now().timestamp();
+            let elapsed = now - last_warmup;
+            if elapsed > WARMUP_EXPIRE_SECS {
+                info!(
+                    "防止预热数据过期({}秒前),清除旧数据并重新预热...",
+                    elapsed
+                );
+                // 清除过期的预热数据
                 user_auth.panpsc = None;
                 user_auth.csrf_token = None;
                 user_auth.bdstoken = None;
                 true
-            };
+            } else {
+                info!(
+                    "检测到已有预热 Cookie({}秒前预热),跳过预热",
+                    elapsed
+                );
+                false
+            }
+        } else {
+            // 有预热数据但没有时间戳（旧版本数据），执行预热
+            info!("预热数据缺少时间戳,重新预热...");
+            user_auth.panpsc = None;
+            user_auth.csrf_token = None;
+            user_auth.bdstoken = None;
+            true
+        };
 
-            if need_warmup {
-                match client.warmup_and_get_cookies().await {
-                    Ok((panpsc, csrf_token, bdstoken, stoken)) => {
-                        info!("预热成功,更新 session.json");
-                        if panpsc.is_some() {
-                            user_auth.panpsc = panpsc;
-                        }
-                        if csrf_token.is_some() {
-                            user_auth.csrf_token = csrf_token;
-                        }
-                        user_auth.bdstoken = bdstoken;
-                        user_auth.last_warmup_at = Some(chrono::Utc::now().timestamp());
-                        // 预热时下发的 STOKEN 优先于之前保存的
-                        if stoken.is_some() {
-                            user_auth.stoken = stoken;
-                        }
-
-                        // 更新内存中的用户信息
-                        *self.current_user.write().await = Some(user_auth.clone());
-
-                        // 保存到 session.json
-                        if let Err(e) = session_manager.save_session(&user_auth).await {
-                            error!("保存预热 Cookie 失败: {}", e);
-                        }
+        if need_warmup {
+            match client.warmup_and_get_cookies().await {
+                Ok((panpsc, csrf_token, bdstoken, stoken)) => {
+                    info!("预热成功,更新 session.json");
+                    if panpsc.is_some() {
+                        user_auth.panpsc = panpsc;
                     }
-                    Err(e) => {
-                        warn!("预热失败(可能需要重新登录): {}", e);
+                    if csrf_token.is_some() {
+                        user_auth.csrf_token = csrf_token;
+                    }
+                    user_auth.bdstoken = bdstoken;
+                    user_auth.last_warmup_at = Some(chrono::Utc::now().timestamp());
+                    // 预热时下发的 STOKEN 优先于之前保存的
+                    if stoken.is_some() {
+                        user_auth.stoken = stoken;
                     }
-                }
-            }
-
-            let client_arc = Arc::new(client.clone());
-            *self.netdisk_client.write().await = Some(client.clone());
-
-            // 初始化下载管理器（从配置读取参数）
-            let config = self.config.read().await;
-            let download_dir = config.download.download_dir.clone();
-            let max_global_threads = config.download.max_global_threads;
-            let max_concurrent_tasks = config.download.max_concurrent_tasks;
-            let max_retries = config.download.max_retries;
-            drop(config);
-
-            let mut manager = DownloadManager::with_config(
-                user_auth.clone(),
-                download_dir,
-                max_global_threads,
-                max_concurrent_tasks,
-                max_retries,
-                proxy_config_for_client.as_ref(),
-                fallback_for_client,
-            )?;
 
-            // 🔥 设置持久化管理器
-            manager.set_persistence_manager(Arc::clone(&pm_arc));
+                    // 更新内存中的用户信息
+                    *self.current_user.write().await = Some(user_auth.clone());
 
-            // 🔥 设置 WebSocket 管理器
-            manager.set_ws_manager(Arc::clone(&self.ws_manager)).await;
+                    // 保存到 session.json
+                    if let Err(e) = self.session_manager.lock().await.save_session(&user_auth).await {
+                        error!("保存预热 Cookie 失败: {}", e);
+                    }
+                }
+                Err(e) => {
+                    warn!("预热失败(可能需要重新登录): {}", e);
+                }
+            }
+        }
 
-            let manager_arc = Arc::new(manager);
-            *self.download_manager.write().await = Some(Arc::clone(&manager_arc));
+        let client_arc = Arc::new(client.clone());
+        *self.netdisk_client.write().await = Some(client.clone());
 
-            // 设置文件夹下载管理器的依赖
-            self.folder_download_manager
-                .set_download_manager(Arc::clone(&manager_arc))
-                .await;
-            self.folder_download_manager
-                .set_netdisk_client(client_arc)
-                .await;
+        // 初始化下载管理器（从配置读取参数）
+        let config = self.config.read().await;
+        let download_dir = config.download.download_dir.clone();
+        let max_global_threads = config.download.max_global_threads;
+        let max_concurrent_tasks = config.download.max_concurrent_tasks;
+        let retry_config = config.download.chunk_retry_config();
+        let global_speed_limit_kbps = config.download.global_speed_limit_kbps;
+        let verify_md5_after_download = config.download.verify_md5_after_download;
+        let max_task_retries = config.download.max_retries;
+        let rate_limit_config = config.download.rate_limit.clone();
+        let adaptive_concurrency_config = config.download.adaptive_concurrency.clone();
+        let completion_webhook_url = config.download.completion_webhook_url.clone();
+        let completion_webhook_secret = config.download.completion_webhook_secret.clone();
+        let schedule_config = config.schedule.clone();
+        let min_free_space_mb = config.download.min_free_space_mb;
+        let max_chunks_per_task = config.download.max_chunks_per_task;
+        let dedup_local = config.download.dedup_local;
+        let dedup_hardlink = config.download.dedup_hardlink;
+        let max_chunks_per_host = config.download.max_chunks_per_host;
+        let max_stall_secs = config.download.max_stall_secs;
+        let type_dirs = config.download.type_dirs.clone();
+        let filesystem_config = config.filesystem.clone();
+        drop(config);
 
-            // 🔥 设置文件夹下载管理器的 WAL 目录（用于文件夹持久化）
-            let wal_dir = pm_arc.lock().await.wal_dir().clone();
-            self.folder_download_manager.set_wal_dir(wal_dir.clone()).await;
+        let mut manager = DownloadManager::with_config(
+            user_auth.clone(),
+            download_dir,
+            max_global_threads,
+            max_concurrent_tasks,
+            retry_config,
+            proxy_config_for_client.as_ref(),
+            fallback_for_client,
+        )?;
+
+        // 🔥 设置持久化管理器
+        manager.set_persistence_manager(Arc::clone(&pm_arc));
+
+        // 🔥 设置 WebSocket 管理器
+        manager.set_ws_manager(Arc::clone(&self.ws_manager)).await;
+
+        // 🔥 设置全局限速和 MD5 校验开关
+        manager.update_global_speed_limit(global_speed_limit_kbps);
+        manager.update_verify_md5(verify_md5_after_download);
+        manager.update_max_task_retries(max_task_retries);
+        manager.update_rate_limit_config(rate_limit_config);
+        manager.update_adaptive_concurrency_config(adaptive_concurrency_config);
+        manager.update_completion_webhook(completion_webhook_url, completion_webhook_secret);
+        manager.update_download_schedule(&schedule_config);
+        manager.update_min_free_space_mb(min_free_space_mb);
+        manager.update_max_chunks_per_task(max_chunks_per_task);
+        manager.update_dedup_local(dedup_local, dedup_hardlink);
+        manager.update_max_chunks_per_host(max_chunks_per_host);
+        manager.update_max_stall_secs(max_stall_secs);
+        manager.update_type_dirs(type_dirs).await;
+        manager.load_dedup_index(pm_arc.lock().await.wal_dir());
+        manager.update_filesystem_config(filesystem_config).await;
+
+        let manager_arc = Arc::new(manager);
+        *self.download_manager.write().await = Some(Arc::clone(&manager_arc));
+
+        // 设置文件夹下载管理器的依赖
+        self.folder_download_manager
+            .set_download_manager(Arc::clone(&manager_arc))
+            .await;
+        self.folder_download_manager
+            .set_netdisk_client(client_arc)
+            .await;
+
+        // 🔥 设置文件夹下载管理器的 WAL 目录（用于文件夹持久化）
+        let wal_dir = pm_arc.lock().await.wal_dir().clone();
+        self.folder_download_manager.set_wal_dir(wal_dir.clone()).await;
+
+        // 🔥 设置文件夹下载管理器的持久化管理器（用于加载历史文件夹）
+        self.folder_download_manager
+            .set_persistence_manager(Arc::clone(&pm_arc))
+            .await;
+
+        // 🔥 设置文件夹下载管理器的 WebSocket 管理器
+        self.folder_download_manager
+            .set_ws_manager(Arc::clone(&self.ws_manager))
+            .await;
+
+        // 🔥 设置下载管理器对文件夹管理器的引用（用于回收借调槽位）
+        manager_arc
+            .set_folder_manager(Arc::clone(&self.folder_download_manager))
+            .await;
+
+        // 初始化上传管理器（从配置读取参数）
+        let config = self.config.read().await;
+        let upload_config = config.upload.clone();
+        let transfer_config = config.transfer.clone();
+        drop(config);
 
-            // 🔥 设置文件夹下载管理器的持久化管理器（用于加载历史文件夹）
-            self.folder_download_manager
-                .set_persistence_manager(Arc::clone(&pm_arc))
-                .await;
+        // 🔥 配置目录（用于读取 encryption.json）
+        let config_dir = std::path::Path::new("config");
+        let upload_manager =
+            UploadManager::new_with_config(client.clone(), &user_auth, &upload_config, config_dir);
+        let upload_manager_arc = Arc::new(upload_manager);
 
-            // 🔥 设置文件夹下载管理器的 WebSocket 管理器
-            self.folder_download_manager
-                .set_ws_manager(Arc::clone(&self.ws_manager))
-                .await;
+        // 🔥 设置持久化管理器
+        upload_manager_arc
+            .set_persistence_manager(Arc::clone(&pm_arc))
+            .await;
 
-            // 🔥 设置下载管理器对文件夹管理器的引用（用于回收借调槽位）
-            manager_arc
-                .set_folder_manager(Arc::clone(&self.folder_download_manager))
-                .await;
+        // 🔥 设置上传管理器的 WebSocket 管理器
+        upload_manager_arc
+            .set_ws_manager(Arc::clone(&self.ws_manager))
+            .await;
 
-            // 初始化上传管理器（从配置读取参数）
-            let config = self.config.read().await;
-            let upload_config = config.upload.clone();
-            let transfer_config = config.transfer.clone();
-            drop(config);
+        // 🔥 设置备份记录管理器（用于文件夹名加密映射）
+        upload_manager_arc
+            .set_backup_record_manager(Arc::clone(&self.backup_record_manager))
+            .await;
 
-            // 🔥 配置目录（用于读取 encryption.json）
-            let config_dir = std::path::Path::new("config");
-            let upload_manager =
-                UploadManager::new_with_config(client.clone(), &user_auth, &upload_config, config_dir);
-            let upload_manager_arc = Arc::new(upload_manager);
+        *self.upload_manager.write().await = Some(Arc::clone(&upload_manager_arc));
 
-            // 🔥 设置持久化管理器
-            upload_manager_arc
-                .set_persistence_manager(Arc::clone(&pm_arc))
-                .await;
+        // 🔥 初始化扫描管理器
+        let config = self.config.read().await;
+        let max_pending = config.scan.max_pending_tasks;
+        drop(config);
+        let scan_mgr = ScanManager::new(
+            Arc::clone(&upload_manager_arc),
+            Arc::clone(&self.ws_manager),
+            Arc::clone(&self.memory_monitor),
+            wal_dir.clone(),
+            max_pending,
+        );
+        *self.scan_manager.write().await = Some(Arc::new(scan_mgr));
+        info!("扫描管理器初始化完成");
+
+        // 初始化转存管理器
+        let transfer_manager =
+            TransferManager::new(Arc::new(std::sync::RwLock::new(client)), transfer_config, Arc::clone(&self.config));
+        let transfer_manager_arc = Arc::new(transfer_manager);
+
+        // 设置下载管理器（用于自动下载功能）
+        transfer_manager_arc
+            .set_download_manager(Arc::clone(&manager_arc))
+            .await;
+
+        // 设置文件夹下载管理器（用于自动下载文件夹）
+        transfer_manager_arc
+            .set_folder_download_manager(Arc::clone(&self.folder_download_manager))
+            .await;
+
+        // 🔥 设置持久化管理器
+        transfer_manager_arc
+            .set_persistence_manager(Arc::clone(&pm_arc))
+            .await;
+
+        // 🔥 设置转存管理器的 WebSocket 管理器
+        transfer_manager_arc
+            .set_ws_manager(Arc::clone(&self.ws_manager))
+            .await;
+
+        *self.transfer_manager.write().await = Some(Arc::clone(&transfer_manager_arc));
+        info!("转存管理器初始化完成");
+
+        // 🔥 初始化离线下载监听服务（切换账号时先停止旧账号的监听）
+        if let Some(old_monitor) = self.cloud_dl_monitor.write().await.take() {
+            old_monitor.stop();
+        }
+        self.init_cloud_dl_monitor().await;
 
-            // 🔥 设置上传管理器的 WebSocket 管理器
-            upload_manager_arc
-                .set_ws_manager(Arc::clone(&self.ws_manager))
-                .await;
+        Ok((manager_arc, upload_manager_arc, transfer_manager_arc))
+    }
 
-            // 🔥 设置备份记录管理器（用于文件夹名加密映射）
-            upload_manager_arc
-                .set_backup_record_manager(Arc::clone(&self.backup_record_manager))
-                .await;
+    /// 🔥 为账号准备持久化目录
+    ///
+    /// WAL 和历史记录按 UID 隔离。持久化管理器已属于该账号时跳过；否则先暂停并停用旧账号的
+    /// 下载/上传/扫描/转存管理器（进度写入旧账号的 WAL），清空内存中的文件夹，再关闭旧的持久化管理器
+    /// 并原地替换为该账号的实例，所有持有 `persistence_manager` 引用的模块随之使用新目录
+    async fn prepare_account(&self, uid: u64) {
+        if self.persistence_manager.lock().await.account_uid() == Some(uid) {
+            return;
+        }
 
-            *self.upload_manager.write().await = Some(Arc::clone(&upload_manager_arc));
-
-            // 🔥 初始化扫描管理器
-            let config = self.config.read().await;
-            let max_pending = config.scan.max_pending_tasks;
-            drop(config);
-            let scan_mgr = ScanManager::new(
-                Arc::clone(&upload_manager_arc),
-                Arc::clone(&self.ws_manager),
-                Arc::clone(&self.memory_monitor),
-                wal_dir.clone(),
-                max_pending,
+        let old_download = self.download_manager.write().await.take();
+        if let Some(dm) = old_download {
+            let (interrupted, unfinished) = dm.shutdown(SHUTDOWN_DRAIN_TIMEOUT).await;
+            info!(
+                "旧账号下载管理器已停用: 中断任务 {} 个, 未退出分片 {} 个",
+                interrupted, unfinished
             );
-            *self.scan_manager.write().await = Some(Arc::new(scan_mgr));
-            info!("扫描管理器初始化完成");
-
-            // 初始化转存管理器
-            let transfer_manager =
-                TransferManager::new(Arc::new(std::sync::RwLock::new(client)), transfer_config, Arc::clone(&self.config));
-            let transfer_manager_arc = Arc::new(transfer_manager);
+        }
+        let old_upload = self.upload_manager.write().await.take();
+        if let Some(um) = old_upload {
+            let (interrupted, unfinished) = um.shutdown(SHUTDOWN_DRAIN_TIMEOUT).await;
+            info!(
+                "旧账号上传管理器已停用: 中断任务 {} 个, 未退出分片 {} 个",
+                interrupted, unfinished
+            );
+        }
+        let old_scan = self.scan_manager.write().await.take();
+        if let Some(sm) = old_scan {
+            info!("旧账号扫描管理器已停用: 取消扫描 {} 个", sm.shutdown());
+        }
+        let old_transfer = self.transfer_manager.write().await.take();
+        if let Some(tm) = old_transfer {
+            info!("旧账号转存管理器已停用: 中断任务 {} 个", tm.shutdown());
+        }
+        self.folder_download_manager.clear_folders().await;
 
-            // 设置下载管理器（用于自动下载功能）
-            transfer_manager_arc
-                .set_download_manager(Arc::clone(&manager_arc))
-                .await;
+        let persistence_config = self.config.read().await.persistence.clone();
+        let mut pm = self.persistence_manager.lock().await;
+        pm.shutdown().await;
+        let mut account_pm =
+            PersistenceManager::new_for_account(persistence_config, std::path::Path::new("."), uid);
+        account_pm.start();
+        *pm = account_pm;
+        info!("持久化管理器已切换到账号 {}: {:?}", uid, pm.wal_dir());
+    }
 
-            // 设置文件夹下载管理器（用于自动下载文件夹）
-            transfer_manager_arc
-                .set_folder_download_manager(Arc::clone(&self.folder_download_manager))
+    /// 🔥 激活账号（启动加载、扫码登录和切换账号的公共流程）
+    ///
+    /// 停用旧账号的全部管理器并切换持久化目录，初始化该账号的网盘客户端和各管理器，
+    /// 恢复该账号目录中的持久化任务，已初始化的自动备份管理器改用新账号的管理器
+    pub async fn activate_account(
+        &self,
+        user_auth: UserAuth,
+    ) -> anyhow::Result<(Arc<DownloadManager>, Arc<UploadManager>, Arc<TransferManager>)> {
+        let (manager_arc, upload_manager_arc, transfer_manager_arc) =
+            self.init_user_resources(user_auth).await?;
+
+        // 🔥 恢复该账号的任务（WAL 按账号隔离，激活前不在内存中）
+        self.recover_tasks(
+            &manager_arc,
+            &upload_manager_arc,
+            &transfer_manager_arc,
+            &self.persistence_manager,
+        )
+        .await;
+
+        // 🔥 自动备份管理器改用新账号的管理器
+        if let Some(ref autobackup) = *self.autobackup_manager.read().await {
+            autobackup.set_upload_manager(Arc::clone(&upload_manager_arc));
+            autobackup.set_download_manager(Arc::clone(&manager_arc));
+
+            let encryption_config_store = autobackup.get_encryption_config_store();
+            manager_arc
+                .set_snapshot_manager(Arc::clone(&self.snapshot_manager))
                 .await;
-
-            // 🔥 设置持久化管理器
-            transfer_manager_arc
-                .set_persistence_manager(Arc::clone(&pm_arc))
+            manager_arc
+                .set_encryption_config_store(encryption_config_store)
                 .await;
-
-            // 🔥 设置转存管理器的 WebSocket 管理器
-            transfer_manager_arc
-                .set_ws_manager(Arc::clone(&self.ws_manager))
+            upload_manager_arc
+                .set_snapshot_manager(Arc::clone(&self.snapshot_manager))
                 .await;
+        }
 
-            *self.transfer_manager.write().await = Some(Arc::clone(&transfer_manager_arc));
-            info!("转存管理器初始化完成");
-
-            // 🔥 初始化离线下载监听服务
-            self.init_cloud_dl_monitor().await;
-
-            // 🔥 恢复任务
-            self.recover_tasks(
-                &manager_arc,
-                &upload_manager_arc,
-                &transfer_manager_arc,
-                &pm_arc,
-            )
-                .await;
+        Ok((manager_arc, upload_manager_arc, transfer_manager_arc))
+    }
 
-            // 🔥 启动时清理孤立临时目录（如果配置启用）
-            transfer_manager_arc.cleanup_orphaned_on_startup_if_enabled().await;
-        }
+    /// 🔥 切换到已保存的账号
+    ///
+    /// 切换会话后按新账号激活（停用旧账号的管理器、重建各管理器并恢复新账号的持久化任务）
+    pub async fn switch_account(&self, uid: u64) -> anyhow::Result<UserAuth> {
+        let user_auth = self.session_manager.lock().await.switch_session(uid).await?;
+        info!("切换账号: UID={}, 用户名={}", user_auth.uid, user_auth.username);
 
-        // 🔥 启动 WebSocket 批量发送器
-        Arc::clone(&self.ws_manager).start_batch_sender();
-        info!("WebSocket 批量发送器已启动");
+        self.activate_account(user_auth).await?;
 
-        // 🔥 启动内存监控器
-        Arc::clone(&self.memory_monitor).start();
-        info!("内存监控器已启动");
+        let current = self.current_user.read().await.clone();
+        current.context("切换账号后用户信息丢失")
+    }
 
-        // 🔥 初始化自动备份管理器
-        self.init_autobackup_manager().await;
+    /// 🔥 列出所有已保存会话的账号
+    pub async fn list_accounts(&self) -> anyhow::Result<Vec<UserAuth>> {
+        self.session_manager.lock().await.list_sessions().await
+    }
 
-        Ok(())
+    /// 🔥 持久化当前激活账号 UID 到配置文件
+    ///
+    /// 与当前配置相同时跳过写入，保存失败只记录日志
+    pub async fn persist_active_uid(&self, uid: Option<u64>) {
+        let mut config = self.config.write().await;
+        if config.account.active_uid == uid {
+            return;
+        }
+        config.account.active_uid = uid;
+        if let Err(e) = config.save_to_file("config/app.toml").await {
+            warn!("保存激活账号到配置失败: {}", e);
+        }
     }
 
     /// 🔥 恢复持久化的任务
@@ -411,6 +777,7 @@ impl AppState {
             info!("任务自动恢复已禁用");
             return;
         }
+        let verify_chunks = config.persistence.verify_chunks_on_recovery;
         drop(config);
 
         info!("开始扫描可恢复的任务...");
@@ -449,12 +816,17 @@ impl AppState {
 
                 // 恢复下载任务（子任务会关联到已恢复的文件夹）
                 if !scan_result.download_tasks.is_empty() {
-                    let recovery_infos: Vec<DownloadRecoveryInfo> = scan_result
+                    let mut recovery_infos: Vec<DownloadRecoveryInfo> = scan_result
                         .download_tasks
                         .iter()
                         .filter_map(|t| DownloadRecoveryInfo::from_recovered(t))
                         .collect();
 
+                    // 🔥 恢复前校验最近完成的分片，不一致的分片重新排队下载
+                    if verify_chunks {
+                        recovery_infos = verify_recovered_chunks(recovery_infos).await;
+                    }
+
                     let (success, failed) = download_manager.restore_tasks(recovery_infos).await;
                     info!("下载任务恢复完成: {} 成功, {} 失败", success, failed);
 
@@ -715,7 +1087,8 @@ impl AppState {
 
     /// 🔥 优雅关闭
     ///
-    /// 关闭持久化管理器，确保所有 WAL 数据刷写到磁盘
+    /// 停止启动新任务并等待进行中的下载/上传分片结束，关闭 WebSocket 连接，
+    /// 最后关闭持久化管理器，确保所有 WAL 数据刷写到磁盘
     pub async fn shutdown(&self) {
         info!("正在关闭应用状态...");
 
@@ -729,11 +1102,63 @@ impl AppState {
         self.memory_monitor.stop();
         info!("内存监控器已停止");
 
+        // 停止登录态检测任务
+        if let Some(handle) = self.auth_check_handle.lock().await.take() {
+            handle.abort();
+            info!("登录态检测任务已停止");
+        }
+
+        // 停止下载时间段检查任务
+        if let Some(handle) = self.schedule_handle.lock().await.take() {
+            handle.abort();
+        }
+
+        // 🔥 排空下载：中断进行中的任务，等待分片线程保存部分进度后退出
+        let (interrupted_tasks, unfinished_chunks) =
+            match self.download_manager.read().await.clone() {
+                Some(dm) => dm.prepare_shutdown(SHUTDOWN_DRAIN_TIMEOUT).await,
+                None => (0, 0),
+            };
+        if unfinished_chunks > 0 {
+            warn!(
+                "等待分片写入超时（{} 秒），仍有 {} 个分片未退出，未保存的进度将在重启后重新下载",
+                SHUTDOWN_DRAIN_TIMEOUT.as_secs(),
+                unfinished_chunks
+            );
+        }
+
+        // 🔥 排空上传：取消进行中的任务，等待分片线程退出（已完成的分片已写入 WAL）
+        let (interrupted_uploads, unfinished_upload_chunks) =
+            match self.upload_manager.read().await.clone() {
+                Some(um) => um.prepare_shutdown(SHUTDOWN_DRAIN_TIMEOUT).await,
+                None => (0, 0),
+            };
+        if unfinished_upload_chunks > 0 {
+            warn!(
+                "等待上传分片超时（{} 秒），仍有 {} 个分片未退出，未完成的分片将在重启后重新上传",
+                SHUTDOWN_DRAIN_TIMEOUT.as_secs(),
+                unfinished_upload_chunks
+            );
+        }
+
+        // 🔥 关闭 WebSocket 连接（发送 Close 帧）
+        let closed_connections = self.ws_manager.close_all();
+        self.ws_manager.stop_batch_sender();
+
         // 关闭持久化管理器
         let mut pm = self.persistence_manager.lock().await;
+        let persisted_tasks = pm.task_count();
         pm.shutdown().await;
 
-        info!("应用状态已安全关闭");
+        info!(
+            "应用状态已安全关闭: 中断下载任务 {} 个, 未退出分片 {} 个, 中断上传任务 {} 个, 未退出上传分片 {} 个, 已持久化任务 {} 个, 关闭 WebSocket 连接 {} 个",
+            interrupted_tasks,
+            unfinished_chunks,
+            interrupted_uploads,
+            unfinished_upload_chunks,
+            persisted_tasks,
+            closed_connections
+        );
     }
 }
 
@@ -857,3 +1282,65 @@ impl ProxyHotUpdater for AppState {
         }
     }
 }
+
+/// 崩溃恢复时每个下载任务校验的最近完成分片数
+const RECOVERY_VERIFY_CHUNKS: usize = 8;
+
+/// 校验恢复任务最近完成的分片（读取文件，在阻塞线程池中执行）
+async fn verify_recovered_chunks(
+    mut infos: Vec<DownloadRecoveryInfo>,
+) -> Vec<DownloadRecoveryInfo> {
+    let unverified = infos.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        for info in infos.iter_mut() {
+            if info.chunk_crcs.is_empty() {
+                continue;
+            }
+            // 下载中数据写入临时文件，临时文件不存在时回退到最终路径（与 restore_task 一致）
+            let temp_path = crate::downloader::DownloadTask::build_temp_path(&info.local_path);
+            let data_path = if temp_path.exists() {
+                temp_path
+            } else {
+                info.local_path.clone()
+            };
+            let mismatched = info.verify_recent_chunks(&data_path, RECOVERY_VERIFY_CHUNKS);
+            if !mismatched.is_empty() {
+                warn!(
+                    "任务 {} 有 {} 个分片校验失败，将重新下载: {:?}",
+                    info.task_id,
+                    mismatched.len(),
+                    mismatched
+                );
+            }
+        }
+        infos
+    })
+    .await;
+
+    match result {
+        Ok(infos) => infos,
+        Err(e) => {
+            error!("分片恢复校验任务异常，跳过校验: {}", e);
+            unverified
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_auth_check_delay() {
+        // 检测成功或确认失效：恢复基础间隔
+        assert_eq!(next_auth_check_delay(600, 2400, false), 600);
+
+        // 网络错误：翻倍退避
+        assert_eq!(next_auth_check_delay(600, 600, true), 1200);
+        assert_eq!(next_auth_check_delay(600, 1200, true), 2400);
+
+        // 退避上限为基础间隔的 8 倍
+        assert_eq!(next_auth_check_delay(600, 4800, true), 4800);
+        assert_eq!(next_auth_check_delay(600, 9999, true), 4800);
+    }
+}
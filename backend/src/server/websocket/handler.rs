@@ -4,16 +4,22 @@ use crate::server::websocket::message::{WsClientMessage, WsServerMessage};
 use crate::AppState;
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
+        ws::{close_code, CloseFrame, Message, WebSocket},
         State, WebSocketUpgrade,
     },
     response::IntoResponse,
 };
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// 服务端 Ping 间隔（秒）
+const PING_INTERVAL_SECS: u64 = 20;
+/// 连接超时时间（秒），超过该时长没有 Pong 或任何客户端消息则断开
+const PONG_TIMEOUT_SECS: u64 = 60;
+
 /// WebSocket 路由处理器
 ///
 /// 升级 HTTP 连接为 WebSocket，处理消息收发
@@ -45,22 +51,54 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     }
 
     let ws_manager = Arc::clone(&state.ws_manager);
-    let _conn_id = connection_id.clone();
+    let ws_manager_send = Arc::clone(&state.ws_manager);
+    let conn_id_send = connection_id.clone();
 
     // 启动发送任务
     let send_task = tokio::spawn(async move {
-        while let Some(message) = message_receiver.recv().await {
-            match serde_json::to_string(&message) {
-                Ok(json) => {
-                    if sender.send(Message::Text(json)).await.is_err() {
+        // 🔥 定时 Ping，客户端休眠/断网后不再回 Pong，超时即断开，避免向僵尸连接持续推送
+        let ping_period = Duration::from_secs(PING_INTERVAL_SECS);
+        let mut ping_interval =
+            tokio::time::interval_at(tokio::time::Instant::now() + ping_period, ping_period);
+        let mut close_reason = "服务器正在关闭";
+
+        loop {
+            tokio::select! {
+                message = message_receiver.recv() => {
+                    let Some(message) = message else {
                         break;
+                    };
+                    match serde_json::to_string(&message) {
+                        Ok(json) => {
+                            if sender.send(Message::Text(json)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            error!("序列化消息失败: {}", e);
+                        }
                     }
                 }
-                Err(e) => {
-                    error!("序列化消息失败: {}", e);
+                _ = ping_interval.tick() => {
+                    if ws_manager_send.is_stale(&conn_id_send, Duration::from_secs(PONG_TIMEOUT_SECS)) {
+                        warn!("WebSocket 连接 {} 秒未响应，断开: {}", PONG_TIMEOUT_SECS, conn_id_send);
+                        close_reason = "连接超时";
+                        break;
+                    }
+                    if sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
                 }
             }
         }
+
+        // 🔥 连接被服务端移除（如服务关闭）时发送 Close 帧，前端据此走正常的断线重连
+        let _ = sender
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::AWAY,
+                reason: close_reason.into(),
+            })))
+            .await;
     });
 
     let state_recv = state.clone();
@@ -132,6 +170,14 @@ async fn handle_client_message(state: &AppState, connection_id: &str, text: &str
                 let pong = WsServerMessage::pong(Some(timestamp));
                 state.ws_manager.send_to(connection_id, pong);
             }
+            WsClientMessage::Resume { since_seq } => {
+                debug!("收到事件回放请求: {} - since_seq={}", connection_id, since_seq);
+                if state.ws_manager.replay_since(connection_id, since_seq).is_none() {
+                    info!("事件已超出回放范围，改为发送状态快照: {}", connection_id);
+                    let snapshot = get_snapshot(state).await;
+                    state.ws_manager.send_to(connection_id, snapshot);
+                }
+            }
             WsClientMessage::RequestSnapshot => {
                 debug!("收到状态快照请求: {}", connection_id);
                 let snapshot = get_snapshot(state).await;
@@ -6,11 +6,13 @@
 //! - 订阅管理：支持通配符匹配（如 `download:*`）
 //! - 反向索引优化：高并发场景性能提升
 //! - 节流机制：按 event_type:task_id 分桶，避免事件覆盖
+//! - 事件回放：保留最近的事件，重连客户端可按 event_id 补发断线期间的事件
 
-use crate::server::events::{EventPriority, TaskEvent, TimestampedEvent};
+use crate::server::events::{EventPriority, TaskEvent, TimestampedEvent, TransferMetrics};
 use crate::server::websocket::message::WsServerMessage;
-use dashmap::DashMap;
-use std::collections::{HashMap, HashSet};
+use dashmap::{DashMap, DashSet};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -25,6 +27,14 @@ const BATCH_INTERVAL_MS: u64 = 100;
 const DEFAULT_MAX_BATCH_SIZE: usize = 10;
 /// last_sent 过期时间（秒）
 const LAST_SENT_EXPIRE_SECS: u64 = 60;
+/// 事件回放缓冲区容量（超出后丢弃最旧事件）
+pub const REPLAY_BUFFER_CAPACITY: usize = 500;
+/// 统计心跳推送间隔（毫秒）
+const STATS_INTERVAL_MS: u64 = 1000;
+/// 统计心跳订阅模式
+pub const STATS_SUBSCRIPTION: &str = "stats";
+/// 新连接的默认订阅（收到第一条订阅消息后被替换）
+const DEFAULT_SUBSCRIPTION: &str = "*";
 /// 每个连接的最大待发送事件数（防止内存无限增长）
 /// Requirements: 13.3
 pub const MAX_PENDING_EVENTS_PER_CONNECTION: usize = 100;
@@ -68,6 +78,9 @@ pub struct WebSocketManager {
     /// 用于快速查找订阅了某个模式的所有连接
     subscription_index: DashMap<Arc<str>, HashSet<String>>,
 
+    /// 仍使用默认订阅（`*`）的连接，首次显式订阅时移除默认订阅
+    default_subscribed: DashSet<String>,
+
     /// 待发送事件：connection_id -> throttle_key -> PendingEvent
     /// throttle_key = event_type:task_id，避免同一任务的不同事件类型互相覆盖
     pending_events: DashMap<String, HashMap<String, PendingEvent>>,
@@ -78,8 +91,17 @@ pub struct WebSocketManager {
     /// 全局事件 ID 计数器
     event_id_counter: Arc<AtomicU64>,
 
+    /// 最近事件回放缓冲区（按 event_id 递增，容量 REPLAY_BUFFER_CAPACITY，不含进度事件）
+    replay_buffer: Mutex<VecDeque<PendingEvent>>,
+
+    /// 最近一次被挤出回放缓冲区的事件 ID（0 表示尚未丢弃过事件）
+    replay_evicted_id: AtomicU64,
+
     /// 是否正在运行
     running: AtomicBool,
+
+    /// 统计心跳是否正在运行
+    stats_running: AtomicBool,
 }
 
 impl WebSocketManager {
@@ -89,10 +111,14 @@ impl WebSocketManager {
             connections: DashMap::new(),
             subscriptions: DashMap::new(),
             subscription_index: DashMap::new(),
+            default_subscribed: DashSet::new(),
             pending_events: DashMap::new(),
             last_sent: DashMap::new(),
             event_id_counter: Arc::new(AtomicU64::new(1)),
+            replay_buffer: Mutex::new(VecDeque::with_capacity(REPLAY_BUFFER_CAPACITY)),
+            replay_evicted_id: AtomicU64::new(0),
             running: AtomicBool::new(false),
+            stats_running: AtomicBool::new(false),
         }
     }
 
@@ -121,10 +147,16 @@ impl WebSocketManager {
 
     /// 添加订阅
     ///
+    /// 连接的第一条订阅消息会替换注册时的默认订阅 `*`，之后的订阅在已有订阅上追加
+    ///
     /// # 参数
     /// - `connection_id`: 连接 ID
     /// - `patterns`: 订阅模式列表
     pub fn subscribe(&self, connection_id: &str, patterns: Vec<String>) {
+        if self.default_subscribed.remove(connection_id).is_some() {
+            self.unsubscribe(connection_id, vec![DEFAULT_SUBSCRIPTION.to_string()]);
+        }
+
         let mut conn_subs = self.subscriptions.entry(connection_id.to_string()).or_default();
 
         for pattern in patterns {
@@ -287,6 +319,7 @@ impl WebSocketManager {
 
     /// 注册新连接
     ///
+    /// 新连接默认订阅所有事件（`*`），兼容不发送订阅消息的客户端
     /// 返回用于接收服务端消息的接收器
     pub fn register(&self, connection_id: String) -> mpsc::UnboundedReceiver<WsServerMessage> {
         let (sender, receiver) = mpsc::unbounded_channel();
@@ -300,6 +333,8 @@ impl WebSocketManager {
         };
 
         self.connections.insert(connection_id.clone(), connection);
+        self.subscribe(&connection_id, vec![DEFAULT_SUBSCRIPTION.to_string()]);
+        self.default_subscribed.insert(connection_id.clone());
         info!("WebSocket 连接已注册: {}", connection_id);
 
         receiver
@@ -312,6 +347,7 @@ impl WebSocketManager {
         if self.connections.remove(connection_id).is_some() {
             // 清理订阅和反向索引
             self.unsubscribe_all(connection_id);
+            self.default_subscribed.remove(connection_id);
 
             // 清理 pending_events
             self.pending_events.remove(connection_id);
@@ -323,6 +359,17 @@ impl WebSocketManager {
         }
     }
 
+    /// 关闭所有连接（服务关闭时调用）
+    ///
+    /// 移除连接后发送通道随之关闭，各连接的发送任务向客户端发送 Close 帧后退出
+    pub fn close_all(&self) -> usize {
+        let ids: Vec<String> = self.connections.iter().map(|conn| conn.id.clone()).collect();
+        for id in &ids {
+            self.unregister(id);
+        }
+        ids.len()
+    }
+
     /// 更新连接活动时间
     pub fn touch(&self, connection_id: &str) {
         if let Some(mut conn) = self.connections.get_mut(connection_id) {
@@ -330,6 +377,16 @@ impl WebSocketManager {
         }
     }
 
+    /// 连接是否已超时（超过 `timeout` 没有任何客户端消息或 Pong）
+    ///
+    /// 连接不存在时返回 false，由发送通道关闭来结束连接
+    pub fn is_stale(&self, connection_id: &str, timeout: Duration) -> bool {
+        self.connections
+            .get(connection_id)
+            .map(|conn| conn.last_active.elapsed() > timeout)
+            .unwrap_or(false)
+    }
+
     /// 获取连接数量
     pub fn connection_count(&self) -> usize {
         self.connections.len()
@@ -384,12 +441,18 @@ impl WebSocketManager {
     /// - `event`: 任务事件
     /// - `group_id`: 可选的分组 ID（用于文件夹下载等场景）
     pub fn send_if_subscribed(&self, event: TaskEvent, group_id: Option<String>) {
+        TransferMetrics::global().observe_event(&event);
+
+        let event_id = self.event_id_counter.fetch_add(1, Ordering::SeqCst);
+        let timestamped = TimestampedEvent::new(event_id, event.clone());
+
+        // 🔥 无连接时也要记录，断线期间的事件正是重连后需要回放的
+        self.record_for_replay(&timestamped, &group_id);
+
         if self.connection_count() == 0 {
             return;
         }
 
-        let event_id = self.event_id_counter.fetch_add(1, Ordering::SeqCst);
-        let timestamped = TimestampedEvent::new(event_id, event.clone());
         let throttle_key = Self::get_throttle_key(&event);
         let priority = event.priority();
         let now = Instant::now();
@@ -420,15 +483,15 @@ impl WebSocketManager {
                     if self.send_to(connection_id, WsServerMessage::event(timestamped.clone())) {
                         // 🔥 记录成功发送的事件
                         info!(
-                            "📡 WS事件已发送 | 连接={} | 类别={} | 事件={} | 任务={} | 分组={:?} | 事件ID={} | 优先级={:?} | 节流键={}",
-                            connection_id,
-                            timestamped.event.category(),
-                            timestamped.event.event_type(),
-                            timestamped.event.task_id(),
-                            group_id,
-                            timestamped.event_id,
-                            priority,
-                            throttle_key
+                            connection_id = %connection_id,
+                            category = timestamped.event.category(),
+                            event = timestamped.event.event_type(),
+                            task_id = timestamped.event.task_id(),
+                            group_id = ?group_id,
+                            event_id = timestamped.event_id,
+                            priority = ?priority,
+                            throttle_key = %throttle_key,
+                            "📡 WS事件已发送"
                         );
 
                         self.last_sent
@@ -473,6 +536,62 @@ impl WebSocketManager {
         }
     }
 
+    /// 记录事件到回放缓冲区，超出容量时丢弃最旧事件
+    ///
+    /// 进度事件不进入缓冲区：它们频繁且会被下一次进度覆盖，缓冲后只会挤掉状态变更事件
+    fn record_for_replay(&self, event: &TimestampedEvent, group_id: &Option<String>) {
+        if event.event.event_type() == "progress" {
+            return;
+        }
+
+        let mut buffer = self.replay_buffer.lock();
+        if buffer.len() >= REPLAY_BUFFER_CAPACITY {
+            if let Some(evicted) = buffer.pop_front() {
+                self.replay_evicted_id.store(evicted.event.event_id, Ordering::SeqCst);
+            }
+        }
+        buffer.push_back(PendingEvent {
+            event: event.clone(),
+            group_id: group_id.clone(),
+        });
+    }
+
+    /// 回放 event_id 大于 `since_seq` 的事件（按连接当前订阅过滤）
+    ///
+    /// 返回回放的事件数；缓冲区已无法覆盖 `since_seq`（事件已被丢弃或服务端重启过）时返回 None，
+    /// 调用方应改为发送状态快照。进度事件不回放，客户端在下一次进度推送时更新
+    pub fn replay_since(&self, connection_id: &str, since_seq: u64) -> Option<usize> {
+        let next_event_id = self.event_id_counter.load(Ordering::SeqCst);
+        // 客户端的 event_id 来自上一个服务端实例
+        if since_seq >= next_event_id {
+            return None;
+        }
+
+        let events: Vec<TimestampedEvent> = {
+            let buffer = self.replay_buffer.lock();
+            // 客户端尚未收到的事件已被挤出缓冲区（进度事件本就不缓冲，不算丢失）
+            if since_seq < self.replay_evicted_id.load(Ordering::SeqCst) {
+                return None;
+            }
+
+            buffer
+                .iter()
+                .filter(|pe| pe.event.event_id > since_seq)
+                .filter(|pe| {
+                    self.should_send_event(connection_id, &pe.event.event, pe.group_id.as_deref())
+                })
+                .map(|pe| pe.event.clone())
+                .collect()
+        };
+
+        let count = events.len();
+        if count > 0 {
+            self.send_to(connection_id, WsServerMessage::event_batch(events));
+        }
+        debug!("连接 {} 回放 event_id > {} 的事件 {} 条", connection_id, since_seq, count);
+        Some(count)
+    }
+
     /// 启动批量发送器
     ///
     /// 使用 Weak 引用避免循环引用导致的内存泄漏
@@ -516,6 +635,67 @@ impl WebSocketManager {
         info!("WebSocket 批量发送器已停止");
     }
 
+    /// 订阅了统计心跳的连接
+    fn stats_subscribers(&self) -> Vec<String> {
+        self.subscription_index
+            .get(STATS_SUBSCRIPTION)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 启动统计心跳
+    ///
+    /// 每秒调用 `provider` 汇总全局速度和任务数，推送给订阅了 `stats` 的连接；
+    /// 没有连接或没有订阅者时跳过，不做任何统计
+    pub fn start_stats_sender<F, Fut>(self: Arc<Self>, provider: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = WsServerMessage> + Send,
+    {
+        if self.stats_running.swap(true, Ordering::SeqCst) {
+            warn!("统计心跳已在运行");
+            return;
+        }
+
+        let weak_self = Arc::downgrade(&self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(STATS_INTERVAL_MS));
+
+            loop {
+                interval.tick().await;
+
+                let Some(manager) = weak_self.upgrade() else {
+                    info!("WebSocketManager 已销毁，统计心跳退出");
+                    break;
+                };
+                if !manager.stats_running.load(Ordering::SeqCst) {
+                    info!("统计心跳收到停止信号");
+                    break;
+                }
+                if manager.connection_count() == 0 {
+                    continue;
+                }
+                let subscribers = manager.stats_subscribers();
+                if subscribers.is_empty() {
+                    continue;
+                }
+
+                let message = provider().await;
+                for connection_id in subscribers {
+                    manager.send_to(&connection_id, message.clone());
+                }
+            }
+        });
+
+        info!("WebSocket 统计心跳已启动");
+    }
+
+    /// 停止统计心跳
+    pub fn stop_stats_sender(&self) {
+        self.stats_running.store(false, Ordering::SeqCst);
+    }
+
     /// 刷新待发送事件
     ///
     /// 按连接分组处理，只遍历有 pending 的连接
@@ -586,21 +766,21 @@ impl WebSocketManager {
                 if events_to_send.len() == 1 {
                     let event = events_to_send.remove(0);
                     info!(
-                        "📡 WS批量事件已发送(单条) | 连接={} | 类别={} | 事件={} | 任务={} | 事件ID={}",
-                        connection_id,
-                        event.event.category(),
-                        event.event.event_type(),
-                        event.event.task_id(),
-                        event.event_id
+                        connection_id = %connection_id,
+                        category = event.event.category(),
+                        event = event.event.event_type(),
+                        task_id = event.event.task_id(),
+                        event_id = event.event_id,
+                        "📡 WS批量事件已发送(单条)"
                     );
                     self.send_to(&connection_id, WsServerMessage::event(event));
                 } else {
                     info!(
-                        "📡 WS批量事件已发送({}) | 连接={} | 事件ID范围=[{}-{}]",
-                        events_to_send.len(),
-                        connection_id,
-                        events_to_send.first().map(|e| e.event_id).unwrap_or(0),
-                        events_to_send.last().map(|e| e.event_id).unwrap_or(0)
+                        connection_id = %connection_id,
+                        count = events_to_send.len(),
+                        first_event_id = events_to_send.first().map(|e| e.event_id).unwrap_or(0),
+                        last_event_id = events_to_send.last().map(|e| e.event_id).unwrap_or(0),
+                        "📡 WS批量事件已发送"
                     );
                     self.send_to(&connection_id, WsServerMessage::event_batch(events_to_send));
                 }
@@ -665,6 +845,7 @@ impl WebSocketManager {
 
         // 清理订阅和反向索引
         self.unsubscribe_all(connection_id);
+        self.default_subscribed.remove(connection_id);
 
         // 从连接列表移除
         if self.connections.remove(connection_id).is_some() {
@@ -700,7 +881,7 @@ impl Default for WebSocketManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::server::events::DownloadEvent;
+    use crate::server::events::{DownloadEvent, UploadEvent};
 
     #[tokio::test]
     async fn test_register_unregister() {
@@ -713,6 +894,145 @@ mod tests {
         assert_eq!(manager.connection_count(), 0);
     }
 
+    #[tokio::test]
+    async fn test_close_all() {
+        let manager = WebSocketManager::new();
+
+        let mut receiver_1 = manager.register("conn-1".to_string());
+        let mut receiver_2 = manager.register("conn-2".to_string());
+
+        assert_eq!(manager.close_all(), 2);
+        assert_eq!(manager.connection_count(), 0);
+
+        // 发送通道已关闭，发送任务会退出并发送 Close 帧
+        assert!(receiver_1.recv().await.is_none());
+        assert!(receiver_2.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replay_since() {
+        let manager = WebSocketManager::new();
+
+        // 无连接时发生的事件也会进入回放缓冲区
+        for i in 0..3 {
+            manager.send_if_subscribed(
+                TaskEvent::Download(DownloadEvent::Completed {
+                    task_id: format!("task-{}", i),
+                    completed_at: 0,
+                    group_id: None,
+                    is_backup: false,
+                }),
+                None,
+            );
+        }
+
+        let mut receiver = manager.register("conn-1".to_string());
+        manager.subscribe("conn-1", vec!["download".to_string()]);
+
+        // 已收到 event_id=1，回放 2、3
+        assert_eq!(manager.replay_since("conn-1", 1), Some(2));
+        match receiver.recv().await.unwrap() {
+            WsServerMessage::EventBatch { events } => {
+                let ids: Vec<u64> = events.iter().map(|e| e.event_id).collect();
+                assert_eq!(ids, vec![2, 3]);
+            }
+            other => panic!("Expected EventBatch, got {:?}", other),
+        }
+
+        // 已是最新，无需回放
+        assert_eq!(manager.replay_since("conn-1", 3), Some(0));
+
+        // 来自上一个服务端实例的 event_id
+        assert_eq!(manager.replay_since("conn-1", 100), None);
+    }
+
+    #[tokio::test]
+    async fn test_replay_buffer_drops_oldest() {
+        let manager = WebSocketManager::new();
+
+        for i in 0..(REPLAY_BUFFER_CAPACITY + 5) {
+            manager.send_if_subscribed(
+                TaskEvent::Download(DownloadEvent::Completed {
+                    task_id: format!("task-{}", i),
+                    completed_at: 0,
+                    group_id: None,
+                    is_backup: false,
+                }),
+                None,
+            );
+        }
+
+        let _receiver = manager.register("conn-1".to_string());
+        manager.subscribe("conn-1", vec!["download".to_string()]);
+
+        // event_id 1..=5 已被丢弃
+        assert_eq!(manager.replay_since("conn-1", 0), None);
+        assert_eq!(manager.replay_since("conn-1", 5), Some(REPLAY_BUFFER_CAPACITY));
+    }
+
+    #[tokio::test]
+    async fn test_replay_skips_progress_events() {
+        let manager = WebSocketManager::new();
+
+        manager.send_if_subscribed(
+            TaskEvent::Download(DownloadEvent::Completed {
+                task_id: "task-1".to_string(),
+                completed_at: 0,
+                group_id: None,
+                is_backup: false,
+            }),
+            None,
+        );
+        // 大量进度事件不会挤掉状态事件
+        for _ in 0..(REPLAY_BUFFER_CAPACITY + 5) {
+            manager.send_if_subscribed(
+                TaskEvent::Download(DownloadEvent::Progress {
+                    task_id: "task-2".to_string(),
+                    downloaded_size: 1,
+                    total_size: 2,
+                    speed: 1,
+                    progress: 50.0,
+                    group_id: None,
+                    is_backup: false,
+                    cdn_host: None,
+                    eta_secs: None,
+                }),
+                None,
+            );
+        }
+
+        let mut receiver = manager.register("conn-1".to_string());
+        manager.subscribe("conn-1", vec!["download".to_string()]);
+
+        assert_eq!(manager.replay_since("conn-1", 0), Some(1));
+        match receiver.recv().await.unwrap() {
+            WsServerMessage::EventBatch { events } => {
+                assert_eq!(events.len(), 1);
+                assert_eq!(events[0].event_id, 1);
+            }
+            other => panic!("Expected EventBatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_is_stale() {
+        let manager = WebSocketManager::new();
+        let _receiver = manager.register("conn-1".to_string());
+
+        assert!(!manager.is_stale("conn-1", Duration::from_secs(60)));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(manager.is_stale("conn-1", Duration::from_millis(10)));
+
+        // touch 后重新计时
+        manager.touch("conn-1");
+        assert!(!manager.is_stale("conn-1", Duration::from_millis(10)));
+
+        // 已移除的连接不视为超时
+        manager.unregister("conn-1");
+        assert!(!manager.is_stale("conn-1", Duration::ZERO));
+    }
+
     #[tokio::test]
     async fn test_send_to_connection() {
         let manager = WebSocketManager::new();
@@ -757,25 +1077,34 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_no_subscription_no_event() {
+    async fn test_default_subscription_replaced_by_subscribe() {
         let manager = WebSocketManager::new();
         let mut receiver = manager.register("conn-1".to_string());
+        assert_eq!(manager.get_subscriptions("conn-1"), vec!["*"]);
+
+        let upload_event = |task_id: &str| {
+            TaskEvent::Upload(UploadEvent::Completed {
+                task_id: task_id.to_string(),
+                completed_at: 0,
+                is_rapid_upload: false,
+                is_backup: false,
+            })
+        };
 
-        // 不订阅，直接发送事件
-        let event = TaskEvent::Download(DownloadEvent::Progress {
-            task_id: "test-1".to_string(),
-            downloaded_size: 100,
-            total_size: 1024,
-            speed: 100,
-            progress: 10.0,
-            group_id: None,
-            is_backup: false,
-        });
-
-        manager.send_if_subscribed(event, None);
+        // 未订阅的连接默认接收所有事件
+        manager.send_if_subscribed(upload_event("up-1"), None);
+        assert!(receiver.try_recv().is_ok());
 
-        // 使用 try_recv 验证没有消息
+        // 第一条订阅消息替换默认订阅
+        manager.subscribe("conn-1", vec!["download".to_string()]);
+        assert_eq!(manager.get_subscriptions("conn-1"), vec!["download"]);
+        manager.send_if_subscribed(upload_event("up-2"), None);
         tokio::time::sleep(Duration::from_millis(50)).await;
         assert!(receiver.try_recv().is_err());
+
+        // 之后的订阅在已有订阅上追加
+        manager.subscribe("conn-1", vec!["upload".to_string()]);
+        manager.send_if_subscribed(upload_event("up-3"), None);
+        assert!(receiver.try_recv().is_ok());
     }
 }
@@ -24,16 +24,29 @@ pub enum WsClientMessage {
     /// - `transfer` - 所有转存事件
     /// - `cloud_dl` - 所有离线下载事件
     /// - `cloud_dl:*` - 所有离线下载事件（通配符）
+    /// - `stats` - 全局传输统计心跳（每秒推送，需单独订阅，`*` 不包含）
     /// - `*` - 所有事件
+    ///
+    /// 未发送订阅消息的连接默认订阅 `*`，第一条订阅消息会替换该默认订阅
     Subscribe {
-        /// 要订阅的模式列表
+        /// 要订阅的模式列表（兼容 `kinds` 字段名）
+        #[serde(alias = "kinds")]
         subscriptions: Vec<String>,
     },
     /// 取消订阅事件
     Unsubscribe {
-        /// 要取消订阅的模式列表
+        /// 要取消订阅的模式列表（兼容 `kinds` 字段名）
+        #[serde(alias = "kinds")]
         subscriptions: Vec<String>,
     },
+    /// 断线重连后请求回放事件
+    ///
+    /// 服务端按当前订阅补发 event_id 大于 `since_seq` 的事件（需在 Subscribe 之后发送）；
+    /// 事件已超出回放缓冲区时改为推送状态快照
+    Resume {
+        /// 客户端最后收到的事件 ID
+        since_seq: u64,
+    },
 }
 
 /// 服务端发送给客户端的消息
@@ -93,6 +106,31 @@ pub enum WsServerMessage {
         /// 剩余订阅列表
         subscriptions: Vec<String>,
     },
+    /// 登录态失效（BDUSS 过期）
+    SessionExpired {
+        /// 失效账号 UID
+        uid: u64,
+        /// 提示信息
+        message: String,
+        /// 服务端时间戳（毫秒）
+        timestamp: i64,
+    },
+    /// 触发百度限流，进入全局冷却（暂停启动新任务并降低并发）
+    RateLimited {
+        /// 冷却时长（秒）
+        cooldown_secs: u64,
+    },
+    /// 全局传输统计心跳（订阅 `stats` 后每秒推送一次）
+    Stats {
+        /// 下载总速度（字节/秒）
+        down_speed: u64,
+        /// 上传总速度（字节/秒）
+        up_speed: u64,
+        /// 进行中的上传/下载任务数
+        active: usize,
+        /// 排队中的上传/下载任务数
+        queued: usize,
+    },
 }
 
 impl WsServerMessage {
@@ -139,6 +177,30 @@ impl WsServerMessage {
     pub fn unsubscribe_success(subscriptions: Vec<String>) -> Self {
         Self::UnsubscribeSuccess { subscriptions }
     }
+
+    /// 创建登录态失效消息
+    pub fn session_expired(uid: u64, message: impl Into<String>) -> Self {
+        Self::SessionExpired {
+            uid,
+            message: message.into(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// 创建限流冷却消息
+    pub fn rate_limited(cooldown_secs: u64) -> Self {
+        Self::RateLimited { cooldown_secs }
+    }
+
+    /// 创建全局传输统计消息
+    pub fn stats(down_speed: u64, up_speed: u64, active: usize, queued: usize) -> Self {
+        Self::Stats {
+            down_speed,
+            up_speed,
+            active,
+            queued,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -155,6 +217,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subscribe_kinds_alias() {
+        let json = r#"{"type":"subscribe","kinds":["download","upload"]}"#;
+        let msg: WsClientMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            WsClientMessage::Subscribe { subscriptions } => {
+                assert_eq!(subscriptions, vec!["download", "upload"])
+            }
+            _ => panic!("Expected Subscribe message"),
+        }
+    }
+
+    #[test]
+    fn test_resume_parsing() {
+        let json = r#"{"type":"resume","since_seq":42}"#;
+        let msg: WsClientMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            WsClientMessage::Resume { since_seq } => assert_eq!(since_seq, 42),
+            _ => panic!("Expected Resume message"),
+        }
+    }
+
     #[test]
     fn test_server_message_serialization() {
         let msg = WsServerMessage::pong(Some(1234567890));
@@ -162,4 +246,27 @@ mod tests {
         assert!(json.contains("pong"));
         assert!(json.contains("1234567890"));
     }
+
+    #[test]
+    fn test_session_expired_serialization() {
+        let msg = WsServerMessage::session_expired(42, "登录已过期");
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains(r#""type":"session_expired""#));
+        assert!(json.contains(r#""uid":42"#));
+    }
+
+    #[test]
+    fn test_rate_limited_serialization() {
+        let json = serde_json::to_string(&WsServerMessage::rate_limited(300)).unwrap();
+        assert_eq!(json, r#"{"type":"rate_limited","cooldown_secs":300}"#);
+    }
+
+    #[test]
+    fn test_stats_serialization() {
+        let json = serde_json::to_string(&WsServerMessage::stats(1024, 512, 3, 2)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"stats","down_speed":1024,"up_speed":512,"active":3,"queued":2}"#
+        );
+    }
 }
@@ -7,5 +7,8 @@ pub mod manager;
 mod message;
 
 pub use handler::handle_websocket;
-pub use manager::{WebSocketManager, PendingEvent, MAX_PENDING_EVENTS_PER_CONNECTION};
+pub use manager::{
+    WebSocketManager, PendingEvent, MAX_PENDING_EVENTS_PER_CONNECTION, REPLAY_BUFFER_CAPACITY,
+    STATS_SUBSCRIPTION,
+};
 pub use message::{WsClientMessage, WsServerMessage};
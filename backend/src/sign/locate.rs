@@ -28,17 +28,12 @@ impl LocateSign {
     /// # 返回
     /// 包含完整签名信息的 LocateSign 实例
     pub fn new(uid: u64, bduss: &str) -> Self {
-        let time = chrono::Utc::now().timestamp();
-        let devuid = generate_devuid(bduss);
-
-        let mut sign = Self {
-            time,
-            rand: String::new(),
-            devuid,
-        };
+        Self::with_devuid(uid, bduss, generate_devuid(bduss))
+    }
 
-        sign.generate_rand(uid, bduss);
-        sign
+    /// 使用已持久化的 DevUID 创建签名（时间戳取当前时间）
+    pub fn with_devuid(uid: u64, bduss: &str, devuid: String) -> Self {
+        Self::with_time_and_devuid(chrono::Utc::now().timestamp(), devuid, uid, bduss)
     }
 
     /// 使用指定时间戳和 DevUID 创建签名
@@ -9,6 +9,7 @@
 //! - 子任务（SubTask）：中等优先级，可抢占备份任务的槽位
 //! - 备份任务（Backup）：优先级最低，只能使用空闲槽位，可被抢占
 
+use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -25,7 +26,8 @@ pub const STALE_RELEASE_THRESHOLD: Duration = Duration::from_secs(300);
 pub const CLEANUP_INTERVAL: Duration = Duration::from_secs(30);
 
 /// 任务位类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TaskSlotType {
     /// 固定任务位（单文件或文件夹的主任务位）
     Fixed,
@@ -34,7 +36,8 @@ pub enum TaskSlotType {
 }
 
 /// 任务优先级（与 autobackup/priority/policy.rs 中的 Priority 对应）
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TaskPriority {
     /// 普通任务（优先级最高，值=10）
     Normal = 10,
@@ -133,6 +136,52 @@ impl TaskSlot {
     }
 }
 
+/// 固定任务位分配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotAllocation {
+    /// 分配成功（任务已持有固定位时返回原槽位）
+    Allocated {
+        /// 槽位ID
+        slot_id: usize,
+        /// 被抢占的备份任务ID
+        preempted: Option<String>,
+    },
+    /// 无空闲槽位，但有文件夹持有可回收的借调位
+    Reclaimable,
+    /// 无可用槽位
+    Unavailable,
+}
+
+/// 单个槽位的占用情况（调试用）
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotOccupancy {
+    /// 槽位ID
+    pub slot_id: usize,
+    /// 槽位类型
+    pub slot_type: TaskSlotType,
+    /// 占用此位的任务ID（空闲时为 null）
+    pub task_id: Option<String>,
+    /// 是否为文件夹主任务位
+    pub is_folder_main: bool,
+    /// 任务优先级
+    pub priority: TaskPriority,
+    /// 距最后更新的秒数（空闲时为 null，接近 STALE_RELEASE_THRESHOLD 时会被自动释放）
+    pub idle_secs: Option<u64>,
+}
+
+/// 任务位池占用快照（调试用）
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotPoolSnapshot {
+    /// 最大槽位数
+    pub max_slots: usize,
+    /// 已使用槽位数（仅统计 max_slots 范围内）
+    pub used_slots: usize,
+    /// 所有槽位（缩容后可能包含超出 max_slots、仍被占用的槽位）
+    pub slots: Vec<SlotOccupancy>,
+    /// 文件夹借调位记录 folder_id -> [borrowed_slot_ids]
+    pub borrowed: HashMap<String, Vec<usize>>,
+}
+
 /// 任务位池管理器
 #[derive(Debug)]
 pub struct TaskSlotPool {
@@ -280,7 +329,17 @@ impl TaskSlotPool {
     ) -> Option<(usize, Option<String>)> {
         let max_slots = self.max_slots.load(Ordering::SeqCst);
         let mut slots = self.slots.write().await;
+        Self::allocate_fixed_in(&mut slots, max_slots, task_id, is_folder, priority)
+    }
 
+    /// 在已持有的槽位锁内分配固定位（空闲位优先，其次抢占备份任务）
+    fn allocate_fixed_in(
+        slots: &mut [TaskSlot],
+        max_slots: usize,
+        task_id: &str,
+        is_folder: bool,
+        priority: TaskPriority,
+    ) -> Option<(usize, Option<String>)> {
         for slot in slots.iter_mut() {
             if slot.id < max_slots && slot.is_free() {
                 slot.allocate_with_priority(task_id, TaskSlotType::Fixed, is_folder, priority);
@@ -310,6 +369,91 @@ impl TaskSlotPool {
         None
     }
 
+    /// 分配固定任务位，或判断是否可以回收借调位
+    ///
+    /// 启动/恢复任务的统一入口：
+    /// - 任务已持有固定位时直接返回原槽位（重复恢复不会占用第二个槽位）
+    /// - 否则按 allocate_fixed_slot_with_priority 分配空闲位或抢占备份任务
+    /// - 都失败时，非备份任务在有文件夹借调位可回收时返回 Reclaimable，
+    ///   由调用方暂停借调子任务后通过 reassign_borrowed_slot 接管槽位
+    pub async fn allocate_or_find_reclaimable(
+        &self,
+        task_id: &str,
+        is_folder: bool,
+        priority: TaskPriority,
+    ) -> SlotAllocation {
+        let max_slots = self.max_slots.load(Ordering::SeqCst);
+        {
+            // 检查已持有与分配在同一把锁内完成，并发恢复同一任务时只会占用一个槽位
+            let mut slots = self.slots.write().await;
+            let existing = slots
+                .iter()
+                .find(|s| s.task_id.as_deref() == Some(task_id) && s.slot_type == TaskSlotType::Fixed)
+                .map(|s| s.id);
+            if let Some(slot_id) = existing {
+                debug!("任务 {} 已持有固定任务位: slot_id={}", task_id, slot_id);
+                return SlotAllocation::Allocated { slot_id, preempted: None };
+            }
+
+            if let Some((slot_id, preempted)) =
+                Self::allocate_fixed_in(&mut slots, max_slots, task_id, is_folder, priority)
+            {
+                return SlotAllocation::Allocated { slot_id, preempted };
+            }
+        }
+
+        if priority.can_preempt(TaskPriority::Backup) && self.find_folder_with_borrowed_slots().await.is_some() {
+            SlotAllocation::Reclaimable
+        } else {
+            SlotAllocation::Unavailable
+        }
+    }
+
+    /// 将文件夹的借调位直接转为指定任务的固定位
+    ///
+    /// 回收借调位时使用：槽位在同一把锁内从文件夹转给新任务，不经过空闲状态，
+    /// 避免并发启动的其他任务在释放与重新分配之间抢走槽位、导致任务数超出上限
+    ///
+    /// # Returns
+    /// 槽位仍属于该文件夹且转移成功时返回 true
+    pub async fn reassign_borrowed_slot(
+        &self,
+        folder_id: &str,
+        slot_id: usize,
+        task_id: &str,
+        is_folder: bool,
+        priority: TaskPriority,
+    ) -> bool {
+        let reassigned = {
+            let mut slots = self.slots.write().await;
+            match slots.iter_mut().find(|s| s.id == slot_id) {
+                Some(slot) if slot.task_id.as_deref() == Some(folder_id) && slot.slot_type == TaskSlotType::Borrowed => {
+                    slot.allocate_with_priority(task_id, TaskSlotType::Fixed, is_folder, priority);
+                    true
+                }
+                _ => false,
+            }
+        };
+
+        let mut borrowed_map = self.borrowed_map.write().await;
+        if let Some(borrowed_list) = borrowed_map.get_mut(folder_id) {
+            borrowed_list.retain(|&id| id != slot_id);
+            if borrowed_list.is_empty() {
+                borrowed_map.remove(folder_id);
+            }
+        }
+
+        if reassigned {
+            info!(
+                "借调位转为固定位: slot_id={}, folder_id={} -> task_id={}",
+                slot_id, folder_id, task_id
+            );
+        } else {
+            warn!("借调位转移失败：slot {} 不是 folder {} 的借调位", slot_id, folder_id);
+        }
+        reassigned
+    }
+
     /// 为备份任务分配槽位（仅使用空闲槽位，不抢占）
     ///
     /// # Arguments
@@ -619,6 +763,36 @@ impl TaskSlotPool {
             .collect()
     }
 
+    /// 获取任务位池占用快照（用于调试借调位等问题）
+    pub async fn snapshot(&self) -> SlotPoolSnapshot {
+        let max_slots = self.max_slots.load(Ordering::SeqCst);
+        let slots: Vec<SlotOccupancy> = self
+            .slots
+            .read()
+            .await
+            .iter()
+            .map(|s| SlotOccupancy {
+                slot_id: s.id,
+                slot_type: s.slot_type,
+                task_id: s.task_id.clone(),
+                is_folder_main: s.is_folder_main,
+                priority: s.priority,
+                idle_secs: s.last_updated_at.map(|t| t.elapsed().as_secs()),
+            })
+            .collect();
+        let used_slots = slots
+            .iter()
+            .filter(|s| s.slot_id < max_slots && s.task_id.is_some())
+            .count();
+
+        SlotPoolSnapshot {
+            max_slots,
+            used_slots,
+            slots,
+            borrowed: self.get_all_borrowed_records().await,
+        }
+    }
+
     /// 获取指定槽位的详细信息（用于调试和测试）
     ///
     /// # Arguments
@@ -1367,4 +1541,119 @@ mod tests {
         assert_eq!(success, 10);
         assert_eq!(pool.available_slots().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let pool = TaskSlotPool::new(3);
+        pool.allocate_fixed_slot("folder1", true).await;
+        let borrowed = pool.allocate_borrowed_slots_no_preempt("folder1", 1).await;
+        assert_eq!(borrowed.len(), 1);
+
+        let snapshot = pool.snapshot().await;
+        assert_eq!(snapshot.max_slots, 3);
+        assert_eq!(snapshot.used_slots, 2);
+        assert_eq!(snapshot.slots.len(), 3);
+        assert!(snapshot.slots[0].is_folder_main);
+        assert_eq!(snapshot.slots[0].idle_secs, Some(0));
+        assert_eq!(snapshot.slots[borrowed[0]].slot_type, TaskSlotType::Borrowed);
+        assert_eq!(snapshot.borrowed.get("folder1"), Some(&borrowed));
+        assert!(snapshot.slots[2].task_id.is_none());
+        assert!(snapshot.slots[2].idle_secs.is_none());
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["slots"][1]["slot_type"], "borrowed");
+        assert_eq!(json["slots"][0]["priority"], "normal");
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_borrowed_slot_reassigns_atomically() {
+        // 无空闲槽位：文件夹占用固定位 0，借调位 1
+        let pool = TaskSlotPool::new(2);
+        pool.allocate_fixed_slot("folder1", true).await;
+        assert_eq!(pool.allocate_borrowed_slots_no_preempt("folder1", 1).await, vec![1]);
+
+        assert_eq!(
+            pool.allocate_or_find_reclaimable("task1", false, TaskPriority::Normal).await,
+            SlotAllocation::Reclaimable
+        );
+        // 备份任务不回收借调位
+        assert_eq!(
+            pool.allocate_or_find_reclaimable("backup1", false, TaskPriority::Backup).await,
+            SlotAllocation::Unavailable
+        );
+
+        assert!(pool.reassign_borrowed_slot("folder1", 1, "task1", false, TaskPriority::Normal).await);
+        assert_eq!(pool.get_task_slot("task1").await, Some((1, TaskSlotType::Fixed)));
+        assert!(pool.get_borrowed_slots("folder1").await.is_empty());
+        assert!(pool.find_folder_with_borrowed_slots().await.is_none());
+        // 转移过程中槽位从未空闲
+        assert_eq!(pool.available_slots().await, 0);
+        assert_eq!(pool.used_slots().await, 2);
+
+        // 槽位已不属于文件夹，重复转移失败
+        assert!(!pool.reassign_borrowed_slot("folder1", 1, "task2", false, TaskPriority::Normal).await);
+        assert_eq!(pool.get_task_slot("task1").await, Some((1, TaskSlotType::Fixed)));
+    }
+
+    #[tokio::test]
+    async fn test_simultaneous_resume_single_file_tasks() {
+        let pool = Arc::new(TaskSlotPool::new(2));
+        pool.allocate_fixed_slot("folder1", true).await;
+        pool.allocate_borrowed_slots_no_preempt("folder1", 1).await;
+
+        // 两个单文件任务同时恢复，争夺同一个借调位
+        let handles: Vec<_> = ["task1", "task2"]
+            .into_iter()
+            .map(|task_id| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    match pool.allocate_or_find_reclaimable(task_id, false, TaskPriority::Normal).await {
+                        SlotAllocation::Allocated { .. } => true,
+                        SlotAllocation::Reclaimable => {
+                            pool.reassign_borrowed_slot("folder1", 1, task_id, false, TaskPriority::Normal).await
+                        }
+                        SlotAllocation::Unavailable => false,
+                    }
+                })
+            })
+            .collect();
+
+        let mut success = 0;
+        for handle in handles {
+            if handle.await.unwrap() {
+                success += 1;
+            }
+        }
+        assert_eq!(success, 1);
+        assert_eq!(pool.used_slots().await, 2);
+
+        // 同一任务重复恢复只占用一个槽位
+        let pool = TaskSlotPool::new(3);
+        let first = pool.allocate_or_find_reclaimable("task1", false, TaskPriority::Normal).await;
+        let second = pool.allocate_or_find_reclaimable("task1", false, TaskPriority::Normal).await;
+        assert_eq!(first, second);
+        assert_eq!(pool.used_slots().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pause_then_requeue_after_reclaim() {
+        let pool = TaskSlotPool::new(2);
+        pool.allocate_fixed_slot("folder1", true).await;
+        pool.allocate_borrowed_slots_no_preempt("folder1", 1).await;
+        assert!(pool.reassign_borrowed_slot("folder1", 1, "task1", false, TaskPriority::Normal).await);
+
+        // 被回收的子任务重新排队，此时没有空闲槽位也没有借调位可回收
+        assert_eq!(
+            pool.allocate_or_find_reclaimable("subtask1", false, TaskPriority::SubTask).await,
+            SlotAllocation::Unavailable
+        );
+
+        // 单文件任务暂停释放固定位后，排队的子任务获得该槽位
+        pool.release_fixed_slot("task1").await;
+        assert_eq!(
+            pool.allocate_or_find_reclaimable("subtask1", false, TaskPriority::SubTask).await,
+            SlotAllocation::Allocated { slot_id: 1, preempted: None }
+        );
+        assert_eq!(pool.used_slots().await, 2);
+    }
 }
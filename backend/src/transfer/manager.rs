@@ -1,23 +1,26 @@
 // 转存任务管理器
 
-use crate::config::{AppConfig, TransferConfig};
+use crate::config::{
+    AppConfig, TransferConfig, TRANSFER_BEHAVIOR_AND_DOWNLOAD, TRANSFER_BEHAVIOR_DOWNLOAD_CLEANUP,
+};
 use crate::downloader::{DownloadManager, FolderDownloadManager, FolderStatus, TaskStatus};
 use crate::netdisk::NetdiskClient;
 use crate::persistence::{
-    PersistenceManager, TaskMetadata, TransferRecoveryInfo,
+    PersistenceManager, TaskHistoryQuery, TaskMetadata, TransferRecoveryInfo,
 };
 use crate::server::events::{TaskEvent, TransferEvent};
 use crate::server::websocket::WebSocketManager;
 use crate::transfer::task::{TransferStatus, TransferTask};
-use crate::transfer::types::{BatchGroupInfo, CleanupResult, CleanupStatus, ShareLink, SharePageInfo, SharedFileInfo, TransferResult};
+use crate::transfer::types::{BatchGroupInfo, CleanupResult, CleanupStatus, ShareDirectSource, ShareLink, ShareCaptchaAnswer, ShareInspectResult, SharePageInfo, ShareSubfolderNaming, SharedFileInfo, TransferError, TransferResult};
 use anyhow::{Context, Result};
 use dashmap::DashMap;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock as StdRwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
 use std::time::Duration;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{Mutex, Notify, RwLock};
 use tokio_util::sync::CancellationToken;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use tracing::{debug, error, info, warn};
 
 /// 转存任务信息（包含任务和取消令牌）
@@ -44,6 +47,106 @@ pub struct TransferManager {
     persistence_manager: Arc<Mutex<Option<Arc<Mutex<PersistenceManager>>>>>,
     /// 🔥 WebSocket 管理器
     ws_manager: Arc<RwLock<Option<Arc<WebSocketManager>>>>,
+    /// 🔥 转存执行槽位（限制同时执行的转存任务数）
+    transfer_slots: Arc<TransferSlots>,
+}
+
+/// 🔥 转存执行槽位
+///
+/// 同时执行的转存任务数不超过 `max_concurrent_transfers`，其余按创建顺序在等待队列中排队；
+/// 任务进入自动下载阶段后即释放槽位（下载并发由下载管理器控制）
+struct TransferSlots {
+    /// 等待执行的任务 ID（队首优先）
+    waiting_queue: StdMutex<VecDeque<String>>,
+    /// 正在执行转存的任务数
+    running: AtomicUsize,
+    /// 槽位释放、队列变化或上限调整时唤醒等待者
+    notify: Notify,
+}
+
+/// 持有期间占用一个转存槽位，释放时唤醒排队任务
+struct TransferSlotGuard {
+    slots: Arc<TransferSlots>,
+}
+
+impl Drop for TransferSlotGuard {
+    fn drop(&mut self) {
+        self.slots.running.fetch_sub(1, Ordering::SeqCst);
+        self.slots.notify.notify_waiters();
+    }
+}
+
+impl TransferSlots {
+    fn new() -> Self {
+        Self {
+            waiting_queue: StdMutex::new(VecDeque::new()),
+            running: AtomicUsize::new(0),
+            notify: Notify::new(),
+        }
+    }
+
+    /// 加入等待队列，返回是否需要排队（没有立即可用的槽位）
+    fn enqueue(&self, task_id: String, max_concurrent: usize) -> bool {
+        let mut queue = self.waiting_queue.lock().unwrap();
+        queue.push_back(task_id);
+        queue.len() > 1 || self.running.load(Ordering::SeqCst) >= max_concurrent
+    }
+
+    /// 任务在等待队列中的位置（从 1 开始），不在队列中返回 None
+    fn queue_position(&self, task_id: &str) -> Option<usize> {
+        let queue = self.waiting_queue.lock().unwrap();
+        queue.iter().position(|id| id == task_id).map(|i| i + 1)
+    }
+
+    /// 排在队首且有空闲槽位时出队并占用槽位
+    fn try_acquire(self: &Arc<Self>, task_id: &str, max_concurrent: usize) -> Option<TransferSlotGuard> {
+        let mut queue = self.waiting_queue.lock().unwrap();
+        if queue.front().map(String::as_str) != Some(task_id)
+            || self.running.load(Ordering::SeqCst) >= max_concurrent
+        {
+            return None;
+        }
+        queue.pop_front();
+        self.running.fetch_add(1, Ordering::SeqCst);
+        Some(TransferSlotGuard {
+            slots: Arc::clone(self),
+        })
+    }
+
+    /// 从等待队列移除（任务取消时）
+    fn remove(&self, task_id: &str) {
+        self.waiting_queue.lock().unwrap().retain(|id| id != task_id);
+        self.notify.notify_waiters();
+    }
+
+    /// 等待转存槽位，任务被取消时返回 None
+    async fn acquire(
+        self: &Arc<Self>,
+        task_id: &str,
+        config: &RwLock<TransferConfig>,
+        cancellation_token: &CancellationToken,
+    ) -> Option<TransferSlotGuard> {
+        loop {
+            // 先注册通知再检查条件，避免检查后、等待前的唤醒丢失
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if cancellation_token.is_cancelled() {
+                self.remove(task_id);
+                return None;
+            }
+            let max_concurrent = config.read().await.max_concurrent_transfers.max(1);
+            if let Some(guard) = self.try_acquire(task_id, max_concurrent) {
+                return Some(guard);
+            }
+
+            tokio::select! {
+                _ = &mut notified => {}
+                _ = cancellation_token.cancelled() => {}
+            }
+        }
+    }
 }
 
 /// 创建转存任务请求
@@ -59,12 +162,20 @@ pub struct CreateTransferRequest {
     /// 分享直下任务会自动创建临时目录，下载完成后自动清理
     #[allow(dead_code)]
     pub is_share_direct_download: bool,
+    /// 转存到临时目录、下载成功后删除网盘副本（None 且未指定 auto_download 时使用全局配置）
+    pub download_cleanup: Option<bool>,
     /// 用户选择的文件 fs_id 列表（可选）
     /// 为空或未提供时转存所有文件（向后兼容）
     pub selected_fs_ids: Option<Vec<u64>>,
     /// 用户选择的文件完整信息列表（可选）
     /// 前端在文件选择模式下传入，包含选中文件的名称、大小、类型等信息
     pub selected_files: Option<Vec<SharedFileInfo>>,
+    /// 自动下载时按分享创建本地子文件夹（可选）
+    pub share_subfolder: Option<ShareSubfolderNaming>,
+    /// 递归展开选中的文件夹后按文件分批转存
+    pub recursive: bool,
+    /// 提取码验证需要验证码时，用户输入的验证码（见 retry_with_captcha）
+    pub captcha: Option<ShareCaptchaAnswer>,
 }
 
 /// 创建转存任务响应
@@ -74,6 +185,9 @@ pub struct CreateTransferResponse {
     pub status: Option<TransferStatus>,
     pub need_password: bool,
     pub error: Option<String>,
+    /// 验证提取码需要输入验证码或验证码错误时返回（CaptchaRequired / CaptchaIncorrect，
+    /// 附带验证码图片和令牌，通过 retry_with_captcha 提交）
+    pub captcha_error: Option<TransferError>,
 }
 
 /// 预览分享结果（包含文件列表和分享信息）
@@ -85,6 +199,28 @@ pub struct PreviewShareResult {
     pub bdstoken: String,
 }
 
+/// 免转存直接下载请求
+#[derive(Debug, Clone)]
+pub struct DirectDownloadRequest {
+    pub share_url: String,
+    pub password: Option<String>,
+    /// 用户选择的文件（可选，为空时下载分享根目录下的全部文件）
+    pub selected_files: Option<Vec<SharedFileInfo>>,
+    /// 本地下载目录（可选，默认使用下载配置中的目录）
+    pub local_download_path: Option<String>,
+    /// 提取码验证需要验证码时，用户输入的验证码
+    pub captcha: Option<ShareCaptchaAnswer>,
+}
+
+/// 免转存直接下载结果
+#[derive(Debug, Clone)]
+pub struct DirectDownloadResult {
+    /// 创建的下载任务 ID 列表
+    pub task_ids: Vec<String>,
+    /// 未获取到直链或创建任务失败的文件路径
+    pub failed_paths: Vec<String>,
+}
+
 /// handle_transfer_error 的返回值，区分恢复成功、友好失败、无法识别三种场景
 enum TransferErrorHandled {
     /// 分享直下 -30 恢复成功，携带恢复的文件信息 (name, Option<fs_id>, Option<temp_dir_path>, source_share_path)
@@ -112,6 +248,7 @@ impl TransferManager {
             app_config,
             persistence_manager: Arc::new(Mutex::new(None)),
             ws_manager: Arc::new(RwLock::new(None)),
+            transfer_slots: Arc::new(TransferSlots::new()),
         }
     }
 
@@ -168,13 +305,15 @@ impl TransferManager {
     /// 步骤：
     /// 1. parse_share_link(share_url) → 提取 short_key 和可能的密码
     /// 2. access_share_page(short_key, password) → 获取 SharePageInfo
-    /// 3. 如果有密码，调用 verify_share_password() → 验证密码并获取 sekey
+    /// 3. 如果有密码，调用 verify_share_password_with_captcha() → 验证密码并获取 sekey
+    ///    （需要验证码时返回 TransferError::CaptchaRequired，用户携带 captcha 重新预览）
     /// 4. list_share_files(short_key, shareid, uk, bdstoken, page, num) → 获取根目录文件列表
     /// 5. 返回 PreviewShareResult（文件列表 + 分享信息）
     pub async fn preview_share(
         &self,
         share_url: &str,
         password: Option<String>,
+        captcha: Option<ShareCaptchaAnswer>,
         page: u32,
         num: u32,
     ) -> Result<PreviewShareResult> {
@@ -198,12 +337,13 @@ impl TransferManager {
         if let Some(ref pwd) = password {
             let referer = format!("https://pan.baidu.com/s/{}", share_link.short_key);
             client
-                .verify_share_password(
+                .verify_share_password_with_captcha(
                     &share_info.shareid,
                     &share_info.share_uk,
                     &share_info.bdstoken,
                     pwd,
                     &referer,
+                    captcha.as_ref(),
                 )
                 .await?;
             info!("预览: 提取码验证成功");
@@ -266,6 +406,365 @@ impl TransferManager {
         Ok(file_list)
     }
 
+    /// 检查分享链接是否有效（不验证提取码、不转存）
+    ///
+    /// 链接格式无效、分享已失效或不存在时返回 valid=false；需要提取码时无法列出文件，
+    /// 只返回 needs_password=true；否则列出根目录返回文件数和标题（列出失败时省略）
+    pub async fn inspect_share(&self, share_url: &str) -> Result<ShareInspectResult> {
+        info!("检查分享链接: url={}", share_url);
+
+        let share_link = match self.client.read().unwrap().parse_share_link(share_url) {
+            Ok(link) => link,
+            Err(e) => return Ok(ShareInspectResult::invalid(e.to_string())),
+        };
+
+        let client = self.client.read().unwrap().clone();
+        let share_info = match client
+            .access_share_page(&share_link.short_key, &None, true)
+            .await
+        {
+            Ok(info) => info,
+            Err(e) => {
+                return match TransferError::find(&e) {
+                    Some(TransferError::NeedPassword) => Ok(ShareInspectResult {
+                        valid: true,
+                        needs_password: true,
+                        file_count: None,
+                        title: None,
+                        reason: None,
+                    }),
+                    Some(err @ (TransferError::ShareExpired | TransferError::ShareNotFound)) => {
+                        Ok(ShareInspectResult::invalid(err.to_string()))
+                    }
+                    _ => Err(e),
+                };
+            }
+        };
+
+        // 列出根目录（最多 INSPECT_LIST_MAX_PAGES 页）
+        let mut files = Vec::new();
+        for page in 1..=INSPECT_LIST_MAX_PAGES {
+            match client
+                .list_share_files(&share_link.short_key, &share_info.bdstoken, page, SHARE_LIST_PAGE_SIZE)
+                .await
+            {
+                Ok(result) => {
+                    let count = result.files.len();
+                    files.extend(result.files);
+                    if count < SHARE_LIST_PAGE_SIZE as usize {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!("检查分享链接: 列出根目录失败，省略文件数: {}", e);
+                    return Ok(ShareInspectResult {
+                        valid: true,
+                        needs_password: false,
+                        file_count: None,
+                        title: None,
+                        reason: None,
+                    });
+                }
+            }
+        }
+
+        info!("检查分享链接: 有效，根目录 {} 个文件", files.len());
+        Ok(ShareInspectResult {
+            valid: true,
+            needs_password: false,
+            file_count: Some(files.len()),
+            title: share_title(&files),
+            reason: None,
+        })
+    }
+
+    /// 免转存直接下载分享文件
+    ///
+    /// 步骤：
+    /// 1. 访问分享页面并验证提取码，获取 sekey
+    /// 2. 携带 sekey 列出分享目录，获取文件直链（dlink），子目录递归展开
+    /// 3. 为每个文件创建下载任务并设置直链，由 DownloadEngine 直接下载
+    ///
+    /// 不会向自己的网盘转存任何文件，也不创建转存任务
+    pub async fn create_direct_download(
+        &self,
+        request: DirectDownloadRequest,
+    ) -> Result<DirectDownloadResult> {
+        info!("免转存直接下载: url={}", request.share_url);
+
+        let download_manager = self
+            .download_manager
+            .read()
+            .await
+            .clone()
+            .context("下载管理器未初始化")?;
+
+        // 1. 解析分享链接并验证
+        let share_link = self.client.read().unwrap().parse_share_link(&request.share_url)?;
+        let password = request.password.or(share_link.password.clone());
+        let client = self.client.read().unwrap().clone();
+
+        let share_info = client
+            .access_share_page(&share_link.short_key, &password, true)
+            .await?;
+
+        let sekey = match password {
+            Some(ref pwd) => {
+                let referer = format!("https://pan.baidu.com/s/{}", share_link.short_key);
+                let randsk = client
+                    .verify_share_password_with_captcha(
+                        &share_info.shareid,
+                        &share_info.share_uk,
+                        &share_info.bdstoken,
+                        pwd,
+                        &referer,
+                        request.captcha.as_ref(),
+                    )
+                    .await?;
+                Some(randsk).filter(|k| !k.is_empty())
+            }
+            None => None,
+        };
+
+        // access_share_page 可能提取不到 uk/shareid，用根目录列表响应补充
+        let (uk, shareid) = if share_info.uk.is_empty() || share_info.shareid.is_empty() {
+            let list_result = client
+                .list_share_files(&share_link.short_key, &share_info.bdstoken, 1, 1)
+                .await?;
+            (list_result.uk, list_result.shareid)
+        } else {
+            (share_info.uk.clone(), share_info.shareid.clone())
+        };
+
+        let source = ShareDirectSource {
+            short_key: share_link.short_key,
+            shareid,
+            uk,
+            bdstoken: share_info.bdstoken,
+            sekey,
+        };
+
+        // 2. 确定下载入口（选中文件或根目录全部文件）
+        // 🔥 直链一律来自服务端列目录结果，不信任请求中携带的 dlink（下载时会附带账号 Cookie）
+        let mut failed_paths = Vec::new();
+        let root_files = Self::list_share_dir_with_dlink(&client, &source, None).await?;
+        let entries = match request.selected_files {
+            Some(selected) if !selected.is_empty() => {
+                let mut dir_cache: HashMap<String, Vec<SharedFileInfo>> = HashMap::new();
+                let mut entries = Vec::with_capacity(selected.len());
+                for mut file in selected {
+                    file.dlink = None;
+                    if file.is_dir {
+                        entries.push(file);
+                        continue;
+                    }
+                    if let Some(found) = root_files.iter().find(|f| f.fs_id == file.fs_id) {
+                        entries.push(found.clone());
+                        continue;
+                    }
+                    // 子目录中选中的文件：列出其父目录获取直链
+                    let parent = extract_parent_dir_str(&file.path).to_string();
+                    if !dir_cache.contains_key(&parent) {
+                        let listed =
+                            Self::list_share_dir_with_dlink(&client, &source, Some(&parent)).await?;
+                        dir_cache.insert(parent.clone(), listed);
+                    }
+                    match dir_cache[&parent].iter().find(|f| f.fs_id == file.fs_id) {
+                        Some(found) => entries.push(found.clone()),
+                        None => {
+                            warn!("分享中未找到选中的文件，跳过: {}", file.path);
+                            failed_paths.push(file.path);
+                        }
+                    }
+                }
+                entries
+            }
+            _ => root_files,
+        };
+
+        let base_dir = match request.local_download_path {
+            Some(ref path) if !path.is_empty() => PathBuf::from(path),
+            _ => download_manager.download_dir().await,
+        };
+
+        // 3. 展开目录（深度优先），收集 (文件, 本地目录)
+        let mut files: Vec<(SharedFileInfo, PathBuf)> = Vec::new();
+        let mut pending_dirs: Vec<(SharedFileInfo, PathBuf)> = Vec::new();
+        for entry in entries {
+            if entry.is_dir {
+                pending_dirs.push((entry, base_dir.clone()));
+            } else {
+                files.push((entry, base_dir.clone()));
+            }
+        }
+
+        while let Some((dir, parent_local)) = pending_dirs.pop() {
+            let local_dir = parent_local.join(&dir.name);
+            let children =
+                Self::list_share_dir_with_dlink(&client, &source, Some(&dir.path)).await?;
+            debug!("展开分享子目录: {} ({} 项)", dir.path, children.len());
+            for child in children {
+                if child.is_dir {
+                    pending_dirs.push((child, local_dir.clone()));
+                } else {
+                    files.push((child, local_dir.clone()));
+                }
+            }
+        }
+
+        // 4. 创建下载任务并设置直链
+        let mut task_ids = Vec::new();
+        for (file, local_dir) in files {
+            let Some(dlink) = file.dlink.clone() else {
+                warn!("分享文件未返回直链，跳过: {}", file.path);
+                failed_paths.push(file.path);
+                continue;
+            };
+
+            let task_id = match download_manager
+                .create_task_with_dir(file.fs_id, file.path.clone(), file.name.clone(), file.size, &local_dir, file.md5.clone(), None, None)
+                .await
+            {
+                Ok(id) => id,
+                Err(e) => {
+                    warn!("创建直接下载任务失败: path={}, 错误: {}", file.path, e);
+                    failed_paths.push(file.path);
+                    continue;
+                }
+            };
+
+            if let Err(e) = download_manager.set_task_direct_dlink(&task_id, dlink, source.clone()).await {
+                warn!("设置分享直链失败: task_id={}, 错误: {}", task_id, e);
+                failed_paths.push(file.path);
+                continue;
+            }
+            if let Err(e) = download_manager.start_task(&task_id).await {
+                warn!("启动直接下载任务失败: task_id={}, 错误: {}", task_id, e);
+            }
+            task_ids.push(task_id);
+        }
+
+        info!(
+            "免转存直接下载: 创建 {} 个下载任务, {} 个失败",
+            task_ids.len(),
+            failed_paths.len()
+        );
+
+        Ok(DirectDownloadResult {
+            task_ids,
+            failed_paths,
+        })
+    }
+
+    /// 列出分享目录下的全部文件（自动翻页，携带直链）
+    async fn list_share_dir_with_dlink(
+        client: &NetdiskClient,
+        source: &ShareDirectSource,
+        dir: Option<&str>,
+    ) -> Result<Vec<SharedFileInfo>> {
+        const PAGE_SIZE: u32 = 100;
+        const MAX_PAGES: u32 = 100;
+
+        let mut all_files = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let files = client
+                .list_share_files_with_dlink(source, dir, page, PAGE_SIZE)
+                .await?;
+            let count = files.len();
+            all_files.extend(files);
+            if count < PAGE_SIZE as usize {
+                break;
+            }
+        }
+        Ok(all_files)
+    }
+
+    /// 递归展开分享中的文件夹（自动翻页），返回全部文件
+    ///
+    /// 空文件夹保留自身，转存后仍能还原目录结构；展开的文件数超过上限时返回错误
+    async fn expand_share_dirs(
+        client: &NetdiskClient,
+        short_key: &str,
+        share_info: &SharePageInfo,
+        entries: Vec<SharedFileInfo>,
+    ) -> Result<Vec<SharedFileInfo>> {
+        // access_share_page 可能提取不到 uk/shareid，用根目录列表响应补充
+        let (uk, shareid) = if share_info.uk.is_empty() || share_info.shareid.is_empty() {
+            let list_result = client
+                .list_share_files(short_key, &share_info.bdstoken, 1, 1)
+                .await?;
+            (list_result.uk, list_result.shareid)
+        } else {
+            (share_info.uk.clone(), share_info.shareid.clone())
+        };
+
+        let mut files = Vec::new();
+        let mut pending_dirs: Vec<SharedFileInfo> = Vec::new();
+        for entry in entries {
+            if entry.is_dir {
+                pending_dirs.push(entry);
+            } else {
+                files.push(entry);
+            }
+        }
+
+        while let Some(dir) = pending_dirs.pop() {
+            let mut children = Vec::new();
+            for page in 1..=SHARE_LIST_MAX_PAGES {
+                let batch = client
+                    .list_share_files_in_dir(
+                        short_key,
+                        &shareid,
+                        &uk,
+                        &share_info.bdstoken,
+                        &dir.path,
+                        page,
+                        SHARE_LIST_PAGE_SIZE,
+                    )
+                    .await?;
+                let count = batch.len();
+                children.extend(batch);
+                if count < SHARE_LIST_PAGE_SIZE as usize {
+                    break;
+                }
+            }
+            debug!("展开分享子目录: {} ({} 项)", dir.path, children.len());
+
+            if children.is_empty() {
+                files.push(dir);
+                continue;
+            }
+            for child in children {
+                if child.is_dir {
+                    pending_dirs.push(child);
+                } else {
+                    files.push(child);
+                }
+            }
+            if files.len() > MAX_RECURSIVE_TRANSFER_FILES {
+                anyhow::bail!("分享文件数超过递归转存上限 {}", MAX_RECURSIVE_TRANSFER_FILES);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// 携带验证码重新创建转存任务
+    ///
+    /// create_task 返回 captcha 后，用户识别验证码并连同原始请求一起提交
+    pub async fn retry_with_captcha(
+        &self,
+        request: CreateTransferRequest,
+        answer: ShareCaptchaAnswer,
+    ) -> Result<CreateTransferResponse> {
+        info!("携带验证码重新创建转存任务: url={}", request.share_url);
+        self.create_task(CreateTransferRequest {
+            captcha: Some(answer),
+            ..request
+        })
+            .await
+    }
+
     /// 创建转存任务
     ///
     /// 如果需要密码，返回 need_password=true
@@ -289,8 +788,19 @@ impl TransferManager {
             password: password.clone(), // 密码已提取
         };
 
+        // 🔥 「转存→下载→删除网盘副本」复用分享直下流程（临时目录 + 强制自动下载）
+        let download_cleanup = !request.is_share_direct_download
+            && match request.download_cleanup {
+                Some(v) => v,
+                None => {
+                    request.auto_download.is_none()
+                        && self.config.read().await.default_behavior == TRANSFER_BEHAVIOR_DOWNLOAD_CLEANUP
+                }
+            };
+        let is_share_direct_download = request.is_share_direct_download || download_cleanup;
+
         // 2. 处理分享直下模式
-        let (save_path, save_fs_id, auto_download, temp_dir) = if request.is_share_direct_download {
+        let (save_path, save_fs_id, auto_download, temp_dir) = if is_share_direct_download {
             // 分享直下模式：生成临时目录路径
             let task_uuid = uuid::Uuid::new_v4().to_string();
             let app_cfg = self.app_config.read().await;
@@ -307,7 +817,7 @@ impl TransferManager {
                 Some(v) => v,
                 None => {
                     let config = self.config.read().await;
-                    config.default_behavior == "transfer_and_download"
+                    config.default_behavior == TRANSFER_BEHAVIOR_AND_DOWNLOAD
                 }
             };
             (request.save_path.clone(), request.save_fs_id, auto_download, None)
@@ -324,14 +834,17 @@ impl TransferManager {
         );
 
         // 设置分享直下相关字段
-        if request.is_share_direct_download {
+        if is_share_direct_download {
             task.is_share_direct_download = true;
             task.temp_dir = temp_dir.clone();
+            task.download_cleanup = download_cleanup;
         }
 
         // 设置选择性转存字段
         task.selected_fs_ids = request.selected_fs_ids.clone();
         task.selected_files = request.selected_files.clone();
+        task.share_subfolder = request.share_subfolder;
+        task.recursive = request.recursive;
 
         let task_id = task.id.clone();
 
@@ -347,12 +860,13 @@ impl TransferManager {
                 if let Some(ref pwd) = password {
                     let referer = format!("https://pan.baidu.com/s/{}", share_link.short_key);
                     match client
-                        .verify_share_password(
+                        .verify_share_password_with_captcha(
                             &info.shareid,
                             &info.share_uk,
                             &info.bdstoken,
                             pwd,
                             &referer,
+                            request.captcha.as_ref(),
                         )
                         .await
                     {
@@ -360,20 +874,22 @@ impl TransferManager {
                             info!("提取码验证成功");
                         }
                         Err(e) => {
-                            let err_msg = e.to_string();
-                            if err_msg.contains("提取码错误") || err_msg.contains("-9") {
+                            // 🔥 区分提取码错误和需要验证码，验证码返回给前端展示
+                            if let Some(err) = TransferError::find(&e) {
                                 return Ok(CreateTransferResponse {
                                     task_id: None,
                                     status: None,
                                     need_password: false,
-                                    error: Some("提取码错误".to_string()),
+                                    captcha_error: err.captcha().is_some().then(|| err.clone()),
+                                    error: Some(err.to_string()),
                                 });
                             }
                             return Ok(CreateTransferResponse {
                                 task_id: None,
                                 status: None,
                                 need_password: false,
-                                error: Some(err_msg),
+                                captcha_error: None,
+                                error: Some(e.to_string()),
                             });
                         }
                     }
@@ -417,7 +933,7 @@ impl TransferManager {
                     }
 
                     // 🔥 如果是分享直下任务，更新分享直下相关字段
-                    if request.is_share_direct_download {
+                    if is_share_direct_download {
                         if let Err(e) = pm_arc.lock().await.update_share_direct_download_info(
                             &task_id,
                             true,
@@ -426,6 +942,29 @@ impl TransferManager {
                             warn!("更新分享直下信息失败: {}", e);
                         }
                     }
+                    if download_cleanup {
+                        if let Err(e) = pm_arc
+                            .lock()
+                            .await
+                            .update_transfer_download_cleanup(&task_id, true)
+                        {
+                            warn!("更新下载后清理标记失败: {}", e);
+                        }
+                    }
+                    if request.recursive {
+                        if let Err(e) = pm_arc.lock().await.update_transfer_recursive(&task_id, true) {
+                            warn!("更新递归转存标记失败: {}", e);
+                        }
+                    }
+                    if let Some(share_subfolder) = request.share_subfolder {
+                        if let Err(e) = pm_arc
+                            .lock()
+                            .await
+                            .update_transfer_share_subfolder(&task_id, share_subfolder)
+                        {
+                            warn!("更新分享子文件夹命名失败: {}", e);
+                        }
+                    }
                 }
 
                 // 🔥 发送任务创建事件
@@ -437,66 +976,62 @@ impl TransferManager {
                 })
                     .await;
 
-                // 启动异步执行
-                self.spawn_task_execution(task_id.clone(), share_link, cancellation_token)
+                // 启动异步执行（超出并发上限时排队）
+                let queued = self
+                    .spawn_task_execution(task_id.clone(), share_link, cancellation_token)
                     .await;
 
                 Ok(CreateTransferResponse {
                     task_id: Some(task_id),
-                    status: Some(TransferStatus::CheckingShare),
+                    status: Some(if queued {
+                        TransferStatus::Queued
+                    } else {
+                        TransferStatus::CheckingShare
+                    }),
                     need_password: false,
+                    captcha_error: None,
                     error: None,
                 })
             }
             Err(e) => {
-                let err_msg = e.to_string();
-
-                // 检查是否需要密码
-                if err_msg.contains("需要密码") || err_msg.contains("need password") {
-                    if password.is_none() {
-                        return Ok(CreateTransferResponse {
-                            task_id: None,
-                            status: None,
-                            need_password: true,
-                            error: Some("需要提取码".to_string()),
-                        });
-                    }
-                    // 有密码但可能是错误的，继续尝试验证
-                }
-
-                // 检查分享是否失效
-                if err_msg.contains("已失效") || err_msg.contains("expired") {
-                    return Ok(CreateTransferResponse {
-                        task_id: None,
-                        status: None,
-                        need_password: false,
-                        error: Some("分享已失效".to_string()),
-                    });
-                }
-
-                // 检查分享是否不存在
-                if err_msg.contains("不存在") || err_msg.contains("not found") {
-                    return Ok(CreateTransferResponse {
-                        task_id: None,
-                        status: None,
-                        need_password: false,
-                        error: Some("分享不存在".to_string()),
-                    });
-                }
+                let need_password = match TransferError::find(&e) {
+                    Some(TransferError::NeedPassword) if password.is_none() => true,
+                    Some(TransferError::ShareExpired | TransferError::ShareNotFound) => false,
+                    // 其他错误（有密码但仍提示需要密码时同样按错误返回）
+                    _ => return Err(e),
+                };
 
-                // 其他错误
-                Err(e)
+                Ok(CreateTransferResponse {
+                    task_id: None,
+                    status: None,
+                    need_password,
+                    captcha_error: None,
+                    error: Some(e.to_string()),
+                })
             }
         }
     }
 
     /// 异步执行转存任务
+    ///
+    /// 先在等待队列中排队获取转存槽位，返回是否需要排队
     async fn spawn_task_execution(
         &self,
         task_id: String,
         share_link: ShareLink,
         cancellation_token: CancellationToken,
-    ) {
+    ) -> bool {
+        let max_concurrent = self.config.read().await.max_concurrent_transfers.max(1);
+        let queued = self.transfer_slots.enqueue(task_id.clone(), max_concurrent);
+        if queued {
+            info!(
+                "转存任务排队中: task_id={}, 队列位置={:?}",
+                task_id,
+                self.transfer_slots.queue_position(&task_id)
+            );
+        }
+
+        let transfer_slots = self.transfer_slots.clone();
         let client = self.client.clone();
         let tasks = self.tasks.clone();
         let download_manager = self.download_manager.clone();
@@ -507,6 +1042,15 @@ impl TransferManager {
         let ws_manager = self.ws_manager.read().await.clone();
 
         tokio::spawn(async move {
+            // 🔥 等待转存槽位，排队期间被取消则直接退出
+            let Some(_slot) = transfer_slots
+                .acquire(&task_id, &config, &cancellation_token)
+                .await
+            else {
+                info!("排队中的转存任务已取消: task_id={}", task_id);
+                return;
+            };
+
             let result = Self::execute_task(
                 client,
                 tasks.clone(),
@@ -560,6 +1104,8 @@ impl TransferManager {
                 }
             }
         });
+
+        queued
     }
 
     /// 执行转存任务的核心逻辑
@@ -609,11 +1155,13 @@ impl TransferManager {
             return Ok(());
         }
 
-        // 获取分享信息
-        let share_info = {
+        // 获取分享信息（bdstoken 失效时会重新访问分享页刷新）
+        let mut share_info = {
             let t = task.read().await;
             t.share_info.clone().context("分享信息未设置")?
         };
+        // 🔥 每个任务只刷新一次 bdstoken，避免无效重试
+        let mut bdstoken_refreshed = false;
 
         // 检查取消
         if cancellation_token.is_cancelled() {
@@ -690,17 +1238,7 @@ impl TransferManager {
         };
 
         // 🔥 从过滤后的文件列表中提取主要文件名
-        let transfer_file_name = if !filtered_file_list.is_empty() {
-            if filtered_file_list.len() == 1 {
-                // 只有一个文件/文件夹，使用其名称
-                Some(filtered_file_list[0].name.clone())
-            } else {
-                // 多个文件，使用第一个文件名 + 等x个文件
-                Some(format!("{} 等{}个文件", filtered_file_list[0].name, filtered_file_list.len()))
-            }
-        } else {
-            None
-        };
+        let transfer_file_name = share_title(&filtered_file_list);
 
         // 更新任务文件列表和文件名（使用过滤后的列表）
         let old_status;
@@ -768,6 +1306,11 @@ impl TransferManager {
 
         info!("转存参数: save_path={}, is_share_direct_download={}", save_path, is_share_direct_download);
 
+        // 🔥 转存前检查网盘剩余空间（未展开的文件夹大小未知，按已知文件大小估算）
+        client
+            .ensure_free_space(filtered_file_list.iter().map(|f| f.size).sum())
+            .await?;
+
         // 分享直下模式：转存前先在网盘上创建临时目录
         if is_share_direct_download {
             info!("分享直下模式: 创建临时目录 {}", save_path);
@@ -925,14 +1468,39 @@ impl TransferManager {
             }
         }
 
+        // 🔥 递归模式：展开选中的文件夹，按文件（保留目录结构）分批转存
+        let recursive = task.read().await.recursive;
+        let transfer_files = if recursive {
+            let expanded = Self::expand_share_dirs(
+                &client,
+                &share_link.short_key,
+                &share_info,
+                filtered_file_list.clone(),
+            )
+                .await?;
+            info!(
+                "递归展开分享文件夹: {} 个选中项 -> {} 个文件",
+                filtered_file_list.len(),
+                expanded.len()
+            );
+            task.write().await.total_count = expanded.len();
+            // 展开后文件大小已知，按实际总大小再检查一次剩余空间
+            client
+                .ensure_free_space(expanded.iter().map(|f| f.size).sum())
+                .await?;
+            expanded
+        } else {
+            filtered_file_list.clone()
+        };
+
         // ========== 转存策略：统一按原始父目录分组，保留完整目录结构 ==========
         let (transfer_result, batch_groups_info): (Result<TransferResult>, Option<Vec<BatchGroupInfo>>) = {
-            let share_root = infer_share_root(&filtered_file_list);
-            let groups = group_files_by_parent_dir(&filtered_file_list, &share_root);
+            let share_root = infer_share_root(&transfer_files);
+            let groups = group_files_by_parent_dir(&transfer_files, &share_root);
             let total_groups = groups.len();
 
             // 诊断日志：跨目录同名检测（仅用于日志）
-            let cross_dir_dups = detect_cross_dir_duplicates(&filtered_file_list);
+            let cross_dir_dups = detect_cross_dir_duplicates(&transfer_files);
             if !cross_dir_dups.is_empty() {
                 warn!(
                         "检测到 {} 个跨目录同名 basename: {:?}",
@@ -994,75 +1562,140 @@ impl TransferManager {
                     }
                 }
 
-                // 提取该组的 fs_ids
-                let group_fs_ids: Vec<u64> = group_files.iter().map(|f| f.fs_id).collect();
-
-                // 转存该组
-                let result = client
-                    .transfer_share_files(
-                        &share_info.shareid,
-                        &share_info.share_uk,
-                        &share_info.bdstoken,
-                        &group_fs_ids,
-                        &group_target_dir,
-                        &referer,
-                        Some(task_id),
-                    )
-                    .await;
+                // 🔥 按单次转存上限分块，超过百度文件数上限（errno=12）时继续对半拆分
+                let mut pending_chunks = chunk_transfer_files(group_files, TRANSFER_CHUNK_SIZE);
+                while let Some(chunk_files) = pending_chunks.pop_front() {
+                    if cancellation_token.is_cancelled() {
+                        break;
+                    }
 
-                // errno=2 重试：逐级创建中间目录后重试一次
-                let result = match &result {
-                    Ok(r) if !r.success => {
-                        let err_msg = r.error.as_deref().unwrap_or("");
-                        if err_msg.contains("errno\":2") || err_msg.contains("路径不存在") {
-                            warn!("批次 {} 路径不存在，逐级创建目录后重试: {}", batch_num, group_target_dir);
-                            let save_base = save_path.trim_end_matches('/');
-                            if !is_share_direct_download {
-                                let mut cumulative = String::new();
-                                for seg in save_base.split('/').filter(|s| !s.is_empty()) {
-                                    cumulative.push('/');
-                                    cumulative.push_str(seg);
+                    // 提取该块的 fs_ids
+                    let group_fs_ids: Vec<u64> = chunk_files.iter().map(|f| f.fs_id).collect();
+
+                    // 转存该块
+                    let result = client
+                        .transfer_share_files(
+                            &share_info.shareid,
+                            &share_info.share_uk,
+                            &share_info.bdstoken,
+                            &group_fs_ids,
+                            &group_target_dir,
+                            &referer,
+                            Some(task_id),
+                        )
+                        .await;
+
+                    // errno=2 重试：逐级创建中间目录后重试一次
+                    let result = match &result {
+                        Ok(r) if !r.success => {
+                            let err_msg = r.error.as_deref().unwrap_or("");
+                            if err_msg.contains("errno\":2") || err_msg.contains("路径不存在") {
+                                warn!("批次 {} 路径不存在，逐级创建目录后重试: {}", batch_num, group_target_dir);
+                                let save_base = save_path.trim_end_matches('/');
+                                if !is_share_direct_download {
+                                    let mut cumulative = String::new();
+                                    for seg in save_base.split('/').filter(|s| !s.is_empty()) {
+                                        cumulative.push('/');
+                                        cumulative.push_str(seg);
+                                        let _ = client.create_folder(&cumulative).await;
+                                    }
+                                }
+                                let segments: Vec<&str> = relative_parent.split('/').filter(|s| !s.is_empty()).collect();
+                                let mut cumulative = save_base.to_string();
+                                for seg in &segments {
+                                    cumulative = format!("{}/{}", cumulative, seg);
                                     let _ = client.create_folder(&cumulative).await;
                                 }
+                                client
+                                    .transfer_share_files(
+                                        &share_info.shareid,
+                                        &share_info.share_uk,
+                                        &share_info.bdstoken,
+                                        &group_fs_ids,
+                                        &group_target_dir,
+                                        &referer,
+                                        Some(task_id),
+                                    )
+                                    .await
+                            } else {
+                                result
                             }
-                            let segments: Vec<&str> = relative_parent.split('/').filter(|s| !s.is_empty()).collect();
-                            let mut cumulative = save_base.to_string();
-                            for seg in &segments {
-                                cumulative = format!("{}/{}", cumulative, seg);
-                                let _ = client.create_folder(&cumulative).await;
-                            }
-                            client
-                                .transfer_share_files(
-                                    &share_info.shareid,
-                                    &share_info.share_uk,
-                                    &share_info.bdstoken,
-                                    &group_fs_ids,
-                                    &group_target_dir,
-                                    &referer,
-                                    Some(task_id),
-                                )
+                        }
+                        _ => result,
+                    };
+
+                    // 🔥 bdstoken 失效（errno=-6）：重新访问分享页获取新 bdstoken 后重试一次
+                    let result = match &result {
+                        Ok(r) if is_stale_token_error(r) && !bdstoken_refreshed => {
+                            bdstoken_refreshed = true;
+                            warn!("批次 {} bdstoken 已失效，重新访问分享页后重试: {:?}", batch_num, r.error);
+                            match client
+                                .access_share_page(&share_link.short_key, &share_link.password, false)
                                 .await
-                        } else {
-                            result
+                            {
+                                Ok(fresh) if !fresh.bdstoken.is_empty() => {
+                                    share_info.bdstoken = fresh.bdstoken;
+                                    {
+                                        let mut t = task.write().await;
+                                        if let Some(ref mut info) = t.share_info {
+                                            info.bdstoken = share_info.bdstoken.clone();
+                                        }
+                                    }
+                                    client
+                                        .transfer_share_files(
+                                            &share_info.shareid,
+                                            &share_info.share_uk,
+                                            &share_info.bdstoken,
+                                            &group_fs_ids,
+                                            &group_target_dir,
+                                            &referer,
+                                            Some(task_id),
+                                        )
+                                        .await
+                                }
+                                Ok(_) => {
+                                    warn!("重新访问分享页未获取到 bdstoken，放弃重试");
+                                    result
+                                }
+                                Err(e) => {
+                                    warn!("重新访问分享页失败，放弃重试: {}", e);
+                                    result
+                                }
+                            }
+                        }
+                        _ => result,
+                    };
+
+                    if let Ok(ref r) = result {
+                        if is_transfer_limit_error(r) && chunk_files.len() > 1 {
+                            let mut first = chunk_files;
+                            let second = first.split_off(first.len() / 2);
+                            warn!(
+                                "批次 {} 转存文件数超过上限，拆分为 {} + {} 个文件后重试: {:?}",
+                                batch_num, first.len(), second.len(), r.error
+                            );
+                            pending_chunks.push_front(second);
+                            pending_chunks.push_front(first);
+                            tokio::time::sleep(Duration::from_millis(800)).await;
+                            continue;
                         }
                     }
-                    _ => result,
-                };
 
-                let batch_ok = match &result {
-                    Ok(r) => r.success,
-                    Err(_) => false,
-                };
-                info!(
-                        "批次 {}/{} 结果: success={}",
-                        batch_num, total_groups, batch_ok
-                    );
+                    let batch_ok = match &result {
+                        Ok(r) => r.success,
+                        Err(_) => false,
+                    };
+                    info!(
+                            "批次 {}/{} 结果: success={}, 文件数={}",
+                            batch_num, total_groups, batch_ok, chunk_files.len()
+                        );
 
-                all_results.push((batch_num, relative_parent, group_files, result));
+                    all_results.push((batch_num, relative_parent.clone(), chunk_files, result));
 
-                // 批次之间添加防抖延时
-                if batch_num < total_groups {
-                    tokio::time::sleep(Duration::from_millis(800)).await;
+                    // 批次之间添加防抖延时
+                    if batch_num < total_groups || !pending_chunks.is_empty() {
+                        tokio::time::sleep(Duration::from_millis(800)).await;
+                    }
                 }
             }
 
@@ -1214,6 +1847,16 @@ impl TransferManager {
                     (t.auto_download, t.file_list.clone(), t.is_share_direct_download)
                 };
 
+                if let Some(ref pm) = persistence_manager {
+                    if let Err(e) = pm
+                        .lock()
+                        .await
+                        .update_transferred_count(task_id, result.transferred_paths.len())
+                    {
+                        warn!("持久化转存成功数量失败: {}", e);
+                    }
+                }
+
                 if auto_download {
                     // 启动自动下载
                     Self::start_auto_download(
@@ -1226,7 +1869,8 @@ impl TransferManager {
                         ws_manager.clone(),
                         task_id,
                         result,
-                        file_list,
+                        // 递归模式下按展开后的文件列表匹配转存结果
+                        if recursive { transfer_files } else { file_list },
                         save_path,
                         cancellation_token,
                         is_share_direct_download,
@@ -1328,12 +1972,22 @@ impl TransferManager {
                         );
 
                         // 更新任务状态为已转存（不标记失败）
-                        let (auto_download, file_list) = {
+                        let (auto_download, file_list, transferred_count) = {
                             let mut t = task.write().await;
                             t.transferred_count = t.total_count;
-                            (t.auto_download, t.file_list.clone())
+                            (t.auto_download, t.file_list.clone(), t.transferred_count)
                         };
 
+                        if let Some(ref pm) = persistence_manager {
+                            if let Err(e) = pm
+                                .lock()
+                                .await
+                                .update_transferred_count(task_id, transferred_count)
+                            {
+                                warn!("持久化转存成功数量失败: {}", e);
+                            }
+                        }
+
                         if auto_download {
                             // 🔥 直接使用恢复结果构造 TransferResult，
                             // 不再重新扫描临时目录第一页（避免 >1000 项或选择性转存时丢项）
@@ -1380,6 +2034,7 @@ impl TransferManager {
                                 from_paths,
                                 error: None,
                                 transferred_fs_ids,
+                                errno: None,
                             };
 
                             Self::start_auto_download(
@@ -1544,7 +2199,7 @@ impl TransferManager {
     /// 2. 遍历转存的文件/文件夹，文件调用文件下载，文件夹调用文件夹下载
     /// 3. 启动下载状态监听，更新转存任务状态
     async fn start_auto_download(
-        _client: Arc<StdRwLock<NetdiskClient>>,
+        client: Arc<StdRwLock<NetdiskClient>>,
         tasks: Arc<DashMap<String, TransferTaskInfo>>,
         download_manager: Arc<RwLock<Option<Arc<DownloadManager>>>>,
         folder_download_manager: Arc<RwLock<Option<Arc<FolderDownloadManager>>>>,
@@ -1568,15 +2223,19 @@ impl TransferManager {
         drop(task_info);
 
         // 获取本地下载路径配置
-        let (local_download_path, ask_each_time, default_download_dir) = {
+        let (local_download_path, ask_each_time, default_download_dir, share_subfolder) = {
             let t = task.read().await;
             let local_path = t.local_download_path.clone();
+            let share_subfolder = t.share_subfolder.and_then(|naming| {
+                let short_key = client.read().unwrap().parse_share_link(&t.share_url).ok().map(|link| link.short_key);
+                share_subfolder_name(naming, t.file_name.as_deref(), short_key.as_deref())
+            });
             drop(t);
 
             let cfg = app_config.read().await;
             let ask = cfg.download.ask_each_time;
             let default_dir = cfg.download.download_dir.clone();
-            (local_path, ask, default_dir)
+            (local_path, ask, default_dir, share_subfolder)
         };
 
         // 确定下载目录
@@ -1594,6 +2253,12 @@ impl TransferManager {
             default_download_dir
         };
 
+        // 🔥 按分享创建子文件夹，多个分享下载到同一目录时互不混杂
+        let download_dir = match share_subfolder {
+            Some(name) => download_dir.join(name),
+            None => download_dir,
+        };
+
         info!(
             "开始自动下载: task_id={}, 文件数={}, 下载目录={:?}",
             task_id,
@@ -1610,7 +2275,7 @@ impl TransferManager {
 
         // 分类收集需要下载的文件和文件夹
         // 元组：(fs_id, remote_path, filename, size, local_dir)
-        let mut download_files: Vec<(u64, String, String, u64, PathBuf)> = Vec::new();
+        let mut download_files: Vec<(u64, String, String, u64, PathBuf, Option<String>)> = Vec::new();
         let mut download_folders: Vec<(String, PathBuf)> = Vec::new(); // (remote_path, local_dir)
 
         let is_batch = batch_groups_info.is_some();
@@ -1700,6 +2365,7 @@ impl TransferManager {
                         file_info.name.clone(),
                         file_info.size,
                         local_dir,
+                        file_info.md5.clone(),
                     ));
                 }
             } else {
@@ -1707,7 +2373,7 @@ impl TransferManager {
                 warn!("无法匹配文件信息: idx={}, path={}, from={:?}, to_filename={}",
                     idx, transferred_path, from_filename, to_filename);
                 let fs_id = transferred_fs_id.unwrap_or(0);
-                download_files.push((fs_id, transferred_path.clone(), to_filename.to_string(), 0, local_dir));
+                download_files.push((fs_id, transferred_path.clone(), to_filename.to_string(), 0, local_dir, None));
             }
         }
 
@@ -1720,7 +2386,7 @@ impl TransferManager {
 
         // 创建文件下载任务
         let mut download_task_ids = Vec::new();
-        for (fs_id, remote_path, filename, size, local_dir) in download_files {
+        for (fs_id, remote_path, filename, size, local_dir, md5) in download_files {
             // 确保本地下载目录存在（分批模式下可能是按原始结构还原出的父目录）
             if !local_dir.exists() {
                 if let Err(e) = tokio::fs::create_dir_all(&local_dir).await {
@@ -1734,6 +2400,8 @@ impl TransferManager {
                     filename.clone(),
                     size,
                     &local_dir,
+                    md5,
+                    None,
                     None,
                 )
                 .await
@@ -1785,7 +2453,7 @@ impl TransferManager {
                         }
                     }
                     match fdm
-                        .create_folder_download_with_dir(folder_path.clone(), &local_dir, None, None)
+                        .create_folder_download_with_dir(folder_path.clone(), &local_dir, None, None, None)
                         .await
                     {
                         Ok(folder_id) => {
@@ -1883,7 +2551,7 @@ impl TransferManager {
 
         // 启动下载状态监听
         Self::start_download_status_watcher(
-            _client,
+            client,
             tasks,
             download_manager,
             folder_download_manager,
@@ -1964,9 +2632,9 @@ impl TransferManager {
                         );
 
                         // 获取分享直下相关信息
-                        let (is_share_direct_download, temp_dir) = {
+                        let (is_share_direct_download, temp_dir, download_cleanup) = {
                             let t = task.read().await;
-                            (t.is_share_direct_download, t.temp_dir.clone())
+                            (t.is_share_direct_download, t.temp_dir.clone(), t.download_cleanup)
                         };
 
                         {
@@ -1980,7 +2648,7 @@ impl TransferManager {
                         if is_share_direct_download {
                             let (cleanup_on_failure, configured_root) = {
                                 let cfg = app_config.read().await;
-                                (cfg.share_direct_download.cleanup_on_failure, cfg.share_direct_download.temp_dir.clone())
+                                (should_cleanup_temp_dir(download_cleanup, false, false, &cfg), cfg.share_direct_download.temp_dir.clone())
                             };
 
                             if cleanup_on_failure {
@@ -2011,14 +2679,19 @@ impl TransferManager {
                         task_id, new_status
                     );
 
+                    // 🔥 下载后清理任务只有在文件全部实际校验通过时才删除网盘副本
+                    let md5_verified = new_status == TransferStatus::Completed
+                        && Self::downloads_md5_verified(&download_manager, &folder_download_manager, &download_task_ids).await;
+
                     // 获取分享直下相关信息
-                    let (is_share_direct_download, temp_dir, auto_cleanup, configured_root) = {
+                    let (is_share_direct_download, temp_dir, download_cleanup, auto_cleanup, configured_root) = {
                         let t = task.read().await;
                         let cfg = app_config.read().await;
                         (
                             t.is_share_direct_download,
                             t.temp_dir.clone(),
-                            cfg.share_direct_download.auto_cleanup,
+                            t.download_cleanup,
+                            should_cleanup_temp_dir(t.download_cleanup, true, md5_verified, &cfg),
                             cfg.share_direct_download.temp_dir.clone(),
                         )
                     };
@@ -2117,6 +2790,12 @@ impl TransferManager {
                                     }
                                 } else {
                                     // 不自动清理，直接标记为完成
+                                    if download_cleanup {
+                                        warn!(
+                                            "下载文件未全部通过 MD5 校验，保留网盘副本: task_id={}, temp_dir={:?}",
+                                            task_id, temp_dir
+                                        );
+                                    }
                                     let old_status;
                                     {
                                         let mut t = task.write().await;
@@ -2151,7 +2830,7 @@ impl TransferManager {
                                 // 下载失败，根据配置决定是否清理
                                 let cleanup_on_failure = {
                                     let cfg = app_config.read().await;
-                                    cfg.share_direct_download.cleanup_on_failure
+                                    should_cleanup_temp_dir(download_cleanup, false, false, &cfg)
                                 };
 
                                 let old_status;
@@ -3051,6 +3730,46 @@ impl TransferManager {
         None
     }
 
+    /// 检查所有关联下载任务是否都通过了 MD5 校验
+    ///
+    /// 文件任务看任务自身的 md5_verified，文件夹任务要求每个文件都校验通过；
+    /// 任务已不在内存中（取消、重启后）无法确认，视为未校验
+    async fn downloads_md5_verified(
+        download_manager: &Arc<RwLock<Option<Arc<DownloadManager>>>>,
+        folder_download_manager: &Arc<RwLock<Option<Arc<FolderDownloadManager>>>>,
+        download_task_ids: &[String],
+    ) -> bool {
+        let dm_lock = download_manager.read().await;
+        let Some(dm) = dm_lock.as_ref() else {
+            return false;
+        };
+        let fdm_lock = folder_download_manager.read().await;
+
+        for task_id in download_task_ids {
+            let verified = if let Some(folder_id) = task_id.strip_prefix("folder:") {
+                match fdm_lock.as_ref() {
+                    Some(fdm) => match fdm.get_folder(folder_id).await {
+                        Some(folder) => folder.md5_verified_count >= folder.total_files,
+                        None => false,
+                    },
+                    None => false,
+                }
+            } else {
+                match dm.get_task(task_id).await {
+                    Some(task) => task.md5_verified,
+                    None => false,
+                }
+            };
+
+            if !verified {
+                debug!("下载任务未通过 MD5 校验: {}", task_id);
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// 获取所有任务（包括当前任务和历史任务）
     pub async fn get_all_tasks(&self) -> Vec<TransferTask> {
         let mut result = Vec::new();
@@ -3058,7 +3777,9 @@ impl TransferManager {
         // 获取当前任务
         for entry in self.tasks.iter() {
             if let Ok(task) = entry.value().task.try_read() {
-                result.push(task.clone());
+                let mut task = task.clone();
+                task.queue_position = self.transfer_slots.queue_position(&task.id);
+                result.push(task);
             }
         }
 
@@ -3097,6 +3818,104 @@ impl TransferManager {
         result
     }
 
+    /// 分页查询转存历史（直接查询历史数据库）
+    ///
+    /// 关键字匹配分享链接和转存目标路径
+    pub async fn query_history(&self, mut query: TaskHistoryQuery) -> (Vec<TransferTask>, usize) {
+        let Some(pm_arc) = self
+            .persistence_manager
+            .lock()
+            .await
+            .as_ref()
+            .map(|pm| pm.clone())
+        else {
+            return (Vec::new(), 0);
+        };
+
+        query.task_type = Some("transfer".to_string());
+
+        let result = pm_arc.lock().await.query_history_tasks(&query);
+        match result {
+            Some((history_tasks, total)) => (
+                history_tasks
+                    .iter()
+                    .filter_map(Self::convert_history_to_task)
+                    .collect(),
+                total,
+            ),
+            None => (Vec::new(), 0),
+        }
+    }
+
+    /// 清除已完成的转存任务（Completed/Transferred）
+    ///
+    /// 同时清理内存、持久化文件和历史数据库，返回清除数量（同时在内存和历史中的任务只计一次）
+    pub async fn clear_completed(&self) -> usize {
+        // 1. 收集内存中的已完成任务
+        //    先复制任务引用并释放 DashMap 分片锁，再逐个等待任务锁
+        let snapshot: Vec<(String, Arc<RwLock<TransferTask>>)> = self
+            .tasks
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.task.clone()))
+            .collect();
+        let mut to_remove = Vec::new();
+        for (task_id, task) in snapshot {
+            if matches!(
+                task.read().await.status,
+                TransferStatus::Completed | TransferStatus::Transferred
+            ) {
+                to_remove.push(task_id);
+            }
+        }
+
+        // 2. 从内存中移除
+        let memory_count = to_remove.len();
+        for task_id in &to_remove {
+            self.tasks.remove(task_id);
+        }
+
+        // 3. 清理持久化文件，并从历史数据库中清除已完成任务
+        let mut cleared: HashSet<String> = to_remove.iter().cloned().collect();
+        let mut history_count = 0;
+        if let Some(pm_arc) = self
+            .persistence_manager
+            .lock()
+            .await
+            .as_ref()
+            .map(|pm| pm.clone())
+        {
+            let pm_guard = pm_arc.lock().await;
+            for task_id in &to_remove {
+                if let Err(e) = pm_guard.on_task_deleted(task_id) {
+                    warn!("清理转存任务持久化文件失败: task_id={}, 错误: {}", task_id, e);
+                }
+            }
+            let history_db = pm_guard.history_db().cloned();
+
+            // 释放 pm_guard，避免长时间持锁
+            drop(pm_guard);
+
+            if let Some(db) = history_db {
+                match db.remove_task_ids_by_type_and_status("transfer", "completed") {
+                    Ok(ids) => {
+                        history_count = ids.len();
+                        cleared.extend(ids);
+                    }
+                    Err(e) => {
+                        warn!("从历史数据库删除已完成转存任务失败: {}", e);
+                    }
+                }
+            }
+        }
+
+        let total_count = cleared.len();
+        info!(
+            "清除了 {} 个已完成的转存任务（内存: {}, 历史: {}）",
+            total_count, memory_count, history_count
+        );
+        total_count
+    }
+
     /// 将历史元数据转换为转存任务
     fn convert_history_to_task(metadata: &TaskMetadata) -> Option<TransferTask> {
         // 验证必要字段
@@ -3127,13 +3946,17 @@ impl TransferManager {
             _ => TransferStatus::Completed, // 已完成的任务默认使用 Completed
         };
 
-        // 根据文件列表计算 total_count 和 transferred_count
+        // 根据文件列表计算 total_count
         let total_count = if !file_list.is_empty() {
             file_list.len()
         } else {
             metadata.download_task_ids.len()
         };
-        let transferred_count = total_count;
+        // 优先使用持久化的实际转存数量（部分转存/转存失败时小于文件总数），旧记录回退为按状态推断
+        let transferred_count = metadata.transferred_count.unwrap_or(match status {
+            TransferStatus::TransferFailed => 0,
+            _ => total_count,
+        });
 
         Some(TransferTask {
             id: metadata.task_id.clone(),
@@ -3158,20 +3981,42 @@ impl TransferManager {
             file_name: metadata.transfer_file_name.clone(),
             is_share_direct_download: metadata.is_share_direct_download.unwrap_or(false),
             temp_dir: metadata.temp_dir.clone(),
+            download_cleanup: metadata.download_cleanup.unwrap_or(false),
             selected_fs_ids: None,
             selected_files: None,
+            share_subfolder: metadata.share_subfolder,
+            recursive: metadata.recursive.unwrap_or(false),
+            queue_position: None,
         })
     }
 
     /// 获取单个任务
     pub async fn get_task(&self, id: &str) -> Option<TransferTask> {
         if let Some(task_info) = self.tasks.get(id) {
-            Some(task_info.task.read().await.clone())
+            let mut task = task_info.task.read().await.clone();
+            task.queue_position = self.transfer_slots.queue_position(id);
+            Some(task)
         } else {
             None
         }
     }
 
+    /// 🔥 停用管理器（切换账号时调用）
+    ///
+    /// 取消排队和执行中的转存及下载状态监听，任务状态保持不变，
+    /// 切回该账号时从 WAL 恢复。返回被中断的任务数
+    pub fn shutdown(&self) -> usize {
+        let interrupted = self
+            .tasks
+            .iter()
+            .filter(|entry| !entry.cancellation_token.is_cancelled())
+            .inspect(|entry| entry.cancellation_token.cancel())
+            .count();
+        // 唤醒排队中的任务，使其检查取消令牌后退出
+        self.transfer_slots.notify.notify_waiters();
+        interrupted
+    }
+
     /// 取消任务
     ///
     /// 扩展的取消逻辑，支持分享直下任务的清理：
@@ -3402,6 +4247,9 @@ impl TransferManager {
     pub async fn update_config(&self, config: TransferConfig) {
         let mut cfg = self.config.write().await;
         *cfg = config;
+        drop(cfg);
+        // 并发上限可能调大，唤醒排队任务重新检查
+        self.transfer_slots.notify.notify_waiters();
     }
 
     // ========================================================================
@@ -3442,6 +4290,8 @@ impl TransferManager {
         // 恢复任务 ID（保持原有 ID）
         task.id = task_id.clone();
         task.created_at = recovery_info.created_at;
+        task.recursive = recovery_info.recursive;
+        task.share_subfolder = recovery_info.share_subfolder;
 
         // 恢复文件列表
         if let Some(ref json) = recovery_info.file_list_json {
@@ -3468,6 +4318,7 @@ impl TransferManager {
                 // 恢复分享直下相关字段
                 task.is_share_direct_download = recovery_info.is_share_direct_download;
                 task.temp_dir = recovery_info.temp_dir.clone();
+                task.download_cleanup = recovery_info.download_cleanup;
                 info!(
                     "恢复转存任务(下载中): id={}, 关联下载任务数={}, is_share_direct_download={}",
                     task_id,
@@ -3810,6 +4661,85 @@ impl TransferManager {
     }
 }
 
+/// 递归展开分享目录时的分页大小
+const SHARE_LIST_PAGE_SIZE: u32 = 100;
+/// 递归展开单个分享目录的最大页数
+const SHARE_LIST_MAX_PAGES: u32 = 100;
+/// 递归转存展开的最大文件数
+const MAX_RECURSIVE_TRANSFER_FILES: usize = 50_000;
+/// 单次转存请求的最大文件数（仍超过百度上限时继续对半拆分）
+const TRANSFER_CHUNK_SIZE: usize = 500;
+
+/// 将同一目录组的文件按单次转存上限分块
+fn chunk_transfer_files(files: Vec<SharedFileInfo>, chunk_size: usize) -> VecDeque<Vec<SharedFileInfo>> {
+    files
+        .chunks(chunk_size.max(1))
+        .map(<[SharedFileInfo]>::to_vec)
+        .collect()
+}
+
+/// 转存是否因文件数超过上限失败（errno=12，见 NetdiskClient::transfer_share_files）
+fn is_transfer_limit_error(result: &TransferResult) -> bool {
+    !result.success && result.errno == Some(12)
+}
+
+/// 转存是否因 bdstoken 失效失败（errno=-6 身份验证失败）
+///
+/// 同名文件、空间不足、文件数超限等错误不属于此类，重试无意义
+fn is_stale_token_error(result: &TransferResult) -> bool {
+    !result.success && result.errno == Some(-6)
+}
+
+/// 分享直下任务下载结束后是否清理网盘临时目录
+///
+/// 「转存→下载→删除网盘副本」任务只在下载成功且每个文件都实际通过 MD5 校验时清理
+/// （下载失败、未校验或网盘未返回 MD5 时保留网盘副本），普通分享直下任务按 share_direct_download 配置决定
+fn should_cleanup_temp_dir(
+    download_cleanup: bool,
+    download_succeeded: bool,
+    md5_verified: bool,
+    cfg: &AppConfig,
+) -> bool {
+    if download_cleanup {
+        download_succeeded && md5_verified
+    } else if download_succeeded {
+        cfg.share_direct_download.auto_cleanup
+    } else {
+        cfg.share_direct_download.cleanup_on_failure
+    }
+}
+
+/// 检查分享链接时最多列出的根目录页数
+const INSPECT_LIST_MAX_PAGES: u32 = 10;
+
+/// 从文件列表生成分享标题
+///
+/// 只有一个文件/文件夹时使用其名称，多个文件时使用 "首个文件名 等N个文件"，列表为空时返回 None
+fn share_title(files: &[SharedFileInfo]) -> Option<String> {
+    match files {
+        [] => None,
+        [file] => Some(file.name.clone()),
+        [first, ..] => Some(format!("{} 等{}个文件", first.name, files.len())),
+    }
+}
+
+/// 计算按分享创建的本地子文件夹名（已清理非法字符）
+///
+/// 按标题命名但标题为空或清理后为空时回退到 short_key；都不可用时返回 None（不创建子文件夹）
+fn share_subfolder_name(
+    naming: ShareSubfolderNaming,
+    title: Option<&str>,
+    short_key: Option<&str>,
+) -> Option<String> {
+    let from_short_key = || short_key.and_then(crate::common::path_utils::sanitize_folder_name);
+    match naming {
+        ShareSubfolderNaming::Title => title
+            .and_then(crate::common::path_utils::sanitize_folder_name)
+            .or_else(from_short_key),
+        ShareSubfolderNaming::ShortKey => from_short_key(),
+    }
+}
+
 /// 根据 selected_fs_ids 构建实际要转存的 fs_id 列表
 ///
 /// - selected_fs_ids 为 None 或空数组 → 返回 file_list 中所有文件的 fs_id（向后兼容）
@@ -3911,6 +4841,7 @@ fn merge_batch_results(
         from_paths: Vec::new(),
         transferred_fs_ids: Vec::new(),
         error: None,
+        errno: None,
     };
 
     let mut batch_groups_info = Vec::new();
@@ -4053,6 +4984,8 @@ mod tests {
             path: path.to_string(),
             size: 100,
             name,
+            dlink: None,
+            md5: None,
         }
     }
 
@@ -4228,6 +5161,103 @@ mod tests {
         assert_eq!(local_dir, download_dir.join("抖音"));
     }
 
+    #[test]
+    fn test_chunk_transfer_files_and_limit_error() {
+        let files: Vec<SharedFileInfo> = (0..5)
+            .map(|i| make_file(&format!("/sharelink1-2/dir/{}.txt", i), i))
+            .collect();
+        let chunks = chunk_transfer_files(files, 2);
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(chunks[2][0].fs_id, 4);
+
+        let mut result = TransferResult {
+            success: false,
+            transferred_paths: vec![],
+            from_paths: vec![],
+            error: Some("转存文件数 600 超过上限 500".to_string()),
+            transferred_fs_ids: vec![],
+            errno: Some(12),
+        };
+        assert!(is_transfer_limit_error(&result));
+        result.error = Some("同名文件已存在: a.txt".to_string());
+        result.errno = Some(-30);
+        assert!(!is_transfer_limit_error(&result));
+    }
+
+    #[test]
+    fn test_should_cleanup_temp_dir() {
+        let mut cfg = AppConfig::default();
+        cfg.share_direct_download.auto_cleanup = true;
+        cfg.share_direct_download.cleanup_on_failure = true;
+
+        // 下载后清理任务：只在成功且实际校验通过时删除网盘副本
+        assert!(should_cleanup_temp_dir(true, true, true, &cfg));
+        assert!(!should_cleanup_temp_dir(true, true, false, &cfg));
+        assert!(!should_cleanup_temp_dir(true, false, false, &cfg));
+
+        // 普通分享直下任务按配置清理
+        assert!(should_cleanup_temp_dir(false, true, false, &cfg));
+        assert!(should_cleanup_temp_dir(false, false, false, &cfg));
+        cfg.share_direct_download.cleanup_on_failure = false;
+        assert!(!should_cleanup_temp_dir(false, false, false, &cfg));
+    }
+
+    #[test]
+    fn test_is_stale_token_error() {
+        let mut result = TransferResult {
+            success: false,
+            transferred_paths: vec![],
+            from_paths: vec![],
+            error: Some(r#"转存失败: {"errno":-6,"request_id":123}"#.to_string()),
+            transferred_fs_ids: vec![],
+            errno: Some(-6),
+        };
+        assert!(is_stale_token_error(&result));
+
+        // 其他错误码（-62 需要验证码、4 同名文件、12 超限）不重试
+        for errno in [-62, 4, 12] {
+            result.errno = Some(errno);
+            assert!(!is_stale_token_error(&result));
+        }
+
+        // 只按错误码判断，错误信息中恰好包含 errno:-6 不视为 bdstoken 失效
+        result.errno = Some(12);
+        result.error = Some(r#"转存失败: {"errno":12,"info":[{"errno":-6}]}"#.to_string());
+        assert!(!is_stale_token_error(&result));
+
+        result.success = true;
+        result.errno = Some(-6);
+        assert!(!is_stale_token_error(&result));
+    }
+
+    #[test]
+    fn test_share_title() {
+        assert!(share_title(&[]).is_none());
+        assert_eq!(share_title(&[make_file("/电影", 1)]).as_deref(), Some("电影"));
+        assert_eq!(
+            share_title(&[make_file("/a.mkv", 1), make_file("/b.mkv", 2), make_file("/c.mkv", 3)]).as_deref(),
+            Some("a.mkv 等3个文件")
+        );
+    }
+
+    #[test]
+    fn test_share_subfolder_name() {
+        assert_eq!(
+            share_subfolder_name(ShareSubfolderNaming::Title, Some("电影: 合集 等3个文件"), Some("1abc")).as_deref(),
+            Some("电影_ 合集 等3个文件")
+        );
+        // 标题不可用时回退到 short_key
+        assert_eq!(
+            share_subfolder_name(ShareSubfolderNaming::Title, Some("??"), Some("1abc")).as_deref(),
+            Some("1abc")
+        );
+        assert_eq!(
+            share_subfolder_name(ShareSubfolderNaming::ShortKey, Some("电影"), Some("1abc")).as_deref(),
+            Some("1abc")
+        );
+        assert!(share_subfolder_name(ShareSubfolderNaming::Title, None, None).is_none());
+    }
+
     #[test]
     fn test_local_dir_from_transferred_path_root_file() {
         let download_dir = PathBuf::from("D:/Downloads");
@@ -4268,6 +5298,7 @@ mod tests {
             from_paths: vec!["/share/抖音/photo.jpg".to_string()],
             transferred_fs_ids: vec![100],
             error: None,
+            errno: None,
         };
         let r2 = TransferResult {
             success: true,
@@ -4275,6 +5306,7 @@ mod tests {
             from_paths: vec!["/share/微信/photo.jpg".to_string()],
             transferred_fs_ids: vec![200],
             error: None,
+            errno: None,
         };
         let results = vec![
             (1usize, "抖音".to_string(), vec![make_file("/share/抖音/photo.jpg", 1)], Ok(r1)),
@@ -4297,6 +5329,7 @@ mod tests {
             from_paths: vec!["/share/抖音/a.jpg".to_string()],
             transferred_fs_ids: vec![100],
             error: None,
+            errno: None,
         };
         let results = vec![
             (1usize, "抖音".to_string(), vec![make_file("/share/抖音/a.jpg", 1)], Ok(r1)),
@@ -4329,6 +5362,7 @@ mod tests {
             from_paths: vec![],
             transferred_fs_ids: vec![1],
             error: None,
+            errno: None,
         };
         let results = vec![
             (1usize, "".to_string(), vec![], Ok(r1)),
@@ -4339,4 +5373,68 @@ mod tests {
         // empty group_id means root level, remote_dir should be temp_dir itself
         assert_eq!(groups_info[0].remote_dir, "/tmp");
     }
+
+    #[tokio::test]
+    async fn test_transfer_slots_queue_in_order() {
+        let slots = Arc::new(TransferSlots::new());
+        let config = RwLock::new(TransferConfig {
+            max_concurrent_transfers: 1,
+            ..TransferConfig::default()
+        });
+        let token = CancellationToken::new();
+
+        assert!(!slots.enqueue("a".to_string(), 1));
+        assert!(slots.enqueue("b".to_string(), 1));
+        assert!(slots.enqueue("c".to_string(), 1));
+        assert_eq!(slots.queue_position("c"), Some(3));
+
+        let guard_a = slots.acquire("a", &config, &token).await.unwrap();
+        assert_eq!(slots.queue_position("a"), None);
+        assert_eq!(slots.queue_position("b"), Some(1));
+
+        // 槽位已满，b 需要等待；c 不在队首，槽位释放后也要排在 b 后面
+        assert!(slots.try_acquire("b", 1).is_none());
+        assert!(slots.try_acquire("c", 1).is_none());
+
+        let waiter = {
+            let slots = slots.clone();
+            let token = token.clone();
+            tokio::spawn(async move {
+                let config = RwLock::new(TransferConfig {
+                    max_concurrent_transfers: 1,
+                    ..TransferConfig::default()
+                });
+                slots.acquire("b", &config, &token).await.is_some()
+            })
+        };
+        drop(guard_a);
+        assert!(waiter.await.unwrap());
+        assert_eq!(slots.queue_position("c"), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_slots_cancel_while_queued() {
+        let slots = Arc::new(TransferSlots::new());
+        let config = RwLock::new(TransferConfig {
+            max_concurrent_transfers: 1,
+            ..TransferConfig::default()
+        });
+
+        slots.enqueue("a".to_string(), 1);
+        let _guard_a = slots
+            .acquire("a", &config, &CancellationToken::new())
+            .await
+            .unwrap();
+
+        slots.enqueue("b".to_string(), 1);
+        slots.enqueue("c".to_string(), 1);
+        let token_b = CancellationToken::new();
+        token_b.cancel();
+        assert!(slots.acquire("b", &config, &token_b).await.is_none());
+
+        // b 取消后 c 前移到队首
+        assert_eq!(slots.queue_position("b"), None);
+        assert_eq!(slots.queue_position("c"), Some(1));
+        assert!(slots.try_acquire("c", 2).is_some());
+    }
 }
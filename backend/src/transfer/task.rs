@@ -1,6 +1,6 @@
 // 转存任务定义
 
-use super::types::{SharePageInfo, SharedFileInfo};
+use super::types::{SharePageInfo, ShareSubfolderNaming, SharedFileInfo};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -115,6 +115,10 @@ pub struct TransferTask {
     /// 临时目录路径（网盘路径，分享直下专用，用于清理）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub temp_dir: Option<String>,
+    /// 「转存→下载→删除网盘副本」任务：以分享直下方式执行，
+    /// 只在下载成功且 MD5 校验开启时删除网盘临时目录，失败时保留
+    #[serde(default)]
+    pub download_cleanup: bool,
     /// 用户选择的文件 fs_id 列表（可选，用于选择性转存）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub selected_fs_ids: Option<Vec<u64>>,
@@ -122,6 +126,15 @@ pub struct TransferTask {
     /// 解决子目录选择场景下后端无法从根目录文件列表中匹配到子文件信息的问题
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub selected_files: Option<Vec<SharedFileInfo>>,
+    /// 自动下载时在本地下载目录下按分享创建子文件夹（None 表示直接下载到下载目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_subfolder: Option<ShareSubfolderNaming>,
+    /// 是否递归展开选中的文件夹后按文件转存（保留目录结构，用于超过单次转存文件数上限的大文件夹）
+    #[serde(default)]
+    pub recursive: bool,
+    /// 在转存等待队列中的位置（从 1 开始，仅排队中的任务有值，查询时填充）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<usize>,
 }
 
 impl TransferTask {
@@ -158,8 +171,12 @@ impl TransferTask {
             file_name: None,
             is_share_direct_download: false,
             temp_dir: None,
+            download_cleanup: false,
             selected_fs_ids: None,
             selected_files: None,
+            share_subfolder: None,
+            recursive: false,
+            queue_position: None,
         }
     }
 
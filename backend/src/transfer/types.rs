@@ -25,6 +25,34 @@ pub const ERROR_TASK_NOT_FOUND: i32 = 2008;
 /// 清理临时目录失败（分享直下专用）
 pub const ERROR_CLEANUP_FAILED: i32 = 2009;
 
+/// 自动下载时按分享创建本地子文件夹的命名方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareSubfolderNaming {
+    /// 使用分享标题（转存文件名，如 "电影 等3个文件"），为空时回退到 short_key
+    Title,
+    /// 使用分享短链 ID（short_key）
+    ShortKey,
+}
+
+/// 分享提取码验证的图片验证码（share/verify 返回 -19/-62 时需要）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareCaptcha {
+    /// 验证码图片地址
+    pub img_url: String,
+    /// 验证码令牌（提交验证码时原样回传）
+    pub vcode_str: String,
+}
+
+/// 用户输入的分享验证码
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ShareCaptchaAnswer {
+    /// 用户识别出的验证码
+    pub vcode: String,
+    /// 对应 ShareCaptcha::vcode_str
+    pub vcode_str: String,
+}
+
 /// 分享链接解析结果
 #[derive(Debug, Clone)]
 pub struct ShareLink {
@@ -36,6 +64,37 @@ pub struct ShareLink {
     pub password: Option<String>,
 }
 
+/// 分享链接检查结果（不验证提取码、不转存）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ShareInspectResult {
+    /// 链接是否有效（需要提取码的有效链接同样为 true）
+    pub valid: bool,
+    /// 是否需要提取码
+    pub needs_password: bool,
+    /// 根目录文件数（需要提取码或列出失败时为 null）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_count: Option<usize>,
+    /// 分享标题（单个文件为文件名，多个文件为 "首个文件名 等N个文件"）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 无效原因（如 "分享已失效"）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ShareInspectResult {
+    /// 无效链接
+    pub fn invalid(reason: impl Into<String>) -> Self {
+        Self {
+            valid: false,
+            needs_password: false,
+            file_count: None,
+            title: None,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
 /// 分享页面信息（从页面 JS 提取）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharePageInfo {
@@ -73,6 +132,27 @@ pub struct SharedFileInfo {
     pub size: u64,
     /// 文件名
     pub name: String,
+    /// 分享直链（仅 share/list 携带 sekey 时返回，用于免转存直接下载）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlink: Option<String>,
+    /// 文件 MD5（目录无此字段，下载完成后用于校验）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+}
+
+/// 已验证的分享来源（免转存直接下载时用于列目录获取直链）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareDirectSource {
+    /// surl 或短链 ID（如 "1abcDEFg"）
+    pub short_key: String,
+    /// 分享 ID
+    pub shareid: String,
+    /// 分享者 UK
+    pub uk: String,
+    /// CSRF 令牌
+    pub bdstoken: String,
+    /// 提取码验证后获得的 randsk（无提取码的分享为空）
+    pub sekey: Option<String>,
 }
 
 /// 转存结果
@@ -88,6 +168,9 @@ pub struct TransferResult {
     pub error: Option<String>,
     /// 转存后的文件 fs_id 列表
     pub transferred_fs_ids: Vec<u64>,
+    /// 转存接口返回的错误码（失败时设置，用于判断是否值得重试）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errno: Option<i64>,
 }
 
 /// 分批转存组信息（用于本地下载目录规划）
@@ -127,6 +210,10 @@ pub enum TransferError {
     NeedPassword,
     /// 提取码错误
     InvalidPassword,
+    /// 需要输入验证码（多次验证提取码后触发）
+    CaptchaRequired(ShareCaptcha),
+    /// 验证码错误（附带新的验证码）
+    CaptchaIncorrect(ShareCaptcha),
     /// 分享已失效
     ShareExpired,
     /// 分享不存在
@@ -148,6 +235,8 @@ impl std::fmt::Display for TransferError {
         match self {
             TransferError::NeedPassword => write!(f, "需要提取码"),
             TransferError::InvalidPassword => write!(f, "提取码错误"),
+            TransferError::CaptchaRequired(_) => write!(f, "需要输入验证码"),
+            TransferError::CaptchaIncorrect(_) => write!(f, "验证码错误，请重新输入"),
             TransferError::ShareExpired => write!(f, "分享已失效"),
             TransferError::ShareNotFound => write!(f, "分享不存在"),
             TransferError::FileExists(name) => write!(f, "同名文件已存在: {}", name),
@@ -163,6 +252,21 @@ impl std::fmt::Display for TransferError {
 
 impl std::error::Error for TransferError {}
 
+impl TransferError {
+    /// 沿错误链查找转存错误
+    pub fn find(error: &anyhow::Error) -> Option<Self> {
+        error.chain().find_map(|cause| cause.downcast_ref::<Self>().cloned())
+    }
+
+    /// 需要用户输入的验证码（仅验证码相关错误）
+    pub fn captcha(&self) -> Option<&ShareCaptcha> {
+        match self {
+            Self::CaptchaRequired(captcha) | Self::CaptchaIncorrect(captcha) => Some(captcha),
+            _ => None,
+        }
+    }
+}
+
 // ============================================
 // 清理结果类型定义
 // ============================================
@@ -198,6 +302,24 @@ pub struct CleanupResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transfer_error_find_captcha() {
+        let captcha = ShareCaptcha {
+            img_url: "https://pan.baidu.com/genimage?abc".to_string(),
+            vcode_str: "abc".to_string(),
+        };
+        let err = anyhow::Error::new(TransferError::CaptchaRequired(captcha.clone()))
+            .context("验证提取码失败");
+        let found = TransferError::find(&err).unwrap();
+        assert_eq!(found.to_string(), "需要输入验证码");
+        assert_eq!(found.captcha(), Some(&captcha));
+
+        let wrong = anyhow::Error::new(TransferError::InvalidPassword);
+        assert_eq!(wrong.to_string(), "提取码错误");
+        assert_eq!(TransferError::find(&wrong).unwrap().captcha(), None);
+        assert_eq!(TransferError::find(&anyhow::anyhow!("网络错误")), None);
+    }
+
     #[test]
     fn test_cleanup_status_serialize() {
         let status = CleanupStatus::Success;
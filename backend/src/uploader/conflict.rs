@@ -5,10 +5,14 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "snake_case")]
 pub enum UploadConflictStrategy {
     /// 智能去重：比较 block_list，相同则秒传，不同则重命名（rtype=2）
+    #[serde(alias = "dedup")]
     SmartDedup,
     /// 自动重命名：路径冲突时自动生成唯一名称（rtype=1）
+    #[serde(alias = "rename")]
     AutoRename,
     /// 覆盖：直接覆盖已存在的文件（rtype=3，危险操作）
+    ///
+    /// 网盘同名文件会被直接替换，仅在确认需要替换时使用
     Overwrite,
 }
 
@@ -24,9 +28,10 @@ impl Default for UploadConflictStrategy {
 pub enum DownloadConflictStrategy {
     /// 覆盖：目标文件存在时覆盖
     Overwrite,
-    /// 跳过：目标文件存在时跳过
+    /// 跳过：目标文件存在且与网盘文件一致（大小/MD5）时跳过，不一致时重新下载
     Skip,
     /// 自动重命名：目标文件存在时生成唯一名称
+    #[serde(alias = "rename")]
     AutoRename,
 }
 
@@ -70,6 +75,18 @@ pub fn conflict_strategy_to_rtype(strategy: UploadConflictStrategy) -> &'static
     }
 }
 
+/// 百度网盘 precreate/create 接口接受的 rtype 取值
+pub const VALID_RTYPES: [&str; 3] = ["1", "2", "3"];
+
+/// 校验 rtype 参数，百度网盘对未知取值的处理未定义，提前拒绝
+pub fn validate_rtype(rtype: &str) -> anyhow::Result<()> {
+    if VALID_RTYPES.contains(&rtype) {
+        Ok(())
+    } else {
+        anyhow::bail!("无效的 rtype 参数: {:?}（仅支持 1=重命名, 2=智能去重, 3=覆盖）", rtype)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +210,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_rtype() {
+        for strategy in [
+            UploadConflictStrategy::SmartDedup,
+            UploadConflictStrategy::AutoRename,
+            UploadConflictStrategy::Overwrite,
+        ] {
+            assert!(validate_rtype(conflict_strategy_to_rtype(strategy)).is_ok());
+        }
+        assert!(validate_rtype("0").is_err());
+        assert!(validate_rtype("").is_err());
+        assert!(validate_rtype(" 1").is_err());
+    }
+
+    #[test]
+    fn test_upload_strategy_aliases() {
+        let parse = |s: &str| serde_json::from_str::<UploadConflictStrategy>(s).unwrap();
+        assert_eq!(parse(r#""rename""#), UploadConflictStrategy::AutoRename);
+        assert_eq!(parse(r#""dedup""#), UploadConflictStrategy::SmartDedup);
+        assert_eq!(parse(r#""overwrite""#), UploadConflictStrategy::Overwrite);
+    }
+
     #[test]
     fn test_conflict_strategy_to_rtype_all_variants() {
         // 测试所有策略映射到正确的 rtype 值
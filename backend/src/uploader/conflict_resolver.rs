@@ -3,7 +3,7 @@ use crate::uploader::{ConflictResolution, DownloadConflictStrategy};
 use anyhow::Result;
 use std::path::Path;
 use std::sync::{Arc, RwLock as StdRwLock};
-use tracing::info;
+use tracing::{info, warn};
 
 /// 冲突解决器（仅用于下载）
 pub struct ConflictResolver {
@@ -52,6 +52,63 @@ impl ConflictResolver {
             }
         }
     }
+
+    /// 解决下载冲突（跳过策略下校验已有文件）
+    ///
+    /// 跳过策略只在已有文件与网盘文件一致时跳过，否则视为中断残留的文件按覆盖处理：
+    /// - 文件大小必须一致
+    /// - 提供了有效的网盘 MD5（32 位十六进制）时同时校验 MD5
+    ///
+    /// 覆盖、自动重命名策略与 `resolve_download_conflict` 相同
+    pub async fn resolve_download_conflict_verified(
+        local_path: &Path,
+        strategy: DownloadConflictStrategy,
+        expected_size: u64,
+        expected_md5: Option<&str>,
+    ) -> Result<ConflictResolution> {
+        if strategy != DownloadConflictStrategy::Skip || !local_path.exists() {
+            return Self::resolve_download_conflict(local_path, strategy);
+        }
+
+        let path = local_path.to_path_buf();
+        let md5 = expected_md5.map(str::to_string);
+        let matches =
+            tokio::task::spawn_blocking(move || existing_file_matches(&path, expected_size, md5.as_deref()))
+                .await?;
+
+        if matches {
+            info!("跳过策略：已有文件与网盘文件一致，跳过下载 - local: {:?}", local_path);
+            Ok(ConflictResolution::Skip)
+        } else {
+            info!("跳过策略：已有文件与网盘文件不一致，重新下载 - local: {:?}", local_path);
+            Ok(ConflictResolution::Proceed)
+        }
+    }
+}
+
+/// 已有本地文件是否与网盘文件一致（大小一致，且 MD5 有效时 MD5 一致）
+fn existing_file_matches(path: &Path, expected_size: u64, expected_md5: Option<&str>) -> bool {
+    let size_matches = std::fs::metadata(path)
+        .map(|m| m.is_file() && m.len() == expected_size)
+        .unwrap_or(false);
+    if !size_matches {
+        return false;
+    }
+
+    let Some(expected) = expected_md5
+        .map(str::to_ascii_lowercase)
+        .filter(|md5| md5.len() == 32 && md5.chars().all(|c| c.is_ascii_hexdigit()))
+    else {
+        return true;
+    };
+
+    match crate::downloader::ChunkScheduler::calculate_file_md5(path) {
+        Ok(actual) => actual == expected,
+        Err(e) => {
+            warn!("计算已有文件 MD5 失败: {:?}, {}", path, e);
+            false
+        }
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +220,55 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_resolve_download_conflict_verified_skip() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        std::fs::write(&file_path, "content").unwrap();
+        let skip = DownloadConflictStrategy::Skip;
+
+        // 大小一致、未提供 MD5：跳过
+        let result = ConflictResolver::resolve_download_conflict_verified(&file_path, skip, 7, None)
+            .await
+            .unwrap();
+        assert_eq!(result, ConflictResolution::Skip);
+
+        // 大小不一致（中断残留）：重新下载
+        let result = ConflictResolver::resolve_download_conflict_verified(&file_path, skip, 1024, None)
+            .await
+            .unwrap();
+        assert_eq!(result, ConflictResolution::Proceed);
+
+        // MD5 一致（大小写不敏感）：跳过
+        let md5 = format!("{:x}", md5::compute("content"));
+        let result = ConflictResolver::resolve_download_conflict_verified(
+            &file_path,
+            skip,
+            7,
+            Some(&md5.to_ascii_uppercase()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, ConflictResolution::Skip);
+
+        // MD5 不一致：重新下载
+        let result = ConflictResolver::resolve_download_conflict_verified(
+            &file_path,
+            skip,
+            7,
+            Some("0123456789abcdef0123456789abcdef"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, ConflictResolution::Proceed);
+
+        // 无法识别的 MD5 只校验大小
+        let result = ConflictResolver::resolve_download_conflict_verified(&file_path, skip, 7, Some("abc"))
+            .await
+            .unwrap();
+        assert_eq!(result, ConflictResolution::Skip);
+    }
+
     // 注意：上传冲突解决的测试需要 NetdiskClient，这些测试应该是集成测试
     // 或者需要一个 mock 框架。由于时间限制，我们先实现下载相关的单元测试
     // 上传相关的测试可以在集成测试中完成
@@ -690,6 +690,7 @@ async fn upload_single_chunk(
                     t.total_chunks = total_chunks;
                     if speed > 0 {
                         t.speed = speed;
+                        t.update_eta();
                     }
                 }
 
@@ -18,15 +18,15 @@ use crate::autobackup::record::BackupRecordManager;
 use crate::config::{UploadConfig, VipType};
 use crate::netdisk::NetdiskClient;
 use crate::persistence::{
-    PersistenceManager, TaskMetadata, UploadRecoveryInfo,
+    file_mtime_secs, PersistenceManager, TaskHistoryQuery, TaskMetadata, UploadRecoveryInfo,
 };
 use crate::server::events::{ProgressThrottler, TaskEvent, UploadEvent};
 use crate::server::websocket::WebSocketManager;
 use crate::task_slot_pool::{TaskPriority, TaskSlotPool};
 use crate::uploader::{
-    calculate_upload_task_max_chunks, FolderScanner, PcsServerHealthManager, ScanOptions,
-    UploadChunkManager, UploadChunkScheduler, UploadEngine, UploadTask, UploadTaskScheduleInfo,
-    UploadTaskStatus,
+    calculate_upload_task_max_chunks, FolderScanner, PcsServerHealthManager, RapidUploadChecker,
+    RapidUploadProbeResult, ScanOptions, UploadChunkManager, UploadChunkScheduler, UploadEngine,
+    UploadTask, UploadTaskScheduleInfo, UploadTaskStatus,
 };
 use anyhow::{Context, Result};
 use dashmap::DashMap;
@@ -76,6 +76,57 @@ pub struct RestoredChunkInfo {
     pub chunk_md5s: Vec<Option<String>>,
 }
 
+/// 秒传预检使用的网盘探测目录（命中秒传后删除其中的探测文件，删除的文件会进入回收站）
+const RAPID_CHECK_PROBE_DIR: &str = "/.rapid_upload_check";
+
+/// 文件夹上传组的任务总量（内部统计）
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct UploadGroupTotals {
+    total_files: usize,
+    total_size: u64,
+    /// 尚未完成的任务数
+    unfinished_files: usize,
+    /// 扫描已结束，不会再有新任务加入
+    scan_finished: bool,
+}
+
+impl UploadGroupTotals {
+    /// 扫描已结束且组内任务都已完成或删除
+    fn is_drained(&self) -> bool {
+        self.scan_finished && self.unfinished_files == 0
+    }
+}
+
+/// 文件夹上传组统计表：group_id → 组内任务总量（管理器与调度器共享）
+pub(crate) type UploadGroupMap = DashMap<String, UploadGroupTotals>;
+
+/// 组内一个任务已完成：扫描结束后最后一个任务完成时移除该组统计
+pub(crate) fn finish_group_task(groups: &UploadGroupMap, group_id: &str) {
+    if let Some(mut totals) = groups.get_mut(group_id) {
+        totals.unfinished_files = totals.unfinished_files.saturating_sub(1);
+    }
+    groups.remove_if(group_id, |_, totals| totals.is_drained());
+}
+
+/// 文件夹上传组的聚合进度
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct UploadGroupProgress {
+    /// 组内已创建的上传任务数
+    pub total_files: usize,
+    /// 已完成（含秒传）的文件数
+    pub completed_files: usize,
+    /// 失败的文件数
+    pub failed_files: usize,
+    /// 等待中或上传中的文件数
+    pub active_files: usize,
+    /// 组内文件总大小
+    pub total_size: u64,
+    /// 已上传大小
+    pub uploaded_size: u64,
+    /// 当前总速度 (bytes/s)
+    pub speed: u64,
+}
+
 /// 上传管理器
 pub struct UploadManager {
     /// 网盘客户端（共享引用，代理热更新时自动生效）
@@ -118,8 +169,14 @@ pub struct UploadManager {
     dedup_index: DashMap<(PathBuf, String), String>,
     /// 反向索引：task_id → (local_path, original_remote_path)
     dedup_reverse: DashMap<String, (PathBuf, String)>,
+    /// 🔥 文件夹上传组统计：group_id → 已创建任务的文件数和总大小
+    /// （已完成的任务会立即从 tasks 中移除，需要单独记录总量才能计算聚合进度；
+    /// 扫描结束且组内任务全部完成或删除后移除）
+    upload_groups: Arc<UploadGroupMap>,
     /// 🔥 活跃任务计数器（Pending/Uploading/Encrypting/CheckingRapid），O(1) 查询
     active_count: Arc<AtomicUsize>,
+    /// 🔥 管理器已停用（切换账号后），不再启动等待队列中的任务
+    shutting_down: Arc<AtomicBool>,
 }
 
 impl UploadManager {
@@ -238,7 +295,9 @@ impl UploadManager {
             backup_record_manager: Arc::new(RwLock::new(None)),
             dedup_index: DashMap::new(),
             dedup_reverse: DashMap::new(),
+            upload_groups: Arc::new(DashMap::new()),
             active_count: Arc::new(AtomicUsize::new(0)),
+            shutting_down: Arc::new(AtomicBool::new(false)),
         };
 
         // 🔥 设置槽位超时释放处理器
@@ -1028,6 +1087,40 @@ impl UploadManager {
         self.scheduler.clone()
     }
 
+    /// 🔥 关闭前排空上传：停止启动等待任务，取消进行中的上传并等待分片线程退出
+    ///
+    /// 任务状态保持不变，已完成的分片已写入 WAL，重启后从 WAL 恢复续传
+    /// 返回 (被中断的任务数, 超时后仍未退出的分片线程数)
+    pub async fn prepare_shutdown(&self, timeout: std::time::Duration) -> (usize, usize) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let interrupted = self
+            .tasks
+            .iter()
+            .filter(|entry| !entry.cancel_token.is_cancelled())
+            .inspect(|entry| entry.cancel_token.cancel())
+            .count();
+
+        let Some(scheduler) = &self.scheduler else {
+            return (interrupted, 0);
+        };
+        let deadline = tokio::time::Instant::now() + timeout;
+        while scheduler.active_threads() > 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+
+        (interrupted, scheduler.active_threads())
+    }
+
+    /// 🔥 停用管理器（切换账号时调用）：排空进行中的上传后停止调度器
+    pub async fn shutdown(&self, timeout: std::time::Duration) -> (usize, usize) {
+        let drained = self.prepare_shutdown(timeout).await;
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.stop();
+        }
+        drained
+    }
+
     /// 🔥 设置持久化管理器
     ///
     /// 由 AppState 在初始化时调用，注入持久化管理器
@@ -1666,6 +1759,7 @@ impl UploadManager {
         let persistence_manager = self.persistence_manager.lock().await.clone();
         let ws_manager = self.ws_manager.read().await.clone();
         let tasks = self.tasks.clone();
+        let upload_groups = self.upload_groups.clone();
         let backup_notification_tx = self.backup_notification_tx.read().await.clone();
         let (is_backup, encrypt_enabled, original_size) = {
             let t = task.lock().await;
@@ -1848,7 +1942,7 @@ impl UploadManager {
             // 2. 检查是否有恢复的 upload_id
             let upload_id = if let Some(restored_id) = restored_upload_id {
                 info!(
-                    "使用恢复的 upload_id: {} (如果合并失败，说明已过期，需要重新上传)",
+                    "使用恢复的 upload_id: {} (合并时若提示分片缺失，说明已过期，会重置进度并在重试时重新预创建)",
                     restored_id
                 );
                 restored_id
@@ -1909,6 +2003,9 @@ impl UploadManager {
                     task_slot_pool.release_fixed_slot(&task_id_string).await;
                     let mut t = task.lock().await;
                     t.mark_rapid_upload_success();
+                    if let Some(group_id) = &t.group_id {
+                        finish_group_task(&upload_groups, group_id);
+                    }
                     return;
                 }
 
@@ -2035,6 +2132,8 @@ impl UploadManager {
                 snapshot_manager,
                 // 🔥 Manager 任务列表引用（用于任务完成时立即清理）
                 manager_tasks: Some(tasks.clone()),
+                // 🔥 文件夹上传组统计（组内最后一个任务完成时移除）
+                upload_groups: Some(upload_groups.clone()),
             };
 
             if let Err(e) = scheduler.register_task(schedule_info).await {
@@ -2445,6 +2544,7 @@ impl UploadManager {
         }
 
         // 移除任务
+        self.release_task_group(task_id).await;
         self.tasks.remove(task_id);
 
         // 🔥 活跃计数 -1
@@ -2549,6 +2649,7 @@ impl UploadManager {
         }
 
         // 移除任务
+        self.release_task_group(task_id).await;
         self.tasks.remove(task_id);
 
         if was_active {
@@ -2905,6 +3006,7 @@ impl UploadManager {
             uploaded_size: file_size, // 已完成的任务
             status: UploadTaskStatus::Completed,
             speed: 0,
+            eta_secs: None,
             created_at: metadata.created_at.timestamp(),
             started_at: Some(metadata.created_at.timestamp()),
             completed_at: metadata.completed_at.map(|t| t.timestamp()),
@@ -2940,9 +3042,42 @@ impl UploadManager {
             encryption_key_version: metadata.encryption_key_version.unwrap_or(1),
             // 冲突策略（历史任务使用默认值）
             conflict_strategy: crate::uploader::UploadConflictStrategy::default(),
+            // 剩余时间估算字段（历史任务已完成）
+            eta_estimator: crate::common::EtaEstimator::default(),
         })
     }
 
+    /// 分页查询上传历史（直接查询历史数据库，不合并内存任务）
+    ///
+    /// # Returns
+    /// * `(Vec<UploadTask>, usize)` - (当前页任务, 符合条件的总数)
+    pub async fn query_history(&self, mut query: TaskHistoryQuery) -> (Vec<UploadTask>, usize) {
+        let Some(pm_arc) = self
+            .persistence_manager
+            .lock()
+            .await
+            .as_ref()
+            .map(|pm| pm.clone())
+        else {
+            return (Vec::new(), 0);
+        };
+
+        query.task_type = Some("upload".to_string());
+        query.exclude_backup = true;
+
+        let result = pm_arc.lock().await.query_history_tasks(&query);
+        match result {
+            Some((history_tasks, total)) => (
+                history_tasks
+                    .iter()
+                    .filter_map(Self::convert_history_to_task)
+                    .collect(),
+                total,
+            ),
+            None => (Vec::new(), 0),
+        }
+    }
+
     /// 获取正在传输的任务数（Uploading/CheckingRapid）
     pub fn transferring_task_count(&self) -> usize {
         let mut count = 0;
@@ -2964,6 +3099,33 @@ impl UploadManager {
         self.active_count.load(Ordering::SeqCst)
     }
 
+    /// 获取当前线程池状态 (活跃分片线程数, 最大线程数)
+    ///
+    /// 未启用调度器模式时返回 (0, 0)
+    pub fn get_thread_pool_stats(&self) -> (usize, usize) {
+        match &self.scheduler {
+            Some(scheduler) => (scheduler.active_threads(), scheduler.max_threads()),
+            None => (0, 0),
+        }
+    }
+
+    /// 获取等待队列长度
+    pub async fn waiting_count(&self) -> usize {
+        self.waiting_queue.read().await.len()
+    }
+
+    /// 获取所有上传中任务的速度总和 (bytes/s)
+    pub async fn total_speed(&self) -> u64 {
+        let mut speed = 0;
+        for entry in self.tasks.iter() {
+            let task = entry.task.lock().await;
+            if task.status == UploadTaskStatus::Uploading {
+                speed += task.speed;
+            }
+        }
+        speed
+    }
+
     /// 活跃计数 +1
     fn inc_active(&self) {
         self.active_count.fetch_add(1, Ordering::SeqCst);
@@ -3050,6 +3212,7 @@ impl UploadManager {
 
         for task_id in to_remove {
             self.remove_dedup_entry(&task_id);
+            self.release_task_group(&task_id).await;
             self.tasks.remove(&task_id);
             removed += 1;
         }
@@ -3187,13 +3350,105 @@ impl UploadManager {
         }
     }
 
+    /// 将任务归入文件夹上传组
+    ///
+    /// relative_path 为任务远程路径相对 group_root 的部分
+    async fn assign_task_group(&self, task_id: &str, group_id: &str, group_root: &str) {
+        let Some(task_info) = self.tasks.get(task_id).map(|t| t.task.clone()) else {
+            return;
+        };
+        let mut task = task_info.lock().await;
+        let relative_path = task
+            .remote_path
+            .strip_prefix(group_root)
+            .unwrap_or(&task.remote_path)
+            .trim_start_matches('/')
+            .to_string();
+        task.group_id = Some(group_id.to_string());
+        task.group_root = Some(group_root.to_string());
+        task.relative_path = Some(relative_path);
+
+        let mut totals = self.upload_groups.entry(group_id.to_string()).or_default();
+        totals.total_files += 1;
+        totals.total_size += task.total_size;
+        totals.unfinished_files += 1;
+    }
+
+    /// 任务被删除时从所属上传组的统计中扣除，组内已没有剩余任务时移除该组
+    async fn release_task_group(&self, task_id: &str) {
+        let Some(task_info) = self.tasks.get(task_id).map(|t| t.task.clone()) else {
+            return;
+        };
+        let task = task_info.lock().await;
+        let Some(group_id) = &task.group_id else {
+            return;
+        };
+        let finished = matches!(
+            task.status,
+            UploadTaskStatus::Completed | UploadTaskStatus::RapidUploadSuccess
+        );
+        if let Some(mut totals) = self.upload_groups.get_mut(group_id) {
+            totals.total_files = totals.total_files.saturating_sub(1);
+            totals.total_size = totals.total_size.saturating_sub(task.total_size);
+            if !finished {
+                totals.unfinished_files = totals.unfinished_files.saturating_sub(1);
+            }
+        }
+        self.upload_groups.remove_if(group_id, |_, totals| totals.is_drained());
+    }
+
+    /// 文件夹扫描结束：之后组内最后一个任务完成或删除时移除该组统计
+    pub fn finish_group_scan(&self, group_id: &str) {
+        if let Some(mut totals) = self.upload_groups.get_mut(group_id) {
+            totals.scan_finished = true;
+        }
+        self.upload_groups.remove_if(group_id, |_, totals| totals.is_drained());
+    }
+
+    /// 获取文件夹上传组的聚合进度
+    ///
+    /// 已完成的任务会从内存中移除，因此完成数和已上传大小由组总量减去仍在内存中的任务推算
+    pub async fn get_group_progress(&self, group_id: &str) -> Option<UploadGroupProgress> {
+        let totals = *self.upload_groups.get(group_id)?;
+        let mut progress = UploadGroupProgress {
+            total_files: totals.total_files,
+            total_size: totals.total_size,
+            ..Default::default()
+        };
+
+        let mut in_memory_files = 0usize;
+        let mut remaining_size = 0u64;
+        for entry in self.tasks.iter() {
+            let task = entry.task.lock().await;
+            if task.group_id.as_deref() != Some(group_id) {
+                continue;
+            }
+            match task.status {
+                UploadTaskStatus::Completed | UploadTaskStatus::RapidUploadSuccess => continue,
+                UploadTaskStatus::Failed => progress.failed_files += 1,
+                UploadTaskStatus::Paused => {}
+                _ => progress.active_files += 1,
+            }
+            in_memory_files += 1;
+            remaining_size += task.total_size.saturating_sub(task.uploaded_size);
+            progress.speed += task.speed;
+        }
+
+        progress.completed_files = totals.total_files.saturating_sub(in_memory_files);
+        progress.uploaded_size = totals.total_size.saturating_sub(remaining_size);
+        Some(progress)
+    }
+
     /// 批量创建去重任务
+    ///
+    /// `group` 为 (group_id, group_root)，文件夹上传时用于归组并统计聚合进度
     pub async fn create_batch_tasks_dedup(
         &self,
         files: Vec<(PathBuf, String)>,
         encrypt: bool,
         is_folder_upload: bool,
         conflict_strategy: Option<crate::uploader::UploadConflictStrategy>,
+        group: Option<(&str, &str)>,
     ) -> Result<(Vec<String>, Vec<String>)> {
         let mut new_ids = Vec::new();
         let mut existing_ids = Vec::new();
@@ -3222,6 +3477,10 @@ impl UploadManager {
                     // 更新 metadata 的 original_remote_path
                     self.update_task_original_remote_path(&task_id, &original_remote_path).await;
 
+                    if let Some((group_id, group_root)) = group {
+                        self.assign_task_group(&task_id, group_id, group_root).await;
+                    }
+
                     if let Err(e) = self.start_task(&task_id).await {
                         warn!("启动上传任务 {} 失败: {}", task_id, e);
                     }
@@ -3533,7 +3792,7 @@ impl UploadManager {
     /// - 普通任务优先启动
     /// - 备份任务只有在没有普通任务等待时才启动
     async fn try_start_waiting_tasks(&self) {
-        if !self.use_scheduler {
+        if !self.use_scheduler || self.shutting_down.load(Ordering::SeqCst) {
             return;
         }
 
@@ -3741,6 +4000,7 @@ impl UploadManager {
             None => return,
         };
         let tasks = self.tasks.clone();
+        let upload_groups = self.upload_groups.clone();
         let client = self.client.clone();
         let server_health = self.server_health.clone();
         let vip_type = self.vip_type;
@@ -3754,6 +4014,7 @@ impl UploadManager {
         let snapshot_manager = self.snapshot_manager.clone();
         // 🔥 克隆加密配置存储（用于后台监控启动加密任务）
         let encryption_config_store = self.encryption_config_store.clone();
+        let shutting_down = self.shutting_down.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
@@ -3761,6 +4022,11 @@ impl UploadManager {
             loop {
                 interval.tick().await;
 
+                // 🔥 管理器停用后退出监控
+                if shutting_down.load(Ordering::SeqCst) {
+                    break;
+                }
+
                 // 检查是否有等待任务
                 let has_waiting = {
                     let queue = waiting_queue.read().await;
@@ -3958,6 +4224,7 @@ impl UploadManager {
                             let ws_manager_clone = ws_manager.read().await.clone();
                             // 🔥 克隆 tasks 引用，用于保存创建的分片管理器
                             let tasks_clone = tasks.clone();
+                            let upload_groups = upload_groups.clone();
                             // 🔥 克隆备份通知发送器
                             let backup_notification_tx_clone =
                                 backup_notification_tx.read().await.clone();
@@ -4168,6 +4435,9 @@ impl UploadManager {
                                         .await;
                                     let mut t = task.lock().await;
                                     t.mark_rapid_upload_success();
+                                    if let Some(group_id) = &t.group_id {
+                                        finish_group_task(&upload_groups, group_id);
+                                    }
                                     drop(t);
 
                                     // 🔥 发送秒传成功通知
@@ -4312,6 +4582,8 @@ impl UploadManager {
                                     snapshot_manager: snapshot_manager_clone,
                                     // 🔥 Manager 任务列表引用（用于任务完成时立即清理）
                                     manager_tasks: Some(tasks_clone.clone()),
+                                    // 🔥 文件夹上传组统计（组内最后一个任务完成时移除）
+                                    upload_groups: Some(upload_groups.clone()),
                                 };
 
                                 if let Err(e) = scheduler_clone.register_task(schedule_info).await {
@@ -4361,6 +4633,64 @@ impl UploadManager {
         });
     }
 
+    /// 🔥 秒传预检（不创建任务）
+    ///
+    /// 流式计算文件的秒传哈希和 block_list（内存占用与文件大小无关），
+    /// 以探测路径调用 precreate 判断是否命中秒传。
+    /// 命中秒传时服务端会直接创建探测文件，检查完成后立即删除。
+    ///
+    /// # 副作用
+    /// - 每次预检使用唯一的探测文件名，并发预检互不影响
+    /// - 网盘删除接口会把探测文件移入回收站（不占用网盘容量，随回收站过期自动清除）
+    ///
+    /// # Arguments
+    /// * `local_path` - 本地文件路径
+    pub async fn check_rapid_upload(&self, local_path: &Path) -> Result<RapidUploadProbeResult> {
+        let hash = RapidUploadChecker::calculate_hash(local_path).await?;
+        let block_list = RapidUploadChecker::calculate_block_list(local_path, self.vip_type).await?;
+
+        let file_name = local_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".to_string());
+        let probe_path = format!(
+            "{}/{}_{}",
+            RAPID_CHECK_PROBE_DIR,
+            uuid::Uuid::new_v4().simple(),
+            file_name
+        );
+
+        let client = self.client.read().unwrap().clone();
+        // rtype=1：探测路径冲突时重命名，避免覆盖任何已有文件
+        let response = client
+            .precreate(&probe_path, hash.file_size, &block_list, "1")
+            .await
+            .context("秒传预检 precreate 失败")?;
+
+        let rapid_upload = response.is_rapid_upload();
+        if rapid_upload {
+            // 命中秒传时探测文件已被创建，只删除本次的探测文件（不删除目录，避免影响并发进行的预检）
+            match client.delete_files(std::slice::from_ref(&probe_path)).await {
+                Ok(resp) if resp.success => {}
+                Ok(resp) => warn!("删除秒传探测文件失败: {:?}", resp.error),
+                Err(e) => warn!("删除秒传探测文件失败: {}", e),
+            }
+        }
+
+        info!(
+            "秒传预检完成: path={:?}, size={}, rapid_upload={}",
+            local_path, hash.file_size, rapid_upload
+        );
+
+        Ok(RapidUploadProbeResult {
+            local_path: local_path.to_string_lossy().to_string(),
+            file_size: hash.file_size,
+            content_md5: hash.content_md5,
+            slice_md5: hash.slice_md5,
+            rapid_upload,
+        })
+    }
+
     /// 🔥 从恢复信息创建上传任务
     ///
     /// 用于程序启动时恢复未完成的上传任务
@@ -4375,7 +4705,7 @@ impl UploadManager {
     /// # 注意
     /// - upload_id 可能已过期，启动任务时会重新 precreate
     /// - 已完成的分片会在分片管理器中标记为完成
-    pub async fn restore_task(&self, recovery_info: UploadRecoveryInfo) -> Result<String> {
+    pub async fn restore_task(&self, mut recovery_info: UploadRecoveryInfo) -> Result<String> {
         let task_id = recovery_info.task_id.clone();
 
         // 检查任务是否已存在
@@ -4388,6 +4718,19 @@ impl UploadManager {
             anyhow::bail!("源文件不存在: {:?}", recovery_info.source_path);
         }
 
+        // 🔥 源文件在两次上传之间被修改：放弃 upload_id 和已上传分片，启动时重新 precreate
+        let progress_reset_reason = recovery_info.source_changed_reason();
+        if let Some(ref reason) = progress_reset_reason {
+            warn!("上传任务 {} {}，放弃断点和 upload_id", task_id, reason);
+            if let Ok(meta) = std::fs::metadata(&recovery_info.source_path) {
+                recovery_info.file_size = meta.len();
+            }
+            recovery_info.source_mtime = file_mtime_secs(&recovery_info.source_path);
+            recovery_info.upload_id = None;
+            recovery_info.completed_chunks = bit_set::BitSet::new();
+            recovery_info.chunk_md5s = vec![None; recovery_info.total_chunks];
+        }
+
         // 创建恢复任务（使用 Paused 状态）
         // 🔥 根据是否为备份任务选择不同的构造方式
         let mut task = if recovery_info.is_backup {
@@ -4495,6 +4838,31 @@ impl UploadManager {
             ) {
                 warn!("恢复任务持久化状态失败: {}", e);
             }
+
+            // 🔥 源文件已变化：清空持久化断点并记录新的文件信息
+            if progress_reset_reason.is_some() {
+                let pm = pm_arc.lock().await;
+                if let Err(e) = pm.reset_upload_progress(&task_id) {
+                    warn!("重置上传任务断点进度失败: {}", e);
+                }
+                if let Err(e) = pm.update_upload_source_info(
+                    &task_id,
+                    recovery_info.file_size,
+                    recovery_info.source_mtime,
+                ) {
+                    warn!("更新上传源文件信息失败: {}", e);
+                }
+            }
+        }
+
+        // 🔥 通知前端断点已重置的原因
+        if let Some(reason) = progress_reset_reason {
+            self.publish_event(UploadEvent::ProgressReset {
+                task_id: task_id.clone(),
+                reason,
+                is_backup: recovery_info.is_backup,
+            })
+            .await;
         }
 
         // 重建去重索引
@@ -4638,6 +5006,66 @@ mod tests {
         assert!(manager.get_task(&task_id).await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_group_progress() {
+        let manager = create_test_manager();
+        let mut temp_files = Vec::new();
+        let mut task_ids = Vec::new();
+
+        for (i, content) in [&b"aaaa"[..], &b"bbbbbbbb"[..], &b"cc"[..]].iter().enumerate() {
+            let mut temp_file = NamedTempFile::new().unwrap();
+            temp_file.write_all(content).unwrap();
+            temp_file.flush().unwrap();
+
+            let task_id = manager
+                .create_task(
+                    temp_file.path().to_path_buf(),
+                    format!("/backup/photos/sub/file{}.txt", i),
+                    false, // encrypt
+                    true,  // is_folder_upload
+                    None,  // conflict_strategy
+                )
+                .await
+                .unwrap();
+            manager
+                .assign_task_group(&task_id, "scan_1", "/backup/photos")
+                .await;
+            task_ids.push(task_id);
+            temp_files.push(temp_file);
+        }
+
+        assert!(manager.get_group_progress("scan_other").await.is_none());
+
+        let task = manager.get_task(&task_ids[0]).await.unwrap();
+        assert_eq!(task.relative_path.as_deref(), Some("sub/file0.txt"));
+
+        let progress = manager.get_group_progress("scan_1").await.unwrap();
+        assert_eq!(progress.total_files, 3);
+        assert_eq!(progress.total_size, 14);
+        assert_eq!(progress.completed_files, 0);
+        assert_eq!(progress.uploaded_size, 0);
+
+        // 模拟任务完成：调度器完成后会直接从 tasks 中移除并更新组统计
+        manager.tasks.remove(&task_ids[1]);
+        finish_group_task(&manager.upload_groups, "scan_1");
+        // 用户删除的任务不计入组统计
+        manager.delete_task(&task_ids[2]).await.unwrap();
+
+        let progress = manager.get_group_progress("scan_1").await.unwrap();
+        assert_eq!(progress.total_files, 2);
+        assert_eq!(progress.total_size, 12);
+        assert_eq!(progress.completed_files, 1);
+        assert_eq!(progress.uploaded_size, 8);
+        assert_eq!(progress.active_files, 1);
+
+        // 扫描结束前即使任务都已结束也保留组统计，之后最后一个任务结束时移除
+        manager.tasks.remove(&task_ids[0]);
+        finish_group_task(&manager.upload_groups, "scan_1");
+        assert!(manager.get_group_progress("scan_1").await.is_some());
+        manager.finish_group_scan("scan_1");
+        assert!(manager.get_group_progress("scan_1").await.is_none());
+    }
+
     #[tokio::test]
     async fn test_create_folder_task() {
         let manager = create_test_manager();
@@ -4943,4 +5371,38 @@ mod tests {
         assert!(max_slots > 0, "最大槽位数应该大于0");
         assert_eq!(available_slots, max_slots, "初始状态所有槽位都应该可用");
     }
+
+    #[tokio::test]
+    async fn test_prepare_shutdown_cancels_tasks() {
+        let manager = create_test_manager();
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"shutdown drain").unwrap();
+        temp_file.flush().unwrap();
+        let task_id = manager
+            .create_task(
+                temp_file.path().to_path_buf(),
+                "/test/shutdown.txt".to_string(),
+                false,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let (interrupted, unfinished) = manager
+            .prepare_shutdown(std::time::Duration::from_secs(1))
+            .await;
+        assert_eq!(interrupted, 1);
+        assert_eq!(unfinished, 0);
+        let cancelled = manager.tasks.get(&task_id).unwrap().cancel_token.is_cancelled();
+        assert!(cancelled);
+        assert!(manager.shutting_down.load(Ordering::SeqCst));
+
+        // 重复调用不再计入已取消的任务
+        let (interrupted, _) = manager
+            .prepare_shutdown(std::time::Duration::from_secs(1))
+            .await;
+        assert_eq!(interrupted, 0);
+    }
 }
@@ -32,8 +32,10 @@ pub use block_list_comparator::BlockListComparator;
 pub use engine::UploadEngine;
 pub use folder::{FolderScanner, ScanOptions, ScannedFile, BatchedScanIterator, SCAN_BATCH_SIZE};
 pub use health::PcsServerHealthManager;
-pub use manager::{UploadManager, UploadTaskInfo};
-pub use rapid_upload::{RapidCheckResult, RapidUploadChecker, RapidUploadHash};
+pub use manager::{UploadGroupProgress, UploadManager, UploadTaskInfo};
+pub use rapid_upload::{
+    RapidCheckResult, RapidUploadChecker, RapidUploadHash, RapidUploadProbeResult,
+};
 pub use scheduler::{UploadChunkScheduler, UploadTaskScheduleInfo};
 pub use scan_manager::{ScanManager, ScanTaskInfo, ScanTaskStatus, ScanCheckpoint};
 pub use task::{UploadTask, UploadTaskStatus};
@@ -41,10 +41,31 @@ pub struct RapidCheckResult {
     pub error: Option<String>,
 }
 
+/// 秒传预检结果（不创建上传任务）
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RapidUploadProbeResult {
+    /// 本地文件路径
+    pub local_path: String,
+    /// 文件大小
+    pub file_size: u64,
+    /// 文件完整 MD5
+    pub content_md5: String,
+    /// 文件前 256KB MD5
+    pub slice_md5: String,
+    /// 是否命中秒传（precreate return_type=2）
+    pub rapid_upload: bool,
+}
+
 impl RapidUploadChecker {
     /// 前 256KB 的大小常量
     const SLICE_SIZE: usize = 256 * 1024;
 
+    /// 哈希流水线单次读取的缓冲区大小
+    const READ_BUFFER_SIZE: usize = 256 * 1024;
+
+    /// 哈希流水线中同时在途的缓冲区数量
+    const PIPELINE_DEPTH: usize = 8;
+
     /// 计算文件的秒传哈希值
     ///
     /// # 参数
@@ -62,42 +83,89 @@ impl RapidUploadChecker {
     }
 
     /// 同步计算文件哈希（内部方法）
+    ///
+    /// 单次读取、并行哈希：当前线程按 256KB 块顺序读文件（只读一遍），每块同时分发给
+    /// 完整 MD5 线程和前 256KB MD5 线程，磁盘 I/O 与两路 MD5 计算重叠执行。
+    /// 完整 MD5 线程用完的缓冲区归还给读取端复用，在途缓冲区受 `PIPELINE_DEPTH` 限制
     fn calculate_hash_sync(path: &Path) -> Result<RapidUploadHash> {
         use std::fs::File;
+        use std::sync::{mpsc, Arc};
 
-        let file = File::open(path).context(format!("无法打开文件: {:?}", path))?;
+        let mut file = File::open(path).context(format!("无法打开文件: {:?}", path))?;
         let metadata = file.metadata().context("无法获取文件元数据")?;
         let file_size = metadata.len();
 
-        // 使用 BufReader 提高读取效率
-        let mut reader = std::io::BufReader::with_capacity(1024 * 1024, file);
-
-        // 1. 计算完整 MD5 和前 256KB MD5
-        let mut full_hasher = Md5Context::new();
-        let mut slice_hasher = Md5Context::new();
-        let mut slice_bytes_read: usize = 0;
-        let mut buffer = [0u8; 65536]; // 64KB 缓冲区
-
-        loop {
-            let bytes_read = reader.read(&mut buffer).context("读取文件失败")?;
-            if bytes_read == 0 {
-                break;
-            }
-
-            // 更新完整 MD5
-            full_hasher.consume(&buffer[..bytes_read]);
+        // 已读取的数据块（读取端按实际字节数截断），两个哈希线程共享同一份数据
+        let (full_tx, full_rx) = mpsc::sync_channel::<Arc<Vec<u8>>>(Self::PIPELINE_DEPTH);
+        let (slice_tx, slice_rx) = mpsc::sync_channel::<Arc<Vec<u8>>>(Self::PIPELINE_DEPTH);
+        // 完整 MD5 计算完成后归还的空闲缓冲区
+        let (free_tx, free_rx) = mpsc::channel::<Vec<u8>>();
+
+        let (content_md5, slice_md5) = std::thread::scope(|scope| -> Result<(String, String)> {
+            // 1. 完整 MD5 线程
+            let full_worker = scope.spawn(move || {
+                let mut hasher = Md5Context::new();
+                for chunk in full_rx {
+                    hasher.consume(chunk.as_slice());
+                    // 前 256KB 线程已释放引用时才能回收
+                    if let Ok(buffer) = Arc::try_unwrap(chunk) {
+                        let _ = free_tx.send(buffer);
+                    }
+                }
+                format!("{:x}", hasher.compute())
+            });
+
+            // 2. 前 256KB MD5 线程（读满 256KB 后提前结束）
+            let slice_worker = scope.spawn(move || {
+                let mut hasher = Md5Context::new();
+                let mut slice_bytes_read: usize = 0;
+                for chunk in slice_rx {
+                    let slice_bytes = chunk.len().min(Self::SLICE_SIZE - slice_bytes_read);
+                    hasher.consume(&chunk[..slice_bytes]);
+                    slice_bytes_read += slice_bytes;
+                    if slice_bytes_read >= Self::SLICE_SIZE {
+                        break;
+                    }
+                }
+                format!("{:x}", hasher.compute())
+            });
 
-            // 更新前 256KB MD5
-            if slice_bytes_read < Self::SLICE_SIZE {
-                let remaining = Self::SLICE_SIZE - slice_bytes_read;
-                let slice_bytes = bytes_read.min(remaining);
-                slice_hasher.consume(&buffer[..slice_bytes]);
-                slice_bytes_read += slice_bytes;
+            // 3. 当前线程顺序读取文件并分发；出错返回时通道随之关闭，哈希线程自然退出
+            let mut slice_tx = Some(slice_tx);
+            let mut slice_bytes_sent: usize = 0;
+            loop {
+                let mut buffer = free_rx
+                    .try_recv()
+                    .unwrap_or_else(|_| Vec::with_capacity(Self::READ_BUFFER_SIZE));
+                buffer.resize(Self::READ_BUFFER_SIZE, 0);
+                let bytes_read = file.read(&mut buffer).context("读取文件失败")?;
+                if bytes_read == 0 {
+                    break;
+                }
+                buffer.truncate(bytes_read);
+                let chunk = Arc::new(buffer);
+
+                if let Some(tx) = slice_tx.take() {
+                    slice_bytes_sent += bytes_read;
+                    if tx.send(Arc::clone(&chunk)).is_ok() && slice_bytes_sent < Self::SLICE_SIZE {
+                        slice_tx = Some(tx);
+                    }
+                }
+                if full_tx.send(chunk).is_err() {
+                    break;
+                }
             }
-        }
-
-        let content_md5 = format!("{:x}", full_hasher.compute());
-        let slice_md5 = format!("{:x}", slice_hasher.compute());
+            drop(slice_tx);
+            drop(full_tx);
+
+            let content_md5 = full_worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("完整 MD5 计算线程异常退出"))?;
+            let slice_md5 = slice_worker
+                .join()
+                .map_err(|_| anyhow::anyhow!("前 256KB MD5 计算线程异常退出"))?;
+            Ok((content_md5, slice_md5))
+        })?;
 
         debug!(
             "文件哈希计算完成: path={:?}, size={}, content_md5={}, slice_md5={}",
@@ -383,4 +451,105 @@ mod tests {
         assert_eq!(hash1.content_md5, hash2.content_md5);
         assert_eq!(hash1.slice_md5, hash2.slice_md5);
     }
+
+    /// 旧版单线程实现（64KB 缓冲区顺序读取并计算），作为并行哈希实现的对照
+    fn sequential_hash(path: &Path) -> (String, String) {
+        let mut reader = std::fs::File::open(path).unwrap();
+        let mut full_hasher = Md5Context::new();
+        let mut slice_hasher = Md5Context::new();
+        let mut slice_bytes_read: usize = 0;
+        let mut buffer = [0u8; 65536];
+
+        loop {
+            let bytes_read = reader.read(&mut buffer).unwrap();
+            if bytes_read == 0 {
+                break;
+            }
+            full_hasher.consume(&buffer[..bytes_read]);
+            if slice_bytes_read < RapidUploadChecker::SLICE_SIZE {
+                let slice_bytes = bytes_read.min(RapidUploadChecker::SLICE_SIZE - slice_bytes_read);
+                slice_hasher.consume(&buffer[..slice_bytes]);
+                slice_bytes_read += slice_bytes;
+            }
+        }
+
+        (
+            format!("{:x}", full_hasher.compute()),
+            format!("{:x}", slice_hasher.compute()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pipelined_hash_matches_sequential() {
+        // 固定输入：跨越多个读取缓冲区且不是缓冲区大小的整数倍
+        let content: Vec<u8> = (0..(5 * 1024 * 1024 + 12345u32))
+            .map(|i| (i.wrapping_mul(31) ^ (i >> 7)) as u8)
+            .collect();
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(&content).unwrap();
+        temp_file.flush().unwrap();
+
+        let hash = RapidUploadChecker::calculate_hash(temp_file.path())
+            .await
+            .unwrap();
+        let (content_md5, slice_md5) = sequential_hash(temp_file.path());
+
+        assert_eq!(hash.file_size, content.len() as u64);
+        assert_eq!(hash.content_md5, content_md5);
+        assert_eq!(hash.slice_md5, slice_md5);
+        assert_eq!(hash.content_md5, format!("{:x}", md5::compute(&content)));
+        assert_eq!(
+            hash.slice_md5,
+            format!(
+                "{:x}",
+                md5::compute(&content[..RapidUploadChecker::SLICE_SIZE])
+            )
+        );
+    }
+
+    /// 基准测试：对比旧版单线程实现与单次读取并行哈希实现的吞吐量
+    ///
+    /// 运行：`cargo test --release rapid_upload::tests::bench_ -- --ignored --nocapture`
+    /// 可通过 `RAPID_HASH_BENCH_MB` 指定测试文件大小（默认 512MB）
+    #[tokio::test]
+    #[ignore]
+    async fn bench_pipelined_hash_vs_sequential() {
+        let size_mb: usize = std::env::var("RAPID_HASH_BENCH_MB")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(512);
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let block: Vec<u8> = (0..1024 * 1024u32).map(|i| (i ^ (i >> 9)) as u8).collect();
+        for _ in 0..size_mb {
+            temp_file.write_all(&block).unwrap();
+        }
+        temp_file.flush().unwrap();
+
+        // 预热页缓存，两种实现读取同样的缓存数据，只比较哈希流水线本身
+        let _ = sequential_hash(temp_file.path());
+
+        let start = std::time::Instant::now();
+        let (content_md5, slice_md5) = sequential_hash(temp_file.path());
+        let sequential = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let hash = RapidUploadChecker::calculate_hash(temp_file.path())
+            .await
+            .unwrap();
+        let pipelined = start.elapsed();
+
+        assert_eq!(hash.content_md5, content_md5);
+        assert_eq!(hash.slice_md5, slice_md5);
+
+        let throughput = |d: std::time::Duration| size_mb as f64 / d.as_secs_f64();
+        println!(
+            "{}MB: 单线程 {:?} ({:.1} MB/s), 并行哈希 {:?} ({:.1} MB/s), 提升 {:.2}x",
+            size_mb,
+            sequential,
+            throughput(sequential),
+            pipelined,
+            throughput(pipelined),
+            sequential.as_secs_f64() / pipelined.as_secs_f64()
+        );
+    }
 }
@@ -272,7 +272,13 @@ impl ScanManager {
 
             // 创建任务（带去重）
             match upload_manager
-                .create_batch_tasks_dedup(files, encrypt, true, conflict_strategy)
+                .create_batch_tasks_dedup(
+                    files,
+                    encrypt,
+                    true,
+                    conflict_strategy,
+                    Some((&scan_task_id, &remote_folder)),
+                )
                 .await
             {
                 Ok((new_ids, existing_ids)) => {
@@ -383,6 +389,9 @@ impl ScanManager {
             _ => {}
         }
 
+        // 不会再有新任务加入上传组，组内任务全部结束后移除组统计
+        upload_manager.finish_group_scan(&scan_task_id);
+
         // 删除检查点文件
         delete_checkpoint(&wal_dir, &scan_task_id);
 
@@ -403,6 +412,17 @@ impl ScanManager {
         false
     }
 
+    /// 🔥 停用扫描管理器（切换账号时调用）
+    ///
+    /// 取消所有进行中的扫描，返回取消的扫描数
+    pub fn shutdown(&self) -> usize {
+        self.active_scans
+            .iter()
+            .filter(|info| info.status == ScanTaskStatus::Scanning)
+            .inspect(|info| info.cancel_token.cancel())
+            .count()
+    }
+
     /// 查询扫描状态（Task 6.5）
     pub fn get_scan_status(&self, scan_task_id: &str) -> Option<ScanTaskInfo> {
         self.active_scans.get(scan_task_id).map(|v| v.clone())
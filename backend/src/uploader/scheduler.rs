@@ -12,7 +12,7 @@
 
 use crate::autobackup::events::{BackupTransferNotification, TransferTaskType};
 use crate::encryption::SnapshotManager;
-use crate::netdisk::{NetdiskClient, UploadErrorKind};
+use crate::netdisk::{NetdiskClient, NetdiskError, UploadErrorKind};
 use crate::persistence::PersistenceManager;
 use crate::task_slot_pool::TaskSlotPool;
 use crate::server::events::{ProgressThrottler, TaskEvent, UploadEvent};
@@ -182,6 +182,68 @@ pub struct UploadTaskScheduleInfo {
     // 🔥 Manager 任务列表引用（用于任务完成时立即清理，避免内存泄漏）
     /// UploadManager.tasks 的引用，任务完成后从中移除
     pub manager_tasks: Option<Arc<dashmap::DashMap<String, crate::uploader::UploadTaskInfo>>>,
+
+    // 🔥 文件夹上传组统计引用（组内最后一个任务完成时移除该组）
+    /// UploadManager.upload_groups 的引用
+    pub(crate) upload_groups: Option<Arc<crate::uploader::manager::UploadGroupMap>>,
+}
+
+// =====================================================
+// upload_id 失效处理
+// =====================================================
+
+/// 重置已失效的上传会话，返回任务错误信息
+///
+/// 合并分片返回分片缺失或上传分片返回 upload_id 失效（errno=31363）时，服务端已清理该 upload_id
+/// 下的分片，清空持久化断点以及 Manager 中恢复的 upload_id / 分片信息，
+/// 任务重试时会重新 precreate 并从头上传
+async fn reset_expired_upload_session(
+    task_id: &str,
+    task_info: &UploadTaskScheduleInfo,
+    detail: impl std::fmt::Display,
+) -> String {
+    let err_msg = format!("上传会话已过期（{}），已重置上传进度，重试将重新预创建", detail);
+    error!("上传任务 {} {}", task_id, err_msg);
+
+    if let Some(ref pm) = task_info.persistence_manager {
+        if let Err(e) = pm.lock().await.reset_upload_progress(task_id) {
+            warn!("重置上传任务 {} 断点进度失败: {}", task_id, e);
+        }
+    }
+
+    if let Some(ref manager_tasks) = task_info.manager_tasks {
+        if let Some(mut info) = manager_tasks.get_mut(task_id) {
+            info.restored_upload_id = None;
+            info.restored_completed_chunks = None;
+            info.chunk_manager = None;
+        }
+    }
+
+    task_info.uploaded_bytes.store(0, Ordering::SeqCst);
+    let mut t = task_info.task.lock().await;
+    t.uploaded_size = 0;
+    t.completed_chunks = 0;
+    drop(t);
+
+    warn!("上传任务 {} 的 upload_id 已失效，已重置上传进度", task_id);
+    err_msg
+}
+
+/// 合并分片失败时生成任务错误信息
+///
+/// 分片缺失（errno=31363）说明 upload_id 已在服务端失效，同时重置断点以便重试时重新 precreate
+async fn create_file_error_message(
+    task_id: &str,
+    task_info: &UploadTaskScheduleInfo,
+    errno: i32,
+    errmsg: &str,
+) -> String {
+    if UploadErrorKind::from_errno(errno) == UploadErrorKind::UploadIdExpired {
+        return reset_expired_upload_session(task_id, task_info, format!("errno={}", errno)).await;
+    }
+    let err_msg = format!("合并分片失败: errno={}, errmsg={}", errno, errmsg);
+    error!("上传任务 {} {}", task_id, err_msg);
+    err_msg
 }
 
 // =====================================================
@@ -661,6 +723,9 @@ impl UploadChunkScheduler {
                                                     t.mark_completed();
                                                     (t.group_id.clone(), t.encrypted_temp_path.clone(), t.is_backup)
                                                 };
+                                                if let (Some(groups), Some(gid)) = (&task_info.upload_groups, &group_id) {
+                                                    crate::uploader::manager::finish_group_task(groups, gid);
+                                                }
 
                                                 // 🔥 更新加密映射（调度循环触发）
                                                 update_encryption_mapping(task_id, &task_info, is_backup).await;
@@ -710,11 +775,13 @@ impl UploadChunkScheduler {
                                                     }
                                                 }
                                             } else {
-                                                let err_msg = format!(
-                                                    "合并分片失败: errno={}, errmsg={}",
-                                                    response.errno, response.errmsg
-                                                );
-                                                error!("上传任务 {} {}", task_id, err_msg);
+                                                let err_msg = create_file_error_message(
+                                                    task_id,
+                                                    &task_info,
+                                                    response.errno,
+                                                    &response.errmsg,
+                                                )
+                                                .await;
 
                                                 // 🔥 如果是备份任务，通知失败
                                                 let is_backup = {
@@ -846,6 +913,10 @@ impl UploadChunkScheduler {
                         slot_id, chunk_index, e
                     );
 
+                    // 🔥 upload_id 已失效时重新调度分片无意义，重置断点后直接失败任务
+                    let session_expired =
+                        NetdiskError::find(&e) == Some(NetdiskError::UploadIdExpired);
+
                     // 取消上传标记 + 递增分片调度级重试计数
                     let chunk_retries = {
                         let mut manager = task_info.chunk_manager.lock().await;
@@ -858,15 +929,19 @@ impl UploadChunkScheduler {
                     // 外层调度级重试上限 = 内层重试 * 2
                     let max_schedule_retries = max_retries.load(Ordering::SeqCst) as u32 * 2;
 
-                    if chunk_retries < max_schedule_retries {
+                    if !session_expired && chunk_retries < max_schedule_retries {
                         // 分片还有重试机会，留在任务中等待调度器下一轮重新调度
                         warn!(
                             "[上传线程{}] 分片 #{} 第 {}/{} 次调度失败，等待重新调度: {}",
                             slot_id, chunk_index, chunk_retries, max_schedule_retries, e
                         );
                     } else {
-                        // 重试耗尽，杀掉整个任务
-                        let error_msg = e.to_string();
+                        // 重试耗尽或上传会话失效，杀掉整个任务
+                        let error_msg = if session_expired {
+                            reset_expired_upload_session(&task_id, &task_info, &e).await
+                        } else {
+                            e.to_string()
+                        };
                         let is_backup = {
                             let mut t = task_info.task.lock().await;
                             t.mark_failed(error_msg.clone());
@@ -986,6 +1061,9 @@ impl UploadChunkScheduler {
                                         t.mark_completed();
                                         (t.group_id.clone(), t.is_backup, t.encrypted_temp_path.clone())
                                     };
+                                    if let (Some(groups), Some(gid)) = (&task_info.upload_groups, &group_id) {
+                                        crate::uploader::manager::finish_group_task(groups, gid);
+                                    }
 
                                     // 🔥 更新加密映射（回调触发）
                                     update_encryption_mapping(&task_id, &task_info, is_backup).await;
@@ -1038,11 +1116,13 @@ impl UploadChunkScheduler {
                                         }
                                     }
                                 } else {
-                                    let err_msg = format!(
-                                        "合并分片失败: errno={}, errmsg={}",
-                                        response.errno, response.errmsg
-                                    );
-                                    error!("上传任务 {} {}", task_id, err_msg);
+                                    let err_msg = create_file_error_message(
+                                        &task_id,
+                                        &task_info,
+                                        response.errno,
+                                        &response.errmsg,
+                                    )
+                                    .await;
 
                                     let is_backup = {
                                         let mut t = task_info.task.lock().await;
@@ -1252,6 +1332,7 @@ impl UploadChunkScheduler {
                         t.total_chunks = total_chunks;
                         if speed > 0 {
                             t.speed = speed;
+                            t.update_eta();
                         }
 
                         // 🔥 刷新槽位时间戳（带节流，防止槽位超时释放）
@@ -1284,6 +1365,7 @@ impl UploadChunkScheduler {
                                             total_size,
                                             speed,
                                             progress,
+                                            eta_secs: t.eta_secs,
                                             completed_chunks,
                                             total_chunks,
                                             is_backup: t.is_backup,
@@ -1322,8 +1404,10 @@ impl UploadChunkScheduler {
                 }
                 Err(e) => {
                     let error_kind = classify_upload_error(&e);
+                    let session_expired =
+                        NetdiskError::find(&e) == Some(NetdiskError::UploadIdExpired);
 
-                    if !error_kind.is_retriable() {
+                    if session_expired || !error_kind.is_retriable() {
                         error!(
                             "[上传线程{}] 分片 #{} 上传失败（不可重试）: {:?}, 错误: {}",
                             slot_id, chunk.index, error_kind, e
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use crate::common::EtaEstimator;
+
 /// 上传任务状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -45,6 +47,9 @@ pub struct UploadTask {
     pub status: UploadTaskStatus,
     /// 上传速度 (bytes/s)
     pub speed: u64,
+    /// 预计剩余时间（秒），速度为 0 或总大小未知时为 None
+    #[serde(default)]
+    pub eta_secs: Option<u64>,
     /// 创建时间 (Unix timestamp)
     pub created_at: i64,
     /// 开始时间 (Unix timestamp)
@@ -155,6 +160,11 @@ pub struct UploadTask {
     /// 冲突处理策略（用于转换为百度 API 的 rtype 参数）
     #[serde(default)]
     pub conflict_strategy: crate::uploader::UploadConflictStrategy,
+
+    // === 🔥 剩余时间估算相关字段 ===
+    /// 剩余时间估算器（速度移动平均，不持久化）
+    #[serde(skip)]
+    pub eta_estimator: EtaEstimator,
 }
 
 fn default_key_version() -> u32 {
@@ -172,6 +182,7 @@ impl UploadTask {
             uploaded_size: 0,
             status: UploadTaskStatus::Pending,
             speed: 0,
+            eta_secs: None,
             created_at: chrono::Utc::now().timestamp(),
             started_at: None,
             completed_at: None,
@@ -206,6 +217,8 @@ impl UploadTask {
             encryption_key_version: 1,
             // 冲突策略初始化
             conflict_strategy: crate::uploader::UploadConflictStrategy::default(),
+            // 剩余时间估算字段初始化
+            eta_estimator: EtaEstimator::default(),
         }
     }
 
@@ -252,13 +265,18 @@ impl UploadTask {
         (self.uploaded_size as f64 / self.total_size as f64) * 100.0
     }
 
-    /// 估算剩余时间 (秒)
-    pub fn eta(&self) -> Option<u64> {
-        if self.speed == 0 || self.uploaded_size >= self.total_size {
-            return None;
-        }
-        let remaining = self.total_size - self.uploaded_size;
-        Some(remaining / self.speed)
+    /// 按当前速度更新预计剩余时间（速度经移动平均平滑）
+    pub fn update_eta(&mut self) -> Option<u64> {
+        self.eta_secs = self
+            .eta_estimator
+            .update(self.speed, self.uploaded_size, self.total_size);
+        self.eta_secs
+    }
+
+    /// 清除预计剩余时间（任务不再传输时调用）
+    pub fn clear_eta(&mut self) {
+        self.eta_secs = None;
+        self.eta_estimator.reset();
     }
 
     /// 标记为秒传检查中
@@ -332,6 +350,7 @@ impl UploadTask {
         self.status = UploadTaskStatus::Completed;
         self.completed_at = Some(chrono::Utc::now().timestamp());
         self.uploaded_size = self.total_size;
+        self.clear_eta();
     }
 
     /// 标记为秒传成功
@@ -340,17 +359,20 @@ impl UploadTask {
         self.completed_at = Some(chrono::Utc::now().timestamp());
         self.uploaded_size = self.total_size;
         self.is_rapid_upload = true;
+        self.clear_eta();
     }
 
     /// 标记为失败
     pub fn mark_failed(&mut self, error: String) {
         self.status = UploadTaskStatus::Failed;
         self.error = Some(error);
+        self.clear_eta();
     }
 
     /// 标记为暂停
     pub fn mark_paused(&mut self) {
         self.status = UploadTaskStatus::Paused;
+        self.clear_eta();
     }
 
     /// 设置秒传哈希值
@@ -404,10 +426,12 @@ mod tests {
 
         task.uploaded_size = 200;
         task.speed = 100; // 100 bytes/s
-        assert_eq!(task.eta(), Some(8)); // (1000 - 200) / 100 = 8s
+        assert_eq!(task.update_eta(), Some(8)); // (1000 - 200) / 100 = 8s
+        assert_eq!(task.eta_secs, Some(8));
 
         task.speed = 0;
-        assert_eq!(task.eta(), None); // 速度为0，无法估算
+        assert_eq!(task.update_eta(), None); // 速度为0，无法估算
+        assert_eq!(task.eta_secs, None);
     }
 
     #[test]
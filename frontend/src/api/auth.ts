@@ -103,6 +103,24 @@ export async function getCurrentUser(): Promise<UserAuth> {
   throw new Error(response.message || '获取用户信息失败')
 }
 
+/// 网盘空间配额（字节）
+export interface QuotaInfo {
+  total: number
+  used: number
+  free: number
+}
+
+/**
+ * 获取网盘空间配额（用于展示空间使用情况）
+ */
+export async function getUserQuota(): Promise<QuotaInfo> {
+  const response = (await apiClient.get('/user/quota')) as ApiResponse<QuotaInfo>
+  if (response.code === 0 && response.data) {
+    return response.data
+  }
+  throw new Error(response.message || '获取网盘配额失败')
+}
+
 export interface CookieLoginResult {
   user: UserAuth
   message: string
@@ -119,9 +137,75 @@ export async function cookieLogin(cookies: string): Promise<CookieLoginResult> {
   return { user: response.data, message: response.message }
 }
 
+export interface ImportCredentialsRequest {
+  bduss: string
+  stoken?: string
+  ptoken?: string
+}
+
+/**
+ * 导入 BDUSS / STOKEN / PTOKEN 凭证登录
+ */
+export async function importCredentials(req: ImportCredentialsRequest): Promise<CookieLoginResult> {
+  const response = (await apiClient.post('/auth/import', req)) as ApiResponse<UserAuth>
+  if (response.code !== 0 || !response.data) {
+    throw new Error(response.message || '导入凭证登录失败')
+  }
+  return { user: response.data, message: response.message }
+}
+
 /**
  * 登出
  */
 export async function logout(): Promise<void> {
   await apiClient.post('/auth/logout')
 }
+
+export interface AccountInfo {
+  uid: number
+  username: string
+  nickname?: string
+  avatar_url?: string
+  vip_type?: number
+  is_active: boolean
+}
+
+/**
+ * 获取已保存的账号列表
+ */
+export async function getAccounts(): Promise<AccountInfo[]> {
+  const response = (await apiClient.get('/auth/accounts')) as ApiResponse<AccountInfo[]>
+  if (response.code !== 0 || !response.data) {
+    throw new Error(response.message || '获取账号列表失败')
+  }
+  return response.data
+}
+
+/**
+ * 切换账号
+ */
+export async function switchAccount(uid: number): Promise<UserAuth> {
+  const response = (await apiClient.post('/auth/switch', { uid })) as ApiResponse<UserAuth>
+  if (response.code !== 0 || !response.data) {
+    throw new Error(response.message || '切换账号失败')
+  }
+  return response.data
+}
+
+export interface AuthStatus {
+  logged_in: boolean
+  auth_valid: boolean
+  uid?: number
+  last_checked_at?: number
+}
+
+/**
+ * 获取登录态状态（后台 BDUSS 检测结果）
+ */
+export async function getAuthStatus(): Promise<AuthStatus> {
+  const response = (await apiClient.get('/auth/status')) as ApiResponse<AuthStatus>
+  if (response.code !== 0 || !response.data) {
+    throw new Error(response.message || '获取登录态失败')
+  }
+  return response.data
+}
@@ -3,6 +3,7 @@
  *
  * 本模块提供离线下载功能的前端 API 接口，包括：
  * - 添加离线下载任务
+ * - 批量添加离线下载任务
  * - 查询任务列表
  * - 查询单个任务详情
  * - 取消任务
@@ -83,6 +84,8 @@ export interface CloudDlTaskInfo {
   file_list: CloudDlFileInfo[]
   /** 结果码 */
   result: number
+  /** 下载进度百分比（0-100，后端计算） */
+  progress_percent?: number
 }
 
 /**
@@ -93,9 +96,9 @@ export interface AddTaskRequest {
   source_url: string
   /** 网盘保存路径（默认为根目录 "/"） */
   save_path?: string
-  /** 是否启用自动下载到本地 */
+  /** 是否启用自动下载到本地（不传时使用全局配置 cloud_dl.auto_download） */
   auto_download?: boolean
-  /** 本地下载目录（自动下载时使用） */
+  /** 本地下载目录（自动下载时使用，不传时使用全局配置目录） */
   local_download_path?: string
   /** 完成时是否询问下载目录 */
   ask_download_path?: boolean
@@ -107,6 +110,37 @@ export interface AddTaskRequest {
 export interface AddTaskResponse {
   /** 新创建的任务 ID */
   task_id: number
+  /** 添加后查询到的任务详情（查询失败时为 null） */
+  task: CloudDlTaskInfo | null
+}
+
+/**
+ * 批量添加任务请求（保存路径和自动下载设置对所有链接生效）
+ */
+export interface BatchAddTaskRequest extends Omit<AddTaskRequest, 'source_url'> {
+  /** 下载源链接列表（空白行和重复链接会被忽略） */
+  urls: string[]
+}
+
+/**
+ * 批量添加中单个链接的结果
+ */
+export interface BatchAddTaskItem {
+  source_url: string
+  /** 新创建的任务 ID（失败时为 null） */
+  task_id: number | null
+  /** 失败原因 */
+  error?: string
+}
+
+/**
+ * 批量添加任务响应
+ */
+export interface BatchAddTaskResponse {
+  /** 每个链接的结果（与请求顺序一致） */
+  results: BatchAddTaskItem[]
+  success_count: number
+  failed_count: number
 }
 
 /**
@@ -133,6 +167,8 @@ export interface OperationResponse {
   success: boolean
   /** 可选的消息 */
   message?: string
+  /** 操作后的任务详情（任务已不存在或查询失败时不返回） */
+  task?: CloudDlTaskInfo
 }
 
 /**
@@ -163,6 +199,18 @@ export async function addTask(req: AddTaskRequest): Promise<AddTaskResponse> {
   return apiClient.post('/cloud-dl/tasks', req)
 }
 
+/**
+ * 批量添加离线下载任务
+ *
+ * 后端逐个添加并间隔请求，链接较多时耗时较长，超时按链接数放宽
+ *
+ * @param req 批量添加请求
+ * @returns 每个链接的添加结果
+ */
+export async function batchAddTasks(req: BatchAddTaskRequest): Promise<BatchAddTaskResponse> {
+  return apiClient.post('/cloud-dl/tasks/batch', req, { timeout: 30000 + req.urls.length * 5000 })
+}
+
 /**
  * 获取离线下载任务列表
  *
@@ -0,0 +1,44 @@
+import { apiClient } from './client'
+
+/// 测速请求（所有字段可选，未指定 path 时从网盘根目录自动选择文件）
+export interface SpeedTestRequest {
+  path?: string
+  fs_id?: number
+  size_mb?: number // 默认 8，最大 64
+}
+
+/// 下载测速结果
+export interface SpeedTestResult {
+  remote_path: string
+  bytes: number
+  elapsed_ms: number
+  speed_mbps: number // MB/s
+  cdn_host: string | null
+  url_index: number // 可作为下载任务的 dlink_prefer
+  url_count: number
+}
+
+/**
+ * 下载测速（下载一小段数据测量当前速度，不创建下载任务）
+ */
+export async function runSpeedTest(req: SpeedTestRequest = {}): Promise<SpeedTestResult> {
+  return apiClient.post('/diagnostics/speedtest', req)
+}
+
+/// 签名诊断结果（BDUSS 已脱敏）
+export interface SignDiagnostics {
+  uid: number
+  bduss: string
+  devuid: string
+  time: number
+  rand: string
+  url_params: string
+  locate_url: string
+}
+
+/**
+ * 签名诊断（需开启 security.sign_diagnostics，只读模式下不可用）
+ */
+export async function getSignDiagnostics(path: string): Promise<SignDiagnostics> {
+  return apiClient.get('/diagnostics/sign', { params: { path } })
+}
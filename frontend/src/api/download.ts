@@ -10,6 +10,9 @@ export type DownloadConflictStrategy = 'overwrite' | 'skip' | 'auto_rename'
 /// 任务状态
 export type TaskStatus = 'pending' | 'downloading' | 'decrypting' | 'paused' | 'completed' | 'failed'
 
+/// 下载任务排队优先级
+export type DownloadPriority = 'high' | 'normal' | 'low'
+
 /// 下载任务
 export interface DownloadTask {
   id: string
@@ -20,6 +23,7 @@ export interface DownloadTask {
   downloaded_size: number
   status: TaskStatus
   speed: number
+  eta_secs?: number | null // 预计剩余时间（秒）
   created_at: number
   started_at?: number
   completed_at?: number
@@ -35,15 +39,28 @@ export interface DownloadTask {
   decrypt_progress?: number
   decrypted_path?: string
   original_filename?: string
+  /** 批量下载批次ID（同一次批量下载创建的任务共享） */
+  batch_id?: string
+  /** 等待队列优先级（同优先级先进先出） */
+  priority?: DownloadPriority
+  /** 首选下载链接索引（未指定时使用全部可用链接） */
+  dlink_prefer?: number
+  /** 单任务最大并发分片数覆盖值（未指定时使用全局默认或按文件大小计算） */
+  max_chunks_override?: number
+  /** 实际提供下载的 CDN 主机 */
+  cdn_host?: string
 }
 
 /// 创建下载任务请求
 export interface CreateDownloadRequest {
   fs_id: number
-  remote_path: string
-  filename: string
-  total_size: number
+  remote_path?: string             // 未指定时按 fs_id 解析路径、文件名和大小
+  filename?: string
+  total_size?: number
+  md5?: string                     // 网盘文件 MD5（用于下载完成后校验）
   conflict_strategy?: DownloadConflictStrategy
+  dlink_prefer?: number            // 首选下载链接索引（用于避开有问题的 CDN 节点）
+  max_chunks_override?: number     // 最大并发分片数（超过会员等级上限时按上限处理，过高可能导致限速）
 }
 
 /**
@@ -60,6 +77,29 @@ export async function getAllDownloads(): Promise<DownloadTask[]> {
   return apiClient.get('/downloads')
 }
 
+/// 历史任务分页查询参数
+export interface HistoryQueryParams {
+  page?: number // 从 1 开始
+  size?: number // 最大 200
+  status?: string
+  q?: string // 文件名（路径）子串
+}
+
+/// 历史任务分页响应
+export interface HistoryPage<T> {
+  total: number
+  page: number
+  size: number
+  tasks: T[]
+}
+
+/**
+ * 分页查询下载历史
+ */
+export async function getDownloadHistory(params: HistoryQueryParams = {}): Promise<HistoryPage<DownloadTask>> {
+  return apiClient.get('/downloads/history', { params })
+}
+
 /**
  * 获取指定下载任务
  */
@@ -67,6 +107,14 @@ export async function getDownload(taskId: string): Promise<DownloadTask> {
   return apiClient.get(`/downloads/${taskId}`)
 }
 
+/**
+ * 获取下载任务最近的日志（仅内存中保留的部分）
+ * @param limit 返回最近多少行，默认 100
+ */
+export async function getDownloadLogs(taskId: string, limit?: number): Promise<string[]> {
+  return apiClient.get(`/downloads/${taskId}/logs`, { params: { limit } })
+}
+
 /**
  * 暂停下载任务
  */
@@ -81,6 +129,97 @@ export async function resumeDownload(taskId: string): Promise<string> {
   return apiClient.post(`/downloads/${taskId}/resume`)
 }
 
+/**
+ * 设置下载任务排队优先级（等待中的任务立即重新排队）
+ */
+export async function setDownloadPriority(taskId: string, priority: DownloadPriority): Promise<string> {
+  return apiClient.post(`/downloads/${taskId}/priority`, { priority })
+}
+
+/// 等待队列条目
+export interface WaitingQueueItem {
+  position: number // 从 0 开始，越小越先启动
+  task_id: string
+  filename: string
+  group_id?: string
+  group_root?: string
+  priority: DownloadPriority
+  is_backup: boolean
+}
+
+/**
+ * 获取下载等待队列（按启动顺序）
+ */
+export async function getDownloadQueue(): Promise<WaitingQueueItem[]> {
+  return apiClient.get('/downloads/queue')
+}
+
+/// 活跃任务的槽位与分片占用
+export interface ActiveTaskSlotInfo {
+  task_id: string
+  group_id: string | null
+  slot_id: number | null
+  is_borrowed_slot: boolean
+  active_chunks: number
+  max_concurrent_chunks: number
+  speed: number // bytes/s
+  cdn_host: string | null // 实际提供下载的 CDN 主机
+}
+
+/// 任务位池中的单个槽位
+export interface SlotOccupancy {
+  slot_id: number
+  slot_type: 'fixed' | 'borrowed'
+  task_id: string | null
+  is_folder_main: boolean
+  priority: 'normal' | 'subtask' | 'backup'
+  idle_secs: number | null // 距最后更新的秒数
+}
+
+/// 下载调度调试信息
+export interface SlotDebugInfo {
+  active_threads: number
+  max_threads: number
+  tasks: ActiveTaskSlotInfo[]
+  slot_pool: {
+    max_slots: number
+    used_slots: number
+    slots: SlotOccupancy[]
+    borrowed: Record<string, number[]> // folder_id -> 借调槽位ID
+  }
+}
+
+/**
+ * 获取下载调度调试信息（排查任务卡住、借调位异常）
+ */
+export async function getDownloadSlotDebug(): Promise<SlotDebugInfo> {
+  return apiClient.get('/downloads/debug/slots')
+}
+
+/**
+ * 调整下载任务在等待队列中的位置
+ * @returns 调整后的等待队列顺序（任务ID列表）
+ */
+export async function reorderDownload(taskId: string, position: number): Promise<string[]> {
+  return apiClient.post(`/downloads/${taskId}/reorder`, { position })
+}
+
+/**
+ * 手动刷新下载任务的 CDN 链接
+ * @returns 新增/更新的链接数量
+ */
+export async function refreshDownloadUrl(taskId: string): Promise<{ refreshed_count: number }> {
+  return apiClient.post(`/downloads/${taskId}/refresh-url`)
+}
+
+/**
+ * 修改下载任务的本地目录（仅已暂停/已失败的任务，下载中的任务需先暂停）
+ * @returns 修改后的本地保存路径
+ */
+export async function moveDownloadDir(taskId: string, dir: string): Promise<{ local_path: string }> {
+  return apiClient.post(`/downloads/${taskId}/move-dir`, { dir })
+}
+
 /**
  * 删除下载任务
  * @param taskId 任务ID
@@ -90,6 +229,16 @@ export async function deleteDownload(taskId: string, deleteFile: boolean = false
   return apiClient.delete(`/downloads/${taskId}`, { params: { delete_file: deleteFile } })
 }
 
+/**
+ * 删除同一 group（文件夹）的所有下载任务
+ * @param groupId 文件夹任务ID
+ * @param deleteFiles 是否删除本地文件
+ * @returns 删除的任务数
+ */
+export async function deleteDownloadGroup(groupId: string, deleteFiles: boolean = false): Promise<number> {
+  return apiClient.delete(`/downloads/group/${groupId}`, { params: { delete_files: deleteFiles } })
+}
+
 /**
  * 清除已完成的任务
  */
@@ -122,8 +271,15 @@ export interface BatchDownloadItem {
   size?: number
   /// 原始名称（加密文件/文件夹的还原名称）
   original_name?: string
+  /// 文件 MD5（下载完成后校验）
+  md5?: string
 }
 
+/// 平铺下载时不同子目录下同名文件的处理方式
+/// - rename: 追加序号（a.txt -> a (1).txt）
+/// - prefix_path: 以子目录路径作为前缀（sub/a.txt -> sub_a.txt）
+export type FlattenCollisionPolicy = 'rename' | 'prefix_path'
+
 /// 批量下载请求
 export interface CreateBatchDownloadRequest {
   /// 下载项列表
@@ -132,6 +288,10 @@ export interface CreateBatchDownloadRequest {
   target_dir: string
   /// 冲突策略
   conflict_strategy?: DownloadConflictStrategy
+  /// 文件夹是否平铺下载（默认保留目录结构）
+  flatten?: boolean
+  /// 平铺下载时同名文件的处理方式（默认 rename）
+  flatten_collision?: FlattenCollisionPolicy
 }
 
 /// 批量下载错误项
@@ -144,6 +304,8 @@ export interface BatchDownloadError {
 
 /// 批量下载响应
 export interface BatchDownloadResponse {
+  /// 批次ID（本次创建的单文件任务共享）
+  batch_id: string
   /// 成功创建的单文件任务ID列表
   task_ids: string[]
   /// 成功创建的文件夹任务ID列表
@@ -276,16 +438,20 @@ export interface DownloadItem {
  * @param remotePath 远程路径
  * @param originalName 原始文件夹名（如果是加密文件夹，传入还原后的名称）
  * @param conflictStrategy 冲突策略
+ * @param flatten 平铺下载时同名文件的处理方式（不传则保留目录结构）
  */
 export async function createFolderDownload(
     remotePath: string,
     originalName?: string,
-    conflictStrategy?: DownloadConflictStrategy
+    conflictStrategy?: DownloadConflictStrategy,
+    flatten?: FlattenCollisionPolicy
 ): Promise<string> {
   return apiClient.post('/downloads/folder', {
     path: remotePath,
     original_name: originalName,
-    conflict_strategy: conflictStrategy
+    conflict_strategy: conflictStrategy,
+    flatten: flatten !== undefined,
+    flatten_collision: flatten
   })
 }
 
@@ -303,6 +469,29 @@ export async function getFolderDownload(folderId: string): Promise<FolderDownloa
   return apiClient.get(`/downloads/folder/${folderId}`)
 }
 
+/**
+ * 文件夹内单个文件的下载进度
+ */
+export interface FolderFileProgress {
+  task_id: string
+  file_name: string
+  /** 相对于文件夹根目录的路径 */
+  relative_path: string
+  status: TaskStatus
+  downloaded_size: number
+  total_size: number
+  /** 下载速度 (bytes/s) */
+  speed: number
+  error?: string
+}
+
+/**
+ * 获取文件夹内各文件的下载进度（按状态、路径排序；已完成并清理的文件不在列表中）
+ */
+export async function getFolderDownloadFiles(folderId: string): Promise<FolderFileProgress[]> {
+  return apiClient.get(`/downloads/folder/${folderId}/files`)
+}
+
 /**
  * 暂停文件夹下载
  */
@@ -602,6 +791,16 @@ export async function batchResumeDownloads(req: BatchOperationRequest): Promise<
   return apiClient.post('/downloads/batch/resume', req)
 }
 
+/** 一键暂停全部下载（含文件夹，暂停后不会自动启动） */
+export async function pauseAllDownloads(): Promise<BatchOperationResponse> {
+  return apiClient.post('/downloads/pause-all')
+}
+
+/** 一键恢复全部下载（按暂停前的顺序） */
+export async function resumeAllDownloads(): Promise<BatchOperationResponse> {
+  return apiClient.post('/downloads/resume-all')
+}
+
 /** 批量删除下载 */
 export async function batchDeleteDownloads(req: BatchOperationRequest): Promise<BatchOperationResponse> {
   return apiClient.post('/downloads/batch/delete', req)
@@ -61,22 +61,119 @@ export interface DownloadUrlData {
   url: string
 }
 
+/** 缩略图尺寸 */
+export type ThumbnailSize = 'icon' | 'small' | 'medium' | 'large'
+
+export interface ThumbnailData {
+  fs_id: number
+  size: ThumbnailSize
+  /** 缩略图URL（带签名，短时间内有效） */
+  url: string
+}
+
+export interface DirectUrlData {
+  /** CDN 直链（有效期较短） */
+  url: string
+  url_index: number
+  url_count: number
+  /** 使用直链时必须携带的请求头（Cookie / User-Agent），未指定 includeCredentials 时 BDUSS 已脱敏 */
+  headers: Record<string, string>
+  warning: string
+}
+
 export interface CreateFolderData {
   fs_id: number
   path: string
   isdir: number
 }
 
+export interface DeletePathResult {
+  path: string
+  success: boolean
+}
+
+export interface DeleteFilesData {
+  deleted_count: number
+  failed_paths: string[]
+  results: DeletePathResult[]
+}
+
+export interface FileOperationData {
+  success_count: number
+  failed_paths: string[]
+  task_id?: number
+}
+
+export interface RecycleItem {
+  fs_id: number
+  path: string
+  server_filename: string
+  size: number
+  isdir: number
+  category: number
+  md5?: string
+  delete_time: number
+  left_time: number
+}
+
+export interface RecycleListData {
+  list: RecycleItem[]
+  page: number
+  has_more: boolean
+}
+
+/// 文件列表排序字段
+export type FileListOrder = 'name' | 'time' | 'size'
+
+/// 文件类型筛选
+export type FileTypeFilter = 'folder' | 'file' | 'image' | 'video' | 'doc'
+
+/// 文件列表排序/筛选选项
+export interface FileListOptions {
+  order?: FileListOrder
+  desc?: boolean
+  type?: FileTypeFilter
+}
+
+function listOptionParams(options: FileListOptions) {
+  return {
+    order: options.order,
+    desc: options.desc ? 1 : undefined,
+    type: options.type,
+  }
+}
+
 /**
  * 获取文件列表
  */
 export async function getFileList(
     dir: string = '/',
     page: number = 1,
-    pageSize: number = 50
+    pageSize: number = 50,
+    options: FileListOptions = {}
+): Promise<FileListData> {
+  const response = await apiClient.get<ApiResponse<FileListData>>('/files', {
+    params: { dir, page, page_size: pageSize, ...listOptionParams(options) }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '获取文件列表失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 获取目录下全部文件（后端自动翻页，适用于大目录）
+ * @param concurrency 并发页数（默认 2，最大 4）
+ */
+export async function getAllFiles(
+    dir: string = '/',
+    concurrency?: number,
+    options: FileListOptions = {}
 ): Promise<FileListData> {
   const response = await apiClient.get<ApiResponse<FileListData>>('/files', {
-    params: { dir, page, page_size: pageSize }
+    params: { dir, all: 1, concurrency, ...listOptionParams(options) }
   })
 
   if (response.data.code !== 0 || !response.data.data) {
@@ -101,6 +198,84 @@ export async function getDownloadUrl(fsId: number): Promise<string> {
   return response.data.data.url
 }
 
+/**
+ * 获取图片/视频缩略图链接（不计入下载次数）
+ */
+export async function getThumbnailUrl(fsId: number, size: ThumbnailSize = 'medium'): Promise<string> {
+  const response = await apiClient.get<ApiResponse<ThumbnailData>>('/files/thumbnail', {
+    params: { fs_id: fsId, size }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '获取缩略图失败')
+  }
+
+  return response.data.data.url
+}
+
+/**
+ * 获取文件直链及所需请求头（不创建下载任务，供 aria2 等外部下载器使用）
+ *
+ * 请求头中的 BDUSS 默认脱敏，includeCredentials 为 true 时返回完整凭证
+ */
+export async function getDirectUrl(
+  path: string,
+  prefer = 0,
+  includeCredentials = false
+): Promise<DirectUrlData> {
+  const response = await apiClient.get<ApiResponse<DirectUrlData>>('/files/direct-url', {
+    params: { path, prefer, include_credentials: includeCredentials }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '获取直链失败')
+  }
+
+  return response.data.data
+}
+
+export interface LinkExportRequest {
+  paths?: string[]
+  fs_ids?: number[]
+  /** 首选链接索引（默认 0） */
+  prefer?: number
+  /** 导出完整的登录凭证（默认 false，BDUSS 会被脱敏） */
+  include_credentials?: boolean
+}
+
+export interface LinkExportResponse {
+  /** aria2 输入文件内容或 curl 命令 */
+  content: string
+  count: number
+  failed: { target: string; error: string }[]
+  /** 直链有效期提示 */
+  warning: string
+}
+
+async function exportLinks(format: 'aria2' | 'curl', req: LinkExportRequest): Promise<LinkExportResponse> {
+  const response = await apiClient.post<ApiResponse<LinkExportResponse>>(`/export/${format}`, req)
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '导出下载链接失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 导出 aria2 输入文件（aria2c -i 使用，链接有效期较短）
+ */
+export async function exportAria2(req: LinkExportRequest): Promise<LinkExportResponse> {
+  return exportLinks('aria2', req)
+}
+
+/**
+ * 导出 curl 下载命令（链接有效期较短）
+ */
+export async function exportCurl(req: LinkExportRequest): Promise<LinkExportResponse> {
+  return exportLinks('curl', req)
+}
+
 /**
  * 创建文件夹
  */
@@ -116,6 +291,129 @@ export async function createFolder(path: string): Promise<CreateFolderData> {
   return response.data.data
 }
 
+/**
+ * 删除网盘文件
+ */
+export async function deleteFiles(paths: string[]): Promise<DeleteFilesData> {
+  const response = await apiClient.delete<ApiResponse<DeleteFilesData>>('/files', {
+    data: { paths }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '删除文件失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 重命名网盘文件
+ */
+export async function renameFile(path: string, newName: string): Promise<FileOperationData> {
+  const response = await apiClient.post<ApiResponse<FileOperationData>>('/files/rename', {
+    path,
+    new_name: newName
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '重命名失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 移动网盘文件
+ */
+export async function moveFiles(sources: string[], destDir: string): Promise<FileOperationData> {
+  const response = await apiClient.post<ApiResponse<FileOperationData>>('/files/move', {
+    sources,
+    dest_dir: destDir
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '移动文件失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 复制网盘文件
+ */
+export async function copyFiles(sources: string[], destDir: string): Promise<FileOperationData> {
+  const response = await apiClient.post<ApiResponse<FileOperationData>>('/files/copy', {
+    sources,
+    dest_dir: destDir
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '复制文件失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 获取回收站列表
+ */
+export async function getRecycleList(page: number = 1, num: number = 100): Promise<RecycleListData> {
+  const response = await apiClient.get<ApiResponse<RecycleListData>>('/recycle', {
+    params: { page, num }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '获取回收站列表失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 从回收站还原文件
+ */
+export async function restoreRecycleFiles(fsIds: number[]): Promise<FileOperationData> {
+  const response = await apiClient.post<ApiResponse<FileOperationData>>('/recycle/restore', {
+    fs_ids: fsIds
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '还原文件失败')
+  }
+
+  return response.data.data
+}
+
+/**
+ * 清空回收站
+ */
+export async function cleanRecycleBin(): Promise<void> {
+  const response = await apiClient.delete<ApiResponse<null>>('/recycle')
+
+  if (response.data.code !== 0) {
+    throw new Error(response.data.message || '清空回收站失败')
+  }
+}
+
+/**
+ * 搜索网盘文件
+ */
+export async function searchFiles(
+    key: string,
+    dir: string = '/',
+    recursive: boolean = true
+): Promise<FileItem[]> {
+  const response = await apiClient.get<ApiResponse<{ list: FileItem[] }>>('/files/search', {
+    params: { key, dir, recursive: recursive ? 1 : 0 }
+  })
+
+  if (response.data.code !== 0 || !response.data.data) {
+    throw new Error(response.data.message || '搜索文件失败')
+  }
+
+  return response.data.data.list
+}
+
 // 重新导出共享工具函数，保持向后兼容
 export const formatFileSize = sharedFormatFileSize
 export const formatTime = formatTimestamp
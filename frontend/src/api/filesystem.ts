@@ -74,6 +74,13 @@ export interface FileEntry {
   updatedAt: string          // 后端返回 camelCase
   icon?: string
   path: string
+  diskSpace?: DiskSpace      // 仅驱动器/挂载点目录返回
+}
+
+/// 卷空间信息
+export interface DiskSpace {
+  totalBytes: number
+  freeBytes: number
 }
 
 /// 排序字段
@@ -100,6 +107,7 @@ export interface ListDirectoryResponse {
   page: number
   pageSize: number            // 后端返回 camelCase
   hasMore: boolean            // 后端返回 camelCase
+  diskSpace?: DiskSpace       // 当前目录所在卷的空间信息
 }
 
 /// 路径跳转请求
@@ -197,6 +205,30 @@ export async function getRoots(): Promise<RootsResponse> {
   return response.data.data
 }
 
+/// 驱动器/挂载点信息
+export interface DriveInfo {
+  path: string
+  name: string
+  fsType?: string
+  device?: string
+  totalBytes: number
+  freeBytes: number
+}
+
+/**
+ * 获取驱动器/挂载点列表及空间信息
+ */
+export async function getDrives(): Promise<DriveInfo[]> {
+  const response = await apiClient.get<ApiResponse<{ drives: DriveInfo[] }>>('/fs/drives')
+
+  if (response.data.code !== 0 || !response.data.data) {
+    const message = getFriendlyErrorMessage(response.data.code, response.data.message)
+    throw new Error(message)
+  }
+
+  return response.data.data.drives
+}
+
 /**
  * 格式化文件大小
  */
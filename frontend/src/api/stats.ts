@@ -0,0 +1,34 @@
+import { apiClient } from './client'
+
+/// 单个方向（上传或下载）的传输统计
+export interface TransferStats {
+  active_tasks: number
+  queued_tasks: number
+  speed: number // bytes/s
+  active_threads: number
+  max_threads: number
+}
+
+/// 下载时间段调度状态
+export interface ScheduleStatus {
+  enabled: boolean
+  in_window: boolean // 当前是否允许下载
+  windows: string[]
+  pause_running: boolean
+  next_window_start: string | null // 下一个时间段开始时间（HH:MM）
+}
+
+/// 全局传输统计
+export interface DashboardStats {
+  download: TransferStats
+  upload: TransferStats
+  thread_utilization: number // 0.0 ~ 1.0
+  download_schedule: ScheduleStatus | null // 下载管理器未初始化时为 null
+}
+
+/**
+ * 获取上传/下载综合统计（单次轮询即可获取仪表盘所需数据）
+ */
+export async function getDashboardStats(): Promise<DashboardStats> {
+  return apiClient.get('/stats')
+}
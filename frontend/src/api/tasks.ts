@@ -0,0 +1,37 @@
+import { apiClient } from './client'
+
+export type MigrationTaskType = 'download' | 'upload' | 'transfer'
+
+/// 任务导出包（元数据格式与后端 .meta 文件一致，前端只做透传）
+export interface TaskExportBundle {
+  version: number
+  exported_at: string
+  tasks: Array<Record<string, unknown> & { task_id: string; task_type: MigrationTaskType }>
+}
+
+/// 跳过的导入任务
+export interface SkippedImportTask {
+  task_id: string
+  task_type: MigrationTaskType
+  reason: string
+}
+
+/// 任务导入结果
+export interface TaskImportResult {
+  imported: number
+  skipped: SkippedImportTask[]
+}
+
+/**
+ * 导出未完成的下载/上传/转存任务（不包含文件数据）
+ */
+export async function exportTasks(): Promise<TaskExportBundle> {
+  return apiClient.get('/tasks/export')
+}
+
+/**
+ * 导入任务列表，任务以暂停状态重建
+ */
+export async function importTasks(bundle: TaskExportBundle): Promise<TaskImportResult> {
+  return apiClient.post('/tasks/import', bundle)
+}
@@ -1,4 +1,5 @@
 import { apiClientWithErrorCode } from './client'
+import type { HistoryPage, HistoryQueryParams } from './download'
 import { formatTimestampShort } from './utils'
 
 const apiClient = apiClientWithErrorCode
@@ -25,6 +26,10 @@ export const TransferErrorCodes = {
   TRANSFER_FAILED: 1008,
   /** 下载失败 */
   DOWNLOAD_FAILED: 1009,
+  /** 需要输入验证码（错误响应的 data.captcha 携带验证码） */
+  CAPTCHA_REQUIRED: 1010,
+  /** 验证码错误（错误响应的 data.captcha 携带新的验证码） */
+  INVALID_CAPTCHA: 1011,
 } as const
 
 // ============================================
@@ -58,6 +63,8 @@ export interface SharedFileInfo {
   path: string
   size: number
   name: string
+  /** 分享直链（仅免转存直接下载时由后端获取） */
+  dlink?: string
 }
 
 /// 转存任务
@@ -87,6 +94,10 @@ export interface TransferTask {
   is_share_direct_download?: boolean
   /** 分享直下：临时目录路径（网盘路径） */
   temp_dir?: string
+  /** 下载成功并校验 MD5 后删除网盘副本 */
+  download_cleanup?: boolean
+  /** 在转存等待队列中的位置（从 1 开始，仅排队中的任务有值） */
+  queue_position?: number
 }
 
 /// 创建转存任务请求
@@ -99,10 +110,16 @@ export interface CreateTransferRequest {
   local_download_path?: string
   /** 分享直下：是否为分享直下任务 */
   is_share_direct_download?: boolean
+  /** 转存到临时目录，下载成功并校验 MD5 后删除网盘副本（不传时按默认行为） */
+  download_cleanup?: boolean
   /** 选中的文件 fs_id 列表（可选，为空或未提供时转存所有文件） */
   selected_fs_ids?: number[]
   /** 选中的文件完整信息列表（可选，用于后端获取选中文件的元信息） */
   selected_files?: SharedFileInfo[]
+  /** 自动下载时按分享创建本地子文件夹（title=按分享标题，short_key=按短链 ID，不传则不创建） */
+  share_subfolder?: 'title' | 'short_key'
+  /** 递归展开选中的文件夹后按文件分批转存（保留目录结构，适用于超过单次转存文件数上限的大文件夹） */
+  recursive?: boolean
 }
 
 /// 预览分享文件请求
@@ -113,6 +130,10 @@ export interface PreviewShareRequest {
   page?: number
   /** 每页数量（默认 100） */
   num?: number
+  /** 用户识别出的验证码（上次返回 CAPTCHA_REQUIRED 时提交） */
+  vcode?: string
+  /** 验证码令牌（上次返回的 captcha.vcode_str） */
+  vcode_str?: string
 }
 
 /// 分享信息（用于目录导航）
@@ -127,6 +148,8 @@ export interface PreviewShareInfo {
 export interface PreviewShareResponse {
   files: SharedFileInfo[]
   share_info?: PreviewShareInfo
+  /** 需要输入验证码时返回 */
+  captcha?: ShareCaptcha
 }
 
 /// 浏览分享子目录请求
@@ -147,6 +170,56 @@ export interface CreateTransferResponse {
   task_id?: string
   status?: TransferStatus
   need_password: boolean
+  /** 需要输入验证码时返回 */
+  captcha?: ShareCaptcha
+}
+
+/// 分享提取码验证的图片验证码
+export interface ShareCaptcha {
+  img_url: string
+  vcode_str: string
+}
+
+/// 提交分享验证码请求（连同原始创建转存请求一起提交）
+export interface SubmitCaptchaRequest extends CreateTransferRequest {
+  vcode: string
+  vcode_str: string
+}
+
+/// 分享链接检查结果（不验证提取码、不转存）
+export interface ShareInspectResult {
+  /** 链接是否有效（需要提取码的有效链接同样为 true） */
+  valid: boolean
+  needs_password: boolean
+  /** 根目录文件数（需要提取码时不返回） */
+  file_count?: number
+  title?: string
+  /** 无效原因（如 "分享已失效"） */
+  reason?: string
+}
+
+/// 免转存直接下载请求
+export interface DirectDownloadRequest {
+  share_url: string
+  password?: string
+  /** 选中的文件（为空时下载分享根目录下的全部文件） */
+  selected_files?: SharedFileInfo[]
+  /** 本地下载路径（默认使用下载配置中的目录） */
+  local_download_path?: string
+  /** 用户识别出的验证码（上次返回 CAPTCHA_REQUIRED 时提交） */
+  vcode?: string
+  /** 验证码令牌（上次返回的 captcha.vcode_str） */
+  vcode_str?: string
+}
+
+/// 免转存直接下载响应
+export interface DirectDownloadResponse {
+  /** 创建的下载任务 ID 列表 */
+  task_ids: string[]
+  /** 未获取到直链或创建任务失败的文件路径 */
+  failed_paths: string[]
+  /** 需要输入验证码时返回 */
+  captcha?: ShareCaptcha
 }
 
 /// 转存任务列表响应
@@ -182,6 +255,21 @@ export async function createTransfer(req: CreateTransferRequest): Promise<Create
   return apiClient.post('/transfers', req)
 }
 
+/**
+ * 提交分享验证码并重新创建转存任务
+ * 验证码错误时以 INVALID_CAPTCHA 拒绝，data.captcha 为新的验证码
+ */
+export async function submitShareCaptcha(req: SubmitCaptchaRequest): Promise<CreateTransferResponse> {
+  return apiClient.post('/transfers/captcha', req)
+}
+
+/**
+ * 检查分享链接是否有效、是否需要提取码（不验证提取码、不转存）
+ */
+export async function inspectShare(shareUrl: string): Promise<ShareInspectResult> {
+  return apiClient.post('/transfers/inspect', { share_url: shareUrl }, { timeout: 15000 })
+}
+
 /**
  * 预览分享文件列表（不执行转存）
  * 超时设置为 15s，超时后前端显示提示并允许重试
@@ -199,6 +287,15 @@ export async function previewShareDir(req: PreviewShareDirRequest): Promise<Prev
   return apiClient.post('/transfers/preview/dir', req, { timeout: 10000 })
 }
 
+/**
+ * 免转存直接下载分享文件（不转存到自己的网盘）
+ * 文件夹会递归展开，需要遍历目录获取直链，超时设置为 60s
+ * @throws TransferApiError 特殊错误（需要密码、密码错误等）
+ */
+export async function directDownloadShare(req: DirectDownloadRequest): Promise<DirectDownloadResponse> {
+  return apiClient.post('/transfers/direct-download', req, { timeout: 60000 })
+}
+
 /**
  * 获取所有转存任务
  */
@@ -206,6 +303,20 @@ export async function getAllTransfers(): Promise<TransferListResponse> {
   return apiClient.get('/transfers')
 }
 
+/**
+ * 分页查询转存历史（q 匹配分享链接或转存目标路径）
+ */
+export async function getTransferHistory(params: HistoryQueryParams = {}): Promise<HistoryPage<TransferTask>> {
+  return apiClient.get('/transfers/history', { params })
+}
+
+/**
+ * 清除已完成的转存任务
+ */
+export async function clearCompletedTransfers(): Promise<number> {
+  return apiClient.delete('/transfers/clear/completed')
+}
+
 /**
  * 获取单个转存任务
  */
@@ -1,4 +1,5 @@
 import { apiClient } from './client'
+import type { HistoryPage, HistoryQueryParams } from './download'
 import { formatFileSize as sharedFormatFileSize, formatSpeed as sharedFormatSpeed, formatETA as sharedFormatETA, extractFilename as sharedExtractFilename } from './utils'
 
 /// 上传冲突策略（映射百度网盘 API rtype 参数）
@@ -16,6 +17,7 @@ export interface UploadTask {
   uploaded_size: number
   status: UploadTaskStatus
   speed: number
+  eta_secs?: number | null // 预计剩余时间（秒）
   created_at: number
   started_at?: number
   completed_at?: number
@@ -51,6 +53,7 @@ export interface CreateFolderUploadRequest {
   local_folder: string
   remote_folder: string
   scan_options?: FolderScanOptions
+  skip_hidden?: boolean // 未指定则使用上传配置中的 skip_hidden_files
   encrypt?: boolean
   conflict_strategy?: UploadConflictStrategy
 }
@@ -83,6 +86,51 @@ export async function createBatchUpload(req: CreateBatchUploadRequest): Promise<
   return apiClient.post('/uploads/batch', req)
 }
 
+/// 文件夹上传聚合进度
+export interface UploadGroupProgress {
+  total_files: number
+  completed_files: number
+  failed_files: number
+  active_files: number
+  total_size: number
+  uploaded_size: number
+  speed: number
+}
+
+/// 文件夹扫描状态
+export interface ScanStatus {
+  scan_task_id: string
+  status: 'scanning' | 'completed' | 'failed' | 'cancelled'
+  scanned_files: number
+  created_tasks: number
+  skipped_duplicates: number
+  total_size: number
+  upload_progress?: UploadGroupProgress
+}
+
+/**
+ * 查询文件夹扫描状态（含已创建任务的聚合上传进度）
+ */
+export async function getScanStatus(scanTaskId: string): Promise<ScanStatus> {
+  return apiClient.get(`/uploads/scan/${scanTaskId}`)
+}
+
+/// 秒传预检结果
+export interface RapidUploadProbeResult {
+  local_path: string
+  file_size: number
+  content_md5: string
+  slice_md5: string
+  rapid_upload: boolean
+}
+
+/**
+ * 秒传预检（不创建上传任务）
+ */
+export async function checkRapidUpload(localPath: string): Promise<RapidUploadProbeResult> {
+  return apiClient.post('/uploads/check-rapid', { local_path: localPath })
+}
+
 /**
  * 获取所有上传任务
  */
@@ -90,6 +138,13 @@ export async function getAllUploads(): Promise<UploadTask[]> {
   return apiClient.get('/uploads')
 }
 
+/**
+ * 分页查询上传历史
+ */
+export async function getUploadHistory(params: HistoryQueryParams = {}): Promise<HistoryPage<UploadTask>> {
+  return apiClient.get('/uploads/history', { params })
+}
+
 /**
  * 获取指定上传任务
  */
@@ -15,6 +15,7 @@ export interface DownloadEventCreated {
   group_id?: string
   is_backup?: boolean
   original_filename?: string
+  batch_id?: string
 }
 
 export interface DownloadEventProgress {
@@ -24,6 +25,8 @@ export interface DownloadEventProgress {
   total_size: number
   speed: number
   progress: number
+  eta_secs: number | null // 预计剩余时间（秒）
+  cdn_host?: string // 实际提供下载的 CDN 主机
 }
 
 export interface DownloadEventStatusChanged {
@@ -81,6 +84,23 @@ export interface DownloadEventDecryptCompleted {
   is_backup?: boolean
 }
 
+export interface DownloadEventIntegrityFailed {
+  event_type: 'integrity_failed'
+  task_id: string
+  expected: string
+  actual: string
+  group_id?: string
+  is_backup?: boolean
+}
+
+export interface DownloadEventRetrying {
+  event_type: 'retrying'
+  task_id: string
+  attempt: number
+  max_attempts: number
+  error: string
+}
+
 export type DownloadEvent =
     | DownloadEventCreated
     | DownloadEventProgress
@@ -92,6 +112,8 @@ export type DownloadEvent =
     | DownloadEventDeleted
     | DownloadEventDecryptProgress
     | DownloadEventDecryptCompleted
+    | DownloadEventIntegrityFailed
+    | DownloadEventRetrying
 
 // ============ 文件夹事件 ============
 
@@ -183,6 +205,7 @@ export interface UploadEventProgress {
   total_size: number
   speed: number
   progress: number
+  eta_secs: number | null // 预计剩余时间（秒）
   completed_chunks: number
   total_chunks: number
 }
@@ -239,6 +262,13 @@ export interface UploadEventEncryptCompleted {
   is_backup?: boolean
 }
 
+export interface UploadEventProgressReset {
+  event_type: 'progress_reset'
+  task_id: string
+  reason: string
+  is_backup?: boolean
+}
+
 export type UploadEvent =
     | UploadEventCreated
     | UploadEventProgress
@@ -250,6 +280,7 @@ export type UploadEvent =
     | UploadEventDeleted
     | UploadEventEncryptProgress
     | UploadEventEncryptCompleted
+    | UploadEventProgressReset
 
 // ============ 备份事件 ============
 // 注意：event_type 与后端 BackupEvent 的 serde rename_all = "snake_case" 保持一致
@@ -574,7 +605,18 @@ export interface WsClientUnsubscribe {
   subscriptions: string[]
 }
 
-export type WsClientMessage = WsClientPing | WsClientRequestSnapshot | WsClientSubscribe | WsClientUnsubscribe
+/** 断线重连后请求回放 event_id 大于 since_seq 的事件 */
+export interface WsClientResume {
+  type: 'resume'
+  since_seq: number
+}
+
+export type WsClientMessage =
+    | WsClientPing
+    | WsClientRequestSnapshot
+    | WsClientSubscribe
+    | WsClientUnsubscribe
+    | WsClientResume
 
 export interface WsServerPong {
   type: 'pong'
@@ -625,6 +667,27 @@ export interface WsServerUnsubscribeSuccess {
   subscriptions: string[]
 }
 
+export interface WsServerSessionExpired {
+  type: 'session_expired'
+  uid: number
+  message: string
+  timestamp: number
+}
+
+export interface WsServerRateLimited {
+  type: 'rate_limited'
+  cooldown_secs: number
+}
+
+/** 全局传输统计心跳（订阅 stats 后每秒推送） */
+export interface WsServerStats {
+  type: 'stats'
+  down_speed: number
+  up_speed: number
+  active: number
+  queued: number
+}
+
 export type WsServerMessage =
     | WsServerPong
     | WsServerEvent
@@ -634,3 +697,6 @@ export type WsServerMessage =
     | WsServerError
     | WsServerSubscribeSuccess
     | WsServerUnsubscribeSuccess
+    | WsServerSessionExpired
+    | WsServerRateLimited
+    | WsServerStats
@@ -13,6 +13,10 @@ import type {
   BackupEvent,
   CloudDlEvent,
   TimestampedEvent,
+  WsServerSessionExpired,
+  WsServerRateLimited,
+  WsServerStats,
+  WsServerSnapshot,
 } from '@/types/events'
 
 // 连接状态
@@ -26,6 +30,10 @@ type TransferEventCallback = (event: TransferEvent) => void
 type BackupEventCallback = (event: BackupEvent) => void
 type CloudDlEventCallback = (event: CloudDlEvent) => void
 type ConnectionStateCallback = (state: ConnectionState) => void
+type SessionExpiredCallback = (message: WsServerSessionExpired) => void
+type RateLimitedCallback = (message: WsServerRateLimited) => void
+type StatsCallback = (message: WsServerStats) => void
+type SnapshotCallback = (message: WsServerSnapshot) => void
 
 // 重连配置
 const RECONNECT_DELAYS = [1000, 2000, 4000, 8000, 16000, 30000] // 指数退避
@@ -50,6 +58,10 @@ class WebSocketClient {
   private backupListeners: Set<BackupEventCallback> = new Set()
   private cloudDlListeners: Set<CloudDlEventCallback> = new Set()
   private connectionStateListeners: Set<ConnectionStateCallback> = new Set()
+  private sessionExpiredListeners: Set<SessionExpiredCallback> = new Set()
+  private rateLimitedListeners: Set<RateLimitedCallback> = new Set()
+  private statsListeners: Set<StatsCallback> = new Set()
+  private snapshotListeners: Set<SnapshotCallback> = new Set()
 
   // 连接 ID
   private connectionId: string | null = null
@@ -57,6 +69,9 @@ class WebSocketClient {
   // 🔥 当前订阅集合
   private currentSubscriptions: Set<string> = new Set()
 
+  // 🔥 最后收到的事件 ID（重连后据此请求回放断线期间的事件）
+  private lastEventId = 0
+
   private constructor() {
     // 私有构造函数，强制使用单例
   }
@@ -152,6 +167,12 @@ class WebSocketClient {
           type: 'subscribe',
           subscriptions,
         })
+
+        // 🔥 重连：请求回放断线期间错过的事件
+        if (this.lastEventId > 0) {
+          console.log('[WS] 请求回放事件, since_seq =', this.lastEventId)
+          this.send({ type: 'resume', since_seq: this.lastEventId })
+        }
       }
     }
 
@@ -202,7 +223,10 @@ class WebSocketClient {
 
         case 'snapshot':
           console.log('[WS] 收到状态快照')
-          // TODO: 处理状态快照
+          // 快照之后的事件 ID 重新计数（服务端可能已重启）
+          this.lastEventId = 0
+          // 🔥 断线期间的事件已无法回放，由各页面按快照重新同步任务列表
+          this.snapshotListeners.forEach((cb) => cb(message))
           break
 
         case 'error':
@@ -217,6 +241,20 @@ class WebSocketClient {
           console.log('[WS] 取消订阅成功:', (message as any).subscriptions)
           break
 
+        case 'session_expired':
+          console.warn('[WS] 登录态失效:', message.uid, message.message)
+          this.sessionExpiredListeners.forEach((cb) => cb(message))
+          break
+
+        case 'rate_limited':
+          console.warn('[WS] 触发限流，进入全局冷却:', message.cooldown_secs, '秒')
+          this.rateLimitedListeners.forEach((cb) => cb(message))
+          break
+
+        case 'stats':
+          this.statsListeners.forEach((cb) => cb(message))
+          break
+
         default:
           console.warn('[WS] 未知消息类型:', message)
       }
@@ -230,6 +268,7 @@ class WebSocketClient {
    */
   private dispatchEvent(event: TimestampedEvent): void {
     const { category } = event
+    this.lastEventId = Math.max(this.lastEventId, event.event_id)
 
     // 🔥 记录接收到的事件
     console.log(
@@ -381,6 +420,38 @@ class WebSocketClient {
     return () => this.cloudDlListeners.delete(callback)
   }
 
+  /**
+   * 订阅登录态失效通知
+   */
+  public onSessionExpired(callback: SessionExpiredCallback): () => void {
+    this.sessionExpiredListeners.add(callback)
+    return () => this.sessionExpiredListeners.delete(callback)
+  }
+
+  /**
+   * 订阅限流冷却通知
+   */
+  public onRateLimited(callback: RateLimitedCallback): () => void {
+    this.rateLimitedListeners.add(callback)
+    return () => this.rateLimitedListeners.delete(callback)
+  }
+
+  /**
+   * 订阅全局传输统计心跳（需同时 subscribe(['stats']) 才会推送）
+   */
+  public onStats(callback: StatsCallback): () => void {
+    this.statsListeners.add(callback)
+    return () => this.statsListeners.delete(callback)
+  }
+
+  /**
+   * 订阅状态快照（重连后事件超出回放范围或主动请求快照时推送）
+   */
+  public onSnapshot(callback: SnapshotCallback): () => void {
+    this.snapshotListeners.add(callback)
+    return () => this.snapshotListeners.delete(callback)
+  }
+
   /**
    * 订阅连接状态变化
    */